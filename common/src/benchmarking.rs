@@ -135,16 +135,20 @@ pub fn generate_wasm3(payload: Vec<u8>) -> Result<Vec<u8>, &'static str> {
 pub fn set_program(program_id: H256, code: Vec<u8>, static_pages: WasmPageNumber) {
     let code_id = CodeId::generate(&code).into_origin();
     let allocations = (0..static_pages.0).map(WasmPageNumber);
+    // Zero pages wouldn't be stored.
+    let mut page_buf = PageBuf::new_zeroed();
+    page_buf[0] = 1;
     let persistent_pages_data: BTreeMap<PageNumber, PageBuf> = allocations
         .clone()
         .flat_map(|p| p.to_gear_pages_iter())
-        .map(|p| (p, PageBuf::new_zeroed()))
+        .map(|p| (p, page_buf.clone()))
         .collect();
     super::set_program_and_pages_data(
         program_id,
         ActiveProgram {
             allocations: allocations.collect(),
             pages_with_data: persistent_pages_data.keys().copied().collect(),
+            gas_reservation_map: Default::default(),
             code_hash: code_id,
             state: ProgramState::Initialized,
         },
@@ -38,4 +38,7 @@ pub trait CodeStorage {
     fn get_code(code_id: CodeId) -> Option<InstrumentedCode>;
     fn get_original_code(code_id: CodeId) -> Option<Vec<u8>>;
     fn get_metadata(code_id: CodeId) -> Option<CodeMetadata>;
+    /// Stores type metadata describing the state of programs built from the code.
+    fn set_type_metadata(code_id: CodeId, metadata: CodeTypeMetadata);
+    fn get_type_metadata(code_id: CodeId) -> Option<CodeTypeMetadata>;
 }
@@ -94,6 +94,10 @@ pub enum Reason<R: RuntimeReason, S: SystemReason> {
 pub enum MessageWaitedRuntimeReason {
     /// Program called `gr_wait` while executing message.
     WaitCalled,
+    /// Program called `gr_wait_for` while executing message.
+    WaitForCalled,
+    /// Program called `gr_wait_up_to` while executing message.
+    WaitUpToCalled,
 }
 
 /// System reason for messages waiting.
@@ -148,6 +152,9 @@ pub enum CodeChangeKind<BlockNumber> {
 
     /// Code was reinstrumented.
     Reinstrumented,
+
+    /// Type metadata describing program state was uploaded for the code.
+    TypeMetadataChanged,
 }
 
 /// Runtime reason for messages reading from `Mailbox`.
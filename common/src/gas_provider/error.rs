@@ -48,4 +48,7 @@ pub trait Error {
 
     /// Forbidden operation for the value node.
     fn forbidden() -> Self;
+
+    /// Node can't be consumed while it has locked value.
+    fn consumed_with_lock() -> Self;
 }
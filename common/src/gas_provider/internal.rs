@@ -18,6 +18,16 @@
 
 use super::*;
 
+/// Kind of the node, created from another one with value.
+pub(super) enum ValueNodeKind {
+    /// Child node with specified value.
+    Specified,
+    /// Detached node, which can't be split.
+    ReservedLocal,
+    /// Detached node, which can be split.
+    Reserved,
+}
+
 pub struct TreeImpl<TotalValue, InternalError, Error, ExternalId, StorageMap>(
     PhantomData<(TotalValue, InternalError, Error, ExternalId, StorageMap)>,
 );
@@ -102,6 +112,9 @@ where
             GasNodeType::External { .. } => {
                 unreachable!("node is guaranteed to have a parent, so can't be an external one")
             }
+            GasNodeType::Reserved { .. } => {
+                unreachable!("node is guaranteed to have a parent, so can't be a reserved one")
+            }
         }
 
         // Update parent node
@@ -162,7 +175,7 @@ where
             StorageMap::remove(node_id);
 
             match node.inner {
-                GasNodeType::External { id, value } => {
+                GasNodeType::External { id, value } | GasNodeType::Reserved { id, value } => {
                     return Ok(Some((NegativeImbalance::new(value), id)))
                 }
                 GasNodeType::SpecifiedLocal { parent, .. }
@@ -183,13 +196,12 @@ where
 
     /// Create ValueNode from node key with value
     ///
-    /// if `reserve`, create ValueType::ReservedLocal
-    /// else, create ValueType::SpecifiedLocal
+    /// Type of the created node is detected from `kind`.
     pub(super) fn create_from_with_value(
         key: MapKey,
         new_node_key: MapKey,
         amount: Balance,
-        reserve: bool,
+        kind: ValueNodeKind,
     ) -> Result<(), Error> {
         let mut parent = Self::get_node(key).ok_or_else(InternalError::node_not_found)?;
 
@@ -207,16 +219,23 @@ where
             return Err(InternalError::node_already_exists().into());
         }
 
-        // Detect inner from `reserve`.
-        let inner = if reserve {
-            let id = Self::get_external(key)?.ok_or_else(InternalError::parent_is_lost)?;
-            GasNodeType::ReservedLocal { id, value: amount }
-        } else {
-            parent.spec_refs = parent.spec_refs.saturating_add(1);
+        // Detect inner from `kind`.
+        let inner = match kind {
+            ValueNodeKind::Specified => {
+                parent.spec_refs = parent.spec_refs.saturating_add(1);
 
-            GasNodeType::SpecifiedLocal {
-                value: amount,
-                parent: key,
+                GasNodeType::SpecifiedLocal {
+                    value: amount,
+                    parent: key,
+                }
+            }
+            ValueNodeKind::ReservedLocal => {
+                let id = Self::get_external(key)?.ok_or_else(InternalError::parent_is_lost)?;
+                GasNodeType::ReservedLocal { id, value: amount }
+            }
+            ValueNodeKind::Reserved => {
+                let id = Self::get_external(key)?.ok_or_else(InternalError::parent_is_lost)?;
+                GasNodeType::Reserved { id, value: amount }
             }
         };
 
@@ -238,6 +257,7 @@ where
             spec_refs: 0,
             unspec_refs: 0,
             consumed: false,
+            lock: Zero::zero(),
         };
 
         // Save new node
@@ -310,9 +330,9 @@ where
             // key known, must return the origin, unless corrupted
             let (root, maybe_key) = Self::root(node)?;
             match root.inner {
-                GasNodeType::External { id, .. } | GasNodeType::ReservedLocal { id, .. } => {
-                    Some((maybe_key.unwrap_or(key), id))
-                }
+                GasNodeType::External { id, .. }
+                | GasNodeType::ReservedLocal { id, .. }
+                | GasNodeType::Reserved { id, .. } => Some((maybe_key.unwrap_or(key), id)),
                 _ => unreachable!("Guaranteed by ValueNode::root method"),
             }
         } else {
@@ -344,6 +364,10 @@ where
             return Err(InternalError::node_was_consumed().into());
         }
 
+        if !node.lock.is_zero() {
+            return Err(InternalError::consumed_with_lock().into());
+        }
+
         node.consumed = true;
         Self::move_value_upstream(&mut node)?;
 
@@ -353,9 +377,9 @@ where
             match node.inner {
                 GasNodeType::UnspecifiedLocal { parent }
                 | GasNodeType::SpecifiedLocal { parent, .. } => Self::check_consumed(parent)?,
-                GasNodeType::ReservedLocal { id, value } | GasNodeType::External { id, value } => {
-                    Some((NegativeImbalance::new(value), id))
-                }
+                GasNodeType::ReservedLocal { id, value }
+                | GasNodeType::External { id, value }
+                | GasNodeType::Reserved { id, value } => Some((NegativeImbalance::new(value), id)),
             }
         } else {
             // Save current node
@@ -387,12 +411,29 @@ where
         Ok(NegativeImbalance::new(amount))
     }
 
+    fn refill(
+        key: Self::Key,
+        amount: Self::Balance,
+    ) -> Result<Self::PositiveImbalance, Self::Error> {
+        let (mut node, node_id) =
+            Self::node_with_value(Self::get_node(key).ok_or_else(InternalError::node_not_found)?)?;
+
+        // NOTE: intentional expect. A node_with_value is guaranteed to have inner_value
+        *node.inner_value_mut().expect("Querying node with value") += amount;
+        log::debug!("Refilled {:?} of gas", amount);
+
+        // Save node that delivers limit
+        StorageMap::insert(node_id.unwrap_or(key), node);
+
+        Ok(PositiveImbalance::new(amount))
+    }
+
     fn split_with_value(
         key: Self::Key,
         new_key: Self::Key,
         amount: Self::Balance,
     ) -> Result<(), Self::Error> {
-        Self::create_from_with_value(key, new_key, amount, false)
+        Self::create_from_with_value(key, new_key, amount, ValueNodeKind::Specified)
     }
 
     fn split(key: Self::Key, new_key: Self::Key) -> Result<(), Self::Error> {
@@ -418,6 +459,7 @@ where
             spec_refs: 0,
             unspec_refs: 0,
             consumed: false,
+            lock: Zero::zero(),
         };
 
         // Save new node
@@ -429,6 +471,119 @@ where
     }
 
     fn cut(key: Self::Key, new_key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error> {
-        Self::create_from_with_value(key, new_key, amount, true)
+        Self::create_from_with_value(key, new_key, amount, ValueNodeKind::ReservedLocal)
+    }
+
+    fn reserve(
+        key: Self::Key,
+        new_key: Self::Key,
+        amount: Self::Balance,
+    ) -> Result<(), Self::Error> {
+        Self::create_from_with_value(key, new_key, amount, ValueNodeKind::Reserved)
+    }
+
+    fn lock(key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error> {
+        let mut node = Self::get_node(key).ok_or_else(InternalError::node_not_found)?;
+
+        if node.consumed {
+            return Err(InternalError::node_was_consumed().into());
+        }
+
+        // Upstream node with a concrete value exist for any node.
+        // If it doesn't, the tree is considered invalidated.
+        let (mut ancestor_with_value, ancestor_id) = Self::node_with_value(node.clone())?;
+
+        // NOTE: intentional expect. A node_with_value is guaranteed to have inner_value
+        if ancestor_with_value
+            .inner_value()
+            .expect("Querying node with value")
+            < amount
+        {
+            return Err(InternalError::insufficient_balance().into());
+        }
+
+        node.lock = node.lock.saturating_add(amount);
+
+        if let Some(ancestor_id) = ancestor_id {
+            *ancestor_with_value
+                .inner_value_mut()
+                .expect("Querying node with value") -= amount;
+            StorageMap::insert(ancestor_id, ancestor_with_value);
+        } else {
+            // node and ancestor are the same
+            *node.inner_value_mut().expect("Querying node with value") -= amount;
+        }
+
+        // Update current node
+        StorageMap::insert(key, node);
+
+        Ok(())
+    }
+
+    fn unlock(key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error> {
+        let mut node = Self::get_node(key).ok_or_else(InternalError::node_not_found)?;
+
+        if node.lock < amount {
+            return Err(InternalError::insufficient_balance().into());
+        }
+
+        // Upstream node with a concrete value exist for any node.
+        // If it doesn't, the tree is considered invalidated.
+        let (mut ancestor_with_value, ancestor_id) = Self::node_with_value(node.clone())?;
+
+        node.lock -= amount;
+
+        if let Some(ancestor_id) = ancestor_id {
+            let val = ancestor_with_value
+                .inner_value_mut()
+                .expect("Querying node with value");
+            *val = val.saturating_add(amount);
+            StorageMap::insert(ancestor_id, ancestor_with_value);
+        } else {
+            // node and ancestor are the same
+            let val = node.inner_value_mut().expect("Querying node with value");
+            *val = val.saturating_add(amount);
+        }
+
+        // Update current node
+        StorageMap::insert(key, node);
+
+        Ok(())
+    }
+
+    fn get_lock(key: Self::Key) -> Result<Self::Balance, Self::Error> {
+        let node = Self::get_node(key).ok_or_else(InternalError::node_not_found)?;
+
+        Ok(node.lock)
+    }
+
+    fn values_by_external() -> Result<Vec<(Self::ExternalOrigin, Self::Balance)>, Self::Error> {
+        let mut nodes = Vec::new();
+        StorageMap::mutate_values(|node| {
+            nodes.push(node.clone());
+            node
+        });
+
+        let mut values = Vec::new();
+        for node in nodes {
+            let value = node
+                .inner_value()
+                .unwrap_or_else(Zero::zero)
+                .saturating_add(node.lock);
+
+            if value.is_zero() {
+                continue;
+            }
+
+            let (root, _) = Self::root(node)?;
+            match root.inner {
+                GasNodeType::External { id, .. }
+                | GasNodeType::ReservedLocal { id, .. }
+                | GasNodeType::Reserved { id, .. } => values.push((id, value)),
+                _ => unreachable!("Guaranteed by ValueNode::root method"),
+            }
+        }
+
+        Ok(values)
     }
 }
@@ -130,6 +130,15 @@ pub trait Tree {
     fn spend(key: Self::Key, amount: Self::Balance)
         -> Result<Self::NegativeImbalance, Self::Error>;
 
+    /// Refills underlying value.
+    ///
+    /// This adds the specified amount of value to the node delivering limit for the `key`,
+    /// thereby increasing the overall supply of it, hence positive imbalance.
+    fn refill(
+        key: Self::Key,
+        amount: Self::Balance,
+    ) -> Result<Self::PositiveImbalance, Self::Error>;
+
     /// Split underlying value.
     ///
     /// If `key` does not identify any value or the `amount` exceeds what's locked under that key,
@@ -154,6 +163,52 @@ pub trait Tree {
     ///
     /// This can't create imbalance as no value is burned or created.
     fn cut(key: Self::Key, new_key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error>;
+
+    /// Reserve underlying value to a detached node, which can be split.
+    ///
+    /// If `key` does not identify any value or the `amount` exceeds what's locked under that key,
+    /// an error is returned.
+    ///
+    /// This can't create imbalance as no value is burned or created.
+    fn reserve(
+        key: Self::Key,
+        new_key: Self::Key,
+        amount: Self::Balance,
+    ) -> Result<(), Self::Error>;
+
+    /// Lock some value of the node.
+    ///
+    /// Takes the `amount` from the node delivering limit for the `key`
+    /// and holds it on the node itself, so it can't be spent or split
+    /// until unlocked. Locked node can't be consumed.
+    ///
+    /// If `key` does not identify any value or the `amount` exceeds the limit
+    /// of the node, an error is returned.
+    ///
+    /// This can't create imbalance as no value is burned or created.
+    fn lock(key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error>;
+
+    /// Unlock some value of the node.
+    ///
+    /// Returns the `amount` from the lock of the node back to the node
+    /// delivering limit for the `key`.
+    ///
+    /// If `key` does not identify any value or the `amount` exceeds what's locked
+    /// under that key, an error is returned.
+    ///
+    /// This can't create imbalance as no value is burned or created.
+    fn unlock(key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error>;
+
+    /// Get locked value of the node.
+    ///
+    /// If `key` does not identify any value an error is returned.
+    fn get_lock(key: Self::Key) -> Result<Self::Balance, Self::Error>;
+
+    /// Get value held by each node, including locked one,
+    /// along with the external origin of the node's tree.
+    ///
+    /// Walks through all nodes, so should be used in migrations only.
+    fn values_by_external() -> Result<Vec<(Self::ExternalOrigin, Self::Balance)>, Self::Error>;
 }
 
 pub type GasBalanceKey<Balance, Key> = Option<(Balance, Key)>;
@@ -25,6 +25,7 @@ pub enum GasNodeType<ExternalId, Id, Balance> {
     ReservedLocal { id: ExternalId, value: Balance },
     SpecifiedLocal { parent: Id, value: Balance },
     UnspecifiedLocal { parent: Id },
+    Reserved { id: ExternalId, value: Balance },
 }
 
 #[derive(Clone, Decode, Debug, Encode, MaxEncodedLen, TypeInfo)]
@@ -33,6 +34,7 @@ pub struct GasNode<ExternalId: Clone, Id: Clone, Balance: Zero + Clone> {
     pub unspec_refs: u32,
     pub inner: GasNodeType<ExternalId, Id, Balance>,
     pub consumed: bool,
+    pub lock: Balance,
 }
 
 impl<ExternalId: Clone, Id: Clone + Copy, Balance: Zero + Clone + Copy>
@@ -44,6 +46,7 @@ impl<ExternalId: Clone, Id: Clone + Copy, Balance: Zero + Clone + Copy>
             spec_refs: 0,
             unspec_refs: 0,
             consumed: false,
+            lock: Zero::zero(),
         }
     }
 
@@ -53,6 +56,7 @@ impl<ExternalId: Clone, Id: Clone + Copy, Balance: Zero + Clone + Copy>
             GasNodeType::ReservedLocal { value, .. } => Some(value),
             GasNodeType::SpecifiedLocal { value, .. } => Some(value),
             GasNodeType::UnspecifiedLocal { .. } => None,
+            GasNodeType::Reserved { value, .. } => Some(value),
         }
     }
 
@@ -62,12 +66,15 @@ impl<ExternalId: Clone, Id: Clone + Copy, Balance: Zero + Clone + Copy>
             GasNodeType::ReservedLocal { ref mut value, .. } => Some(value),
             GasNodeType::SpecifiedLocal { ref mut value, .. } => Some(value),
             GasNodeType::UnspecifiedLocal { .. } => None,
+            GasNodeType::Reserved { ref mut value, .. } => Some(value),
         }
     }
 
     pub fn parent(&self) -> Option<Id> {
         match self.inner {
-            GasNodeType::External { .. } | GasNodeType::ReservedLocal { .. } => None,
+            GasNodeType::External { .. }
+            | GasNodeType::ReservedLocal { .. }
+            | GasNodeType::Reserved { .. } => None,
             GasNodeType::SpecifiedLocal { parent, .. }
             | GasNodeType::UnspecifiedLocal { parent } => Some(parent),
         }
@@ -139,6 +139,7 @@ enum Error {
     NodeWasConsumed,
     InsufficientBalance,
     Forbidden,
+    ConsumedWithLock,
 }
 
 impl super::Error for Error {
@@ -169,6 +170,10 @@ impl super::Error for Error {
     fn forbidden() -> Self {
         Self::Forbidden
     }
+
+    fn consumed_with_lock() -> Self {
+        Self::ConsumedWithLock
+    }
 }
 
 struct GasProvider;
@@ -0,0 +1,194 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Journal export support runtime functions
+
+use crate::event::DispatchStatus;
+use codec::{Decode, Encode};
+use gear_core::{
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
+    memory::{PageNumber, WasmPageNumber},
+    message::{DispatchKind, ExitCode},
+};
+use gear_runtime_interface::gear_ri;
+use scale_info::TypeInfo;
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+/// Journal of the queue processing within the block, exported to indexers.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, TypeInfo)]
+pub struct BlockJournal<BlockNumber, Hash> {
+    /// Number of the block.
+    pub number: BlockNumber,
+    /// Hash of the parent block.
+    ///
+    /// Block could be executed several times on different forks,
+    /// so indexers should distinguish journals by parent hash.
+    pub parent_hash: Hash,
+    /// Records of the messages processing in order they were applied.
+    pub records: Vec<JournalRecord>,
+}
+
+/// Summary of the journal note, produced by message processing.
+///
+/// Same as `core_processor::common::JournalNote`, but without
+/// pages data and other details, which are useless for indexers.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, TypeInfo)]
+pub enum JournalRecord {
+    /// Message was dispatched.
+    MessageDispatched {
+        /// Id of the message.
+        message_id: MessageId,
+        /// Source of the message.
+        source: ProgramId,
+        /// Status of the message execution.
+        status: DispatchStatus,
+    },
+    /// Some gas was burned by the message.
+    GasBurned {
+        /// Id of the message.
+        message_id: MessageId,
+        /// Amount of gas burned.
+        amount: u64,
+    },
+    /// Program exited.
+    ProgramExited {
+        /// Id of the program.
+        program_id: ProgramId,
+        /// Destination of the program value.
+        value_destination: ProgramId,
+    },
+    /// Message was handled and no longer exists.
+    MessageConsumed(MessageId),
+    /// Message was sent by the processed one.
+    MessageSent {
+        /// Id of the message, which sent the dispatch.
+        message_id: MessageId,
+        /// Id of the sent dispatch.
+        dispatch_id: MessageId,
+        /// Entry point of the sent dispatch.
+        kind: DispatchKind,
+        /// Destination of the sent dispatch.
+        destination: ProgramId,
+        /// Gas limit of the sent dispatch.
+        gas_limit: Option<u64>,
+        /// Value of the sent dispatch.
+        value: u128,
+        /// Amount of blocks the dispatch is delayed for.
+        delay: u32,
+    },
+    /// Message was put in the waitlist.
+    MessageWaited {
+        /// Id of the message.
+        message_id: MessageId,
+        /// Program the message is addressed to.
+        program_id: ProgramId,
+    },
+    /// Message was woken.
+    MessageWoken {
+        /// Id of the message, which woke the other one.
+        message_id: MessageId,
+        /// Program, which woke the message.
+        program_id: ProgramId,
+        /// Id of the woken message.
+        awakening_id: MessageId,
+    },
+    /// Page of the program memory was updated.
+    PageUpdated {
+        /// Id of the program.
+        program_id: ProgramId,
+        /// Number of the updated page.
+        page_number: PageNumber,
+    },
+    /// Allocations of the program were updated.
+    AllocationsUpdated {
+        /// Id of the program.
+        program_id: ProgramId,
+        /// New allocations of the program.
+        allocations: BTreeSet<WasmPageNumber>,
+    },
+    /// Value was sent.
+    ValueSent {
+        /// Sender of the value.
+        from: ProgramId,
+        /// Beneficiary of the value.
+        to: Option<ProgramId>,
+        /// Amount of the value.
+        value: u128,
+    },
+    /// Programs were requested to be created.
+    ProgramsCreated {
+        /// Program, which requested creation.
+        program_id: ProgramId,
+        /// Code of the programs.
+        code_id: CodeId,
+        /// Ids of the programs along with ids of their init messages.
+        candidates: Vec<(ProgramId, MessageId)>,
+    },
+    /// Gas of the message was reserved.
+    GasReserved {
+        /// Id of the message.
+        message_id: MessageId,
+        /// Id of the reservation.
+        reservation_id: ReservationId,
+        /// Program, which owns the reservation.
+        program_id: ProgramId,
+        /// Amount of reserved gas.
+        amount: u64,
+        /// Block number, at which reservation expires.
+        expiration: u32,
+    },
+    /// Gas reservation was removed.
+    GasUnreserved {
+        /// Id of the reservation.
+        reservation_id: ReservationId,
+        /// Program, which owned the reservation.
+        program_id: ProgramId,
+    },
+    /// Gas of the message was reserved for its signal.
+    SystemGasReserved {
+        /// Id of the message.
+        message_id: MessageId,
+        /// Amount of reserved gas.
+        amount: u64,
+    },
+    /// Gas reserved for the signal of the message was returned.
+    SystemGasUnreserved {
+        /// Id of the message.
+        message_id: MessageId,
+    },
+    /// Signal was sent.
+    SignalSent {
+        /// Id of the message, which execution failed.
+        message_id: MessageId,
+        /// Program, which receives the signal.
+        destination: ProgramId,
+        /// Exit code of the signal.
+        exit_code: ExitCode,
+    },
+}
+
+/// Returns whether the node exports journals of blocks.
+///
+/// The value is a node setting, so it must not affect the state.
+pub fn is_export_enabled() -> bool {
+    gear_ri::is_journal_export_enabled()
+}
+
+/// Exports journal of the block to the node's sink.
+pub fn export<BlockNumber: Encode, Hash: Encode>(journal: &BlockJournal<BlockNumber, Hash>) {
+    gear_ri::export_journal(&journal.encode())
+}
@@ -22,6 +22,7 @@
 extern crate gear_common_codegen;
 
 pub mod event;
+pub mod journal;
 pub mod lazy_pages;
 pub mod scheduler;
 pub mod storage;
@@ -42,7 +43,8 @@ use frame_support::{
     weights::{IdentityFee, WeightToFee},
 };
 use gear_core::{
-    ids::{CodeId, MessageId, ProgramId},
+    gas::GasReservationMap,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{Error as MemoryError, PageBuf, PageNumber, WasmPageNumber},
 };
 use primitive_types::H256;
@@ -51,6 +53,7 @@ use sp_arithmetic::traits::{BaseArithmetic, Unsigned};
 use sp_core::crypto::UncheckedFrom;
 use sp_std::{
     collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+    convert::TryFrom,
     prelude::*,
 };
 use storage::ValueStorage;
@@ -123,6 +126,16 @@ impl Origin for ProgramId {
     }
 }
 
+impl Origin for ReservationId {
+    fn into_origin(self) -> H256 {
+        H256(self.into())
+    }
+
+    fn from_origin(val: H256) -> Self {
+        val.to_fixed_bytes().into()
+    }
+}
+
 impl Origin for CodeId {
     fn into_origin(self) -> H256 {
         H256(self.into())
@@ -169,12 +182,14 @@ pub trait BlockLimiter {
 pub enum Program {
     Active(ActiveProgram),
     Terminated,
+    Exited { inheritor: ProgramId },
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum ProgramError {
     CodeHashNotFound,
     IsTerminated,
+    IsExited,
     DoesNotExist,
 }
 
@@ -187,6 +202,10 @@ impl Program {
         matches!(self, Program::Terminated)
     }
 
+    pub fn is_exited(&self) -> bool {
+        matches!(self, Program::Exited { .. })
+    }
+
     pub fn is_initialized(&self) -> bool {
         matches!(
             self,
@@ -215,6 +234,7 @@ impl core::convert::TryFrom<Program> for ActiveProgram {
         match prog_with_status {
             Program::Active(p) => Ok(p),
             Program::Terminated => Err(ProgramError::IsTerminated),
+            Program::Exited { .. } => Err(ProgramError::IsExited),
         }
     }
 }
@@ -227,6 +247,8 @@ pub struct ActiveProgram {
     pub pages_with_data: BTreeSet<PageNumber>,
     pub code_hash: H256,
     pub state: ProgramState,
+    /// Gas reservations of the program.
+    pub gas_reservation_map: GasReservationMap,
 }
 
 /// Enumeration contains variants for program state.
@@ -257,6 +279,25 @@ impl CodeMetadata {
     }
 }
 
+/// Type information uploaded by the code author to describe program state.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, TypeInfo)]
+pub struct CodeTypeMetadata {
+    /// Scale-encoded portable type registry.
+    pub registry: Vec<u8>,
+    /// Id of the state type within `registry`.
+    #[codec(compact)]
+    pub state_type: u32,
+}
+
+impl CodeTypeMetadata {
+    pub fn new(registry: Vec<u8>, state_type: u32) -> Self {
+        CodeTypeMetadata {
+            registry,
+            state_type,
+        }
+    }
+}
+
 pub fn program_key(id: H256) -> Vec<u8> {
     let mut key = Vec::new();
     key.extend(STORAGE_PROGRAM_PREFIX);
@@ -274,7 +315,8 @@ pub fn pages_prefix(program_id: H256) -> Vec<u8> {
     key
 }
 
-fn page_key(id: H256, page: PageNumber) -> Vec<u8> {
+/// Returns storage key of the data of the program page.
+pub fn page_key(id: H256, page: PageNumber) -> Vec<u8> {
     // try to avoid realloc
     let id_bytes = id.as_fixed_bytes();
     let mut key = Vec::with_capacity(
@@ -298,17 +340,24 @@ pub fn set_program_initialized(id: H256) {
 }
 
 pub fn set_program_terminated_status(id: H256) -> Result<(), ProgramError> {
-    if let Some(program) = get_program(id) {
-        if program.is_terminated() {
-            return Err(ProgramError::IsTerminated);
-        }
+    set_program_inactive_status(id, Program::Terminated)
+}
+
+pub fn set_program_exited_status(id: H256, inheritor: ProgramId) -> Result<(), ProgramError> {
+    set_program_inactive_status(id, Program::Exited { inheritor })
+}
 
-        sp_io::storage::clear_prefix(&pages_prefix(id), None);
-        sp_io::storage::set(&program_key(id), &Program::Terminated.encode());
+fn set_program_inactive_status(id: H256, status: Program) -> Result<(), ProgramError> {
+    match get_program(id) {
+        Some(Program::Active(_)) => {
+            sp_io::storage::clear_prefix(&pages_prefix(id), None);
+            sp_io::storage::set(&program_key(id), &status.encode());
 
-        Ok(())
-    } else {
-        Err(ProgramError::DoesNotExist)
+            Ok(())
+        }
+        Some(Program::Terminated) => Err(ProgramError::IsTerminated),
+        Some(Program::Exited { .. }) => Err(ProgramError::IsExited),
+        None => Err(ProgramError::DoesNotExist),
     }
 }
 
@@ -378,6 +427,10 @@ pub fn set_program_and_pages_data(
         if !program.allocations.contains(&page_num.to_wasm_page()) {
             return Err(PageIsNotAllocatedErr(page_num));
         }
+        // Pages without data are zeroed on load anyway.
+        if page_buf.is_zero() {
+            continue;
+        }
         let key = page_key(id, page_num);
         sp_io::storage::set(&key, page_buf.as_slice());
     }
@@ -389,6 +442,38 @@ pub fn program_exists(id: H256) -> bool {
     sp_io::storage::exists(&program_key(id))
 }
 
+/// Returns all storage keys starting with `prefix`, in lexicographic order.
+fn keys_with_prefix(prefix: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let mut previous = prefix.to_vec();
+
+    sp_std::iter::from_fn(move || {
+        let key = sp_io::storage::next_key(&previous).filter(|key| key.starts_with(prefix))?;
+        previous = key.clone();
+
+        Some(key)
+    })
+}
+
+/// Returns ids of all programs in storage, whatever their status is.
+pub fn program_ids() -> impl Iterator<Item = H256> {
+    keys_with_prefix(STORAGE_PROGRAM_PREFIX).map(|key| {
+        H256::decode(&mut &key[STORAGE_PROGRAM_PREFIX.len()..]).expect("keys encoded correctly")
+    })
+}
+
+/// Returns numbers of the program pages, which have data in storage.
+pub fn get_program_stored_pages(id: H256) -> BTreeSet<PageNumber> {
+    let prefix = pages_prefix(id);
+
+    keys_with_prefix(&prefix)
+        .map(|key| {
+            let page = <[u8; 4]>::try_from(&key[prefix.len()..]).expect("keys encoded correctly");
+
+            PageNumber(u32::from_le_bytes(page))
+        })
+        .collect()
+}
+
 pub fn set_program_allocations(id: H256, allocations: BTreeSet<WasmPageNumber>) {
     if let Some(Program::Active(mut prog)) = get_program(id) {
         prog.allocations = allocations;
@@ -396,6 +481,13 @@ pub fn set_program_allocations(id: H256, allocations: BTreeSet<WasmPageNumber>)
     }
 }
 
+pub fn set_program_gas_reservation_map(id: H256, gas_reservation_map: GasReservationMap) {
+    if let Some(Program::Active(mut prog)) = get_program(id) {
+        prog.gas_reservation_map = gas_reservation_map;
+        sp_io::storage::set(&program_key(id), &Program::Active(prog).encode())
+    }
+}
+
 pub fn set_program_page_data(program_id: H256, page: PageNumber, page_buf: PageBuf) {
     let page_key = page_key(program_id, page);
     sp_io::storage::set(&page_key, page_buf.as_slice());
@@ -26,8 +26,11 @@ mod task;
 pub use scope::*;
 pub use task::*;
 
-use crate::storage::{CountedByKey, KeyIterableByKeyMap, ValueStorage};
+use crate::storage::{CountedByKey, IterableMap, KeyIterableByKeyMap, MapStorage, ValueStorage};
+use codec::{Decode, Encode};
 use core::fmt::Debug;
+use gear_core::{ids::MessageId, message::StoredDispatch};
+use scale_info::TypeInfo;
 
 /// Represents scheduler's logic of centralized delayed tasks management logic.
 pub trait Scheduler {
@@ -69,15 +72,48 @@ pub trait Scheduler {
         > + CountedByKey<Key = Self::BlockNumber, Length = usize>
         + KeyIterableByKeyMap<Key1 = Self::BlockNumber, Key2 = Self::Task>;
 
+    /// Gear dispatch stash.
+    ///
+    /// Dispatch stash holds delayed dispatches along with
+    /// the interval of their holding till the sending block.
+    type DispatchStash: MapStorage<Key = MessageId, Value = (StoredDispatch, Interval<Self::BlockNumber>)>
+        + IterableMap<(StoredDispatch, Interval<Self::BlockNumber>)>;
+
     /// Resets all related to messenger storages.
     ///
     /// It's temporary production solution to avoid DB migrations,
     /// would be available for tests purposes only in future.
     fn reset() {
         Self::TaskPool::clear();
+        Self::DispatchStash::clear();
     }
 }
 
+/// Interval of blocks, during which some item is held in storage.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub struct Interval<BlockNumber> {
+    /// Block number, since which the item is held.
+    pub start: BlockNumber,
+    /// Block number, till which the item is held.
+    pub finish: BlockNumber,
+}
+
+/// Storage, in which item is held, while paying for it per block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub enum HoldKind {
+    /// Message in waitlist.
+    Waitlist,
+    /// Message in mailbox.
+    Mailbox,
+    /// Delayed dispatch in dispatch stash.
+    DispatchStash,
+    /// Gas reservation.
+    ///
+    /// Reservations aren't charged for holding: they are
+    /// held for the duration specified on reserving.
+    Reservation,
+}
+
 /// Storing costs getter trait.
 pub trait SchedulingCostsPerBlock {
     /// Block number type.
@@ -96,4 +132,6 @@ pub trait SchedulingCostsPerBlock {
     fn program() -> Self::Cost;
     /// Cost for storing message in waitlist per block.
     fn waitlist() -> Self::Cost;
+    /// Cost for storing delayed dispatch in dispatch stash per block.
+    fn dispatch_stash() -> Self::Cost;
 }
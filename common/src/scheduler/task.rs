@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use codec::{Decode, Encode};
-use gear_core::ids::{CodeId, MessageId, ProgramId};
+use gear_core::ids::{CodeId, MessageId, ProgramId, ReservationId};
 use scale_info::TypeInfo;
 
 /// Scheduled task sense and required data for processing action.
@@ -28,7 +28,7 @@ pub enum ScheduledTask<AccountId> {
     /// Pause program as out of rent one.
     PauseProgram(ProgramId),
 
-    /// Remove code from code storage as out of rent one.
+    /// Remove code from code storage as unused one.
     RemoveCode(CodeId),
 
     /// Remove message from mailbox as out of rent one.
@@ -44,6 +44,16 @@ pub enum ScheduledTask<AccountId> {
     // -----
     /// Delayed wake of the message at concrete block.
     WakeMessage(ProgramId, MessageId),
+
+    /// Delayed message to program sending.
+    ///
+    /// The message itself stored in DispatchStash.
+    SendDispatch(MessageId),
+
+    // Gas reservation section.
+    // -----
+    /// Remove gas reservation as expired one.
+    RemoveGasReservation(ProgramId, ReservationId),
 }
 
 impl<AccountId> ScheduledTask<AccountId> {
@@ -61,6 +71,10 @@ impl<AccountId> ScheduledTask<AccountId> {
             }
             RemovePausedProgram(program_id) => handler.remove_paused_program(program_id),
             WakeMessage(program_id, message_id) => handler.wake_message(program_id, message_id),
+            SendDispatch(message_id) => handler.send_dispatch(message_id),
+            RemoveGasReservation(program_id, reservation_id) => {
+                handler.remove_gas_reservation(program_id, reservation_id)
+            }
         }
     }
 }
@@ -84,4 +98,11 @@ pub trait TaskHandler<AccountId> {
     // -----
     /// Wake message action.
     fn wake_message(&mut self, program_id: ProgramId, message_id: MessageId);
+    /// Send delayed message to program action.
+    fn send_dispatch(&mut self, stashed_message_id: MessageId);
+
+    // Gas reservation section.
+    // -----
+    /// Remove gas reservation action.
+    fn remove_gas_reservation(&mut self, program_id: ProgramId, reservation_id: ReservationId);
 }
@@ -22,7 +22,8 @@
 //! addressed to users.
 
 use crate::storage::{
-    Callback, CountedByKey, DoubleMapStorage, FallibleCallback, IterableByKeyMap, KeyFor,
+    Callback, CountedByKey, DoubleMapStorage, FallibleCallback, IterableByKeyMap, IterableMap,
+    KeyFor,
 };
 use core::marker::PhantomData;
 
@@ -60,11 +61,18 @@ pub trait MailboxCallbacks<OutputError> {
     /// This value should be the main item of mailbox,
     /// which uses this callbacks store.
     type Value;
+    /// Callback relative type.
+    ///
+    /// This type represents first key of mailbox,
+    /// which uses this callbacks store.
+    type Key;
 
     /// Callback on success `insert`.
     type OnInsert: Callback<Self::Value>;
     /// Callback on success `remove`.
     type OnRemove: FallibleCallback<Self::Value, Error = OutputError>;
+    /// Callback on `drain_key`, called before the key's mailbox is drained.
+    type OnDrain: Callback<Self::Key>;
 }
 
 /// Represents mailbox error type.
@@ -170,7 +178,7 @@ where
     T: DoubleMapStorage + IterableByKeyMap<T::Value, Key = T::Key1>,
     Error: MailboxError,
     OutputError: From<Error>,
-    Callbacks: MailboxCallbacks<OutputError, Value = T::Value>,
+    Callbacks: MailboxCallbacks<OutputError, Value = T::Value, Key = T::Key1>,
     KeyGen: KeyFor<Key = (T::Key1, T::Key2), Value = T::Value>,
 {
     type Key = T::Key1;
@@ -178,6 +186,7 @@ where
     type Iter = T::Iter;
 
     fn drain_key(key: Self::Key) -> Self::DrainIter {
+        Callbacks::OnDrain::call(&key);
         T::drain_key(key)
     }
 
@@ -185,3 +194,26 @@ where
         T::iter_key(key)
     }
 }
+
+// Implementation of `IterableMap` trait for `MailboxImpl` in case,
+// when inner `DoubleMapStorage` implements `IterableMap`.
+impl<T, Error, OutputError, Callbacks, KeyGen> IterableMap<T::Value>
+    for MailboxImpl<T, Error, OutputError, Callbacks, KeyGen>
+where
+    T: DoubleMapStorage + IterableMap<T::Value>,
+    Error: MailboxError,
+    OutputError: From<Error>,
+    Callbacks: MailboxCallbacks<OutputError, Value = T::Value>,
+    KeyGen: KeyFor<Key = (T::Key1, T::Key2), Value = T::Value>,
+{
+    type DrainIter = T::DrainIter;
+    type Iter = T::Iter;
+
+    fn drain() -> Self::DrainIter {
+        T::drain()
+    }
+
+    fn iter() -> Self::Iter {
+        T::iter()
+    }
+}
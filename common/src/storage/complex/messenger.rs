@@ -116,7 +116,8 @@ pub trait Messenger {
             Error = Self::Error,
             OutputError = Self::OutputError,
         > + CountedByKey<Key = Self::MailboxFirstKey, Length = usize>
-        + IterableByKeyMap<Self::MailboxedMessage, Key = Self::MailboxFirstKey>;
+        + IterableByKeyMap<Self::MailboxedMessage, Key = Self::MailboxFirstKey>
+        + IterableMap<Self::MailboxedMessage>;
 
     /// Gear waitlist.
     ///
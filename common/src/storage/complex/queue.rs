@@ -51,6 +51,11 @@ pub trait Queue {
     /// Should be used only for cases, when message was dequeued and
     /// it's execution should be postponed until the next block.
     fn requeue(value: Self::Value) -> Result<(), Self::OutputError>;
+
+    /// Checks, that the queue's inner storage is consistent.
+    ///
+    /// Walks through the whole queue, so should be used in checks only.
+    fn check_consistency() -> Result<(), Self::OutputError>;
 }
 
 /// `Mailbox` implementation based on `Dequeue`.
@@ -95,6 +100,10 @@ where
         let key = KeyGen::key_for(&value);
         T::push_front(key, value).map_err(Into::into)
     }
+
+    fn check_consistency() -> Result<(), Self::OutputError> {
+        T::check_consistency().map_err(Into::into)
+    }
 }
 
 // Implementation of `Counted` trait for `QueueImpl` in case,
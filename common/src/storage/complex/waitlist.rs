@@ -46,7 +46,13 @@ pub trait Waitlist {
     /// Returns bool, defining does first key's waitlist contain second key.
     fn contains(key1: &Self::Key1, key2: &Self::Key2) -> bool;
 
+    /// Returns bool, defining is first key's waitlist full,
+    /// so no more values could be inserted in it.
+    fn is_full(key1: &Self::Key1) -> bool;
+
     /// Inserts given value in waitlist.
+    ///
+    /// Returns error, if the first key's waitlist is full.
     fn insert(value: Self::Value) -> Result<(), Self::OutputError>;
 
     /// Removes and returns value from waitlist by given keys,
@@ -72,13 +78,22 @@ pub trait WaitlistCallbacks {
     /// This type represents block number of stored component in waitlist,
     /// which uses this callbacks store.
     type BlockNumber;
+    /// Callback relative type.
+    ///
+    /// This type represents first key of waitlist,
+    /// which uses this callbacks store.
+    type Key;
 
     /// Callback used for getting current block number.
     type GetBlockNumber: GetCallback<Self::BlockNumber>;
+    /// Callback used for getting max amount of values per first key.
+    type GetCapacity: GetCallback<usize>;
     /// Callback on success `insert`.
     type OnInsert: Callback<(Self::Value, Self::BlockNumber)>;
     /// Callback on success `remove`.
     type OnRemove: Callback<(Self::Value, Self::BlockNumber)>;
+    /// Callback on `drain_key`, called before the key's waitlist is drained.
+    type OnDrain: Callback<Self::Key>;
 }
 
 /// Represents waitlist error type.
@@ -90,9 +105,12 @@ pub trait WaitlistError {
 
     /// Occurs when element wasn't found in storage.
     fn element_not_found() -> Self;
+
+    /// Occurs when first key's waitlist has no room for the value.
+    fn full() -> Self;
 }
 
-/// `Waitlist` implementation based on `DoubleMapStorage`.
+/// `Waitlist` implementation based on `DoubleMapStorage`, counted by first key.
 ///
 /// Generic parameter `Error` requires `WaitlistError` implementation.
 /// Generic parameter `KeyGen` presents key generation for given values.
@@ -112,7 +130,8 @@ where
 impl<T, Value, BlockNumber, Error, OutputError, Callbacks, KeyGen> Waitlist
     for WaitlistImpl<T, Value, BlockNumber, Error, OutputError, Callbacks, KeyGen>
 where
-    T: DoubleMapStorage<Value = (Value, BlockNumber)>,
+    T: DoubleMapStorage<Value = (Value, BlockNumber)>
+        + CountedByKey<Key = <T as DoubleMapStorage>::Key1, Length = usize>,
     Error: WaitlistError,
     OutputError: From<Error>,
     Callbacks: WaitlistCallbacks<Value = Value, BlockNumber = BlockNumber>,
@@ -129,6 +148,10 @@ where
         T::contains_keys(program_id, message_id)
     }
 
+    fn is_full(program_id: &Self::Key1) -> bool {
+        T::len(program_id) >= Callbacks::GetCapacity::call()
+    }
+
     fn insert(message: Self::Value) -> Result<(), Self::OutputError> {
         let (key1, key2) = KeyGen::key_for(&message);
 
@@ -136,6 +159,10 @@ where
             return Err(Self::Error::duplicate_key().into());
         }
 
+        if Self::is_full(&key1) {
+            return Err(Self::Error::full().into());
+        }
+
         let block_number = Callbacks::GetBlockNumber::call();
         let message_with_bn = (message, block_number);
 
@@ -188,7 +215,7 @@ where
     T: DoubleMapStorage<Value = (Value, BlockNumber)> + IterableByKeyMap<T::Value, Key = T::Key1>,
     Error: WaitlistError,
     OutputError: From<Error>,
-    Callbacks: WaitlistCallbacks<Value = Value, BlockNumber = BlockNumber>,
+    Callbacks: WaitlistCallbacks<Value = Value, BlockNumber = BlockNumber, Key = T::Key1>,
     KeyGen: KeyFor<Key = (T::Key1, T::Key2), Value = Value>,
 {
     type Key = T::Key1;
@@ -196,6 +223,7 @@ where
     type Iter = T::Iter;
 
     fn drain_key(key: Self::Key) -> Self::DrainIter {
+        Callbacks::OnDrain::call(&key);
         T::drain_key(key)
     }
 
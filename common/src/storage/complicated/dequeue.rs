@@ -27,6 +27,7 @@
 //! both be set or be empty.
 //! - Inner map should contain values under keys, set in head and tail,
 //! if they present.
+//! - Links of the nodes should lead from head to tail without cycles.
 
 use crate::storage::{Callback, Counted, EmptyCallback, IterableMap, MapStorage, ValueStorage};
 use codec::{Decode, Encode};
@@ -59,6 +60,11 @@ pub trait Dequeue {
 
     /// Removes all values.
     fn clear();
+
+    /// Checks, that links of the dequeue lead from its head to its tail.
+    ///
+    /// Walks through all elements, so should be used in checks only.
+    fn check_consistency() -> Result<(), Self::Error>;
 }
 
 /// Represents store of dequeue's action callbacks.
@@ -314,6 +320,58 @@ where
         MS::clear();
         Callbacks::OnClear::call();
     }
+
+    fn check_consistency() -> Result<(), Self::Error> {
+        let head_key = if let Some(head_key) = HVS::get() {
+            head_key
+        } else if TVS::exists() {
+            return Err(Self::Error::tail_should_not_be_set());
+        } else {
+            return Ok(());
+        };
+
+        let tail_key = TVS::get().ok_or_else(Self::Error::tail_should_be_set)?;
+
+        let next_of = |key: &Key| {
+            MS::get(key)
+                .map(|node| node.next)
+                .ok_or_else(Self::Error::element_not_found)
+        };
+
+        // Links are followed by two cursors, the fast one moving
+        // twice per step, so it catches up with the slow one
+        // only if links are cycled.
+        let mut slow = head_key.clone();
+        let mut fast = head_key;
+
+        let last_key = loop {
+            let next = match next_of(&fast)? {
+                Some(next) => next,
+                None => break fast,
+            };
+
+            fast = match next_of(&next)? {
+                Some(next_next) => next_next,
+                None => break next,
+            };
+
+            slow = next_of(&slow)?.ok_or_else(Self::Error::element_not_found)?;
+
+            if slow == fast {
+                return Err(Self::Error::tail_has_next_key());
+            }
+        };
+
+        if last_key != tail_key {
+            return if next_of(&tail_key)?.is_some() {
+                Err(Self::Error::tail_has_next_key())
+            } else {
+                Err(Self::Error::tail_parent_not_found())
+            };
+        }
+
+        Ok(())
+    }
 }
 
 /// Drain iterator over dequeue's values.
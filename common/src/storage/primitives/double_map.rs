@@ -142,11 +142,155 @@ macro_rules! wrap_storage_double_map {
 /// of `CountedByKey`, `IterableMap` and `IterableByKeyMap`
 /// over double map values.
 ///
+/// If `counter` storage is specified, which is a `Substrate`'s
+/// `StorageMap` from the first key into `u32`, amount of values
+/// under each first key is maintained there, so `CountedByKey::len`
+/// doesn't iterate over the map. Draining functions reset the counter
+/// on call, so returned iterators should be consumed entirely.
+///
 /// `PrefixIterator` from `frame_support` and `KeyValueIteratorWrap` from
 /// this crate should be in scope.
 #[allow(clippy::crate_in_macro_def)]
 #[macro_export]
 macro_rules! wrap_extended_storage_double_map {
+    (storage: $storage: ident, name: $name: ident, key1: $key1: ty,
+        key2: $key2: ty, value: $val: ty, length: $len: ty, counter: $counter: ident) => {
+        pub struct $name<T>(PhantomData<T>);
+
+        impl<T: crate::Config> $name<T> {
+            fn increase_counter(key1: &$key1) {
+                $counter::<T>::mutate(key1, |counter| {
+                    *counter = Some(counter.unwrap_or(0).saturating_add(1))
+                });
+            }
+
+            fn decrease_counter(key1: &$key1) {
+                $counter::<T>::mutate_exists(key1, |counter| {
+                    *counter = counter.and_then(|c| c.checked_sub(1)).filter(|&c| c > 0)
+                });
+            }
+        }
+
+        impl<T: crate::Config> DoubleMapStorage for $name<T> {
+            type Key1 = $key1;
+            type Key2 = $key2;
+            type Value = $val;
+
+            fn contains_keys(key1: &Self::Key1, key2: &Self::Key2) -> bool {
+                $storage::<T>::contains_key(key1, key2)
+            }
+
+            fn get(key1: &Self::Key1, key2: &Self::Key2) -> Option<Self::Value> {
+                $storage::<T>::get(key1, key2)
+            }
+
+            fn insert(key1: Self::Key1, key2: Self::Key2, value: Self::Value) {
+                if !$storage::<T>::contains_key(&key1, &key2) {
+                    Self::increase_counter(&key1);
+                }
+
+                $storage::<T>::insert(key1, key2, value)
+            }
+
+            fn mutate<R, F: FnOnce(&mut Option<Self::Value>) -> R>(
+                key1: Self::Key1,
+                key2: Self::Key2,
+                f: F,
+            ) -> R {
+                let existed = $storage::<T>::contains_key(&key1, &key2);
+                let (res, exists) = $storage::<T>::mutate(&key1, key2, |value| {
+                    let res = f(value);
+                    (res, value.is_some())
+                });
+
+                match (existed, exists) {
+                    (false, true) => Self::increase_counter(&key1),
+                    (true, false) => Self::decrease_counter(&key1),
+                    _ => {}
+                }
+
+                res
+            }
+
+            fn mutate_values<F: FnMut(Self::Value) -> Self::Value>(mut f: F) {
+                let f = |v| Some(f(v));
+                $storage::<T>::translate_values(f)
+            }
+
+            fn remove(key1: Self::Key1, key2: Self::Key2) {
+                let _ = Self::take(key1, key2);
+            }
+
+            fn clear() {
+                let _ = $storage::<T>::clear(u32::MAX, None);
+                let _ = $counter::<T>::clear(u32::MAX, None);
+            }
+
+            fn take(key1: Self::Key1, key2: Self::Key2) -> Option<Self::Value> {
+                let value = $storage::<T>::take(&key1, key2);
+
+                if value.is_some() {
+                    Self::decrease_counter(&key1);
+                }
+
+                value
+            }
+        }
+
+        impl<T: crate::Config> CountedByKey for $name<T> {
+            type Key = $key1;
+            type Length = $len;
+
+            fn len(key: &Self::Key) -> Self::Length {
+                $counter::<T>::get(key).unwrap_or(0) as $len
+            }
+        }
+
+        impl<T: crate::Config> IterableByKeyMap<$val> for $name<T> {
+            type Key = $key1;
+            type DrainIter = IteratorWrap<PrefixIterator<($key2, $val)>, $val, GetSecondPos>;
+            type Iter = IteratorWrap<PrefixIterator<($key2, $val)>, $val, GetSecondPos>;
+
+            fn drain_key(key: Self::Key) -> Self::DrainIter {
+                $counter::<T>::remove(&key);
+                $storage::<T>::drain_prefix(key).into()
+            }
+
+            fn iter_key(key: Self::Key) -> Self::Iter {
+                $storage::<T>::iter_prefix(key).into()
+            }
+        }
+
+        impl<T: crate::Config> IterableMap<$val> for $name<T> {
+            type DrainIter = IteratorWrap<PrefixIterator<($key1, $key2, $val)>, $val, GetThirdPos>;
+            type Iter = IteratorWrap<PrefixIterator<($key1, $key2, $val)>, $val, GetThirdPos>;
+
+            fn drain() -> Self::DrainIter {
+                let _ = $counter::<T>::clear(u32::MAX, None);
+                $storage::<T>::drain().into()
+            }
+
+            fn iter() -> Self::Iter {
+                $storage::<T>::iter().into()
+            }
+        }
+
+        impl<T: crate::Config> KeyIterableByKeyMap for $name<T> {
+            type Key1 = $key1;
+            type Key2 = $key2;
+            type DrainIter = IteratorWrap<PrefixIterator<($key2, $val)>, $key2, GetFirstPos>;
+            type Iter = IteratorWrap<PrefixIterator<($key2, $val)>, $key2, GetFirstPos>;
+
+            fn drain_prefix_keys(key: Self::Key1) -> Self::DrainIter {
+                $counter::<T>::remove(&key);
+                $storage::<T>::drain_prefix(key).into()
+            }
+
+            fn iter_prefix_keys(key: Self::Key1) -> Self::Iter {
+                $storage::<T>::iter_prefix(key).into()
+            }
+        }
+    };
     (storage: $storage: ident, name: $name: ident, key1: $key1: ty,
         key2: $key2: ty, value: $val: ty, length: $len: ty) => {
         $crate::wrap_storage_double_map!(
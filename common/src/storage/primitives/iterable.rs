@@ -28,7 +28,10 @@
 //! just checking them.
 
 use super::TransposeCallback;
-use core::marker::PhantomData;
+use core::{
+    iter::{Skip, Take},
+    marker::PhantomData,
+};
 
 /// Represents iterable logic for double key maps
 /// (Key1 -> Key2 -> Value).
@@ -47,6 +50,12 @@ pub trait IterableByKeyMap<Item> {
     fn drain_key(key: Self::Key) -> Self::DrainIter;
     /// Creates the getting iterator over double map Items.
     fn iter_key(key: Self::Key) -> Self::Iter;
+
+    /// Creates the getting iterator over a page of double map Items:
+    /// at most `limit` Items, following the first `offset` ones.
+    fn iter_key_page(key: Self::Key, offset: usize, limit: usize) -> Take<Skip<Self::Iter>> {
+        Self::iter_key(key).skip(offset).take(limit)
+    }
 }
 
 /// Represents iterable logic for single key maps
@@ -45,7 +45,7 @@ impl<T> ExtErrorProcessor<T> {
                 f(t)?;
                 Ok(0)
             }
-            Err(err) => Ok(err.encoded_size() as u32),
+            Err(err) => Ok(err.encoded_with_code_size() as u32),
         }
     }
 }
@@ -54,7 +54,7 @@ impl ExtErrorProcessor<()> {
     pub fn error_len(self) -> u32 {
         self.inner
             .err()
-            .map(|err| err.encoded_size() as u32)
+            .map(|err| err.encoded_with_code_size() as u32)
             .unwrap_or(0)
     }
 }
@@ -36,10 +36,10 @@ use codec::{Decode, Encode};
 use core::{fmt, ops::Deref};
 use gear_core::{
     env::Ext,
-    gas::GasAmount,
-    ids::{CodeId, MessageId, ProgramId},
+    gas::{GasAmount, GasReserver},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{Memory, PageBuf, PageNumber, WasmPageNumber},
-    message::{ContextStore, Dispatch, DispatchKind},
+    message::{ContextStore, Dispatch, DispatchKind, MessageWaitedType, ProgramEvent},
 };
 use gear_core_errors::{ExtError, MemoryError};
 use scale_info::TypeInfo;
@@ -47,6 +47,19 @@ use scale_info::TypeInfo;
 // Max amount of bytes allowed to be thrown as string explanation of the error.
 pub const TRIMMED_MAX_LEN: usize = 1024;
 
+/// Sys-calls forbidden in the signal handler.
+///
+/// Signal handler is executed on the system reservation, so it can't
+/// wait or spend gas reservations, which would make it outlive the message.
+pub const SIGNAL_FORBIDDEN_FUNCS: [&str; 6] = [
+    "gr_wait",
+    "gr_wait_for",
+    "gr_wait_up_to",
+    "gr_reserve_gas",
+    "gr_reservation_send",
+    "gr_system_reserve_gas",
+];
+
 /// Wrapped string to fit `core-backend::TRIMMED_MAX_LEN` amount of bytes.
 #[derive(
     Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, derive_more::Display,
@@ -80,7 +93,7 @@ pub enum TerminationReason {
     Leave,
     Success,
     Trap(TrapExplanation),
-    Wait,
+    Wait(Option<u32>, MessageWaitedType),
     GasAllowanceExceeded,
 }
 
@@ -101,12 +114,15 @@ pub enum TrapExplanation {
 #[derive(Debug)]
 pub struct ExtInfo {
     pub gas_amount: GasAmount,
+    pub gas_reserver: GasReserver,
     pub allocations: BTreeSet<WasmPageNumber>,
     pub pages_data: BTreeMap<PageNumber, PageBuf>,
-    pub generated_dispatches: Vec<Dispatch>,
-    pub awakening: Vec<MessageId>,
+    pub generated_dispatches: Vec<(Dispatch, u32, Option<ReservationId>)>,
+    pub awakening: Vec<(MessageId, u32)>,
     pub program_candidates_data: BTreeMap<CodeId, Vec<(ProgramId, MessageId)>>,
     pub context_store: ContextStore,
+    pub metrics: BTreeMap<[u8; 32], u64>,
+    pub events: Vec<ProgramEvent>,
 }
 
 pub trait IntoExtInfo {
@@ -160,16 +176,42 @@ pub trait Environment<E: Ext + IntoExtInfo + 'static>: Sized {
     fn get_mem_mut(&mut self) -> &mut Self::Memory;
 
     /// Run instance setup starting at `entry_point` - wasm export function name.
+    /// If `route` is provided, runs `handle::<route>` export instead of `handle`.
     /// Also runs `post_execution_handler` after running instance at provided entry point.
     fn execute<F, T>(
         self,
         entry_point: &DispatchKind,
+        route: Option<&str>,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<Self::Error>>
+    where
+        F: FnOnce(&Self::Memory) -> Result<(), T>,
+        T: fmt::Display;
+
+    /// Run instance at `export` function, which isn't an entry point of dispatches.
+    /// Used for informational executions, like program state queries.
+    /// Also runs `post_execution_handler` after running instance at provided export.
+    fn execute_export<F, T>(
+        self,
+        export: &str,
         post_execution_handler: F,
     ) -> Result<BackendReport, BackendError<Self::Error>>
     where
         F: FnOnce(&Self::Memory) -> Result<(), T>,
         T: fmt::Display;
 
+    /// Run instance at `export` function, which returns pointer to its result in memory,
+    /// like `meta_state` function of metadata binaries.
+    /// Also runs `post_execution_handler` with returned pointer after running instance.
+    fn execute_for_result<F, T>(
+        self,
+        export: &str,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<Self::Error>>
+    where
+        F: FnOnce(&Self::Memory, Option<i32>) -> Result<(), T>,
+        T: fmt::Display;
+
     /// Consumes environment and returns gas state.
     fn into_gas_amount(self) -> GasAmount;
 }
@@ -24,6 +24,7 @@ use crate::{
 };
 use alloc::{
     collections::BTreeSet,
+    format,
     string::{String, ToString},
 };
 use core::fmt;
@@ -32,19 +33,25 @@ use gear_backend_common::{
     IntoExtInfo, TerminationReason, TrapExplanation,
 };
 use gear_core::{
+    code::ROUTE_EXPORT_PREFIX,
     env::{Ext, ExtCarrier},
     gas::GasAmount,
     memory::WasmPageNumber,
     message::DispatchKind,
 };
 use gear_core_errors::MemoryError;
+use parity_wasm::elements::{External, Module};
 use sp_sandbox::{
     default_executor::{EnvironmentDefinitionBuilder, Instance, Memory as DefaultExecutorMemory},
-    HostFuncType, ReturnValue, SandboxEnvironmentBuilder, SandboxInstance, SandboxMemory,
+    HostFuncType, ReturnValue, SandboxEnvironmentBuilder, SandboxInstance, SandboxMemory, Value,
 };
 
 #[derive(Debug, derive_more::Display)]
 pub enum SandboxEnvironmentError {
+    #[display(fmt = "Non-env imports are not supported, but {:?} found", _0)]
+    NonEnvImport(String),
+    #[display(fmt = "Module must import the only memory, but {} found", _0)]
+    InvalidMemoriesAmount(usize),
     #[display(fmt = "Unable to instantiate module: {:?}", _0)]
     ModuleInstantiation(sp_sandbox::Error),
     #[display(fmt = "Unable to get wasm module exports: {}", _0)]
@@ -61,6 +68,40 @@ pub enum SandboxEnvironmentError {
     PostExecutionHandler(String),
 }
 
+/// Checks that module imports only from `env` and imports the only memory.
+///
+/// Code is checked on its acceptance, this is just a guard
+/// against executors, which handle multiple memories.
+fn check_imports(binary: &[u8]) -> Result<(), SandboxEnvironmentError> {
+    // Malformed module is reported on its instantiation.
+    let module: Module = match parity_wasm::deserialize_buffer(binary) {
+        Ok(module) => module,
+        Err(_) => return Ok(()),
+    };
+
+    let entries = module
+        .import_section()
+        .map(|section| section.entries())
+        .unwrap_or_default();
+
+    if let Some(entry) = entries.iter().find(|entry| entry.module() != "env") {
+        return Err(SandboxEnvironmentError::NonEnvImport(
+            entry.field().to_string(),
+        ));
+    }
+
+    let memories = entries
+        .iter()
+        .filter(|entry| matches!(entry.external(), External::Memory(_)))
+        .count();
+
+    if memories != 1 {
+        return Err(SandboxEnvironmentError::InvalidMemoriesAmount(memories));
+    }
+
+    Ok(())
+}
+
 /// Environment to run one module at a time providing Ext.
 pub struct SandboxEnvironment<E: Ext + IntoExtInfo> {
     runtime: Runtime<E>,
@@ -125,11 +166,13 @@ where
         builder.add_func("gr_create_program", Funcs::create_program);
         builder.add_func("gr_create_program_wgas", Funcs::create_program_wgas);
         builder.add_func("gr_debug", Funcs::debug);
+        builder.add_func("gr_emit_event", Funcs::emit_event);
         builder.add_func("gr_error", Funcs::error);
         builder.add_func("gr_exit", Funcs::exit);
         builder.add_func("gr_exit_code", Funcs::exit_code);
         builder.add_func("gr_gas_available", Funcs::gas_available);
         builder.add_func("gr_leave", Funcs::leave);
+        builder.add_func("gr_metric_increment", Funcs::metric_increment);
         builder.add_func("gr_msg_id", Funcs::msg_id);
         builder.add_func("gr_origin", Funcs::origin);
         builder.add_func("gr_program_id", Funcs::program_id);
@@ -138,24 +181,41 @@ where
         builder.add_func("gr_reply_commit", Funcs::reply_commit);
         builder.add_func("gr_reply_commit_wgas", Funcs::reply_commit_wgas);
         builder.add_func("gr_reply_push", Funcs::reply_push);
+        builder.add_func("gr_reply_push_input", Funcs::reply_push_input);
         builder.add_func("gr_reply_to", Funcs::reply_to);
         builder.add_func("gr_reply_wgas", Funcs::reply_wgas);
+        builder.add_func("gr_reservation_send", Funcs::reservation_send);
+        builder.add_func("gr_reserve_gas", Funcs::reserve_gas);
         builder.add_func("gr_send", Funcs::send);
+        builder.add_func("gr_send_cancel", Funcs::send_cancel);
         builder.add_func("gr_send_commit", Funcs::send_commit);
         builder.add_func("gr_send_commit_wgas", Funcs::send_commit_wgas);
         builder.add_func("gr_send_init", Funcs::send_init);
         builder.add_func("gr_send_push", Funcs::send_push);
+        builder.add_func("gr_send_push_input", Funcs::send_push_input);
         builder.add_func("gr_send_wgas", Funcs::send_wgas);
         builder.add_func("gr_size", Funcs::size);
         builder.add_func("gr_source", Funcs::source);
+        builder.add_func("gr_system_reserve_gas", Funcs::system_reserve_gas);
+        builder.add_func("gr_unreserve_gas", Funcs::unreserve_gas);
         builder.add_func("gr_value", Funcs::value);
         builder.add_func("gr_value_available", Funcs::value_available);
         builder.add_func("gr_wait", Funcs::wait);
+        builder.add_func("gr_wait_for", Funcs::wait_for);
+        builder.add_func("gr_wait_up_to", Funcs::wait_up_to);
         builder.add_func("gr_wake", Funcs::wake);
+        builder.add_func("gr_wake_delayed", Funcs::wake_delayed);
         let mut env_builder: EnvironmentDefinitionBuilder<_> = builder.into();
 
         let ext_carrier = ExtCarrier::new(ext);
 
+        if let Err(reason) = check_imports(binary) {
+            return Err(BackendError {
+                reason,
+                gas_amount: ext_carrier.into_inner().into_gas_amount(),
+            });
+        }
+
         let mem: DefaultExecutorMemory = match SandboxMemory::new(mem_size.0, None) {
             Ok(mem) => mem,
             Err(e) => {
@@ -217,19 +277,74 @@ where
     }
 
     fn execute<F, T>(
-        mut self,
+        self,
         entry_point: &DispatchKind,
+        route: Option<&str>,
         post_execution_handler: F,
     ) -> Result<BackendReport, BackendError<Self::Error>>
     where
         F: FnOnce(&Self::Memory) -> Result<(), T>,
         T: fmt::Display,
     {
-        let res = if self.entries.contains(entry_point) {
-            self.instance
-                .invoke(entry_point.into_entry(), &[], &mut self.runtime)
+        let func_name = if let Some(route) = route {
+            Some(format!("{}{}", ROUTE_EXPORT_PREFIX, route))
+        } else if self.entries.contains(entry_point) {
+            Some(entry_point.into_entry().to_string())
         } else {
-            Ok(ReturnValue::Unit)
+            None
+        };
+
+        self.run(func_name.as_deref(), |mem, _| post_execution_handler(mem))
+    }
+
+    fn execute_export<F, T>(
+        self,
+        export: &str,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<Self::Error>>
+    where
+        F: FnOnce(&Self::Memory) -> Result<(), T>,
+        T: fmt::Display,
+    {
+        self.run(Some(export), |mem, _| post_execution_handler(mem))
+    }
+
+    fn execute_for_result<F, T>(
+        self,
+        export: &str,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<Self::Error>>
+    where
+        F: FnOnce(&Self::Memory, Option<i32>) -> Result<(), T>,
+        T: fmt::Display,
+    {
+        self.run(Some(export), post_execution_handler)
+    }
+
+    fn into_gas_amount(self) -> GasAmount {
+        self.runtime.ext.into_inner().into_gas_amount()
+    }
+}
+
+impl<E> SandboxEnvironment<E>
+where
+    E: Ext + IntoExtInfo + 'static,
+    E::Error: AsTerminationReason + IntoExtError,
+{
+    // Runs instance at `func_name` export, if any.
+    // Value returned by the export is passed to `post_execution_handler`.
+    fn run<F, T>(
+        mut self,
+        func_name: Option<&str>,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<SandboxEnvironmentError>>
+    where
+        F: FnOnce(&MemoryWrap, Option<i32>) -> Result<(), T>,
+        T: fmt::Display,
+    {
+        let res = match func_name {
+            Some(func_name) => self.instance.invoke(func_name, &[], &mut self.runtime),
+            None => Ok(ReturnValue::Unit),
         };
 
         let Runtime {
@@ -248,6 +363,11 @@ where
                     gas_amount,
                 })?;
 
+        let returned = match res {
+            Ok(ReturnValue::Value(Value::I32(value))) => Some(value),
+            _ => None,
+        };
+
         let termination = if res.is_err() {
             let reason = trap_explanation
                 .map(TerminationReason::Trap)
@@ -263,7 +383,7 @@ where
             TerminationReason::Success
         };
 
-        match post_execution_handler(&memory) {
+        match post_execution_handler(&memory, returned) {
             Ok(_) => Ok(BackendReport { termination, info }),
             Err(e) => Err(BackendError {
                 reason: SandboxEnvironmentError::PostExecutionHandler(e.to_string()),
@@ -271,8 +391,4 @@ where
             }),
         }
     }
-
-    fn into_gas_amount(self) -> GasAmount {
-        self.runtime.ext.into_inner().into_gas_amount()
-    }
 }
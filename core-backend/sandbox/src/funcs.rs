@@ -23,7 +23,6 @@ use alloc::{
     string::{FromUtf8Error, String},
     vec,
 };
-use codec::Encode;
 use core::{
     convert::{TryFrom, TryInto},
     fmt,
@@ -35,10 +34,14 @@ use gear_backend_common::{
     funcs, AsTerminationReason, IntoExtInfo, TerminationReason, TrapExplanation,
 };
 use gear_core::{
+    costs::RuntimeCosts,
     env::{Ext, ExtCarrierWithError},
-    ids::{MessageId, ProgramId},
+    ids::{MessageId, ProgramId, ReservationId},
     memory::Memory,
-    message::{HandlePacket, InitPacket, ReplyPacket},
+    message::{
+        EventPayload, EventTopics, HandlePacket, InitPacket, MessageWaitedType, Payload,
+        ProgramEvent, ReplyPacket,
+    },
 };
 use gear_core_errors::MemoryError;
 use sp_sandbox::{HostError, ReturnValue, Value};
@@ -75,6 +78,37 @@ fn wto<E>(memory: &mut impl Memory, ptr: usize, buff: &[u8]) -> Result<(), FuncE
     memory.write(ptr, buff).map_err(FuncError::Memory)
 }
 
+fn get_payload<E>(memory: &impl Memory, ptr: usize, len: usize) -> Result<Payload, FuncError<E>> {
+    // Checked before reading, so oversized buffer is never allocated.
+    if len > Payload::MAX_LEN {
+        return Err(FuncError::PayloadSizeLimitExceeded);
+    }
+
+    funcs::get_vec(memory, ptr, len)?
+        .try_into()
+        .map_err(|_| FuncError::PayloadSizeLimitExceeded)
+}
+
+fn get_event<E>(
+    memory: &impl Memory,
+    topics_ptr: usize,
+    topics_len: usize,
+    payload_ptr: usize,
+    payload_len: usize,
+) -> Result<ProgramEvent, FuncError<E>> {
+    // Checked before reading, so oversized buffers are never allocated.
+    if topics_len > EventTopics::MAX_LEN || payload_len > EventPayload::MAX_LEN {
+        return Err(FuncError::EventSizeLimitExceeded);
+    }
+
+    let topics = (0..topics_len)
+        .map(|i| funcs::get_bytes32(memory, topics_ptr.saturating_add(i * 32)))
+        .collect::<Result<_, _>>()?;
+    let payload = funcs::get_vec(memory, payload_ptr, payload_len)?;
+
+    ProgramEvent::new(topics, payload).map_err(|_| FuncError::EventSizeLimitExceeded)
+}
+
 #[derive(Debug, derive_more::Display)]
 pub enum FuncError<E> {
     #[display(fmt = "{}", _0)]
@@ -91,6 +125,10 @@ pub enum FuncError<E> {
     NoReplyContext,
     #[display(fmt = "Failed to parse debug string: {}", _0)]
     DebugString(FromUtf8Error),
+    #[display(fmt = "Payload size limit exceeded")]
+    PayloadSizeLimitExceeded,
+    #[display(fmt = "Event size limit exceeded")]
+    EventSizeLimitExceeded,
     #[display(fmt = "`gr_error` expects error occurred earlier")]
     SyscallErrorExpected,
     #[display(fmt = "Terminated: {:?}", _0)]
@@ -150,7 +188,7 @@ where
 
         ext.with_fallible(|ext| {
             let dest: ProgramId = funcs::get_bytes32(memory, program_id_ptr)?.into();
-            let payload = funcs::get_vec(memory, payload_ptr, payload_len)?;
+            let payload = get_payload(memory, payload_ptr, payload_len)?;
             let value = funcs::get_u128(memory, value_ptr)?;
             let error_len = ext
                 .send(HandlePacket::new(dest, payload, value))
@@ -182,7 +220,7 @@ where
 
         ext.with_fallible(|ext| {
             let dest: ProgramId = funcs::get_bytes32(memory, program_id_ptr)?.into();
-            let payload = funcs::get_vec(memory, payload_ptr, payload_len)?;
+            let payload = get_payload(memory, payload_ptr, payload_len)?;
             let value = funcs::get_u128(memory, value_ptr)?;
 
             let error_len = ext
@@ -268,6 +306,40 @@ where
         })
     }
 
+    pub fn reservation_send(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let reservation_id_ptr = pop_i32(&mut args)?;
+        let program_id_ptr = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+
+        let Runtime { ext, memory, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let reservation_id: ReservationId =
+                funcs::get_bytes32(memory, reservation_id_ptr)?.into();
+            let dest: ProgramId = funcs::get_bytes32(memory, program_id_ptr)?.into();
+            let payload = get_payload(memory, payload_ptr, payload_len)?;
+            let value = funcs::get_u128(memory, value_ptr)?;
+            let error_len = ext
+                .reservation_send(reservation_id, HandlePacket::new(dest, payload, value))
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    wto(memory, message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        })
+        .map(|code| Value::I32(code as i32).into())
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn send_init(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         let mut args = args.iter();
 
@@ -315,18 +387,64 @@ where
         })
     }
 
+    pub fn send_push_input(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let handle = pop_i32(&mut args)?;
+        let offset = pop_i32(&mut args)?;
+        let len = pop_i32(&mut args)?;
+
+        let Runtime { ext, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let error_len = ext
+                .send_push_input(handle, offset, len)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len();
+            Ok(error_len)
+        })
+        .map(|code| Value::I32(code as i32).into())
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn send_cancel(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let handle = pop_i32(&mut args)?;
+
+        let Runtime { ext, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let error_len = ext
+                .send_cancel(handle)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len();
+            Ok(error_len)
+        })
+        .map(|code| Value::I32(code as i32).into())
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn read(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         let mut args = args.iter();
 
         let at = pop_i32(&mut args)?;
-        let len: usize = pop_i32(&mut args)?;
+        let len = pop_i32(&mut args)?;
         let dest = pop_i32(&mut args)?;
 
         let Runtime { ext, memory, .. } = ctx;
 
         ext.with_fallible(|ext| {
-            let msg = ext.msg().to_vec();
-            wto(memory, dest, &msg[at..(at + len)])
+            let data = ext.read(at, len).map_err(FuncError::Core)?;
+            wto(memory, dest, data)
         })
         .map(|()| ReturnValue::Unit)
         .map_err(|err| {
@@ -336,10 +454,15 @@ where
     }
 
     pub fn size(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
-        ctx.ext
-            .with(|ext| ext.msg().len())
-            .map(return_i32)
-            .unwrap_or_else(|_| return_i32(0))
+        let size = ctx
+            .ext
+            .with_fallible(|ext| ext.size().map_err(FuncError::Core))
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i32(size)
     }
 
     pub fn exit(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -360,15 +483,15 @@ where
     }
 
     pub fn exit_code(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
-        let reply_tuple = ctx
+        let exit_code = ctx
             .ext
-            .with_fallible(|ext| ext.reply_to().map_err(FuncError::Core))
+            .with_fallible(|ext| ext.exit_code().map_err(FuncError::Core))
             .map_err(|e| {
                 ctx.err = e;
                 HostError
             })?;
 
-        if let Some((_, exit_code)) = reply_tuple {
+        if let Some(exit_code) = exit_code {
             return_i32(exit_code)
         } else {
             ctx.err = FuncError::NonReplyExitCode;
@@ -485,7 +608,7 @@ where
         let Runtime { ext, memory, .. } = ctx;
 
         ext.with_fallible(|ext| {
-            let payload = funcs::get_vec(memory, payload_ptr, payload_len)?;
+            let payload = get_payload(memory, payload_ptr, payload_len)?;
             let value = funcs::get_u128(memory, value_ptr)?;
             let error_len = ext
                 .reply(ReplyPacket::new(payload, value))
@@ -515,7 +638,7 @@ where
         let Runtime { ext, memory, .. } = ctx;
 
         ext.with_fallible(|ext| {
-            let payload = funcs::get_vec(memory, payload_ptr, payload_len)?;
+            let payload = get_payload(memory, payload_ptr, payload_len)?;
             let value = funcs::get_u128(memory, value_ptr)?;
             let error_len = ext
                 .reply(ReplyPacket::new_with_gas(payload, gas_limit, value))
@@ -595,7 +718,7 @@ where
 
         let dest = pop_i32(&mut args)?;
 
-        let maybe_message_id = ctx
+        let reply_details = ctx
             .ext
             .with_fallible(|ext| ext.reply_to().map_err(FuncError::Core))
             .map_err(|err| {
@@ -603,8 +726,8 @@ where
                 HostError
             })?;
 
-        if let Some((message_id, _)) = maybe_message_id {
-            wto(&mut ctx.memory, dest, message_id.as_ref()).map_err(|err| {
+        if let Some(details) = reply_details {
+            wto(&mut ctx.memory, dest, details.reply_to().as_ref()).map_err(|err| {
                 ctx.err = err;
                 HostError
             })?;
@@ -640,6 +763,29 @@ where
         })
     }
 
+    pub fn reply_push_input(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let offset = pop_i32(&mut args)?;
+        let len = pop_i32(&mut args)?;
+
+        let Runtime { ext, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let error_len = ext
+                .reply_push_input(offset, len)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len();
+            Ok(error_len)
+        })
+        .map(|code| Value::I32(code as i32).into())
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn debug(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         let mut args = args.iter();
 
@@ -662,6 +808,46 @@ where
         })
     }
 
+    pub fn metric_increment(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let key_ptr = pop_i32(&mut args)?;
+        let value = pop_i64(&mut args)?;
+
+        let Runtime { ext, memory, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let key = funcs::get_bytes32(memory, key_ptr)?;
+            ext.metric_increment(key, value).map_err(FuncError::Core)
+        })
+        .map(|()| ReturnValue::Unit)
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn emit_event(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let topics_ptr = pop_i32(&mut args)?;
+        let topics_len = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+
+        let Runtime { ext, memory, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let event = get_event(memory, topics_ptr, topics_len, payload_ptr, payload_len)?;
+            ext.emit_event(event).map_err(FuncError::Core)
+        })
+        .map(|()| ReturnValue::Unit)
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn gas_available(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
         let gas_available = ctx
             .ext
@@ -671,6 +857,75 @@ where
         return_i64(gas_available)
     }
 
+    pub fn reserve_gas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let amount = pop_i64(&mut args)?;
+        let duration = pop_i32(&mut args)?;
+        let id_ptr = pop_i32(&mut args)?;
+
+        let Runtime { ext, memory, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let error_len = ext
+                .reserve_gas(amount, duration)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|id| wto(memory, id_ptr, id.as_ref()))?;
+            Ok(error_len)
+        })
+        .map(|code| Value::I32(code as i32).into())
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn unreserve_gas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let id_ptr = pop_i32(&mut args)?;
+        let amount_ptr = pop_i32(&mut args)?;
+
+        let Runtime { ext, memory, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let id: ReservationId = funcs::get_bytes32(memory, id_ptr)?.into();
+            let error_len = ext
+                .unreserve_gas(id)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|amount| wto(memory, amount_ptr, &amount.to_le_bytes()))?;
+            Ok(error_len)
+        })
+        .map(|code| Value::I32(code as i32).into())
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn system_reserve_gas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let amount = pop_i64(&mut args)?;
+
+        ctx.ext
+            .with_fallible(|ext| {
+                let error_len = ext
+                    .system_reserve_gas(amount)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len();
+                Ok(error_len)
+            })
+            .map(|code| Value::I32(code as i32).into())
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })
+    }
+
     pub fn msg_id(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         let mut args = args.iter();
 
@@ -775,7 +1030,42 @@ where
             .ext
             .with_fallible(|ext| ext.wait().map_err(FuncError::Core))
             .err()
-            .unwrap_or(FuncError::Terminated(TerminationReason::Wait));
+            .unwrap_or(FuncError::Terminated(TerminationReason::Wait(
+                None,
+                MessageWaitedType::Wait,
+            )));
+        Err(HostError)
+    }
+
+    pub fn wait_for(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let duration = pop_i32(&mut args)?;
+
+        ctx.err = ctx
+            .ext
+            .with_fallible(|ext| ext.wait_for(duration).map_err(FuncError::Core))
+            .err()
+            .unwrap_or(FuncError::Terminated(TerminationReason::Wait(
+                Some(duration),
+                MessageWaitedType::WaitFor,
+            )));
+        Err(HostError)
+    }
+
+    pub fn wait_up_to(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let duration = pop_i32(&mut args)?;
+
+        ctx.err = ctx
+            .ext
+            .with_fallible(|ext| ext.wait_up_to(duration).map_err(FuncError::Core))
+            .err()
+            .unwrap_or(FuncError::Terminated(TerminationReason::Wait(
+                Some(duration),
+                MessageWaitedType::WaitUpTo,
+            )));
         Err(HostError)
     }
 
@@ -788,9 +1078,38 @@ where
 
         ext.with_fallible(|ext| {
             let waker_id: MessageId = funcs::get_bytes32(memory, waker_id_ptr)?.into();
-            ext.wake(waker_id).map_err(FuncError::Core)
+            let error_len = ext
+                .wake(waker_id, 0)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len();
+            Ok(error_len)
+        })
+        .map(|code| Value::I32(code as i32).into())
+        .map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn wake_delayed(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let mut args = args.iter();
+
+        let waker_id_ptr = pop_i32(&mut args)?;
+        let delay = pop_i32(&mut args)?;
+
+        let Runtime { ext, memory, .. } = ctx;
+
+        ext.with_fallible(|ext| {
+            let waker_id: MessageId = funcs::get_bytes32(memory, waker_id_ptr)?.into();
+            let error_len = ext
+                .wake(waker_id, delay)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len();
+            Ok(error_len)
         })
-        .map(|_| ReturnValue::Unit)
+        .map(|code| Value::I32(code as i32).into())
         .map_err(|err| {
             ctx.err = err;
             HostError
@@ -806,6 +1125,7 @@ where
         let payload_ptr = pop_i32(&mut args)?;
         let payload_len = pop_i32(&mut args)?;
         let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
         let program_id_ptr = pop_i32(&mut args)?;
 
         let Runtime { ext, memory, .. } = ctx;
@@ -813,13 +1133,14 @@ where
         ext.with_fallible(|ext: &mut E| {
             let code_hash = funcs::get_bytes32(memory, code_hash_ptr)?;
             let salt = funcs::get_vec(memory, salt_ptr, salt_len)?;
-            let payload = funcs::get_vec(memory, payload_ptr, payload_len)?;
+            let payload = get_payload(memory, payload_ptr, payload_len)?;
             let value = funcs::get_u128(memory, value_ptr)?;
             let error_len = ext
                 .create_program(InitPacket::new(code_hash.into(), salt, payload, value))
                 .process_error()
                 .map_err(FuncError::Core)?
-                .error_len_on_success(|new_actor_id| {
+                .error_len_on_success(|(init_message_id, new_actor_id)| {
+                    wto(memory, message_id_ptr, init_message_id.as_ref())?;
                     wto(memory, program_id_ptr, new_actor_id.as_ref())
                 })?;
             Ok(error_len)
@@ -841,6 +1162,7 @@ where
         let payload_len = pop_i32(&mut args)?;
         let gas_limit = pop_i64(&mut args)?;
         let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
         let program_id_ptr = pop_i32(&mut args)?;
 
         let Runtime { ext, memory, .. } = ctx;
@@ -848,7 +1170,7 @@ where
         ext.with_fallible(|ext| {
             let code_hash = funcs::get_bytes32(memory, code_hash_ptr)?;
             let salt = funcs::get_vec(memory, salt_ptr, salt_len)?;
-            let payload = funcs::get_vec(memory, payload_ptr, payload_len)?;
+            let payload = get_payload(memory, payload_ptr, payload_len)?;
             let value = funcs::get_u128(memory, value_ptr)?;
             let error_len = ext
                 .create_program(InitPacket::new_with_gas(
@@ -860,7 +1182,8 @@ where
                 ))
                 .process_error()
                 .map_err(FuncError::Core)?
-                .error_len_on_success(|new_actor_id| {
+                .error_len_on_success(|(init_message_id, new_actor_id)| {
+                    wto(memory, message_id_ptr, init_message_id.as_ref())?;
                     wto(memory, program_id_ptr, new_actor_id.as_ref())
                 })?;
             Ok(error_len)
@@ -880,8 +1203,10 @@ where
         let Runtime { ext, memory, .. } = ctx;
 
         ext.with_fallible(|ext| {
+            ext.charge_gas_runtime(RuntimeCosts::Error)
+                .map_err(FuncError::Core)?;
             let err = ext.last_error().ok_or(FuncError::SyscallErrorExpected)?;
-            let err = err.encode();
+            let err = err.encode_with_code();
             wto(memory, data_ptr, &err)?;
             Ok(())
         })
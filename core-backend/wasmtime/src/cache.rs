@@ -0,0 +1,60 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Cache of compiled modules.
+//!
+//! Compilation dominates the setup of the environment, so each code is
+//! compiled once and then instantiated for every dispatch with a fresh
+//! store and memory. Modules are kept until [`clear`] is called, which is
+//! expected at the end of the queue processing.
+
+use alloc::collections::BTreeMap;
+use core::cell::RefCell;
+use gear_core::ids::CodeId;
+use wasmtime::{Engine, Module};
+
+std::thread_local! {
+    static ENGINE: Engine = Engine::default();
+    // Keyed by hash of the instrumented binary rather than by id of the
+    // original code, so re-instrumented code doesn't hit a stale module.
+    static MODULES: RefCell<BTreeMap<CodeId, Module>> = RefCell::new(BTreeMap::new());
+}
+
+/// Returns engine, which all the cached modules are compiled with.
+pub(crate) fn engine() -> Engine {
+    ENGINE.with(Clone::clone)
+}
+
+/// Returns module compiled from the binary, compiling it on cache miss.
+pub(crate) fn module(binary: &[u8]) -> anyhow::Result<Module> {
+    let key = CodeId::generate(binary);
+
+    if let Some(module) = MODULES.with(|modules| modules.borrow().get(&key).cloned()) {
+        return Ok(module);
+    }
+
+    let module = Module::new(&engine(), binary)?;
+    MODULES.with(|modules| modules.borrow_mut().insert(key, module.clone()));
+
+    Ok(module)
+}
+
+/// Drops all the cached modules.
+pub fn clear() {
+    MODULES.with(|modules| modules.borrow_mut().clear());
+}
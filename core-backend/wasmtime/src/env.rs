@@ -20,10 +20,12 @@
 
 use core::fmt;
 
-use crate::{funcs_tree, memory::MemoryWrapExternal};
+use crate::{cache, funcs_tree, memory::MemoryWrapExternal};
 use alloc::{
     collections::BTreeSet,
+    format,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
 use gear_backend_common::{
@@ -31,13 +33,14 @@ use gear_backend_common::{
     ExtInfo, IntoExtInfo, TerminationReason, TrapExplanation,
 };
 use gear_core::{
+    code::ROUTE_EXPORT_PREFIX,
     env::{ClonedExtCarrier, Ext, ExtCarrier},
     gas::GasAmount,
     memory::WasmPageNumber,
     message::DispatchKind,
 };
 use gear_core_errors::MemoryError;
-use wasmtime::{Engine, Extern, Instance, Memory as WasmtimeMemory, MemoryType, Module, Store};
+use wasmtime::{Extern, ExternType, Instance, Memory as WasmtimeMemory, MemoryType, Store, Val};
 
 /// Data type in wasmtime store
 pub struct StoreData<E: Ext> {
@@ -51,6 +54,8 @@ pub enum WasmtimeEnvironmentError {
     NonEnvImport(Option<String>),
     #[display(fmt = "Function {:?} definition wasn't found", _0)]
     MissingImport(Option<String>),
+    #[display(fmt = "Module must import the only memory, but {} found", _0)]
+    InvalidMemoriesAmount(usize),
     #[display(fmt = "Unable to create module: {}", _0)]
     ModuleCreation(anyhow::Error),
     #[display(fmt = "Unable to create instance: {}", _0)]
@@ -91,7 +96,7 @@ where
         let forbidden_funcs = ext.forbidden_funcs().clone();
         let ext_carrier = ExtCarrier::new(ext);
 
-        let engine = Engine::default();
+        let engine = cache::engine();
         let store_data = StoreData {
             ext: ext_carrier.cloned(),
             termination_reason: TerminationReason::Success,
@@ -110,7 +115,7 @@ where
         };
 
         let funcs = funcs_tree::build(&mut store, memory, Some(forbidden_funcs));
-        let module = match Module::new(&engine, binary) {
+        let module = match cache::module(binary) {
             Ok(module) => module,
             Err(e) => {
                 return Err(BackendError {
@@ -120,6 +125,20 @@ where
             }
         };
 
+        // Code is checked to import the only memory on its acceptance, this is
+        // just a guard against engines, which handle multiple memories.
+        let memories = module
+            .imports()
+            .filter(|import| matches!(import.ty(), ExternType::Memory(_)))
+            .count();
+
+        if memories != 1 {
+            return Err(BackendError {
+                reason: WasmtimeEnvironmentError::InvalidMemoriesAmount(memories),
+                gas_amount: ext_carrier.into_inner().into_gas_amount(),
+            });
+        }
+
         let mut imports = Vec::with_capacity(module.imports().len());
         for import in module.imports() {
             if import.module() != "env" {
@@ -200,13 +219,68 @@ where
     }
 
     fn execute<F, T>(
-        mut self,
+        self,
         entry_point: &DispatchKind,
+        route: Option<&str>,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<Self::Error>>
+    where
+        F: FnOnce(&Self::Memory) -> Result<(), T>,
+        T: fmt::Display,
+    {
+        let func_name = if let Some(route) = route {
+            format!("{}{}", ROUTE_EXPORT_PREFIX, route)
+        } else {
+            entry_point.into_entry().to_string()
+        };
+
+        self.run(&func_name, |mem, _| post_execution_handler(mem))
+    }
+
+    fn execute_export<F, T>(
+        self,
+        export: &str,
         post_execution_handler: F,
     ) -> Result<BackendReport, BackendError<Self::Error>>
     where
         F: FnOnce(&Self::Memory) -> Result<(), T>,
         T: fmt::Display,
+    {
+        self.run(export, |mem, _| post_execution_handler(mem))
+    }
+
+    fn execute_for_result<F, T>(
+        self,
+        export: &str,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<Self::Error>>
+    where
+        F: FnOnce(&Self::Memory, Option<i32>) -> Result<(), T>,
+        T: fmt::Display,
+    {
+        self.run(export, post_execution_handler)
+    }
+
+    fn into_gas_amount(self) -> GasAmount {
+        self.ext.into_inner().into_gas_amount()
+    }
+}
+
+impl<E> WasmtimeEnvironment<E>
+where
+    E: Ext + IntoExtInfo,
+    E::Error: AsTerminationReason + IntoExtError,
+{
+    // Runs instance at `func_name` export. Missing export means empty function.
+    // Value returned by the export is passed to `post_execution_handler`.
+    fn run<F, T>(
+        mut self,
+        func_name: &str,
+        post_execution_handler: F,
+    ) -> Result<BackendReport, BackendError<WasmtimeEnvironmentError>>
+    where
+        F: FnOnce(&MemoryWrapExternal<E>, Option<i32>) -> Result<(), T>,
+        T: fmt::Display,
     {
         struct PreparedInfo<E: Ext> {
             info: ExtInfo,
@@ -216,9 +290,9 @@ where
 
         let func = self
             .instance
-            .get_func(&mut self.memory_wrap.store, entry_point.into_entry());
+            .get_func(&mut self.memory_wrap.store, func_name);
 
-        let prepare_info = |this: Self| -> Result<PreparedInfo<E>, BackendError<Self::Error>> {
+        let prepare_info = |this: Self| -> Result<PreparedInfo<E>, BackendError<_>> {
             let WasmtimeEnvironment {
                 ext, memory_wrap, ..
             } = this;
@@ -246,7 +320,7 @@ where
             } = prepare_info(self)?;
 
             // Entry function not found, so we mean this as empty function
-            return match post_execution_handler(&memory_wrap) {
+            return match post_execution_handler(&memory_wrap, None) {
                 Ok(_) => Ok(BackendReport {
                     termination: TerminationReason::Success,
                     info,
@@ -258,9 +332,14 @@ where
             };
         };
 
-        let res = entry_func.call(&mut self.memory_wrap.store, &[], &mut []);
+        let results_len = entry_func.ty(&self.memory_wrap.store).results().len();
+        let mut results = vec![Val::I32(0); results_len];
+
+        let res = entry_func.call(&mut self.memory_wrap.store, &[], &mut results);
         log::debug!("execution result: {:?}", res);
 
+        let returned = results.first().and_then(Val::i32);
+
         let termination_reason = self.memory_wrap.store.data().termination_reason.clone();
 
         let PreparedInfo {
@@ -284,7 +363,7 @@ where
             TerminationReason::Success
         };
 
-        match post_execution_handler(&memory_wrap) {
+        match post_execution_handler(&memory_wrap, returned) {
             Ok(_) => Ok(BackendReport { termination, info }),
             Err(e) => Err(BackendError {
                 reason: WasmtimeEnvironmentError::PostExecutionHandler(e.to_string()),
@@ -292,8 +371,4 @@ where
             }),
         }
     }
-
-    fn into_gas_amount(self) -> GasAmount {
-        self.ext.into_inner().into_gas_amount()
-    }
 }
@@ -16,24 +16,27 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use core::marker::PhantomData;
+use core::{convert::TryInto, marker::PhantomData};
 
 use crate::{env::StoreData, memory::MemoryWrap};
 use alloc::{
     string::{FromUtf8Error, String, ToString},
     vec,
 };
-use codec::Encode;
 use gear_backend_common::{
     error_processor::{IntoExtError, ProcessError},
     funcs::*,
     AsTerminationReason, IntoExtInfo, TerminationReason, TrapExplanation,
 };
 use gear_core::{
+    costs::RuntimeCosts,
     env::{Ext, ExtCarrierWithError},
-    ids::{MessageId, ProgramId},
+    ids::{MessageId, ProgramId, ReservationId},
     memory::Memory,
-    message::{HandlePacket, InitPacket, ReplyPacket},
+    message::{
+        EventPayload, EventTopics, HandlePacket, InitPacket, MessageWaitedType, Payload,
+        ProgramEvent, ReplyPacket,
+    },
 };
 use gear_core_errors::{CoreError, MemoryError};
 use wasmtime::{AsContextMut, Caller, Func, Memory as WasmtimeMemory, Store, Trap};
@@ -54,6 +57,10 @@ enum FuncError<E> {
     LaterExtWith(ExtCarrierWithError),
     #[display(fmt = "Failed to parse debug string: {}", _0)]
     DebugString(FromUtf8Error),
+    #[display(fmt = "Payload size limit exceeded")]
+    PayloadSizeLimitExceeded,
+    #[display(fmt = "Event size limit exceeded")]
+    EventSizeLimitExceeded,
     #[display(fmt = "Not running in the reply context")]
     NoReplyContext,
     #[display(fmt = "`gr_exit` has been called")]
@@ -107,6 +114,37 @@ fn get_caller_memory<'a, T: Ext>(
     MemoryWrap { mem: *mem, store }
 }
 
+fn get_payload<E>(mem: &impl Memory, ptr: usize, len: usize) -> Result<Payload, FuncError<E>> {
+    // Checked before reading, so oversized buffer is never allocated.
+    if len > Payload::MAX_LEN {
+        return Err(FuncError::PayloadSizeLimitExceeded);
+    }
+
+    get_vec(mem, ptr, len)?
+        .try_into()
+        .map_err(|_| FuncError::PayloadSizeLimitExceeded)
+}
+
+fn get_event<E>(
+    mem: &impl Memory,
+    topics_ptr: usize,
+    topics_len: usize,
+    payload_ptr: usize,
+    payload_len: usize,
+) -> Result<ProgramEvent, FuncError<E>> {
+    // Checked before reading, so oversized buffers are never allocated.
+    if topics_len > EventTopics::MAX_LEN || payload_len > EventPayload::MAX_LEN {
+        return Err(FuncError::EventSizeLimitExceeded);
+    }
+
+    let topics = (0..topics_len)
+        .map(|i| get_bytes32(mem, topics_ptr.saturating_add(i * 32)))
+        .collect::<Result<_, _>>()?;
+    let payload = get_vec(mem, payload_ptr, payload_len)?;
+
+    ProgramEvent::new(topics, payload).map_err(|_| FuncError::EventSizeLimitExceeded)
+}
+
 fn write_to_caller_memory<'a, T: Ext>(
     caller: &'a mut Caller<'_, StoreData<T>>,
     mem: &WasmtimeMemory,
@@ -160,9 +198,8 @@ where
     pub fn exit_code(store: &mut Store<StoreData<E>>) -> Func {
         let f = move |caller: Caller<'_, StoreData<E>>| {
             let ext = &caller.data().ext;
-            ext.with_fallible(|ext| ext.reply_to().map_err(FuncError::Core))
+            ext.with_fallible(|ext| ext.exit_code().map_err(FuncError::Core))
                 .and_then(|v| v.ok_or(FuncError::NoReplyContext))
-                .map(|(_, exit_code)| exit_code)
                 .map_err(Trap::new)
         };
         Func::wrap(store, f)
@@ -203,6 +240,41 @@ where
         Func::wrap(store, f)
     }
 
+    pub fn metric_increment(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
+        let func = move |mut caller: Caller<'_, StoreData<E>>, key_ptr: i32, value: i64| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<(), FuncError<E::Error>> {
+                let key = get_bytes32(&get_caller_memory(&mut caller, &mem), key_ptr as usize)?;
+                ext.metric_increment(key, value as u64)
+                    .map_err(FuncError::Core)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
+    pub fn emit_event(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
+        let func = move |mut caller: Caller<'_, StoreData<E>>,
+                         topics_ptr: i32,
+                         topics_len: i32,
+                         payload_ptr: i32,
+                         payload_len: i32| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<(), FuncError<E::Error>> {
+                let event = get_event(
+                    &get_caller_memory(&mut caller, &mem),
+                    topics_ptr as usize,
+                    topics_len as usize,
+                    payload_ptr as usize,
+                    payload_len as usize,
+                )?;
+                ext.emit_event(event).map_err(FuncError::Core)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
     pub fn gas(store: &mut Store<StoreData<E>>) -> Func {
         let func = move |mut caller: Caller<'_, StoreData<E>>, val: i32| {
             let ext = &caller.data().ext;
@@ -231,6 +303,66 @@ where
         Func::wrap(store, func)
     }
 
+    pub fn reserve_gas(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
+        let func =
+            move |mut caller: Caller<'_, StoreData<E>>, amount: i64, duration: i32, id_ptr: i32| {
+                let ext = caller.data().ext.clone();
+                ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                    let error_len = ext
+                        .reserve_gas(amount as u64, duration as u32)
+                        .process_error()
+                        .map_err(FuncError::Core)?
+                        .error_len_on_success(|id| {
+                            write_to_caller_memory(&mut caller, &mem, id_ptr as _, id.as_ref())
+                        })?;
+                    Ok(error_len)
+                })
+                .map_err(Trap::new)
+            };
+        Func::wrap(store, func)
+    }
+
+    pub fn unreserve_gas(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
+        let func = move |mut caller: Caller<'_, StoreData<E>>, id_ptr: i32, amount_ptr: i32| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                let id: ReservationId =
+                    get_bytes32(&get_caller_memory(&mut caller, &mem), id_ptr as usize)?.into();
+                let error_len = ext
+                    .unreserve_gas(id)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len_on_success(|amount| {
+                        write_to_caller_memory(
+                            &mut caller,
+                            &mem,
+                            amount_ptr as _,
+                            &amount.to_le_bytes(),
+                        )
+                    })?;
+                Ok(error_len)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
+    pub fn system_reserve_gas(store: &mut Store<StoreData<E>>) -> Func {
+        let func = move |caller: Caller<'_, StoreData<E>>, amount: i64| {
+            let ext = &caller.data().ext;
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                let error_len = ext
+                    .system_reserve_gas(amount as u64)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len();
+                Ok(error_len)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
     pub fn exit(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
         let func =
             move |mut caller: Caller<'_, StoreData<E>>, program_id_ptr: i32| -> Result<(), Trap> {
@@ -286,11 +418,9 @@ where
     pub fn read(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
         let func = move |mut caller: Caller<'_, StoreData<E>>, at: i32, len: i32, dest: i32| {
             let ext = caller.data().ext.clone();
-            let at = at as u32 as usize;
-            let len = len as u32 as usize;
             ext.with_fallible(|ext| -> Result<_, FuncError<E::Error>> {
-                let msg = ext.msg().to_vec();
-                write_to_caller_memory(&mut caller, &mem, dest as _, &msg[at..(at + len)])
+                let data = ext.read(at as u32, len as u32).map_err(FuncError::Core)?;
+                write_to_caller_memory(&mut caller, &mem, dest as _, data)
             })
             .map_err(Trap::new)
         };
@@ -306,7 +436,7 @@ where
             let ext = caller.data().ext.clone();
             ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
                 let mem_wrap = get_caller_memory(&mut caller, &mem);
-                let payload = get_vec(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
+                let payload = get_payload(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
                 let value = get_u128(&mem_wrap, value_ptr as usize)?;
 
                 let error_len = ext
@@ -338,7 +468,7 @@ where
             let ext = caller.data().ext.clone();
             ext.with_fallible(|ext| -> Result<_, FuncError<E::Error>> {
                 let mem_wrap = get_caller_memory(&mut caller, &mem);
-                let payload = get_vec(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
+                let payload = get_payload(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
                 let value = get_u128(&mem_wrap, value_ptr as usize)?;
 
                 let error_len = ext
@@ -437,13 +567,34 @@ where
         Func::wrap(store, func)
     }
 
+    pub fn reply_push_input(store: &mut Store<StoreData<E>>) -> Func {
+        let func = move |caller: Caller<'_, StoreData<E>>, offset: i32, len: i32| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                let error_len = ext
+                    .reply_push_input(offset as _, len as _)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len();
+                Ok(error_len)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
     pub fn reply_to(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
         let func = move |mut caller: Caller<'_, StoreData<E>>, dest: i32| {
             let ext = &caller.data().ext;
             ext.with_fallible(|ext| ext.reply_to().map_err(FuncError::Core))
                 .and_then(|v| v.ok_or(FuncError::NoReplyContext))
-                .and_then(|(msg_id, _)| {
-                    write_to_caller_memory(&mut caller, &mem, dest as isize as _, msg_id.as_ref())
+                .and_then(|details| {
+                    write_to_caller_memory(
+                        &mut caller,
+                        &mem,
+                        dest as isize as _,
+                        details.reply_to().as_ref(),
+                    )
                 })
                 .map_err(Trap::new)
         };
@@ -461,7 +612,7 @@ where
             ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
                 let mem_wrap = get_caller_memory(&mut caller, &mem);
                 let dest: ProgramId = get_bytes32(&mem_wrap, program_id_ptr as usize)?.into();
-                let payload = get_vec(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
+                let payload = get_payload(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
                 let value = get_u128(&mem_wrap, value_ptr as usize)?;
                 let error_len = ext
                     .send(HandlePacket::new(dest, payload, value))
@@ -494,7 +645,7 @@ where
             ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
                 let mem_wrap = get_caller_memory(&mut caller, &mem);
                 let dest: ProgramId = get_bytes32(&mem_wrap, program_id_ptr as usize)?.into();
-                let payload = get_vec(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
+                let payload = get_payload(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
                 let value = get_u128(&mem_wrap, value_ptr as usize)?;
 
                 let error_len = ext
@@ -588,6 +739,41 @@ where
         Func::wrap(store, func)
     }
 
+    pub fn reservation_send(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
+        let func = move |mut caller: Caller<'_, StoreData<E>>,
+                         reservation_id_ptr: i32,
+                         program_id_ptr: i32,
+                         payload_ptr: i32,
+                         payload_len: i32,
+                         value_ptr: i32,
+                         message_id_ptr: i32| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                let mem_wrap = get_caller_memory(&mut caller, &mem);
+                let reservation_id: ReservationId =
+                    get_bytes32(&mem_wrap, reservation_id_ptr as usize)?.into();
+                let dest: ProgramId = get_bytes32(&mem_wrap, program_id_ptr as usize)?.into();
+                let payload = get_payload(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
+                let value = get_u128(&mem_wrap, value_ptr as usize)?;
+                let error_len = ext
+                    .reservation_send(reservation_id, HandlePacket::new(dest, payload, value))
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len_on_success(|message_id| {
+                        write_to_caller_memory(
+                            &mut caller,
+                            &mem,
+                            message_id_ptr as isize as _,
+                            message_id.as_ref(),
+                        )
+                    })?;
+                Ok(error_len)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
     pub fn send_init(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
         let func = move |mut caller: Caller<'_, StoreData<E>>, handle_ptr: i32| {
             let ext = caller.data().ext.clone();
@@ -632,6 +818,38 @@ where
         Func::wrap(store, func)
     }
 
+    pub fn send_push_input(store: &mut Store<StoreData<E>>) -> Func {
+        let func = move |caller: Caller<'_, StoreData<E>>, handle: i32, offset: i32, len: i32| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                let error_len = ext
+                    .send_push_input(handle as _, offset as _, len as _)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len();
+                Ok(error_len)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
+    pub fn send_cancel(store: &mut Store<StoreData<E>>) -> Func {
+        let func = move |caller: Caller<'_, StoreData<E>>, handle: i32| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                let error_len = ext
+                    .send_cancel(handle as _)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len();
+                Ok(error_len)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
     pub fn create_program(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
         let func = move |mut caller: Caller<'_, StoreData<E>>,
                          code_hash_ptr: i32,
@@ -640,19 +858,26 @@ where
                          payload_ptr: i32,
                          payload_len: i32,
                          value_ptr: i32,
+                         message_id_ptr: i32,
                          program_id_ptr: i32| {
             let ext = caller.data().ext.clone();
             ext.with_fallible(|ext: &mut E| -> Result<u32, FuncError<E::Error>> {
                 let mem_wrap = get_caller_memory(&mut caller, &mem);
                 let code_hash = get_bytes32(&mem_wrap, code_hash_ptr as usize)?;
                 let salt = get_vec(&mem_wrap, salt_ptr as usize, salt_len as usize)?;
-                let payload = get_vec(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
+                let payload = get_payload(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
                 let value = get_u128(&mem_wrap, value_ptr as usize)?;
                 let error_len = ext
                     .create_program(InitPacket::new(code_hash.into(), salt, payload, value))
                     .process_error()
                     .map_err(FuncError::Core)?
-                    .error_len_on_success(|new_actor_id| {
+                    .error_len_on_success(|(init_message_id, new_actor_id)| {
+                        write_to_caller_memory(
+                            &mut caller,
+                            &mem,
+                            message_id_ptr as isize as _,
+                            init_message_id.as_ref(),
+                        )?;
                         write_to_caller_memory(
                             &mut caller,
                             &mem,
@@ -676,13 +901,14 @@ where
                          payload_len: i32,
                          gas_limit: i64,
                          value_ptr: i32,
+                         message_id_ptr: i32,
                          program_id_ptr: i32| {
             let ext = caller.data().ext.clone();
             ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
                 let mem_wrap = get_caller_memory(&mut caller, &mem);
                 let code_hash = get_bytes32(&mem_wrap, code_hash_ptr as usize)?;
                 let salt = get_vec(&mem_wrap, salt_ptr as usize, salt_len as usize)?;
-                let payload = get_vec(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
+                let payload = get_payload(&mem_wrap, payload_ptr as usize, payload_len as usize)?;
                 let value = get_u128(&mem_wrap, value_ptr as usize)?;
                 let error_len = ext
                     .create_program(InitPacket::new_with_gas(
@@ -694,7 +920,13 @@ where
                     ))
                     .process_error()
                     .map_err(FuncError::Core)?
-                    .error_len_on_success(|new_actor_id| {
+                    .error_len_on_success(|(init_message_id, new_actor_id)| {
+                        write_to_caller_memory(
+                            &mut caller,
+                            &mem,
+                            message_id_ptr as isize as _,
+                            init_message_id.as_ref(),
+                        )?;
                         write_to_caller_memory(
                             &mut caller,
                             &mem,
@@ -712,7 +944,9 @@ where
     pub fn size(store: &mut Store<StoreData<E>>) -> Func {
         let func = move |caller: Caller<'_, StoreData<E>>| {
             let ext = &caller.data().ext;
-            ext.with(|ext| ext.msg().len() as _).unwrap_or(0)
+            ext.with_fallible(|ext| ext.size().map_err(FuncError::Core))
+                .map(|size| size as i32)
+                .map_err(Trap::new)
         };
         Func::wrap(store, func)
     }
@@ -791,7 +1025,8 @@ where
                 if let Err(err) = ext.with_fallible(|ext| ext.wait().map_err(FuncError::Core)) {
                     Trap::new(err)
                 } else {
-                    caller.data_mut().termination_reason = TerminationReason::Wait;
+                    caller.data_mut().termination_reason =
+                        TerminationReason::Wait(None, MessageWaitedType::Wait);
                     Trap::new(FuncError::<E::Error>::Wait)
                 };
             // Intentionally return an error to break the execution
@@ -800,13 +1035,74 @@ where
         Func::wrap(store, func)
     }
 
+    pub fn wait_for(store: &mut Store<StoreData<E>>) -> Func {
+        let func = move |mut caller: Caller<'_, StoreData<E>>, duration: i32| -> Result<(), Trap> {
+            let ext = &caller.data().ext;
+            let duration = duration as u32;
+            let trap = if let Err(err) =
+                ext.with_fallible(|ext| ext.wait_for(duration).map_err(FuncError::Core))
+            {
+                Trap::new(err)
+            } else {
+                caller.data_mut().termination_reason =
+                    TerminationReason::Wait(Some(duration), MessageWaitedType::WaitFor);
+                Trap::new(FuncError::<E::Error>::Wait)
+            };
+            // Intentionally return an error to break the execution
+            Err(trap)
+        };
+        Func::wrap(store, func)
+    }
+
+    pub fn wait_up_to(store: &mut Store<StoreData<E>>) -> Func {
+        let func = move |mut caller: Caller<'_, StoreData<E>>, duration: i32| -> Result<(), Trap> {
+            let ext = &caller.data().ext;
+            let duration = duration as u32;
+            let trap = if let Err(err) =
+                ext.with_fallible(|ext| ext.wait_up_to(duration).map_err(FuncError::Core))
+            {
+                Trap::new(err)
+            } else {
+                caller.data_mut().termination_reason =
+                    TerminationReason::Wait(Some(duration), MessageWaitedType::WaitUpTo);
+                Trap::new(FuncError::<E::Error>::Wait)
+            };
+            // Intentionally return an error to break the execution
+            Err(trap)
+        };
+        Func::wrap(store, func)
+    }
+
     pub fn wake(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
         let func = move |mut caller: Caller<'_, StoreData<E>>, waker_id_ptr: i32| {
             let ext = caller.data().ext.clone();
-            ext.with_fallible(|ext| -> Result<_, FuncError<E::Error>> {
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
                 let mem_wrap = get_caller_memory(&mut caller, &mem);
                 let waker_id: MessageId = get_bytes32(&mem_wrap, waker_id_ptr as usize)?.into();
-                ext.wake(waker_id).map_err(FuncError::Core)
+                let error_len = ext
+                    .wake(waker_id, 0)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len();
+                Ok(error_len)
+            })
+            .map_err(Trap::new)
+        };
+        Func::wrap(store, func)
+    }
+
+    pub fn wake_delayed(store: &mut Store<StoreData<E>>, mem: WasmtimeMemory) -> Func {
+        let func = move |mut caller: Caller<'_, StoreData<E>>, waker_id_ptr: i32, delay: i32| {
+            let ext = caller.data().ext.clone();
+            ext.with_fallible(|ext| -> Result<u32, FuncError<E::Error>> {
+                let mem_wrap = get_caller_memory(&mut caller, &mem);
+                let waker_id: MessageId = get_bytes32(&mem_wrap, waker_id_ptr as usize)?.into();
+                let error_len = ext
+                    .wake(waker_id, delay as u32)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len();
+                Ok(error_len)
             })
             .map_err(Trap::new)
         };
@@ -817,9 +1113,11 @@ where
         let func = move |mut caller: Caller<'_, StoreData<E>>, data_ptr: u32| {
             let ext = caller.data().ext.clone();
             ext.with_fallible(|ext| -> Result<(), FuncError<E::Error>> {
+                ext.charge_gas_runtime(RuntimeCosts::Error)
+                    .map_err(FuncError::Core)?;
                 let mut mem_wrap = get_caller_memory(&mut caller, &mem);
                 let err = ext.last_error().ok_or(FuncError::SyscallErrorExpected)?;
-                let err = err.encode();
+                let err = err.encode_with_code();
                 mem_wrap.write(data_ptr as usize, &err)?;
                 Ok(())
             })
@@ -30,6 +30,11 @@ where
         ("gr_exit_code", FuncsHandler::exit_code(store)),
         ("gr_gas_available", FuncsHandler::gas_available(store)),
         ("gr_debug", FuncsHandler::debug(store, memory)),
+        ("gr_emit_event", FuncsHandler::emit_event(store, memory)),
+        (
+            "gr_metric_increment",
+            FuncsHandler::metric_increment(store, memory),
+        ),
         ("gr_exit", FuncsHandler::exit(store, memory)),
         ("gr_origin", FuncsHandler::origin(store, memory)),
         ("gr_msg_id", FuncsHandler::msg_id(store, memory)),
@@ -43,18 +48,34 @@ where
             FuncsHandler::reply_commit_wgas(store, memory),
         ),
         ("gr_reply_push", FuncsHandler::reply_push(store, memory)),
+        ("gr_reply_push_input", FuncsHandler::reply_push_input(store)),
         ("gr_reply_to", FuncsHandler::reply_to(store, memory)),
+        (
+            "gr_reservation_send",
+            FuncsHandler::reservation_send(store, memory),
+        ),
+        ("gr_reserve_gas", FuncsHandler::reserve_gas(store, memory)),
         ("gr_send_wgas", FuncsHandler::send_wgas(store, memory)),
         ("gr_send", FuncsHandler::send(store, memory)),
         (
             "gr_send_commit_wgas",
             FuncsHandler::send_commit_wgas(store, memory),
         ),
+        ("gr_send_cancel", FuncsHandler::send_cancel(store)),
         ("gr_send_commit", FuncsHandler::send_commit(store, memory)),
         ("gr_send_init", FuncsHandler::send_init(store, memory)),
         ("gr_send_push", FuncsHandler::send_push(store, memory)),
+        ("gr_send_push_input", FuncsHandler::send_push_input(store)),
         ("gr_size", FuncsHandler::size(store)),
         ("gr_source", FuncsHandler::source(store, memory)),
+        (
+            "gr_system_reserve_gas",
+            FuncsHandler::system_reserve_gas(store),
+        ),
+        (
+            "gr_unreserve_gas",
+            FuncsHandler::unreserve_gas(store, memory),
+        ),
         ("gr_value", FuncsHandler::value(store, memory)),
         (
             "gr_value_available",
@@ -62,7 +83,10 @@ where
         ),
         ("gr_leave", FuncsHandler::leave(store)),
         ("gr_wait", FuncsHandler::wait(store)),
+        ("gr_wait_for", FuncsHandler::wait_for(store)),
+        ("gr_wait_up_to", FuncsHandler::wait_up_to(store)),
         ("gr_wake", FuncsHandler::wake(store, memory)),
+        ("gr_wake_delayed", FuncsHandler::wake_delayed(store, memory)),
         ("gr_error", FuncsHandler::error(store, memory)),
     ]
     .into();
@@ -21,7 +21,9 @@
 #![no_std]
 
 extern crate alloc;
+extern crate std;
 
+pub mod cache;
 pub mod env;
 mod funcs;
 pub mod funcs_tree;
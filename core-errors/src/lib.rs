@@ -23,6 +23,9 @@
 
 extern crate alloc;
 
+use alloc::string::String;
+#[cfg(feature = "codec")]
+use alloc::vec::Vec;
 #[cfg(feature = "codec")]
 use codec::{Decode, Encode};
 use core::fmt;
@@ -110,6 +113,114 @@ pub enum MessageError {
         /// Amount of available value.
         value_left: u128,
     },
+
+    /// The error occurs when a program attempts to wake more messages than
+    /// allowed within a single message processing (same as outgoing limit).
+    #[display(fmt = "Awakening limit exceeded")]
+    AwakeningLimitExceeded,
+
+    /// The error occurs when a program tries to send a message with gas limit
+    /// less than minimal one for the kind of the message.
+    #[display(
+        fmt = "Message gas limit {} is less than minimal gas limit {} for this message kind",
+        message_gas_limit,
+        minimal_gas_limit
+    )]
+    GasLimitBelowMinimum {
+        /// Message's gas limit.
+        message_gas_limit: u64,
+        /// Minimal gas limit for the kind of the message.
+        minimal_gas_limit: u64,
+    },
+
+    /// The error occurs when a program tries to reply in the signal handler.
+    #[display(fmt = "Reply is not allowed in the signal handler")]
+    IncorrectEntryForReply,
+
+    /// The error occurs when a program tries to form a message
+    /// with payload exceeding the maximal size.
+    #[display(fmt = "Max message size exceed")]
+    MaxMessageSizeExceed,
+
+    /// The error occurs when total size of payloads of the messages sent by
+    /// a program exceeds the maximum allowed within a single execution.
+    #[display(fmt = "Outgoing messages bytes limit exceeded")]
+    OutgoingMessagesBytesLimitExceeded,
+
+    /// The error occurs when a program tries to forward a range of the
+    /// incoming message payload, which is out of its bounds.
+    #[display(fmt = "Input payload range is out of bounds")]
+    OutOfBoundsInputRange,
+
+    /// The error occurs when a program tries to create more programs
+    /// than allowed within a single execution.
+    #[display(fmt = "Program candidates limit exceeded")]
+    ProgramCandidatesLimitExceeded,
+}
+
+impl MessageError {
+    /// Convert error into stable code.
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            MessageError::LimitExceeded => 200,
+            MessageError::DuplicateReply => 201,
+            MessageError::DuplicateWaking => 202,
+            MessageError::LateAccess => 203,
+            MessageError::OutOfBounds => 204,
+            MessageError::DuplicateInit => 205,
+            MessageError::NotEnoughGas => 206,
+            MessageError::InsufficientValue { .. } => 207,
+            MessageError::InsufficientGasLimit { .. } => 208,
+            MessageError::NotEnoughValue { .. } => 209,
+            MessageError::AwakeningLimitExceeded => 210,
+            MessageError::GasLimitBelowMinimum { .. } => 211,
+            MessageError::IncorrectEntryForReply => 212,
+            MessageError::MaxMessageSizeExceed => 213,
+            MessageError::OutgoingMessagesBytesLimitExceeded => 214,
+            MessageError::OutOfBoundsInputRange => 215,
+            MessageError::ProgramCandidatesLimitExceeded => 216,
+        }
+    }
+
+    /// Convert stable code into error.
+    ///
+    /// Fields of the variants aren't carried by the code, so they are zeroed.
+    pub fn from_u32(code: u32) -> Option<Self> {
+        let err = match code {
+            200 => MessageError::LimitExceeded,
+            201 => MessageError::DuplicateReply,
+            202 => MessageError::DuplicateWaking,
+            203 => MessageError::LateAccess,
+            204 => MessageError::OutOfBounds,
+            205 => MessageError::DuplicateInit,
+            206 => MessageError::NotEnoughGas,
+            207 => MessageError::InsufficientValue {
+                message_value: 0,
+                existential_deposit: 0,
+            },
+            208 => MessageError::InsufficientGasLimit {
+                message_gas_limit: 0,
+                mailbox_threshold: 0,
+            },
+            209 => MessageError::NotEnoughValue {
+                message_value: 0,
+                value_left: 0,
+            },
+            210 => MessageError::AwakeningLimitExceeded,
+            211 => MessageError::GasLimitBelowMinimum {
+                message_gas_limit: 0,
+                minimal_gas_limit: 0,
+            },
+            212 => MessageError::IncorrectEntryForReply,
+            213 => MessageError::MaxMessageSizeExceed,
+            214 => MessageError::OutgoingMessagesBytesLimitExceeded,
+            215 => MessageError::OutOfBoundsInputRange,
+            216 => MessageError::ProgramCandidatesLimitExceeded,
+            _ => return None,
+        };
+
+        Some(err)
+    }
 }
 
 /// Memory error.
@@ -136,6 +247,33 @@ pub enum MemoryError {
     InvalidPageDataSize(u64),
 }
 
+impl MemoryError {
+    /// Convert error into stable code.
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            MemoryError::OutOfBounds => 100,
+            MemoryError::InvalidFree(_) => 101,
+            MemoryError::MemoryAccessError => 102,
+            MemoryError::InvalidPageDataSize(_) => 103,
+        }
+    }
+
+    /// Convert stable code into error.
+    ///
+    /// Fields of the variants aren't carried by the code, so they are zeroed.
+    pub fn from_u32(code: u32) -> Option<Self> {
+        let err = match code {
+            100 => MemoryError::OutOfBounds,
+            101 => MemoryError::InvalidFree(0),
+            102 => MemoryError::MemoryAccessError,
+            103 => MemoryError::InvalidPageDataSize(0),
+            _ => return None,
+        };
+
+        Some(err)
+    }
+}
+
 /// Execution error.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, derive_more::Display)]
 #[cfg_attr(feature = "codec", derive(Encode, Decode, TypeInfo))]
@@ -146,6 +284,81 @@ pub enum ExecutionError {
     /// An error occurs in attempt to refund more gas than burned one.
     #[display(fmt = "Too many gas refunded")]
     TooManyGasAdded,
+    /// An error occurs in attempt to reserve more gas than available.
+    #[display(fmt = "Not enough gas to reserve")]
+    InsufficientGasForReservation,
+    /// An error occurs in attempt to reserve gas for zero blocks.
+    #[display(fmt = "Reservation duration cannot be zero")]
+    ZeroReservationDuration,
+    /// An error occurs in attempt to use reservation, which doesn't exist.
+    #[display(fmt = "Invalid reservation ID")]
+    InvalidReservationId,
+    /// An error occurs in attempt to reserve gas for signal twice.
+    #[display(fmt = "Gas for signal is already reserved")]
+    DuplicateSystemReservation,
+    /// An error occurs in attempt to wait for zero blocks.
+    #[display(fmt = "Wait duration cannot be zero")]
+    ZeroWaitDuration,
+    /// An error occurs in attempt to wait, while the program's waitlist is full.
+    #[display(fmt = "Waitlist of the program is full")]
+    WaitlistFull,
+    /// An error occurs in attempt to wait or use gas reservations
+    /// in the signal handler.
+    #[display(fmt = "Function is not allowed in the signal handler")]
+    ForbiddenInSignal,
+    /// An error occurs in attempt to emit more events than allowed
+    /// within a single execution (same as outgoing limit).
+    #[display(fmt = "Events limit exceeded")]
+    EventsLimitExceeded,
+    /// An error occurs in attempt to increment more metrics than allowed
+    /// within a single execution (same as outgoing limit).
+    #[display(fmt = "Metrics limit exceeded")]
+    MetricsLimitExceeded,
+    /// An error occurs in attempt to create or remove more gas reservations
+    /// than allowed within a single execution (same as outgoing limit).
+    #[display(fmt = "Gas reservations limit exceeded")]
+    ReservationsLimitExceeded,
+}
+
+impl ExecutionError {
+    /// Convert error into stable code.
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            ExecutionError::GasLimitExceeded => 300,
+            ExecutionError::TooManyGasAdded => 301,
+            ExecutionError::InsufficientGasForReservation => 302,
+            ExecutionError::ZeroReservationDuration => 303,
+            ExecutionError::InvalidReservationId => 304,
+            ExecutionError::DuplicateSystemReservation => 305,
+            ExecutionError::ZeroWaitDuration => 306,
+            ExecutionError::WaitlistFull => 307,
+            ExecutionError::ForbiddenInSignal => 308,
+            ExecutionError::EventsLimitExceeded => 309,
+            ExecutionError::MetricsLimitExceeded => 310,
+            ExecutionError::ReservationsLimitExceeded => 311,
+        }
+    }
+
+    /// Convert stable code into error.
+    pub fn from_u32(code: u32) -> Option<Self> {
+        let err = match code {
+            300 => ExecutionError::GasLimitExceeded,
+            301 => ExecutionError::TooManyGasAdded,
+            302 => ExecutionError::InsufficientGasForReservation,
+            303 => ExecutionError::ZeroReservationDuration,
+            304 => ExecutionError::InvalidReservationId,
+            305 => ExecutionError::DuplicateSystemReservation,
+            306 => ExecutionError::ZeroWaitDuration,
+            307 => ExecutionError::WaitlistFull,
+            308 => ExecutionError::ForbiddenInSignal,
+            309 => ExecutionError::EventsLimitExceeded,
+            310 => ExecutionError::MetricsLimitExceeded,
+            311 => ExecutionError::ReservationsLimitExceeded,
+            _ => return None,
+        };
+
+        Some(err)
+    }
 }
 
 /// An error occurred in API.
@@ -169,11 +382,171 @@ pub enum ExtError {
 }
 
 impl ExtError {
+    /// Size of the stable error code.
+    pub const CODE_SIZE: usize = core::mem::size_of::<u32>();
+
+    /// Code of [`ExtError::Some`], which doesn't belong to any error group.
+    const SOME_CODE: u32 = u32::MAX;
+
+    /// Convert error into stable code.
+    ///
+    /// Codes are grouped by hundreds: `1xx` are memory errors, `2xx` are
+    /// message errors and `3xx` are execution errors.
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            ExtError::Some => Self::SOME_CODE,
+            ExtError::Memory(err) => err.to_u32(),
+            ExtError::Message(err) => err.to_u32(),
+            ExtError::Execution(err) => err.to_u32(),
+        }
+    }
+
+    /// Convert stable code into error.
+    pub fn from_u32(code: u32) -> Option<Self> {
+        match code {
+            Self::SOME_CODE => Some(ExtError::Some),
+            100..=199 => MemoryError::from_u32(code).map(Into::into),
+            200..=299 => MessageError::from_u32(code).map(Into::into),
+            300..=399 => ExecutionError::from_u32(code).map(Into::into),
+            _ => None,
+        }
+    }
+
     /// Size of error encoded in SCALE codec
     #[cfg(feature = "codec")]
     pub fn encoded_size(&self) -> usize {
         Encode::encoded_size(self)
     }
+
+    /// Stable code followed by error encoded in SCALE codec.
+    ///
+    /// This is the layout `gr_error` writes into program memory.
+    #[cfg(feature = "codec")]
+    pub fn encode_with_code(&self) -> Vec<u8> {
+        [&self.to_u32().to_le_bytes()[..], &self.encode()].concat()
+    }
+
+    /// Size of [`ExtError::encode_with_code`] output.
+    #[cfg(feature = "codec")]
+    pub fn encoded_with_code_size(&self) -> usize {
+        Self::CODE_SIZE + self.encoded_size()
+    }
 }
 
 impl CoreError for ExtError {}
+
+/// Reason of the error reply, which is sent by the system
+/// when the message couldn't be processed successfully.
+///
+/// Encoded reason is the payload of such replies to programs,
+/// so they can branch on the failure cause.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, derive_more::Display)]
+#[cfg_attr(feature = "codec", derive(Encode, Decode, TypeInfo))]
+pub enum ErrorReplyReason {
+    /// Execution trapped with the given explanation.
+    #[display(fmt = "{}", _0)]
+    Trap(String),
+    /// Execution ran out of gas, explanation tells which limit was hit.
+    #[display(fmt = "{}", _0)]
+    OutOfGas(String),
+    /// Destination can't execute the message: it doesn't exist,
+    /// has exited, failed to initialize or is initialized already.
+    #[display(fmt = "Not executable actor")]
+    UnavailableActor,
+    /// Message was removed from the waitlist as out of rent.
+    #[display(fmt = "Out of rent")]
+    OutOfRent,
+    /// Message was removed from the mailbox as out of rent
+    /// without being consumed by user.
+    #[display(fmt = "Message wasn't consumed by user")]
+    NotConsumed,
+    /// Message was rejected as sent to the destination forbidden by the runtime.
+    #[display(fmt = "Forbidden destination")]
+    ForbiddenDestination,
+    /// Message was rejected as exceeding the depth limit of chains
+    /// of messages sent by programs to each other.
+    #[display(fmt = "Reply chain is too deep")]
+    ReplyChainTooDeep,
+}
+
+impl ErrorReplyReason {
+    /// Decode reason from the payload of an error reply.
+    ///
+    /// Returns `None` if the payload can't be decoded as a reason.
+    #[cfg(feature = "codec")]
+    pub fn from_payload(mut payload: &[u8]) -> Option<Self> {
+        Self::decode(&mut payload).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes_are_stable() {
+        let errors = [
+            ExtError::Some,
+            MemoryError::OutOfBounds.into(),
+            MemoryError::InvalidFree(0).into(),
+            MemoryError::MemoryAccessError.into(),
+            MemoryError::InvalidPageDataSize(0).into(),
+            MessageError::LimitExceeded.into(),
+            MessageError::DuplicateReply.into(),
+            MessageError::DuplicateWaking.into(),
+            MessageError::LateAccess.into(),
+            MessageError::OutOfBounds.into(),
+            MessageError::DuplicateInit.into(),
+            MessageError::NotEnoughGas.into(),
+            MessageError::InsufficientValue {
+                message_value: 0,
+                existential_deposit: 0,
+            }
+            .into(),
+            MessageError::InsufficientGasLimit {
+                message_gas_limit: 0,
+                mailbox_threshold: 0,
+            }
+            .into(),
+            MessageError::NotEnoughValue {
+                message_value: 0,
+                value_left: 0,
+            }
+            .into(),
+            MessageError::AwakeningLimitExceeded.into(),
+            MessageError::GasLimitBelowMinimum {
+                message_gas_limit: 0,
+                minimal_gas_limit: 0,
+            }
+            .into(),
+            MessageError::IncorrectEntryForReply.into(),
+            MessageError::MaxMessageSizeExceed.into(),
+            MessageError::OutgoingMessagesBytesLimitExceeded.into(),
+            MessageError::OutOfBoundsInputRange.into(),
+            MessageError::ProgramCandidatesLimitExceeded.into(),
+            ExecutionError::GasLimitExceeded.into(),
+            ExecutionError::TooManyGasAdded.into(),
+            ExecutionError::InsufficientGasForReservation.into(),
+            ExecutionError::ZeroReservationDuration.into(),
+            ExecutionError::InvalidReservationId.into(),
+            ExecutionError::DuplicateSystemReservation.into(),
+            ExecutionError::ZeroWaitDuration.into(),
+            ExecutionError::WaitlistFull.into(),
+            ExecutionError::ForbiddenInSignal.into(),
+            ExecutionError::EventsLimitExceeded.into(),
+            ExecutionError::MetricsLimitExceeded.into(),
+            ExecutionError::ReservationsLimitExceeded.into(),
+        ];
+
+        for err in errors {
+            assert_eq!(ExtError::from_u32(err.to_u32()), Some(err));
+        }
+
+        assert_eq!(
+            ExtError::from_u32(MemoryError::InvalidFree(42).to_u32()),
+            Some(MemoryError::InvalidFree(0).into())
+        );
+        assert_eq!(ExtError::from_u32(0), None);
+        assert_eq!(ExtError::from_u32(299), None);
+    }
+}
@@ -26,10 +26,13 @@ use alloc::{
 use codec::{Decode, Encode};
 use gear_backend_common::TrapExplanation;
 use gear_core::{
-    gas::GasAmount,
-    ids::{CodeId, MessageId, ProgramId},
+    gas::{GasAmount, GasReservationMap, GasReserver},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber, WasmPageNumber},
-    message::{ContextStore, Dispatch, IncomingDispatch, StoredDispatch},
+    message::{
+        ContextStore, Dispatch, DispatchKind, ExitCode, IncomingDispatch, MessageWaitedType,
+        ProgramEvent, StoredDispatch,
+    },
     program::Program,
 };
 use gear_core_errors::MemoryError;
@@ -43,7 +46,7 @@ pub enum DispatchResultKind {
     /// Trap dispatch.
     Trap(TrapExplanation),
     /// Wait dispatch.
-    Wait,
+    Wait(Option<u32>, MessageWaitedType),
     /// Exit dispatch.
     Exit(ProgramId),
     /// Gas allowance exceed.
@@ -60,18 +63,25 @@ pub struct DispatchResult {
     pub program_id: ProgramId,
     /// Context store after execution.
     pub context_store: ContextStore,
-    /// List of generated messages.
-    pub generated_dispatches: Vec<Dispatch>,
-    /// List of messages that should be woken.
-    pub awakening: Vec<MessageId>,
+    /// List of generated messages with their delays in blocks
+    /// and gas reservations they are sent from.
+    pub generated_dispatches: Vec<(Dispatch, u32, Option<ReservationId>)>,
+    /// List of messages that should be woken with their delays in blocks.
+    pub awakening: Vec<(MessageId, u32)>,
     /// New programs to be created with additional data (corresponding code hash and init message id).
     pub program_candidates: BTreeMap<CodeId, Vec<(ProgramId, MessageId)>>,
     /// Gas amount after execution.
     pub gas_amount: GasAmount,
+    /// Gas reservations changes made within execution.
+    pub gas_reserver: GasReserver,
     /// Page updates.
     pub page_update: BTreeMap<PageNumber, PageBuf>,
     /// New allocations set for program if it has been changed.
     pub allocations: Option<BTreeSet<WasmPageNumber>>,
+    /// Increments of program's custom counters.
+    pub metrics: BTreeMap<[u8; 32], u64>,
+    /// Events emitted by program.
+    pub events: Vec<ProgramEvent>,
 }
 
 impl DispatchResult {
@@ -96,6 +106,51 @@ impl DispatchResult {
     }
 }
 
+/// Gas reserved by the program for the signal of the message.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemReservationContext {
+    /// Reservation made within current execution.
+    pub current_reservation: Option<u64>,
+    /// Reservation made within previous executions of the message.
+    pub previous_reservation: Option<u64>,
+}
+
+impl SystemReservationContext {
+    /// Create context from dispatch, which wasn't executed.
+    pub fn from_dispatch(dispatch: &IncomingDispatch) -> Self {
+        Self {
+            current_reservation: None,
+            previous_reservation: dispatch
+                .context()
+                .as_ref()
+                .and_then(ContextStore::system_reservation),
+        }
+    }
+
+    /// Create context from result of the dispatch execution.
+    pub fn from_dispatch_result(dispatch_result: &DispatchResult) -> Self {
+        let Self {
+            previous_reservation,
+            ..
+        } = Self::from_dispatch(&dispatch_result.dispatch);
+
+        let current_reservation = previous_reservation
+            .is_none()
+            .then(|| dispatch_result.context_store.system_reservation())
+            .flatten();
+
+        Self {
+            current_reservation,
+            previous_reservation,
+        }
+    }
+
+    /// Checks if there is any reservation.
+    pub fn has_any(&self) -> bool {
+        self.current_reservation.is_some() || self.previous_reservation.is_some()
+    }
+}
+
 /// Dispatch outcome of the specific message.
 #[derive(Clone, Debug)]
 pub enum DispatchOutcome {
@@ -166,9 +221,20 @@ pub enum JournalNote {
         message_id: MessageId,
         /// New message with entry point that was generated.
         dispatch: Dispatch,
+        /// Amount of blocks to wait before sending the message.
+        delay: u32,
+        /// Gas reservation, which gas is used by the message.
+        reservation: Option<ReservationId>,
     },
     /// Put this dispatch in the wait list.
-    WaitDispatch(StoredDispatch),
+    WaitDispatch {
+        /// Stored dispatch to be inserted into Waitlist.
+        dispatch: StoredDispatch,
+        /// Expected duration of holding.
+        duration: Option<u32>,
+        /// Type of wait call.
+        waited_type: MessageWaitedType,
+    },
     /// Wake particular message.
     WakeMessage {
         /// Message which has initiated wake.
@@ -177,6 +243,8 @@ pub enum JournalNote {
         program_id: ProgramId,
         /// Message that should be woken.
         awakening_id: MessageId,
+        /// Amount of blocks to wait before waking.
+        delay: u32,
     },
     /// Update page.
     UpdatePage {
@@ -206,6 +274,8 @@ pub enum JournalNote {
     },
     /// Store programs requested by user to be initialized later
     StoreNewPrograms {
+        /// Program which requested creation of the new programs.
+        program_id: ProgramId,
         /// Code hash used to create new programs with ids in `candidates` field
         code_hash: CodeId,
         /// Collection of program candidate ids and their init message ids.
@@ -218,6 +288,65 @@ pub enum JournalNote {
         /// Decreases gas allowance by that amount, burned for processing try.
         gas_burned: u64,
     },
+    /// Reserve gas of the message.
+    ReserveGas {
+        /// Message, which gas is reserved.
+        message_id: MessageId,
+        /// Id of the reservation.
+        reservation_id: ReservationId,
+        /// Program, which owns the reservation.
+        program_id: ProgramId,
+        /// Amount of reserved gas.
+        amount: u64,
+        /// Block number, at which reservation expires.
+        expiration: u32,
+    },
+    /// Remove gas reservation.
+    UnreserveGas {
+        /// Id of the reservation.
+        reservation_id: ReservationId,
+        /// Program, which owns the reservation.
+        program_id: ProgramId,
+        /// Block number, at which reservation expires.
+        expiration: u32,
+    },
+    /// Reserve gas of the message for its signal.
+    SystemReserveGas {
+        /// Message, which gas is reserved.
+        message_id: MessageId,
+        /// Amount of reserved gas.
+        amount: u64,
+    },
+    /// Return gas reserved for the signal of the message.
+    SystemUnreserveGas {
+        /// Message, which gas was reserved.
+        message_id: MessageId,
+    },
+    /// Send signal using gas reserved for it.
+    SendSignal {
+        /// Message, which execution failed.
+        message_id: MessageId,
+        /// Program, which receives the signal.
+        destination: ProgramId,
+        /// Exit code of the signal.
+        exit_code: ExitCode,
+    },
+    /// Increment program's custom counter.
+    IncrementMetric {
+        /// Program, which owns the counter.
+        program_id: ProgramId,
+        /// Hash of the counter's name.
+        key: [u8; 32],
+        /// Amount to add to the counter.
+        value: u64,
+    },
+    /// Record event emitted by program.
+    EmitEvent {
+        /// Program, which emitted the event.
+        program_id: ProgramId,
+        /// Emitted event.
+        event: ProgramEvent,
+    },
 }
 
 /// Journal handler.
@@ -238,15 +367,27 @@ pub trait JournalHandler {
     /// Process message consumed.
     fn message_consumed(&mut self, message_id: MessageId);
     /// Process send dispatch.
-    fn send_dispatch(&mut self, message_id: MessageId, dispatch: Dispatch);
+    fn send_dispatch(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: u32,
+        reservation: Option<ReservationId>,
+    );
     /// Process send message.
-    fn wait_dispatch(&mut self, dispatch: StoredDispatch);
+    fn wait_dispatch(
+        &mut self,
+        dispatch: StoredDispatch,
+        duration: Option<u32>,
+        waited_type: MessageWaitedType,
+    );
     /// Process send message.
     fn wake_message(
         &mut self,
         message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
+        delay: u32,
     );
     /// Process page update.
     fn update_pages_data(
@@ -261,11 +402,42 @@ pub trait JournalHandler {
     /// Store new programs in storage.
     ///
     /// Program ids are ids of _potential_ (planned to be initialized) programs.
-    fn store_new_programs(&mut self, code_hash: CodeId, candidates: Vec<(ProgramId, MessageId)>);
+    fn store_new_programs(
+        &mut self,
+        program_id: ProgramId,
+        code_hash: CodeId,
+        candidates: Vec<(ProgramId, MessageId)>,
+    );
     /// Stop processing queue.
     ///
     /// Pushes StoredDispatch back to the top of the queue and decreases gas allowance.
     fn stop_processing(&mut self, dispatch: StoredDispatch, gas_burned: u64);
+    /// Reserve gas.
+    fn reserve_gas(
+        &mut self,
+        message_id: MessageId,
+        reservation_id: ReservationId,
+        program_id: ProgramId,
+        amount: u64,
+        expiration: u32,
+    );
+    /// Unreserve gas.
+    fn unreserve_gas(
+        &mut self,
+        reservation_id: ReservationId,
+        program_id: ProgramId,
+        expiration: u32,
+    );
+    /// Reserve gas for signal.
+    fn system_reserve_gas(&mut self, message_id: MessageId, amount: u64);
+    /// Unreserve gas reserved for signal.
+    fn system_unreserve_gas(&mut self, message_id: MessageId);
+    /// Send signal.
+    fn send_signal(&mut self, message_id: MessageId, destination: ProgramId, exit_code: ExitCode);
+    /// Increment program's custom counter.
+    fn increment_metric(&mut self, program_id: ProgramId, key: [u8; 32], value: u64);
+    /// Record event emitted by program.
+    fn emit_event(&mut self, program_id: ProgramId, event: ProgramEvent);
 }
 
 /// Execution error.
@@ -339,6 +511,12 @@ pub enum ExecutionErrorReason {
     /// Initial pages data must be empty when execute with lazy pages
     #[display(fmt = "Initial pages data must be empty when execute with lazy pages")]
     InitialPagesContainsDataInLazyPagesMode,
+    /// Message removed from mailbox as out of rent without being consumed by user.
+    #[display(fmt = "Message wasn't consumed by user")]
+    NotConsumed,
+    /// Not enough gas for pages accessed during execution
+    #[display(fmt = "Not enough gas for accessed memory pages")]
+    PagesAccessGasExceeded,
 }
 
 /// Actor.
@@ -359,6 +537,10 @@ pub struct ExecutableActorData {
     pub program: Program,
     /// Data which some program allocated pages may have.
     pub pages_data: BTreeMap<PageNumber, PageBuf>,
+    /// Gas reservations of the program.
+    pub gas_reservation_map: GasReservationMap,
+    /// Whether the waitlist of the program is full.
+    pub waitlist_full: bool,
 }
 
 /// Execution context.
@@ -369,3 +551,14 @@ pub struct WasmExecutionContext {
     /// Gas allowance of the block.
     pub gas_allowance: u64,
 }
+
+impl WasmExecutionContext {
+    /// Checks if value of the dispatch is held in escrow during execution.
+    ///
+    /// Value attached to init message sent by program (factory) can't be
+    /// spent by created program and is transferred to it only once its
+    /// initialization succeeds, otherwise it's returned to the factory.
+    pub fn holds_value_in_escrow(&self, dispatch: &IncomingDispatch) -> bool {
+        dispatch.kind() == DispatchKind::Init && dispatch.source() != self.origin
+    }
+}
@@ -22,14 +22,20 @@ use crate::common::Actor;
 use alloc::collections::BTreeSet;
 use codec::{Decode, Encode};
 use gear_core::{
-    costs::HostFnWeights, ids::ProgramId, memory::WasmPageNumber, message::IncomingDispatch,
+    costs::HostFnWeights,
+    ids::ProgramId,
+    memory::WasmPageNumber,
+    message::{DispatchKind, IncomingDispatch},
 };
+use scale_info::TypeInfo;
 
 const MAX_WASM_PAGES: u32 = 512;
 const INIT_COST: u64 = 5000;
 const ALLOC_COST: u64 = 10000;
 const MEM_GROW_COST: u64 = 10000;
 const LOAD_PAGE_COST: u64 = 3000;
+const PAGE_READ_COST: u64 = 1000;
+const PAGE_WRITE_COST: u64 = 4000;
 
 /// Contextual block information.
 #[derive(Clone, Copy, Debug, Encode, Decode, Default)]
@@ -40,6 +46,60 @@ pub struct BlockInfo {
     pub timestamp: u64,
 }
 
+/// Minimal gas limits of dispatches per entry point.
+///
+/// Dispatches with explicit gas limit less than the minimum of their kind
+/// are rejected before being enqueued.
+#[derive(Clone, Copy, Debug, Encode, Decode, TypeInfo, Default, PartialEq, Eq)]
+pub struct DispatchGasMinimums {
+    /// Minimal gas limit of init dispatch.
+    pub init: u64,
+    /// Minimal gas limit of handle dispatch.
+    pub handle: u64,
+    /// Minimal gas limit of reply dispatch.
+    pub reply: u64,
+}
+
+impl DispatchGasMinimums {
+    /// Minimal gas limit for dispatch of the given kind.
+    pub fn for_kind(&self, kind: DispatchKind) -> u64 {
+        match kind {
+            DispatchKind::Init => self.init,
+            DispatchKind::Handle => self.handle,
+            DispatchKind::Reply => self.reply,
+            // Signals are generated by the system and paid from reservation.
+            DispatchKind::Signal => 0,
+        }
+    }
+}
+
+/// Scaling of the outgoing messages limit with the gas limit of a dispatch.
+///
+/// Makes dispatches with low gas limit unable to send as many messages
+/// as the heavy ones, so they can't cause disproportionate queue growth.
+#[derive(Clone, Copy, Debug, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct OutgoingLimitScaling {
+    /// Amount of gas each outgoing message of a dispatch is backed with.
+    pub gas_per_message: u64,
+    /// Minimal outgoing messages limit of a dispatch.
+    pub floor: u32,
+}
+
+impl OutgoingLimitScaling {
+    /// Outgoing messages limit of a dispatch with the given gas limit,
+    /// which never exceeds the `cap`.
+    pub fn limit(&self, gas_limit: u64, cap: u32) -> u32 {
+        let scaled = gas_limit
+            .checked_div(self.gas_per_message)
+            .unwrap_or(u64::MAX);
+
+        u32::try_from(scaled)
+            .unwrap_or(u32::MAX)
+            .max(self.floor)
+            .min(cap)
+    }
+}
+
 /// Memory/allocation config.
 #[derive(Clone, Debug, Decode, Encode)]
 pub struct AllocationsConfig {
@@ -53,6 +113,10 @@ pub struct AllocationsConfig {
     pub mem_grow_cost: u64,
     /// Load page cost.
     pub load_page_cost: u64,
+    /// Cost of the first read of a gear page within the execution.
+    pub page_read_cost: u64,
+    /// Cost of the first write of a gear page within the execution.
+    pub page_write_cost: u64,
 }
 
 impl Default for AllocationsConfig {
@@ -63,6 +127,8 @@ impl Default for AllocationsConfig {
             alloc_cost: ALLOC_COST,
             mem_grow_cost: MEM_GROW_COST,
             load_page_cost: LOAD_PAGE_COST,
+            page_read_cost: PAGE_READ_COST,
+            page_write_cost: PAGE_WRITE_COST,
         }
     }
 }
@@ -81,6 +147,8 @@ pub struct ExecutionSettings {
     pub forbidden_funcs: BTreeSet<&'static str>,
     /// Threshold for inserting into mailbox
     pub mailbox_threshold: u64,
+    /// Minimal gas limits of sent dispatches.
+    pub gas_minimums: DispatchGasMinimums,
 }
 
 impl ExecutionSettings {
@@ -92,6 +160,7 @@ impl ExecutionSettings {
         host_fn_weights: HostFnWeights,
         forbidden_funcs: BTreeSet<&'static str>,
         mailbox_threshold: u64,
+        gas_minimums: DispatchGasMinimums,
     ) -> Self {
         Self {
             block_info,
@@ -100,6 +169,7 @@ impl ExecutionSettings {
             host_fn_weights,
             forbidden_funcs,
             mailbox_threshold,
+            gas_minimums,
         }
     }
 
@@ -127,6 +197,16 @@ impl ExecutionSettings {
     pub fn load_page_cost(&self) -> u64 {
         self.allocations_config.load_page_cost
     }
+
+    /// Read gear page cost.
+    pub fn page_read_cost(&self) -> u64 {
+        self.allocations_config.page_read_cost
+    }
+
+    /// Write gear page cost.
+    pub fn page_write_cost(&self) -> u64 {
+        self.allocations_config.page_write_cost
+    }
 }
 
 /// Stable parameters for the whole block across processing runs.
@@ -140,12 +220,22 @@ pub struct BlockConfig {
     pub existential_deposit: u128,
     /// Outgoing limit.
     pub outgoing_limit: u32,
+    /// Outgoing bytes limit.
+    pub outgoing_bytes_limit: u32,
+    /// Limit of programs created within a single execution.
+    pub program_candidates_limit: u32,
+    /// Scaling of the outgoing limit with the gas limit of a dispatch.
+    ///
+    /// If set, `outgoing_limit` is the cap of the scaled limit.
+    pub outgoing_scaling: Option<OutgoingLimitScaling>,
     /// Host function weights.
     pub host_fn_weights: HostFnWeights,
     /// Forbidden functions.
     pub forbidden_funcs: BTreeSet<&'static str>,
     /// Mailbox threshold.
     pub mailbox_threshold: u64,
+    /// Minimal gas limits of dispatches.
+    pub gas_minimums: DispatchGasMinimums,
 }
 
 /// Unstable parameters for message execution across processing runs.
@@ -21,20 +21,28 @@ use crate::{
         DispatchResult, DispatchResultKind, ExecutableActorData, ExecutionError,
         ExecutionErrorReason, WasmExecutionContext,
     },
-    configs::ExecutionSettings,
+    configs::{AllocationsConfig, BlockInfo, ExecutionSettings},
     ext::{ProcessorContext, ProcessorExt},
 };
 use alloc::{
     collections::{BTreeMap, BTreeSet},
-    string::ToString,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::mem;
+use gear_backend_common::{
+    BackendReport, Environment, IntoExtInfo, TerminationReason, SIGNAL_FORBIDDEN_FUNCS,
 };
-use gear_backend_common::{BackendReport, Environment, IntoExtInfo, TerminationReason};
 use gear_core::{
     env::Ext as EnvExt,
-    gas::{ChargeResult, GasAllowanceCounter, GasCounter, ValueCounter},
+    gas::{ChargeResult, GasAllowanceCounter, GasCounter, GasReserver, ValueCounter},
     ids::ProgramId,
     memory::{AllocationsContext, Memory, PageBuf, PageNumber, WasmPageNumber},
-    message::{ContextSettings, IncomingDispatch, MessageContext},
+    message::{
+        ContextSettings, DispatchKind, IncomingDispatch, IncomingMessage, MessageContext, Payload,
+    },
 };
 
 /// Make checks that everything with memory pages go well.
@@ -218,10 +226,16 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
     let ExecutableActorData {
         program,
         pages_data: mut pages_initial_data,
+        gas_reservation_map,
+        waitlist_full,
     } = data;
 
     let program_id = program.id();
     let kind = dispatch.kind();
+    let route = program
+        .code()
+        .route(&kind, dispatch.message().payload())
+        .map(ToString::to_string);
 
     log::debug!("Executing program {}", program_id);
     log::debug!("Executing dispatch {:?}", dispatch);
@@ -250,6 +264,9 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
         }
     };
 
+    let page_read_cost = settings.page_read_cost();
+    let page_write_cost = settings.page_write_cost();
+
     // Getting wasm pages allocations.
     let (allocations, is_initial) = if program.get_allocations().is_empty() {
         ((0..static_pages.0).map(WasmPageNumber).collect(), true)
@@ -263,18 +280,37 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
 
     // Creating message context.
     let message_context = MessageContext::new_with_settings(
+        kind,
         dispatch.message().clone(),
         program_id,
         dispatch.context().clone(),
         msg_ctx_settings,
     );
 
+    // Creating gas reserver.
+    let gas_reserver = GasReserver::new(
+        dispatch.id(),
+        message_context.reservation_nonce(),
+        gas_reservation_map,
+    );
+
     // Creating value counter.
-    let value_counter = ValueCounter::new(balance + dispatch.value());
+    let value_counter = if context.holds_value_in_escrow(&dispatch) {
+        ValueCounter::new(balance)
+    } else {
+        ValueCounter::new(balance + dispatch.value())
+    };
+
+    // Signal handler is restricted to the sys-calls, which can't extend its lifetime.
+    let mut forbidden_funcs = settings.forbidden_funcs;
+    if kind.is_signal() {
+        forbidden_funcs.extend(SIGNAL_FORBIDDEN_FUNCS);
+    }
 
     let context = ProcessorContext {
         gas_counter,
         gas_allowance_counter,
+        gas_reserver,
         value_counter,
         allocations_context,
         message_context,
@@ -285,8 +321,12 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
         program_id,
         program_candidates_data: Default::default(),
         host_fn_weights: settings.host_fn_weights,
-        forbidden_funcs: settings.forbidden_funcs,
+        forbidden_funcs,
         mailbox_threshold: settings.mailbox_threshold,
+        gas_minimums: settings.gas_minimums,
+        metrics: Default::default(),
+        events: Default::default(),
+        waitlist_full,
     };
 
     // Creating externalities.
@@ -325,7 +365,7 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
     log::trace!("Stack end page = {:?}", stack_end_page);
 
     // Execute program in backend env.
-    let BackendReport { termination, info } = match env.execute(&kind, |mem| {
+    let BackendReport { termination, info } = match env.execute(&kind, route.as_deref(), |mem| {
         // released pages initial data will be added to `pages_initial_data` after execution.
         if A::is_lazy_pages_enabled() {
             A::lazy_pages_post_execution_actions(mem, &mut pages_initial_data)
@@ -358,13 +398,47 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
 
             DispatchResultKind::Trap(explanation)
         }
-        TerminationReason::Wait => DispatchResultKind::Wait,
+        TerminationReason::Wait(duration, waited_type) => {
+            DispatchResultKind::Wait(duration, waited_type)
+        }
         TerminationReason::GasAllowanceExceeded => DispatchResultKind::GasAllowanceExceed,
     };
 
+    // With lazy pages only accessed pages are loaded during execution,
+    // otherwise all pages have been loaded and charged before execution.
+    let pages_read = if A::is_lazy_pages_enabled() {
+        info.pages_data.len() as u64
+    } else {
+        0
+    };
+
     let page_update =
         get_pages_to_be_updated::<A>(pages_initial_data, info.pages_data, stack_end_page);
 
+    // Charging gas for pages read and written during execution.
+    let mut gas_counter = GasCounter::from(info.gas_amount);
+
+    if !matches!(kind, DispatchResultKind::GasAllowanceExceed) {
+        // Pages written by trapped execution aren't updated.
+        let pages_written = if matches!(kind, DispatchResultKind::Trap(_)) {
+            0
+        } else {
+            page_update.len() as u64
+        };
+
+        let amount = page_read_cost
+            .saturating_mul(pages_read)
+            .saturating_add(page_write_cost.saturating_mul(pages_written));
+
+        if gas_counter.charge(amount) != ChargeResult::Enough {
+            return Err(ExecutionError {
+                program_id,
+                gas_amount: gas_counter.into(),
+                reason: ExecutionErrorReason::PagesAccessGasExceeded,
+            });
+        }
+    }
+
     // Getting new programs that are scheduled to be initialized (respected messages are in `generated_dispatches` collection)
     let program_candidates = info.program_candidates_data;
 
@@ -377,12 +451,218 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
         generated_dispatches: info.generated_dispatches,
         awakening: info.awakening,
         program_candidates,
-        gas_amount: info.gas_amount,
+        gas_amount: gas_counter.into(),
+        gas_reserver: info.gas_reserver,
         page_update,
         allocations: if !is_initial && info.allocations.eq(&allocations) {
             None
         } else {
             Some(info.allocations)
         },
+        metrics: info.metrics,
+        events: info.events,
     })
 }
+
+/// Execute `function` export of the program with `payload` as incoming message
+/// and return payload of the reply, made during the execution.
+///
+/// Execution is informational: program state isn't changed and all
+/// other execution results are discarded.
+pub fn execute_for_reply<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environment<A>>(
+    function: &str,
+    data: ExecutableActorData,
+    payload: Vec<u8>,
+    gas_limit: u64,
+    block_info: BlockInfo,
+) -> Result<Vec<u8>, String> {
+    let binary = data.program.raw_code().to_vec();
+
+    let (env, mut pages_initial_data) =
+        prepare_informational_env::<A, E>(&binary, data, payload, gas_limit, block_info)?;
+
+    let BackendReport { termination, info } = env
+        .execute_export(function, |mem| {
+            if A::is_lazy_pages_enabled() {
+                A::lazy_pages_post_execution_actions(mem, &mut pages_initial_data)
+            } else {
+                Ok(())
+            }
+        })
+        .map_err(|err| err.to_string())?;
+
+    check_informational_termination(termination)?;
+
+    info.generated_dispatches
+        .into_iter()
+        .find_map(|(dispatch, ..)| {
+            dispatch
+                .reply()
+                .is_some()
+                .then(|| dispatch.payload().to_vec())
+        })
+        .ok_or_else(|| String::from("Program didn't reply"))
+}
+
+/// Execute `meta_state` function of the program metadata binary over
+/// the program memory with `payload` as incoming message and return
+/// the state, encoded by the function.
+///
+/// Execution is informational: program state isn't changed and all
+/// other execution results are discarded.
+pub fn execute_for_meta_state<
+    A: ProcessorExt + EnvExt + IntoExtInfo + 'static,
+    E: Environment<A>,
+>(
+    meta_binary: &[u8],
+    data: ExecutableActorData,
+    payload: Vec<u8>,
+    gas_limit: u64,
+    block_info: BlockInfo,
+) -> Result<Vec<u8>, String> {
+    let (env, mut pages_initial_data) =
+        prepare_informational_env::<A, E>(meta_binary, data, payload, gas_limit, block_info)?;
+
+    let mut state = None;
+
+    let BackendReport { termination, .. } = env
+        .execute_for_result("meta_state", |mem, returned| {
+            if let Some(ptr) = returned {
+                state = read_fat_ptr_data(mem, ptr as usize);
+            }
+
+            if A::is_lazy_pages_enabled() {
+                A::lazy_pages_post_execution_actions(mem, &mut pages_initial_data)
+            } else {
+                Ok(())
+            }
+        })
+        .map_err(|err| err.to_string())?;
+
+    check_informational_termination(termination)?;
+
+    state.ok_or_else(|| String::from("Unable to read state returned by `meta_state`"))
+}
+
+/// Reads data referred by the fat pointer (pair of `i32` pointer and length),
+/// placed at `offset` of the memory.
+fn read_fat_ptr_data<M: Memory>(memory: &M, offset: usize) -> Option<Vec<u8>> {
+    let mut ptr = [0u8; mem::size_of::<i32>()];
+    let mut len = [0u8; mem::size_of::<i32>()];
+
+    memory.read(offset, &mut ptr).ok()?;
+    memory.read(offset + ptr.len(), &mut len).ok()?;
+
+    let ptr = i32::from_le_bytes(ptr) as usize;
+    let len = i32::from_le_bytes(len) as usize;
+
+    let mut data = vec![0; len];
+    memory.read(ptr, &mut data).ok()?;
+
+    Some(data)
+}
+
+fn check_informational_termination(termination: TerminationReason) -> Result<(), String> {
+    match termination {
+        TerminationReason::Exit(_) | TerminationReason::Leave | TerminationReason::Success => {
+            Ok(())
+        }
+        TerminationReason::Trap(explanation) => {
+            Err(format!("Program execution trapped: {}", explanation))
+        }
+        TerminationReason::Wait(..) => Err(String::from("Program can't wait here")),
+        TerminationReason::GasAllowanceExceeded => Err(String::from("Gas limit exceeded")),
+    }
+}
+
+/// Creates environment over the `binary` with memory of the program
+/// for informational execution.
+fn prepare_informational_env<
+    A: ProcessorExt + EnvExt + IntoExtInfo + 'static,
+    E: Environment<A>,
+>(
+    binary: &[u8],
+    data: ExecutableActorData,
+    payload: Vec<u8>,
+    gas_limit: u64,
+    block_info: BlockInfo,
+) -> Result<(E, BTreeMap<PageNumber, PageBuf>), String> {
+    let ExecutableActorData {
+        program,
+        pages_data: mut pages_initial_data,
+        gas_reservation_map,
+        waitlist_full,
+    } = data;
+
+    let payload = Payload::try_from(payload).map_err(|err| String::from(<&str>::from(err)))?;
+
+    let program_id = program.id();
+    let static_pages = program.static_pages();
+
+    let allocations = if program.get_allocations().is_empty() {
+        (0..static_pages.0).map(WasmPageNumber).collect()
+    } else {
+        program.get_allocations().clone()
+    };
+
+    let mem_size = allocations
+        .iter()
+        .next_back()
+        .map(|max_wasm_page| *max_wasm_page + 1.into())
+        .unwrap_or(static_pages);
+
+    let message_context = MessageContext::new(
+        DispatchKind::Handle,
+        IncomingMessage::new(
+            Default::default(),
+            Default::default(),
+            payload,
+            gas_limit,
+            0,
+            None,
+        ),
+        program_id,
+        None,
+    );
+
+    let context = ProcessorContext {
+        gas_counter: GasCounter::new(gas_limit),
+        gas_allowance_counter: GasAllowanceCounter::new(gas_limit),
+        gas_reserver: GasReserver::new(Default::default(), 0, gas_reservation_map),
+        value_counter: ValueCounter::new(Default::default()),
+        allocations_context: AllocationsContext::new(
+            allocations,
+            static_pages,
+            AllocationsConfig::default().max_pages,
+        ),
+        message_context,
+        block_info,
+        config: Default::default(),
+        existential_deposit: Default::default(),
+        origin: Default::default(),
+        program_id,
+        program_candidates_data: Default::default(),
+        host_fn_weights: Default::default(),
+        forbidden_funcs: Default::default(),
+        mailbox_threshold: Default::default(),
+        gas_minimums: Default::default(),
+        metrics: Default::default(),
+        events: Default::default(),
+        waitlist_full,
+    };
+
+    let ext = A::new(context);
+
+    let mut env = E::new(ext, binary, program.code().exports().clone(), mem_size)
+        .map_err(|err| format!("Setup instance error: {}", err))?;
+
+    prepare_memory::<A, E::Memory>(
+        program_id,
+        &mut pages_initial_data,
+        static_pages,
+        env.get_mem_mut(),
+    )
+    .map_err(|err| err.to_string())?;
+
+    Ok((env, pages_initial_data))
+}
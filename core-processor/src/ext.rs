@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::configs::{AllocationsConfig, BlockInfo};
+use crate::configs::{AllocationsConfig, BlockInfo, DispatchGasMinimums};
 use alloc::{
     collections::{BTreeMap, BTreeSet},
     string::{String, ToString},
@@ -31,10 +31,13 @@ use gear_core::{
     charge_gas_token,
     costs::{HostFnWeights, RuntimeCosts},
     env::Ext as EnvExt,
-    gas::{ChargeResult, GasAllowanceCounter, GasAmount, GasCounter, ValueCounter},
-    ids::{CodeId, MessageId, ProgramId},
+    gas::{ChargeResult, GasAllowanceCounter, GasAmount, GasCounter, GasReserver, ValueCounter},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{AllocationsContext, Memory, PageBuf, PageNumber, WasmPageNumber},
-    message::{GasLimit, HandlePacket, InitPacket, MessageContext, Packet, ReplyPacket},
+    message::{
+        DispatchKind, ExitCode, GasLimit, HandlePacket, InitPacket, MessageContext, Packet,
+        ProgramEvent, ReplyDetails, ReplyPacket,
+    },
 };
 use gear_core_errors::{CoreError, ExecutionError, ExtError, MemoryError, MessageError};
 
@@ -44,6 +47,8 @@ pub struct ProcessorContext {
     pub gas_counter: GasCounter,
     /// Gas allowance counter.
     pub gas_allowance_counter: GasAllowanceCounter,
+    /// Reserver of program gas.
+    pub gas_reserver: GasReserver,
     /// Value counter.
     pub value_counter: ValueCounter,
     /// Allocations context.
@@ -69,6 +74,14 @@ pub struct ProcessorContext {
     pub forbidden_funcs: BTreeSet<&'static str>,
     /// Mailbox threshold
     pub mailbox_threshold: u64,
+    /// Minimal gas limits of sent dispatches.
+    pub gas_minimums: DispatchGasMinimums,
+    /// Custom counters of the program, incremented within execution.
+    pub metrics: BTreeMap<[u8; 32], u64>,
+    /// Events emitted by the program within execution.
+    pub events: Vec<ProgramEvent>,
+    /// Whether the waitlist of the program is full, so it can't wait.
+    pub waitlist_full: bool,
 }
 
 /// Trait to which ext must have to work in processor wasm executor.
@@ -154,14 +167,63 @@ impl From<ExecutionError> for ProcessorError {
 impl CoreError for ProcessorError {}
 
 impl IntoExtError for ProcessorError {
+    /// Only errors caused by the actor itself are returned to the program,
+    /// while the rest of them terminate execution.
     fn into_ext_error(self) -> Result<ExtError, Self> {
         match self {
-            ProcessorError::Core(err) => Ok(err),
+            ProcessorError::Core(err) if is_actor_error(&err) => Ok(err),
             err => Err(err),
         }
     }
 }
 
+/// Classifies whether the error can be handled by the program.
+///
+/// There's no wildcard arm, so every new error variant must be classified.
+fn is_actor_error(err: &ExtError) -> bool {
+    match err {
+        ExtError::Some => false,
+        ExtError::Memory(err) => match err {
+            MemoryError::OutOfBounds
+            | MemoryError::InvalidFree(_)
+            | MemoryError::MemoryAccessError
+            | MemoryError::InvalidPageDataSize(_) => false,
+        },
+        ExtError::Message(err) => match err {
+            MessageError::LimitExceeded
+            | MessageError::DuplicateReply
+            | MessageError::DuplicateWaking
+            | MessageError::LateAccess
+            | MessageError::OutOfBounds
+            | MessageError::DuplicateInit
+            | MessageError::NotEnoughGas
+            | MessageError::InsufficientValue { .. }
+            | MessageError::InsufficientGasLimit { .. }
+            | MessageError::NotEnoughValue { .. }
+            | MessageError::AwakeningLimitExceeded
+            | MessageError::GasLimitBelowMinimum { .. }
+            | MessageError::IncorrectEntryForReply
+            | MessageError::MaxMessageSizeExceed
+            | MessageError::OutgoingMessagesBytesLimitExceeded
+            | MessageError::OutOfBoundsInputRange
+            | MessageError::ProgramCandidatesLimitExceeded => true,
+        },
+        ExtError::Execution(err) => match err {
+            ExecutionError::GasLimitExceeded | ExecutionError::TooManyGasAdded => false,
+            ExecutionError::InsufficientGasForReservation
+            | ExecutionError::ZeroReservationDuration
+            | ExecutionError::InvalidReservationId
+            | ExecutionError::DuplicateSystemReservation
+            | ExecutionError::ZeroWaitDuration
+            | ExecutionError::WaitlistFull
+            | ExecutionError::ForbiddenInSignal
+            | ExecutionError::EventsLimitExceeded
+            | ExecutionError::MetricsLimitExceeded
+            | ExecutionError::ReservationsLimitExceeded => true,
+        },
+    }
+}
+
 impl AsTerminationReason for ProcessorError {
     fn as_termination_reason(&self) -> Option<&TerminationReason> {
         match self {
@@ -220,9 +282,12 @@ impl IntoExtInfo for Ext {
     ) -> Result<(ExtInfo, Option<TrapExplanation>), (MemoryError, GasAmount)> {
         let ProcessorContext {
             allocations_context,
-            message_context,
+            mut message_context,
             gas_counter,
+            gas_reserver,
             program_candidates_data,
+            metrics,
+            events,
             ..
         } = self.context;
 
@@ -236,17 +301,22 @@ impl IntoExtInfo for Ext {
             pages_data.insert(page, buf);
         }
 
+        message_context.set_reservation_nonce(gas_reserver.nonce());
+
         let (outcome, context_store) = message_context.drain();
         let (generated_dispatches, awakening) = outcome.drain();
 
         let info = ExtInfo {
             gas_amount: gas_counter.into(),
+            gas_reserver,
             allocations: wasm_pages,
             pages_data,
             generated_dispatches,
             awakening,
             context_store,
             program_candidates_data,
+            metrics,
+            events,
         };
         let trap_explanation = self
             .error_explanation
@@ -277,6 +347,16 @@ impl Ext {
         })
     }
 
+    fn check_not_in_signal(&mut self) -> Result<(), ProcessorError> {
+        // Signal handler runs on the system reservation,
+        // so it can't outlive the execution or spend reservations.
+        if self.context.message_context.kind().is_signal() {
+            self.return_and_store_err(Err(ExecutionError::ForbiddenInSignal))
+        } else {
+            Ok(())
+        }
+    }
+
     fn check_message_value(&mut self, message_value: u128) -> Result<(), ProcessorError> {
         let existential_deposit = self.context.existential_deposit;
         // Sending value should apply the range {0} ∪ [existential_deposit; +inf)
@@ -290,11 +370,21 @@ impl Ext {
         }
     }
 
-    fn charge_message_gas(&mut self, gas_limit: Option<GasLimit>) -> Result<(), ProcessorError> {
+    fn charge_message_gas(
+        &mut self,
+        kind: DispatchKind,
+        gas_limit: Option<GasLimit>,
+    ) -> Result<(), ProcessorError> {
         let mailbox_threshold = self.context.mailbox_threshold;
+        let minimal_gas_limit = self.context.gas_minimums.for_kind(kind);
         let gas_limit = gas_limit.unwrap_or(0);
 
-        if gas_limit != 0 && gas_limit < mailbox_threshold {
+        if gas_limit != 0 && gas_limit < minimal_gas_limit {
+            self.return_and_store_err(Err(MessageError::GasLimitBelowMinimum {
+                message_gas_limit: gas_limit,
+                minimal_gas_limit,
+            }))
+        } else if gas_limit != 0 && gas_limit < mailbox_threshold {
             self.return_and_store_err(Err(MessageError::InsufficientGasLimit {
                 message_gas_limit: gas_limit,
                 mailbox_threshold,
@@ -317,10 +407,55 @@ impl Ext {
         }
     }
 
+    /// Limit of outgoing messages, which also bounds other collections of the execution outcome.
+    fn outgoing_limit(&self) -> u32 {
+        self.context.message_context.settings().outgoing_limit()
+    }
+
+    fn reservation_send_commit(
+        &mut self,
+        id: ReservationId,
+        amount: u64,
+        msg: HandlePacket,
+    ) -> Result<MessageId, ProcessorError> {
+        // Gas limit of the message is taken from reservation, so only value is charged.
+        self.charge_message_value(msg.value())?;
+
+        let handle = self.send_init()? as u32;
+
+        let payload = msg
+            .payload()
+            .to_vec()
+            .try_into()
+            .expect("Payload of the packet is already bounded");
+        let packet = HandlePacket::new_with_gas(msg.destination(), payload, amount, msg.value());
+        let result = self
+            .context
+            .message_context
+            .reservation_send_commit(id, handle, packet);
+
+        self.return_and_store_err(result)
+    }
+
+    fn charge_message_fee(&mut self, fee: u64) -> Result<(), ProcessorError> {
+        use ChargeResult::*;
+
+        let common_charge = self.context.gas_counter.charge(fee);
+        let allowance_charge = self.context.gas_allowance_counter.charge(fee);
+
+        let res: Result<(), ProcessorError> = match (common_charge, allowance_charge) {
+            (NotEnough, _) => Err(ExecutionError::GasLimitExceeded.into()),
+            (Enough, NotEnough) => Err(TerminationReason::GasAllowanceExceeded.into()),
+            (Enough, Enough) => Ok(()),
+        };
+
+        self.return_and_store_err(res)
+    }
+
     fn charge_expiring_resources<T: Packet>(&mut self, packet: &T) -> Result<(), ProcessorError> {
         self.check_message_value(packet.value())?;
         // Charge for using expiring resources. Charge for calling sys-call was done earlier.
-        self.charge_message_gas(packet.gas_limit())?;
+        self.charge_message_gas(T::kind(), packet.gas_limit())?;
         self.charge_message_value(packet.value())?;
         Ok(())
     }
@@ -417,6 +552,37 @@ impl EnvExt for Ext {
         self.return_and_store_err(result)
     }
 
+    fn send_push_input(&mut self, handle: usize, offset: u32, len: u32) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SendPushInput(len))?;
+        let result = self
+            .context
+            .message_context
+            .send_push_input(handle as u32, offset, len);
+
+        self.return_and_store_err(result)
+    }
+
+    fn send_cancel(&mut self, handle: usize) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SendCancel)?;
+        let result = self.context.message_context.send_cancel(handle as u32);
+        let payload = self.return_and_store_err(result)?;
+
+        // Payload is discarded, so gas charged for its bytes is refunded.
+        // Bytes could be pushed from the input as well, so the lowest
+        // of per byte weights is refunded.
+        let weights = &self.context.host_fn_weights;
+        let refund = weights
+            .gr_send_push_per_byte
+            .min(weights.gr_send_push_input_per_byte)
+            .saturating_mul(payload.inner().len() as u64);
+
+        if self.context.gas_counter.refund(refund) == ChargeResult::Enough {
+            self.context.gas_allowance_counter.refund(refund);
+        }
+
+        Ok(())
+    }
+
     fn reply_push(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::ReplyPush(buffer.len() as u32))?;
         let result = self.context.message_context.reply_push(buffer);
@@ -424,9 +590,23 @@ impl EnvExt for Ext {
         self.return_and_store_err(result)
     }
 
+    fn reply_push_input(&mut self, offset: u32, len: u32) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ReplyPushInput(len))?;
+        let result = self.context.message_context.reply_push_input(offset, len);
+
+        self.return_and_store_err(result)
+    }
+
     fn send_commit(&mut self, handle: usize, msg: HandlePacket) -> Result<MessageId, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::SendCommit(msg.payload().len() as u32))?;
 
+        let fee = self
+            .context
+            .message_context
+            .settings()
+            .sending_fee(msg.payload().len());
+        self.charge_message_fee(fee)?;
+
         self.charge_expiring_resources(&msg)?;
 
         let result = self.context.message_context.send_commit(handle as u32, msg);
@@ -434,9 +614,45 @@ impl EnvExt for Ext {
         self.return_and_store_err(result)
     }
 
+    fn reservation_send(
+        &mut self,
+        id: ReservationId,
+        msg: HandlePacket,
+    ) -> Result<MessageId, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ReservationSend(msg.payload().len() as u32))?;
+        self.check_not_in_signal()?;
+
+        let fee = self
+            .context
+            .message_context
+            .settings()
+            .sending_fee(msg.payload().len());
+        self.charge_message_fee(fee)?;
+
+        self.check_message_value(msg.value())?;
+
+        let result = self.context.gas_reserver.take(id);
+        let slot = self.return_and_store_err(result)?;
+
+        // Reservation is kept, if the message isn't sent due to recoverable error.
+        let result = self.reservation_send_commit(id, slot.amount, msg);
+        if result.is_err() {
+            self.context.gas_reserver.put_back(id, slot);
+        }
+
+        result
+    }
+
     fn reply_commit(&mut self, msg: ReplyPacket) -> Result<MessageId, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::ReplyCommit(msg.payload().len() as u32))?;
 
+        let fee = self
+            .context
+            .message_context
+            .settings()
+            .reply_fee(msg.payload().len());
+        self.charge_message_fee(fee)?;
+
         self.charge_expiring_resources(&msg)?;
 
         let result = self.context.message_context.reply_commit(msg);
@@ -444,11 +660,21 @@ impl EnvExt for Ext {
         self.return_and_store_err(result)
     }
 
-    fn reply_to(&mut self) -> Result<Option<(MessageId, i32)>, Self::Error> {
+    fn reply_to(&mut self) -> Result<Option<ReplyDetails>, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::ReplyTo)?;
         Ok(self.context.message_context.current().reply())
     }
 
+    fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ExitCode)?;
+        Ok(self
+            .context
+            .message_context
+            .current()
+            .reply()
+            .map(|details| details.exit_code()))
+    }
+
     fn source(&mut self) -> Result<ProgramId, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Source)?;
         Ok(self.context.message_context.current().source())
@@ -470,6 +696,8 @@ impl EnvExt for Ext {
     }
 
     fn free(&mut self, page: WasmPageNumber) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::Free)?;
+
         let result = self.context.allocations_context.free(page);
 
         // Returns back gas for allocated page if it's new
@@ -491,8 +719,49 @@ impl EnvExt for Ext {
         Ok(())
     }
 
-    fn msg(&mut self) -> &[u8] {
-        self.context.message_context.current().payload()
+    fn metric_increment(&mut self, key: [u8; 32], value: u64) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::MetricIncrement)?;
+
+        let metrics = &self.context.metrics;
+        if !metrics.contains_key(&key) && metrics.len() as u32 >= self.outgoing_limit() {
+            return self.return_and_store_err(Err(ExecutionError::MetricsLimitExceeded));
+        }
+
+        let counter = self.context.metrics.entry(key).or_default();
+        *counter = counter.saturating_add(value);
+
+        Ok(())
+    }
+
+    fn emit_event(&mut self, event: ProgramEvent) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::EmitEvent(event.payload().len() as u32))?;
+
+        if self.context.events.len() as u32 >= self.outgoing_limit() {
+            return self.return_and_store_err(Err(ExecutionError::EventsLimitExceeded));
+        }
+
+        self.context.events.push(event);
+
+        Ok(())
+    }
+
+    fn read(&mut self, at: u32, len: u32) -> Result<&[u8], Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::Read(len))?;
+
+        let payload_len = self.context.message_context.current().payload().len();
+        let result = at
+            .checked_add(len)
+            .filter(|&end| end as usize <= payload_len)
+            .map(|end| at as usize..end as usize)
+            .ok_or(MessageError::OutOfBoundsInputRange);
+        let range = self.return_and_store_err(result)?;
+
+        Ok(&self.context.message_context.current().payload()[range])
+    }
+
+    fn size(&mut self) -> Result<usize, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::Size)?;
+        Ok(self.context.message_context.current().payload().len())
     }
 
     fn gas(&mut self, val: u32) -> Result<(), Self::Error> {
@@ -541,6 +810,58 @@ impl EnvExt for Ext {
         Ok(self.context.gas_counter.left())
     }
 
+    fn reserve_gas(&mut self, amount: u64, duration: u32) -> Result<ReservationId, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ReserveGas)?;
+        self.check_not_in_signal()?;
+
+        if duration == 0 {
+            return self.return_and_store_err(Err(ExecutionError::ZeroReservationDuration));
+        }
+
+        if self.context.gas_reserver.states().len() as u32 >= self.outgoing_limit() {
+            return self.return_and_store_err(Err(ExecutionError::ReservationsLimitExceeded));
+        }
+
+        if self.context.gas_counter.reduce(amount) != ChargeResult::Enough {
+            return self.return_and_store_err(Err(ExecutionError::InsufficientGasForReservation));
+        }
+
+        let expiration = self.context.block_info.height.saturating_add(duration);
+
+        Ok(self.context.gas_reserver.reserve(amount, expiration))
+    }
+
+    fn unreserve_gas(&mut self, id: ReservationId) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::UnreserveGas)?;
+
+        // Forgetting reservation, made within the execution, doesn't add a change.
+        let states = self.context.gas_reserver.states();
+        if !states.contains_key(&id) && states.len() as u32 >= self.outgoing_limit() {
+            return self.return_and_store_err(Err(ExecutionError::ReservationsLimitExceeded));
+        }
+
+        let result = self.context.gas_reserver.unreserve(id);
+
+        self.return_and_store_err(result)
+    }
+
+    fn system_reserve_gas(&mut self, amount: u64) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SystemReserveGas)?;
+        self.check_not_in_signal()?;
+
+        if self.context.message_context.system_reservation().is_some() {
+            return self.return_and_store_err(Err(ExecutionError::DuplicateSystemReservation));
+        }
+
+        if self.context.gas_counter.reduce(amount) != ChargeResult::Enough {
+            return self.return_and_store_err(Err(ExecutionError::InsufficientGasForReservation));
+        }
+
+        self.context.message_context.set_system_reservation(amount);
+
+        Ok(())
+    }
+
     fn value(&mut self) -> Result<u128, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Value)?;
         Ok(self.context.message_context.current().value())
@@ -558,19 +879,69 @@ impl EnvExt for Ext {
 
     fn wait(&mut self) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Wait)?;
+        self.check_not_in_signal()?;
+
+        if self.context.waitlist_full {
+            return self.return_and_store_err(Err(ExecutionError::WaitlistFull));
+        }
+
         Ok(())
     }
 
-    fn wake(&mut self, waker_id: MessageId) -> Result<(), Self::Error> {
+    fn wait_for(&mut self, duration: u32) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::WaitFor)?;
+        self.check_not_in_signal()?;
+
+        if duration == 0 {
+            return self.return_and_store_err(Err(ExecutionError::ZeroWaitDuration));
+        }
+
+        if self.context.waitlist_full {
+            return self.return_and_store_err(Err(ExecutionError::WaitlistFull));
+        }
+
+        Ok(())
+    }
+
+    fn wait_up_to(&mut self, duration: u32) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::WaitUpTo)?;
+        self.check_not_in_signal()?;
+
+        if duration == 0 {
+            return self.return_and_store_err(Err(ExecutionError::ZeroWaitDuration));
+        }
+
+        if self.context.waitlist_full {
+            return self.return_and_store_err(Err(ExecutionError::WaitlistFull));
+        }
+
+        Ok(())
+    }
+
+    fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Wake)?;
-        let result = self.context.message_context.wake(waker_id);
+
+        let fee = self.context.message_context.settings().waking_fee();
+        self.charge_message_fee(fee)?;
+
+        let result = self.context.message_context.wake(waker_id, delay);
 
         self.return_and_store_err(result)
     }
 
-    fn create_program(&mut self, packet: InitPacket) -> Result<ProgramId, Self::Error> {
+    fn create_program(
+        &mut self,
+        packet: InitPacket,
+    ) -> Result<(MessageId, ProgramId), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::CreateProgram(packet.payload().len() as u32))?;
 
+        let fee = self
+            .context
+            .message_context
+            .settings()
+            .sending_fee(packet.payload().len());
+        self.charge_message_fee(fee)?;
+
         self.charge_expiring_resources(&packet)?;
 
         let code_hash = packet.code_id();
@@ -589,7 +960,7 @@ impl EnvExt for Ext {
                         .or_default();
                     entry.push((new_prog_id, init_msg_id));
 
-                    new_prog_id
+                    (init_msg_id, new_prog_id)
                 });
 
         self.return_and_store_err(result)
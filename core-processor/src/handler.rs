@@ -44,13 +44,20 @@ pub fn handle_journal(
             JournalNote::SendDispatch {
                 message_id,
                 dispatch,
-            } => handler.send_dispatch(message_id, dispatch),
-            JournalNote::WaitDispatch(dispatch) => handler.wait_dispatch(dispatch),
+                delay,
+                reservation,
+            } => handler.send_dispatch(message_id, dispatch, delay, reservation),
+            JournalNote::WaitDispatch {
+                dispatch,
+                duration,
+                waited_type,
+            } => handler.wait_dispatch(dispatch, duration, waited_type),
             JournalNote::WakeMessage {
                 message_id,
                 program_id,
                 awakening_id,
-            } => handler.wake_message(message_id, program_id, awakening_id),
+                delay,
+            } => handler.wake_message(message_id, program_id, awakening_id, delay),
             JournalNote::UpdatePage {
                 program_id,
                 page_number,
@@ -67,13 +74,43 @@ pub fn handle_journal(
             }
             JournalNote::SendValue { from, to, value } => handler.send_value(from, to, value),
             JournalNote::StoreNewPrograms {
+                program_id,
                 code_hash,
                 candidates,
-            } => handler.store_new_programs(code_hash, candidates),
+            } => handler.store_new_programs(program_id, code_hash, candidates),
             JournalNote::StopProcessing {
                 dispatch,
                 gas_burned,
             } => handler.stop_processing(dispatch, gas_burned),
+            JournalNote::ReserveGas {
+                message_id,
+                reservation_id,
+                program_id,
+                amount,
+                expiration,
+            } => handler.reserve_gas(message_id, reservation_id, program_id, amount, expiration),
+            JournalNote::UnreserveGas {
+                reservation_id,
+                program_id,
+                expiration,
+            } => handler.unreserve_gas(reservation_id, program_id, expiration),
+            JournalNote::SystemReserveGas { message_id, amount } => {
+                handler.system_reserve_gas(message_id, amount)
+            }
+            JournalNote::SystemUnreserveGas { message_id } => {
+                handler.system_unreserve_gas(message_id)
+            }
+            JournalNote::SendSignal {
+                message_id,
+                destination,
+                exit_code,
+            } => handler.send_signal(message_id, destination, exit_code),
+            JournalNote::IncrementMetric {
+                program_id,
+                key,
+                value,
+            } => handler.increment_metric(program_id, key, value),
+            JournalNote::EmitEvent { program_id, event } => handler.emit_event(program_id, event),
         }
     }
 
@@ -39,18 +39,24 @@ pub const ERR_EXIT_CODE: ExitCode = 1;
 
 /// Destination isn't available for the message.
 ///
-/// These messages can be any of `init`,`handle`, `handle_reply`.
+/// These messages can be any of `init`,`handle`, `handle_reply`, `handle_signal`.
 /// If the message is `init` it means either:
 /// 1. Program tries to init program with non existing code hash.
 /// 2. Program tries to init terminated program.
-/// If the message is `handle` or `handle_reply` it means, that destination
+/// If the message is `handle`, `handle_reply` or `handle_signal` it means, that destination
 /// was terminated while the message was in the queue.
 pub const UNAVAILABLE_DEST_EXIT_CODE: ExitCode = 2;
 
 /// A try to init again initialized, existing program.
 pub const RE_INIT_EXIT_CODE: ExitCode = 3;
 
-pub use executor::execute_wasm;
+/// Signal exit code of a message, which execution ran out of gas.
+pub const OUT_OF_GAS_SIGNAL_CODE: ExitCode = 1;
+
+/// Signal exit code of a message, which execution trapped.
+pub const TRAP_SIGNAL_CODE: ExitCode = 2;
+
+pub use executor::{execute_for_meta_state, execute_for_reply, execute_wasm};
 pub use ext::{Ext, ProcessorContext, ProcessorError, ProcessorExt};
 pub use handler::handle_journal;
 pub use processor::process;
@@ -19,7 +19,7 @@
 use crate::{
     common::{
         Actor, DispatchOutcome, DispatchResult, DispatchResultKind, ExecutableActorData,
-        ExecutionErrorReason, JournalNote, WasmExecutionContext,
+        ExecutionErrorReason, JournalNote, SystemReservationContext, WasmExecutionContext,
     },
     configs::{BlockConfig, ExecutionSettings, MessageExecutionContext},
     executor,
@@ -27,18 +27,21 @@ use crate::{
 };
 use alloc::{string::ToString, vec::Vec};
 use codec::Encode;
-use gear_backend_common::{Environment, IntoExtInfo};
+use gear_backend_common::{Environment, IntoExtInfo, TrapExplanation};
 use gear_core::{
     env::Ext as EnvExt,
+    gas::GasReservationState,
     ids::{MessageId, ProgramId},
     message::{
-        DispatchKind, ExitCode, IncomingDispatch, ReplyMessage, ReplyPacket, StoredDispatch,
+        DispatchKind, ExitCode, IncomingDispatch, MessageWaitedType, ReplyMessage, ReplyPacket,
+        StoredDispatch,
     },
 };
+use gear_core_errors::{ErrorReplyReason, ExecutionError, ExtError};
 
 enum SuccessfulDispatchResultKind {
     Exit(ProgramId),
-    Wait,
+    Wait(Option<u32>, MessageWaitedType),
     Success,
 }
 
@@ -92,7 +95,9 @@ fn process_error(
     dispatch: IncomingDispatch,
     program_id: ProgramId,
     gas_burned: u64,
+    system_reservation_ctx: SystemReservationContext,
     err: ExecutionErrorReason,
+    value_escrowed: bool,
 ) -> Vec<JournalNote> {
     let mut journal = Vec::new();
 
@@ -111,8 +116,9 @@ fn process_error(
     // We also check if dispatch had context of previous executions:
     // it's existence shows that we have processed message after
     // being waken, so the value were already transferred in
-    // execution, where `gr_wait` was called.
-    if dispatch.context().is_none() && value != 0 {
+    // execution, where `gr_wait` was called. Escrowed value is
+    // never transferred before the end of execution.
+    if (dispatch.context().is_none() || value_escrowed) && value != 0 {
         // Send back value
         journal.push(JournalNote::SendValue {
             from: origin,
@@ -123,15 +129,45 @@ fn process_error(
 
     if !dispatch.is_reply() || dispatch.exit_code().expect("Checked before") == 0 {
         let id = MessageId::generate_reply(dispatch.id(), crate::ERR_EXIT_CODE);
-        let packet = ReplyPacket::system(err.encode(), crate::ERR_EXIT_CODE);
+        let payload = error_reply_reason(&err)
+            .encode()
+            .try_into()
+            .unwrap_or_default();
+        let packet = ReplyPacket::system(payload, crate::ERR_EXIT_CODE);
         let message = ReplyMessage::from_packet(id, packet);
 
         journal.push(JournalNote::SendDispatch {
             message_id,
             dispatch: message.into_dispatch(program_id, dispatch.source(), dispatch.id()),
+            delay: 0,
+            reservation: None,
         });
     }
 
+    if let Some(amount) = system_reservation_ctx.current_reservation {
+        journal.push(JournalNote::SystemReserveGas { message_id, amount });
+    }
+
+    if system_reservation_ctx.has_any() {
+        // Signal isn't sent for failed signals and for programs failed to initialize,
+        // so the reserved gas is just returned in these cases.
+        if matches!(dispatch.kind(), DispatchKind::Handle | DispatchKind::Reply) {
+            let exit_code = if is_out_of_gas(&err) {
+                crate::OUT_OF_GAS_SIGNAL_CODE
+            } else {
+                crate::TRAP_SIGNAL_CODE
+            };
+
+            journal.push(JournalNote::SendSignal {
+                message_id,
+                destination: program_id,
+                exit_code,
+            });
+        } else {
+            journal.push(JournalNote::SystemUnreserveGas { message_id });
+        }
+    }
+
     let outcome = match dispatch.kind() {
         DispatchKind::Init => DispatchOutcome::InitFailure {
             program_id,
@@ -153,23 +189,52 @@ fn process_error(
     journal
 }
 
+fn is_out_of_gas(err: &ExecutionErrorReason) -> bool {
+    matches!(
+        err,
+        ExecutionErrorReason::Ext(TrapExplanation::Core(ExtError::Execution(
+            ExecutionError::GasLimitExceeded
+        ))) | ExecutionErrorReason::InitialMemoryGasExceeded
+            | ExecutionErrorReason::GrowMemoryGasExceeded
+            | ExecutionErrorReason::LoadMemoryGasExceeded
+            | ExecutionErrorReason::PagesAccessGasExceeded
+    )
+}
+
+/// Reason sent to the message source within the error reply.
+fn error_reply_reason(err: &ExecutionErrorReason) -> ErrorReplyReason {
+    match err {
+        ExecutionErrorReason::NonExecutable => ErrorReplyReason::UnavailableActor,
+        ExecutionErrorReason::OutOfRent => ErrorReplyReason::OutOfRent,
+        ExecutionErrorReason::NotConsumed => ErrorReplyReason::NotConsumed,
+        err if is_out_of_gas(err) => ErrorReplyReason::OutOfGas(err.to_string()),
+        err => ErrorReplyReason::Trap(err.to_string()),
+    }
+}
+
 /// Helper function for journal creation in success case
 fn process_success(
     kind: SuccessfulDispatchResultKind,
     dispatch_result: DispatchResult,
+    value_escrowed: bool,
 ) -> Vec<JournalNote> {
     use SuccessfulDispatchResultKind::*;
 
+    let system_reservation_ctx = SystemReservationContext::from_dispatch_result(&dispatch_result);
+
     let DispatchResult {
         dispatch,
         generated_dispatches,
         awakening,
         program_candidates,
         gas_amount,
+        gas_reserver,
         page_update,
         program_id,
         context_store,
         allocations,
+        metrics,
+        events,
         ..
     } = dispatch_result;
 
@@ -191,7 +256,24 @@ fn process_success(
     // it's existence shows that we have processed message after
     // being waken, so the value were already transferred in
     // execution, where `gr_wait` was called.
-    if dispatch.context().is_none() && value != 0 {
+    //
+    // Escrowed value is transferred only on successful init
+    // and returned to the sender if program exited during it.
+    if value_escrowed {
+        let destination = match kind {
+            Wait(..) => None,
+            Success => Some(Some(program_id)),
+            Exit(_) => Some(None),
+        };
+
+        if let Some(to) = destination.filter(|_| value != 0) {
+            journal.push(JournalNote::SendValue {
+                from: origin,
+                to,
+                value,
+            });
+        }
+    } else if dispatch.context().is_none() && value != 0 {
         // Send value further
         journal.push(JournalNote::SendValue {
             from: origin,
@@ -203,23 +285,48 @@ fn process_success(
     // Must be handled before handling generated dispatches.
     for (code_hash, candidates) in program_candidates {
         journal.push(JournalNote::StoreNewPrograms {
+            program_id,
             code_hash,
             candidates,
         });
     }
 
-    for dispatch in generated_dispatches {
+    // Must be handled before handling generated dispatches,
+    // because they could be sent from created reservations.
+    for (reservation_id, state) in gas_reserver.into_states() {
+        let note = match state {
+            GasReservationState::Created { amount, expiration } => JournalNote::ReserveGas {
+                message_id,
+                reservation_id,
+                program_id,
+                amount,
+                expiration,
+            },
+            GasReservationState::Removed { expiration } => JournalNote::UnreserveGas {
+                reservation_id,
+                program_id,
+                expiration,
+            },
+        };
+
+        journal.push(note);
+    }
+
+    for (dispatch, delay, reservation) in generated_dispatches {
         journal.push(JournalNote::SendDispatch {
             message_id,
             dispatch,
+            delay,
+            reservation,
         });
     }
 
-    for awakening_id in awakening {
+    for (awakening_id, delay) in awakening {
         journal.push(JournalNote::WakeMessage {
             message_id,
             program_id,
             awakening_id,
+            delay,
         });
     }
 
@@ -238,11 +345,34 @@ fn process_success(
         });
     }
 
+    for (key, value) in metrics {
+        journal.push(JournalNote::IncrementMetric {
+            program_id,
+            key,
+            value,
+        });
+    }
+
+    for event in events {
+        journal.push(JournalNote::EmitEvent { program_id, event });
+    }
+
+    if let Some(amount) = system_reservation_ctx.current_reservation {
+        journal.push(JournalNote::SystemReserveGas { message_id, amount });
+    }
+
+    // Reservation is kept for the next executions of the waiting message.
+    if system_reservation_ctx.has_any() && !matches!(kind, Wait(..)) {
+        journal.push(JournalNote::SystemUnreserveGas { message_id });
+    }
+
     let outcome = match kind {
-        Wait => {
-            journal.push(JournalNote::WaitDispatch(
-                dispatch.into_stored(program_id, context_store),
-            ));
+        Wait(duration, waited_type) => {
+            journal.push(JournalNote::WaitDispatch {
+                dispatch: dispatch.into_stored(program_id, context_store),
+                duration,
+                waited_type,
+            });
 
             return journal;
         }
@@ -284,9 +414,13 @@ pub fn process_executable<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: E
         allocations_config,
         existential_deposit,
         outgoing_limit,
+        outgoing_bytes_limit,
+        program_candidates_limit,
+        outgoing_scaling,
         host_fn_weights,
         forbidden_funcs,
         mailbox_threshold,
+        gas_minimums,
     } = block_config;
 
     let execution_settings = ExecutionSettings::new(
@@ -296,14 +430,27 @@ pub fn process_executable<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: E
         host_fn_weights,
         forbidden_funcs,
         mailbox_threshold,
+        gas_minimums,
     );
     let execution_context = WasmExecutionContext {
         origin,
         gas_allowance,
     };
-    let msg_ctx_settings = gear_core::message::ContextSettings::new(0, outgoing_limit);
+    let outgoing_limit = outgoing_scaling
+        .map(|scaling| scaling.limit(dispatch.gas_limit(), outgoing_limit))
+        .unwrap_or(outgoing_limit);
+    let msg_ctx_settings = gear_core::message::ContextSettings::new(
+        0,
+        0,
+        0,
+        0,
+        outgoing_limit,
+        outgoing_bytes_limit,
+        program_candidates_limit,
+    );
 
     let program_id = data.program.id();
+    let value_escrowed = execution_context.holds_value_in_escrow(&dispatch);
 
     let exec_result = executor::execute_wasm::<A, E>(
         balance,
@@ -319,29 +466,48 @@ pub fn process_executable<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: E
     });
 
     match exec_result {
-        Ok(res) => match res.kind {
-            DispatchResultKind::Trap(reason) => process_error(
-                res.dispatch,
-                program_id,
-                res.gas_amount.burned(),
-                ExecutionErrorReason::Ext(reason),
-            ),
-            DispatchResultKind::Success => process_success(Success, res),
-            DispatchResultKind::Wait => process_success(Wait, res),
-            DispatchResultKind::Exit(value_destination) => {
-                process_success(Exit(value_destination), res)
-            }
-            DispatchResultKind::GasAllowanceExceed => {
-                process_allowance_exceed(dispatch, program_id, res.gas_amount.burned())
+        Ok(res) => {
+            let system_reservation_ctx = SystemReservationContext::from_dispatch_result(&res);
+
+            match res.kind {
+                DispatchResultKind::Trap(reason) => process_error(
+                    res.dispatch,
+                    program_id,
+                    res.gas_amount.burned(),
+                    system_reservation_ctx,
+                    ExecutionErrorReason::Ext(reason),
+                    value_escrowed,
+                ),
+                DispatchResultKind::Success => process_success(Success, res, value_escrowed),
+                DispatchResultKind::Wait(duration, waited_type) => {
+                    process_success(Wait(duration, waited_type), res, value_escrowed)
+                }
+                DispatchResultKind::Exit(value_destination) => {
+                    process_success(Exit(value_destination), res, value_escrowed)
+                }
+                DispatchResultKind::GasAllowanceExceed => {
+                    process_allowance_exceed(dispatch, program_id, res.gas_amount.burned())
+                }
             }
-        },
+        }
         Err(e) => match e.reason {
             ExecutionErrorReason::InitialMemoryBlockGasExceeded
             | ExecutionErrorReason::GrowMemoryBlockGasExceeded
             | ExecutionErrorReason::LoadMemoryBlockGasExceeded => {
                 process_allowance_exceed(dispatch, program_id, e.gas_amount.burned())
             }
-            _ => process_error(dispatch, program_id, e.gas_amount.burned(), e.reason),
+            _ => {
+                let system_reservation_ctx = SystemReservationContext::from_dispatch(&dispatch);
+
+                process_error(
+                    dispatch,
+                    program_id,
+                    e.gas_amount.burned(),
+                    system_reservation_ctx,
+                    e.reason,
+                    value_escrowed,
+                )
+            }
         },
     }
 }
@@ -372,7 +538,7 @@ fn process_non_executable(
     exit_code: ExitCode,
 ) -> Vec<JournalNote> {
     // Number of notes is predetermined
-    let mut journal = Vec::with_capacity(4);
+    let mut journal = Vec::with_capacity(5);
 
     let message_id = dispatch.id();
     let source = dispatch.source();
@@ -390,15 +556,25 @@ fn process_non_executable(
     // Reply back to the message `source`
     if !dispatch.is_reply() || dispatch.exit_code().expect("Checked before") == 0 {
         let id = MessageId::generate_reply(dispatch.id(), exit_code);
-        let packet = ReplyPacket::system(ExecutionErrorReason::NonExecutable.encode(), exit_code);
+        let payload = ErrorReplyReason::UnavailableActor
+            .encode()
+            .try_into()
+            .unwrap_or_default();
+        let packet = ReplyPacket::system(payload, exit_code);
         let message = ReplyMessage::from_packet(id, packet);
 
         journal.push(JournalNote::SendDispatch {
             message_id,
             dispatch: message.into_dispatch(program_id, dispatch.source(), dispatch.id()),
+            delay: 0,
+            reservation: None,
         });
     }
 
+    if SystemReservationContext::from_dispatch(&dispatch).has_any() {
+        journal.push(JournalNote::SystemUnreserveGas { message_id });
+    }
+
     journal.push(JournalNote::MessageDispatched {
         message_id,
         source,
@@ -0,0 +1,365 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Declarative DSL for core-processor integration tests.
+//!
+//! Test case is described in terms of
+//! "given programs with such code and pages,
+//! when such dispatch is processed,
+//! expect such journal notes":
+//!
+//! ```ignore
+//! given()
+//!     .program(PROGRAM, WAT)
+//!     .when(Dispatch::handle(USER, PROGRAM))
+//!     .expect(journal![JournalNote::MessageConsumed(_)]);
+//! ```
+
+// Test files use only the builder methods they need.
+#![allow(dead_code)]
+
+use core_processor::{
+    common::{Actor, ExecutableActorData, JournalNote},
+    configs::{BlockConfig, BlockInfo, MessageExecutionContext},
+    Ext,
+};
+use gear_backend_wasmtime::WasmtimeEnvironment;
+use gear_core::{
+    code::{Code, CodeAndId, InstrumentedCodeAndId},
+    ids::{MessageId, ProgramId},
+    memory::{PageBuf, PageNumber},
+    message::{
+        ContextStore, DispatchKind, ExitCode, GasLimit, IncomingDispatch, IncomingMessage, Payload,
+        ReplyDetails, Value,
+    },
+    program::Program,
+};
+use std::collections::BTreeMap;
+use wasm_instrument::gas_metering::ConstantCostRules;
+
+/// Default gas limit of the dispatch.
+pub const GAS_LIMIT: GasLimit = 1_000_000_000;
+
+/// Default gas allowance of the block.
+pub const GAS_ALLOWANCE: u64 = u64::MAX;
+
+/// Boxed predicate over the journal note.
+pub type Matcher = Box<dyn Fn(&JournalNote) -> bool>;
+
+/// Creates list of journal note matchers from patterns.
+macro_rules! journal {
+    ($($pat:pat $(if $guard:expr)?),* $(,)?) => {
+        vec![$(
+            Box::new(|note: &core_processor::common::JournalNote| {
+                matches!(note, $pat $(if $guard)?)
+            }) as $crate::dsl::Matcher
+        ),*]
+    };
+}
+
+/// State of the program before the dispatch processing.
+enum ProgramState {
+    Active {
+        program: Program,
+        pages_data: BTreeMap<PageNumber, PageBuf>,
+        balance: Value,
+    },
+    Terminated,
+}
+
+/// Builds program code from wat text.
+pub fn code(wat: &str) -> Code {
+    let binary = wabt::Wat2Wasm::new()
+        .validate(false)
+        .convert(wat)
+        .expect("failed to parse module")
+        .as_ref()
+        .to_vec();
+
+    Code::try_new(binary, 1, |_| ConstantCostRules::default()).expect("invalid code")
+}
+
+/// Dispatch to be processed.
+pub struct Dispatch {
+    kind: DispatchKind,
+    source: ProgramId,
+    origin: Option<ProgramId>,
+    destination: ProgramId,
+    id: MessageId,
+    payload: Payload,
+    gas_limit: GasLimit,
+    value: Value,
+    reply: Option<ReplyDetails>,
+    context: Option<ContextStore>,
+}
+
+impl Dispatch {
+    fn new(kind: DispatchKind, source: ProgramId, destination: ProgramId) -> Self {
+        Self {
+            kind,
+            source,
+            origin: None,
+            destination,
+            id: MessageId::from(1),
+            payload: Default::default(),
+            gas_limit: GAS_LIMIT,
+            value: 0,
+            reply: None,
+            context: None,
+        }
+    }
+
+    /// Init dispatch from `source` to `destination`.
+    pub fn init(source: ProgramId, destination: ProgramId) -> Self {
+        Self::new(DispatchKind::Init, source, destination)
+    }
+
+    /// Handle dispatch from `source` to `destination`.
+    pub fn handle(source: ProgramId, destination: ProgramId) -> Self {
+        Self::new(DispatchKind::Handle, source, destination)
+    }
+
+    /// Reply dispatch from `source` to `destination` on message `reply_to`.
+    pub fn reply(
+        source: ProgramId,
+        destination: ProgramId,
+        reply_to: MessageId,
+        exit_code: ExitCode,
+    ) -> Self {
+        Self {
+            reply: Some(ReplyDetails::new(reply_to, exit_code)),
+            ..Self::new(DispatchKind::Reply, source, destination)
+        }
+    }
+
+    /// Sets original user of the messages chain, `source` by default.
+    pub fn origin(self, origin: ProgramId) -> Self {
+        Self {
+            origin: Some(origin),
+            ..self
+        }
+    }
+
+    /// Sets message id.
+    pub fn id(self, id: MessageId) -> Self {
+        Self { id, ..self }
+    }
+
+    /// Sets payload.
+    pub fn payload(self, payload: impl Into<Vec<u8>>) -> Self {
+        Self {
+            payload: Payload::try_from(payload.into()).expect("Payload size limit exceeded"),
+            ..self
+        }
+    }
+
+    /// Sets gas limit.
+    pub fn gas_limit(self, gas_limit: GasLimit) -> Self {
+        Self { gas_limit, ..self }
+    }
+
+    /// Sets value.
+    pub fn value(self, value: Value) -> Self {
+        Self { value, ..self }
+    }
+
+    /// Sets context of previous executions.
+    pub fn context(self, context: ContextStore) -> Self {
+        Self {
+            context: Some(context),
+            ..self
+        }
+    }
+
+    fn into_incoming(self) -> (ProgramId, IncomingDispatch) {
+        let message = IncomingMessage::new(
+            self.id,
+            self.source,
+            self.payload,
+            self.gas_limit,
+            self.value,
+            self.reply,
+        );
+
+        (
+            self.destination,
+            IncomingDispatch::new(self.kind, message, self.context),
+        )
+    }
+}
+
+/// Starts description of the test case.
+pub fn given() -> Given {
+    Given {
+        programs: Default::default(),
+        block_config: BlockConfig {
+            block_info: BlockInfo {
+                height: 1,
+                timestamp: 1,
+            },
+            allocations_config: Default::default(),
+            existential_deposit: 0,
+            outgoing_limit: 1024,
+            outgoing_bytes_limit: 64 * 1024 * 1024,
+            program_candidates_limit: 128,
+            outgoing_scaling: None,
+            host_fn_weights: Default::default(),
+            forbidden_funcs: Default::default(),
+            mailbox_threshold: 0,
+            gas_minimums: Default::default(),
+        },
+        gas_allowance: GAS_ALLOWANCE,
+    }
+}
+
+/// Initial state of the test case.
+pub struct Given {
+    programs: BTreeMap<ProgramId, ProgramState>,
+    block_config: BlockConfig,
+    gas_allowance: u64,
+}
+
+impl Given {
+    /// Adds initialized program with given wat code.
+    pub fn program(self, id: ProgramId, wat: &str) -> Self {
+        self.program_with_state(id, wat, true)
+    }
+
+    /// Adds uninitialized program with given wat code.
+    pub fn uninitialized_program(self, id: ProgramId, wat: &str) -> Self {
+        self.program_with_state(id, wat, false)
+    }
+
+    fn program_with_state(mut self, id: ProgramId, wat: &str, initialized: bool) -> Self {
+        let code = InstrumentedCodeAndId::from(CodeAndId::new(code(wat)))
+            .into_parts()
+            .0;
+        let program = Program::from_parts(id, code, Default::default(), initialized);
+
+        self.programs.insert(
+            id,
+            ProgramState::Active {
+                program,
+                pages_data: Default::default(),
+                balance: 0,
+            },
+        );
+        self
+    }
+
+    /// Marks program as terminated one.
+    pub fn terminated(mut self, id: ProgramId) -> Self {
+        self.programs.insert(id, ProgramState::Terminated);
+        self
+    }
+
+    /// Sets balance of the active program.
+    pub fn balance(mut self, id: ProgramId, value: Value) -> Self {
+        match self.programs.get_mut(&id) {
+            Some(ProgramState::Active { balance, .. }) => *balance = value,
+            _ => panic!("Balance could be set only for active program"),
+        }
+        self
+    }
+
+    /// Sets block gas allowance.
+    pub fn gas_allowance(self, gas_allowance: u64) -> Self {
+        Self {
+            gas_allowance,
+            ..self
+        }
+    }
+
+    /// Processes the dispatch.
+    pub fn when(mut self, dispatch: Dispatch) -> Then {
+        let origin = dispatch.origin.unwrap_or(dispatch.source);
+        let (destination, dispatch) = dispatch.into_incoming();
+
+        let (balance, executable_data) = match self.programs.remove(&destination) {
+            Some(ProgramState::Active {
+                program,
+                pages_data,
+                balance,
+            }) => (
+                balance,
+                Some(ExecutableActorData {
+                    program,
+                    pages_data,
+                    gas_reservation_map: Default::default(),
+                    waitlist_full: false,
+                }),
+            ),
+            Some(ProgramState::Terminated) | None => (0, None),
+        };
+
+        let context = MessageExecutionContext {
+            actor: Actor {
+                balance,
+                destination_program: destination,
+                executable_data,
+            },
+            dispatch,
+            origin,
+            gas_allowance: self.gas_allowance,
+        };
+
+        let journal =
+            core_processor::process::<Ext, WasmtimeEnvironment<Ext>>(&self.block_config, context);
+
+        Then { journal }
+    }
+}
+
+/// Result of the dispatch processing.
+pub struct Then {
+    journal: Vec<JournalNote>,
+}
+
+impl Then {
+    /// Returns produced journal.
+    pub fn journal(&self) -> &[JournalNote] {
+        &self.journal
+    }
+
+    /// Checks that journal contains notes matching `matchers` in the same order.
+    pub fn expect(self, matchers: Vec<Matcher>) -> Self {
+        let mut notes = self.journal.iter();
+
+        for (i, matcher) in matchers.iter().enumerate() {
+            if !notes.any(|note| matcher(note)) {
+                panic!(
+                    "Journal note #{} wasn't found (in order) in journal: {:#?}",
+                    i, self.journal
+                );
+            }
+        }
+
+        self
+    }
+
+    /// Checks that journal contains no notes matching any of `matchers`.
+    pub fn expect_none(self, matchers: Vec<Matcher>) -> Self {
+        for (i, matcher) in matchers.iter().enumerate() {
+            if let Some(note) = self.journal.iter().find(|note| matcher(note)) {
+                panic!("Unexpected journal note #{} found: {:#?}", i, note);
+            }
+        }
+
+        self
+    }
+}
@@ -0,0 +1,329 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Processor edge cases described with the declarative test DSL.
+
+#[macro_use]
+mod dsl;
+
+use core_processor::common::{DispatchOutcome, JournalNote};
+use dsl::{given, Dispatch};
+use gear_core::ids::{MessageId, ProgramId};
+use gear_core_errors::ErrorReplyReason;
+
+const USER: u64 = 1;
+const PROGRAM: u64 = 2;
+const FACTORY: u64 = 3;
+
+fn user() -> ProgramId {
+    USER.into()
+}
+
+fn program() -> ProgramId {
+    PROGRAM.into()
+}
+
+fn factory() -> ProgramId {
+    FACTORY.into()
+}
+
+const EXIT_IN_REPLY: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (import "env" "gr_exit" (func $exit (param i32)))
+        (export "handle" (func $handle))
+        (export "handle_reply" (func $handle_reply))
+        (func $handle)
+        (func $handle_reply
+            i32.const 0
+            call $exit
+        )
+    )"#;
+
+const WAKE_ZERO_ID: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (import "env" "gr_wake" (func $wake (param i32) (result i32)))
+        (export "handle" (func $handle))
+        (func $handle
+            i32.const 0
+            call $wake
+            drop
+        )
+    )"#;
+
+const EMPTY: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (export "init" (func $init))
+        (export "handle" (func $handle))
+        (func $init)
+        (func $handle)
+    )"#;
+
+const TRAP_IN_INIT: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (export "init" (func $init))
+        (func $init
+            unreachable
+        )
+    )"#;
+
+const WAIT_IN_INIT: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (import "env" "gr_wait" (func $wait))
+        (export "init" (func $init))
+        (func $init
+            call $wait
+        )
+    )"#;
+
+const TRAP_AFTER_SYSTEM_RESERVATION: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (import "env" "gr_system_reserve_gas" (func $system_reserve_gas (param i64) (result i32)))
+        (export "handle" (func $handle))
+        (func $handle
+            i64.const 1000
+            call $system_reserve_gas
+            drop
+            unreachable
+        )
+    )"#;
+
+const SYSTEM_RESERVATION: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (import "env" "gr_system_reserve_gas" (func $system_reserve_gas (param i64) (result i32)))
+        (export "handle" (func $handle))
+        (func $handle
+            i64.const 1000
+            call $system_reserve_gas
+            drop
+        )
+    )"#;
+
+const TRAP_AFTER_METRIC_INCREMENT: &str = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (import "env" "gr_metric_increment" (func $metric_increment (param i32 i64)))
+        (export "handle" (func $handle))
+        (func $handle
+            i32.const 0
+            i64.const 1
+            call $metric_increment
+            unreachable
+        )
+    )"#;
+
+#[test]
+fn exit_during_reply() {
+    given()
+        .program(program(), EXIT_IN_REPLY)
+        .when(Dispatch::reply(user(), program(), MessageId::from(42), 0))
+        .expect(journal![
+            JournalNote::GasBurned { .. },
+            JournalNote::ExitDispatch { id_exited, value_destination }
+                if *id_exited == program() && *value_destination == ProgramId::default(),
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::Exit { program_id },
+                ..
+            } if *program_id == program(),
+            JournalNote::MessageConsumed(_),
+        ])
+        .expect_none(journal![JournalNote::SendDispatch { .. }]);
+}
+
+#[test]
+fn wake_of_nonexistent_message() {
+    given()
+        .program(program(), WAKE_ZERO_ID)
+        .when(Dispatch::handle(user(), program()))
+        .expect(journal![
+            JournalNote::WakeMessage { program_id, awakening_id, .. }
+                if *program_id == program() && *awakening_id == MessageId::default(),
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::Success,
+                ..
+            },
+            JournalNote::MessageConsumed(_),
+        ]);
+}
+
+#[test]
+fn init_while_terminated() {
+    given()
+        .terminated(program())
+        .when(Dispatch::init(user(), program()).value(1_000))
+        .expect(journal![
+            JournalNote::SendValue { from, to: None, value: 1_000 } if *from == user(),
+            JournalNote::SendDispatch { dispatch, .. }
+                if dispatch.exit_code() == Some(core_processor::UNAVAILABLE_DEST_EXIT_CODE),
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::NoExecution,
+                ..
+            },
+            JournalNote::MessageConsumed(_),
+        ])
+        .expect_none(journal![JournalNote::GasBurned { .. }]);
+}
+
+#[test]
+fn reinit_of_initialized_program() {
+    given()
+        .program(program(), EMPTY)
+        .when(Dispatch::init(user(), program()))
+        .expect(journal![
+            JournalNote::SendDispatch { dispatch, .. }
+                if dispatch.exit_code() == Some(core_processor::RE_INIT_EXIT_CODE),
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::NoExecution,
+                ..
+            },
+        ]);
+}
+
+#[test]
+fn init_of_uninitialized_program() {
+    given()
+        .uninitialized_program(program(), EMPTY)
+        .when(Dispatch::init(user(), program()))
+        .expect(journal![
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::InitSuccess { program_id },
+                ..
+            } if *program_id == program(),
+            JournalNote::MessageConsumed(_),
+        ]);
+}
+
+#[test]
+fn error_replies_carry_reason() {
+    given()
+        .terminated(program())
+        .when(Dispatch::handle(user(), program()))
+        .expect(journal![
+            JournalNote::SendDispatch { dispatch, .. }
+                if ErrorReplyReason::from_payload(dispatch.payload())
+                    == Some(ErrorReplyReason::UnavailableActor),
+        ]);
+
+    given()
+        .uninitialized_program(program(), TRAP_IN_INIT)
+        .when(Dispatch::init(user(), program()))
+        .expect(journal![
+            JournalNote::SendDispatch { dispatch, .. }
+                if matches!(
+                    ErrorReplyReason::from_payload(dispatch.payload()),
+                    Some(ErrorReplyReason::Trap(_))
+                ),
+        ]);
+}
+
+#[test]
+fn child_init_value_transferred_on_success() {
+    given()
+        .uninitialized_program(program(), EMPTY)
+        .when(
+            Dispatch::init(factory(), program())
+                .origin(user())
+                .value(1_000),
+        )
+        .expect(journal![
+            JournalNote::SendValue { from, to: Some(to), value: 1_000 }
+                if *from == factory() && *to == program(),
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::InitSuccess { .. },
+                ..
+            },
+        ]);
+}
+
+#[test]
+fn child_init_value_returned_to_factory_on_failure() {
+    given()
+        .uninitialized_program(program(), TRAP_IN_INIT)
+        .when(
+            Dispatch::init(factory(), program())
+                .origin(user())
+                .value(1_000),
+        )
+        .expect(journal![
+            JournalNote::SendValue { from, to: None, value: 1_000 } if *from == factory(),
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::InitFailure { .. },
+                ..
+            },
+        ])
+        .expect_none(journal![JournalNote::SendValue { to: Some(_), .. }]);
+}
+
+#[test]
+fn child_init_value_held_while_waiting() {
+    given()
+        .uninitialized_program(program(), WAIT_IN_INIT)
+        .when(
+            Dispatch::init(factory(), program())
+                .origin(user())
+                .value(1_000),
+        )
+        .expect(journal![JournalNote::WaitDispatch { .. }])
+        .expect_none(journal![JournalNote::SendValue { .. }]);
+}
+
+#[test]
+fn signal_sent_on_trap_with_system_reservation() {
+    given()
+        .program(program(), TRAP_AFTER_SYSTEM_RESERVATION)
+        .when(Dispatch::handle(user(), program()))
+        .expect(journal![
+            JournalNote::SystemReserveGas { amount: 1_000, .. },
+            JournalNote::SendSignal { destination, exit_code: 2, .. } if *destination == program(),
+            JournalNote::MessageDispatched {
+                outcome: DispatchOutcome::MessageTrap { .. },
+                ..
+            },
+        ])
+        .expect_none(journal![JournalNote::SystemUnreserveGas { .. }]);
+}
+
+#[test]
+fn system_reservation_returned_on_success() {
+    given()
+        .program(program(), SYSTEM_RESERVATION)
+        .when(Dispatch::handle(user(), program()))
+        .expect(journal![
+            JournalNote::SystemReserveGas { amount: 1_000, .. },
+            JournalNote::SystemUnreserveGas { .. },
+        ])
+        .expect_none(journal![JournalNote::SendSignal { .. }]);
+}
+
+#[test]
+fn metrics_dropped_on_trap() {
+    given()
+        .program(program(), TRAP_AFTER_METRIC_INCREMENT)
+        .when(Dispatch::handle(user(), program()))
+        .expect(journal![JournalNote::MessageDispatched {
+            outcome: DispatchOutcome::MessageTrap { .. },
+            ..
+        }])
+        .expect_none(journal![JournalNote::IncrementMetric { .. }]);
+}
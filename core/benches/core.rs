@@ -0,0 +1,203 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Microbenchmarks of the core hot paths.
+//!
+//! Results are collected by `regression-analysis collect-data --criterion`
+//! and compared with `regression-analysis compare --criterion`.
+
+use codec::{Decode, Encode};
+use core::convert::TryInto;
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use gear_core::{
+    gas::{GasAllowanceCounter, GasCounter},
+    ids::{MessageId, ProgramId},
+    message::{
+        DispatchKind, HandlePacket, IncomingMessage, MessageContext, ReplyPacket, StoredMessage,
+    },
+};
+
+const PAYLOAD_SIZES: [usize; 4] = [0, 1024, 16 * 1024, 64 * 1024];
+
+const MESSAGES_AMOUNT: u32 = 128;
+
+fn message_context() -> MessageContext {
+    let message = IncomingMessage::new(
+        MessageId::from(1),
+        ProgramId::from(2),
+        vec![0; 32].try_into().unwrap(),
+        1_000_000,
+        0,
+        None,
+    );
+
+    MessageContext::new(DispatchKind::Handle, message, ProgramId::from(3), None)
+}
+
+fn message_context_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("message_context");
+
+    group.bench_function("send_init", |b| {
+        b.iter_batched(
+            message_context,
+            |mut context| {
+                for _ in 0..MESSAGES_AMOUNT {
+                    black_box(context.send_init().unwrap());
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("send_commit", |b| {
+        b.iter_batched(
+            message_context,
+            |mut context| {
+                for _ in 0..MESSAGES_AMOUNT {
+                    let handle = context.send_init().unwrap();
+                    let packet = HandlePacket::new(ProgramId::from(4), Default::default(), 0);
+                    black_box(context.send_commit(handle, packet).unwrap());
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    for size in PAYLOAD_SIZES {
+        let payload = vec![42; size];
+
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("send_push", size),
+            &payload,
+            |b, payload| {
+                b.iter_batched(
+                    || {
+                        let mut context = message_context();
+                        let handle = context.send_init().unwrap();
+                        (context, handle)
+                    },
+                    |(mut context, handle)| context.send_push(handle, black_box(payload)).unwrap(),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("reply_push", size),
+            &payload,
+            |b, payload| {
+                b.iter_batched(
+                    message_context,
+                    |mut context| context.reply_push(black_box(payload)).unwrap(),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("reply_commit", size),
+            &payload,
+            |b, payload| {
+                b.iter_batched(
+                    || {
+                        (
+                            message_context(),
+                            ReplyPacket::new(payload.clone().try_into().unwrap(), 0),
+                        )
+                    },
+                    |(mut context, packet)| black_box(context.reply_commit(packet).unwrap()),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn payload_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("payload");
+
+    for size in PAYLOAD_SIZES {
+        let message = StoredMessage::new(
+            MessageId::from(1),
+            ProgramId::from(2),
+            ProgramId::from(3),
+            vec![42; size].try_into().unwrap(),
+            0,
+            None,
+        );
+        let encoded = message.encode();
+
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("encode", size), &message, |b, message| {
+            b.iter(|| black_box(message.encode()))
+        });
+
+        group.bench_with_input(BenchmarkId::new("decode", size), &encoded, |b, encoded| {
+            b.iter(|| StoredMessage::decode(&mut black_box(&encoded[..])).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn gas_counter_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gas_counter");
+
+    group.bench_function("charge", |b| {
+        let mut counter = GasCounter::new(u64::MAX);
+        b.iter(|| counter.charge(black_box(1_000)))
+    });
+
+    group.bench_function("reduce", |b| {
+        let mut counter = GasCounter::new(u64::MAX);
+        b.iter(|| counter.reduce(black_box(1_000)))
+    });
+
+    group.bench_function("charge_refund", |b| {
+        let mut counter = GasCounter::new(u64::MAX);
+        b.iter(|| {
+            counter.charge(black_box(1_000));
+            counter.refund(black_box(1_000))
+        })
+    });
+
+    group.bench_function("allowance_charge_refund", |b| {
+        let mut counter = GasAllowanceCounter::new(u64::MAX);
+        b.iter(|| {
+            counter.charge(black_box(1_000));
+            counter.refund(black_box(1_000))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    message_context_benches,
+    payload_benches,
+    gas_counter_benches
+);
+criterion_main!(benches);
@@ -0,0 +1,162 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Vector with limited length.
+
+use alloc::vec::Vec;
+use codec::{Compact, Decode, Encode, Error as CodecError, Input};
+use core::{
+    convert::TryFrom,
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+use scale_info::TypeInfo;
+
+/// Vector, which length never exceeds `N`.
+///
+/// `E` is the error returned on attempt to exceed the limit.
+#[derive(Clone, Default, Eq, Hash, Ord, PartialEq, PartialOrd, Encode, TypeInfo)]
+#[scale_info(skip_type_params(E))]
+pub struct LimitedVec<T, E, const N: usize>(Vec<T>, PhantomData<E>);
+
+impl<T: Debug, E, const N: usize> Debug for LimitedVec<T, E, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Length is checked before decoding elements,
+// so encoded vector can't exceed the limit.
+impl<T: Decode, E, const N: usize> Decode for LimitedVec<T, E, N> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, CodecError> {
+        let len = <Compact<u32>>::decode(input)?.0 as usize;
+
+        if len > N {
+            return Err("LimitedVec exceeds its limit".into());
+        }
+
+        codec::decode_vec_with_len(input, len).map(|vec| Self(vec, PhantomData))
+    }
+}
+
+impl<T, E: Default, const N: usize> TryFrom<Vec<T>> for LimitedVec<T, E, N> {
+    type Error = E;
+
+    fn try_from(vec: Vec<T>) -> Result<Self, Self::Error> {
+        if vec.len() > N {
+            Err(E::default())
+        } else {
+            Ok(Self(vec, PhantomData))
+        }
+    }
+}
+
+impl<T, E, const N: usize> AsRef<[T]> for LimitedVec<T, E, N> {
+    fn as_ref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T, E, const N: usize> LimitedVec<T, E, N> {
+    /// Maximal length of the vector.
+    pub const MAX_LEN: usize = N;
+
+    /// Creates new empty vector.
+    pub fn new() -> Self {
+        Self(Vec::new(), PhantomData)
+    }
+
+    /// Returns reference to the inner data.
+    pub fn inner(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Consumes self, returning the inner vector.
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+
+    /// Prepends elements of the other vector, if the limit allows.
+    ///
+    /// Returns the other vector back otherwise.
+    pub fn try_prepend(&mut self, other: Self) -> Result<(), Self> {
+        if self.0.len().saturating_add(other.0.len()) > N {
+            return Err(other);
+        }
+
+        self.0.splice(0..0, other.0);
+
+        Ok(())
+    }
+}
+
+impl<T: Clone, E: Default, const N: usize> LimitedVec<T, E, N> {
+    /// Appends elements of the slice, if the limit allows.
+    pub fn try_extend_from_slice(&mut self, slice: &[T]) -> Result<(), E> {
+        if self.0.len().saturating_add(slice.len()) > N {
+            return Err(E::default());
+        }
+
+        self.0.extend_from_slice(slice);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LimitedVec;
+    use alloc::vec;
+    use codec::{Decode, Encode};
+    use core::convert::TryFrom;
+
+    #[derive(Debug, Default, PartialEq, Encode)]
+    struct Overflow;
+
+    type Limited = LimitedVec<u8, Overflow, 4>;
+
+    #[test]
+    fn limit_is_respected() {
+        assert_eq!(Limited::try_from(vec![1, 2, 3, 4, 5]), Err(Overflow));
+
+        let mut limited = Limited::try_from(vec![3]).expect("Within limit");
+
+        assert_eq!(limited.try_extend_from_slice(&[4, 5, 6]), Err(Overflow));
+        assert_eq!(limited.try_extend_from_slice(&[4]), Ok(()));
+
+        let prefix = Limited::try_from(vec![1, 2, 3]).expect("Within limit");
+        let prefix = limited.try_prepend(prefix).expect_err("Exceeds limit");
+
+        assert_eq!(limited.inner(), [3, 4]);
+        assert_eq!(prefix.inner(), [1, 2, 3]);
+
+        let prefix = Limited::try_from(vec![1, 2]).expect("Within limit");
+        assert!(limited.try_prepend(prefix).is_ok());
+        assert_eq!(limited.into_vec(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn decode_respects_limit() {
+        let encoded = vec![1u8, 2, 3, 4].encode();
+        let limited = Limited::decode(&mut &encoded[..]).expect("Within limit");
+        assert_eq!(limited.encode(), encoded);
+
+        let encoded = vec![1u8, 2, 3, 4, 5].encode();
+        assert!(Limited::decode(&mut &encoded[..]).is_err());
+    }
+}
@@ -19,18 +19,50 @@
 //! Module for checked code.
 
 use crate::{ids::CodeId, memory::WasmPageNumber, message::DispatchKind};
-use alloc::{collections::BTreeSet, vec::Vec};
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
 use codec::{Decode, Encode};
-use parity_wasm::elements::{Internal, Module};
+use parity_wasm::elements::{
+    Error as DecodeError, Instruction, Internal, Local, Module, Type, ValueType,
+};
 use scale_info::TypeInfo;
 use wasm_instrument::gas_metering::Rules;
 
-/// Parse function exports from wasm module into [`DispatchKind`].
+/// Prefix of the export names declaring named sub-handlers of `handle`.
+///
+/// Export `handle::transfer` declares the `transfer` route.
+pub const ROUTE_EXPORT_PREFIX: &str = "handle::";
+
+/// Byte separating the route name from the rest of the payload.
+pub const ROUTE_SEPARATOR: u8 = b':';
+
+/// Maximal length of the route name.
+pub const MAX_ROUTE_NAME_LEN: usize = 32;
+
+/// Maximal amount of routes declared by the code.
+pub const MAX_ROUTES_AMOUNT: usize = 64;
+
+/// Name of the export, which replies with program state on informational queries.
+pub const STATE_EXPORT_NAME: &str = "state";
+
+/// Checks that route name is non-empty, not too long and consists
+/// only of ascii alphanumeric characters and underscores.
+fn is_valid_route_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_ROUTE_NAME_LEN
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Parse function exports from wasm module into [`DispatchKind`] and routes.
 fn get_exports(
     module: &Module,
     reject_unnececery: bool,
-) -> Result<BTreeSet<DispatchKind>, CodeError> {
+) -> Result<(BTreeSet<DispatchKind>, BTreeSet<String>), CodeError> {
     let mut exports = BTreeSet::<DispatchKind>::new();
+    let mut routes = BTreeSet::<String>::new();
 
     for entry in module
         .export_section()
@@ -45,12 +77,217 @@ fn get_exports(
                 exports.insert(DispatchKind::Handle);
             } else if entry.field() == DispatchKind::Reply.into_entry() {
                 exports.insert(DispatchKind::Reply);
+            } else if entry.field() == DispatchKind::Signal.into_entry() {
+                exports.insert(DispatchKind::Signal);
+            } else if entry.field() == STATE_EXPORT_NAME {
+                continue;
+            } else if let Some(name) = entry.field().strip_prefix(ROUTE_EXPORT_PREFIX) {
+                if is_valid_route_name(name) {
+                    routes.insert(name.to_string());
+                } else if reject_unnececery {
+                    return Err(CodeError::InvalidRouteName);
+                }
             } else if reject_unnececery {
                 return Err(CodeError::NonGearExportFnFound);
             }
         }
     }
-    Ok(exports)
+
+    if reject_unnececery && !routes.is_empty() {
+        if !exports.contains(&DispatchKind::Handle) {
+            return Err(CodeError::RouteWithoutHandle);
+        }
+
+        if routes.len() > MAX_ROUTES_AMOUNT {
+            return Err(CodeError::TooManyRoutes);
+        }
+    }
+
+    Ok((exports, routes))
+}
+
+/// Shared flag of the memory limits, introduced by the threads proposal.
+const SHARED_MEMORY_FLAG: u8 = 0x02;
+
+/// Converts module decoding error into the code one.
+///
+/// Shared memories fail to decode, since `atomics` feature of `parity-wasm`
+/// is disabled, so they are distinguished by the flags of their limits.
+fn decode_error(err: DecodeError) -> CodeError {
+    match err {
+        DecodeError::InvalidLimitsFlags(flags) if flags & SHARED_MEMORY_FLAG != 0 => {
+            CodeError::SharedMemory
+        }
+        _ => CodeError::Decode,
+    }
+}
+
+/// Checks that module declares the only memory.
+fn check_memories(module: &Module) -> Result<(), CodeError> {
+    let imported = module
+        .import_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .filter(|entry| {
+                    matches!(entry.external(), parity_wasm::elements::External::Memory(_))
+                })
+                .count()
+        })
+        .unwrap_or(0);
+
+    let defined = module
+        .memory_section()
+        .map(|section| section.entries().len())
+        .unwrap_or(0);
+
+    if imported + defined > 1 {
+        return Err(CodeError::MultipleMemories);
+    }
+
+    Ok(())
+}
+
+/// Bit pattern of the canonical `f32` NaN.
+pub const CANONICAL_NAN_F32: u32 = 0x7fc0_0000;
+
+/// Bit pattern of the canonical `f64` NaN.
+pub const CANONICAL_NAN_F64: u64 = 0x7ff8_0000_0000_0000;
+
+/// Returns type of the float, which might be a NaN with
+/// non-deterministic bits after executing `instruction`.
+fn nan_producing_type(instruction: &Instruction) -> Option<ValueType> {
+    use Instruction::*;
+
+    match instruction {
+        F32Ceil | F32Floor | F32Trunc | F32Nearest | F32Sqrt | F32Add | F32Sub | F32Mul
+        | F32Div | F32Min | F32Max | F32DemoteF64 => Some(ValueType::F32),
+        F64Ceil | F64Floor | F64Trunc | F64Nearest | F64Sqrt | F64Add | F64Sub | F64Mul
+        | F64Div | F64Min | F64Max | F64PromoteF32 => Some(ValueType::F64),
+        _ => None,
+    }
+}
+
+/// Makes float arithmetic of the module deterministic.
+///
+/// The only non-determinism of wasm floats is the bit pattern of NaNs produced
+/// by arithmetic instructions, so each such instruction is followed by the check
+/// replacing any NaN on top of the stack with the canonical one.
+fn canonicalize_nans(module: &mut Module) -> Result<(), CodeError> {
+    let types: Vec<u32> = module
+        .type_section()
+        .map(|section| {
+            section
+                .types()
+                .iter()
+                .map(|ty| {
+                    let Type::Function(func) = ty;
+                    func.params().len() as u32
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let params: Vec<u32> = module
+        .function_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .map(|func| types.get(func.type_ref() as usize).copied())
+                .collect::<Option<_>>()
+        })
+        .unwrap_or(Some(Vec::new()))
+        .ok_or(CodeError::Decode)?;
+
+    let bodies = match module.code_section_mut() {
+        Some(section) => section.bodies_mut(),
+        None => return Ok(()),
+    };
+
+    if bodies.len() != params.len() {
+        return Err(CodeError::Decode);
+    }
+
+    for (body, params) in bodies.iter_mut().zip(params) {
+        let instructions = body.code().elements();
+
+        if !instructions
+            .iter()
+            .any(|instr| nan_producing_type(instr).is_some())
+        {
+            continue;
+        }
+
+        let locals = body
+            .locals()
+            .iter()
+            .try_fold(params, |acc, local| acc.checked_add(local.count()))
+            .ok_or(CodeError::Decode)?;
+        let scratch_f32 = locals;
+        let scratch_f64 = locals.checked_add(1).ok_or(CodeError::Decode)?;
+
+        let mut canonicalized = Vec::with_capacity(instructions.len());
+
+        for instr in instructions {
+            canonicalized.push(instr.clone());
+
+            let (scratch, canonical_nan, eq) = match nan_producing_type(instr) {
+                Some(ValueType::F32) => (
+                    scratch_f32,
+                    Instruction::F32Const(CANONICAL_NAN_F32),
+                    Instruction::F32Eq,
+                ),
+                Some(_) => (
+                    scratch_f64,
+                    Instruction::F64Const(CANONICAL_NAN_F64),
+                    Instruction::F64Eq,
+                ),
+                None => continue,
+            };
+
+            // NaN is the only value, which isn't equal to itself.
+            canonicalized.extend([
+                Instruction::TeeLocal(scratch),
+                canonical_nan,
+                Instruction::GetLocal(scratch),
+                Instruction::GetLocal(scratch),
+                eq,
+                Instruction::Select,
+            ]);
+        }
+
+        body.locals_mut().push(Local::new(1, ValueType::F32));
+        body.locals_mut().push(Local::new(1, ValueType::F64));
+        *body.code_mut().elements_mut() = canonicalized;
+    }
+
+    Ok(())
+}
+
+/// Returns route for the `handle` dispatch with given payload.
+///
+/// Payload is routed to `handle::<name>` export if it starts
+/// with the `<name>` followed by [`ROUTE_SEPARATOR`].
+fn find_route<'a>(
+    routes: &'a BTreeSet<String>,
+    kind: &DispatchKind,
+    payload: &[u8],
+) -> Option<&'a str> {
+    if !kind.is_handle() || routes.is_empty() {
+        return None;
+    }
+
+    let len = payload.iter().position(|b| *b == ROUTE_SEPARATOR)?;
+
+    if len > MAX_ROUTE_NAME_LEN {
+        return None;
+    }
+
+    let name = core::str::from_utf8(&payload[..len]).ok()?;
+
+    routes.get(name).map(|route| route.as_str())
 }
 
 /// Instrumentation error.
@@ -82,6 +319,16 @@ pub enum CodeError {
     Encode,
     /// We restrict start sections in smart contracts.
     StartSectionIsFound,
+    /// The provided code declares route with invalid name.
+    InvalidRouteName,
+    /// The provided code declares routes, but doesn't contain `handle` export function.
+    RouteWithoutHandle,
+    /// The provided code declares more routes than allowed.
+    TooManyRoutes,
+    /// The provided code declares more than one memory.
+    MultipleMemories,
+    /// The provided code declares shared memory.
+    SharedMemory,
 }
 
 /// Contains instrumented binary code of a program and initial memory size from memory import.
@@ -93,6 +340,8 @@ pub struct Code {
     raw_code: Vec<u8>,
     /// Exports of the wasm module.
     exports: BTreeSet<DispatchKind>,
+    /// Named sub-handlers of `handle` declared by the wasm module.
+    routes: BTreeSet<String>,
     static_pages: WasmPageNumber,
     #[codec(compact)]
     instruction_weights_version: u32,
@@ -101,22 +350,42 @@ pub struct Code {
 impl Code {
     /// Create the code by checking and instrumenting `original_code`.
     pub fn try_new<R, GetRulesFn>(
+        raw_code: Vec<u8>,
+        version: u32,
+        get_gas_rules: GetRulesFn,
+    ) -> Result<Self, CodeError>
+    where
+        R: Rules,
+        GetRulesFn: FnMut(&Module) -> R,
+    {
+        Self::try_new_with_floats(raw_code, version, get_gas_rules, false)
+    }
+
+    /// Same as [`Code::try_new`], but canonicalizes NaNs produced by float
+    /// instructions of `original_code` if `canonicalize_floats` is set.
+    ///
+    /// Whether float instructions are accepted at all is still decided by
+    /// the rules returned from `get_gas_rules`.
+    pub fn try_new_with_floats<R, GetRulesFn>(
         raw_code: Vec<u8>,
         version: u32,
         mut get_gas_rules: GetRulesFn,
+        canonicalize_floats: bool,
     ) -> Result<Self, CodeError>
     where
         R: Rules,
         GetRulesFn: FnMut(&Module) -> R,
     {
-        let module: Module = wasm_instrument::parity_wasm::deserialize_buffer(&raw_code)
-            .map_err(|_| CodeError::Decode)?;
+        let mut module: Module =
+            wasm_instrument::parity_wasm::deserialize_buffer(&raw_code).map_err(decode_error)?;
 
         if module.start_section().is_some() {
             log::debug!("Found start section in contract code, which is not allowed");
             return Err(CodeError::StartSectionIsFound);
         }
 
+        check_memories(&module)?;
+
         // get initial memory size from memory import.
         let static_pages = WasmPageNumber(
             module
@@ -133,9 +402,13 @@ impl Code {
                 .ok_or(CodeError::MemoryEntryNotFound)?,
         );
 
-        let exports = get_exports(&module, true)?;
+        let (exports, routes) = get_exports(&module, true)?;
 
         if exports.contains(&DispatchKind::Init) || exports.contains(&DispatchKind::Handle) {
+            if canonicalize_floats {
+                canonicalize_nans(&mut module)?;
+            }
+
             let gas_rules = get_gas_rules(&module);
             let instrumented_module =
                 wasm_instrument::gas_metering::inject(module, &gas_rules, "env")
@@ -149,6 +422,7 @@ impl Code {
                 code: instrumented,
                 raw_code,
                 exports,
+                routes,
                 static_pages,
                 instruction_weights_version: version,
             })
@@ -166,7 +440,7 @@ impl Code {
     ) -> Result<Self, CodeError> {
         let module = module.unwrap_or(
             wasm_instrument::parity_wasm::deserialize_buffer(&original_code)
-                .map_err(|_| CodeError::Decode)?,
+                .map_err(decode_error)?,
         );
 
         // get initial memory size from memory import.
@@ -185,7 +459,7 @@ impl Code {
                 .ok_or(CodeError::MemoryEntryNotFound)?,
         );
 
-        let exports = get_exports(&module, false)?;
+        let (exports, routes) = get_exports(&module, false)?;
 
         if exports.contains(&DispatchKind::Init) || exports.contains(&DispatchKind::Handle) {
             if instrument_with_const_rules {
@@ -204,6 +478,7 @@ impl Code {
                     raw_code: original_code,
                     code: instrumented,
                     exports,
+                    routes,
                     static_pages,
                     instruction_weights_version: version,
                 })
@@ -212,6 +487,7 @@ impl Code {
                     raw_code: original_code.clone(),
                     code: original_code,
                     exports,
+                    routes,
                     static_pages,
                     instruction_weights_version: version,
                 })
@@ -236,6 +512,11 @@ impl Code {
         &self.exports
     }
 
+    /// Returns routes declared by the wasm module.
+    pub fn routes(&self) -> &BTreeSet<String> {
+        &self.routes
+    }
+
     /// Returns instruction weights version.
     pub fn instruction_weights_version(&self) -> u32 {
         self.instruction_weights_version
@@ -252,6 +533,7 @@ impl Code {
             InstrumentedCode {
                 code: self.code,
                 exports: self.exports,
+                routes: self.routes,
                 static_pages: self.static_pages,
                 version: self.instruction_weights_version,
             },
@@ -303,9 +585,30 @@ pub struct InstrumentedCode {
     exports: BTreeSet<DispatchKind>,
     static_pages: WasmPageNumber,
     version: u32,
+    routes: BTreeSet<String>,
 }
 
 impl InstrumentedCode {
+    /// Creates the instance from its parts.
+    ///
+    /// Doesn't check that the parts correspond to the code,
+    /// so it's only intended for migrations of stored codes.
+    pub fn new_unchecked(
+        code: Vec<u8>,
+        exports: BTreeSet<DispatchKind>,
+        static_pages: WasmPageNumber,
+        version: u32,
+        routes: BTreeSet<String>,
+    ) -> Self {
+        Self {
+            code,
+            exports,
+            static_pages,
+            version,
+            routes,
+        }
+    }
+
     /// Returns reference to the instrumented binary code.
     pub fn code(&self) -> &[u8] {
         &self.code
@@ -321,6 +624,16 @@ impl InstrumentedCode {
         &self.exports
     }
 
+    /// Returns routes declared by the wasm module.
+    pub fn routes(&self) -> &BTreeSet<String> {
+        &self.routes
+    }
+
+    /// Returns route the dispatch of given kind and payload should be executed with.
+    pub fn route(&self, kind: &DispatchKind, payload: &[u8]) -> Option<&str> {
+        find_route(&self.routes, kind, payload)
+    }
+
     /// Returns initial memory size from memory import.
     pub fn static_pages(&self) -> WasmPageNumber {
         self.static_pages
@@ -363,3 +676,191 @@ impl From<CodeAndId> for InstrumentedCodeAndId {
         Self { code, code_id }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Code, CodeError, CANONICAL_NAN_F32, CANONICAL_NAN_F64};
+    use crate::message::DispatchKind;
+    use alloc::{format, vec, vec::Vec};
+    use parity_wasm::elements::{Instruction, Module};
+    use wasm_instrument::gas_metering::ConstantCostRules;
+
+    fn parse_wat(source: &str) -> Vec<u8> {
+        wabt::Wat2Wasm::new()
+            .validate(false)
+            .convert(source)
+            .expect("failed to parse module")
+            .as_ref()
+            .to_vec()
+    }
+
+    fn try_new_code(exports: &str) -> Result<Code, CodeError> {
+        let wat = format!(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                {}
+                (func $func)
+            )"#,
+            exports
+        );
+
+        Code::try_new(parse_wat(&wat), 1, |_| ConstantCostRules::default())
+    }
+
+    #[test]
+    fn routes_are_accepted_and_routed() {
+        let code = try_new_code(
+            r#"
+                (export "handle" (func $func))
+                (export "handle::transfer" (func $func))
+                (export "handle::approve" (func $func))
+            "#,
+        )
+        .expect("valid routes");
+
+        assert_eq!(code.routes().len(), 2);
+
+        let (code, _) = code.into_parts();
+
+        assert_eq!(
+            code.route(&DispatchKind::Handle, b"transfer:data"),
+            Some("transfer")
+        );
+        assert_eq!(
+            code.route(&DispatchKind::Handle, b"approve:"),
+            Some("approve")
+        );
+        assert_eq!(code.route(&DispatchKind::Handle, b"transfer"), None);
+        assert_eq!(code.route(&DispatchKind::Handle, b"burn:data"), None);
+        assert_eq!(code.route(&DispatchKind::Init, b"transfer:data"), None);
+        assert_eq!(code.route(&DispatchKind::Reply, b"transfer:data"), None);
+    }
+
+    #[test]
+    fn invalid_routes_are_rejected() {
+        assert!(matches!(
+            try_new_code(
+                r#"
+                    (export "handle" (func $func))
+                    (export "handle::" (func $func))
+                "#
+            ),
+            Err(CodeError::InvalidRouteName)
+        ));
+
+        assert!(matches!(
+            try_new_code(
+                r#"
+                    (export "handle" (func $func))
+                    (export "handle::trans:fer" (func $func))
+                "#
+            ),
+            Err(CodeError::InvalidRouteName)
+        ));
+
+        assert!(matches!(
+            try_new_code(
+                r#"
+                    (export "init" (func $func))
+                    (export "handle::transfer" (func $func))
+                "#
+            ),
+            Err(CodeError::RouteWithoutHandle)
+        ));
+    }
+
+    #[test]
+    fn signal_entry_is_exported() {
+        let code = try_new_code(
+            r#"
+                (export "handle" (func $func))
+                (export "handle_signal" (func $func))
+            "#,
+        )
+        .expect("valid exports");
+
+        assert!(code.exports().contains(&DispatchKind::Handle));
+        assert!(code.exports().contains(&DispatchKind::Signal));
+    }
+
+    #[test]
+    fn state_export_is_accepted() {
+        let code = try_new_code(
+            r#"
+                (export "handle" (func $func))
+                (export "state" (func $func))
+            "#,
+        )
+        .expect("valid exports");
+
+        assert_eq!(code.exports().len(), 1);
+        assert!(code.exports().contains(&DispatchKind::Handle));
+    }
+
+    #[test]
+    fn multiple_memories_are_rejected() {
+        let wat = r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (memory 1)
+                (export "handle" (func $handle))
+                (func $handle)
+            )"#;
+
+        assert!(matches!(
+            Code::try_new(parse_wat(wat), 1, |_| ConstantCostRules::default()),
+            Err(CodeError::MultipleMemories)
+        ));
+    }
+
+    #[test]
+    fn shared_memory_is_rejected() {
+        // `(module (import "env" "memory" (memory 1 1 shared)))`
+        let code = vec![
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+            0x02, 0x10, 0x01, // import section of the single entry
+            0x03, b'e', b'n', b'v', 0x06, b'm', b'e', b'm', b'o', b'r', b'y', // names
+            0x02, 0x03, 0x01, 0x01, // shared memory with limits
+        ];
+
+        assert!(matches!(
+            Code::try_new(code, 1, |_| ConstantCostRules::default()),
+            Err(CodeError::SharedMemory)
+        ));
+    }
+
+    #[test]
+    fn float_nans_are_canonicalized() {
+        let wat = r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (export "handle" (func $handle))
+                (func $handle (param $x f32) (result f64)
+                    (local $y i32)
+                    (f64.promote_f32 (f32.sqrt (local.get $x)))
+                )
+            )"#;
+
+        let code =
+            Code::try_new_with_floats(parse_wat(wat), 1, |_| ConstantCostRules::default(), true)
+                .expect("floats are canonicalized");
+
+        let module: Module = parity_wasm::deserialize_buffer(code.code()).unwrap();
+        let body = &module.code_section().unwrap().bodies()[0];
+        let instructions = body.code().elements();
+
+        assert_eq!(body.locals().len(), 3);
+        assert!(instructions.contains(&Instruction::F32Const(CANONICAL_NAN_F32)));
+        assert!(instructions.contains(&Instruction::F64Const(CANONICAL_NAN_F64)));
+        // Param and declared local come before the scratch ones.
+        assert!(instructions.contains(&Instruction::TeeLocal(2)));
+        assert!(instructions.contains(&Instruction::TeeLocal(3)));
+
+        let code = Code::try_new(parse_wat(wat), 1, |_| ConstantCostRules::default())
+            .expect("floats are accepted by constant rules");
+        let module: Module = parity_wasm::deserialize_buffer(code.code()).unwrap();
+
+        assert_eq!(module.code_section().unwrap().bodies()[0].locals().len(), 1);
+    }
+}
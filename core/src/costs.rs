@@ -23,11 +23,14 @@ use crate::gas::Token;
 use codec::{Decode, Encode};
 
 /// Describes the weight for each imported function that a program is allowed to call.
-#[derive(Clone, Encode, Decode, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq, Default)]
 pub struct HostFnWeights {
     /// Weight of calling `alloc`.
     pub alloc: u64,
 
+    /// Weight of calling `free`.
+    pub free: u64,
+
     /// Weight of calling `gr_gas_available`.
     pub gr_gas_available: u64,
 
@@ -73,6 +76,15 @@ pub struct HostFnWeights {
     /// Weight per payload byte by `gr_send_push`.
     pub gr_send_push_per_byte: u64,
 
+    /// Weight of calling `gr_send_push_input`.
+    pub gr_send_push_input: u64,
+
+    /// Weight per payload byte by `gr_send_push_input`.
+    pub gr_send_push_input_per_byte: u64,
+
+    /// Weight of calling `gr_send_cancel`.
+    pub gr_send_cancel: u64,
+
     /// Weight of calling `gr_send_commit`.
     pub gr_send_commit: u64,
 
@@ -91,6 +103,12 @@ pub struct HostFnWeights {
     /// Weight per payload byte by `gr_reply_push`.
     pub gr_reply_push_per_byte: u64,
 
+    /// Weight of calling `gr_reply_push_input`.
+    pub gr_reply_push_input: u64,
+
+    /// Weight per payload byte by `gr_reply_push_input`.
+    pub gr_reply_push_input_per_byte: u64,
+
     /// Weight of calling `gr_reply_to`.
     pub gr_reply_to: u64,
 
@@ -100,6 +118,9 @@ pub struct HostFnWeights {
     /// Weight of calling `gr_exit_code`.
     pub gr_exit_code: u64,
 
+    /// Weight of calling `gr_error`.
+    pub gr_error: u64,
+
     /// Weight of calling `gr_exit`.
     pub gr_exit: u64,
 
@@ -109,6 +130,12 @@ pub struct HostFnWeights {
     /// Weight of calling `gr_wait`.
     pub gr_wait: u64,
 
+    /// Weight of calling `gr_wait_for`.
+    pub gr_wait_for: u64,
+
+    /// Weight of calling `gr_wait_up_to`.
+    pub gr_wait_up_to: u64,
+
     /// Weight of calling `gr_wake`.
     pub gr_wake: u64,
 
@@ -118,6 +145,27 @@ pub struct HostFnWeights {
     /// Weight per payload byte by `gr_create_program_wgas`.
     pub gr_create_program_wgas_per_byte: u64,
 
+    /// Weight of calling `gr_reserve_gas`.
+    pub gr_reserve_gas: u64,
+
+    /// Weight of calling `gr_unreserve_gas`.
+    pub gr_unreserve_gas: u64,
+
+    /// Weight of calling `gr_reservation_send`.
+    pub gr_reservation_send: u64,
+
+    /// Weight of calling `gr_system_reserve_gas`.
+    pub gr_system_reserve_gas: u64,
+
+    /// Weight of calling `gr_metric_increment`.
+    pub gr_metric_increment: u64,
+
+    /// Weight of calling `gr_emit_event`.
+    pub gr_emit_event: u64,
+
+    /// Weight per payload byte by `gr_emit_event`.
+    pub gr_emit_event_per_byte: u64,
+
     /// Weight of calling `gas`.
     pub gas: u64,
 }
@@ -155,6 +203,8 @@ pub enum RuntimeCosts {
     MeteringBlock(u32),
     /// Weight of calling `alloc`.
     Alloc,
+    /// Weight of calling `free`.
+    Free,
     /// Weight of calling `gr_gas_available`.
     GasAvailable,
     /// Weight of calling `gr_msg_id`.
@@ -181,28 +231,52 @@ pub enum RuntimeCosts {
     SendInit,
     /// Weight of calling `gr_send_push`.
     SendPush(u32),
+    /// Weight of calling `gr_send_push_input`.
+    SendPushInput(u32),
+    /// Weight of calling `gr_send_cancel`.
+    SendCancel,
     /// Weight of calling `gr_send_commit`.
     SendCommit(u32),
     /// Weight of calling `gr_reply_commit`.
     ReplyCommit(u32),
     /// Weight of calling `gr_reply_push`.
     ReplyPush(u32),
+    /// Weight of calling `gr_reply_push_input`.
+    ReplyPushInput(u32),
     /// Weight of calling `gr_reply_to`.
     ReplyTo,
     /// Weight of calling `gr_debug`.
     Debug,
     /// Weight of calling `gr_exit_code`.
     ExitCode,
+    /// Weight of calling `gr_error`.
+    Error,
     /// Weight of calling `gr_exit`.
     Exit,
     /// Weight of calling `gr_leave`.
     Leave,
     /// Weight of calling `gr_wait`.
     Wait,
-    /// Weight of calling `gr_wake`.
+    /// Weight of calling `gr_wait_for`.
+    WaitFor,
+    /// Weight of calling `gr_wait_up_to`.
+    WaitUpTo,
+    /// Weight of calling `gr_wake` or `gr_wake_delayed`.
     Wake,
     /// Weight of calling `gr_create_program_wgas`.
     CreateProgram(u32),
+    /// Weight of calling `gr_reserve_gas`.
+    ReserveGas,
+    /// Weight of calling `gr_unreserve_gas`.
+    UnreserveGas,
+    /// Weight of calling `gr_reservation_send`.
+    ReservationSend(u32),
+    /// Weight of calling `gr_system_reserve_gas`.
+    SystemReserveGas,
+    /// Weight of calling `gr_metric_increment`.
+    MetricIncrement,
+    /// Weight of calling `gr_emit_event`.
+    EmitEvent(u32),
 }
 
 impl RuntimeCosts {
@@ -212,6 +286,7 @@ impl RuntimeCosts {
         let weight = match *self {
             MeteringBlock(amount) => s.gas.saturating_add(amount.into()),
             Alloc => s.alloc,
+            Free => s.free,
             GasAvailable => s.gr_gas_available,
             MsgId => s.gr_msg_id,
             Origin => s.gr_origin,
@@ -229,6 +304,10 @@ impl RuntimeCosts {
             SendPush(len) => s
                 .gr_send_push
                 .saturating_add(s.gr_send_push_per_byte.saturating_mul(len.into())),
+            SendPushInput(len) => s
+                .gr_send_push_input
+                .saturating_add(s.gr_send_push_input_per_byte.saturating_mul(len.into())),
+            SendCancel => s.gr_send_cancel,
             SendCommit(len) => s
                 .gr_send_commit
                 .saturating_add(s.gr_send_commit_per_byte.saturating_mul(len.into())),
@@ -238,16 +317,32 @@ impl RuntimeCosts {
             ReplyPush(len) => s
                 .gr_reply_push
                 .saturating_add(s.gr_reply_push_per_byte.saturating_mul(len.into())),
+            ReplyPushInput(len) => s
+                .gr_reply_push_input
+                .saturating_add(s.gr_reply_push_input_per_byte.saturating_mul(len.into())),
             ReplyTo => s.gr_reply_to,
             Debug => s.gr_debug,
             ExitCode => s.gr_exit_code,
+            Error => s.gr_error,
             Exit => s.gr_exit,
             Leave => s.gr_leave,
             Wait => s.gr_wait,
+            WaitFor => s.gr_wait_for,
+            WaitUpTo => s.gr_wait_up_to,
             Wake => s.gr_wake,
             CreateProgram(len) => s
                 .gr_create_program_wgas
                 .saturating_add(s.gr_create_program_wgas_per_byte.saturating_mul(len.into())),
+            ReserveGas => s.gr_reserve_gas,
+            UnreserveGas => s.gr_unreserve_gas,
+            ReservationSend(len) => s
+                .gr_reservation_send
+                .saturating_add(s.gr_send_commit_per_byte.saturating_mul(len.into())),
+            SystemReserveGas => s.gr_system_reserve_gas,
+            MetricIncrement => s.gr_metric_increment,
+            EmitEvent(len) => s
+                .gr_emit_event
+                .saturating_add(s.gr_emit_event_per_byte.saturating_mul(len.into())),
         };
         RuntimeToken { weight }
     }
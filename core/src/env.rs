@@ -20,9 +20,9 @@
 
 use crate::{
     costs::RuntimeCosts,
-    ids::{MessageId, ProgramId},
+    ids::{MessageId, ProgramId, ReservationId},
     memory::{Memory, WasmPageNumber},
-    message::{ExitCode, HandlePacket, InitPacket, ReplyPacket},
+    message::{ExitCode, HandlePacket, InitPacket, ProgramEvent, ReplyDetails, ReplyPacket},
 };
 use alloc::{collections::BTreeSet, rc::Rc};
 use codec::{Decode, Encode};
@@ -70,6 +70,12 @@ pub trait Ext {
     /// Push an extra buffer into message payload by handle.
     fn send_push(&mut self, handle: usize, buffer: &[u8]) -> Result<(), Self::Error>;
 
+    /// Push a range of the incoming message payload into message payload by handle.
+    fn send_push_input(&mut self, handle: usize, offset: u32, len: u32) -> Result<(), Self::Error>;
+
+    /// Cancel incomplete message by handle, discarding its payload.
+    fn send_cancel(&mut self, handle: usize) -> Result<(), Self::Error>;
+
     /// Complete message and send it to another program.
     fn send_commit(&mut self, handle: usize, msg: HandlePacket) -> Result<MessageId, Self::Error>;
 
@@ -79,9 +85,21 @@ pub trait Ext {
         self.send_commit(handle, msg)
     }
 
+    /// Send message to another program using gas of the reservation.
+    ///
+    /// Reservation is removed after sending.
+    fn reservation_send(
+        &mut self,
+        id: ReservationId,
+        msg: HandlePacket,
+    ) -> Result<MessageId, Self::Error>;
+
     /// Push an extra buffer into reply message.
     fn reply_push(&mut self, buffer: &[u8]) -> Result<(), Self::Error>;
 
+    /// Push a range of the incoming message payload into reply message.
+    fn reply_push_input(&mut self, offset: u32, len: u32) -> Result<(), Self::Error>;
+
     /// Complete reply message and send it to source program.
     fn reply_commit(&mut self, msg: ReplyPacket) -> Result<MessageId, Self::Error>;
 
@@ -90,8 +108,11 @@ pub trait Ext {
         self.reply_commit(msg)
     }
 
-    /// Read the message id, if current message is a reply.
-    fn reply_to(&mut self) -> Result<Option<(MessageId, ExitCode)>, Self::Error>;
+    /// Read the reply details, if current message is a reply.
+    fn reply_to(&mut self) -> Result<Option<ReplyDetails>, Self::Error>;
+
+    /// Read the exit code, if current message is a reply.
+    fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error>;
 
     /// Get the source of the message currently being handled.
     fn source(&mut self) -> Result<ProgramId, Self::Error>;
@@ -116,11 +137,22 @@ pub trait Ext {
     /// This should be no-op in release builds.
     fn debug(&mut self, data: &str) -> Result<(), Self::Error>;
 
+    /// Increase program's custom counter, identified by hash of its name, by `value`.
+    ///
+    /// Counters are accumulated only on chains, which enable them.
+    fn metric_increment(&mut self, key: [u8; 32], value: u64) -> Result<(), Self::Error>;
+
+    /// Emit event with topics, which is recorded on chain, if the execution succeeds.
+    fn emit_event(&mut self, event: ProgramEvent) -> Result<(), Self::Error>;
+
     /// Interrupt the program, saving it's state.
     fn leave(&mut self) -> Result<(), Self::Error>;
 
-    /// Access currently handled message payload.
-    fn msg(&mut self) -> &[u8];
+    /// Read the range of currently handled message payload.
+    fn read(&mut self, at: u32, len: u32) -> Result<&[u8], Self::Error>;
+
+    /// Get the size of currently handled message payload.
+    fn size(&mut self) -> Result<usize, Self::Error>;
 
     /// Default gas host call.
     fn gas(&mut self, amount: u32) -> Result<(), Self::Error>;
@@ -137,6 +169,18 @@ pub trait Ext {
     /// Tell how much gas is left in running context.
     fn gas_available(&mut self) -> Result<u64, Self::Error>;
 
+    /// Reserve `amount` of gas for `duration` blocks.
+    fn reserve_gas(&mut self, amount: u64, duration: u32) -> Result<ReservationId, Self::Error>;
+
+    /// Remove gas reservation and return amount of gas, it has held.
+    ///
+    /// Unreserved gas is returned to the origin of the reservation.
+    fn unreserve_gas(&mut self, id: ReservationId) -> Result<u64, Self::Error>;
+
+    /// Reserve `amount` of gas for the signal, which is sent to the program
+    /// if execution of the current message fails.
+    fn system_reserve_gas(&mut self, amount: u64) -> Result<(), Self::Error>;
+
     /// Value associated with message.
     fn value(&mut self) -> Result<u128, Self::Error>;
 
@@ -146,11 +190,22 @@ pub trait Ext {
     /// Interrupt the program and reschedule execution.
     fn wait(&mut self) -> Result<(), Self::Error>;
 
-    /// Wake the waiting message and move it to the processing queue.
-    fn wake(&mut self, waker_id: MessageId) -> Result<(), Self::Error>;
+    /// Interrupt the program and reschedule execution in `duration` blocks.
+    fn wait_for(&mut self, duration: u32) -> Result<(), Self::Error>;
 
-    /// Send init message to create a new program
-    fn create_program(&mut self, packet: InitPacket) -> Result<ProgramId, Self::Error>;
+    /// Interrupt the program and reschedule execution in `duration` blocks
+    /// or earlier, if the message isn't able to pay the rent for all of them.
+    fn wait_up_to(&mut self, duration: u32) -> Result<(), Self::Error>;
+
+    /// Wake the waiting message and move it to the processing queue
+    /// after `delay` blocks, where zero means right away.
+    fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Self::Error>;
+
+    /// Send init message to create a new program.
+    ///
+    /// Returns id of the init message and id of the program to be created.
+    fn create_program(&mut self, packet: InitPacket)
+        -> Result<(MessageId, ProgramId), Self::Error>;
 
     /// Return the set of functions that are forbidden to be called.
     fn forbidden_funcs(&self) -> &BTreeSet<&'static str>;
@@ -293,12 +348,26 @@ mod tests {
         fn send_push(&mut self, _handle: usize, _buffer: &[u8]) -> Result<(), Self::Error> {
             Ok(())
         }
+        fn send_push_input(
+            &mut self,
+            _handle: usize,
+            _offset: u32,
+            _len: u32,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn send_cancel(&mut self, _handle: usize) -> Result<(), Self::Error> {
+            Ok(())
+        }
         fn reply_commit(&mut self, _msg: ReplyPacket) -> Result<MessageId, Self::Error> {
             Ok(MessageId::default())
         }
         fn reply_push(&mut self, _buffer: &[u8]) -> Result<(), Self::Error> {
             Ok(())
         }
+        fn reply_push_input(&mut self, _offset: u32, _len: u32) -> Result<(), Self::Error> {
+            Ok(())
+        }
         fn send_commit(
             &mut self,
             _handle: usize,
@@ -306,7 +375,10 @@ mod tests {
         ) -> Result<MessageId, Self::Error> {
             Ok(MessageId::default())
         }
-        fn reply_to(&mut self) -> Result<Option<(MessageId, i32)>, Self::Error> {
+        fn reply_to(&mut self) -> Result<Option<ReplyDetails>, Self::Error> {
+            Ok(None)
+        }
+        fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error> {
             Ok(None)
         }
         fn source(&mut self) -> Result<ProgramId, Self::Error> {
@@ -327,8 +399,17 @@ mod tests {
         fn debug(&mut self, _data: &str) -> Result<(), Self::Error> {
             Ok(())
         }
-        fn msg(&mut self) -> &[u8] {
-            &[]
+        fn metric_increment(&mut self, _key: [u8; 32], _value: u64) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn emit_event(&mut self, _event: ProgramEvent) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn read(&mut self, _at: u32, _len: u32) -> Result<&[u8], Self::Error> {
+            Ok(&[])
+        }
+        fn size(&mut self) -> Result<usize, Self::Error> {
+            Ok(0)
         }
         fn gas(&mut self, _amount: u32) -> Result<(), Self::Error> {
             Ok(())
@@ -357,12 +438,41 @@ mod tests {
         fn wait(&mut self) -> Result<(), Self::Error> {
             Ok(())
         }
-        fn wake(&mut self, _waker_id: MessageId) -> Result<(), Self::Error> {
+        fn wait_for(&mut self, _duration: u32) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn wait_up_to(&mut self, _duration: u32) -> Result<(), Self::Error> {
             Ok(())
         }
-        fn create_program(&mut self, _packet: InitPacket) -> Result<ProgramId, Self::Error> {
+        fn wake(&mut self, _waker_id: MessageId, _delay: u32) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn create_program(
+            &mut self,
+            _packet: InitPacket,
+        ) -> Result<(MessageId, ProgramId), Self::Error> {
             Ok(Default::default())
         }
+        fn reserve_gas(
+            &mut self,
+            _amount: u64,
+            _duration: u32,
+        ) -> Result<ReservationId, Self::Error> {
+            Ok(Default::default())
+        }
+        fn unreserve_gas(&mut self, _id: ReservationId) -> Result<u64, Self::Error> {
+            Ok(0)
+        }
+        fn reservation_send(
+            &mut self,
+            _id: ReservationId,
+            _msg: HandlePacket,
+        ) -> Result<MessageId, Self::Error> {
+            Ok(MessageId::default())
+        }
+        fn system_reserve_gas(&mut self, _amount: u64) -> Result<(), Self::Error> {
+            Ok(())
+        }
         fn forbidden_funcs(&self) -> &BTreeSet<&'static str> {
             &self.0
         }
@@ -18,6 +18,12 @@
 
 //! Gas module.
 
+use crate::ids::{MessageId, ReservationId};
+use alloc::collections::BTreeMap;
+use codec::{Decode, Encode};
+use gear_core_errors::ExecutionError;
+use scale_info::TypeInfo;
+
 /// This trait represents a token that can be used for charging `GasCounter`.
 ///
 /// Implementing type is expected to be super lightweight hence `Copy` (`Clone` is added
@@ -173,6 +179,14 @@ impl From<GasCounter> for GasAmount {
     }
 }
 
+impl From<GasAmount> for GasCounter {
+    fn from(gas_amount: GasAmount) -> Self {
+        let GasAmount { left, burned } = gas_amount;
+
+        Self { left, burned }
+    }
+}
+
 /// Value counter with some predefined maximum value.
 #[derive(Debug)]
 pub struct ValueCounter(u128);
@@ -260,9 +274,124 @@ impl GasAllowanceCounter {
     }
 }
 
+/// Gas reserved by the program for the future usage.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Decode, Encode, TypeInfo)]
+pub struct GasReservationSlot {
+    /// Amount of reserved gas.
+    pub amount: u64,
+    /// Block number, at which reservation expires.
+    pub expiration: u32,
+}
+
+/// Gas reservations of the program.
+pub type GasReservationMap = BTreeMap<ReservationId, GasReservationSlot>;
+
+/// Change of the gas reservation, which was made during execution.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Decode, Encode, TypeInfo)]
+pub enum GasReservationState {
+    /// Reservation was created.
+    Created {
+        /// Amount of reserved gas.
+        amount: u64,
+        /// Block number, at which reservation expires.
+        expiration: u32,
+    },
+    /// Reservation, created in one of the previous executions, was removed.
+    Removed {
+        /// Block number, at which reservation expires.
+        expiration: u32,
+    },
+}
+
+/// Gas reserver.
+///
+/// Keeps reservations of the program and tracks their changes during execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GasReserver {
+    message_id: MessageId,
+    nonce: u64,
+    map: GasReservationMap,
+    states: BTreeMap<ReservationId, GasReservationState>,
+}
+
+impl GasReserver {
+    /// New gas reserver for the message with given reservations of the program.
+    ///
+    /// Nonce is used for reservation ids generation and should be kept between
+    /// executions of the same message.
+    pub fn new(message_id: MessageId, nonce: u64, map: GasReservationMap) -> Self {
+        Self {
+            message_id,
+            nonce,
+            map,
+            states: Default::default(),
+        }
+    }
+
+    /// Reserves `amount` of gas till `expiration` block.
+    pub fn reserve(&mut self, amount: u64, expiration: u32) -> ReservationId {
+        let id = ReservationId::generate(self.message_id, self.nonce);
+        self.nonce = self.nonce.saturating_add(1);
+
+        self.map
+            .insert(id, GasReservationSlot { amount, expiration });
+        self.states
+            .insert(id, GasReservationState::Created { amount, expiration });
+
+        id
+    }
+
+    /// Removes reservation and returns its amount.
+    ///
+    /// Reservation made within the current execution is just forgotten.
+    pub fn unreserve(&mut self, id: ReservationId) -> Result<u64, ExecutionError> {
+        let GasReservationSlot { amount, expiration } = self.take(id)?;
+
+        if self.states.remove(&id).is_none() {
+            self.states
+                .insert(id, GasReservationState::Removed { expiration });
+        }
+
+        Ok(amount)
+    }
+
+    /// Takes reservation to send message with its gas.
+    ///
+    /// Reservation isn't marked as removed, because it's
+    /// removed along with the message sending.
+    pub fn take(&mut self, id: ReservationId) -> Result<GasReservationSlot, ExecutionError> {
+        self.map
+            .remove(&id)
+            .ok_or(ExecutionError::InvalidReservationId)
+    }
+
+    /// Puts back reservation, which was taken to send message,
+    /// but the message failed to be sent.
+    pub fn put_back(&mut self, id: ReservationId, slot: GasReservationSlot) {
+        self.map.insert(id, slot);
+    }
+
+    /// Changes of reservations, which were made during execution.
+    pub fn states(&self) -> &BTreeMap<ReservationId, GasReservationState> {
+        &self.states
+    }
+
+    /// Nonce, which will be used for the next reservation.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Destructs reserver and returns changes of reservations.
+    pub fn into_states(self) -> BTreeMap<ReservationId, GasReservationState> {
+        self.states
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ChargeResult, GasCounter};
+    use super::{ChargeResult, GasCounter, GasReservationState, GasReserver};
+    use crate::ids::MessageId;
+    use alloc::collections::BTreeMap;
 
     #[test]
     /// Test that `GasCounter` object returns `Enough` and decreases the remaining count
@@ -281,4 +410,39 @@ mod tests {
         assert_eq!(result, ChargeResult::NotEnough);
         assert_eq!(counter.left(), 100);
     }
+
+    #[test]
+    fn gas_reserver_tracks_states() {
+        let mut reserver = GasReserver::new(MessageId::from(1), 0, Default::default());
+
+        let created = reserver.reserve(100, 10);
+        let forgotten = reserver.reserve(200, 10);
+        assert_eq!(reserver.nonce(), 2);
+
+        assert_eq!(reserver.unreserve(forgotten), Ok(200));
+        assert!(reserver.unreserve(forgotten).is_err());
+
+        let mut expected = BTreeMap::new();
+        expected.insert(
+            created,
+            GasReservationState::Created {
+                amount: 100,
+                expiration: 10,
+            },
+        );
+
+        assert_eq!(reserver.into_states(), expected);
+    }
+
+    #[test]
+    fn gas_reserver_puts_back_taken_slot() {
+        let mut reserver = GasReserver::new(MessageId::from(1), 0, Default::default());
+
+        let id = reserver.reserve(100, 10);
+        let slot = reserver.take(id).expect("Reserved above");
+        assert!(reserver.take(id).is_err());
+
+        reserver.put_back(id, slot);
+        assert_eq!(reserver.unreserve(id), Ok(100));
+    }
 }
@@ -187,6 +187,21 @@ impl MessageId {
 
         hash(&argument).into()
     }
+
+    /// Generate MessageId for signal message of the origin message
+    pub fn generate_signal(origin_msg_id: MessageId) -> MessageId {
+        let unique_flag = b"signal";
+
+        let origin_msg_id = origin_msg_id.as_ref();
+
+        let len = unique_flag.len() + origin_msg_id.len();
+
+        let mut argument = Vec::with_capacity(len);
+        argument.extend_from_slice(unique_flag);
+        argument.extend(origin_msg_id);
+
+        hash(&argument).into()
+    }
 }
 
 declare_id!(ProgramId: "Program identifier");
@@ -205,3 +220,24 @@ impl ProgramId {
         hash(&argument).into()
     }
 }
+
+declare_id!(ReservationId: "Reservation identifier");
+
+impl ReservationId {
+    /// Generate ReservationId from given message id and reservation nonce
+    pub fn generate(msg_id: MessageId, nonce: u64) -> Self {
+        let unique_flag = b"reservation";
+
+        let msg_id = msg_id.as_ref();
+        let nonce = nonce.to_le_bytes();
+
+        let len = unique_flag.len() + msg_id.len() + nonce.len();
+
+        let mut argument = Vec::with_capacity(len);
+        argument.extend_from_slice(unique_flag);
+        argument.extend_from_slice(msg_id);
+        argument.extend(nonce);
+
+        hash(&argument).into()
+    }
+}
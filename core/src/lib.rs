@@ -27,6 +27,7 @@
 
 extern crate alloc;
 
+pub mod buffer;
 pub mod code;
 pub mod costs;
 pub mod env;
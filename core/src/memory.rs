@@ -93,6 +93,11 @@ impl PageBuf {
     pub fn into_vec(self) -> Vec<u8> {
         (self.0 as Box<[_]>).into_vec()
     }
+
+    /// Returns whether all bytes of the page buffer are zero.
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
 }
 
 /// Tries to convert vector data map to page buffer data map.
@@ -24,6 +24,40 @@ use codec::{Decode, Encode};
 use core::ops::Deref;
 use scale_info::TypeInfo;
 
+/// Details of the message replying on another one.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
+pub struct ReplyDetails {
+    /// Message id, this message replies on.
+    reply_to: MessageId,
+    /// Exit code of the reply.
+    exit_code: ExitCode,
+}
+
+impl ReplyDetails {
+    /// Create new reply details.
+    pub fn new(reply_to: MessageId, exit_code: ExitCode) -> Self {
+        Self {
+            reply_to,
+            exit_code,
+        }
+    }
+
+    /// Message id, this message replies on.
+    pub fn reply_to(&self) -> MessageId {
+        self.reply_to
+    }
+
+    /// Exit code of the reply.
+    pub fn exit_code(&self) -> ExitCode {
+        self.exit_code
+    }
+
+    /// Destructure details into parts.
+    pub fn into_parts(self) -> (MessageId, ExitCode) {
+        (self.reply_to, self.exit_code)
+    }
+}
+
 /// An entity that is used for interaction between actors.
 /// Can transfer value and executes by programs in corresponding function: init, handle or handle_reply.
 #[derive(Clone, Default, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
@@ -40,8 +74,8 @@ pub struct Message {
     gas_limit: Option<GasLimit>,
     /// Message value.
     value: Value,
-    /// Message id replied on with exit code.
-    reply: Option<(MessageId, ExitCode)>,
+    /// Message reply details.
+    reply: Option<ReplyDetails>,
 }
 
 impl From<Message> for StoredMessage {
@@ -66,7 +100,7 @@ impl Message {
         payload: Payload,
         gas_limit: Option<GasLimit>,
         value: Value,
-        reply: Option<(MessageId, ExitCode)>,
+        reply: Option<ReplyDetails>,
     ) -> Self {
         Self {
             id,
@@ -115,7 +149,7 @@ impl Message {
     }
 
     /// Message reply.
-    pub fn reply(&self) -> Option<(MessageId, ExitCode)> {
+    pub fn reply(&self) -> Option<ReplyDetails> {
         self.reply
     }
 
@@ -126,12 +160,12 @@ impl Message {
 
     /// Message id what this message replies to, if reply.
     pub fn reply_to(&self) -> Option<MessageId> {
-        self.reply.map(|(id, _)| id)
+        self.reply.map(|details| details.reply_to())
     }
 
     /// Exit code of the message, if reply.
     pub fn exit_code(&self) -> Option<ExitCode> {
-        self.reply.map(|(_, exit_code)| exit_code)
+        self.reply.map(|details| details.exit_code())
     }
 }
 
@@ -190,3 +224,19 @@ impl Deref for Dispatch {
         self.message()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ReplyDetails;
+    use crate::ids::MessageId;
+    use codec::Encode;
+
+    #[test]
+    fn reply_details_keep_encoding() {
+        // Stored messages were encoded with reply details as a tuple.
+        let details = ReplyDetails::new(MessageId::from(42), 1);
+
+        assert_eq!(details.encode(), (MessageId::from(42), 1i32).encode());
+        assert_eq!(details.into_parts(), (MessageId::from(42), 1));
+    }
+}
@@ -17,10 +17,10 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    ids::{MessageId, ProgramId},
+    ids::{MessageId, ProgramId, ReservationId},
     message::{
-        Dispatch, HandleMessage, HandlePacket, IncomingMessage, InitMessage, InitPacket, Payload,
-        ReplyMessage, ReplyPacket,
+        Dispatch, DispatchKind, HandleMessage, HandlePacket, IncomingMessage, InitMessage,
+        InitPacket, Packet, Payload, ReplyMessage, ReplyPacket,
     },
 };
 use alloc::{
@@ -28,33 +28,97 @@ use alloc::{
     vec::Vec,
 };
 use codec::{Decode, Encode};
+use core::convert::TryFrom;
 use gear_core_errors::MessageError as Error;
 use scale_info::TypeInfo;
 
 pub const OUTGOING_LIMIT: u32 = 1024;
+pub const OUTGOING_BYTES_LIMIT: u32 = 64 * 1024 * 1024;
+pub const PROGRAM_CANDIDATES_LIMIT: u32 = 128;
+pub const SIGNAL_OUTGOING_LIMIT: u32 = 16;
 
 /// Context settings.
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
 pub struct ContextSettings {
-    /// Fee for sending message.
+    /// Fee for sending message, including program initialization.
     sending_fee: u64,
+    /// Fee for sending reply.
+    reply_fee: u64,
+    /// Fee for waking message.
+    waking_fee: u64,
+    /// Fee per payload byte of outgoing messages and replies.
+    byte_fee: u64,
     /// Limit of outgoing messages that program can send during execution of current message.
+    ///
+    /// Also limits amount of messages the program can wake, events it can emit,
+    /// metrics and gas reservations it can create, so the outcome of the
+    /// execution has bounded size.
     outgoing_limit: u32,
+    /// Limit of total payload bytes of outgoing messages that program can send
+    /// during execution of current message.
+    outgoing_bytes_limit: u32,
+    /// Limit of programs that program can create during execution of current message.
+    program_candidates_limit: u32,
 }
 
 impl ContextSettings {
     /// Create new ContextSettings.
-    pub fn new(sending_fee: u64, outgoing_limit: u32) -> Self {
+    pub fn new(
+        sending_fee: u64,
+        reply_fee: u64,
+        waking_fee: u64,
+        byte_fee: u64,
+        outgoing_limit: u32,
+        outgoing_bytes_limit: u32,
+        program_candidates_limit: u32,
+    ) -> Self {
         Self {
             sending_fee,
+            reply_fee,
+            waking_fee,
+            byte_fee,
             outgoing_limit,
+            outgoing_bytes_limit,
+            program_candidates_limit,
         }
     }
+
+    /// Fee for sending message with payload of `len` bytes.
+    pub fn sending_fee(&self, len: usize) -> u64 {
+        self.sending_fee.saturating_add(self.payload_fee(len))
+    }
+
+    /// Fee for sending reply with payload of `len` bytes.
+    pub fn reply_fee(&self, len: usize) -> u64 {
+        self.reply_fee.saturating_add(self.payload_fee(len))
+    }
+
+    /// Fee for waking message.
+    pub fn waking_fee(&self) -> u64 {
+        self.waking_fee
+    }
+
+    /// Limit of outgoing messages within execution of current message.
+    pub fn outgoing_limit(&self) -> u32 {
+        self.outgoing_limit
+    }
+
+    fn payload_fee(&self, len: usize) -> u64 {
+        self.byte_fee.saturating_mul(len as u64)
+    }
 }
 
 impl Default for ContextSettings {
     fn default() -> Self {
-        Self::new(0, OUTGOING_LIMIT)
+        Self::new(
+            0,
+            0,
+            0,
+            0,
+            OUTGOING_LIMIT,
+            OUTGOING_BYTES_LIMIT,
+            PROGRAM_CANDIDATES_LIMIT,
+        )
     }
 }
 
@@ -64,9 +128,9 @@ impl Default for ContextSettings {
 #[derive(Default, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
 pub struct ContextOutcome {
     init: Vec<InitMessage>,
-    handle: Vec<HandleMessage>,
+    handle: Vec<(HandleMessage, u32, Option<ReservationId>)>,
     reply: Option<ReplyMessage>,
-    awakening: Vec<MessageId>,
+    awakening: Vec<(MessageId, u32)>,
     // Additional information section.
     program_id: ProgramId,
     source: ProgramId,
@@ -85,19 +149,31 @@ impl ContextOutcome {
     }
 
     /// Destructs outcome after execution and returns provided dispatches and awaken message ids.
-    pub fn drain(self) -> (Vec<Dispatch>, Vec<MessageId>) {
+    ///
+    /// Each dispatch is paired with the amount of blocks it should be delayed for,
+    /// where zero means that dispatch should be sent right away, and with the id
+    /// of the gas reservation, it's sent from. Awaken message ids are paired
+    /// with their wake delays in the same way.
+    #[allow(clippy::type_complexity)]
+    pub fn drain(
+        self,
+    ) -> (
+        Vec<(Dispatch, u32, Option<ReservationId>)>,
+        Vec<(MessageId, u32)>,
+    ) {
         let mut dispatches = Vec::new();
 
         for msg in self.init.into_iter() {
-            dispatches.push(msg.into_dispatch(self.program_id));
+            dispatches.push((msg.into_dispatch(self.program_id), 0, None));
         }
 
-        for msg in self.handle.into_iter() {
-            dispatches.push(msg.into_dispatch(self.program_id));
+        for (msg, delay, reservation) in self.handle.into_iter() {
+            dispatches.push((msg.into_dispatch(self.program_id), delay, reservation));
         }
 
         if let Some(msg) = self.reply {
-            dispatches.push(msg.into_dispatch(self.program_id, self.source, self.origin_msg_id));
+            let dispatch = msg.into_dispatch(self.program_id, self.source, self.origin_msg_id);
+            dispatches.push((dispatch, 0, None));
         };
 
         (dispatches, self.awakening)
@@ -112,42 +188,113 @@ pub struct ContextStore {
     initialized: BTreeSet<ProgramId>,
     awaken: BTreeSet<MessageId>,
     reply_sent: bool,
+    reservation_nonce: u64,
+    system_reservation: Option<u64>,
+    outgoing_nonce: u32,
+}
+
+impl ContextStore {
+    /// Create new ContextStore from the state of the previous executions.
+    ///
+    /// Gas reservations aren't restored, while outgoing nonce follows
+    /// the greatest handle, which ids of the sent messages were generated from.
+    pub fn new(
+        outgoing: BTreeMap<u32, Option<Payload>>,
+        reply: Option<Payload>,
+        initialized: BTreeSet<ProgramId>,
+        awaken: BTreeSet<MessageId>,
+        reply_sent: bool,
+    ) -> Self {
+        let outgoing_nonce = outgoing
+            .keys()
+            .next_back()
+            .map_or(0, |handle| handle.saturating_add(1));
+
+        Self {
+            outgoing,
+            reply,
+            initialized,
+            awaken,
+            reply_sent,
+            reservation_nonce: 0,
+            system_reservation: None,
+            outgoing_nonce,
+        }
+    }
+
+    /// Amount of gas reserved for the signal of the message.
+    pub fn system_reservation(&self) -> Option<u64> {
+        self.system_reservation
+    }
 }
 
 /// Context of currently processing incoming message.
 #[derive(Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
 pub struct MessageContext {
+    kind: DispatchKind,
     current: IncomingMessage,
     outcome: ContextOutcome,
     store: ContextStore,
     settings: ContextSettings,
+    outgoing_bytes_counter: u32,
 }
 
 impl MessageContext {
     /// Create new MessageContext with default ContextSettings.
     pub fn new(
+        kind: DispatchKind,
         message: IncomingMessage,
         program_id: ProgramId,
         store: Option<ContextStore>,
     ) -> Self {
-        Self::new_with_settings(message, program_id, store, Default::default())
+        Self::new_with_settings(kind, message, program_id, store, Default::default())
     }
 
     /// Create new MessageContext with given ContextSettings.
     pub fn new_with_settings(
+        kind: DispatchKind,
         message: IncomingMessage,
         program_id: ProgramId,
         store: Option<ContextStore>,
         settings: ContextSettings,
     ) -> Self {
         Self {
+            kind,
             outcome: ContextOutcome::new(program_id, message.source(), message.id()),
             current: message,
             store: store.unwrap_or_default(),
             settings,
+            outgoing_bytes_counter: 0,
         }
     }
 
+    /// Returns total payload bytes of outgoing messages
+    /// after sending `len` more, if they don't exceed the limit.
+    fn checked_outgoing_bytes(
+        settings: &ContextSettings,
+        counter: u32,
+        len: usize,
+    ) -> Result<u32, Error> {
+        u32::try_from(len)
+            .ok()
+            .and_then(|len| counter.checked_add(len))
+            .filter(|&counter| counter <= settings.outgoing_bytes_limit)
+            .ok_or(Error::OutgoingMessagesBytesLimitExceeded)
+    }
+
+    /// Returns the part of the incoming message payload, if it's in bounds.
+    fn input_range(current: &IncomingMessage, offset: u32, len: u32) -> Result<&[u8], Error> {
+        let start = offset as usize;
+        let end = start
+            .checked_add(len as usize)
+            .ok_or(Error::OutOfBoundsInputRange)?;
+
+        current
+            .payload()
+            .get(start..end)
+            .ok_or(Error::OutOfBoundsInputRange)
+    }
+
     /// Send a new program initialization message.
     ///
     /// Generates a new message from provided data packet.
@@ -159,16 +306,22 @@ impl MessageContext {
             return Err(Error::DuplicateInit);
         }
 
-        let last = self.store.outgoing.len() as u32;
-
-        if last >= self.settings.outgoing_limit {
-            return Err(Error::LimitExceeded);
+        if self.outcome.init.len() as u32 >= self.settings.program_candidates_limit {
+            return Err(Error::ProgramCandidatesLimitExceeded);
         }
 
-        let message_id = MessageId::generate_outgoing(self.current.id(), last);
+        let handle = self.next_outgoing_handle()?;
+
+        self.outgoing_bytes_counter = Self::checked_outgoing_bytes(
+            &self.settings,
+            self.outgoing_bytes_counter,
+            packet.payload().len(),
+        )?;
+
+        let message_id = self.next_outgoing_id();
         let message = InitMessage::from_packet(message_id, packet);
 
-        self.store.outgoing.insert(last, None);
+        self.store.outgoing.insert(handle, None);
         self.store.initialized.insert(program_id);
         self.outcome.init.push(message);
 
@@ -180,18 +333,74 @@ impl MessageContext {
     /// Generates message from provided data packet and stored by handle payload.
     /// Returns message id.
     pub fn send_commit(&mut self, handle: u32, packet: HandlePacket) -> Result<MessageId, Error> {
+        self.send_commit_delayed(handle, packet, 0)
+    }
+
+    /// Send a new message, which will be sent after given amount of blocks.
+    ///
+    /// Generates message from provided data packet and stored by handle payload.
+    /// Returns message id.
+    pub fn send_commit_delayed(
+        &mut self,
+        handle: u32,
+        packet: HandlePacket,
+        delay: u32,
+    ) -> Result<MessageId, Error> {
+        self.commit_outgoing(handle, packet, delay, None)
+    }
+
+    /// Send a new message using gas of the reservation.
+    ///
+    /// Generates message from provided data packet, which gas limit
+    /// is expected to be equal to the reserved amount.
+    /// Returns message id.
+    pub fn reservation_send_commit(
+        &mut self,
+        id: ReservationId,
+        handle: u32,
+        packet: HandlePacket,
+    ) -> Result<MessageId, Error> {
+        self.commit_outgoing(handle, packet, 0, Some(id))
+    }
+
+    fn commit_outgoing(
+        &mut self,
+        handle: u32,
+        packet: HandlePacket,
+        delay: u32,
+        reservation: Option<ReservationId>,
+    ) -> Result<MessageId, Error> {
         if let Some(payload) = self.store.outgoing.get_mut(&handle) {
             if let Some(data) = payload.take() {
+                let outgoing_bytes = match Self::checked_outgoing_bytes(
+                    &self.settings,
+                    self.outgoing_bytes_counter,
+                    packet.payload().len(),
+                ) {
+                    Ok(outgoing_bytes) => outgoing_bytes,
+                    Err(err) => {
+                        *payload = Some(data);
+                        return Err(err);
+                    }
+                };
+
                 let packet = {
                     let mut packet = packet;
-                    packet.prepend(data);
+
+                    if let Err(data) = packet.try_prepend(data) {
+                        *payload = Some(data);
+                        return Err(Error::MaxMessageSizeExceed);
+                    }
+
                     packet
                 };
 
-                let message_id = MessageId::generate_outgoing(self.current.id(), handle);
+                self.outgoing_bytes_counter = outgoing_bytes;
+
+                let message_id = self.next_outgoing_id();
                 let message = HandleMessage::from_packet(message_id, packet);
 
-                self.outcome.handle.push(message);
+                self.outcome.handle.push((message, delay, reservation));
 
                 Ok(message_id)
             } else {
@@ -202,26 +411,139 @@ impl MessageContext {
         }
     }
 
+    /// Send a new message from provided data packet after given amount of blocks.
+    ///
+    /// Returns message id.
+    pub fn send_delayed(&mut self, packet: HandlePacket, delay: u32) -> Result<MessageId, Error> {
+        let handle = self.send_init()?;
+
+        self.send_commit_delayed(handle, packet, delay)
+    }
+
     /// Provide space for storing payload for future message creation.
     ///
     /// Returns it's handle.
     pub fn send_init(&mut self) -> Result<u32, Error> {
-        let last = self.store.outgoing.len() as u32;
+        let handle = self.next_outgoing_handle()?;
 
-        if last < self.settings.outgoing_limit {
-            self.store.outgoing.insert(last, Some(Default::default()));
+        self.store.outgoing.insert(handle, Some(Default::default()));
+
+        Ok(handle)
+    }
+
+    /// Cancels message, which was initialized, but isn't committed yet.
+    ///
+    /// Releases its handle, so it isn't counted against outgoing limit.
+    /// Returns payload, pushed into the message.
+    pub fn send_cancel(&mut self, handle: u32) -> Result<Payload, Error> {
+        match self.store.outgoing.get(&handle) {
+            Some(Some(_)) => {}
+            Some(None) => return Err(Error::LateAccess),
+            None => return Err(Error::OutOfBounds),
+        }
 
-            Ok(last)
+        let payload = self
+            .store
+            .outgoing
+            .remove(&handle)
+            .flatten()
+            .unwrap_or_default();
+
+        self.outgoing_bytes_counter = self
+            .outgoing_bytes_counter
+            .saturating_sub(payload.inner().len() as u32);
+
+        Ok(payload)
+    }
+
+    /// Returns handle for the next outgoing message, if outgoing limit isn't reached.
+    ///
+    /// Limit counts messages sent within current execution and ones
+    /// being formed, so messages committed before waiting don't exhaust it.
+    fn next_outgoing_handle(&self) -> Result<u32, Error> {
+        let forming = self
+            .store
+            .outgoing
+            .values()
+            .filter(|payload| payload.is_some())
+            .count();
+        let sent = self.outcome.init.len() + self.outcome.handle.len();
+
+        if (forming + sent) as u32 >= self.outgoing_limit() {
+            return Err(Error::LimitExceeded);
+        }
+
+        // Handles of committed messages are reused, otherwise
+        // the next handle follows the greatest one in use.
+        let handle = self
+            .store
+            .outgoing
+            .iter()
+            .find_map(|(&handle, payload)| payload.is_none().then(|| handle))
+            .or_else(|| self.store.outgoing.keys().next_back().map(|last| last + 1))
+            .unwrap_or(0);
+
+        Ok(handle)
+    }
+
+    /// Limit of outgoing messages for current entry point.
+    ///
+    /// Signal handler is funded only by the system reservation,
+    /// so it's allowed to send a few messages at most.
+    fn outgoing_limit(&self) -> u32 {
+        if self.kind.is_signal() {
+            self.settings.outgoing_limit.min(SIGNAL_OUTGOING_LIMIT)
         } else {
-            Err(Error::LimitExceeded)
+            self.settings.outgoing_limit
         }
     }
 
+    /// Returns id for the next outgoing message.
+    ///
+    /// Ids are generated from the nonce rather than handle,
+    /// because handles of committed messages are reused.
+    fn next_outgoing_id(&mut self) -> MessageId {
+        let message_id = MessageId::generate_outgoing(self.current.id(), self.store.outgoing_nonce);
+        self.store.outgoing_nonce = self.store.outgoing_nonce.saturating_add(1);
+
+        message_id
+    }
+
     /// Pushes payload into stored payload by handle.
     pub fn send_push(&mut self, handle: u32, buffer: &[u8]) -> Result<(), Error> {
         match self.store.outgoing.get_mut(&handle) {
             Some(Some(data)) => {
-                data.extend_from_slice(buffer);
+                let outgoing_bytes = Self::checked_outgoing_bytes(
+                    &self.settings,
+                    self.outgoing_bytes_counter,
+                    buffer.len(),
+                )?;
+
+                data.try_extend_from_slice(buffer)?;
+                self.outgoing_bytes_counter = outgoing_bytes;
+
+                Ok(())
+            }
+            Some(None) => Err(Error::LateAccess),
+            None => Err(Error::OutOfBounds),
+        }
+    }
+
+    /// Pushes the part of the incoming message payload into stored payload by handle.
+    pub fn send_push_input(&mut self, handle: u32, offset: u32, len: u32) -> Result<(), Error> {
+        let input = Self::input_range(&self.current, offset, len)?;
+
+        match self.store.outgoing.get_mut(&handle) {
+            Some(Some(data)) => {
+                let outgoing_bytes = Self::checked_outgoing_bytes(
+                    &self.settings,
+                    self.outgoing_bytes_counter,
+                    input.len(),
+                )?;
+
+                data.try_extend_from_slice(input)?;
+                self.outgoing_bytes_counter = outgoing_bytes;
+
                 Ok(())
             }
             Some(None) => Err(Error::LateAccess),
@@ -234,12 +556,21 @@ impl MessageContext {
     /// Generates reply from provided data packet and stored reply payload.
     /// Returns message id.
     pub fn reply_commit(&mut self, packet: ReplyPacket) -> Result<MessageId, Error> {
+        if self.kind.is_signal() {
+            return Err(Error::IncorrectEntryForReply);
+        }
+
         if !self.store.reply_sent {
             let data = self.store.reply.take().unwrap_or_default();
 
             let packet = {
                 let mut packet = packet;
-                packet.prepend(data);
+
+                if let Err(data) = packet.try_prepend(data) {
+                    self.store.reply = Some(data);
+                    return Err(Error::MaxMessageSizeExceed);
+                }
+
                 packet
             };
 
@@ -257,9 +588,13 @@ impl MessageContext {
 
     /// Pushes payload into stored reply payload.
     pub fn reply_push(&mut self, buffer: &[u8]) -> Result<(), Error> {
+        if self.kind.is_signal() {
+            return Err(Error::IncorrectEntryForReply);
+        }
+
         if !self.store.reply_sent {
             let data = self.store.reply.get_or_insert_with(Default::default);
-            data.extend_from_slice(buffer);
+            data.try_extend_from_slice(buffer)?;
 
             Ok(())
         } else {
@@ -267,17 +602,46 @@ impl MessageContext {
         }
     }
 
-    /// Wake message by it's message id.
-    pub fn wake(&mut self, waker_id: MessageId) -> Result<(), Error> {
-        if self.store.awaken.insert(waker_id) {
-            self.outcome.awakening.push(waker_id);
+    /// Pushes the part of the incoming message payload into stored reply payload.
+    pub fn reply_push_input(&mut self, offset: u32, len: u32) -> Result<(), Error> {
+        if self.kind.is_signal() {
+            return Err(Error::IncorrectEntryForReply);
+        }
+
+        let input = Self::input_range(&self.current, offset, len)?;
+
+        if !self.store.reply_sent {
+            let data = self.store.reply.get_or_insert_with(Default::default);
+            data.try_extend_from_slice(input)?;
 
             Ok(())
         } else {
-            Err(Error::DuplicateWaking)
+            Err(Error::LateAccess)
         }
     }
 
+    /// Wake message by it's message id after the given amount of blocks,
+    /// where zero means that message should be woken right away.
+    pub fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Error> {
+        if self.store.awaken.contains(&waker_id) {
+            return Err(Error::DuplicateWaking);
+        }
+
+        if self.outcome.awakening.len() as u32 >= self.settings.outgoing_limit {
+            return Err(Error::AwakeningLimitExceeded);
+        }
+
+        self.store.awaken.insert(waker_id);
+        self.outcome.awakening.push((waker_id, delay));
+
+        Ok(())
+    }
+
+    /// Entry point of current processing incoming message.
+    pub fn kind(&self) -> DispatchKind {
+        self.kind
+    }
+
     /// Current processing incoming message.
     pub fn current(&self) -> &IncomingMessage {
         &self.current
@@ -288,6 +652,31 @@ impl MessageContext {
         self.outcome.program_id
     }
 
+    /// Settings of the context.
+    pub fn settings(&self) -> &ContextSettings {
+        &self.settings
+    }
+
+    /// Nonce for the next gas reservation of the message.
+    pub fn reservation_nonce(&self) -> u64 {
+        self.store.reservation_nonce
+    }
+
+    /// Keeps nonce for gas reservations made in next executions of the message.
+    pub fn set_reservation_nonce(&mut self, nonce: u64) {
+        self.store.reservation_nonce = nonce;
+    }
+
+    /// Amount of gas reserved for the signal of the message.
+    pub fn system_reservation(&self) -> Option<u64> {
+        self.store.system_reservation
+    }
+
+    /// Reserves gas for the signal, which is sent if execution of the message fails.
+    pub fn set_system_reservation(&mut self, amount: u64) {
+        self.store.system_reservation = Some(amount);
+    }
+
     /// Destructs context after execution and returns provided outcome and store.
     pub fn drain(self) -> (ContextOutcome, ContextStore) {
         let Self { outcome, store, .. } = self;
@@ -299,13 +688,22 @@ impl MessageContext {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ids;
+    use crate::{
+        gas::{ChargeResult, GasCounter},
+        ids,
+        message::MAX_PAYLOAD_SIZE,
+    };
     use alloc::vec;
+    use core::convert::TryInto;
 
     #[test]
     fn default_message_context() {
-        let message_context =
-            MessageContext::new(Default::default(), Default::default(), Default::default());
+        let message_context = MessageContext::new(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
 
         // Check that created in new ContextSettings have valid outgoing_limit.
         assert_eq!(message_context.settings.outgoing_limit, OUTGOING_LIMIT);
@@ -313,8 +711,12 @@ mod tests {
 
     #[test]
     fn duplicated_init() {
-        let mut message_context =
-            MessageContext::new(Default::default(), Default::default(), Default::default());
+        let mut message_context = MessageContext::new(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
         // first init to default ProgramId.
         let result = message_context.init_program(Default::default());
 
@@ -332,9 +734,18 @@ mod tests {
 
         for n in 0..=max_n {
             // for outgoing_limit n checking that LimitExceeded will be after n's message.
-            let settings = ContextSettings::new(0, n);
+            let settings = ContextSettings::new(
+                0,
+                0,
+                0,
+                0,
+                n,
+                OUTGOING_BYTES_LIMIT,
+                PROGRAM_CANDIDATES_LIMIT,
+            );
 
             let mut message_context = MessageContext::new_with_settings(
+                DispatchKind::Handle,
                 Default::default(),
                 Default::default(),
                 Default::default(),
@@ -360,10 +771,250 @@ mod tests {
         }
     }
 
+    #[test]
+    fn program_candidates_limit_exceeded() {
+        let mut message_context = MessageContext::new_with_settings(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            ContextSettings::new(0, 0, 0, 0, OUTGOING_LIMIT, OUTGOING_BYTES_LIMIT, 2),
+        );
+
+        let packet =
+            |salt: &[u8]| InitPacket::new(Default::default(), salt.to_vec(), Default::default(), 0);
+
+        assert!(message_context.init_program(packet(b"first")).is_ok());
+        assert!(message_context.init_program(packet(b"second")).is_ok());
+        assert_eq!(
+            message_context.init_program(packet(b"third")),
+            Err(Error::ProgramCandidatesLimitExceeded)
+        );
+
+        // Limit doesn't affect sending of other messages.
+        let handle = message_context.send_init().expect("unreachable");
+        assert!(message_context
+            .send_commit(handle, HandlePacket::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn outgoing_bytes_limit_exceeded() {
+        let mut message_context = MessageContext::new_with_settings(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            ContextSettings::new(0, 0, 0, 0, OUTGOING_LIMIT, 10, PROGRAM_CANDIDATES_LIMIT),
+        );
+
+        let handle = message_context.send_init().expect("unreachable");
+        message_context
+            .send_push(handle, b"payload")
+            .expect("unreachable");
+
+        // Pushed bytes are taken into account.
+        assert_eq!(
+            message_context.send_push(handle, b"payload"),
+            Err(Error::OutgoingMessagesBytesLimitExceeded)
+        );
+
+        // As well as bytes of the committed packet.
+        let packet = HandlePacket::new(
+            Default::default(),
+            b"payload".to_vec().try_into().unwrap(),
+            0,
+        );
+        assert_eq!(
+            message_context.send_commit(handle, packet),
+            Err(Error::OutgoingMessagesBytesLimitExceeded)
+        );
+
+        // Failed commit keeps the pushed payload, so it could be committed again.
+        let packet = HandlePacket::new(Default::default(), b"pay".to_vec().try_into().unwrap(), 0);
+        assert!(message_context.send_commit(handle, packet).is_ok());
+
+        // Limit is reached, so even empty payload is allowed.
+        let handle = message_context.send_init().expect("unreachable");
+        assert!(message_context.send_push(handle, b"").is_ok());
+        assert_eq!(
+            message_context.send_push(handle, b"p"),
+            Err(Error::OutgoingMessagesBytesLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn send_cancel_releases_handle() {
+        let mut message_context = MessageContext::new_with_settings(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            ContextSettings::new(0, 0, 0, 0, 2, 10, PROGRAM_CANDIDATES_LIMIT),
+        );
+
+        let handle = message_context.send_init().expect("unreachable");
+        message_context
+            .send_push(handle, b"payload")
+            .expect("unreachable");
+        let committed = message_context.send_init().expect("unreachable");
+        message_context
+            .send_commit(committed, HandlePacket::default())
+            .expect("unreachable");
+
+        assert_eq!(message_context.send_init(), Err(Error::LimitExceeded));
+
+        // Pushed payload is returned back.
+        let payload = message_context.send_cancel(handle).expect("unreachable");
+        assert_eq!(payload.inner(), b"payload");
+
+        // Cancelled and committed messages can't be cancelled.
+        assert_eq!(message_context.send_cancel(handle), Err(Error::OutOfBounds));
+        assert_eq!(
+            message_context.send_cancel(committed),
+            Err(Error::LateAccess)
+        );
+
+        // Slot and payload bytes are released, handle of committed message is reused.
+        let handle = message_context.send_init().expect("unreachable");
+        assert_eq!(handle, committed);
+        assert!(message_context.send_push(handle, b"payload").is_ok());
+        assert!(message_context
+            .send_commit(handle, HandlePacket::default())
+            .is_ok());
+
+        let (outcome, _) = message_context.drain();
+        let (dispatches, _) = outcome.drain();
+        assert_eq!(dispatches.len(), 2);
+        assert_eq!(dispatches[1].0.payload(), b"payload");
+    }
+
+    #[test]
+    fn committed_handles_are_reused_after_wait() {
+        let settings = ContextSettings::new(
+            0,
+            0,
+            0,
+            0,
+            2,
+            OUTGOING_BYTES_LIMIT,
+            PROGRAM_CANDIDATES_LIMIT,
+        );
+        let mut store = None;
+        let mut forming = None;
+        let mut ids = BTreeSet::new();
+
+        // Each execution commits the message formed before waiting
+        // and starts forming the next one, reaching the limit.
+        for _ in 0..4 {
+            let mut message_context = MessageContext::new_with_settings(
+                DispatchKind::Handle,
+                Default::default(),
+                Default::default(),
+                store.take(),
+                settings,
+            );
+
+            let handle = match forming {
+                Some(handle) => handle,
+                None => message_context.send_init().expect("unreachable"),
+            };
+            let message_id = message_context
+                .send_commit(handle, HandlePacket::default())
+                .expect("unreachable");
+            assert!(ids.insert(message_id));
+
+            let handle = message_context.send_init().expect("unreachable");
+            assert!(message_context.send_push(handle, b"payload").is_ok());
+            assert_eq!(message_context.send_init(), Err(Error::LimitExceeded));
+            forming = Some(handle);
+
+            let (_, context_store) = message_context.drain();
+            store = Some(context_store);
+        }
+
+        // Only the committed slot and the forming one are stored.
+        let store = store.expect("unreachable");
+        assert_eq!(store.outgoing.len(), 2);
+        assert_eq!(store.outgoing.values().filter(|p| p.is_some()).count(), 1);
+    }
+
+    #[test]
+    fn awakening_limit_exceeded() {
+        let limit = 3;
+
+        let mut message_context = MessageContext::new_with_settings(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            ContextSettings::new(
+                0,
+                0,
+                0,
+                0,
+                limit,
+                OUTGOING_BYTES_LIMIT,
+                PROGRAM_CANDIDATES_LIMIT,
+            ),
+        );
+
+        for id in 0..limit as u64 {
+            assert!(message_context.wake(MessageId::from(id), 0).is_ok());
+        }
+
+        // Duplicates are checked before the limit.
+        assert_eq!(
+            message_context.wake(MessageId::from(0), 0),
+            Err(Error::DuplicateWaking)
+        );
+        assert_eq!(
+            message_context.wake(MessageId::from(limit as u64), 0),
+            Err(Error::AwakeningLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn awakening_limit_ignores_previous_executions() {
+        let limit = 2;
+        let awaken = (0..limit as u64).map(MessageId::from).collect();
+        let store = ContextStore::new(Default::default(), None, Default::default(), awaken, false);
+
+        let mut message_context = MessageContext::new_with_settings(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Some(store),
+            ContextSettings::new(
+                0,
+                0,
+                0,
+                0,
+                limit,
+                OUTGOING_BYTES_LIMIT,
+                PROGRAM_CANDIDATES_LIMIT,
+            ),
+        );
+
+        // Messages woken in previous executions don't count to the limit.
+        for id in limit as u64..2 * limit as u64 {
+            assert!(message_context.wake(MessageId::from(id), 0).is_ok());
+        }
+
+        assert_eq!(
+            message_context.wake(MessageId::from(2 * limit as u64), 0),
+            Err(Error::AwakeningLimitExceeded)
+        );
+    }
+
     #[test]
     fn invalid_out_of_bounds() {
-        let mut message_context =
-            MessageContext::new(Default::default(), Default::default(), Default::default());
+        let mut message_context = MessageContext::new(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
 
         // Use invalid handle 0.
         let out_of_bounds = message_context.send_commit(0, Default::default());
@@ -384,8 +1035,12 @@ mod tests {
 
     #[test]
     fn double_reply() {
-        let mut message_context =
-            MessageContext::new(Default::default(), Default::default(), Default::default());
+        let mut message_context = MessageContext::new(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
 
         // First reply.
         let result = message_context.reply_commit(Default::default());
@@ -396,6 +1051,69 @@ mod tests {
         assert!(matches!(result, Err(Error::DuplicateReply)));
     }
 
+    #[test]
+    fn push_input_forwards_incoming_payload() {
+        let incoming_message = IncomingMessage::new(
+            Default::default(),
+            Default::default(),
+            b"payload".to_vec().try_into().unwrap(),
+            0,
+            0,
+            None,
+        );
+
+        let mut message_context = MessageContext::new_with_settings(
+            DispatchKind::Handle,
+            incoming_message,
+            Default::default(),
+            Default::default(),
+            ContextSettings::new(0, 0, 0, 0, OUTGOING_LIMIT, 10, PROGRAM_CANDIDATES_LIMIT),
+        );
+
+        let handle = message_context.send_init().expect("unreachable");
+        assert!(message_context.send_push_input(handle, 0, 3).is_ok());
+        assert!(message_context.send_push(handle, b"-").is_ok());
+        assert!(message_context.send_push_input(handle, 3, 4).is_ok());
+
+        // Range must be within the incoming payload.
+        assert_eq!(
+            message_context.send_push_input(handle, 5, 3),
+            Err(Error::OutOfBoundsInputRange)
+        );
+        assert_eq!(
+            message_context.send_push_input(handle, u32::MAX, 1),
+            Err(Error::OutOfBoundsInputRange)
+        );
+
+        // Forwarded bytes are counted against outgoing bytes limit.
+        assert_eq!(
+            message_context.send_push_input(handle, 0, 3),
+            Err(Error::OutgoingMessagesBytesLimitExceeded)
+        );
+
+        message_context
+            .send_commit(handle, HandlePacket::default())
+            .expect("unreachable");
+        assert_eq!(
+            message_context.send_push_input(handle, 0, 1),
+            Err(Error::LateAccess)
+        );
+
+        assert!(message_context.reply_push_input(0, 7).is_ok());
+        message_context
+            .reply_commit(ReplyPacket::default())
+            .expect("unreachable");
+        assert_eq!(
+            message_context.reply_push_input(0, 1),
+            Err(Error::LateAccess)
+        );
+
+        let (outcome, _) = message_context.drain();
+        let (dispatches, _) = outcome.drain();
+        assert_eq!(dispatches[0].0.payload(), b"pay-load");
+        assert_eq!(dispatches[1].0.payload(), b"payload");
+    }
+
     // Set of constants for clarity of a part of the test
     const INCOMING_MESSAGE_ID: u64 = 3;
     const INCOMING_MESSAGE_SOURCE: u64 = 4;
@@ -407,7 +1125,7 @@ mod tests {
         let incoming_message = IncomingMessage::new(
             MessageId::from(INCOMING_MESSAGE_ID),
             ProgramId::from(INCOMING_MESSAGE_SOURCE),
-            vec![1, 2],
+            vec![1, 2].try_into().unwrap(),
             0,
             0,
             None,
@@ -415,6 +1133,7 @@ mod tests {
 
         // Creating a message context
         let mut context = MessageContext::new(
+            DispatchKind::Handle,
             incoming_message,
             ids::ProgramId::from(INCOMING_MESSAGE_ID),
             None,
@@ -426,7 +1145,7 @@ mod tests {
         assert!(context.outcome.reply.is_none());
 
         // Creating a reply packet
-        let reply_packet = ReplyPacket::new(vec![0, 0], 0);
+        let reply_packet = ReplyPacket::new(vec![0, 0].try_into().unwrap(), 0);
 
         // Checking that we are able to initialize reply
         assert!(context.reply_push(&[1, 2, 3]).is_ok());
@@ -518,6 +1237,240 @@ mod tests {
         // Checking that on drain we get only messages that were fully formed (directly sent or committed)
         let (expected_result, _) = context.drain();
         assert_eq!(expected_result.handle.len(), 1);
-        assert_eq!(expected_result.handle[0].payload(), vec![5, 7, 9]);
+        assert_eq!(expected_result.handle[0].0.payload(), vec![5, 7, 9]);
+    }
+
+    #[test]
+    fn max_payload_size_exceeded() {
+        let mut message_context = MessageContext::new(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+        );
+
+        let handle = message_context
+            .send_init()
+            .expect("Outgoing limit is not reached");
+        let payload = vec![0; MAX_PAYLOAD_SIZE];
+
+        assert_eq!(message_context.send_push(handle, &payload), Ok(()));
+        assert_eq!(
+            message_context.send_push(handle, &[0]),
+            Err(Error::MaxMessageSizeExceed)
+        );
+
+        // Payload of the packet is prepended with the pushed one on commit.
+        let packet = HandlePacket::new(Default::default(), vec![0].try_into().unwrap(), 0);
+        assert_eq!(
+            message_context.send_commit(handle, packet),
+            Err(Error::MaxMessageSizeExceed)
+        );
+
+        // Pushed payload is kept, so the message still could be committed.
+        assert!(message_context
+            .send_commit(handle, HandlePacket::default())
+            .is_ok());
+
+        assert_eq!(message_context.reply_push(&payload), Ok(()));
+
+        let packet = ReplyPacket::new(vec![0].try_into().unwrap(), 0);
+        assert_eq!(
+            message_context.reply_commit(packet),
+            Err(Error::MaxMessageSizeExceed)
+        );
+        assert!(message_context.reply_commit(Default::default()).is_ok());
+    }
+
+    #[test]
+    fn reply_in_signal() {
+        let mut message_context = MessageContext::new(
+            DispatchKind::Signal,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        assert_eq!(
+            message_context.reply_push(&[0]),
+            Err(Error::IncorrectEntryForReply)
+        );
+        assert_eq!(
+            message_context.reply_commit(Default::default()),
+            Err(Error::IncorrectEntryForReply)
+        );
+    }
+
+    #[test]
+    fn outgoing_limit_in_signal() {
+        let mut message_context = MessageContext::new(
+            DispatchKind::Signal,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        for _ in 0..SIGNAL_OUTGOING_LIMIT {
+            let handle = message_context.send_init().expect("unreachable");
+            assert!(message_context
+                .send_commit(handle, HandlePacket::default())
+                .is_ok());
+        }
+
+        assert_eq!(message_context.send_init(), Err(Error::LimitExceeded));
+        assert_eq!(
+            message_context.send_delayed(HandlePacket::default(), 0),
+            Err(Error::LimitExceeded)
+        );
+    }
+
+    #[test]
+    fn delayed_sending() {
+        let mut message_context = MessageContext::new(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        let handle = message_context.send_init().expect("unreachable");
+        message_context
+            .send_commit(handle, HandlePacket::default())
+            .expect("unreachable");
+
+        message_context
+            .send_delayed(HandlePacket::default(), 10)
+            .expect("unreachable");
+
+        let (outcome, _) = message_context.drain();
+        let (dispatches, _) = outcome.drain();
+
+        let delays: Vec<u32> = dispatches.iter().map(|(_, delay, _)| *delay).collect();
+        assert_eq!(delays, vec![0, 10]);
+    }
+
+    #[test]
+    fn delayed_waking() {
+        let mut message_context = MessageContext::new(
+            DispatchKind::Handle,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        message_context
+            .wake(MessageId::from(1), 0)
+            .expect("unreachable");
+        message_context
+            .wake(MessageId::from(2), 10)
+            .expect("unreachable");
+
+        // Delayed waking is still a waking, so duplicates are checked.
+        assert_eq!(
+            message_context.wake(MessageId::from(1), 5),
+            Err(Error::DuplicateWaking)
+        );
+
+        let (outcome, _) = message_context.drain();
+        let (_, awakening) = outcome.drain();
+
+        assert_eq!(
+            awakening,
+            vec![(MessageId::from(1), 0), (MessageId::from(2), 10)]
+        );
+    }
+
+    fn fee_settings(sending: u64, reply: u64, waking: u64, byte: u64) -> ContextSettings {
+        ContextSettings::new(
+            sending,
+            reply,
+            waking,
+            byte,
+            OUTGOING_LIMIT,
+            OUTGOING_BYTES_LIMIT,
+            PROGRAM_CANDIDATES_LIMIT,
+        )
+    }
+
+    #[test]
+    fn fees_differ_per_kind() {
+        let settings = fee_settings(10, 20, 30, 2);
+
+        assert_eq!(settings.sending_fee(0), 10);
+        assert_eq!(settings.sending_fee(5), 20);
+        assert_eq!(settings.reply_fee(0), 20);
+        assert_eq!(settings.reply_fee(5), 30);
+        // Waking has no payload, so only the base fee is charged.
+        assert_eq!(settings.waking_fee(), 30);
+    }
+
+    #[test]
+    fn zero_fees() {
+        for settings in [ContextSettings::default(), fee_settings(0, 0, 0, 0)] {
+            for len in [0, 1, MAX_PAYLOAD_SIZE] {
+                assert_eq!(settings.sending_fee(len), 0);
+                assert_eq!(settings.reply_fee(len), 0);
+            }
+
+            assert_eq!(settings.waking_fee(), 0);
+        }
+
+        // Payload isn't charged without byte fee.
+        let settings = fee_settings(1, 2, 3, 0);
+
+        assert_eq!(settings.sending_fee(MAX_PAYLOAD_SIZE), 1);
+        assert_eq!(settings.reply_fee(MAX_PAYLOAD_SIZE), 2);
+    }
+
+    #[test]
+    fn max_fees_saturate() {
+        let settings = fee_settings(u64::MAX, u64::MAX, u64::MAX, u64::MAX);
+
+        for len in [0, 1, MAX_PAYLOAD_SIZE] {
+            assert_eq!(settings.sending_fee(len), u64::MAX);
+            assert_eq!(settings.reply_fee(len), u64::MAX);
+        }
+
+        assert_eq!(settings.waking_fee(), u64::MAX);
+
+        // Only byte fee is maximal.
+        let settings = fee_settings(1, 1, 0, u64::MAX);
+
+        assert_eq!(settings.sending_fee(0), 1);
+        assert_eq!(settings.sending_fee(1), u64::MAX);
+        assert_eq!(settings.reply_fee(MAX_PAYLOAD_SIZE), u64::MAX);
+
+        // Base fee is maximal, while payload is charged.
+        let settings = fee_settings(u64::MAX - 1, u64::MAX, 0, 1);
+
+        assert_eq!(settings.sending_fee(0), u64::MAX - 1);
+        assert_eq!(settings.sending_fee(1), u64::MAX);
+        assert_eq!(settings.sending_fee(2), u64::MAX);
+    }
+
+    #[test]
+    fn exact_gas_covers_fee() {
+        let settings = fee_settings(100, 200, 300, 3);
+
+        for fee in [
+            settings.sending_fee(10),
+            settings.reply_fee(10),
+            settings.waking_fee(),
+            fee_settings(u64::MAX, 0, 0, 0).sending_fee(0),
+        ] {
+            let mut counter = GasCounter::new(fee);
+            assert_eq!(counter.charge(fee), ChargeResult::Enough);
+            assert_eq!(counter.left(), 0);
+
+            let mut counter = GasCounter::new(fee - 1);
+            assert_eq!(counter.charge(fee), ChargeResult::NotEnough);
+        }
+
+        // Zero fee is charged even if there is no gas left.
+        let mut counter = GasCounter::new(0);
+        assert_eq!(
+            counter.charge(ContextSettings::default().sending_fee(MAX_PAYLOAD_SIZE)),
+            ChargeResult::Enough
+        );
     }
 }
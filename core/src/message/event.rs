@@ -0,0 +1,85 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::buffer::LimitedVec;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use core::convert::TryInto;
+use scale_info::TypeInfo;
+
+/// Max amount of topics, which one event can have.
+pub const MAX_EVENT_TOPICS: usize = 4;
+
+/// Max payload size, which one event can have (16 KiB).
+pub const MAX_EVENT_PAYLOAD_SIZE: usize = 16 * 1024;
+
+/// Event topics or payload size exceed error.
+#[derive(
+    Clone, Copy, Default, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo,
+)]
+pub struct EventSizeError;
+
+impl From<EventSizeError> for &str {
+    fn from(_: EventSizeError) -> Self {
+        "Event size limit exceeded"
+    }
+}
+
+/// Topics of the event.
+pub type EventTopics = LimitedVec<[u8; 32], EventSizeError, MAX_EVENT_TOPICS>;
+
+/// Payload of the event.
+pub type EventPayload = LimitedVec<u8, EventSizeError, MAX_EVENT_PAYLOAD_SIZE>;
+
+/// Event emitted by program.
+///
+/// Unlike messages, events have no destination: they are recorded
+/// on chain along with their topics, so that the events could be
+/// filtered by the topics without decoding the payload.
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq, Decode, Encode, TypeInfo)]
+pub struct ProgramEvent {
+    /// Topics of the event.
+    topics: EventTopics,
+    /// Payload of the event.
+    payload: EventPayload,
+}
+
+impl ProgramEvent {
+    /// Create new event, checking limits of its topics and payload.
+    pub fn new(topics: Vec<[u8; 32]>, payload: Vec<u8>) -> Result<Self, EventSizeError> {
+        Ok(Self {
+            topics: topics.try_into()?,
+            payload: payload.try_into()?,
+        })
+    }
+
+    /// Event topics.
+    pub fn topics(&self) -> &[[u8; 32]] {
+        self.topics.inner()
+    }
+
+    /// Event payload reference.
+    pub fn payload(&self) -> &[u8] {
+        self.payload.inner()
+    }
+
+    /// Decompose event into topics and payload.
+    pub fn into_parts(self) -> (Vec<[u8; 32]>, Vec<u8>) {
+        (self.topics.into_vec(), self.payload.into_vec())
+    }
+}
@@ -150,8 +150,10 @@ impl HandlePacket {
     }
 
     /// Prepend payload.
-    pub(super) fn prepend(&mut self, data: Payload) {
-        self.payload.splice(0..0, data);
+    ///
+    /// Returns given payload back, if the resulting one exceeds the limit.
+    pub(super) fn try_prepend(&mut self, data: Payload) -> Result<(), Payload> {
+        self.payload.try_prepend(data)
     }
 
     /// Packet destination.
@@ -172,4 +174,8 @@ impl Packet for HandlePacket {
     fn value(&self) -> Value {
         self.value
     }
+
+    fn kind() -> DispatchKind {
+        DispatchKind::Handle
+    }
 }
@@ -19,8 +19,8 @@
 use crate::{
     ids::{MessageId, ProgramId},
     message::{
-        ContextStore, DispatchKind, ExitCode, GasLimit, Payload, StoredDispatch, StoredMessage,
-        Value,
+        ContextStore, DispatchKind, ExitCode, GasLimit, Payload, ReplyDetails, StoredDispatch,
+        StoredMessage, Value,
     },
 };
 use codec::{Decode, Encode};
@@ -43,7 +43,7 @@ pub struct IncomingMessage {
     /// Message value.
     value: Value,
     /// Message id replied on with exit code.
-    reply: Option<(MessageId, ExitCode)>,
+    reply: Option<ReplyDetails>,
 }
 
 impl IncomingMessage {
@@ -54,7 +54,7 @@ impl IncomingMessage {
         payload: Payload,
         gas_limit: GasLimit,
         value: Value,
-        reply: Option<(MessageId, ExitCode)>,
+        reply: Option<ReplyDetails>,
     ) -> Self {
         Self {
             id,
@@ -103,7 +103,7 @@ impl IncomingMessage {
     }
 
     /// Message reply.
-    pub fn reply(&self) -> Option<(MessageId, ExitCode)> {
+    pub fn reply(&self) -> Option<ReplyDetails> {
         self.reply
     }
 
@@ -114,12 +114,12 @@ impl IncomingMessage {
 
     /// Message id what this message replies to, if reply.
     pub fn reply_to(&self) -> Option<MessageId> {
-        self.reply.map(|(id, _)| id)
+        self.reply.map(|details| details.reply_to())
     }
 
     /// Exit code of the message, if reply.
     pub fn exit_code(&self) -> Option<ExitCode> {
-        self.reply.map(|(_, exit_code)| exit_code)
+        self.reply.map(|details| details.exit_code())
     }
 }
 
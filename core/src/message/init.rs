@@ -186,4 +186,8 @@ impl Packet for InitPacket {
     fn value(&self) -> Value {
         self.value
     }
+
+    fn kind() -> DispatchKind {
+        DispatchKind::Init
+    }
 }
@@ -18,28 +18,58 @@
 
 //! Message processing module.
 
+use crate::buffer::LimitedVec;
 use alloc::vec::Vec;
 use codec::{Decode, Encode};
+use gear_core_errors::MessageError;
 use scale_info::TypeInfo;
 
 mod common;
 mod context;
+mod event;
 mod handle;
 mod incoming;
 mod init;
 mod reply;
+mod signal;
 mod stored;
 
-pub use common::{Dispatch, Message};
+pub use common::{Dispatch, Message, ReplyDetails};
 pub use context::{ContextOutcome, ContextSettings, ContextStore, MessageContext};
+pub use event::{
+    EventPayload, EventSizeError, EventTopics, ProgramEvent, MAX_EVENT_PAYLOAD_SIZE,
+    MAX_EVENT_TOPICS,
+};
 pub use handle::{HandleMessage, HandlePacket};
 pub use incoming::{IncomingDispatch, IncomingMessage};
 pub use init::{InitMessage, InitPacket};
 pub use reply::{ReplyMessage, ReplyPacket};
+pub use signal::SignalMessage;
 pub use stored::{StoredDispatch, StoredMessage};
 
+/// Max payload size which one message can have (8 MiB).
+pub const MAX_PAYLOAD_SIZE: usize = 8 * 1024 * 1024;
+
+/// Payload size exceed error.
+#[derive(
+    Clone, Copy, Default, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo,
+)]
+pub struct PayloadSizeError;
+
+impl From<PayloadSizeError> for MessageError {
+    fn from(_: PayloadSizeError) -> Self {
+        MessageError::MaxMessageSizeExceed
+    }
+}
+
+impl From<PayloadSizeError> for &str {
+    fn from(_: PayloadSizeError) -> Self {
+        "Payload size limit exceeded"
+    }
+}
+
 /// Payload type for message.
-pub type Payload = Vec<u8>;
+pub type Payload = LimitedVec<u8, PayloadSizeError, MAX_PAYLOAD_SIZE>;
 
 /// Gas limit type for message.
 pub type GasLimit = u64;
@@ -62,6 +92,8 @@ pub enum DispatchKind {
     Handle,
     /// Handle reply.
     Reply,
+    /// System signal.
+    Signal,
 }
 
 impl DispatchKind {
@@ -71,6 +103,7 @@ impl DispatchKind {
             Self::Init => "init",
             Self::Handle => "handle",
             Self::Reply => "handle_reply",
+            Self::Signal => "handle_signal",
         }
     }
 
@@ -88,6 +121,23 @@ impl DispatchKind {
     pub fn is_reply(&self) -> bool {
         matches!(self, Self::Reply)
     }
+
+    /// Check if kind is signal.
+    pub fn is_signal(&self) -> bool {
+        matches!(self, Self::Signal)
+    }
+}
+
+/// Type of wait call, which put the message into the waitlist.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
+pub enum MessageWaitedType {
+    /// Plain `wait`: message stays in the waitlist while it's able to pay the rent.
+    Wait,
+    /// `wait_for`: message is woken after exactly given amount of blocks.
+    WaitFor,
+    /// `wait_up_to`: message is woken after given amount of blocks
+    /// or earlier, if it isn't able to pay the rent for all of them.
+    WaitUpTo,
 }
 
 /// Message packet.
@@ -102,4 +152,7 @@ pub trait Packet {
 
     /// Packet value.
     fn value(&self) -> Value;
+
+    /// Kind of dispatch the packet will be turned into.
+    fn kind() -> DispatchKind;
 }
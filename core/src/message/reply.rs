@@ -19,8 +19,8 @@
 use crate::{
     ids::{MessageId, ProgramId},
     message::{
-        Dispatch, DispatchKind, ExitCode, GasLimit, Message, Packet, Payload, StoredDispatch,
-        StoredMessage, Value,
+        Dispatch, DispatchKind, ExitCode, GasLimit, Message, Packet, Payload, ReplyDetails,
+        StoredDispatch, StoredMessage, Value,
     },
 };
 use codec::{Decode, Encode};
@@ -78,7 +78,7 @@ impl ReplyMessage {
             self.payload,
             self.gas_limit,
             self.value,
-            Some((origin_msg_id, self.exit_code)),
+            Some(ReplyDetails::new(origin_msg_id, self.exit_code)),
         )
     }
 
@@ -191,8 +191,10 @@ impl ReplyPacket {
     }
 
     /// Prepend payload.
-    pub(super) fn prepend(&mut self, data: Payload) {
-        self.payload.splice(0..0, data);
+    ///
+    /// Returns given payload back, if the resulting one exceeds the limit.
+    pub(super) fn try_prepend(&mut self, data: Payload) -> Result<(), Payload> {
+        self.payload.try_prepend(data)
     }
 
     /// Packet exit code.
@@ -213,4 +215,8 @@ impl Packet for ReplyPacket {
     fn value(&self) -> Value {
         self.value
     }
+
+    fn kind() -> DispatchKind {
+        DispatchKind::Reply
+    }
 }
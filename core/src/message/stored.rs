@@ -20,12 +20,12 @@ use crate::{
     ids::{MessageId, ProgramId},
     message::{
         ContextStore, DispatchKind, ExitCode, GasLimit, IncomingDispatch, IncomingMessage, Payload,
-        Value,
+        ReplyDetails, Value,
     },
 };
 use alloc::string::ToString;
 use codec::{Decode, Encode};
-use core::ops::Deref;
+use core::{convert::TryInto, ops::Deref};
 use scale_info::TypeInfo;
 
 /// Stored message.
@@ -45,7 +45,7 @@ pub struct StoredMessage {
     #[codec(compact)]
     value: Value,
     /// Message id replied on with exit code.
-    reply: Option<(MessageId, ExitCode)>,
+    reply: Option<ReplyDetails>,
 }
 
 impl StoredMessage {
@@ -56,7 +56,7 @@ impl StoredMessage {
         destination: ProgramId,
         payload: Payload,
         value: Value,
-        reply: Option<(MessageId, ExitCode)>,
+        reply: Option<ReplyDetails>,
     ) -> Self {
         Self {
             id,
@@ -106,7 +106,7 @@ impl StoredMessage {
     }
 
     /// Message reply.
-    pub fn reply(&self) -> Option<(MessageId, ExitCode)> {
+    pub fn reply(&self) -> Option<ReplyDetails> {
         self.reply
     }
 
@@ -117,12 +117,12 @@ impl StoredMessage {
 
     /// Message id what this message replies to, if reply.
     pub fn reply_to(&self) -> Option<MessageId> {
-        self.reply.map(|(id, _)| id)
+        self.reply.map(|details| details.reply_to())
     }
 
     /// Exit code of the message, if reply.
     pub fn exit_code(&self) -> Option<ExitCode> {
-        self.reply.map(|(_, exit_code)| exit_code)
+        self.reply.map(|details| details.exit_code())
     }
 
     /// Consumes self in order to create new `StoredMessage`, which payload
@@ -132,7 +132,9 @@ impl StoredMessage {
         let payload = D::decode(&mut self.payload.as_ref())
             .ok()?
             .to_string()
-            .into_bytes();
+            .into_bytes()
+            .try_into()
+            .ok()?;
 
         Some(Self { payload, ..self })
     }
@@ -104,7 +104,7 @@ mod types {
 
         /// Wake the start message.
         pub fn wake(&self) {
-            exec::wake(self.message_id);
+            exec::wake(self.message_id).expect("Failed to wake the message");
         }
 
         /// The result of calculation.
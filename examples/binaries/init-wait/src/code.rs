@@ -53,6 +53,6 @@ unsafe extern "C" fn handle_reply() {
         for k in 20..40 {
             TEST_DYNAMIC_MEMORY.insert(k, ());
         }
-        exec::wake(INIT_MESSAGE);
+        exec::wake(INIT_MESSAGE).expect("Failed to wake the message");
     }
 }
@@ -257,12 +257,12 @@ unsafe extern "C" fn handle_reply() {
                 } else {
                     transition.state = TransitionState::Failed;
                 }
-                exec::wake(transition.message_id);
+                exec::wake(transition.message_id).expect("Failed to wake the message");
             }
             Err(e) => {
                 transition.state = TransitionState::Failed;
                 debug!("Error processing reply: {:?}", e);
-                exec::wake(transition.message_id);
+                exec::wake(transition.message_id).expect("Failed to wake the message");
             }
         }
     } else {
@@ -65,7 +65,7 @@ mod wasm {
         match msg::load().expect("provided invalid payload") {
             CreateProgram::Default => {
                 let submitted_code = CHILD_CODE_HASH.into();
-                let new_program_id = prog::create_program_with_gas(
+                let (_init_message_id, new_program_id) = prog::create_program_with_gas(
                     submitted_code,
                     COUNTER.to_le_bytes(),
                     [],
@@ -80,7 +80,7 @@ mod wasm {
             CreateProgram::Custom(custom_child_data) => {
                 for (code_hash, salt, gas_limit) in custom_child_data {
                     let submitted_code = code_hash.into();
-                    let new_program_id =
+                    let (_init_message_id, new_program_id) =
                         prog::create_program_with_gas(submitted_code, &salt, [], gas_limit, 0)
                             .unwrap();
                     let msg_id = msg::send_bytes(new_program_id, [], 0).unwrap();
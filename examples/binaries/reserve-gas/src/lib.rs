@@ -0,0 +1,70 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use gcore::ReservationId;
+use gstd::{msg, prelude::*};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Encode, Debug, Decode, PartialEq, Eq)]
+pub enum Request {
+    /// Reserves `amount` of gas for `duration` blocks.
+    Reserve { amount: u64, duration: u32 },
+    /// Sends the payload to the source, paying with the reservation.
+    SendFromReservation(Vec<u8>),
+    /// Removes the reservation, replying with the amount of released gas.
+    Unreserve,
+}
+
+static mut RESERVATION: Option<ReservationId> = None;
+
+fn process_request(request: Request) {
+    match request {
+        Request::Reserve { amount, duration } => {
+            let id = gcore::exec::reserve_gas(amount, duration).expect("Failed to reserve gas");
+            unsafe { RESERVATION = Some(id) };
+        }
+        Request::SendFromReservation(payload) => {
+            let id = unsafe { RESERVATION.take() }.expect("Gas isn't reserved");
+            gcore::msg::reservation_send(id, msg::source().into(), &payload, 0)
+                .expect("Failed to send from reservation");
+        }
+        Request::Unreserve => {
+            let id = unsafe { RESERVATION.take() }.expect("Gas isn't reserved");
+            let amount = gcore::exec::unreserve_gas(id).expect("Failed to unreserve gas");
+            msg::reply(amount, 0).unwrap();
+        }
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn init() {}
+
+#[no_mangle]
+unsafe extern "C" fn handle() {
+    msg::load::<Request>().map(process_request).unwrap();
+}
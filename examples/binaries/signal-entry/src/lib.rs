@@ -0,0 +1,85 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use gstd::{msg, ActorId};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+/// Gas reserved by `handle` for the signal.
+pub const SIGNAL_GAS: u64 = 1_000_000_000;
+
+#[derive(Encode, Debug, Decode, PartialEq, Eq)]
+pub enum HandleAction {
+    /// Replies, so the gas reserved for the signal is returned.
+    Reply,
+    /// Panics, so the signal is received by `handle_signal`.
+    Panic,
+}
+
+/// Behaviour of `handle_signal`, which is set by the init payload.
+#[derive(Encode, Debug, Decode, PartialEq, Eq, Clone, Copy)]
+pub enum SignalAction {
+    /// Reports the signal to the owner.
+    Report,
+    /// Tries to wait, which isn't allowed in the signal handler.
+    Wait,
+}
+
+// Memory changes of the failed execution are discarded,
+// so the receiver of signal reports is set in `init`.
+static mut OWNER: Option<ActorId> = None;
+static mut SIGNAL_ACTION: SignalAction = SignalAction::Report;
+
+#[no_mangle]
+unsafe extern "C" fn init() {
+    OWNER = Some(msg::source());
+    SIGNAL_ACTION = msg::load().unwrap_or(SignalAction::Report);
+}
+
+#[no_mangle]
+unsafe extern "C" fn handle() {
+    gcore::exec::system_reserve_gas(SIGNAL_GAS).expect("Failed to reserve gas for signal");
+
+    match msg::load::<HandleAction>().expect("Failed to load action") {
+        HandleAction::Reply => {
+            msg::reply_bytes(b"Handled", 0).unwrap();
+        }
+        HandleAction::Panic => panic!("Handle failed on purpose"),
+    }
+}
+
+/// Reports id of the failed message and the signal code to the owner.
+#[no_mangle]
+unsafe extern "C" fn handle_signal() {
+    let owner = OWNER.expect("Owner is set in init");
+
+    if SIGNAL_ACTION == SignalAction::Wait {
+        gstd::exec::wait();
+    }
+
+    msg::send(owner, (msg::reply_to(), msg::exit_code()), 0).unwrap();
+}
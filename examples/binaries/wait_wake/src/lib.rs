@@ -35,6 +35,13 @@ pub use code::WASM_BINARY_OPT as WASM_BINARY;
 pub enum Request {
     EchoWait(u32),
     Wake(MessageId),
+    /// Waits for the given amount of blocks, then replies with the echo.
+    EchoWaitFor {
+        echo: u32,
+        duration: u32,
+    },
+    /// Wakes the message after the given amount of blocks.
+    WakeDelayed(MessageId, u32),
 }
 
 static mut ECHOES: Option<BTreeMap<MessageId, u32>> = None;
@@ -49,7 +56,18 @@ fn process_request(request: Request) {
             };
             exec::wait();
         }
-        Request::Wake(id) => exec::wake(id),
+        Request::Wake(id) => exec::wake(id).expect("Failed to wake the message"),
+        Request::EchoWaitFor { echo, duration } => {
+            unsafe {
+                ECHOES
+                    .get_or_insert_with(BTreeMap::new)
+                    .insert(msg::id(), echo)
+            };
+            exec::wait_for(duration);
+        }
+        Request::WakeDelayed(id, delay) => {
+            exec::wake_delayed(id, delay).expect("Failed to wake the message")
+        }
     }
 }
 
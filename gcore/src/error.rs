@@ -16,11 +16,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-pub use gear_core_errors::{ExtError, MemoryError, MessageError};
+pub use gear_core_errors::{ErrorReplyReason, ExtError, MemoryError, MessageError};
 
 pub type Result<T, E = ExtError> = core::result::Result<T, E>;
 
-#[cfg(feature = "codec")]
 mod sys {
     extern "C" {
         pub fn gr_error(data: *mut u8);
@@ -46,23 +45,31 @@ impl SyscallError {
 
 /// We get an error using `gr_error` syscall which expects
 /// the error occurred earlier in another syscall or you'll get trap.
+/// The syscall writes stable error code followed by the encoded error.
 /// Error decoding is expected to be successful because we use
 /// SCALE codec crate of same versions (at least major ones)
 /// to encode and to decode error so error representation stays same.
-/// If `len` argument is less than actual encoded error length you'll get trap.
+/// If `len` argument is less than actual error data length you'll get trap.
 #[cfg(feature = "codec")]
 fn get_syscall_error(len: u32) -> ExtError {
-    use alloc::vec;
     use codec::Decode;
 
-    unsafe {
-        let mut data = vec![0; len as usize];
-        sys::gr_error(data.as_mut_ptr());
-        ExtError::decode(&mut data.as_slice()).expect("error decoded successfully")
-    }
+    let data = get_syscall_error_data(len);
+    ExtError::decode(&mut &data[ExtError::CODE_SIZE..]).expect("error decoded successfully")
 }
 
+/// Without `codec` feature only stable error code is used, so fields
+/// of the error are zeroed.
 #[cfg(not(feature = "codec"))]
-fn get_syscall_error(_len: u32) -> ExtError {
-    ExtError::Some
+fn get_syscall_error(len: u32) -> ExtError {
+    let data = get_syscall_error_data(len);
+    let mut code = [0; ExtError::CODE_SIZE];
+    code.copy_from_slice(&data[..ExtError::CODE_SIZE]);
+    ExtError::from_u32(u32::from_le_bytes(code)).unwrap_or(ExtError::Some)
+}
+
+fn get_syscall_error_data(len: u32) -> alloc::vec::Vec<u8> {
+    let mut data = alloc::vec![0; len as usize];
+    unsafe { sys::gr_error(data.as_mut_ptr()) };
+    data
 }
@@ -20,20 +20,34 @@
 //!
 //! Provides API for low-level async implementation.
 
-use crate::{ActorId, MessageId};
+use crate::{error::Result, ActorId, MessageId, ReservationId};
 
 mod sys {
+    use crate::error::SyscallError;
+
     extern "C" {
         pub fn gr_block_height() -> u32;
         pub fn gr_block_timestamp() -> u64;
+        pub fn gr_emit_event(
+            topics_ptr: *const [u8; 32],
+            topics_len: u32,
+            payload_ptr: *const u8,
+            payload_len: u32,
+        );
         pub fn gr_exit(value_dest_ptr: *const u8) -> !;
         pub fn gr_gas_available() -> u64;
         pub fn gr_program_id(val: *mut u8);
         pub fn gr_origin(origin_ptr: *mut u8);
         pub fn gr_leave() -> !;
+        pub fn gr_reserve_gas(amount: u64, duration: u32, id_ptr: *mut u8) -> SyscallError;
+        pub fn gr_unreserve_gas(id_ptr: *const u8, amount_ptr: *mut u8) -> SyscallError;
+        pub fn gr_system_reserve_gas(amount: u64) -> SyscallError;
         pub fn gr_value_available(val: *mut u8);
         pub fn gr_wait() -> !;
-        pub fn gr_wake(waker_id_ptr: *const u8);
+        pub fn gr_wait_for(duration: u32) -> !;
+        pub fn gr_wait_up_to(duration: u32) -> !;
+        pub fn gr_wake(waker_id_ptr: *const u8) -> SyscallError;
+        pub fn gr_wake_delayed(waker_id_ptr: *const u8, delay: u32) -> SyscallError;
     }
 }
 
@@ -79,6 +93,35 @@ pub fn block_timestamp() -> u64 {
     unsafe { sys::gr_block_timestamp() }
 }
 
+/// Emit event with topics.
+///
+/// Event is recorded on chain only if the execution succeeds, so indexers
+/// could filter events by topics without parsing payloads. At most 4 topics
+/// and 16 KiB of payload are allowed, otherwise the execution is trapped.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// // Emit event tagged with the "transfer" topic
+/// unsafe extern "C" fn handle() {
+///     let mut topic = [0u8; 32];
+///     topic[..8].copy_from_slice(b"transfer");
+///     exec::emit_event(&[topic], b"42");
+/// }
+/// ```
+pub fn emit_event(topics: &[[u8; 32]], payload: &[u8]) {
+    unsafe {
+        sys::gr_emit_event(
+            topics.as_ptr(),
+            topics.len() as u32,
+            payload.as_ptr(),
+            payload.len() as u32,
+        )
+    }
+}
+
 /// Terminate the execution of a program. The program and all corresponding data
 /// are removed from the storage. This is similiar to
 /// `std::process::exit`. `value_destination` specifies the address where all
@@ -188,6 +231,46 @@ pub fn wait() -> ! {
     unsafe { sys::gr_wait() }
 }
 
+/// Pause the current message handling for the given amount of blocks.
+///
+/// Same as [`wait`], but the message is automatically awakened after
+/// `duration` blocks, if it wasn't awakened earlier with [`wake`]. If
+/// the message isn't able to pay the rent for the whole `duration`, it's
+/// removed from the *waiting queue* with error reply, once the gas is out.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     exec::wait_for(10);
+/// }
+/// ```
+pub fn wait_for(duration: u32) -> ! {
+    unsafe { sys::gr_wait_for(duration) }
+}
+
+/// Pause the current message handling for up to the given amount of blocks.
+///
+/// Same as [`wait_for`], but if the message isn't able to pay the rent
+/// for the whole `duration`, it's awakened earlier, once the gas is out.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     exec::wait_up_to(10);
+/// }
+/// ```
+pub fn wait_up_to(duration: u32) -> ! {
+    unsafe { sys::gr_wait_up_to(duration) }
+}
+
 /// Resume previously paused message handling.
 ///
 /// If a message has been paused using the [`wait`] function, then it is
@@ -202,15 +285,101 @@ pub fn wait() -> ! {
 ///
 /// unsafe extern "C" fn handle() {
 ///     // ...
-///     exec::wake(MessageId::default());
+///     exec::wake(MessageId::default()).unwrap();
+/// }
+/// ```
+pub fn wake(waker_id: MessageId) -> Result<()> {
+    unsafe { sys::gr_wake(waker_id.as_slice().as_ptr()).into_result() }
+}
+
+/// Resume previously paused message handling after the given amount of blocks.
+///
+/// Same as [`wake`], but the message is put into the *processing queue*
+/// only after `delay` blocks, so the program doesn't need to wake it on
+/// every block to poll for some state change.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, MessageId};
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     exec::wake_delayed(MessageId::default(), 10).unwrap();
+/// }
+/// ```
+pub fn wake_delayed(waker_id: MessageId, delay: u32) -> Result<()> {
+    unsafe { sys::gr_wake_delayed(waker_id.as_slice().as_ptr(), delay).into_result() }
+}
+
+/// Reserve the `amount` of gas for the further usage.
+///
+/// Reserved gas is taken from the gas of the current message and is held
+/// for `duration` blocks. Until then, it can be used to send a message with
+/// [`msg::reservation_send`](crate::msg::reservation_send) or be returned
+/// back with [`unreserve_gas`].
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     let id = exec::reserve_gas(5_000_000, 100).unwrap();
 /// }
 /// ```
-pub fn wake(waker_id: MessageId) {
+pub fn reserve_gas(amount: u64, duration: u32) -> Result<ReservationId> {
     unsafe {
-        sys::gr_wake(waker_id.as_slice().as_ptr());
+        let mut id = ReservationId::default();
+        sys::gr_reserve_gas(amount, duration, id.as_mut_slice().as_mut_ptr()).into_result()?;
+        Ok(id)
     }
 }
 
+/// Remove the gas reservation with the given `id`.
+///
+/// Returns the amount of gas, which was held by the reservation.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     let id = exec::reserve_gas(5_000_000, 100).unwrap();
+///     // ...
+///     let amount = exec::unreserve_gas(id).unwrap();
+/// }
+/// ```
+pub fn unreserve_gas(id: ReservationId) -> Result<u64> {
+    unsafe {
+        let mut amount = [0u8; 8];
+        sys::gr_unreserve_gas(id.as_slice().as_ptr(), amount.as_mut_ptr()).into_result()?;
+        Ok(u64::from_le_bytes(amount))
+    }
+}
+
+/// Reserve the `amount` of gas for the signal of the current message.
+///
+/// If execution of the current message fails, the program receives a signal
+/// at the `handle_signal` entry point, which is paid by this reservation.
+/// Otherwise, the reserved gas is returned back. The reservation can be made
+/// only once per message.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     exec::system_reserve_gas(1_000_000).unwrap();
+///     // ...
+/// }
+/// ```
+pub fn system_reserve_gas(amount: u64) -> Result<()> {
+    unsafe { sys::gr_system_reserve_gas(amount).into_result() }
+}
+
 /// Return ID of the current program.
 ///
 /// # Examples
@@ -146,6 +146,25 @@ impl ActorId {
     }
 }
 
+/// Gas reservation identifier.
+///
+/// The identifier is returned by [`exec::reserve_gas`](crate::exec::reserve_gas)
+/// function and can be used later to send a message using the reserved gas
+/// or to unreserve it.
+#[derive(Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq)]
+pub struct ReservationId(pub [u8; 32]);
+
+impl ReservationId {
+    /// Get `ReservationId` represented as a slice of `u8`.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.0[..]
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq)]
 pub struct CodeHash(pub [u8; 32]);
 
@@ -25,7 +25,7 @@
 //! processing a program can send messages to other programs and users including
 //! reply to the initial message.
 
-use crate::{error::Result, ActorId, MessageHandle, MessageId};
+use crate::{error::Result, ActorId, MessageHandle, MessageId, ReservationId};
 use core::mem::MaybeUninit;
 
 mod sys {
@@ -55,6 +55,7 @@ mod sys {
             message_id_ptr: *mut u8,
         ) -> SyscallError;
         pub fn gr_reply_push(data_ptr: *const u8, data_len: u32) -> SyscallError;
+        pub fn gr_reply_push_input(offset: u32, len: u32) -> SyscallError;
         pub fn gr_reply_to(dest: *mut u8);
         pub fn gr_send(
             program: *const u8,
@@ -63,6 +64,14 @@ mod sys {
             value_ptr: *const u8,
             message_id_ptr: *mut u8,
         ) -> SyscallError;
+        pub fn gr_reservation_send(
+            reservation_id_ptr: *const u8,
+            program: *const u8,
+            data_ptr: *const u8,
+            data_len: u32,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+        ) -> SyscallError;
         pub fn gr_send_wgas(
             program: *const u8,
             data_ptr: *const u8,
@@ -84,8 +93,10 @@ mod sys {
             gas_limit: u64,
             value_ptr: *const u8,
         ) -> SyscallError;
+        pub fn gr_send_cancel(handle: u32) -> SyscallError;
         pub fn gr_send_init(handle: *mut u32) -> SyscallError;
         pub fn gr_send_push(handle: u32, data_ptr: *const u8, data_len: u32) -> SyscallError;
+        pub fn gr_send_push_input(handle: u32, offset: u32, len: u32) -> SyscallError;
         pub fn gr_size() -> u32;
         pub fn gr_source(program: *mut u8);
         pub fn gr_value(val: *mut u8);
@@ -333,6 +344,27 @@ pub fn reply_push(payload: &[u8]) -> Result<()> {
     unsafe { sys::gr_reply_push(payload.as_ptr(), payload.len() as _).into_result() }
 }
 
+/// Push a part of the incoming message payload to the current reply message.
+///
+/// Works like [`reply_push`], but takes `len` bytes of the payload of the
+/// message being processed starting from `offset`, so the program doesn't
+/// need to load them into its own memory first.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     msg::reply_push(b"Echo: ").unwrap();
+///     msg::reply_push_input(0, msg::size() as u32).unwrap();
+/// }
+/// ```
+pub fn reply_push_input(offset: u32, len: u32) -> Result<()> {
+    unsafe { sys::gr_reply_push_input(offset, len).into_result() }
+}
+
 /// Get an identifier of the initial message which the current handle_reply
 /// function is called on.
 ///
@@ -450,6 +482,43 @@ pub fn send_with_gas(
     }
 }
 
+/// Same as [`send`], but with gas taken from the reservation.
+///
+/// Message gas limit is equal to the amount of reserved gas.
+/// Reservation is removed after sending.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg, ActorId};
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let id = exec::reserve_gas(5_000_000, 100).unwrap();
+///     msg::reservation_send(id, ActorId::from(1), b"HELLO", 0).unwrap();
+/// }
+/// ```
+pub fn reservation_send(
+    id: ReservationId,
+    program: ActorId,
+    payload: &[u8],
+    value: u128,
+) -> Result<MessageId> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        sys::gr_reservation_send(
+            id.as_slice().as_ptr(),
+            program.as_slice().as_ptr(),
+            payload.as_ptr(),
+            payload.len() as _,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+        )
+        .into_result()?;
+        Ok(message_id)
+    }
+}
+
 /// Finalize and send message formed in parts.
 ///
 /// Gear allows programs to work with messages that consist of several parts.
@@ -600,6 +669,62 @@ pub fn send_push(handle: &MessageHandle, payload: &[u8]) -> Result<()> {
     unsafe { sys::gr_send_push(handle.0, payload.as_ptr(), payload.len() as _).into_result() }
 }
 
+/// Push a part of the incoming message payload to the message to be sent in
+/// parts.
+///
+/// Works like [`send_push`], but takes `len` bytes of the payload of the
+/// message being processed starting from `offset`, so the program doesn't
+/// need to load them into its own memory first.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let msg_handle = msg::send_init().unwrap();
+///     msg::send_push_input(&msg_handle, 0, msg::size() as u32).unwrap();
+///     msg::send_commit(msg_handle, msg::source(), 0).unwrap();
+/// }
+/// ```
+///
+/// # See also
+///
+/// [`send_init`], [`send_push`], [`send_commit`] functions allows to form
+/// and send a message to send in parts.
+pub fn send_push_input(handle: &MessageHandle, offset: u32, len: u32) -> Result<()> {
+    unsafe { sys::gr_send_push_input(handle.0, offset, len).into_result() }
+}
+
+/// Cancel the message to be sent in parts.
+///
+/// Gear allows programs to work with messages in parts.
+/// This function discards the message specified by message `handle`, which
+/// was formed with [`send_init`] and [`send_push`], but not committed yet.
+/// The handle doesn't count against the limit of outgoing messages anymore.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let msg_handle = msg::send_init().unwrap();
+///     msg::send_push(&msg_handle, b"PING");
+///     msg::send_cancel(msg_handle);
+/// }
+/// ```
+///
+/// # See also
+///
+/// [`send_init`], [`send_push`], [`send_commit`] functions allows to form
+/// and send a message to send in parts.
+pub fn send_cancel(handle: MessageHandle) -> Result<()> {
+    unsafe { sys::gr_send_cancel(handle.0).into_result() }
+}
+
 /// Get the payload size of the message being processed.
 ///
 /// This function is used to obtain the payload size of the current message
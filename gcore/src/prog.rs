@@ -18,7 +18,7 @@
 
 //! Program creation API for Gear programs.
 
-use crate::{error::Result, ActorId, CodeHash};
+use crate::{error::Result, ActorId, CodeHash, MessageId};
 
 mod sys {
     use crate::error::SyscallError;
@@ -31,6 +31,7 @@ mod sys {
             data_ptr: *const u8,
             data_len: u32,
             value_ptr: *const u8,
+            message_id_ptr: *mut u8,
             program_id_ptr: *mut u8,
         ) -> SyscallError;
 
@@ -42,6 +43,7 @@ mod sys {
             data_len: u32,
             gas_limit: u64,
             value_ptr: *const u8,
+            message_id_ptr: *mut u8,
             program_id_ptr: *mut u8,
         ) -> SyscallError;
     }
@@ -53,8 +55,9 @@ pub fn create_program(
     salt: &[u8],
     payload: &[u8],
     value: u128,
-) -> Result<ActorId> {
+) -> Result<(MessageId, ActorId)> {
     unsafe {
+        let mut message_id = MessageId::default();
         let mut program_id = ActorId::default();
         sys::gr_create_program(
             code_hash.as_slice().as_ptr(),
@@ -63,14 +66,19 @@ pub fn create_program(
             payload.as_ptr(),
             payload.len() as _,
             value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
             program_id.as_mut_slice().as_mut_ptr(),
         )
         .into_result()?;
-        Ok(program_id)
+        Ok((message_id, program_id))
     }
 }
 
-/// Creates a new program and returns its address, with gas limit.
+/// Creates a new program with gas limit and returns the id of its
+/// initialization message together with the new program address.
+///
+/// The message id can be used to correlate a reply from the `init` function
+/// of the created program.
 ///
 /// The function creates a program initialization message and, as
 /// any message send function in the crate, this one requires common additional
@@ -106,7 +114,7 @@ pub fn create_program(
 ///     let submitted_code: CodeHash =
 ///         hex_literal::hex!("abf3746e72a6e8740bd9e12b879fbdd59e052cb390f116454e9116c22021ae4a")
 ///             .into();
-///     let new_program_id =
+///     let (init_message_id, new_program_id) =
 ///         prog::create_program_with_gas(submitted_code, &get().to_le_bytes(), b"", 10_000, 0)
 ///             .unwrap();
 /// }
@@ -120,7 +128,7 @@ pub fn create_program(
 ///     # let submitted_code: CodeHash = hex_literal::hex!("abf3746e72a6e8740bd9e12b879fbdd59e052cb390f116454e9116c22021ae4a").into();
 ///     let mut salt = vec![0u8; msg::size()];
 ///     msg::load(&mut salt[..]);
-///     let new_program_id = prog::create_program_with_gas(submitted_code, &salt, b"", 10_000, 0).unwrap();
+///     let (_, new_program_id) = prog::create_program_with_gas(submitted_code, &salt, b"", 10_000, 0).unwrap();
 /// }
 /// ```
 ///
@@ -133,7 +141,7 @@ pub fn create_program(
 ///     # let submitted_code: CodeHash = hex_literal::hex!("abf3746e72a6e8740bd9e12b879fbdd59e052cb390f116454e9116c22021ae4a").into();
 ///     # let mut salt = vec![0u8; msg::size()];
 ///     # msg::load(&mut salt[..]);
-///     let new_program_id = prog::create_program_with_gas(submitted_code, &salt, b"", 10_000, 0).unwrap();
+///     let (_, new_program_id) = prog::create_program_with_gas(submitted_code, &salt, b"", 10_000, 0).unwrap();
 ///     msg::send_with_gas(new_program_id, b"payload for a new program", 10_000, 0).unwrap();
 /// }
 /// ```
@@ -143,8 +151,9 @@ pub fn create_program_with_gas(
     payload: &[u8],
     gas_limit: u64,
     value: u128,
-) -> Result<ActorId> {
+) -> Result<(MessageId, ActorId)> {
     unsafe {
+        let mut message_id = MessageId::default();
         let mut program_id = ActorId::default();
         sys::gr_create_program_wgas(
             code_hash.as_slice().as_ptr(),
@@ -154,9 +163,10 @@ pub fn create_program_with_gas(
             payload.len() as _,
             gas_limit,
             value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
             program_id.as_mut_slice().as_mut_ptr(),
         )
         .into_result()?;
-        Ok(program_id)
+        Ok((message_id, program_id))
     }
 }
@@ -21,10 +21,20 @@ pub mod ext {
     mod sys {
         extern "C" {
             pub fn gr_debug(msg_ptr: *const u8, msg_len: u32);
+            pub fn gr_metric_increment(key_ptr: *const u8, value: u64);
         }
     }
 
     pub fn debug(s: &str) {
         unsafe { sys::gr_debug(s.as_ptr(), s.as_bytes().len() as _) }
     }
+
+    /// Add `value` to the program's custom counter, identified by
+    /// `key_hash` - hash of the counter's name.
+    ///
+    /// Counters are accumulated only on chains with debug pallet, which
+    /// stores them per program, and only for successful executions.
+    pub fn metric_increment(key_hash: [u8; 32], value: u64) {
+        unsafe { sys::gr_metric_increment(key_hash.as_ptr(), value) }
+    }
 }
@@ -278,7 +278,9 @@ pub fn check_messages(
                                         MetaData::CodecBytes((*msg.payload()).to_vec())
                                             .convert(&path, &meta_type)
                                             .expect("Unable to get bytes")
-                                            .into_bytes();
+                                            .into_bytes()
+                                            .try_into()
+                                            .expect("Payload size limit exceeded");
 
                                     *msg = StoredMessage::new(
                                         msg.id(),
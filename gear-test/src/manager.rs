@@ -20,9 +20,12 @@ use crate::check::ExecutionContext;
 use core_processor::common::*;
 use gear_core::{
     code::{Code, CodeAndId, InstrumentedCodeAndId},
-    ids::{CodeId, MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber, WasmPageNumber},
-    message::{Dispatch, DispatchKind, GasLimit, StoredDispatch, StoredMessage},
+    message::{
+        Dispatch, DispatchKind, ExitCode, GasLimit, MessageWaitedType, ProgramEvent, SignalMessage,
+        StoredDispatch, StoredMessage,
+    },
     program::Program,
 };
 use std::{
@@ -144,6 +147,8 @@ impl ExecutionContext for InMemoryExtManager {
                 executable_data: Some(ExecutableActorData {
                     program: program.clone(),
                     pages_data: Default::default(),
+                    gas_reservation_map: Default::default(),
+                    waitlist_full: false,
                 }),
             },
         );
@@ -230,7 +235,15 @@ impl JournalHandler for InMemoryExtManager {
             self.dispatch_queue.remove(index);
         }
     }
-    fn send_dispatch(&mut self, _message_id: MessageId, dispatch: Dispatch) {
+    // There is no scheduler in the test environment,
+    // so delayed dispatches are sent right away.
+    fn send_dispatch(
+        &mut self,
+        _message_id: MessageId,
+        dispatch: Dispatch,
+        _delay: u32,
+        _reservation: Option<ReservationId>,
+    ) {
         let destination = dispatch.destination();
         if self.actors.contains_key(&destination) || self.marked_destinations.contains(&destination)
         {
@@ -259,16 +272,26 @@ impl JournalHandler for InMemoryExtManager {
             self.log.push(dispatch.into_parts().1.into_stored());
         }
     }
-    fn wait_dispatch(&mut self, dispatch: StoredDispatch) {
+    // There is no scheduler in the test environment,
+    // so waiting messages never expire.
+    fn wait_dispatch(
+        &mut self,
+        dispatch: StoredDispatch,
+        _duration: Option<u32>,
+        _waited_type: MessageWaitedType,
+    ) {
         self.message_consumed(dispatch.id());
         self.wait_list
             .insert((dispatch.destination(), dispatch.id()), dispatch);
     }
+    // There is no scheduler in the test environment,
+    // so delayed wakes are done right away.
     fn wake_message(
         &mut self,
         _message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
+        _delay: u32,
     ) {
         if let Some(dispatch) = self.wait_list.remove(&(program_id, awakening_id)) {
             self.dispatch_queue.push_back(dispatch);
@@ -334,7 +357,12 @@ impl JournalHandler for InMemoryExtManager {
         };
     }
 
-    fn store_new_programs(&mut self, code_hash: CodeId, candidates: Vec<(ProgramId, MessageId)>) {
+    fn store_new_programs(
+        &mut self,
+        _program_id: ProgramId,
+        code_hash: CodeId,
+        candidates: Vec<(ProgramId, MessageId)>,
+    ) {
         if let Some(code) = self.original_codes.get(&code_hash).cloned() {
             for (candidate_id, init_message_id) in candidates {
                 if !self.actors.contains_key(&candidate_id) {
@@ -360,4 +388,38 @@ impl JournalHandler for InMemoryExtManager {
     fn stop_processing(&mut self, _dispatch: StoredDispatch, _gas_burned: u64) {
         panic!("Processing stopped. Used for on-chain logic only.");
     }
+
+    // Gas isn't tracked in the test environment, so reservations are ignored.
+    fn reserve_gas(
+        &mut self,
+        _message_id: MessageId,
+        _reservation_id: ReservationId,
+        _program_id: ProgramId,
+        _amount: u64,
+        _expiration: u32,
+    ) {
+    }
+
+    fn unreserve_gas(
+        &mut self,
+        _reservation_id: ReservationId,
+        _program_id: ProgramId,
+        _expiration: u32,
+    ) {
+    }
+
+    fn system_reserve_gas(&mut self, _message_id: MessageId, _amount: u64) {}
+
+    fn system_unreserve_gas(&mut self, _message_id: MessageId) {}
+
+    fn send_signal(&mut self, message_id: MessageId, destination: ProgramId, exit_code: ExitCode) {
+        let dispatch =
+            SignalMessage::new(message_id, exit_code).into_dispatch(message_id, destination);
+
+        self.send_dispatch(message_id, dispatch, 0, None);
+    }
+
+    fn increment_metric(&mut self, _program_id: ProgramId, _key: [u8; 32], _value: u64) {}
+
+    fn emit_event(&mut self, _program_id: ProgramId, _event: ProgramEvent) {}
 }
@@ -39,6 +39,8 @@ use wasm_instrument::gas_metering::ConstantCostRules;
 
 pub const EXISTENTIAL_DEPOSIT: u128 = 500;
 pub const OUTGOING_LIMIT: u32 = 1024;
+pub const OUTGOING_BYTES_LIMIT: u32 = 64 * 1024 * 1024;
+pub const PROGRAM_CANDIDATES_LIMIT: u32 = 128;
 pub const MAILBOX_THRESHOLD: u64 = 3000;
 
 pub fn parse_payload(payload: String) -> String {
@@ -110,6 +112,8 @@ where
             executable_data: Some(ExecutableActorData {
                 program,
                 pages_data: Default::default(),
+                gas_reservation_map: Default::default(),
+                waitlist_full: false,
             }),
         },
         dispatch: message.into(),
@@ -189,7 +193,9 @@ where
                 message: IncomingMessage::new(
                     message_id,
                     init_source,
-                    init_message,
+                    init_message
+                        .try_into()
+                        .expect("Payload size limit exceeded"),
                     program.init_gas_limit.unwrap_or(GAS_LIMIT),
                     program.init_value.unwrap_or(0) as u128,
                     None,
@@ -247,14 +253,14 @@ where
             message_id,
             message_source,
             message.destination.to_program_id(),
-            payload,
+            payload.try_into().expect("Payload size limit exceeded"),
             Some(gas_limit),
             message.value.unwrap_or_default() as _,
             None,
         );
         let dispatch = Dispatch::new(DispatchKind::Handle, message);
 
-        journal_handler.send_dispatch(Default::default(), dispatch);
+        journal_handler.send_dispatch(Default::default(), dispatch, 0, None);
 
         nonce += 1;
     }
@@ -359,8 +365,12 @@ fn test_block_config(block_info: BlockInfo) -> BlockConfig {
         allocations_config: Default::default(),
         existential_deposit: EXISTENTIAL_DEPOSIT,
         outgoing_limit: OUTGOING_LIMIT,
+        outgoing_bytes_limit: OUTGOING_BYTES_LIMIT,
+        program_candidates_limit: PROGRAM_CANDIDATES_LIMIT,
+        outgoing_scaling: None,
         host_fn_weights: Default::default(),
         forbidden_funcs: Default::default(),
         mailbox_threshold: MAILBOX_THRESHOLD,
+        gas_minimums: Default::default(),
     }
 }
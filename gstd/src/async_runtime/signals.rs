@@ -69,7 +69,7 @@ impl WakeSignals {
             if let Some(waker) = &signal.waker {
                 waker.wake_by_ref();
             }
-            crate::exec::wake(signal.message_id);
+            crate::exec::wake(signal.message_id).expect("Failed to wake the message");
         } else {
             crate::debug!("Received reply for the message we don't expect reply to or already processed before");
         }
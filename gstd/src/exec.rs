@@ -77,8 +77,8 @@
 //!     let _my_balance = exec::value_available();
 //! }
 //! ```
-use crate::{ActorId, MessageId};
-pub use gcore::exec::{block_height, block_timestamp, gas_available, value_available};
+use crate::{common::errors::Result, ActorId, MessageId};
+pub use gcore::exec::{block_height, block_timestamp, emit_event, gas_available, value_available};
 
 /// Terminate the execution of a program. The program and all corresponding data
 /// are removed from the storage. This is similiar to
@@ -142,6 +142,46 @@ pub fn wait() -> ! {
     gcore::exec::wait()
 }
 
+/// Pause the current message handling for the given amount of blocks.
+///
+/// Same as [`wait`], but the message is automatically awakened after
+/// `duration` blocks, if it wasn't awakened earlier with [`wake`]. If
+/// the message isn't able to pay the rent for the whole `duration`, it's
+/// removed from the *waiting queue* with error reply, once the gas is out.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     exec::wait_for(10);
+/// }
+/// ```
+pub fn wait_for(duration: u32) -> ! {
+    gcore::exec::wait_for(duration)
+}
+
+/// Pause the current message handling for up to the given amount of blocks.
+///
+/// Same as [`wait_for`], but if the message isn't able to pay the rent
+/// for the whole `duration`, it's awakened earlier, once the gas is out.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     exec::wait_up_to(10);
+/// }
+/// ```
+pub fn wait_up_to(duration: u32) -> ! {
+    gcore::exec::wait_up_to(duration)
+}
+
 /// Resume previously paused message handling.
 ///
 /// If a message has been paused using the [`wait`] function, then it is
@@ -157,11 +197,34 @@ pub fn wait() -> ! {
 /// unsafe extern "C" fn handle() {
 ///     // ...
 ///     let msg_id = msg::id();
-///     exec::wake(msg_id);
+///     exec::wake(msg_id).unwrap();
+/// }
+/// ```
+pub fn wake(waker_id: MessageId) -> Result<()> {
+    gcore::exec::wake(waker_id.into())?;
+    Ok(())
+}
+
+/// Resume previously paused message handling after the given amount of blocks.
+///
+/// Same as [`wake`], but the message is put into the *processing queue*
+/// only after `delay` blocks, so the program doesn't need to wake it on
+/// every block to poll for some state change.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let msg_id = msg::id();
+///     exec::wake_delayed(msg_id, 10).unwrap();
 /// }
 /// ```
-pub fn wake(waker_id: MessageId) {
-    gcore::exec::wake(waker_id.into())
+pub fn wake_delayed(waker_id: MessageId, delay: u32) -> Result<()> {
+    gcore::exec::wake_delayed(waker_id.into(), delay)?;
+    Ok(())
 }
 
 /// Return ID of the current program.
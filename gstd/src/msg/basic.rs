@@ -54,6 +54,8 @@ where
 /// [`msg::send_init`](crate::msg::send_init) - message initialization.
 /// [`msg::send_push`](crate::msg::send_push) - adds a `payload` part to the
 /// message  specified by `MessageHandle`.
+/// [`msg::send_push_input`](crate::msg::send_push_input) - adds a part of the
+/// incoming message payload to the message specified by `MessageHandle`.
 /// [`msg::send_commit`](crate::msg::send_commit) - send a message with the
 /// following arguments:
 ///     - the address of the target account.
@@ -61,6 +63,8 @@ where
 ///     reply message processing.
 ///     - the value to be transferred from the current program account
 ///     to the message target account.
+/// [`msg::send_cancel`](crate::msg::send_cancel) - discard a message, which
+/// isn't needed to be sent anymore.
 ///
 /// Send transaction will be posted only after the execution of message
 /// processing is finished.
@@ -91,10 +95,18 @@ impl MessageHandle {
         send_push(self, payload)
     }
 
+    pub fn push_input(&self, offset: u32, len: u32) -> Result<()> {
+        send_push_input(self, offset, len)
+    }
+
     pub fn commit(self, program: ActorId, value: u128) -> Result<MessageId> {
         send_commit(self, program, value)
     }
 
+    pub fn cancel(self) -> Result<()> {
+        send_cancel(self)
+    }
+
     pub fn commit_with_gas(
         self,
         program: ActorId,
@@ -326,6 +338,27 @@ pub fn reply_push<T: AsRef<[u8]>>(payload: T) -> Result<()> {
     gcore::msg::reply_push(payload.as_ref()).into_contract_result()
 }
 
+/// Push a part of the incoming message payload to the current reply message.
+///
+/// Works like [`reply_push`], but takes `len` bytes of the payload of the
+/// message being processed starting from `offset`, so the program doesn't
+/// need to load them into its own memory first.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     msg::reply_push(b"Echo: ").unwrap();
+///     msg::reply_push_input(0, msg::size() as u32).unwrap();
+/// }
+/// ```
+pub fn reply_push_input(offset: u32, len: u32) -> Result<()> {
+    gcore::msg::reply_push_input(offset, len).into_contract_result()
+}
+
 /// Get an identifier of the initial message which the current handle_reply
 /// function is called on.
 ///
@@ -542,6 +575,62 @@ pub fn send_push<T: AsRef<[u8]>>(handle: &MessageHandle, payload: T) -> Result<(
     gcore::msg::send_push(handle.as_ref(), payload.as_ref()).into_contract_result()
 }
 
+/// Push a part of the incoming message payload to the message to be sent in
+/// parts.
+///
+/// Works like [`send_push`], but takes `len` bytes of the payload of the
+/// message being processed starting from `offset`, so the program doesn't
+/// need to load them into its own memory first.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let msg_handle = msg::send_init().expect("Unable to init");
+///     msg::send_push_input(&msg_handle, 0, msg::size() as u32).expect("Unable to push");
+///     msg::send_commit(msg_handle, msg::source(), 0).expect("Unable to commit");
+/// }
+/// ```
+///
+/// # See also
+///
+/// [`send_init`], [`send_push`], [`send_commit`] functions allows to form
+/// and send a message to send in parts.
+pub fn send_push_input(handle: &MessageHandle, offset: u32, len: u32) -> Result<()> {
+    gcore::msg::send_push_input(handle.as_ref(), offset, len).into_contract_result()
+}
+
+/// Cancel the message to be sent in parts.
+///
+/// Gear allows programs to work with messages in parts.
+/// This function discards the message specified by message `handle`, which
+/// was formed with [`send_init`] and [`send_push`], but not committed yet.
+/// The handle doesn't count against the limit of outgoing messages anymore.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let msg_handle = msg::send_init().expect("Unable to init");
+///     msg::send_push(&msg_handle, b"PING").expect("Unable to push");
+///     msg::send_cancel(msg_handle).expect("Unable to cancel");
+/// }
+/// ```
+///
+/// # See also
+///
+/// [`send_init`], [`send_push`], [`send_commit`] functions allows to form
+/// and send a message to send in parts.
+pub fn send_cancel(handle: MessageHandle) -> Result<()> {
+    gcore::msg::send_cancel(handle.into()).into_contract_result()
+}
+
 /// Get the payload size of the message being processed.
 ///
 /// This function is used to obtain the payload size of the current message
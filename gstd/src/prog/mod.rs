@@ -22,16 +22,17 @@ mod generator;
 
 pub use generator::ProgramGenerator;
 
-use crate::{common::errors::Result, prelude::convert::AsRef, ActorId, CodeHash};
+use crate::{common::errors::Result, prelude::convert::AsRef, ActorId, CodeHash, MessageId};
 
 pub fn create_program<T1: AsRef<[u8]>, T2: AsRef<[u8]>>(
     code_hash: CodeHash,
     salt: T1,
     payload: T2,
     value: u128,
-) -> Result<ActorId> {
-    let id = gcore::prog::create_program(code_hash.into(), salt.as_ref(), payload.as_ref(), value)?;
-    Ok(id.into())
+) -> Result<(MessageId, ActorId)> {
+    let (message_id, program_id) =
+        gcore::prog::create_program(code_hash.into(), salt.as_ref(), payload.as_ref(), value)?;
+    Ok((message_id.into(), program_id.into()))
 }
 
 pub fn create_program_with_gas<T1: AsRef<[u8]>, T2: AsRef<[u8]>>(
@@ -40,13 +41,13 @@ pub fn create_program_with_gas<T1: AsRef<[u8]>, T2: AsRef<[u8]>>(
     payload: T2,
     gas_limit: u64,
     value: u128,
-) -> Result<ActorId> {
-    let id = gcore::prog::create_program_with_gas(
+) -> Result<(MessageId, ActorId)> {
+    let (message_id, program_id) = gcore::prog::create_program_with_gas(
         code_hash.into(),
         salt.as_ref(),
         payload.as_ref(),
         gas_limit,
         value,
     )?;
-    Ok(id.into())
+    Ok((message_id.into(), program_id.into()))
 }
@@ -19,7 +19,7 @@
 use anyhow::Error as AnyhowError;
 use codec::Error as CodecError;
 use core_processor::ProcessorError;
-use gear_core::{ids::ProgramId, memory::WasmPageNumber};
+use gear_core::{ids::ProgramId, memory::WasmPageNumber, message::PayloadSizeError};
 use wasmtime::MemoryAccessError;
 
 /// Type alias for the testing functions running result.
@@ -65,6 +65,10 @@ pub enum TestError {
     #[display(fmt = "Failed to call unsupported function: `{}`", _0)]
     UnsupportedFunction(String),
 
+    /// Payload size limit exceeded.
+    #[display(fmt = "Payload size limit exceeded")]
+    PayloadSizeLimitExceeded(PayloadSizeError),
+
     /// Wrapper for [`ProcessorError`].
     #[display(fmt = "{}", _0)]
     ExecutionError(ProcessorError),
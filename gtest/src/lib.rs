@@ -21,12 +21,14 @@ mod log;
 mod mailbox;
 mod manager;
 mod program;
+mod schedule;
 mod system;
 mod wasm_executor;
 
 pub use error::{Result, TestError};
 pub use log::{CoreLog, Log, RunResult};
 pub use program::{calculate_program_id, Gas, Program, WasmProgram};
+pub use schedule::{Schedule, OUTGOING_BYTES_LIMIT, OUTGOING_LIMIT, PROGRAM_CANDIDATES_LIMIT};
 pub use system::System;
 
 pub const EXISTENTIAL_DEPOSIT: u128 = 500;
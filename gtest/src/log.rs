@@ -20,16 +20,20 @@ use crate::program::{Gas, ProgramIdWrapper};
 use codec::{Codec, Encode};
 use gear_core::{
     ids::{MessageId, ProgramId},
-    message::{ExitCode, Payload, StoredMessage},
+    memory::PageNumber,
+    message::{ExitCode, StoredMessage},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
 };
-use std::fmt::Debug;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CoreLog {
     id: MessageId,
     source: ProgramId,
     destination: ProgramId,
-    payload: Payload,
+    payload: Vec<u8>,
     exit_code: Option<ExitCode>,
 }
 
@@ -171,7 +175,7 @@ impl Log {
 
 impl PartialEq<StoredMessage> for Log {
     fn eq(&self, other: &StoredMessage) -> bool {
-        if matches!(other.reply(), Some(reply) if reply.1 != self.exit_code) {
+        if matches!(other.reply(), Some(reply) if reply.exit_code() != self.exit_code) {
             return false;
         }
         if matches!(self.source, Some(source) if source != other.source()) {
@@ -253,6 +257,8 @@ pub struct RunResult {
     pub(crate) total_processed: u32,
     pub(crate) main_gas_burned: Gas,
     pub(crate) others_gas_burned: Gas,
+    pub(crate) read_pages: BTreeMap<MessageId, BTreeSet<PageNumber>>,
+    pub(crate) written_pages: BTreeMap<MessageId, BTreeSet<PageNumber>>,
 }
 
 impl RunResult {
@@ -290,6 +296,63 @@ impl RunResult {
         self.others_gas_burned
     }
 
+    /// Gear pages, which data was loaded for the execution of the message.
+    ///
+    /// The test environment executes programs without lazy pages,
+    /// so all pages with data of the program are loaded.
+    pub fn read_pages(&self, message_id: MessageId) -> BTreeSet<PageNumber> {
+        self.read_pages
+            .get(&message_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Gear pages, which data was changed by the execution of the message.
+    pub fn written_pages(&self, message_id: MessageId) -> BTreeSet<PageNumber> {
+        self.written_pages
+            .get(&message_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn assert_read_pages<I: IntoIterator<Item = u32>>(&self, message_id: MessageId, pages: I) {
+        let expected: BTreeSet<_> = pages.into_iter().map(PageNumber).collect();
+        let actual = self.read_pages(message_id);
+
+        assert_eq!(
+            actual, expected,
+            "Read pages {:?} differ from expected {:?}",
+            actual, expected
+        );
+    }
+
+    pub fn assert_written_pages<I: IntoIterator<Item = u32>>(
+        &self,
+        message_id: MessageId,
+        pages: I,
+    ) {
+        let expected: BTreeSet<_> = pages.into_iter().map(PageNumber).collect();
+        let actual = self.written_pages(message_id);
+
+        assert_eq!(
+            actual, expected,
+            "Written pages {:?} differ from expected {:?}",
+            actual, expected
+        );
+    }
+
+    pub fn assert_written_pages_at_most(&self, message_id: MessageId, count: usize) {
+        let actual = self.written_pages(message_id);
+
+        assert!(
+            actual.len() <= count,
+            "Message wrote {} pages ({:?}), while at most {} expected",
+            actual.len(),
+            actual,
+            count
+        );
+    }
+
     pub fn decoded_log<T: Codec + Debug>(&self) -> Vec<DecodedCoreLog<T>> {
         self.log
             .clone()
@@ -108,7 +108,11 @@ impl<'a> MessageReplier<'a> {
             MessageId::from(self.manager.borrow_mut().fetch_inc_message_nonce()),
             self.log.destination(),
             self.log.source(),
-            raw_payload.as_ref().to_vec(),
+            raw_payload
+                .as_ref()
+                .to_vec()
+                .try_into()
+                .expect("Payload size limit exceeded"),
             None,
             value,
             self.log
@@ -128,7 +132,7 @@ mod tests {
     use codec::Encode;
     use gear_core::{
         ids::MessageId,
-        message::{Dispatch, DispatchKind, Message, Payload},
+        message::{Dispatch, DispatchKind, Message},
     };
 
     #[test]
@@ -138,10 +142,10 @@ mod tests {
         let message_id: MessageId = Default::default();
         let source_user_id = ProgramIdWrapper::from(100).0;
         let destination_user_id = ProgramIdWrapper::from(200).0;
-        let message_payload: Payload = vec![1, 2, 3];
-        let encoded_message_payload: Payload = message_payload.encode();
-        let reply_payload: Payload = vec![3, 2, 1];
-        let encoded_reply_payload: Payload = reply_payload.encode();
+        let message_payload: Vec<u8> = vec![1, 2, 3];
+        let encoded_message_payload: Vec<u8> = message_payload.encode();
+        let reply_payload: Vec<u8> = vec![3, 2, 1];
+        let encoded_reply_payload: Vec<u8> = reply_payload.encode();
         let log = Log::builder().payload(message_payload);
 
         //Building message based on arranged data
@@ -149,7 +153,7 @@ mod tests {
             message_id,
             source_user_id,
             destination_user_id,
-            encoded_message_payload.clone(),
+            encoded_message_payload.clone().try_into().unwrap(),
             Default::default(),
             0,
             None,
@@ -197,8 +201,8 @@ mod tests {
         let message_id: MessageId = Default::default();
         let source_user_id = ProgramIdWrapper::from(100).0;
         let destination_user_id = ProgramIdWrapper::from(200).0;
-        let message_payload: Payload = vec![1, 2, 3];
-        let reply_payload: Payload = vec![3, 2, 1];
+        let message_payload: Vec<u8> = vec![1, 2, 3];
+        let reply_payload: Vec<u8> = vec![3, 2, 1];
         let message_log = Log::builder().payload(message_payload.clone());
 
         //Building message based on arranged data
@@ -206,7 +210,7 @@ mod tests {
             message_id,
             source_user_id,
             destination_user_id,
-            message_payload.encode(),
+            message_payload.encode().try_into().unwrap(),
             Default::default(),
             0,
             None,
@@ -231,9 +235,9 @@ mod tests {
         let message_id: MessageId = Default::default();
         let source_user_id = ProgramIdWrapper::from(100).0;
         let destination_user_id = ProgramIdWrapper::from(200).0;
-        let message_payload: Payload = vec![1, 2, 3];
+        let message_payload: Vec<u8> = vec![1, 2, 3];
         let reply_payload_array: [u8; 3] = [3, 2, 1];
-        let reply_payload: Payload = reply_payload_array.to_vec();
+        let reply_payload: Vec<u8> = reply_payload_array.to_vec();
         let log = Log::builder().payload(message_payload.clone());
 
         //Building message based on arranged data
@@ -241,7 +245,7 @@ mod tests {
             message_id,
             source_user_id,
             destination_user_id,
-            message_payload.encode(),
+            message_payload.encode().try_into().unwrap(),
             Default::default(),
             0,
             None,
@@ -269,7 +273,7 @@ mod tests {
         let message_id: MessageId = Default::default();
         let source_user_id = ProgramIdWrapper::from(100).0;
         let destination_user_id = ProgramIdWrapper::from(200).0;
-        let message_payload: Payload = vec![1, 2, 3];
+        let message_payload: Vec<u8> = vec![1, 2, 3];
         let log = Log::builder().payload(message_payload.clone());
 
         //Building message based on arranged data
@@ -277,7 +281,7 @@ mod tests {
             message_id,
             source_user_id,
             destination_user_id,
-            message_payload.encode(),
+            message_payload.encode().try_into().unwrap(),
             Default::default(),
             0,
             None,
@@ -341,7 +345,7 @@ mod tests {
             message_id,
             sender_id.into(),
             receiver_id.into(),
-            payload.encode(),
+            payload.encode().try_into().unwrap(),
             Default::default(),
             1000,
             None,
@@ -19,8 +19,9 @@
 use crate::{
     log::{CoreLog, RunResult},
     program::{Gas, WasmProgram},
+    schedule::Schedule,
     wasm_executor::WasmExecutor,
-    Result, TestError, EXISTENTIAL_DEPOSIT, MAILBOX_THRESHOLD,
+    Result, TestError, EXISTENTIAL_DEPOSIT,
 };
 use core_processor::{
     common::*,
@@ -30,10 +31,11 @@ use core_processor::{
 use gear_backend_wasmtime::WasmtimeEnvironment;
 use gear_core::{
     code::{Code, CodeAndId, InstrumentedCodeAndId},
-    ids::{CodeId, MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber, WasmPageNumber},
     message::{
-        Dispatch, DispatchKind, Payload, ReplyMessage, ReplyPacket, StoredDispatch, StoredMessage,
+        Dispatch, DispatchKind, ExitCode, MessageWaitedType, Payload, ProgramEvent, ReplyMessage,
+        ReplyPacket, SignalMessage, StoredDispatch, StoredMessage,
     },
     program::Program as CoreProgram,
 };
@@ -43,8 +45,6 @@ use std::{
 };
 use wasm_instrument::gas_metering::ConstantCostRules;
 
-const OUTGOING_LIMIT: u32 = 1024;
-
 pub(crate) type Balance = u128;
 
 #[derive(Debug)]
@@ -136,6 +136,8 @@ impl TestActor {
         Some(ExecutableActorData {
             program,
             pages_data,
+            gas_reservation_map: Default::default(),
+            waitlist_full: false,
         })
     }
 }
@@ -173,6 +175,7 @@ impl Program {
 pub(crate) struct ExtManager {
     // State metadata
     pub(crate) block_info: BlockInfo,
+    pub(crate) schedule: Schedule,
 
     // Messaging and programs meta
     pub(crate) msg_nonce: u64,
@@ -196,6 +199,8 @@ pub(crate) struct ExtManager {
     pub(crate) others_failed: bool,
     pub(crate) main_gas_burned: Gas,
     pub(crate) others_gas_burned: Gas,
+    pub(crate) read_pages: BTreeMap<MessageId, BTreeSet<PageNumber>>,
+    pub(crate) written_pages: BTreeMap<MessageId, BTreeSet<PageNumber>>,
 }
 
 impl ExtManager {
@@ -311,7 +316,7 @@ impl ExtManager {
                     .entry(dest)
                     .or_default()
                     .push(message_id);
-                self.wait_dispatch(dispatch);
+                self.wait_dispatch(dispatch, None, MessageWaitedType::Wait);
 
                 continue;
             }
@@ -335,6 +340,8 @@ impl ExtManager {
             total_processed += 1;
         }
 
+        gear_backend_wasmtime::cache::clear();
+
         let log = self.log.clone();
 
         RunResult {
@@ -345,6 +352,8 @@ impl ExtManager {
             total_processed,
             main_gas_burned: self.main_gas_burned,
             others_gas_burned: self.others_gas_burned,
+            read_pages: self.read_pages.clone(),
+            written_pages: self.written_pages.clone(),
         }
     }
 
@@ -405,6 +414,8 @@ impl ExtManager {
         self.others_failed = false;
         self.main_gas_burned = Gas::zero();
         self.others_gas_burned = Gas::zero();
+        self.read_pages.clear();
+        self.written_pages.clear();
 
         // TODO: Remove this check after #349.
         if !self.dispatches.is_empty() {
@@ -475,7 +486,11 @@ impl ExtManager {
             DispatchKind::Init => mock.init(payload),
             DispatchKind::Handle => mock.handle(payload),
             DispatchKind::Reply => mock.handle_reply(payload),
+            // Mocks don't handle signals.
+            DispatchKind::Signal => Ok(None),
         };
+        let response =
+            response.and_then(|reply| reply.map(Payload::try_from).transpose().map_err(Into::into));
 
         match response {
             Ok(reply) => {
@@ -495,6 +510,8 @@ impl ExtManager {
                     self.send_dispatch(
                         message_id,
                         reply_message.into_dispatch(program_id, dispatch.source(), message_id),
+                        0,
+                        None,
                     );
                 }
             }
@@ -528,6 +545,8 @@ impl ExtManager {
                 self.send_dispatch(
                     message_id,
                     reply_message.into_dispatch(program_id, dispatch.source(), message_id),
+                    0,
+                    None,
                 );
             }
         }
@@ -568,13 +587,26 @@ impl ExtManager {
             .unwrap_or(u64::MAX);
         let block_config = BlockConfig {
             block_info: self.block_info,
-            allocations_config: Default::default(),
+            allocations_config: self.schedule.allocations_config.clone(),
             existential_deposit: EXISTENTIAL_DEPOSIT,
-            outgoing_limit: OUTGOING_LIMIT,
-            host_fn_weights: Default::default(),
+            outgoing_limit: self.schedule.outgoing_limit,
+            outgoing_bytes_limit: self.schedule.outgoing_bytes_limit,
+            program_candidates_limit: self.schedule.program_candidates_limit,
+            outgoing_scaling: None,
+            host_fn_weights: self.schedule.host_fn_weights.clone(),
             forbidden_funcs: Default::default(),
-            mailbox_threshold: MAILBOX_THRESHOLD,
+            mailbox_threshold: self.schedule.mailbox_threshold,
+            gas_minimums: Default::default(),
         };
+        let message_id = dispatch.id();
+
+        // Programs are executed without lazy pages,
+        // so all pages with data are loaded for the execution.
+        if let Some(data) = &data {
+            self.read_pages
+                .insert(message_id, data.pages_data.keys().copied().collect());
+        }
+
         let message_execution_context = MessageExecutionContext {
             actor: Actor {
                 balance,
@@ -590,6 +622,15 @@ impl ExtManager {
             message_execution_context,
         );
 
+        let written_pages = journal
+            .iter()
+            .filter_map(|note| match note {
+                JournalNote::UpdatePage { page_number, .. } => Some(*page_number),
+                _ => None,
+            })
+            .collect();
+        self.written_pages.insert(message_id, written_pages);
+
         core_processor::handle_journal(journal, self);
     }
 
@@ -662,7 +703,15 @@ impl JournalHandler for ExtManager {
         }
     }
 
-    fn send_dispatch(&mut self, _message_id: MessageId, dispatch: Dispatch) {
+    // There is no scheduler in the test environment,
+    // so delayed dispatches are sent right away.
+    fn send_dispatch(
+        &mut self,
+        _message_id: MessageId,
+        dispatch: Dispatch,
+        _delay: u32,
+        _reservation: Option<ReservationId>,
+    ) {
         self.gas_limits.insert(dispatch.id(), dispatch.gas_limit());
 
         if !self.is_user(&dispatch.destination()) {
@@ -679,17 +728,27 @@ impl JournalHandler for ExtManager {
         }
     }
 
-    fn wait_dispatch(&mut self, dispatch: StoredDispatch) {
+    // There is no scheduler in the test environment,
+    // so waiting messages never expire.
+    fn wait_dispatch(
+        &mut self,
+        dispatch: StoredDispatch,
+        _duration: Option<u32>,
+        _waited_type: MessageWaitedType,
+    ) {
         self.message_consumed(dispatch.id());
         self.wait_list
             .insert((dispatch.destination(), dispatch.id()), dispatch);
     }
 
+    // There is no scheduler in the test environment,
+    // so delayed wakes are done right away.
     fn wake_message(
         &mut self,
         _message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
+        _delay: u32,
     ) {
         if let Some(msg) = self.wait_list.remove(&(program_id, awakening_id)) {
             self.dispatches.push_back(msg);
@@ -763,12 +822,17 @@ impl JournalHandler for ExtManager {
             }
 
             self.mint_to(to, value);
-        } else {
+        } else if self.is_user(&from) {
             self.mint_to(&from, value);
         }
     }
 
-    fn store_new_programs(&mut self, code_hash: CodeId, candidates: Vec<(ProgramId, MessageId)>) {
+    fn store_new_programs(
+        &mut self,
+        _program_id: ProgramId,
+        code_hash: CodeId,
+        candidates: Vec<(ProgramId, MessageId)>,
+    ) {
         if let Some(code) = self.opt_binaries.get(&code_hash).cloned() {
             for (candidate_id, init_message_id) in candidates {
                 if !self.actors.contains_key(&candidate_id) {
@@ -803,4 +867,38 @@ impl JournalHandler for ExtManager {
     fn stop_processing(&mut self, _dispatch: StoredDispatch, _gas_burned: u64) {
         panic!("Processing stopped. Used for on-chain logic only.")
     }
+
+    // Gas isn't tracked in the test environment, so reservations are ignored.
+    fn reserve_gas(
+        &mut self,
+        _message_id: MessageId,
+        _reservation_id: ReservationId,
+        _program_id: ProgramId,
+        _amount: u64,
+        _expiration: u32,
+    ) {
+    }
+
+    fn unreserve_gas(
+        &mut self,
+        _reservation_id: ReservationId,
+        _program_id: ProgramId,
+        _expiration: u32,
+    ) {
+    }
+
+    fn system_reserve_gas(&mut self, _message_id: MessageId, _amount: u64) {}
+
+    fn system_unreserve_gas(&mut self, _message_id: MessageId) {}
+
+    fn send_signal(&mut self, message_id: MessageId, destination: ProgramId, exit_code: ExitCode) {
+        let dispatch =
+            SignalMessage::new(message_id, exit_code).into_dispatch(message_id, destination);
+
+        self.send_dispatch(message_id, dispatch, 0, None);
+    }
+
+    fn increment_metric(&mut self, _program_id: ProgramId, _key: [u8; 32], _value: u64) {}
+
+    fn emit_event(&mut self, _program_id: ProgramId, _event: ProgramEvent) {}
 }
@@ -389,7 +389,11 @@ impl<'a> Program<'a> {
             ),
             source,
             self.id,
-            payload.as_ref().to_vec(),
+            payload
+                .as_ref()
+                .to_vec()
+                .try_into()
+                .expect("Payload size limit exceeded"),
             Some(u64::MAX),
             value,
             None,
@@ -416,9 +420,11 @@ impl<'a> Program<'a> {
     }
 
     pub fn meta_state_with_bytes(&self, payload: impl AsRef<[u8]>) -> Result<Vec<u8>> {
+        let payload = payload.as_ref().to_vec().try_into()?;
+
         self.manager
             .borrow_mut()
-            .call_meta(&self.id, Some(payload.as_ref().into()), "meta_state")
+            .call_meta(&self.id, Some(payload), "meta_state")
     }
 
     pub fn meta_state_empty<D: Decode>(&self) -> Result<D> {
@@ -456,7 +462,7 @@ pub fn calculate_program_id(code_hash: CodeId, salt: &[u8]) -> ProgramId {
 
 #[cfg(test)]
 mod tests {
-    use super::Program;
+    use super::{Gas, Program};
     use crate::{Log, System};
 
     #[test]
@@ -509,6 +515,55 @@ mod tests {
         assert_eq!(sys.balance_of(user_id), 3500);
     }
 
+    #[test]
+    fn accessed_pages() {
+        let sys = System::new();
+        sys.init_logger();
+
+        let user_id = 42;
+
+        // `init` writes gear page 1, while `handle` copies
+        // its value into gear page 3.
+        let wat = r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (export "init" (func $init))
+                (export "handle" (func $handle))
+                (func $init
+                    (i32.store (i32.const 0x1000) (i32.const 42))
+                )
+                (func $handle
+                    (i32.store (i32.const 0x3000) (i32.load (i32.const 0x1000)))
+                )
+            )"#;
+        let code = wabt::Wat2Wasm::new()
+            .validate(false)
+            .convert(wat)
+            .expect("failed to parse module")
+            .as_ref()
+            .to_vec();
+
+        let prog = Program::from_opt_and_meta_code_with_id(&sys, 137, code, None);
+
+        let run_result = prog.send_bytes(user_id, b"");
+        let message_id = run_result.sent_message_id();
+        assert!(!run_result.main_failed());
+        run_result.assert_read_pages(message_id, []);
+        run_result.assert_written_pages(message_id, [1]);
+
+        let run_result = prog.send_bytes(user_id, b"");
+        let message_id = run_result.sent_message_id();
+        assert!(!run_result.main_failed());
+        run_result.assert_read_pages(message_id, [1]);
+        run_result.assert_written_pages(message_id, [3]);
+
+        // Data of the pages is the same, so nothing is written.
+        let run_result = prog.send_bytes(user_id, b"");
+        let message_id = run_result.sent_message_id();
+        run_result.assert_read_pages(message_id, [1, 3]);
+        run_result.assert_written_pages_at_most(message_id, 0);
+    }
+
     #[test]
     fn piggy_bank() {
         let sys = System::new();
@@ -0,0 +1,60 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use core_processor::configs::AllocationsConfig;
+use gear_core::costs::HostFnWeights;
+
+/// The maximum amount of messages that can be produced in single run.
+pub const OUTGOING_LIMIT: u32 = 1024;
+pub const OUTGOING_BYTES_LIMIT: u32 = 64 * 1024 * 1024;
+/// The maximum amount of programs that can be created in single run.
+pub const PROGRAM_CANDIDATES_LIMIT: u32 = 128;
+
+/// Constants the test engine uses for processing of messages.
+///
+/// Defaults are the test engine's own values. To mirror behavior of the
+/// connected chain, fill it with values, returned by `GearScheduleApi`
+/// runtime API, and pass it to [`System::set_schedule`](crate::System::set_schedule).
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    /// Memory/allocation config.
+    pub allocations_config: AllocationsConfig,
+    /// Weights of host functions.
+    pub host_fn_weights: HostFnWeights,
+    /// The maximum amount of messages that can be produced in single run.
+    pub outgoing_limit: u32,
+    /// The maximum amount of payload bytes that can be produced in single run.
+    pub outgoing_bytes_limit: u32,
+    /// The maximum amount of programs that can be created in single run.
+    pub program_candidates_limit: u32,
+    /// The minimal gas amount for message to be inserted in mailbox.
+    pub mailbox_threshold: u64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            allocations_config: Default::default(),
+            host_fn_weights: Default::default(),
+            outgoing_limit: OUTGOING_LIMIT,
+            outgoing_bytes_limit: OUTGOING_BYTES_LIMIT,
+            program_candidates_limit: PROGRAM_CANDIDATES_LIMIT,
+            mailbox_threshold: crate::MAILBOX_THRESHOLD,
+        }
+    }
+}
@@ -21,6 +21,7 @@ use crate::{
     mailbox::Mailbox,
     manager::{Balance, ExtManager},
     program::{Program, ProgramIdWrapper},
+    schedule::Schedule,
 };
 use colored::Colorize;
 use env_logger::{Builder, Env};
@@ -83,6 +84,17 @@ impl System {
         manager.block_info.timestamp += amount as u64;
     }
 
+    /// Returns constants used for processing of messages.
+    pub fn schedule(&self) -> Schedule {
+        self.0.borrow().schedule.clone()
+    }
+
+    /// Replaces constants used for processing of messages, e.g. with ones
+    /// of the connected chain.
+    pub fn set_schedule(&self, schedule: Schedule) {
+        self.0.borrow_mut().schedule = schedule;
+    }
+
     /// Returns a [`Program`] by `id`.
     ///
     /// The method doesn't check whether program exists or not.
@@ -21,9 +21,9 @@ use gear_backend_common::TerminationReason;
 use gear_backend_wasmtime::{env::StoreData, funcs_tree};
 use gear_core::{
     env::{Ext as ExtTrait, ExtCarrier},
-    gas::{GasAllowanceCounter, GasCounter, ValueCounter},
+    gas::{GasAllowanceCounter, GasCounter, GasReserver, ValueCounter},
     memory::{AllocationsContext, PageBuf, PageNumber, WasmPageNumber},
-    message::{IncomingMessage, MessageContext, Payload},
+    message::{DispatchKind, IncomingMessage, MessageContext, Payload},
     program::Program,
 };
 use std::{collections::BTreeMap, mem};
@@ -131,6 +131,7 @@ impl WasmExecutor {
         Ext::new(ProcessorContext {
             gas_counter: GasCounter::new(u64::MAX),
             gas_allowance_counter: GasAllowanceCounter::new(u64::MAX),
+            gas_reserver: GasReserver::new(Default::default(), 0, Default::default()),
             value_counter: ValueCounter::new(u128::MAX),
             allocations_context: AllocationsContext::new(
                 program.get_allocations().clone(),
@@ -138,6 +139,7 @@ impl WasmExecutor {
                 WasmPageNumber(512u32),
             ),
             message_context: MessageContext::new(
+                DispatchKind::Handle,
                 IncomingMessage::new(Default::default(), Default::default(), payload, 0, 0, None),
                 program.id(),
                 None,
@@ -151,6 +153,10 @@ impl WasmExecutor {
             host_fn_weights: Default::default(),
             forbidden_funcs: Default::default(),
             mailbox_threshold: MAILBOX_THRESHOLD,
+            gas_minimums: Default::default(),
+            metrics: Default::default(),
+            events: Default::default(),
+            waitlist_full: false,
         })
     }
 
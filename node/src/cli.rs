@@ -25,6 +25,20 @@ pub struct Cli {
 
     #[clap(flatten)]
     pub run: RunCmd,
+
+    /// Export journals of processed blocks for indexers.
+    ///
+    /// The sink is either a path to the file or `tcp://<address>` of the socket.
+    #[clap(long, value_name = "SINK")]
+    pub journal_export: Option<String>,
+
+    /// Serve program dry-runs over HTTP on the address.
+    ///
+    /// `POST /dry-run` runs the scenario of the program against the runtime
+    /// without submitting transactions.
+    #[cfg(feature = "dry-run")]
+    #[clap(long, value_name = "ADDR")]
+    pub dry_run_addr: Option<std::net::SocketAddr>,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -74,4 +88,10 @@ pub enum Subcommand {
         about = "Run gear runtime tests with yaml."
     )]
     GearRuntimeTest(gear_runtime_test_cli::GearRuntimeTestCmd),
+
+    #[clap(
+        name = "gear-bench",
+        about = "Fill blocks of a temporary chain with gear extrinsics and report throughput."
+    )]
+    GearBench(gear_bench_cli::GearBenchCmd),
 }
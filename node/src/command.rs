@@ -19,7 +19,7 @@
 use crate::{
     chain_spec,
     cli::{Cli, Subcommand},
-    command_helper::{inherent_benchmark_data, BenchmarkExtrinsicBuilder},
+    command_helper::{inherent_benchmark_data, set_journal_sink, BenchmarkExtrinsicBuilder},
     service,
 };
 use frame_benchmarking_cli::{BenchmarkCmd, SUBSTRATE_REFERENCE_HARDWARE};
@@ -197,6 +197,11 @@ pub fn run() -> sc_cli::Result<()> {
 
             runner.sync_run(|config| cmd.run(config))
         }
+        Some(Subcommand::GearBench(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+
+            runner.sync_run(|config| cmd.run(config))
+        }
         #[cfg(feature = "try-runtime")]
         Some(Subcommand::TryRuntime(cmd)) => {
             let runner = cli.create_runner(cmd)?;
@@ -222,9 +227,18 @@ pub fn run() -> sc_cli::Result<()> {
             runner.sync_run(|config| cmd.run::<Block>(&config))
         }
         None => {
+            if let Some(sink) = &cli.journal_export {
+                set_journal_sink(sink)?;
+            }
+
+            #[cfg(feature = "dry-run")]
+            let dry_run_addr = cli.dry_run_addr;
+            #[cfg(not(feature = "dry-run"))]
+            let dry_run_addr = None;
+
             let runner = cli.create_runner(&cli.run)?;
             runner.run_node_until_exit(|config| async move {
-                service::new_full(config).map_err(sc_cli::Error::Service)
+                service::new_full(config, dry_run_addr).map_err(sc_cli::Error::Service)
             })
         }
     }
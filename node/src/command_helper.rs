@@ -22,6 +22,7 @@
 use crate::service::FullClient;
 
 use gear_runtime as runtime;
+use gear_runtime_interface::journal_export;
 use runtime::SystemCall;
 use sc_cli::Result;
 use sc_client_api::BlockBackend;
@@ -30,7 +31,13 @@ use sp_inherents::{InherentData, InherentDataProvider};
 use sp_keyring::Sr25519Keyring;
 use sp_runtime::{OpaqueExtrinsic, SaturatedConversion};
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    net::TcpStream,
+    sync::Arc,
+    time::Duration,
+};
 
 /// Generates extrinsics for the `benchmark overhead` command.
 ///
@@ -133,3 +140,22 @@ pub fn inherent_benchmark_data() -> Result<InherentData> {
         .map_err(|e| format!("creating inherent data: {:?}", e))?;
     Ok(inherent_data)
 }
+
+/// Sets the sink for export of journals of processed blocks.
+///
+/// The sink is either a path to the file, which journals are appended to,
+/// or `tcp://<address>` of the socket to connect to.
+pub fn set_journal_sink(sink: &str) -> Result<()> {
+    let sink: Box<dyn Write + Send> = match sink.strip_prefix("tcp://") {
+        Some(address) => Box::new(TcpStream::connect(address)?),
+        None => Box::new(BufWriter::new(
+            OpenOptions::new().create(true).append(true).open(sink)?,
+        )),
+    };
+
+    if !journal_export::set_sink(sink) {
+        return Err("journal sink is already set".into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! HTTP service dry-running programs against the runtime of the node.
+//!
+//! `POST /dry-run` accepts a JSON [`Scenario`]: the code of the program and
+//! the messages to be sent to it after the initialization. Each step is run
+//! on top of the best block with the gas calculation of the runtime, keeping
+//! state changes of the previous steps, so nothing is submitted to the chain.
+
+use crate::service::FullClient;
+use gear_core::ids::{CodeId, ProgramId};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use pallet_gear_rpc_runtime_api::{GasInfo, GearApi, HandleKind, GAS_CALCULATION_SALT};
+use sc_service::error::Error as ServiceError;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use sp_runtime::generic::BlockId;
+use std::{convert::Infallible, future::Future, net::SocketAddr, sync::Arc};
+
+const PATH: &str = "/dry-run";
+
+/// Program initialization followed by messages sent to the program.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scenario {
+    /// Sender of the init and all following messages.
+    pub source: H256,
+    /// Wasm code of the program.
+    pub code: Bytes,
+    #[serde(default)]
+    pub init_payload: Bytes,
+    #[serde(default)]
+    pub init_value: u128,
+    #[serde(default)]
+    pub messages: Vec<ScenarioMessage>,
+}
+
+/// Handle message sent to the program within the scenario.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioMessage {
+    #[serde(default)]
+    pub payload: Bytes,
+    #[serde(default)]
+    pub value: u128,
+}
+
+/// Gas results of the scenario steps.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub program_id: H256,
+    pub init: GasInfo,
+    pub messages: Vec<GasInfo>,
+}
+
+fn run_scenario(client: &FullClient, scenario: Scenario) -> Result<Report, String> {
+    let at = BlockId::hash(client.info().best_hash);
+    // State changes of each call are kept by the api instance,
+    // so the following messages are processed by the initialized program.
+    let api = client.runtime_api();
+
+    let Scenario {
+        source,
+        code,
+        init_payload,
+        init_value,
+        messages,
+    } = scenario;
+
+    let calculate = |kind: HandleKind, payload: Bytes, value: u128| {
+        api.calculate_gas_info(&at, source, kind, payload.0, value, false, None)
+            .map_err(|e| format!("{:?}", e))?
+            .map_err(|e| String::from_utf8_lossy(&e).into_owned())
+    };
+
+    let program_id = ProgramId::generate(CodeId::generate(&code), GAS_CALCULATION_SALT);
+
+    let init = calculate(HandleKind::Init(code.0), init_payload, init_value)?;
+
+    let messages = messages
+        .into_iter()
+        .map(|msg| calculate(HandleKind::Handle(program_id), msg.payload, msg.value))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Report {
+        program_id: H256::from_slice(program_id.as_ref()),
+        init,
+        messages,
+    })
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("Response is valid; qed")
+}
+
+fn error_response(status: StatusCode, error: impl ToString) -> Response<Body> {
+    json_response(status, serde_json::json!({ "error": error.to_string() }))
+}
+
+async fn handle(client: Arc<FullClient>, req: Request<Body>) -> Response<Body> {
+    if req.method() != Method::POST || req.uri().path() != PATH {
+        return error_response(StatusCode::NOT_FOUND, "not found");
+    }
+
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => body,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+
+    let scenario: Scenario = match serde_json::from_slice(&body) {
+        Ok(scenario) => scenario,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+
+    match run_scenario(&client, scenario) {
+        Ok(report) => json_response(
+            StatusCode::OK,
+            serde_json::to_value(report).expect("Report is serializable; qed"),
+        ),
+        Err(e) => error_response(StatusCode::UNPROCESSABLE_ENTITY, e),
+    }
+}
+
+/// Binds the dry-run service to the address.
+///
+/// Returns the future serving requests, which is expected to be spawned.
+pub fn start(
+    client: Arc<FullClient>,
+    addr: SocketAddr,
+) -> Result<impl Future<Output = ()>, ServiceError> {
+    let make_service = make_service_fn(move |_| {
+        let client = client.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let client = client.clone();
+
+                async move { Ok::<_, Infallible>(handle(client, req).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)
+        .map_err(|e| ServiceError::Other(format!("Error binding dry-run service: {}", e)))?
+        .serve(make_service);
+
+    log::info!("Dry-run service listening on http://{}{}", addr, PATH);
+
+    Ok(async move {
+        if let Err(e) = server.await {
+            log::error!("Dry-run service failed: {}", e);
+        }
+    })
+}
@@ -22,6 +22,8 @@ mod service;
 mod cli;
 mod command;
 mod command_helper;
+#[cfg(feature = "dry-run")]
+mod dry_run;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
@@ -24,6 +24,7 @@ use std::sync::Arc;
 
 use gear_runtime::{opaque::Block, AccountId, Balance, Index};
 use jsonrpsee::RpcModule;
+use sc_client_api::ProofProvider;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -48,7 +49,7 @@ pub fn create_full<C, P>(
 where
     C: ProvideRuntimeApi<Block>,
     C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
-    C: Send + Sync + 'static,
+    C: ProofProvider<Block> + Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: pallet_gear_rpc::GearRuntimeApi<Block>,
@@ -26,7 +26,7 @@ use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -182,7 +182,10 @@ fn remote_keystore(_url: &str) -> Result<Arc<LocalKeystore>, &'static str> {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(
+    mut config: Configuration,
+    dry_run_addr: Option<SocketAddr>,
+) -> Result<TaskManager, ServiceError> {
     let sc_service::PartialComponents {
         client,
         backend,
@@ -267,6 +270,14 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
         })
     };
 
+    #[cfg(feature = "dry-run")]
+    if let Some(addr) = dry_run_addr {
+        let dry_run = crate::dry_run::start(client.clone(), addr)?;
+        task_manager.spawn_handle().spawn("dry-run", None, dry_run);
+    }
+    #[cfg(not(feature = "dry-run"))]
+    let _ = dry_run_addr;
+
     let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
         network: network.clone(),
         client: client.clone(),
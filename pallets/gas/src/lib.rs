@@ -34,24 +34,30 @@
 //! - Managing number of remaining gas, i.e. gas allowance.
 //! - Managing gas tree: create, split, cut, etc new nodes determining
 //! execution resources of messages.
+//! - Depositing events on splits, spends, locks and consumes of gas amounts
+//! not less than the configured threshold, so large gas budgets could be traced.
 //!
 //! ## Interface
 //!
 //! The Gear Gas Pallet implements `gear_common::GasProvider` trait
-//! and shouldn't contain any other functionality, except this trait declares.
+//! and shouldn't contain any other functionality, except this trait declares
+//! and events of the gas tree accounting.
 //!
 //! ## Usage
 //!
 //! How to use the gas functionality from the Gear Gas Pallet:
 //!
-//! 1. Implement its `Config` for your runtime with specified `BlockGasLimit` type.
+//! 1. Implement its `Config` for your runtime with specified `BlockGasLimit`
+//! and `EventThreshold` types.
 //!
 //! ```ignore
 //! // `runtime/src/lib.rs`
 //! // ... //
 //!
 //! impl pallet_gear_gas::Config for Runtime {
+//!     type Event = Event;
 //!     type BlockGasLimit = .. ;
+//!     type EventThreshold = .. ;
 //! }
 //!
 //! // ... //
@@ -125,13 +131,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use common::{
+    gas_provider::{ConsumeOutput, GasBalanceKey, OriginResult, Tree},
     storage::{MapStorage, ValueStorage},
     BlockLimiter, GasProvider,
 };
-use frame_support::{dispatch::DispatchError, pallet_prelude::*};
+use frame_support::{dispatch::DispatchError, pallet_prelude::*, traits::Imbalance};
 pub use pallet::*;
 pub use primitive_types::H256;
-use sp_std::convert::TryInto;
+use sp_std::{convert::TryInto, vec::Vec};
 
 #[cfg(test)]
 mod mock;
@@ -153,15 +160,39 @@ pub mod pallet {
 
     #[pallet::config]
     pub trait Config: frame_system::Config {
+        /// Because this pallet emits events, it depends on the runtime's definition of an event.
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
         /// The maximum amount of gas that can be used within a single block.
         #[pallet::constant]
         type BlockGasLimit: Get<u64>;
+
+        /// The minimal gas amount of the gas tree operation to deposit an event about it.
+        #[pallet::constant]
+        type EventThreshold: Get<u64>;
     }
 
     #[pallet::pallet]
     #[pallet::generate_store(pub(super) trait Store)]
     pub struct Pallet<T>(_);
 
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Gas amount was split from the node to the new one.
+        Split {
+            key: Key,
+            new_key: Key,
+            amount: Balance,
+        },
+        /// Gas amount of the node was spent.
+        Spent { key: Key, amount: Balance },
+        /// Gas amount of the node was locked.
+        Locked { key: Key, amount: Balance },
+        /// Node was consumed, returning the gas amount to its external origin.
+        Consumed { key: Key, amount: Balance },
+    }
+
     // Gas pallet error.
     #[pallet::error]
     pub enum Error<T> {
@@ -172,6 +203,7 @@ pub mod pallet {
         NodeWasConsumed,
         ParentIsLost,
         ParentHasNoChildren,
+        ConsumedWithLock,
     }
 
     impl<T: Config> GasError for Error<T> {
@@ -202,6 +234,10 @@ pub mod pallet {
         fn forbidden() -> Self {
             Self::Forbidden
         }
+
+        fn consumed_with_lock() -> Self {
+            Self::ConsumedWithLock
+        }
     }
 
     pub type Balance = u64;
@@ -269,13 +305,144 @@ pub mod pallet {
         type InternalError = Error<T>;
         type Error = DispatchError;
 
-        type GasTree = TreeImpl<
-            TotalIssuanceWrap<T>,
-            Self::InternalError,
-            Self::Error,
-            Self::ExternalOrigin,
-            GasNodesWrap<T>,
-        >;
+        type GasTree = EventfulTree<T>;
+    }
+
+    type TreeImplOf<T> =
+        TreeImpl<TotalIssuanceWrap<T>, Error<T>, DispatchError, AccountIdOf<T>, GasNodesWrap<T>>;
+
+    /// Gas tree, depositing events on operations with gas amounts
+    /// not less than `Config::EventThreshold`.
+    pub struct EventfulTree<T: Config>(PhantomData<T>);
+
+    impl<T: Config> EventfulTree<T> {
+        fn deposit_above_threshold(amount: Balance, event: Event<T>) {
+            if amount >= T::EventThreshold::get() {
+                Pallet::<T>::deposit_event(event);
+            }
+        }
+    }
+
+    impl<T: Config> Tree for EventfulTree<T> {
+        type ExternalOrigin = AccountIdOf<T>;
+        type Key = Key;
+        type Balance = Balance;
+        type PositiveImbalance = PositiveImbalance<Self::Balance, TotalIssuanceWrap<T>>;
+        type NegativeImbalance = NegativeImbalance<Self::Balance, TotalIssuanceWrap<T>>;
+        type InternalError = Error<T>;
+        type Error = DispatchError;
+
+        fn total_supply() -> Self::Balance {
+            TreeImplOf::<T>::total_supply()
+        }
+
+        fn create(
+            origin: Self::ExternalOrigin,
+            key: Self::Key,
+            amount: Self::Balance,
+        ) -> Result<Self::PositiveImbalance, Self::Error> {
+            TreeImplOf::<T>::create(origin, key, amount)
+        }
+
+        fn get_origin(
+            key: Self::Key,
+        ) -> Result<OriginResult<Self::Key, Self::ExternalOrigin>, Self::Error> {
+            TreeImplOf::<T>::get_origin(key)
+        }
+
+        fn get_limit(
+            key: Self::Key,
+        ) -> Result<GasBalanceKey<Self::Balance, Self::Key>, Self::Error> {
+            TreeImplOf::<T>::get_limit(key)
+        }
+
+        fn consume(
+            key: Self::Key,
+        ) -> Result<ConsumeOutput<Self::NegativeImbalance, Self::ExternalOrigin>, Self::Error>
+        {
+            let output = TreeImplOf::<T>::consume(key)?;
+
+            if let Some((imbalance, _)) = &output {
+                let amount = imbalance.peek();
+                Self::deposit_above_threshold(amount, Event::Consumed { key, amount });
+            }
+
+            Ok(output)
+        }
+
+        fn spend(
+            key: Self::Key,
+            amount: Self::Balance,
+        ) -> Result<Self::NegativeImbalance, Self::Error> {
+            let imbalance = TreeImplOf::<T>::spend(key, amount)?;
+            Self::deposit_above_threshold(amount, Event::Spent { key, amount });
+
+            Ok(imbalance)
+        }
+
+        fn refill(
+            key: Self::Key,
+            amount: Self::Balance,
+        ) -> Result<Self::PositiveImbalance, Self::Error> {
+            TreeImplOf::<T>::refill(key, amount)
+        }
+
+        fn split_with_value(
+            key: Self::Key,
+            new_key: Self::Key,
+            amount: Self::Balance,
+        ) -> Result<(), Self::Error> {
+            TreeImplOf::<T>::split_with_value(key, new_key, amount)?;
+            Self::deposit_above_threshold(
+                amount,
+                Event::Split {
+                    key,
+                    new_key,
+                    amount,
+                },
+            );
+
+            Ok(())
+        }
+
+        fn split(key: Self::Key, new_key: Self::Key) -> Result<(), Self::Error> {
+            TreeImplOf::<T>::split(key, new_key)
+        }
+
+        fn cut(
+            key: Self::Key,
+            new_key: Self::Key,
+            amount: Self::Balance,
+        ) -> Result<(), Self::Error> {
+            TreeImplOf::<T>::cut(key, new_key, amount)
+        }
+
+        fn reserve(
+            key: Self::Key,
+            new_key: Self::Key,
+            amount: Self::Balance,
+        ) -> Result<(), Self::Error> {
+            TreeImplOf::<T>::reserve(key, new_key, amount)
+        }
+
+        fn lock(key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error> {
+            TreeImplOf::<T>::lock(key, amount)?;
+            Self::deposit_above_threshold(amount, Event::Locked { key, amount });
+
+            Ok(())
+        }
+
+        fn unlock(key: Self::Key, amount: Self::Balance) -> Result<(), Self::Error> {
+            TreeImplOf::<T>::unlock(key, amount)
+        }
+
+        fn get_lock(key: Self::Key) -> Result<Self::Balance, Self::Error> {
+            TreeImplOf::<T>::get_lock(key)
+        }
+
+        fn values_by_external() -> Result<Vec<(Self::ExternalOrigin, Self::Balance)>, Self::Error> {
+            TreeImplOf::<T>::values_by_external()
+        }
     }
 
     impl<T: Config> BlockLimiter for Pallet<T> {
@@ -286,8 +453,38 @@ pub mod pallet {
         type GasAllowance = GasAllowance<T>;
     }
 
+    impl<T: Config> Pallet<T> {
+        /// Checks that values of the gas nodes cover the total supply.
+        pub fn check_supply() -> Result<(), &'static str> {
+            let values = TreeImplOf::<T>::values_by_external()
+                .map_err(|_| "Gas tree is corrupted")?
+                .into_iter()
+                .try_fold(0 as Balance, |total, (_, value)| total.checked_add(value))
+                .ok_or("Values of gas nodes overflow")?;
+
+            let total_supply = TreeImplOf::<T>::total_supply();
+
+            if values != total_supply {
+                log::error!(
+                    "Values of gas nodes {:?} don't match total supply {:?}",
+                    values,
+                    total_supply
+                );
+                return Err("Values of gas nodes don't match total supply");
+            }
+
+            Ok(())
+        }
+    }
+
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        /// Consistency check of the storage after runtime upgrade.
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            Self::check_supply()
+        }
+
         /// Initialization
         fn on_initialize(_bn: BlockNumberFor<T>) -> Weight {
             // Reset block gas allowance
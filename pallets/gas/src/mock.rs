@@ -41,7 +41,7 @@ construct_runtime!(
         UncheckedExtrinsic = UncheckedExtrinsic,
     {
         System: system::{Pallet, Call, Config, Storage, Event<T>},
-        Gas: pallet_gas::{Pallet, Storage},
+        Gas: pallet_gas::{Pallet, Storage, Event<T>},
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
     }
 );
@@ -91,8 +91,14 @@ impl system::Config for Test {
     type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+parameter_types! {
+    pub const EventThreshold: u64 = 500;
+}
+
 impl pallet_gas::Config for Test {
+    type Event = Event;
     type BlockGasLimit = ();
+    type EventThreshold = EventThreshold;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -102,6 +102,52 @@ fn can_cut_nodes() {
     })
 }
 
+#[test]
+fn reserved_nodes_can_be_split() {
+    new_test_ext().execute_with(|| {
+        let (root, reserved, specified) = (
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+        );
+        let (total_supply, reserved_value, specified_value) = (1000, 400, 300);
+
+        assert_ok!(Gas::create(ALICE, root, total_supply));
+        assert_ok!(Gas::reserve(root, reserved, reserved_value));
+        assert_ok!(Gas::split_with_value(reserved, specified, specified_value));
+
+        assert_eq!(
+            Gas::get_limit(root).map(|gas_limit| gas_limit.map(|(g, _)| g)),
+            Ok(Some(total_supply - reserved_value))
+        );
+        assert_eq!(
+            Gas::get_limit(reserved).map(|gas_limit| gas_limit.map(|(g, _)| g)),
+            Ok(Some(reserved_value - specified_value))
+        );
+        assert_eq!(Gas::get_origin_key(specified), Ok(Some(reserved)));
+        assert_eq!(Gas::get_external(specified), Ok(Some(ALICE)));
+
+        // Reserved node isn't removed till its children are consumed.
+        assert_eq!(Gas::consume(reserved), Ok(None));
+
+        {
+            let (neg, owner) = Gas::consume(specified).unwrap().unwrap();
+
+            assert_eq!(neg.peek(), reserved_value);
+            assert_eq!(owner, ALICE);
+        }
+        assert!(GasTree::get(reserved).is_none());
+
+        {
+            let (neg, owner) = Gas::consume(root).unwrap().unwrap();
+
+            assert_eq!(neg.peek(), total_supply - reserved_value);
+            assert_eq!(owner, ALICE);
+        }
+        assert_eq!(Gas::total_supply(), 0);
+    })
+}
+
 #[test]
 fn value_tree_with_all_kinds_of_nodes() {
     new_test_ext().execute_with(|| {
@@ -629,3 +675,178 @@ fn gas_free_after_consumed() {
         assert_eq!(Gas::get_limit(root_msg_id), Ok(None));
     })
 }
+
+#[test]
+fn lock_and_unlock_keep_limit() {
+    new_test_ext().execute_with(|| {
+        let (root, specified, unspecified) = (
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+        );
+
+        assert_ok!(Gas::create(ALICE, root, 1000));
+        assert_ok!(Gas::split_with_value(root, specified, 400));
+        assert_ok!(Gas::split(root, unspecified));
+
+        // Lock on the node with value takes from its own limit
+        assert_ok!(Gas::lock(specified, 150));
+        assert_eq!(Gas::get_lock(specified), Ok(150));
+        assert_eq!(
+            Gas::get_limit(specified).unwrap().map(|(g, _)| g),
+            Some(250)
+        );
+
+        // Lock on the unspecified node takes from the ancestor with value
+        assert_ok!(Gas::lock(unspecified, 100));
+        assert_eq!(Gas::get_lock(unspecified), Ok(100));
+        assert_eq!(Gas::get_limit(root).unwrap().map(|(g, _)| g), Some(500));
+
+        // Locked and free values sum up to the original limit
+        assert_eq!(
+            Gas::get_limit(specified).unwrap().map(|(g, _)| g + 150),
+            Some(400)
+        );
+
+        assert_noop!(
+            Gas::lock(specified, 251),
+            Error::<Test>::InsufficientBalance
+        );
+        assert_noop!(
+            Gas::unlock(unspecified, 101),
+            Error::<Test>::InsufficientBalance
+        );
+
+        // Locked node can't be consumed
+        assert_noop!(Gas::consume(specified), Error::<Test>::ConsumedWithLock);
+
+        assert_ok!(Gas::unlock(specified, 150));
+        assert_ok!(Gas::unlock(unspecified, 100));
+        assert_eq!(Gas::get_lock(specified), Ok(0));
+        assert_eq!(
+            Gas::get_limit(specified).unwrap().map(|(g, _)| g),
+            Some(400)
+        );
+        assert_eq!(Gas::get_limit(root).unwrap().map(|(g, _)| g), Some(600));
+
+        assert_ok!(Gas::consume(specified));
+        assert_ok!(Gas::consume(unspecified));
+        let (neg, _) = Gas::consume(root).unwrap().unwrap();
+        assert_eq!(neg.peek(), 1000);
+    });
+}
+
+#[test]
+fn events_deposited_above_threshold() {
+    new_test_ext().execute_with(|| {
+        let (root, big, small) = (
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+        );
+
+        assert_ok!(Gas::create(ALICE, root, 3000));
+        assert_ok!(Gas::split_with_value(root, big, 500));
+        assert_ok!(Gas::split_with_value(root, small, 499));
+        assert_ok!(Gas::lock(root, 600));
+        assert_ok!(Gas::spend(root, 700));
+        assert_ok!(Gas::spend(big, 100));
+        assert_ok!(Gas::unlock(root, 600));
+
+        assert_ok!(Gas::consume(small));
+        assert_ok!(Gas::consume(big));
+        let (neg, _) = Gas::consume(root).unwrap().unwrap();
+
+        let events: Vec<_> = System::events()
+            .into_iter()
+            .filter_map(|record| match record.event {
+                crate::mock::Event::Gas(event) => Some(event),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            events,
+            vec![
+                pallet::Event::Split {
+                    key: root,
+                    new_key: big,
+                    amount: 500,
+                },
+                pallet::Event::Locked {
+                    key: root,
+                    amount: 600,
+                },
+                pallet::Event::Spent {
+                    key: root,
+                    amount: 700,
+                },
+                pallet::Event::Consumed {
+                    key: root,
+                    amount: neg.peek(),
+                },
+            ]
+        );
+    });
+}
+
+#[test]
+fn values_by_external_cover_supply() {
+    new_test_ext().execute_with(|| {
+        let (alice_root, bob_root, specified, unspecified, reserved) = (
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+        );
+
+        assert_ok!(Gas::create(ALICE, alice_root, 1000));
+        assert_ok!(Gas::split_with_value(alice_root, specified, 400));
+        assert_ok!(Gas::split(alice_root, unspecified));
+        assert_ok!(Gas::lock(unspecified, 100));
+        assert_ok!(Gas::reserve(specified, reserved, 150));
+
+        assert_ok!(Gas::create(BOB, bob_root, 300));
+        assert_ok!(Gas::spend(bob_root, 50));
+
+        // Consumed root keeps value for its unspecified child.
+        assert_ok!(Gas::consume(alice_root));
+
+        let mut alice_value = 0;
+        let mut bob_value = 0;
+        for (external, value) in Gas::values_by_external().unwrap() {
+            match external {
+                ALICE => alice_value += value,
+                BOB => bob_value += value,
+                _ => unreachable!("Unknown external origin"),
+            }
+        }
+
+        assert_eq!(alice_value, 1000);
+        assert_eq!(bob_value, 250);
+        assert_eq!(alice_value + bob_value, Gas::total_supply());
+        assert_ok!(Pallet::<Test>::check_supply());
+    });
+}
+
+#[test]
+fn check_supply_detects_lost_node() {
+    new_test_ext().execute_with(|| {
+        let (root, child) = (
+            MessageId::from_origin(H256::random()),
+            MessageId::from_origin(H256::random()),
+        );
+
+        assert_ok!(Gas::create(ALICE, root, 1000));
+        assert_ok!(Gas::split_with_value(root, child, 400));
+        assert_ok!(Pallet::<Test>::check_supply());
+
+        GasTree::remove(child);
+
+        assert_eq!(
+            Pallet::<Test>::check_supply(),
+            Err("Values of gas nodes don't match total supply")
+        );
+    });
+}
@@ -148,6 +148,14 @@ pub mod pallet {
     #[pallet::getter(fn programs_map)]
     pub type ProgramsMap<T> = StorageValue<_, BTreeMap<H256, H256>, ValueQuery>;
 
+    /// Custom counters of programs, incremented by `gr_metric_increment`.
+    ///
+    /// Counters are keyed by the program and the hash of the counter's name.
+    #[pallet::storage]
+    #[pallet::getter(fn program_metric)]
+    pub type ProgramMetrics<T> =
+        StorageDoubleMap<_, Identity, ProgramId, Identity, H256, u64, ValueQuery>;
+
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
         /// Initialization
@@ -187,7 +195,10 @@ pub mod pallet {
             msg.id(),
             ProgramId::from_origin(source),
             ProgramId::from_origin(destination),
-            (*msg.payload()).to_vec(),
+            msg.payload()
+                .to_vec()
+                .try_into()
+                .expect("Payload of stored message is already bounded"),
             msg.value(),
             msg.reply(),
         );
@@ -263,6 +274,12 @@ pub mod pallet {
 
             QueueOf::<T>::mutate_values(|d| remap_with(d, &programs_map));
         }
+
+        fn increment_metric(program_id: ProgramId, key: H256, value: u64) {
+            ProgramMetrics::<T>::mutate(program_id, key, |counter| {
+                *counter = counter.saturating_add(value)
+            });
+        }
     }
 
     #[pallet::call]
@@ -710,3 +710,60 @@ fn check_changed_pages_in_storage() {
         );
     })
 }
+
+#[test]
+fn program_metrics_are_accumulated() {
+    // Handle increments counter `0x01..01` by 5 and 7,
+    // and counter `0x02..02` by 1 in each execution.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_metric_increment" (func $metric_increment (param i32 i64)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $metric_increment (i32.const 0) (i64.const 5))
+                (call $metric_increment (i32.const 0) (i64.const 7))
+                (call $metric_increment (i32.const 32) (i64.const 1))
+            )
+            (data (i32.const 0) "\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01")
+            (data (i32.const 32) "\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02")
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = parse_wat(wat);
+        let program_id = generate_program_id(&code);
+
+        assert_ok!(PalletGear::<Test>::submit_program(
+            Origin::signed(1),
+            code,
+            b"salt".to_vec(),
+            Vec::new(),
+            10_000_000_000_u64,
+            0_u128,
+        ));
+
+        run_to_block(2, None);
+
+        for _ in 0..2 {
+            assert_ok!(PalletGear::<Test>::send_message(
+                Origin::signed(1),
+                program_id,
+                vec![],
+                1_000_000_000_u64,
+                0_u128,
+            ));
+        }
+
+        run_to_block(3, None);
+
+        assert_eq!(
+            Pallet::<Test>::program_metric(program_id, H256([1; 32])),
+            24
+        );
+        assert_eq!(Pallet::<Test>::program_metric(program_id, H256([2; 32])), 2);
+        assert_eq!(Pallet::<Test>::program_metric(program_id, H256([3; 32])), 0);
+    })
+}
@@ -0,0 +1,152 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+#[allow(unused)]
+use crate::Pallet as GearMessenger;
+use common::{benchmarking, storage::*, Origin};
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_support::traits::Get;
+use gear_core::{
+    ids::{MessageId, ProgramId},
+    message::{DispatchKind, StoredDispatch, StoredMessage},
+};
+use sp_std::{convert::TryInto, vec, vec::Vec};
+
+type MailboxOf<T> = <Pallet<T> as Messenger>::Mailbox;
+type WaitlistOf<T> = <Pallet<T> as Messenger>::Waitlist;
+
+/// Maximal payload length used for worst-case messages.
+const MAX_PAYLOAD_LEN: u32 = 64 * 1024;
+
+fn message(id: u64, source: ProgramId, destination: ProgramId, len: u32) -> StoredMessage {
+    StoredMessage::new(
+        MessageId::from(id),
+        source,
+        destination,
+        vec![0xff; len as usize]
+            .try_into()
+            .expect("Payload length is below the limit"),
+        0,
+        None,
+    )
+}
+
+fn dispatch(id: u64, destination: ProgramId, len: u32) -> StoredDispatch {
+    StoredDispatch::new(
+        DispatchKind::Handle,
+        message(id, Default::default(), destination, len),
+        None,
+    )
+}
+
+fn user<T: Config>() -> T::AccountId
+where
+    T::AccountId: Origin,
+{
+    benchmarking::account("user", 0, 0)
+}
+
+fn program<T: Config>() -> ProgramId
+where
+    T::AccountId: Origin,
+{
+    ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin())
+}
+
+benchmarks! {
+    where_clause { where
+        T::AccountId: Origin,
+    }
+
+    waitlist_insert {
+        let l in 0 .. MAX_PAYLOAD_LEN;
+        let dispatch = dispatch(1, program::<T>(), l);
+    }: {
+        WaitlistOf::<T>::insert(dispatch).expect("Waitlist corrupted");
+    }
+    verify {
+        assert!(WaitlistOf::<T>::contains(&program::<T>(), &MessageId::from(1)));
+    }
+
+    waitlist_remove {
+        let l in 0 .. MAX_PAYLOAD_LEN;
+        WaitlistOf::<T>::insert(dispatch(1, program::<T>(), l)).expect("Waitlist corrupted");
+    }: {
+        WaitlistOf::<T>::remove(program::<T>(), MessageId::from(1)).expect("Waitlist corrupted");
+    }
+    verify {
+        assert!(!WaitlistOf::<T>::contains(&program::<T>(), &MessageId::from(1)));
+    }
+
+    waitlist_drain {
+        let q in 0 .. T::WaitlistCapacity::get();
+        for id in 0..q {
+            WaitlistOf::<T>::insert(dispatch(id.into(), program::<T>(), MAX_PAYLOAD_LEN))
+                .expect("Waitlist corrupted");
+        }
+    }: {
+        let _ = WaitlistOf::<T>::drain_key(program::<T>()).collect::<Vec<_>>();
+    }
+    verify {
+        assert_eq!(WaitlistOf::<T>::drain_key(program::<T>()).count(), 0);
+    }
+
+    mailbox_insert {
+        let l in 0 .. MAX_PAYLOAD_LEN;
+        let destination = ProgramId::from_origin(user::<T>().into_origin());
+        let message = message(1, program::<T>(), destination, l);
+    }: {
+        MailboxOf::<T>::insert(message).expect("Mailbox corrupted");
+    }
+    verify {
+        assert!(MailboxOf::<T>::contains(&user::<T>(), &MessageId::from(1)));
+    }
+
+    mailbox_remove {
+        let l in 0 .. MAX_PAYLOAD_LEN;
+        let destination = ProgramId::from_origin(user::<T>().into_origin());
+        MailboxOf::<T>::insert(message(1, program::<T>(), destination, l))
+            .expect("Mailbox corrupted");
+    }: {
+        MailboxOf::<T>::remove(user::<T>(), MessageId::from(1)).expect("Mailbox corrupted");
+    }
+    verify {
+        assert!(!MailboxOf::<T>::contains(&user::<T>(), &MessageId::from(1)));
+    }
+
+    mailbox_drain {
+        let q in 0 .. 256;
+        let destination = ProgramId::from_origin(user::<T>().into_origin());
+        for id in 0..q {
+            MailboxOf::<T>::insert(message(id.into(), program::<T>(), destination, MAX_PAYLOAD_LEN))
+                .expect("Mailbox corrupted");
+        }
+    }: {
+        let _ = MailboxOf::<T>::drain_key(user::<T>()).collect::<Vec<_>>();
+    }
+    verify {
+        assert_eq!(MailboxOf::<T>::drain_key(user::<T>()).count(), 0);
+    }
+}
+
+impl_benchmark_test_suite!(
+    GearMessenger,
+    crate::mock::new_test_ext(),
+    crate::mock::Test
+);
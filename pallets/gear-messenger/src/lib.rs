@@ -35,9 +35,7 @@
 //! or skipped withing the current block.
 //! - Managing continuation of queue processing withing the current block.
 //! - Storing and managing message queue, it's pushing and popping algorithms.
-//! - Storing and managing mailbox, it's insertion and removal algorithms,
-//! including the value claiming with Balances Pallet as `Currency`
-//! `Config`'s associated type.
+//! - Storing and managing mailbox, it's insertion and removal algorithms.
 //!
 //! ## Interface
 //!
@@ -48,14 +46,15 @@
 //!
 //! How to use the messaging functionality from the Gear Messenger Pallet:
 //!
-//! 1. Implement it's `Config` for your runtime with specified `Currency` type.
+//! 1. Implement it's `Config` for your runtime with specified `BlockLimiter` type.
 //!
 //! ```ignore
 //! // `runtime/src/lib.rs`
 //! // ... //
 //!
 //! impl pallet_gear_messenger::Config for Runtime {
-//!     type Currency = .. ;
+//!     type BlockLimiter = .. ;
+//!     type WaitlistCapacity = .. ;
 //! }
 //!
 //! // ... //
@@ -136,6 +135,10 @@
 
 // Database migration module.
 pub mod migration;
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 
 // Runtime mock for running tests.
 #[cfg(test)]
@@ -147,6 +150,7 @@ mod tests;
 
 // Public exports from pallet.
 pub use pallet::*;
+pub use weights::WeightInfo;
 
 // Gear Messenger Pallet module.
 #[frame_support::pallet]
@@ -155,11 +159,7 @@ pub mod pallet {
 
     use common::{storage::*, BlockLimiter, Origin};
     use frame_support::{
-        dispatch::DispatchError,
-        pallet_prelude::*,
-        sp_runtime::traits::UniqueSaturatedInto,
-        storage::PrefixIterator,
-        traits::{BalanceStatus, ReservableCurrency, StorageVersion},
+        dispatch::DispatchError, pallet_prelude::*, storage::PrefixIterator, traits::StorageVersion,
     };
     use frame_system::{pallet_prelude::*, Pallet as SystemPallet};
     use gear_core::{
@@ -167,17 +167,21 @@ pub mod pallet {
         message::{StoredDispatch, StoredMessage},
     };
     use sp_std::{convert::TryInto, marker::PhantomData};
+    use weights::WeightInfo;
 
     /// The current storage version.
-    const MESSENGER_STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+    const MESSENGER_STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
     // Gear Messenger Pallet's `Config`.
     #[pallet::config]
     pub trait Config: frame_system::Config {
-        /// Balances management trait for gas/value migrations.
-        type Currency: ReservableCurrency<Self::AccountId>;
         /// Block limits.
         type BlockLimiter: BlockLimiter<Balance = u64>;
+        /// Max amount of messages in the waitlist of a single program.
+        #[pallet::constant]
+        type WaitlistCapacity: Get<u32>;
+        /// Weight information for mailbox and waitlist operations.
+        type WeightInfo: WeightInfo;
     }
 
     // Gear Messenger Pallet itself.
@@ -226,6 +230,8 @@ pub mod pallet {
         WaitlistDuplicateKey,
         /// Occurs when waitlist's element wasn't found in storage.
         WaitlistElementNotFound,
+        /// Occurs when program's waitlist has reached its capacity.
+        WaitlistFull,
     }
 
     // Implementation of `DequeueError` for `Error<T>`
@@ -286,8 +292,15 @@ pub mod pallet {
         fn element_not_found() -> Self {
             Self::WaitlistElementNotFound
         }
+
+        fn full() -> Self {
+            Self::WaitlistFull
+        }
     }
 
+    /// Block gas allowance of the pallet.
+    pub(crate) type GasAllowanceOf<T> = <<T as Config>::BlockLimiter as BlockLimiter>::GasAllowance;
+
     /// Numeric type defining the maximum amount of messages can be sent
     /// from outside (from extrinsics) or processed in single block.
     pub type Capacity = u32;
@@ -312,7 +325,7 @@ pub mod pallet {
 
     // Private storage for queue's elements.
     #[pallet::storage]
-    type Dispatches<T> =
+    pub(crate) type Dispatches<T> =
         CountedStorageMap<_, Identity, MessageId, LinkedNode<MessageId, StoredDispatch>>;
 
     // Public wrap of the queue's elements.
@@ -337,9 +350,13 @@ pub mod pallet {
 
     // Private storage for mailbox elements.
     #[pallet::storage]
-    type Mailbox<T: Config> =
+    pub(crate) type Mailbox<T: Config> =
         StorageDoubleMap<_, Identity, T::AccountId, Identity, MessageId, StoredMessage>;
 
+    // Private storage for amount of mailbox elements per user.
+    #[pallet::storage]
+    pub(crate) type MailboxCounts<T: Config> = StorageMap<_, Identity, T::AccountId, u32>;
+
     // Public wrap of the mailbox elements.
     common::wrap_extended_storage_double_map!(
         storage: Mailbox,
@@ -347,7 +364,8 @@ pub mod pallet {
         key1: T::AccountId,
         key2: MessageId,
         value: StoredMessage,
-        length: usize
+        length: usize,
+        counter: MailboxCounts
     );
 
     // ----
@@ -385,7 +403,7 @@ pub mod pallet {
 
     // Private storage for waitlist elements.
     #[pallet::storage]
-    type Waitlist<T: Config> = StorageDoubleMap<
+    pub(crate) type Waitlist<T: Config> = StorageDoubleMap<
         _,
         Identity,
         ProgramId,
@@ -394,6 +412,10 @@ pub mod pallet {
         (StoredDispatch, T::BlockNumber),
     >;
 
+    // Private storage for amount of waitlist elements per program.
+    #[pallet::storage]
+    pub(crate) type WaitlistCounts<T> = StorageMap<_, Identity, ProgramId, u32>;
+
     // Public wrap of the waitlist elements.
     common::wrap_extended_storage_double_map!(
         storage: Waitlist,
@@ -401,7 +423,8 @@ pub mod pallet {
         key1: ProgramId,
         key2: MessageId,
         value: (StoredDispatch, T::BlockNumber),
-        length: usize
+        length: usize,
+        counter: WaitlistCounts
     );
 
     // ----
@@ -488,8 +511,10 @@ pub mod pallet {
 
     // Callback trait implementation.
     //
-    // Remove from mailbox means auto-claim in Gear Messenger Context,
-    // so if value present, it will be added to user's balance.
+    // Removal from mailbox reduces block gas allowance
+    // by benchmarked weight of the operation. Value of the
+    // message is managed by the caller: it's either claimed
+    // by the user or returned to the sender on expiration.
     impl<T: crate::Config> FallibleCallback<StoredMessage> for OnRemove<T>
     where
         T::AccountId: Origin,
@@ -497,15 +522,9 @@ pub mod pallet {
         type Error = DispatchError;
 
         fn call(message: &StoredMessage) -> Result<(), Self::Error> {
-            if message.value() > 0 {
-                // Assuming the programs has enough balance
-                <T as Config>::Currency::repatriate_reserved(
-                    &<T::AccountId as Origin>::from_origin(message.source().into_origin()),
-                    &<T::AccountId as Origin>::from_origin(message.destination().into_origin()),
-                    message.value().unique_saturated_into(),
-                    BalanceStatus::Free,
-                )?;
-            }
+            GasAllowanceOf::<T>::decrease(T::WeightInfo::mailbox_remove(
+                message.payload().len() as u32
+            ));
 
             Ok(())
         }
@@ -513,6 +532,50 @@ pub mod pallet {
 
     // ----
 
+    /// Callback function for success `insert` action.
+    pub struct OnMailboxInsert<T: crate::Config>(PhantomData<T>)
+    where
+        T::AccountId: Origin;
+
+    // Callback trait implementation.
+    //
+    // Insertion into mailbox reduces block gas allowance
+    // by benchmarked weight of the operation.
+    impl<T: crate::Config> Callback<StoredMessage> for OnMailboxInsert<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call(message: &StoredMessage) {
+            GasAllowanceOf::<T>::decrease(T::WeightInfo::mailbox_insert(
+                message.payload().len() as u32
+            ));
+        }
+    }
+
+    // ----
+
+    /// Callback function for `drain_key` action.
+    pub struct OnMailboxDrain<T: crate::Config>(PhantomData<T>)
+    where
+        T::AccountId: Origin;
+
+    // Callback trait implementation.
+    //
+    // Draining of the user's mailbox reduces block gas allowance
+    // by benchmarked weight of the operation for the amount of
+    // messages in the mailbox.
+    impl<T: crate::Config> Callback<T::AccountId> for OnMailboxDrain<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call(account: &T::AccountId) {
+            let count = MailboxCounts::<T>::get(account).unwrap_or(0);
+            GasAllowanceOf::<T>::decrease(T::WeightInfo::mailbox_drain(count));
+        }
+    }
+
+    // ----
+
     /// Store of mailbox action's callbacks.
     pub struct MailBoxCallbacks<T: crate::Config>(PhantomData<T>)
     where
@@ -526,9 +589,11 @@ pub mod pallet {
         T::AccountId: Origin,
     {
         type Value = <Pallet<T> as Messenger>::MailboxedMessage;
+        type Key = <Pallet<T> as Messenger>::MailboxFirstKey;
 
-        type OnInsert = ();
+        type OnInsert = OnMailboxInsert<T>;
         type OnRemove = OnRemove<T>;
+        type OnDrain = OnMailboxDrain<T>;
     }
 
     // ----
@@ -557,6 +622,90 @@ pub mod pallet {
 
     // ----
 
+    /// Callback function for getting max amount of messages
+    /// in the waitlist of a single program.
+    pub struct GetWaitlistCapacity<T: crate::Config>(PhantomData<T>)
+    where
+        T::AccountId: Origin;
+
+    // Callback trait implementation.
+    impl<T: crate::Config> GetCallback<usize> for GetWaitlistCapacity<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call() -> usize {
+            T::WaitlistCapacity::get() as usize
+        }
+    }
+
+    // ----
+
+    /// Callback function for success `insert` action.
+    pub struct OnWaitlistInsert<T: crate::Config>(PhantomData<T>)
+    where
+        T::AccountId: Origin;
+
+    // Callback trait implementation.
+    //
+    // Insertion into waitlist reduces block gas allowance
+    // by benchmarked weight of the operation.
+    impl<T: crate::Config> Callback<(StoredDispatch, T::BlockNumber)> for OnWaitlistInsert<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call((dispatch, _bn): &(StoredDispatch, T::BlockNumber)) {
+            GasAllowanceOf::<T>::decrease(T::WeightInfo::waitlist_insert(
+                dispatch.payload().len() as u32
+            ));
+        }
+    }
+
+    // ----
+
+    /// Callback function for success `remove` action.
+    pub struct OnWaitlistRemove<T: crate::Config>(PhantomData<T>)
+    where
+        T::AccountId: Origin;
+
+    // Callback trait implementation.
+    //
+    // Removal from waitlist reduces block gas allowance
+    // by benchmarked weight of the operation.
+    impl<T: crate::Config> Callback<(StoredDispatch, T::BlockNumber)> for OnWaitlistRemove<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call((dispatch, _bn): &(StoredDispatch, T::BlockNumber)) {
+            GasAllowanceOf::<T>::decrease(T::WeightInfo::waitlist_remove(
+                dispatch.payload().len() as u32
+            ));
+        }
+    }
+
+    // ----
+
+    /// Callback function for `drain_key` action.
+    pub struct OnWaitlistDrain<T: crate::Config>(PhantomData<T>)
+    where
+        T::AccountId: Origin;
+
+    // Callback trait implementation.
+    //
+    // Draining of the program's waitlist reduces block gas allowance
+    // by benchmarked weight of the operation for the amount of
+    // messages in the waitlist.
+    impl<T: crate::Config> Callback<ProgramId> for OnWaitlistDrain<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call(program_id: &ProgramId) {
+            let count = WaitlistCounts::<T>::get(program_id).unwrap_or(0);
+            GasAllowanceOf::<T>::decrease(T::WeightInfo::waitlist_drain(count));
+        }
+    }
+
+    // ----
+
     /// Store of waitlist action's callbacks.
     pub struct WaitListCallbacks<T: crate::Config>(PhantomData<T>)
     where
@@ -570,10 +719,13 @@ pub mod pallet {
     {
         type Value = <Pallet<T> as Messenger>::WaitlistedMessage;
         type BlockNumber = <Pallet<T> as Messenger>::BlockNumber;
+        type Key = <Pallet<T> as Messenger>::WaitlistFirstKey;
 
         type GetBlockNumber = GetBlockNumber<T>;
-        type OnInsert = ();
-        type OnRemove = ();
+        type GetCapacity = GetWaitlistCapacity<T>;
+        type OnInsert = OnWaitlistInsert<T>;
+        type OnRemove = OnWaitlistRemove<T>;
+        type OnDrain = OnWaitlistDrain<T>;
     }
 
     // ----
@@ -653,6 +805,20 @@ pub mod pallet {
     where
         T::AccountId: Origin,
     {
+        /// Runtime upgrade.
+        fn on_runtime_upgrade() -> Weight {
+            crate::migration::migrate::<T>()
+        }
+
+        /// Consistency check of the storage after runtime upgrade.
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            <Self as Messenger>::Queue::check_consistency().map_err(|e| {
+                log::error!("Message queue is inconsistent: {:?}", e);
+                "Message queue is inconsistent"
+            })
+        }
+
         /// Block initialization.
         fn on_initialize(_bn: BlockNumberFor<T>) -> Weight {
             // Amount of weight used for initialization.
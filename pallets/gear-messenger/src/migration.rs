@@ -19,13 +19,117 @@
 //! Database migration module.
 
 use crate::{Config, Pallet, Weight};
+use frame_support::traits::{Get, StorageVersion};
 
 /// Wrapper for all migrations of this pallet, based on `StorageVersion`.
 pub fn migrate<T: Config>() -> Weight {
-    use frame_support::traits::StorageVersion;
+    let version = StorageVersion::get::<Pallet<T>>();
+    let mut weight: Weight = 0;
 
-    let _version = StorageVersion::get::<Pallet<T>>();
-    let weight: Weight = 0;
+    if version < 2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
+
+    if version < 3 {
+        weight = weight.saturating_add(v3::migrate::<T>());
+    }
 
     weight
 }
+
+/// Mailbox and waitlist maintain amount of their elements per key.
+mod v2 {
+    use super::*;
+    use crate::{Mailbox, MailboxCounts, Waitlist, WaitlistCounts};
+
+    pub(super) fn migrate<T: Config>() -> Weight {
+        let mut count: u64 = 0;
+
+        for (account, _, _) in Mailbox::<T>::iter() {
+            count += 1;
+            MailboxCounts::<T>::mutate(account, |c| *c = Some(c.unwrap_or(0) + 1));
+        }
+
+        for (program_id, _, _) in Waitlist::<T>::iter() {
+            count += 1;
+            WaitlistCounts::<T>::mutate(program_id, |c| *c = Some(c.unwrap_or(0) + 1));
+        }
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!("Counted {} mailbox and waitlist elements for v2", count);
+
+        T::DbWeight::get().reads_writes(count * 2, count + 1)
+    }
+}
+
+/// Stored execution contexts keep gas reservation nonce,
+/// system reservation and outgoing messages nonce.
+pub(crate) mod v3 {
+    use super::*;
+    use crate::{Dispatches, Waitlist};
+    use codec::{Decode, Encode};
+    use common::storage::LinkedNode;
+    use gear_core::{
+        ids::{MessageId, ProgramId},
+        message::{ContextStore, DispatchKind, Payload, StoredDispatch, StoredMessage},
+    };
+    use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+
+    #[derive(Decode, Encode)]
+    pub(crate) struct OldContextStore {
+        pub outgoing: BTreeMap<u32, Option<Payload>>,
+        pub reply: Option<Payload>,
+        pub initialized: BTreeSet<ProgramId>,
+        pub awaken: BTreeSet<MessageId>,
+        pub reply_sent: bool,
+    }
+
+    #[derive(Decode, Encode)]
+    pub(crate) struct OldStoredDispatch {
+        pub kind: DispatchKind,
+        pub message: StoredMessage,
+        pub context: Option<OldContextStore>,
+    }
+
+    impl From<OldStoredDispatch> for StoredDispatch {
+        fn from(old: OldStoredDispatch) -> Self {
+            let context = old.context.map(|context| {
+                ContextStore::new(
+                    context.outgoing,
+                    context.reply,
+                    context.initialized,
+                    context.awaken,
+                    context.reply_sent,
+                )
+            });
+
+            StoredDispatch::new(old.kind, old.message, context)
+        }
+    }
+
+    pub(super) fn migrate<T: Config>() -> Weight {
+        let mut count: u64 = 0;
+
+        Dispatches::<T>::translate(|_, node: LinkedNode<MessageId, OldStoredDispatch>| {
+            count += 1;
+
+            Some(LinkedNode {
+                next: node.next,
+                value: node.value.into(),
+            })
+        });
+
+        Waitlist::<T>::translate_values(|(dispatch, bn): (OldStoredDispatch, T::BlockNumber)| {
+            count += 1;
+
+            Some((dispatch.into(), bn))
+        });
+
+        StorageVersion::new(3).put::<Pallet<T>>();
+
+        log::info!("Migrated {} queued and waitlisted dispatches to v3", count);
+
+        T::DbWeight::get().reads_writes(count + 1, count + 1)
+    }
+}
@@ -21,7 +21,7 @@
 use crate as pallet_gear_messenger;
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::{OnFinalize, OnInitialize},
+    traits::{ConstU64, OnFinalize, OnInitialize},
 };
 use frame_system as system;
 use primitive_types::H256;
@@ -43,7 +43,7 @@ construct_runtime!(
     {
         System: system::{Pallet, Call, Config, Storage, Event<T>},
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
-        GearGas: pallet_gear_gas::{Pallet},
+        GearGas: pallet_gear_gas::{Pallet, Event<T>},
         GearMessenger: pallet_gear_messenger::{Pallet},
     }
 );
@@ -68,7 +68,9 @@ parameter_types! {
 }
 
 impl pallet_gear_gas::Config for Test {
+    type Event = Event;
     type BlockGasLimit = BlockGasLimit;
+    type EventThreshold = ConstU64<{ u64::MAX }>;
 }
 
 impl system::Config for Test {
@@ -99,8 +101,9 @@ impl system::Config for Test {
 }
 
 impl pallet_gear_messenger::Config for Test {
-    type Currency = ();
     type BlockLimiter = GearGas;
+    type WaitlistCapacity = frame_support::traits::ConstU32<4>;
+    type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
@@ -22,8 +22,8 @@ use super::*;
 use crate::mock::*;
 use common::storage::*;
 use gear_core::{
-    ids::MessageId,
-    message::{DispatchKind, StoredDispatch, StoredMessage},
+    ids::{MessageId, ProgramId},
+    message::{ContextStore, DispatchKind, StoredDispatch, StoredMessage},
 };
 
 type SentOf = <Pallet<Test> as Messenger>::Sent;
@@ -31,6 +31,8 @@ type DequeuedOf = <Pallet<Test> as Messenger>::Dequeued;
 type QueueProcessingOf = <Pallet<Test> as Messenger>::QueueProcessing;
 type QueueOf = <Pallet<Test> as Messenger>::Queue;
 
+mod model;
+
 pub(crate) fn init_logger() {
     let _ = env_logger::Builder::from_default_env()
         .format_module_path(false)
@@ -299,3 +301,148 @@ fn queue_works() {
         assert_eq!(QueueOf::len(), 1);
     });
 }
+
+#[test]
+fn queue_inconsistency_detected() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let dispatch_with_id = |id: MessageId| {
+            StoredDispatch::new(
+                DispatchKind::Handle,
+                StoredMessage::new(
+                    id,
+                    Default::default(),
+                    Default::default(),
+                    Default::default(),
+                    Default::default(),
+                    Default::default(),
+                ),
+                None,
+            )
+        };
+
+        let id_1: MessageId = 1.into();
+        let id_2: MessageId = 2.into();
+        let id_3: MessageId = 3.into();
+
+        // Empty queue is consistent.
+        assert_eq!(QueueOf::check_consistency(), Ok(()));
+
+        for id in [id_1, id_2, id_3] {
+            QueueOf::queue(dispatch_with_id(id)).expect("Algorithmic error");
+        }
+
+        assert_eq!(QueueOf::check_consistency(), Ok(()));
+
+        // Cycled links are detected.
+        Dispatches::<Test>::mutate(id_3, |node| {
+            node.as_mut().expect("Node exists").next = Some(id_1)
+        });
+
+        assert_eq!(
+            QueueOf::check_consistency(),
+            Err(Error::<Test>::QueueTailHasNextKey.into())
+        );
+
+        // Tail, unreachable from head, is detected.
+        Dispatches::<Test>::mutate(id_3, |node| node.as_mut().expect("Node exists").next = None);
+        Dispatches::<Test>::mutate(id_2, |node| node.as_mut().expect("Node exists").next = None);
+
+        assert_eq!(
+            QueueOf::check_consistency(),
+            Err(Error::<Test>::QueueTailParentNotFound.into())
+        );
+
+        // Link to missing node is detected.
+        Dispatches::<Test>::remove(id_2);
+
+        assert_eq!(
+            QueueOf::check_consistency(),
+            Err(Error::<Test>::QueueElementNotFound.into())
+        );
+    });
+}
+
+#[test]
+fn migration_restores_stored_contexts() {
+    use frame_support::{
+        storage::unhashed,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use migration::v3::{OldContextStore, OldStoredDispatch};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(2).put::<Pallet<Test>>();
+
+        let message_id: MessageId = 1.into();
+        let program_id: ProgramId = 2.into();
+
+        // Message waited after sending one message and initializing the other one.
+        let outgoing = || {
+            [(0, None), (1, Some(Default::default()))]
+                .into_iter()
+                .collect()
+        };
+        let old_dispatch = || OldStoredDispatch {
+            kind: DispatchKind::Handle,
+            message: StoredMessage::new(
+                message_id,
+                Default::default(),
+                program_id,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ),
+            context: Some(OldContextStore {
+                outgoing: outgoing(),
+                reply: None,
+                initialized: Default::default(),
+                awaken: Default::default(),
+                reply_sent: false,
+            }),
+        };
+
+        // Values of the previous layout, written before the upgrade.
+        unhashed::put(
+            &Dispatches::<Test>::hashed_key_for(message_id),
+            &LinkedNode {
+                next: None,
+                value: old_dispatch(),
+            },
+        );
+        unhashed::put(
+            &Waitlist::<Test>::hashed_key_for(program_id, message_id),
+            &(old_dispatch(), 5u64),
+        );
+
+        migration::migrate::<Test>();
+
+        assert_eq!(Pallet::<Test>::on_chain_storage_version(), 3);
+
+        let expected = Some(ContextStore::new(
+            outgoing(),
+            None,
+            Default::default(),
+            Default::default(),
+            false,
+        ));
+
+        let node = Dispatches::<Test>::get(message_id).expect("Dispatch is migrated");
+        assert_eq!(node.value.context(), &expected);
+
+        let (dispatch, bn) =
+            Waitlist::<Test>::get(program_id, message_id).expect("Dispatch is migrated");
+        assert_eq!(dispatch.context(), &expected);
+        assert_eq!(bn, 5);
+
+        // Gas for signal couldn't be reserved before the upgrade.
+        assert_eq!(
+            dispatch
+                .context()
+                .as_ref()
+                .and_then(ContextStore::system_reservation),
+            None
+        );
+    });
+}
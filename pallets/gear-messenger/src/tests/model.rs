@@ -0,0 +1,301 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Model-based tests of messenger storages.
+//!
+//! Random sequences of actions are applied both to the `common::storage`
+//! implementations of queue, mailbox and waitlist and to trivial in-memory
+//! models of them. After each action counters and contents of the storages
+//! are compared with the model, so corruption of counters or of the linked
+//! list, underlying the queue, is caught right after the breaking action.
+
+use super::*;
+use common::Origin;
+use frame_support::traits::Get;
+use gear_core::ids::ProgramId;
+use proptest::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+type MailboxOf = <Pallet<Test> as Messenger>::Mailbox;
+type WaitlistOf = <Pallet<Test> as Messenger>::Waitlist;
+
+/// Amount of distinct message ids, kept small to make collisions frequent.
+const KEYS: u64 = 8;
+/// Amount of distinct users or programs owning messages.
+const OWNERS: u64 = 3;
+const MAX_ACTIONS: usize = 64;
+
+#[derive(Debug, Clone)]
+enum QueueAction {
+    Queue(u64),
+    Requeue(u64),
+    Dequeue,
+}
+
+#[derive(Debug, Clone)]
+enum KeyedAction {
+    Insert { owner: u64, id: u64 },
+    Remove { owner: u64, id: u64 },
+    Drain(u64),
+}
+
+fn queue_actions() -> impl Strategy<Value = Vec<QueueAction>> {
+    let action = prop_oneof![
+        (0..KEYS).prop_map(QueueAction::Queue),
+        (0..KEYS).prop_map(QueueAction::Requeue),
+        Just(QueueAction::Dequeue),
+    ];
+
+    prop::collection::vec(action, 0..MAX_ACTIONS)
+}
+
+fn keyed_actions() -> impl Strategy<Value = Vec<KeyedAction>> {
+    let action = prop_oneof![
+        3 => (0..OWNERS, 0..KEYS).prop_map(|(owner, id)| KeyedAction::Insert { owner, id }),
+        3 => (0..OWNERS, 0..KEYS).prop_map(|(owner, id)| KeyedAction::Remove { owner, id }),
+        1 => (0..OWNERS).prop_map(KeyedAction::Drain),
+    ];
+
+    prop::collection::vec(action, 0..MAX_ACTIONS)
+}
+
+fn message(id: u64, destination: ProgramId) -> StoredMessage {
+    StoredMessage::new(
+        id.into(),
+        Default::default(),
+        destination,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+}
+
+fn dispatch(id: u64, destination: ProgramId) -> StoredDispatch {
+    StoredDispatch::new(DispatchKind::Handle, message(id, destination), None)
+}
+
+fn program(owner: u64) -> ProgramId {
+    ProgramId::from_origin(owner.into_origin())
+}
+
+fn assert_queue_matches(model: &VecDeque<MessageId>) {
+    let ids: Vec<MessageId> = QueueOf::iter()
+        .map(|dispatch| dispatch.expect("Queue corrupted").id())
+        .collect();
+
+    assert_eq!(QueueOf::len() as usize, model.len());
+    assert_eq!(QueueOf::is_empty(), model.is_empty());
+    assert_eq!(ids, model.iter().copied().collect::<Vec<_>>());
+    assert_eq!(QueueOf::check_consistency(), Ok(()));
+}
+
+fn assert_mailbox_matches(model: &BTreeMap<u64, BTreeSet<MessageId>>) {
+    for user in 0..OWNERS {
+        let expected = model.get(&user).cloned().unwrap_or_default();
+        let ids: BTreeSet<MessageId> = MailboxOf::iter_key(user).map(|msg| msg.id()).collect();
+
+        assert_eq!(MailboxOf::len(&user) as usize, expected.len());
+        assert_eq!(ids, expected);
+
+        for id in 0..KEYS {
+            let id: MessageId = id.into();
+            assert_eq!(MailboxOf::contains(&user, &id), expected.contains(&id));
+        }
+    }
+}
+
+fn assert_waitlist_matches(model: &BTreeMap<u64, BTreeSet<MessageId>>) {
+    for owner in 0..OWNERS {
+        let program_id = program(owner);
+        let expected = model.get(&owner).cloned().unwrap_or_default();
+        let ids: BTreeSet<MessageId> = WaitlistOf::iter_key(program_id)
+            .map(|(dispatch, _bn)| dispatch.id())
+            .collect();
+
+        assert_eq!(WaitlistOf::len(&program_id) as usize, expected.len());
+        assert_eq!(ids, expected);
+
+        for id in 0..KEYS {
+            let id: MessageId = id.into();
+            assert_eq!(
+                WaitlistOf::contains(&program_id, &id),
+                expected.contains(&id)
+            );
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn queue_follows_model(actions in queue_actions()) {
+        new_test_ext().execute_with(|| {
+            let mut model: VecDeque<MessageId> = VecDeque::new();
+
+            for action in actions {
+                match action {
+                    QueueAction::Queue(id) => {
+                        let duplicate = model.contains(&MessageId::from(id));
+                        let res = QueueOf::queue(dispatch(id, Default::default()));
+
+                        assert_eq!(res.is_err(), duplicate);
+
+                        if !duplicate {
+                            model.push_back(id.into());
+                        }
+                    }
+                    QueueAction::Requeue(id) => {
+                        let duplicate = model.contains(&MessageId::from(id));
+                        let res = QueueOf::requeue(dispatch(id, Default::default()));
+
+                        assert_eq!(res.is_err(), duplicate);
+
+                        if !duplicate {
+                            model.push_front(id.into());
+                        }
+                    }
+                    QueueAction::Dequeue => {
+                        let dispatch = QueueOf::dequeue().expect("Queue corrupted");
+
+                        assert_eq!(dispatch.map(|d| d.id()), model.pop_front());
+                    }
+                }
+
+                assert_queue_matches(&model);
+            }
+        });
+    }
+
+    #[test]
+    fn mailbox_follows_model(actions in keyed_actions()) {
+        new_test_ext().execute_with(|| {
+            let mut model: BTreeMap<u64, BTreeSet<MessageId>> = BTreeMap::new();
+
+            for action in actions {
+                match action {
+                    KeyedAction::Insert { owner, id } => {
+                        let inserted = model.entry(owner).or_default().insert(id.into());
+                        let res = MailboxOf::insert(message(id, program(owner)));
+
+                        assert_eq!(res.is_ok(), inserted);
+                    }
+                    KeyedAction::Remove { owner, id } => {
+                        let removed = model.entry(owner).or_default().remove(&MessageId::from(id));
+                        let res = MailboxOf::remove(owner, id.into());
+
+                        assert_eq!(res.is_ok(), removed);
+
+                        if let Ok(msg) = res {
+                            assert_eq!(msg.id(), MessageId::from(id));
+                            assert_eq!(msg.destination(), program(owner));
+                        }
+                    }
+                    KeyedAction::Drain(owner) => {
+                        let expected = model.remove(&owner).unwrap_or_default();
+                        let drained: BTreeSet<MessageId> =
+                            MailboxOf::drain_key(owner).map(|msg| msg.id()).collect();
+
+                        assert_eq!(drained, expected);
+                    }
+                }
+
+                assert_mailbox_matches(&model);
+            }
+        });
+    }
+
+    #[test]
+    fn waitlist_follows_model(actions in keyed_actions()) {
+        new_test_ext().execute_with(|| {
+            let mut model: BTreeMap<u64, BTreeSet<MessageId>> = BTreeMap::new();
+
+            for action in actions {
+                match action {
+                    KeyedAction::Insert { owner, id } => {
+                        let ids = model.entry(owner).or_default();
+                        let duplicate = ids.contains(&MessageId::from(id));
+                        let full = ids.len() >= <Test as Config>::WaitlistCapacity::get() as usize;
+                        let res = WaitlistOf::insert(dispatch(id, program(owner)));
+
+                        if duplicate {
+                            assert_eq!(res, Err(Error::<Test>::WaitlistDuplicateKey.into()));
+                        } else if full {
+                            assert_eq!(res, Err(Error::<Test>::WaitlistFull.into()));
+                        } else {
+                            assert!(res.is_ok());
+                            ids.insert(id.into());
+                        }
+                    }
+                    KeyedAction::Remove { owner, id } => {
+                        let removed = model.entry(owner).or_default().remove(&MessageId::from(id));
+                        let res = WaitlistOf::remove(program(owner), id.into());
+
+                        assert_eq!(res.is_ok(), removed);
+
+                        if let Ok((dispatch, _bn)) = res {
+                            assert_eq!(dispatch.id(), MessageId::from(id));
+                            assert_eq!(dispatch.destination(), program(owner));
+                        }
+                    }
+                    KeyedAction::Drain(owner) => {
+                        let expected = model.remove(&owner).unwrap_or_default();
+                        let drained: BTreeSet<MessageId> = WaitlistOf::drain_key(program(owner))
+                            .map(|(dispatch, _bn)| dispatch.id())
+                            .collect();
+
+                        assert_eq!(drained, expected);
+                    }
+                }
+
+                assert_waitlist_matches(&model);
+            }
+        });
+    }
+
+    #[test]
+    fn waitlist_pages_cover_key(
+        owner in 0..OWNERS,
+        ids in prop::collection::btree_set(0..KEYS, 0..KEYS as usize),
+        limit in 1usize..4,
+    ) {
+        new_test_ext().execute_with(|| {
+            let program_id = program(owner);
+
+            for id in &ids {
+                let _ = WaitlistOf::insert(dispatch(*id, program_id));
+            }
+
+            let expected: Vec<MessageId> = WaitlistOf::iter_key(program_id)
+                .map(|(dispatch, _bn)| dispatch.id())
+                .collect();
+            let mut paged = Vec::new();
+
+            for offset in (0..=expected.len()).step_by(limit) {
+                let page: Vec<MessageId> = WaitlistOf::iter_key_page(program_id, offset, limit)
+                    .map(|(dispatch, _bn)| dispatch.id())
+                    .collect();
+
+                assert!(page.len() <= limit);
+                paged.extend(page);
+            }
+
+            assert_eq!(paged, expected);
+        });
+    }
+}
@@ -0,0 +1,55 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use frame_support::weights::Weight;
+
+mod pallet_gear_messenger;
+pub use self::pallet_gear_messenger::WeightInfo as GearMessengerWeight;
+
+/// Weight functions for pallet_gear_messenger.
+pub trait WeightInfo {
+    fn waitlist_insert(l: u32) -> Weight;
+    fn waitlist_remove(l: u32) -> Weight;
+    fn waitlist_drain(q: u32) -> Weight;
+    fn mailbox_insert(l: u32) -> Weight;
+    fn mailbox_remove(l: u32) -> Weight;
+    fn mailbox_drain(q: u32) -> Weight;
+}
+
+// For backwards compatibility and tests: storage operations
+// don't affect block gas allowance.
+impl WeightInfo for () {
+    fn waitlist_insert(_l: u32) -> Weight {
+        0
+    }
+    fn waitlist_remove(_l: u32) -> Weight {
+        0
+    }
+    fn waitlist_drain(_q: u32) -> Weight {
+        0
+    }
+    fn mailbox_insert(_l: u32) -> Weight {
+        0
+    }
+    fn mailbox_remove(_l: u32) -> Weight {
+        0
+    }
+    fn mailbox_drain(_q: u32) -> Weight {
+        0
+    }
+}
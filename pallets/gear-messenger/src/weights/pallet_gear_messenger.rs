@@ -0,0 +1,64 @@
+//! Placeholder weights for `pallet_gear_messenger`
+//!
+//! THESE WEIGHTS ARE ESTIMATES, THEY WEREN'T PRODUCED BY THE BENCHMARK CLI.
+//! Regenerate them on the reference hardware before relying on them:
+
+// ./target/release/gear-node benchmark pallet --chain=dev --steps=50 --repeat=20 --pallet=pallet_gear_messenger --extrinsic=* --execution=wasm --wasm-execution=compiled --heap-pages=4096 --output=./pallets/gear-messenger/src/weights/
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(clippy::unnecessary_cast)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `pallet_gear_messenger`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
+	// Storage: GearMessenger Waitlist (r:1 w:1)
+	// Storage: System Number (r:1 w:0)
+	fn waitlist_insert(l: u32, ) -> Weight {
+		(18_514_000 as Weight)
+			.saturating_add((1_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: GearMessenger Waitlist (r:1 w:1)
+	fn waitlist_remove(l: u32, ) -> Weight {
+		(21_036_000 as Weight)
+			.saturating_add((1_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: GearMessenger Waitlist (r:1 w:1)
+	fn waitlist_drain(q: u32, ) -> Weight {
+		(4_927_000 as Weight)
+			.saturating_add((84_662_000 as Weight).saturating_mul(q as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(q as Weight)))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(q as Weight)))
+	}
+	// Storage: GearMessenger Mailbox (r:1 w:1)
+	fn mailbox_insert(l: u32, ) -> Weight {
+		(16_380_000 as Weight)
+			.saturating_add((1_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: GearMessenger Mailbox (r:1 w:1)
+	fn mailbox_remove(l: u32, ) -> Weight {
+		(19_802_000 as Weight)
+			.saturating_add((1_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: GearMessenger Mailbox (r:1 w:1)
+	fn mailbox_drain(q: u32, ) -> Weight {
+		(4_713_000 as Weight)
+			.saturating_add((82_105_000 as Weight).saturating_mul(q as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(q as Weight)))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(q as Weight)))
+	}
+}
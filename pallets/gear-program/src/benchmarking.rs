@@ -19,16 +19,17 @@
 use super::*;
 #[allow(unused)]
 use crate::Pallet as GearProgram;
-use common::{benchmarking, Origin};
+use common::{benchmarking, storage::*, Origin};
 use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
 use frame_support::traits::Currency;
 use frame_system::RawOrigin;
 use gear_core::{
-    ids::ProgramId,
-    memory::{PageNumber, WasmPageNumber},
+    ids::{MessageId, ProgramId},
+    memory::{PageBuf, PageNumber, WasmPageNumber},
+    message::{DispatchKind, StoredDispatch, StoredMessage},
 };
 use sp_runtime::traits::UniqueSaturatedInto;
-use sp_std::vec::Vec;
+use sp_std::{collections::btree_map::BTreeMap, convert::TryInto, vec, vec::Vec};
 
 benchmarks! {
     where_clause { where
@@ -47,13 +48,112 @@ benchmarks! {
         let wasm_pages = (0..q).map(WasmPageNumber).collect::<Vec<WasmPageNumber>>();
         let pages: Vec<PageNumber> = wasm_pages.iter().flat_map(|p| p.to_gear_pages_iter()).collect();
         let memory_pages = common::get_program_data_for_pages(program_id.into_origin(), pages.iter()).unwrap().into_iter().map(|(page, data)| (page, data.into_vec())).collect();
+        let program: common::ActiveProgram = common::get_program(program_id.into_origin()).unwrap().try_into().unwrap();
 
         crate::Pallet::<T>::pause_program(program_id).unwrap();
-    }: _(RawOrigin::Signed(caller), program_id, memory_pages, Default::default(), 10_000u32.into())
+    }: _(RawOrigin::Signed(caller), program_id, program, memory_pages, Default::default(), 10_000u32.into())
     verify {
         assert!(crate::Pallet::<T>::program_exists(program_id));
         assert!(!crate::Pallet::<T>::program_paused(program_id));
     }
+
+    pause_own_program {
+        let w in 0 .. 1024;
+        let caller: T::AccountId = benchmarking::account("caller", 0, 0);
+        let code = benchmarking::generate_wasm(WasmPageNumber(16)).unwrap();
+
+        let program_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin());
+        benchmarking::set_program(program_id.into_origin(), code, WasmPageNumber(16));
+        crate::Pallet::<T>::set_program_creator(program_id, caller.clone());
+
+        for i in 0..w {
+            let message = StoredMessage::new(MessageId::from(i as u64), ProgramId::default(), program_id, Default::default(), 0, None);
+            WaitlistOf::<T>::insert(StoredDispatch::new(DispatchKind::Handle, message, None)).unwrap();
+        }
+    }: _(RawOrigin::Signed(caller), program_id, w)
+    verify {
+        assert!(crate::Pallet::<T>::program_paused(program_id));
+    }
+
+    resume_session_init {
+        let w in 0 .. 64 * 1024;
+        let caller: T::AccountId = benchmarking::account("caller", 0, 0);
+        <T as Config>::Currency::deposit_creating(&caller, (1u128 << 60).unique_saturated_into());
+        let code = benchmarking::generate_wasm(WasmPageNumber(16)).unwrap();
+
+        let program_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin());
+        benchmarking::set_program(program_id.into_origin(), code, WasmPageNumber(16));
+        let program: common::ActiveProgram = common::get_program(program_id.into_origin()).unwrap().try_into().unwrap();
+
+        let message = StoredMessage::new(MessageId::default(), ProgramId::default(), program_id, vec![0; w as usize].try_into().unwrap(), 0, None);
+        let wait_list: BTreeMap<_, _> = [(message.id(), StoredDispatch::new(DispatchKind::Handle, message, None))].into();
+
+        crate::Pallet::<T>::pause_program(program_id).unwrap();
+    }: _(RawOrigin::Signed(caller), program_id, program, wait_list, 10_000u32.into())
+    verify {
+        assert!(ResumeSessions::<T>::contains_key(program_id));
+    }
+
+    resume_session_push {
+        let p in 1 .. 256;
+        let caller: T::AccountId = benchmarking::account("caller", 0, 0);
+        <T as Config>::Currency::deposit_creating(&caller, (1u128 << 60).unique_saturated_into());
+        let code = benchmarking::generate_wasm(WasmPageNumber(16)).unwrap();
+
+        let program_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin());
+        benchmarking::set_program(program_id.into_origin(), code, WasmPageNumber(16));
+        let program: common::ActiveProgram = common::get_program(program_id.into_origin()).unwrap().try_into().unwrap();
+
+        crate::Pallet::<T>::pause_program(program_id).unwrap();
+        crate::Pallet::<T>::resume_session_init(RawOrigin::Signed(caller.clone()).into(), program_id, program, Default::default(), 10_000u32.into()).unwrap();
+
+        let memory_pages = (0..p).map(|page| (PageNumber(page), PageBuf::new_zeroed().into_vec())).collect();
+    }: _(RawOrigin::Signed(caller), program_id, memory_pages)
+    verify {
+        assert_eq!(ResumeSessionPages::<T>::iter_prefix(program_id).count(), p as usize);
+    }
+
+    resume_session_commit {
+        let caller: T::AccountId = benchmarking::account("caller", 0, 0);
+        <T as Config>::Currency::deposit_creating(&caller, (1u128 << 60).unique_saturated_into());
+        let code = benchmarking::generate_wasm(WasmPageNumber(16)).unwrap();
+
+        let program_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin());
+        benchmarking::set_program(program_id.into_origin(), code, WasmPageNumber(16));
+
+        let pages: Vec<PageNumber> = WasmPageNumber(16).to_gear_pages_iter().collect();
+        let memory_pages = common::get_program_data_for_pages(program_id.into_origin(), pages.iter()).unwrap().into_iter().map(|(page, data)| (page, data.into_vec())).collect();
+        let program: common::ActiveProgram = common::get_program(program_id.into_origin()).unwrap().try_into().unwrap();
+
+        crate::Pallet::<T>::pause_program(program_id).unwrap();
+        crate::Pallet::<T>::resume_session_init(RawOrigin::Signed(caller.clone()).into(), program_id, program, Default::default(), 10_000u32.into()).unwrap();
+        crate::Pallet::<T>::resume_session_push(RawOrigin::Signed(caller.clone()).into(), program_id, memory_pages).unwrap();
+    }: _(RawOrigin::Signed(caller), program_id)
+    verify {
+        assert!(crate::Pallet::<T>::program_exists(program_id));
+        assert!(!crate::Pallet::<T>::program_paused(program_id));
+    }
+
+    resume_session_cancel {
+        let p in 1 .. 256;
+        let caller: T::AccountId = benchmarking::account("caller", 0, 0);
+        <T as Config>::Currency::deposit_creating(&caller, (1u128 << 60).unique_saturated_into());
+        let code = benchmarking::generate_wasm(WasmPageNumber(16)).unwrap();
+
+        let program_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin());
+        benchmarking::set_program(program_id.into_origin(), code, WasmPageNumber(16));
+        let program: common::ActiveProgram = common::get_program(program_id.into_origin()).unwrap().try_into().unwrap();
+
+        crate::Pallet::<T>::pause_program(program_id).unwrap();
+        crate::Pallet::<T>::resume_session_init(RawOrigin::Signed(caller.clone()).into(), program_id, program, Default::default(), 10_000u32.into()).unwrap();
+
+        let memory_pages = (0..p).map(|page| (PageNumber(page), PageBuf::new_zeroed().into_vec())).collect();
+        crate::Pallet::<T>::resume_session_push(RawOrigin::Signed(caller.clone()).into(), program_id, memory_pages).unwrap();
+    }: _(RawOrigin::Signed(caller), program_id, p)
+    verify {
+        assert!(!ResumeSessions::<T>::contains_key(program_id));
+        assert_eq!(ResumeSessionPages::<T>::iter_prefix(program_id).count(), 0);
+    }
 }
 
 impl_benchmark_test_suite!(GearProgram, crate::mock::new_test_ext(), crate::mock::Test);
@@ -17,11 +17,13 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use super::*;
-use common::{CodeMetadata, CodeStorageError};
+use common::{CodeMetadata, CodeStorageError, CodeTypeMetadata};
+use frame_support::traits::{Get, ReservableCurrency};
 use gear_core::{
     code::{CodeAndId, InstrumentedCode, InstrumentedCodeAndId},
     ids::CodeId,
 };
+use sp_runtime::{traits::Saturating, DispatchResult};
 use sp_std::vec::Vec;
 
 impl<T: Config> common::CodeStorage for pallet::Pallet<T> {
@@ -78,4 +80,82 @@ impl<T: Config> common::CodeStorage for pallet::Pallet<T> {
     fn get_metadata(code_id: CodeId) -> Option<CodeMetadata> {
         MetadataStorage::<T>::get(code_id)
     }
+
+    fn set_type_metadata(code_id: CodeId, metadata: CodeTypeMetadata) {
+        TypeMetadataStorage::<T>::insert(code_id, metadata)
+    }
+
+    fn get_type_metadata(code_id: CodeId) -> Option<CodeTypeMetadata> {
+        TypeMetadataStorage::<T>::get(code_id)
+    }
+}
+
+impl<T: Config> pallet::Pallet<T> {
+    /// Reserves `CodeDeposit` from the account, which uploads new code.
+    pub fn reserve_code_deposit(code_id: CodeId, uploader: T::AccountId) -> DispatchResult {
+        let deposit = T::CodeDeposit::get();
+        T::Currency::reserve(&uploader, deposit)?;
+
+        CodeDeposits::<T>::insert(code_id, (uploader, deposit));
+
+        Ok(())
+    }
+
+    /// Registers new program built from the code.
+    pub fn add_code_user(code_id: CodeId) {
+        CodeUsers::<T>::mutate(code_id, |users| {
+            *users = Some(users.unwrap_or_default().saturating_add(1))
+        });
+    }
+
+    /// Unregisters the program, which no longer uses the code.
+    ///
+    /// Returns `true` if the code should be pruned: it's left without users
+    /// and pruning is enabled. Codes uploaded before deposits were introduced
+    /// have no deposit recorded, so they are never pruned.
+    pub fn remove_code_user(code_id: CodeId) -> bool {
+        let users = CodeUsers::<T>::mutate_exists(code_id, |users| {
+            *users = users
+                .and_then(|users| users.checked_sub(1))
+                .filter(|users| *users > 0);
+
+            users.unwrap_or_default()
+        });
+
+        users == 0
+            && T::CodeDepositRefund::get().is_some()
+            && CodeDeposits::<T>::contains_key(code_id)
+    }
+
+    /// Prunes the code, if it's still unused, returning `CodeDepositRefund`
+    /// share of its deposit to the uploader and slashing the rest.
+    ///
+    /// Returns `true` if the code was pruned.
+    pub fn prune_code(code_id: CodeId) -> bool {
+        let refund = match T::CodeDepositRefund::get() {
+            Some(refund) => refund,
+            None => return false,
+        };
+
+        // New program could be created from the code since pruning was scheduled.
+        if CodeUsers::<T>::contains_key(code_id) {
+            return false;
+        }
+
+        let (uploader, deposit) = match CodeDeposits::<T>::take(code_id) {
+            Some(deposit) => deposit,
+            None => return false,
+        };
+
+        CodeStorage::<T>::remove(code_id);
+        OriginalCodeStorage::<T>::remove(code_id);
+        MetadataStorage::<T>::remove(code_id);
+        TypeMetadataStorage::<T>::remove(code_id);
+
+        let refunded = refund * deposit;
+        T::Currency::unreserve(&uploader, refunded);
+        let _ = T::Currency::slash_reserved(&uploader, deposit.saturating_sub(refunded));
+
+        true
+    }
 }
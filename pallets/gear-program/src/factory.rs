@@ -0,0 +1,69 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use gear_core::ids::ProgramId;
+use sp_std::vec::Vec;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FactoryError {
+    QuotaExceeded,
+    ChildAlreadyLinked,
+}
+
+impl<T: Config> pallet::Pallet<T> {
+    /// Returns program which created the `child` one, if any.
+    pub fn program_parent(child: ProgramId) -> Option<ProgramId> {
+        ProgramParents::<T>::get(child)
+    }
+
+    /// Returns programs created by the `parent` program.
+    pub fn program_children(parent: ProgramId) -> Vec<ProgramId> {
+        ProgramChildren::<T>::iter_key_prefix(parent).collect()
+    }
+
+    /// Returns amount of programs ever created by the `parent` program.
+    pub fn created_programs_count(parent: ProgramId) -> u32 {
+        CreatedProgramsCount::<T>::get(parent)
+    }
+
+    /// Checks that `parent` program is allowed to create one more program.
+    pub fn can_create_program(parent: ProgramId) -> bool {
+        T::ProgramCreationQuota::get()
+            .map(|quota| Self::created_programs_count(parent) < quota)
+            .unwrap_or(true)
+    }
+
+    /// Links `child` program to the `parent` one,
+    /// which is charged from the creation quota.
+    pub fn link_child(parent: ProgramId, child: ProgramId) -> Result<(), FactoryError> {
+        if ProgramParents::<T>::contains_key(child) {
+            return Err(FactoryError::ChildAlreadyLinked);
+        }
+
+        if !Self::can_create_program(parent) {
+            return Err(FactoryError::QuotaExceeded);
+        }
+
+        ProgramParents::<T>::insert(child, parent);
+        ProgramChildren::<T>::insert(parent, child, ());
+        CreatedProgramsCount::<T>::mutate(parent, |count| *count = count.saturating_add(1));
+
+        Ok(())
+    }
+}
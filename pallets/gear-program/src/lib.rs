@@ -18,10 +18,12 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub use factory::FactoryError;
 pub use pallet::*;
 pub use pause::PauseError;
 
 mod code;
+mod factory;
 mod pause;
 mod program;
 
@@ -44,14 +46,11 @@ pub mod pallet {
     pub(crate) type WaitlistOf<T> = <<T as Config>::Messenger as Messenger>::Waitlist;
 
     use super::*;
-    use common::{storage::*, CodeMetadata, Origin as _};
+    use common::{storage::*, CodeMetadata, CodeTypeMetadata, Origin as _};
     use frame_support::{
         dispatch::DispatchResultWithPostInfo,
         pallet_prelude::*,
-        traits::{
-            Currency, ExistenceRequirement, LockIdentifier, LockableCurrency, StorageVersion,
-            WithdrawReasons,
-        },
+        traits::{Currency, LockIdentifier, LockableCurrency, ReservableCurrency, StorageVersion},
     };
     use frame_system::pallet_prelude::*;
     use gear_core::{
@@ -60,14 +59,17 @@ pub mod pallet {
         memory::{vec_page_data_map_to_page_buf_map, PageNumber},
         message::StoredDispatch,
     };
-    use sp_runtime::{traits::Zero, DispatchError};
+    use sp_runtime::{
+        traits::{Saturating, Zero},
+        DispatchError, Perbill,
+    };
     use sp_std::{collections::btree_map::BTreeMap, convert::TryInto, prelude::*};
     use weights::WeightInfo;
 
-    const LOCK_ID: LockIdentifier = *b"resume_p";
+    pub(crate) const LOCK_ID: LockIdentifier = *b"resume_p";
 
     /// The current storage version.
-    const PROGRAM_STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+    const PROGRAM_STORAGE_VERSION: StorageVersion = StorageVersion::new(4);
 
     #[pallet::config]
     pub trait Config: frame_system::Config {
@@ -77,7 +79,7 @@ pub mod pallet {
         /// Weight information for extrinsics in this pallet.
         type WeightInfo: WeightInfo;
 
-        type Currency: LockableCurrency<Self::AccountId>;
+        type Currency: LockableCurrency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
 
         type Messenger: Messenger<
             OutputError = DispatchError,
@@ -85,9 +87,47 @@ pub mod pallet {
             WaitlistSecondKey = MessageId,
             WaitlistedMessage = StoredDispatch,
         >;
+
+        /// The maximum amount of programs a single program is allowed to create.
+        ///
+        /// `None` means that amount of created programs isn't limited.
+        #[pallet::constant]
+        type ProgramCreationQuota: Get<Option<u32>>;
+
+        /// The deposit reserved from the account, which starts a resume session.
+        ///
+        /// The deposit is slashed if uploaded program data doesn't match
+        /// the one the program had before it was paused.
+        #[pallet::constant]
+        type ResumeSessionDeposit: Get<BalanceOf<Self>>;
+
+        /// The amount of blocks the resume session lasts.
+        ///
+        /// Once it's over, the session could be cancelled by anyone,
+        /// so abandoned sessions don't block resuming of the program.
+        #[pallet::constant]
+        type ResumeSessionDuration: Get<Self::BlockNumber>;
+
+        /// The deposit reserved from the account, which uploads new code.
+        #[pallet::constant]
+        type CodeDeposit: Get<BalanceOf<Self>>;
+
+        /// The share of the code deposit returned to the uploader, once the code
+        /// is pruned after failed initialization of the last program using it.
+        /// The rest of the deposit is slashed.
+        ///
+        /// `None` means that codes are never pruned.
+        #[pallet::constant]
+        type CodeDepositRefund: Get<Option<Perbill>>;
+
+        /// Callback on pausing of the program, called before its wait list is taken.
+        type OnProgramPause: Callback<ProgramId>;
+
+        /// Callback on resuming of the program, called after its wait list is restored.
+        type OnProgramResume: Callback<ProgramId>;
     }
 
-    type BalanceOf<T> =
+    pub(crate) type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
     #[pallet::pallet]
@@ -102,16 +142,31 @@ pub mod pallet {
         ProgramResumed(ProgramId),
         /// Program has been paused
         ProgramPaused(ProgramId),
+        /// Resume session for the program has been started
+        ResumeSessionStarted(ProgramId),
+        /// Resume session for the program has failed and its deposit has been slashed
+        ResumeSessionFailed(ProgramId),
+        /// Resume session for the program has been cancelled
+        ResumeSessionCancelled(ProgramId),
     }
 
     #[pallet::error]
     pub enum Error<T> {
         PausedProgramNotFound,
-        WrongMemoryPages,
+        WrongProgramData,
         NotAllocatedPageWithData,
         ResumeProgramNotEnoughValue,
-        WrongWaitList,
         InvalidPageData,
+        ProgramNotFound,
+        ProgramTerminated,
+        NotProgramCreator,
+        ResumeSessionNotFound,
+        ResumeSessionAlreadyExists,
+        NotResumeSessionOwner,
+        ResumeSessionNotExpired,
+        ResumeSessionPagesUnderestimated,
+        DuplicateWaitlistMessage,
+        WaitlistLenUnderestimated,
     }
 
     #[pallet::storage]
@@ -126,13 +181,72 @@ pub mod pallet {
     #[pallet::unbounded]
     pub(crate) type MetadataStorage<T: Config> = StorageMap<_, Identity, CodeId, CodeMetadata>;
 
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type TypeMetadataStorage<T: Config> =
+        StorageMap<_, Identity, CodeId, CodeTypeMetadata>;
+
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type CodeDeposits<T: Config> =
+        StorageMap<_, Identity, CodeId, (T::AccountId, BalanceOf<T>)>;
+
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type CodeUsers<T: Config> = StorageMap<_, Identity, CodeId, u32>;
+
     #[pallet::storage]
     #[pallet::unbounded]
     pub(crate) type PausedPrograms<T: Config> =
         StorageMap<_, Identity, ProgramId, pause::PausedProgram>;
 
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type ProgramCreators<T: Config> = StorageMap<_, Identity, ProgramId, T::AccountId>;
+
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type ProgramParents<T: Config> = StorageMap<_, Identity, ProgramId, ProgramId>;
+
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type ProgramChildren<T: Config> =
+        StorageDoubleMap<_, Identity, ProgramId, Identity, ProgramId, ()>;
+
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type CreatedProgramsCount<T: Config> =
+        StorageMap<_, Identity, ProgramId, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type ResumeSessions<T: Config> = StorageMap<
+        _,
+        Identity,
+        ProgramId,
+        pause::ResumeSession<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+    >;
+
+    #[pallet::storage]
+    #[pallet::unbounded]
+    pub(crate) type ResumeSessionPages<T: Config> =
+        StorageDoubleMap<_, Identity, ProgramId, Identity, PageNumber, Vec<u8>>;
+
     #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T>
+    where
+        T::AccountId: common::Origin,
+    {
+        fn on_runtime_upgrade() -> Weight {
+            migration::migrate::<T>()
+        }
+
+        /// Consistency check of the storage after runtime upgrade.
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            Self::check_pages_consistency()
+        }
+    }
 
     #[pallet::call]
     impl<T: Config> Pallet<T>
@@ -148,7 +262,9 @@ pub mod pallet {
         ///
         /// Parameters:
         /// - `program_id`: id of the program to resume.
+        /// - `program`: program data before it was paused.
         /// - `memory_pages`: program memory before it was paused.
+        /// - `wait_list`: program wait list before it was paused.
         /// - `value`: balance to be transferred to the program once it's been resumed.
         ///
         /// - `ProgramResumed(H256)` in the case of success.
@@ -157,6 +273,7 @@ pub mod pallet {
         pub fn resume_program(
             origin: OriginFor<T>,
             program_id: ProgramId,
+            program: common::ActiveProgram,
             memory_pages: BTreeMap<PageNumber, Vec<u8>>,
             wait_list: BTreeMap<MessageId, gear_core::message::StoredDispatch>,
             value: BalanceOf<T>,
@@ -172,24 +289,257 @@ pub mod pallet {
             let account = ensure_signed(origin)?;
 
             ensure!(!value.is_zero(), Error::<T>::ResumeProgramNotEnoughValue);
+            ensure!(
+                !ResumeSessions::<T>::contains_key(program_id),
+                Error::<T>::ResumeSessionAlreadyExists
+            );
 
-            Self::resume_program_impl(program_id, memory_pages, wait_list)?;
+            Self::resume_program_impl(program_id, program, memory_pages, wait_list)?;
+            Self::transfer_resume_value(&account, program_id, value)?;
 
-            // The value movement `transfer` call respects existence requirements rules, so no need to check
-            // value for being in the valid interval like it's done in `pallet_gear` calls.
-            let program_account =
-                &<T::AccountId as common::Origin>::from_origin(program_id.into_origin());
-            T::Currency::transfer(
-                &account,
-                program_account,
-                value,
-                ExistenceRequirement::AllowDeath,
-            )?;
+            Self::deposit_event(Event::ProgramResumed(program_id));
 
-            // TODO: maybe it is sufficient just to reserve value? (#762)
-            T::Currency::extend_lock(LOCK_ID, program_account, value, WithdrawReasons::FEE);
+            Ok(().into())
+        }
 
-            Self::deposit_event(Event::ProgramResumed(program_id));
+        /// Starts a session resuming a previously paused program
+        ///
+        /// The origin must be Signed and the sender must have sufficient funds
+        /// to reserve `ResumeSessionDeposit`. Memory pages of the program are
+        /// uploaded within the session by `resume_session_push` in any number
+        /// of chunks, then the program is resumed by `resume_session_commit`.
+        ///
+        /// Parameters:
+        /// - `program_id`: id of the program to resume.
+        /// - `program`: program data before it was paused.
+        /// - `wait_list`: program wait list before it was paused.
+        /// - `value`: balance to be transferred to the program once it's been resumed.
+        ///
+        /// Weight depends on the encoded size of the program and its wait list,
+        /// which are stored within the session and restored on commit.
+        ///
+        /// - `ResumeSessionStarted(H256)` in the case of success.
+        ///
+        #[pallet::weight(<T as Config>::WeightInfo::resume_session_init((program, wait_list).encoded_size() as u32))]
+        pub fn resume_session_init(
+            origin: OriginFor<T>,
+            program_id: ProgramId,
+            program: common::ActiveProgram,
+            wait_list: BTreeMap<MessageId, gear_core::message::StoredDispatch>,
+            value: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let account = ensure_signed(origin)?;
+
+            ensure!(!value.is_zero(), Error::<T>::ResumeProgramNotEnoughValue);
+            ensure!(
+                !ResumeSessions::<T>::contains_key(program_id),
+                Error::<T>::ResumeSessionAlreadyExists
+            );
+
+            ensure!(
+                Self::program_paused(program_id),
+                Error::<T>::PausedProgramNotFound
+            );
+
+            let deposit = T::ResumeSessionDeposit::get();
+            T::Currency::reserve(&account, deposit)?;
+
+            let expires_at = <frame_system::Pallet<T>>::block_number()
+                .saturating_add(T::ResumeSessionDuration::get());
+
+            ResumeSessions::<T>::insert(
+                program_id,
+                pause::ResumeSession {
+                    owner: account,
+                    deposit,
+                    value,
+                    program,
+                    wait_list,
+                    expires_at,
+                    pages: 0,
+                },
+            );
+
+            Self::deposit_event(Event::ResumeSessionStarted(program_id));
+
+            Ok(().into())
+        }
+
+        /// Uploads a chunk of memory pages within the resume session
+        ///
+        /// The origin must be Signed and the sender must be the account,
+        /// which started the session. Pages uploaded earlier are overwritten.
+        /// Moving the pages into the program storage on commit is charged here.
+        ///
+        /// Parameters:
+        /// - `program_id`: id of the program to resume.
+        /// - `memory_pages`: chunk of program memory before it was paused.
+        ///
+        // TODO: unfortunately we cannot pass pages data in [PageBuf],
+        // because polkadot-js api can not support this type.
+        #[pallet::weight(<T as Config>::WeightInfo::resume_session_push(memory_pages.len() as u32))]
+        pub fn resume_session_push(
+            origin: OriginFor<T>,
+            program_id: ProgramId,
+            memory_pages: BTreeMap<PageNumber, Vec<u8>>,
+        ) -> DispatchResultWithPostInfo {
+            let account = ensure_signed(origin)?;
+
+            let mut session = Self::ensure_resume_session_owner(program_id, &account)?;
+
+            let memory_pages = match vec_page_data_map_to_page_buf_map(memory_pages) {
+                Ok(data) => data,
+                Err(err) => {
+                    log::debug!("resume session received wrong pages data: {}", err);
+                    return Err(Error::<T>::InvalidPageData.into());
+                }
+            };
+
+            for (page, data) in memory_pages {
+                if !ResumeSessionPages::<T>::contains_key(program_id, page) {
+                    session.pages = session.pages.saturating_add(1);
+                }
+
+                ResumeSessionPages::<T>::insert(program_id, page, data.into_vec());
+            }
+
+            ResumeSessions::<T>::insert(program_id, session);
+
+            Ok(().into())
+        }
+
+        /// Finishes the resume session
+        ///
+        /// The origin must be Signed and the sender must be the account,
+        /// which started the session. If uploaded program data matches the one
+        /// the program had before it was paused, the program is resumed and
+        /// the deposit is returned. Otherwise the deposit is slashed.
+        ///
+        /// Parameters:
+        /// - `program_id`: id of the program to resume.
+        ///
+        /// - `ProgramResumed(H256)` in the case of success.
+        /// - `ResumeSessionFailed(H256)` in the case of mismatching program data.
+        ///
+        #[pallet::weight(<T as Config>::WeightInfo::resume_session_commit())]
+        pub fn resume_session_commit(
+            origin: OriginFor<T>,
+            program_id: ProgramId,
+        ) -> DispatchResultWithPostInfo {
+            let account = ensure_signed(origin)?;
+
+            let session = Self::ensure_resume_session_owner(program_id, &account)?;
+
+            let memory_pages = vec_page_data_map_to_page_buf_map(
+                ResumeSessionPages::<T>::drain_prefix(program_id).collect(),
+            )
+            .map_err(|_| Error::<T>::InvalidPageData)?;
+
+            ResumeSessions::<T>::remove(program_id);
+
+            match Self::resume_program_impl(
+                program_id,
+                session.program,
+                memory_pages,
+                session.wait_list,
+            ) {
+                Ok(()) => {
+                    T::Currency::unreserve(&account, session.deposit);
+                    Self::transfer_resume_value(&account, program_id, session.value)?;
+
+                    Self::deposit_event(Event::ProgramResumed(program_id));
+                }
+                Err(err) if err == Error::<T>::WrongProgramData.into() => {
+                    let _ = T::Currency::slash_reserved(&account, session.deposit);
+
+                    Self::deposit_event(Event::ResumeSessionFailed(program_id));
+                }
+                Err(err) => return Err(err.into()),
+            }
+
+            Ok(().into())
+        }
+
+        /// Cancels the resume session
+        ///
+        /// The origin must be Signed. The account, which started the session,
+        /// is able to cancel it at any time, getting the deposit back. Once
+        /// `ResumeSessionDuration` is over, the session could be cancelled
+        /// by anyone, while the deposit is slashed.
+        ///
+        /// Parameters:
+        /// - `program_id`: id of the program to resume.
+        /// - `pages`: upper bound of the amount of memory pages uploaded within the session.
+        ///
+        /// - `ResumeSessionCancelled(H256)` in the case of success.
+        ///
+        #[pallet::weight(<T as Config>::WeightInfo::resume_session_cancel(*pages))]
+        pub fn resume_session_cancel(
+            origin: OriginFor<T>,
+            program_id: ProgramId,
+            pages: u32,
+        ) -> DispatchResultWithPostInfo {
+            let account = ensure_signed(origin)?;
+
+            let session =
+                ResumeSessions::<T>::get(program_id).ok_or(Error::<T>::ResumeSessionNotFound)?;
+
+            ensure!(
+                session.pages <= pages,
+                Error::<T>::ResumeSessionPagesUnderestimated
+            );
+
+            if session.owner == account {
+                T::Currency::unreserve(&account, session.deposit);
+            } else {
+                ensure!(
+                    <frame_system::Pallet<T>>::block_number() >= session.expires_at,
+                    Error::<T>::ResumeSessionNotExpired
+                );
+
+                let _ = T::Currency::slash_reserved(&session.owner, session.deposit);
+            }
+
+            ResumeSessions::<T>::remove(program_id);
+            ResumeSessionPages::<T>::drain_prefix(program_id).for_each(drop);
+
+            Self::deposit_event(Event::ResumeSessionCancelled(program_id));
+
+            Ok(().into())
+        }
+
+        /// Pauses a program on behalf of its creator
+        ///
+        /// The origin must be Signed and the sender must be the account,
+        /// which created the program. The program can be resumed later
+        /// with `resume_program`.
+        ///
+        /// Parameters:
+        /// - `program_id`: id of the program to pause.
+        /// - `wait_list_len`: upper bound of the amount of messages in the waitlist of the program.
+        ///
+        /// - `ProgramPaused(H256)` in the case of success.
+        ///
+        #[pallet::weight(<T as Config>::WeightInfo::pause_own_program(*wait_list_len))]
+        pub fn pause_own_program(
+            origin: OriginFor<T>,
+            program_id: ProgramId,
+            wait_list_len: u32,
+        ) -> DispatchResultWithPostInfo {
+            let account = ensure_signed(origin)?;
+
+            ensure!(
+                Self::program_creator(program_id) == Some(account),
+                Error::<T>::NotProgramCreator
+            );
+
+            // Waitlist is taken along with the program, so its size is paid in advance.
+            ensure!(
+                WaitlistOf::<T>::len(&program_id) <= wait_list_len as usize,
+                Error::<T>::WaitlistLenUnderestimated
+            );
+
+            Self::pause_program(program_id).map_err(Error::<T>::from)?;
 
             Ok(().into())
         }
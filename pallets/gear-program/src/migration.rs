@@ -16,18 +16,186 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Config, Pallet, Weight};
+use crate::{pause, CodeStorage, Config, Pallet, PausedPrograms, Weight};
 use common::Origin;
+use frame_support::traits::{Get, StorageVersion};
 
 /// Wrapper for all migrations of this pallet, based on `StorageVersion`.
 pub fn migrate<T: Config>() -> Weight
 where
     <T as frame_system::Config>::AccountId: Origin,
 {
-    use frame_support::traits::StorageVersion;
+    let version = StorageVersion::get::<Pallet<T>>();
+    let mut weight: Weight = 0;
 
-    let _version = StorageVersion::get::<Pallet<T>>();
-    let weight: Weight = 0;
+    if version < 2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
+
+    if version < 3 {
+        weight = weight.saturating_add(v3::migrate::<T>());
+    }
+
+    if version < 4 {
+        weight = weight.saturating_add(v4::migrate::<T>());
+    }
 
     weight
 }
+
+/// Paused programs keep the only hash of their data instead of the data itself.
+mod v2 {
+    use super::*;
+    use codec::Decode;
+    use gear_core::ids::{MessageId, ProgramId};
+    use primitive_types::H256;
+    use sp_std::vec::Vec;
+
+    // Programs paused before the upgrade have the layout
+    // with no gas reservations, which are appended by v4.
+    //
+    // Note, that hashes of pages and wait lists are kept as is,
+    // because the data they were calculated from isn't stored on chain.
+    #[derive(Decode)]
+    struct PausedProgram {
+        program_id: ProgramId,
+        program: v4::OldActiveProgram,
+        pages_hash: H256,
+        wait_list_hash: H256,
+        waiting_init: Vec<MessageId>,
+    }
+
+    pub(super) fn migrate<T: Config>() -> Weight {
+        let mut count: u64 = 0;
+
+        PausedPrograms::<T>::translate_values(|old: PausedProgram| {
+            count += 1;
+
+            Some(pause::PausedProgram {
+                program_id: old.program_id,
+                program_hash: pause::paused_program_hash(
+                    &old.program.into(),
+                    old.pages_hash,
+                    old.wait_list_hash,
+                ),
+                waiting_init: old.waiting_init,
+            })
+        });
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!("Migrated {} paused programs to v2", count);
+
+        T::DbWeight::get().reads_writes(count + 1, count + 1)
+    }
+}
+
+/// Instrumented codes keep routes declared by the program.
+mod v3 {
+    use super::*;
+    use codec::Decode;
+    use gear_core::{code::InstrumentedCode, memory::WasmPageNumber, message::DispatchKind};
+    use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+    #[derive(Decode)]
+    struct OldInstrumentedCode {
+        code: Vec<u8>,
+        exports: BTreeSet<DispatchKind>,
+        static_pages: WasmPageNumber,
+        version: u32,
+    }
+
+    pub(super) fn migrate<T: Config>() -> Weight {
+        let mut count: u64 = 0;
+
+        // Programs uploaded before routing was introduced keep
+        // executing all their handle dispatches in `handle`.
+        CodeStorage::<T>::translate_values(|old: OldInstrumentedCode| {
+            count += 1;
+
+            Some(InstrumentedCode::new_unchecked(
+                old.code,
+                old.exports,
+                old.static_pages,
+                old.version,
+                Default::default(),
+            ))
+        });
+
+        StorageVersion::new(3).put::<Pallet<T>>();
+
+        log::info!("Migrated {} instrumented codes to v3", count);
+
+        T::DbWeight::get().reads_writes(count + 1, count + 1)
+    }
+}
+
+/// Active programs keep their gas reservations.
+mod v4 {
+    use super::*;
+    use codec::Decode;
+    use common::{ActiveProgram, ProgramState};
+    use gear_core::{
+        ids::ProgramId,
+        memory::{PageNumber, WasmPageNumber},
+    };
+    use primitive_types::H256;
+    use sp_std::collections::btree_set::BTreeSet;
+
+    #[derive(Decode)]
+    pub(super) struct OldActiveProgram {
+        allocations: BTreeSet<WasmPageNumber>,
+        pages_with_data: BTreeSet<PageNumber>,
+        code_hash: H256,
+        state: ProgramState,
+    }
+
+    impl From<OldActiveProgram> for ActiveProgram {
+        fn from(old: OldActiveProgram) -> Self {
+            ActiveProgram {
+                allocations: old.allocations,
+                pages_with_data: old.pages_with_data,
+                code_hash: old.code_hash,
+                state: old.state,
+                gas_reservation_map: Default::default(),
+            }
+        }
+    }
+
+    #[derive(Decode)]
+    enum OldProgram {
+        Active(OldActiveProgram),
+        Terminated,
+        #[allow(unused)]
+        Exited {
+            inheritor: ProgramId,
+        },
+    }
+
+    pub(super) fn migrate<T: Config>() -> Weight {
+        let mut reads: u64 = 0;
+        let mut count: u64 = 0;
+
+        for id in common::program_ids() {
+            reads += 1;
+
+            let program = sp_io::storage::get(&common::program_key(id)).and_then(|data| {
+                OldProgram::decode(&mut &data[..])
+                    .map_err(|e| log::error!("Failed to decode program {}: {:?}", id, e))
+                    .ok()
+            });
+
+            // Inactive programs don't contain `ActiveProgram`, so their layout is unchanged.
+            if let Some(OldProgram::Active(program)) = program {
+                count += 1;
+                common::set_program(id, program.into());
+            }
+        }
+
+        StorageVersion::new(4).put::<Pallet<T>>();
+
+        log::info!("Migrated {} active programs to v4", count);
+
+        T::DbWeight::get().reads_writes(reads + 1, count + 1)
+    }
+}
@@ -19,7 +19,7 @@
 use crate as pallet_gear_program;
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::{OnFinalize, OnInitialize},
+    traits::{ConstU64, OnFinalize, OnInitialize},
 };
 use frame_system as system;
 use sp_core::H256;
@@ -42,7 +42,7 @@ construct_runtime!(
     {
         System: system::{Pallet, Call, Config, Storage, Event<T>},
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
-        GearGas: pallet_gear_gas::{Pallet},
+        GearGas: pallet_gear_gas::{Pallet, Event<T>},
         GearProgram: pallet_gear_program::{Pallet, Storage, Event<T>},
         GearMessenger: pallet_gear_messenger::{Pallet},
     }
@@ -53,10 +53,15 @@ parameter_types! {
     pub const SS58Prefix: u8 = 42;
     pub const ExistentialDeposit: u64 = 1;
     pub const BlockGasLimit: u64 = 100_000_000;
+    pub static ProgramCreationQuota: Option<u32> = None;
+    pub const ResumeSessionDeposit: u128 = 1_000;
+    pub const ResumeSessionDuration: u64 = 10;
 }
 
 impl pallet_gear_gas::Config for Test {
+    type Event = Event;
     type BlockGasLimit = BlockGasLimit;
+    type EventThreshold = ConstU64<{ u64::MAX }>;
 }
 
 impl system::Config for Test {
@@ -99,8 +104,9 @@ impl pallet_balances::Config for Test {
 }
 
 impl pallet_gear_messenger::Config for Test {
-    type Currency = Balances;
     type BlockLimiter = GearGas;
+    type WaitlistCapacity = frame_support::traits::ConstU32<1024>;
+    type WeightInfo = ();
 }
 
 impl pallet_gear_program::Config for Test {
@@ -108,6 +114,13 @@ impl pallet_gear_program::Config for Test {
     type WeightInfo = ();
     type Currency = Balances;
     type Messenger = GearMessenger;
+    type ProgramCreationQuota = ProgramCreationQuota;
+    type ResumeSessionDeposit = ResumeSessionDeposit;
+    type ResumeSessionDuration = ResumeSessionDuration;
+    type CodeDeposit = ();
+    type CodeDepositRefund = ();
+    type OnProgramPause = ();
+    type OnProgramResume = ();
 }
 
 // Build genesis storage according to the mock runtime.
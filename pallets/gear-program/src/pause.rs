@@ -19,7 +19,10 @@
 use super::*;
 use codec::{Decode, Encode};
 use common::{storage::*, Origin as _};
-use frame_support::dispatch::DispatchResult;
+use frame_support::{
+    dispatch::DispatchResult,
+    traits::{Currency, ExistenceRequirement, LockableCurrency, WithdrawReasons},
+};
 use gear_core::{
     ids::{MessageId, ProgramId},
     memory::{PageBuf, PageNumber},
@@ -31,21 +34,54 @@ use sp_std::{collections::btree_map::BTreeMap, convert::TryInto, vec::Vec};
 
 #[derive(Clone, Debug, PartialEq, Eq, Decode, Encode, TypeInfo)]
 pub(super) struct PausedProgram {
-    program_id: ProgramId,
-    program: common::ActiveProgram,
-    pages_hash: H256,
-    wait_list_hash: H256,
-    waiting_init: Vec<MessageId>,
+    pub(super) program_id: ProgramId,
+    /// Hash of the program, its memory pages and wait list, see [`paused_program_hash`].
+    pub(super) program_hash: H256,
+    pub(super) waiting_init: Vec<MessageId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Decode, Encode, TypeInfo)]
+pub(super) struct ResumeSession<AccountId, Balance, BlockNumber> {
+    pub(super) owner: AccountId,
+    pub(super) deposit: Balance,
+    pub(super) value: Balance,
+    pub(super) program: common::ActiveProgram,
+    pub(super) wait_list: BTreeMap<MessageId, StoredDispatch>,
+    /// Block number, since which the session could be cancelled by anyone.
+    pub(super) expires_at: BlockNumber,
+    /// Amount of memory pages uploaded within the session.
+    pub(super) pages: u32,
 }
 
+/// Zero pages are skipped, so the hash doesn't depend on whether
+/// they are stored or provided explicitly.
 fn memory_pages_hash(pages: &BTreeMap<PageNumber, PageBuf>) -> H256 {
-    pages.using_encoded(sp_io::hashing::blake2_256).into()
+    pages
+        .iter()
+        .filter(|(_, data)| !data.is_zero())
+        .collect::<BTreeMap<_, _>>()
+        .using_encoded(sp_io::hashing::blake2_256)
+        .into()
 }
 
 fn wait_list_hash(wait_list: &BTreeMap<MessageId, StoredDispatch>) -> H256 {
     wait_list.using_encoded(sp_io::hashing::blake2_256).into()
 }
 
+/// The only thing kept on chain to verify the data of the program on resume.
+///
+/// Program itself holds allocations and code hash, while memory pages and
+/// wait list are hashed separately, so they can be verified one by one.
+pub(super) fn paused_program_hash(
+    program: &common::ActiveProgram,
+    pages_hash: H256,
+    wait_list_hash: H256,
+) -> H256 {
+    (program, pages_hash, wait_list_hash)
+        .using_encoded(sp_io::hashing::blake2_256)
+        .into()
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PauseError {
     ProgramNotFound,
@@ -53,6 +89,16 @@ pub enum PauseError {
     InvalidPageDataSize,
 }
 
+impl<T: Config> From<PauseError> for Error<T> {
+    fn from(err: PauseError) -> Self {
+        match err {
+            PauseError::ProgramNotFound => Error::<T>::ProgramNotFound,
+            PauseError::ProgramTerminated => Error::<T>::ProgramTerminated,
+            PauseError::InvalidPageDataSize => Error::<T>::InvalidPageData,
+        }
+    }
+}
+
 impl<T: Config> pallet::Pallet<T> {
     pub fn pause_program(program_id: ProgramId) -> Result<(), PauseError> {
         let program =
@@ -67,15 +113,21 @@ impl<T: Config> pallet::Pallet<T> {
                 PauseError::InvalidPageDataSize
             })?;
 
+        T::OnProgramPause::call(&program_id);
+
         // TODO: update gas limit in `ValueTree` here (issue #1022).
+        let wait_list_hash = wait_list_hash(
+            &WaitlistOf::<T>::drain_key(program_id)
+                .map(|(d, _)| (d.id(), d))
+                .collect(),
+        );
+
         let paused_program = PausedProgram {
             program_id,
-            pages_hash: memory_pages_hash(&pages_data),
-            program,
-            wait_list_hash: wait_list_hash(
-                &WaitlistOf::<T>::drain_key(program_id)
-                    .map(|(d, _)| (d.id(), d))
-                    .collect(),
+            program_hash: paused_program_hash(
+                &program,
+                memory_pages_hash(&pages_data),
+                wait_list_hash,
             ),
             waiting_init: common::waiting_init_take_messages(program_id),
         };
@@ -96,41 +148,92 @@ impl<T: Config> pallet::Pallet<T> {
         PausedPrograms::<T>::contains_key(id)
     }
 
+    pub(super) fn ensure_resume_session_owner(
+        program_id: ProgramId,
+        account: &T::AccountId,
+    ) -> Result<ResumeSession<T::AccountId, BalanceOf<T>, T::BlockNumber>, Error<T>> {
+        let session =
+            ResumeSessions::<T>::get(program_id).ok_or(Error::<T>::ResumeSessionNotFound)?;
+
+        if &session.owner != account {
+            return Err(Error::<T>::NotResumeSessionOwner);
+        }
+
+        Ok(session)
+    }
+
+    pub(super) fn transfer_resume_value(
+        account: &T::AccountId,
+        program_id: ProgramId,
+        value: BalanceOf<T>,
+    ) -> DispatchResult
+    where
+        T::AccountId: common::Origin,
+    {
+        // The value movement `transfer` call respects existence requirements rules, so no need to check
+        // value for being in the valid interval like it's done in `pallet_gear` calls.
+        let program_account =
+            &<T::AccountId as common::Origin>::from_origin(program_id.into_origin());
+        T::Currency::transfer(
+            account,
+            program_account,
+            value,
+            ExistenceRequirement::AllowDeath,
+        )?;
+
+        // TODO: maybe it is sufficient just to reserve value? (#762)
+        T::Currency::extend_lock(LOCK_ID, program_account, value, WithdrawReasons::FEE);
+
+        Ok(())
+    }
+
     pub(super) fn resume_program_impl(
         program_id: ProgramId,
+        program: common::ActiveProgram,
         memory_pages: BTreeMap<PageNumber, PageBuf>,
         wait_list: BTreeMap<MessageId, StoredDispatch>,
     ) -> DispatchResult {
         let paused_program =
             PausedPrograms::<T>::get(program_id).ok_or(Error::<T>::PausedProgramNotFound)?;
 
-        if paused_program.pages_hash != memory_pages_hash(&memory_pages) {
-            return Err(Error::<T>::WrongMemoryPages.into());
+        let program_hash = paused_program_hash(
+            &program,
+            memory_pages_hash(&memory_pages),
+            wait_list_hash(&wait_list),
+        );
+
+        if paused_program.program_hash != program_hash {
+            return Err(Error::<T>::WrongProgramData.into());
         }
 
-        if paused_program.wait_list_hash != wait_list_hash(&wait_list) {
-            return Err(Error::<T>::WrongWaitList.into());
+        // Checked before any changes, so the error doesn't leave
+        // the program half resumed.
+        if wait_list
+            .keys()
+            .any(|message_id| WaitlistOf::<T>::contains(&program_id, message_id))
+        {
+            return Err(Error::<T>::DuplicateWaitlistMessage.into());
         }
 
         PausedPrograms::<T>::remove(program_id);
 
-        if let Err(err) = common::set_program_and_pages_data(
-            program_id.into_origin(),
-            paused_program.program,
-            memory_pages,
-        ) {
+        if let Err(err) =
+            common::set_program_and_pages_data(program_id.into_origin(), program, memory_pages)
+        {
             log::error!("resume_program_impl error: {}", err);
             return Err(Error::<T>::NotAllocatedPageWithData.into());
         }
 
-        wait_list.into_iter().for_each(|(_, d)| {
-            WaitlistOf::<T>::insert(d).expect("Duplicate message is wl");
-        });
+        for (_, dispatch) in wait_list {
+            WaitlistOf::<T>::insert(dispatch)?;
+        }
         sp_io::storage::set(
             &common::waiting_init_prefix(program_id),
             &paused_program.waiting_init.encode()[..],
         );
 
+        T::OnProgramResume::call(&program_id);
+
         Ok(())
     }
 }
@@ -25,7 +25,52 @@ impl<T: Config> pallet::Pallet<T> {
         common::program_exists(program_id.into_origin()) | Self::program_paused(program_id)
     }
 
+    /// Returns account which created the program, if any.
+    pub fn program_creator(program_id: ProgramId) -> Option<T::AccountId> {
+        ProgramCreators::<T>::get(program_id)
+    }
+
+    /// Records `creator` account of the program.
+    pub fn set_program_creator(program_id: ProgramId, creator: T::AccountId) {
+        ProgramCreators::<T>::insert(program_id, creator);
+    }
+
+    /// Forgets creator of the program, which became inactive.
+    pub fn remove_program_creator(program_id: ProgramId) {
+        ProgramCreators::<T>::remove(program_id);
+    }
+
+    /// Checks that pages with data of every active program match its pages
+    /// in storage, while inactive programs have no pages at all.
+    pub fn check_pages_consistency() -> Result<(), &'static str> {
+        for id in common::program_ids() {
+            let stored_pages = common::get_program_stored_pages(id);
+
+            let pages_with_data = match common::get_program(id) {
+                Some(common::Program::Active(program)) => program.pages_with_data,
+                _ => Default::default(),
+            };
+
+            if pages_with_data != stored_pages {
+                log::error!(
+                    "Pages of program {:?} are inconsistent: pages with data = {:?}, stored pages = {:?}",
+                    id,
+                    pages_with_data,
+                    stored_pages
+                );
+                return Err("Program pages are inconsistent");
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn reset_storage() {
         let _ = PausedPrograms::<T>::clear(u32::MAX, None);
+        let _ = ProgramCreators::<T>::clear(u32::MAX, None);
+        let _ = ProgramParents::<T>::clear(u32::MAX, None);
+        let _ = ProgramChildren::<T>::clear(u32::MAX, None);
+        let _ = CreatedProgramsCount::<T>::clear(u32::MAX, None);
+        let _ = CodeUsers::<T>::clear(u32::MAX, None);
     }
 }
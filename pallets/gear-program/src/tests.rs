@@ -19,7 +19,7 @@
 use super::*;
 use crate::mock::*;
 use common::{storage::*, ActiveProgram, CodeMetadata, CodeStorage, Origin as _, ProgramState};
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, traits::Currency};
 use gear_core::{
     code::{Code, CodeAndId},
     ids::{CodeId, MessageId, ProgramId},
@@ -27,7 +27,7 @@ use gear_core::{
     message::{DispatchKind, StoredDispatch, StoredMessage},
 };
 use hex_literal::hex;
-use sp_std::collections::btree_map::BTreeMap;
+use sp_std::{collections::btree_map::BTreeMap, convert::TryInto};
 use utils::CreateProgramResult;
 use wasm_instrument::gas_metering::ConstantCostRules;
 
@@ -46,15 +46,18 @@ fn pause_program_works() {
 
         let wasm_static_pages = WasmPageNumber(16);
         let memory_pages = {
+            let mut page_buf = PageBuf::new_zeroed();
+            page_buf[0] = 1;
+
             let mut pages = BTreeMap::new();
             for page in wasm_static_pages.to_gear_pages_iter() {
-                pages.insert(page, PageBuf::new_zeroed());
+                pages.insert(page, page_buf.clone());
             }
             for page in (wasm_static_pages + 2.into()).to_gear_pages_iter() {
-                pages.insert(page, PageBuf::new_zeroed());
+                pages.insert(page, page_buf.clone());
             }
             for i in 0..wasm_static_pages.to_gear_page().0 {
-                pages.insert(i.into(), PageBuf::new_zeroed());
+                pages.insert(i.into(), page_buf.clone());
             }
 
             pages
@@ -69,6 +72,7 @@ fn pause_program_works() {
             ActiveProgram {
                 allocations,
                 pages_with_data,
+                gas_reservation_map: Default::default(),
                 code_hash,
                 state: ProgramState::Initialized,
             },
@@ -130,6 +134,7 @@ fn pause_program_twice_fails() {
             ActiveProgram {
                 allocations: Default::default(),
                 pages_with_data: Default::default(),
+                gas_reservation_map: Default::default(),
                 code_hash,
                 state: ProgramState::Initialized,
             },
@@ -163,6 +168,7 @@ fn pause_terminated_program_fails() {
             ActiveProgram {
                 allocations: Default::default(),
                 pages_with_data: Default::default(),
+                gas_reservation_map: Default::default(),
                 code_hash,
                 state: ProgramState::Initialized,
             },
@@ -192,6 +198,7 @@ fn pause_uninitialized_program_works() {
             msg_1,
             msg_2,
             memory_pages,
+            ..
         } = utils::create_uninitialized_program_messages(static_pages);
 
         run_to_block(2, None);
@@ -218,6 +225,47 @@ fn pause_uninitialized_program_works() {
     });
 }
 
+#[test]
+fn pause_own_program_works() {
+    new_test_ext().execute_with(|| {
+        let (creator, stranger) = (1, 2);
+        let CreateProgramResult { program_id, .. } =
+            utils::create_uninitialized_program_messages(WasmPageNumber(16));
+
+        GearProgram::set_program_creator(program_id, creator);
+
+        run_to_block(2, None);
+
+        // Init message and two messages waiting for init are in the waitlist.
+        let wait_list_len = 3;
+
+        assert_noop!(
+            GearProgram::pause_own_program(Origin::signed(stranger), program_id, wait_list_len),
+            Error::<Test>::NotProgramCreator
+        );
+        assert_noop!(
+            GearProgram::pause_own_program(Origin::signed(creator), 42.into(), wait_list_len),
+            Error::<Test>::NotProgramCreator
+        );
+        assert_noop!(
+            GearProgram::pause_own_program(Origin::signed(creator), program_id, wait_list_len - 1),
+            Error::<Test>::WaitlistLenUnderestimated
+        );
+
+        assert_ok!(GearProgram::pause_own_program(
+            Origin::signed(creator),
+            program_id,
+            wait_list_len
+        ));
+        assert!(GearProgram::program_paused(program_id));
+
+        assert_noop!(
+            GearProgram::pause_own_program(Origin::signed(creator), program_id, wait_list_len),
+            Error::<Test>::ProgramNotFound
+        );
+    });
+}
+
 #[test]
 fn resume_uninitialized_program_works() {
     let _ = env_logger::Builder::from_default_env()
@@ -228,6 +276,7 @@ fn resume_uninitialized_program_works() {
         let static_pages = WasmPageNumber(16);
         let CreateProgramResult {
             program_id,
+            program,
             init_msg,
             msg_1,
             msg_2,
@@ -246,6 +295,7 @@ fn resume_uninitialized_program_works() {
         run_to_block(100, None);
         assert_ok!(GearProgram::resume_program_impl(
             program_id,
+            program,
             memory_pages.clone(),
             wait_list,
         ));
@@ -288,6 +338,7 @@ fn resume_program_twice_fails() {
         let static_pages = WasmPageNumber(16);
         let CreateProgramResult {
             program_id,
+            program,
             memory_pages,
             init_msg,
             msg_1,
@@ -307,11 +358,12 @@ fn resume_program_twice_fails() {
 
         assert_ok!(GearProgram::resume_program_impl(
             program_id,
+            program.clone(),
             memory_pages.clone(),
             wait_list.clone(),
         ));
         assert_noop!(
-            GearProgram::resume_program_impl(program_id, memory_pages, wait_list),
+            GearProgram::resume_program_impl(program_id, program, memory_pages, wait_list),
             Error::<Test>::PausedProgramNotFound
         );
     });
@@ -323,6 +375,7 @@ fn resume_program_wrong_memory_fails() {
         let static_pages = WasmPageNumber(16);
         let CreateProgramResult {
             program_id,
+            program,
             mut memory_pages,
             init_msg,
             msg_1,
@@ -339,22 +392,61 @@ fn resume_program_wrong_memory_fails() {
         assert_noop!(
             GearProgram::resume_program_impl(
                 program_id,
+                program,
                 memory_pages,
                 IntoIterator::into_iter([init_msg, msg_1, msg_2])
                     .map(|d| (d.id(), d))
                     .collect()
             ),
-            Error::<Test>::WrongMemoryPages
+            Error::<Test>::WrongProgramData
         );
     });
 }
 
+#[test]
+fn resume_program_ignores_zero_pages() {
+    new_test_ext().execute_with(|| {
+        let static_pages = WasmPageNumber(16);
+        let CreateProgramResult {
+            program_id,
+            program,
+            mut memory_pages,
+            init_msg,
+            msg_1,
+            msg_2,
+            ..
+        } = utils::create_uninitialized_program_messages(static_pages);
+
+        // Page became zero, so it was removed from storage.
+        let zero_page = PageNumber(0);
+        common::remove_program_page_data(program_id.into_origin(), zero_page);
+
+        run_to_block(2, None);
+
+        assert_ok!(GearProgram::pause_program(program_id));
+
+        run_to_block(100, None);
+        memory_pages.insert(zero_page, PageBuf::new_zeroed());
+        assert_ok!(GearProgram::resume_program_impl(
+            program_id,
+            program,
+            memory_pages,
+            IntoIterator::into_iter([init_msg, msg_1, msg_2])
+                .map(|d| (d.id(), d))
+                .collect()
+        ));
+
+        assert!(common::get_program_page_data(program_id.into_origin(), zero_page).is_none());
+    });
+}
+
 #[test]
 fn resume_program_wrong_list_fails() {
     new_test_ext().execute_with(|| {
         let static_pages = WasmPageNumber(16);
         let CreateProgramResult {
             program_id,
+            program,
             memory_pages,
             init_msg,
             msg_1,
@@ -376,7 +468,7 @@ fn resume_program_wrong_list_fails() {
                 message.id(),
                 message.source(),
                 message.destination(),
-                vec![0, 1, 2, 3, 4, 5],
+                vec![0, 1, 2, 3, 4, 5].try_into().unwrap(),
                 message.value(),
                 message.reply(),
             ),
@@ -386,12 +478,444 @@ fn resume_program_wrong_list_fails() {
         assert_noop!(
             GearProgram::resume_program_impl(
                 program_id,
+                program,
+                memory_pages,
+                IntoIterator::into_iter([init_msg, msg_1, msg_2])
+                    .map(|d| (d.id(), d))
+                    .collect()
+            ),
+            Error::<Test>::WrongProgramData
+        );
+    });
+}
+
+#[test]
+fn resume_program_wrong_program_fails() {
+    new_test_ext().execute_with(|| {
+        let static_pages = WasmPageNumber(16);
+        let CreateProgramResult {
+            program_id,
+            mut program,
+            memory_pages,
+            init_msg,
+            msg_1,
+            msg_2,
+            ..
+        } = utils::create_uninitialized_program_messages(static_pages);
+
+        run_to_block(2, None);
+
+        assert_ok!(GearProgram::pause_program(program_id));
+
+        run_to_block(100, None);
+        program.allocations.insert(static_pages + 4.into());
+        assert_noop!(
+            GearProgram::resume_program_impl(
+                program_id,
+                program,
                 memory_pages,
                 IntoIterator::into_iter([init_msg, msg_1, msg_2])
                     .map(|d| (d.id(), d))
                     .collect()
             ),
-            Error::<Test>::WrongWaitList
+            Error::<Test>::WrongProgramData
+        );
+    });
+}
+
+#[test]
+fn resume_session_works() {
+    new_test_ext().execute_with(|| {
+        let user = 1;
+        let static_pages = WasmPageNumber(16);
+        let CreateProgramResult {
+            program_id,
+            program,
+            memory_pages,
+            init_msg,
+            msg_1,
+            msg_2,
+            ..
+        } = utils::create_uninitialized_program_messages(static_pages);
+
+        Balances::make_free_balance_be(&user, 1_000_000);
+
+        run_to_block(2, None);
+
+        assert_ok!(GearProgram::pause_program(program_id));
+
+        let wait_list: BTreeMap<_, _> = IntoIterator::into_iter([init_msg, msg_1, msg_2])
+            .map(|d| (d.id(), d))
+            .collect();
+
+        assert_ok!(GearProgram::resume_session_init(
+            Origin::signed(user),
+            program_id,
+            program.clone(),
+            wait_list.clone(),
+            10_000,
+        ));
+        assert_eq!(
+            Balances::reserved_balance(&user),
+            ResumeSessionDeposit::get()
+        );
+        assert_noop!(
+            GearProgram::resume_session_init(
+                Origin::signed(user),
+                program_id,
+                program,
+                wait_list,
+                10_000
+            ),
+            Error::<Test>::ResumeSessionAlreadyExists
+        );
+
+        let mut pages: Vec<_> = memory_pages
+            .clone()
+            .into_iter()
+            .map(|(page, data)| (page, data.into_vec()))
+            .collect();
+        let chunk = pages.split_off(pages.len() / 2);
+
+        assert_noop!(
+            GearProgram::resume_session_push(
+                Origin::signed(user + 1),
+                program_id,
+                chunk.clone().into_iter().collect()
+            ),
+            Error::<Test>::NotResumeSessionOwner
+        );
+        assert_ok!(GearProgram::resume_session_push(
+            Origin::signed(user),
+            program_id,
+            pages.into_iter().collect(),
+        ));
+        assert_ok!(GearProgram::resume_session_push(
+            Origin::signed(user),
+            program_id,
+            chunk.into_iter().collect(),
+        ));
+
+        assert_ok!(GearProgram::resume_session_commit(
+            Origin::signed(user),
+            program_id
+        ));
+        assert!(!GearProgram::program_paused(program_id));
+        assert!(ResumeSessions::<Test>::get(program_id).is_none());
+        assert_eq!(
+            ResumeSessionPages::<Test>::iter_prefix(program_id).count(),
+            0
+        );
+
+        let new_memory_pages =
+            common::get_program_data_for_pages(program_id.into_origin(), memory_pages.keys())
+                .unwrap();
+        assert_eq!(memory_pages, new_memory_pages);
+
+        assert_eq!(Balances::reserved_balance(&user), 0);
+        assert_eq!(Balances::free_balance(&user), 1_000_000 - 10_000);
+    });
+}
+
+#[test]
+fn resume_session_wrong_memory_slashes_deposit() {
+    new_test_ext().execute_with(|| {
+        let user = 1;
+        let static_pages = WasmPageNumber(16);
+        let CreateProgramResult {
+            program_id,
+            program,
+            mut memory_pages,
+            init_msg,
+            msg_1,
+            msg_2,
+            ..
+        } = utils::create_uninitialized_program_messages(static_pages);
+
+        Balances::make_free_balance_be(&user, 1_000_000);
+
+        run_to_block(2, None);
+
+        assert_ok!(GearProgram::pause_program(program_id));
+
+        assert_ok!(GearProgram::resume_session_init(
+            Origin::signed(user),
+            program_id,
+            program,
+            IntoIterator::into_iter([init_msg, msg_1, msg_2])
+                .map(|d| (d.id(), d))
+                .collect(),
+            10_000,
+        ));
+
+        memory_pages.remove(&0.into());
+        assert_ok!(GearProgram::resume_session_push(
+            Origin::signed(user),
+            program_id,
+            memory_pages
+                .into_iter()
+                .map(|(page, data)| (page, data.into_vec()))
+                .collect(),
+        ));
+
+        assert_ok!(GearProgram::resume_session_commit(
+            Origin::signed(user),
+            program_id
+        ));
+        assert!(GearProgram::program_paused(program_id));
+        assert!(ResumeSessions::<Test>::get(program_id).is_none());
+
+        assert_eq!(Balances::reserved_balance(&user), 0);
+        assert_eq!(
+            Balances::free_balance(&user),
+            1_000_000 - ResumeSessionDeposit::get()
+        );
+    });
+}
+
+#[test]
+fn resume_session_cancel_works() {
+    new_test_ext().execute_with(|| {
+        let (user, stranger) = (1, 2);
+        let static_pages = WasmPageNumber(16);
+        let CreateProgramResult {
+            program_id,
+            program,
+            memory_pages,
+            ..
+        } = utils::create_uninitialized_program_messages(static_pages);
+
+        Balances::make_free_balance_be(&user, 1_000_000);
+        Balances::make_free_balance_be(&stranger, 1_000_000);
+
+        run_to_block(2, None);
+
+        assert_ok!(GearProgram::pause_program(program_id));
+
+        assert_ok!(GearProgram::resume_session_init(
+            Origin::signed(user),
+            program_id,
+            program.clone(),
+            Default::default(),
+            10_000,
+        ));
+
+        let pages = memory_pages.len() as u32;
+        assert_ok!(GearProgram::resume_session_push(
+            Origin::signed(user),
+            program_id,
+            memory_pages
+                .into_iter()
+                .map(|(page, data)| (page, data.into_vec()))
+                .collect(),
+        ));
+
+        assert_noop!(
+            GearProgram::resume_session_cancel(Origin::signed(stranger), program_id, pages),
+            Error::<Test>::ResumeSessionNotExpired
+        );
+
+        run_to_block(2 + ResumeSessionDuration::get(), None);
+
+        assert_noop!(
+            GearProgram::resume_session_cancel(Origin::signed(stranger), program_id, pages - 1),
+            Error::<Test>::ResumeSessionPagesUnderestimated
+        );
+        assert_ok!(GearProgram::resume_session_cancel(
+            Origin::signed(stranger),
+            program_id,
+            pages
+        ));
+        assert!(ResumeSessions::<Test>::get(program_id).is_none());
+        assert_eq!(
+            ResumeSessionPages::<Test>::iter_prefix(program_id).count(),
+            0
+        );
+
+        // Abandoned session is slashed.
+        assert_eq!(Balances::reserved_balance(&user), 0);
+        assert_eq!(
+            Balances::free_balance(&user),
+            1_000_000 - ResumeSessionDeposit::get()
+        );
+
+        // Session is cancelled by its owner with the deposit returned.
+        assert_ok!(GearProgram::resume_session_init(
+            Origin::signed(stranger),
+            program_id,
+            program,
+            Default::default(),
+            10_000,
+        ));
+        assert_ok!(GearProgram::resume_session_cancel(
+            Origin::signed(stranger),
+            program_id,
+            0
+        ));
+        assert_eq!(Balances::free_balance(&stranger), 1_000_000);
+    });
+}
+
+#[test]
+fn link_child_respects_quota() {
+    new_test_ext().execute_with(|| {
+        let parent: ProgramId = 1.into();
+        let (first, second): (ProgramId, ProgramId) = (2.into(), 3.into());
+
+        ProgramCreationQuota::set(Some(1));
+
+        assert_eq!(GearProgram::link_child(parent, first), Ok(()));
+        assert_eq!(
+            GearProgram::link_child(parent, first),
+            Err(FactoryError::ChildAlreadyLinked)
+        );
+        assert_eq!(
+            GearProgram::link_child(parent, second),
+            Err(FactoryError::QuotaExceeded)
+        );
+
+        assert_eq!(GearProgram::program_parent(first), Some(parent));
+        assert_eq!(GearProgram::program_parent(second), None);
+        assert_eq!(GearProgram::program_children(parent), vec![first]);
+        assert_eq!(GearProgram::created_programs_count(parent), 1);
+
+        ProgramCreationQuota::set(None);
+
+        assert_eq!(GearProgram::link_child(parent, second), Ok(()));
+        assert_eq!(GearProgram::created_programs_count(parent), 2);
+    });
+}
+
+#[test]
+fn check_pages_consistency_works() {
+    new_test_ext().execute_with(|| {
+        let CreateProgramResult {
+            program_id,
+            memory_pages,
+            ..
+        } = utils::create_uninitialized_program_messages(WasmPageNumber(16));
+        let program_id = program_id.into_origin();
+
+        assert_ok!(GearProgram::check_pages_consistency());
+
+        // Page data is lost, while the page is still recorded as one with data.
+        let (&page, page_buf) = memory_pages.iter().next().expect("pages are not empty");
+        common::remove_program_page_data(program_id, page);
+        assert_eq!(
+            GearProgram::check_pages_consistency(),
+            Err("Program pages are inconsistent")
+        );
+
+        // Inactive program has no pages.
+        assert_ok!(common::set_program_terminated_status(program_id));
+        assert_ok!(GearProgram::check_pages_consistency());
+
+        common::set_program_page_data(program_id, page, page_buf.clone());
+        assert_eq!(
+            GearProgram::check_pages_consistency(),
+            Err("Program pages are inconsistent")
+        );
+    });
+}
+
+#[test]
+fn migration_appends_gas_reservations_to_active_programs() {
+    use codec::Encode;
+    use common::Program;
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+    use gear_core::memory::PageNumber;
+    use primitive_types::H256;
+    use sp_std::collections::btree_set::BTreeSet;
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(3).put::<Pallet<Test>>();
+
+        let active_id = H256::from_low_u64_be(1);
+        let terminated_id = H256::from_low_u64_be(2);
+
+        let allocations: BTreeSet<WasmPageNumber> = (0..2).map(WasmPageNumber).collect();
+        let pages_with_data: BTreeSet<PageNumber> = (0..3).map(PageNumber).collect();
+        let code_hash = H256::from_low_u64_be(3);
+        let state = ProgramState::Uninitialized {
+            message_id: 4.into(),
+        };
+
+        // Programs of the previous layout: `Program::Active` variant
+        // with no gas reservations and `Program::Terminated` one.
+        let old_active = (0u8, &allocations, &pages_with_data, code_hash, &state).encode();
+        sp_io::storage::set(&common::program_key(active_id), &old_active);
+        sp_io::storage::set(&common::program_key(terminated_id), &[1u8]);
+
+        migration::migrate::<Test>();
+
+        assert_eq!(GearProgram::on_chain_storage_version(), 4);
+        assert_eq!(
+            common::get_program(active_id),
+            Some(Program::Active(ActiveProgram {
+                allocations,
+                pages_with_data,
+                code_hash,
+                state,
+                gas_reservation_map: Default::default(),
+            }))
+        );
+        assert_eq!(
+            common::get_program(terminated_id),
+            Some(Program::Terminated)
+        );
+    });
+}
+
+#[test]
+fn migration_rehashes_paused_programs_of_previous_layout() {
+    use codec::Encode;
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+    use gear_core::memory::PageNumber;
+    use primitive_types::H256;
+    use sp_std::collections::btree_set::BTreeSet;
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<Pallet<Test>>();
+
+        let program_id: ProgramId = 1.into();
+        let allocations: BTreeSet<WasmPageNumber> = (0..2).map(WasmPageNumber).collect();
+        let pages_with_data: BTreeSet<PageNumber> = (0..3).map(PageNumber).collect();
+        let code_hash = H256::from_low_u64_be(2);
+        let state = ProgramState::Initialized;
+        let pages_hash = H256::from_low_u64_be(3);
+        let wait_list_hash = H256::from_low_u64_be(4);
+        let waiting_init: Vec<MessageId> = vec![5.into()];
+
+        // Paused program of v1 layout, which contains the whole program
+        // with no gas reservations and hashes of its pages and wait list.
+        let old = (
+            program_id,
+            (&allocations, &pages_with_data, code_hash, &state),
+            pages_hash,
+            wait_list_hash,
+            &waiting_init,
+        )
+            .encode();
+        sp_io::storage::set(&PausedPrograms::<Test>::hashed_key_for(program_id), &old);
+
+        migration::migrate::<Test>();
+
+        assert_eq!(GearProgram::on_chain_storage_version(), 4);
+
+        let program = ActiveProgram {
+            allocations,
+            pages_with_data,
+            code_hash,
+            state,
+            gas_reservation_map: Default::default(),
+        };
+        assert_eq!(
+            PausedPrograms::<Test>::get(program_id),
+            Some(pause::PausedProgram {
+                program_id,
+                program_hash: pause::paused_program_hash(&program, pages_hash, wait_list_hash),
+                waiting_init,
+            })
         );
     });
 }
@@ -408,6 +932,7 @@ mod utils {
         pub msg_1: StoredDispatch,
         pub msg_2: StoredDispatch,
         pub memory_pages: BTreeMap<PageNumber, PageBuf>,
+        pub program: ActiveProgram,
     }
 
     pub fn create_uninitialized_program_messages(
@@ -423,15 +948,19 @@ mod utils {
         Pallet::<Test>::add_code(code_and_id, CodeMetadata::new([0; 32].into(), 1)).unwrap();
 
         let memory_pages = {
+            // Zero pages aren't stored, so pages are filled with some data.
+            let mut page_buf = PageBuf::new_zeroed();
+            page_buf[0] = 1;
+
             let mut pages = BTreeMap::new();
             for page in wasm_static_pages.to_gear_pages_iter() {
-                pages.insert(page, PageBuf::new_zeroed());
+                pages.insert(page, page_buf.clone());
             }
             for page in (wasm_static_pages + 2.into()).to_gear_pages_iter() {
-                pages.insert(page, PageBuf::new_zeroed());
+                pages.insert(page, page_buf.clone());
             }
             for i in 0..wasm_static_pages.to_gear_page().0 {
-                pages.insert(i.into(), PageBuf::new_zeroed());
+                pages.insert(i.into(), page_buf.clone());
             }
 
             pages
@@ -441,16 +970,18 @@ mod utils {
 
         let init_msg_id: MessageId = 3.into();
         let program_id: ProgramId = 1.into();
+        let program = ActiveProgram {
+            allocations,
+            pages_with_data,
+            gas_reservation_map: Default::default(),
+            code_hash: code_id.into_origin(),
+            state: ProgramState::Uninitialized {
+                message_id: init_msg_id,
+            },
+        };
         common::set_program_and_pages_data(
             program_id.into_origin(),
-            ActiveProgram {
-                allocations,
-                pages_with_data,
-                code_hash: code_id.into_origin(),
-                state: ProgramState::Uninitialized {
-                    message_id: init_msg_id,
-                },
-            },
+            program.clone(),
             memory_pages.clone(),
         )
         .expect("memory_pages has invalid pages number");
@@ -495,6 +1026,7 @@ mod utils {
             msg_1,
             msg_2,
             memory_pages,
+            program,
         }
     }
 }
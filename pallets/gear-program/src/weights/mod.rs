@@ -24,6 +24,11 @@ pub use self::pallet_gear_program::WeightInfo as GearProgramWeight;
 /// Weight functions for pallet_gear_program.
 pub trait WeightInfo {
     fn resume_program(q: u32) -> Weight;
+    fn pause_own_program(w: u32) -> Weight;
+    fn resume_session_init(w: u32) -> Weight;
+    fn resume_session_push(p: u32) -> Weight;
+    fn resume_session_commit() -> Weight;
+    fn resume_session_cancel(p: u32) -> Weight;
 }
 
 // For backwards compatibility and tests
@@ -35,4 +40,41 @@ impl WeightInfo for () {
             .saturating_add(RocksDbWeight::get().writes(4u64))
             .saturating_add(SUBMIT_WEIGHT_PER_BYTE.saturating_mul(q as Weight))
     }
+
+    fn pause_own_program(w: u32) -> Weight {
+        (0u64)
+            .saturating_add(RocksDbWeight::get().reads(4u64))
+            .saturating_add(RocksDbWeight::get().reads(w as Weight))
+            .saturating_add(RocksDbWeight::get().writes(4u64))
+            .saturating_add(RocksDbWeight::get().writes(w as Weight))
+    }
+
+    fn resume_session_init(w: u32) -> Weight {
+        (0u64)
+            .saturating_add(RocksDbWeight::get().reads(3u64))
+            .saturating_add(RocksDbWeight::get().writes(2u64))
+            .saturating_add(SUBMIT_WEIGHT_PER_BYTE.saturating_mul(w as Weight))
+    }
+
+    fn resume_session_push(p: u32) -> Weight {
+        (0u64)
+            .saturating_add(RocksDbWeight::get().reads(1u64))
+            .saturating_add(RocksDbWeight::get().reads(p as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1u64))
+            .saturating_add(RocksDbWeight::get().writes(p as Weight))
+            .saturating_add(SUBMIT_WEIGHT_PER_BYTE.saturating_mul(p as Weight))
+    }
+
+    fn resume_session_commit() -> Weight {
+        (0u64)
+            .saturating_add(RocksDbWeight::get().reads(4u64))
+            .saturating_add(RocksDbWeight::get().writes(7u64))
+    }
+
+    fn resume_session_cancel(p: u32) -> Weight {
+        (0u64)
+            .saturating_add(RocksDbWeight::get().reads(2u64))
+            .saturating_add(RocksDbWeight::get().writes(2u64))
+            .saturating_add(RocksDbWeight::get().writes(p as Weight))
+    }
 }
@@ -4,6 +4,10 @@
 //! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
 //! DATE: 2022-03-14, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
 //! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("local"), DB CACHE: 1024
+//!
+//! Weights marked as placeholders were added by hand after the generation and
+//! weren't produced by the benchmark CLI. Regenerate the file with the command
+//! below before relying on them.
 
 // Executed Command:
 // ./target/release/gear-node benchmark --chain=local --execution=wasm --wasm-execution=compiled --pallet=pallet_gear_program --steps 50 --repeat 20 --output ./pallets/gear-program/src/weights/ --extrinsic=*
@@ -32,4 +36,58 @@ impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
 			.saturating_add(T::DbWeight::get().writes(5 as Weight))
 			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(q as Weight)))
 	}
+	// Storage: GearProgram ProgramCreators (r:1 w:0)
+	// Storage: GearMessenger Waitlist (r:1 w:1)
+	// Storage: GearProgram PausedPrograms (r:0 w:1)
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn pause_own_program(w: u32, ) -> Weight {
+		(41_278_000 as Weight)
+			.saturating_add((1_500_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(w as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(w as Weight)))
+	}
+	// Storage: GearProgram ResumeSessions (r:1 w:1)
+	// Storage: GearProgram PausedPrograms (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn resume_session_init(w: u32, ) -> Weight {
+		(38_412_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: GearProgram ResumeSessions (r:1 w:1)
+	// Storage: GearProgram ResumeSessionPages (r:1 w:1)
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn resume_session_push(p: u32, ) -> Weight {
+		(12_905_000 as Weight)
+			.saturating_add((1_374_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(p as Weight)))
+	}
+	// Storage: GearProgram ResumeSessions (r:1 w:1)
+	// Storage: GearProgram ResumeSessionPages (r:1 w:1)
+	// Storage: GearProgram PausedPrograms (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn resume_session_commit() -> Weight {
+		(62_733_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: GearProgram ResumeSessions (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: GearProgram ResumeSessionPages (r:0 w:1)
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn resume_session_cancel(p: u32, ) -> Weight {
+		(31_546_000 as Weight)
+			.saturating_add((1_112_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(p as Weight)))
+	}
 }
@@ -51,6 +51,7 @@ pub mod pallet {
         traits::{Get, StorageVersion},
     };
     use frame_system::pallet_prelude::*;
+    use gear_core::{ids::MessageId, message::StoredDispatch};
     use sp_std::{collections::btree_set::BTreeSet, convert::TryInto, marker::PhantomData};
 
     pub type Cost = u64;
@@ -73,6 +74,18 @@ pub mod pallet {
         /// Cost for storing in waitlist per block.
         #[pallet::constant]
         type WaitlistCost: Get<Cost>;
+
+        /// Cost for storing in mailbox per block.
+        #[pallet::constant]
+        type MailboxCost: Get<Cost>;
+
+        /// Cost for storing in dispatch stash per block.
+        #[pallet::constant]
+        type DispatchStashCost: Get<Cost>;
+
+        /// Cost for holding program active per block.
+        #[pallet::constant]
+        type ProgramCost: Get<Cost>;
     }
 
     // Gear Scheduler Pallet itself.
@@ -145,6 +158,48 @@ pub mod pallet {
 
     // ----
 
+    // Private storage for delayed dispatches with their holding intervals.
+    #[pallet::storage]
+    type DispatchStash<T: Config> =
+        StorageMap<_, Identity, MessageId, (StoredDispatch, Interval<BlockNumberFor<T>>)>;
+
+    // Public wrap of the dispatch stash elements.
+    common::wrap_storage_map!(
+        storage: DispatchStash,
+        name: DispatchStashWrap,
+        key: MessageId,
+        value: (StoredDispatch, Interval<BlockNumberFor<T>>)
+    );
+
+    // Iteration over the dispatch stash values.
+    //
+    // Stash is small and walked only by checks and migrations,
+    // so no counter is kept for it.
+    impl<T: crate::Config> IterableMap<(StoredDispatch, Interval<BlockNumberFor<T>>)>
+        for DispatchStashWrap<T>
+    {
+        type DrainIter = IteratorWrap<
+            PrefixIterator<(MessageId, (StoredDispatch, Interval<BlockNumberFor<T>>))>,
+            (StoredDispatch, Interval<BlockNumberFor<T>>),
+            GetSecondPos,
+        >;
+        type Iter = IteratorWrap<
+            PrefixIterator<(MessageId, (StoredDispatch, Interval<BlockNumberFor<T>>))>,
+            (StoredDispatch, Interval<BlockNumberFor<T>>),
+            GetSecondPos,
+        >;
+
+        fn drain() -> Self::DrainIter {
+            DispatchStash::<T>::drain().into()
+        }
+
+        fn iter() -> Self::Iter {
+            DispatchStash::<T>::iter().into()
+        }
+    }
+
+    // ----
+
     // Private storage for task pool elements.
     // Primary item stored as second key of double map for optimization.
     // Value here is useless, so unit type used as space saver:
@@ -217,18 +272,41 @@ pub mod pallet {
         }
 
         fn mailbox() -> Self::Cost {
-            todo!("#646");
+            T::MailboxCost::get()
         }
 
         fn program() -> Self::Cost {
-            todo!("#646");
+            T::ProgramCost::get()
         }
 
         fn waitlist() -> Self::Cost {
             T::WaitlistCost::get()
         }
+
+        fn dispatch_stash() -> Self::Cost {
+            T::DispatchStashCost::get()
+        }
+    }
+
+    // Below goes helpers for tests of the runtimes using this pallet.
+
+    impl<T: crate::Config> Pallet<T> {
+        /// Returns the nearest block after the given one,
+        /// where some tasks are scheduled.
+        ///
+        /// Iterates over the whole task pool, so intended to be used
+        /// in tests only, in order to skip blocks without tasks.
+        #[cfg(feature = "std")]
+        pub fn next_task_block(bn: BlockNumberFor<T>) -> Option<BlockNumberFor<T>> {
+            TaskPool::<T>::iter_keys()
+                .map(|(task_bn, _)| task_bn)
+                .filter(|task_bn| *task_bn > bn)
+                .min()
+        }
     }
 
+    // ----
+
     // Below goes final `Scheduler` implementation for
     // Gear Scheduler Pallet based on above generated
     // types and parameters.
@@ -260,5 +338,7 @@ pub mod pallet {
             DispatchError,
             TaskPoolCallbacksImpl<T>,
         >;
+
+        type DispatchStash = DispatchStashWrap<T>;
     }
 }
@@ -21,7 +21,7 @@ use frame_support::{
     construct_runtime,
     pallet_prelude::*,
     parameter_types,
-    traits::{ConstU64, FindAuthor},
+    traits::{ConstBool, ConstU32, ConstU64, FindAuthor, ReservableCurrency},
     weights::constants::RocksDbWeight,
 };
 use frame_system as system;
@@ -56,7 +56,7 @@ construct_runtime!(
         GearMessenger: pallet_gear_messenger::{Pallet},
         GearScheduler: pallet_gear_scheduler::{Pallet},
         Gear: pallet_gear::{Pallet, Call, Storage, Event<T>},
-        GearGas: pallet_gear_gas::{Pallet},
+        GearGas: pallet_gear_gas::{Pallet, Event<T>},
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
         Authorship: pallet_authorship::{Pallet, Storage},
         Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
@@ -118,12 +118,21 @@ impl pallet_gear_program::Config for Test {
     type WeightInfo = ();
     type Currency = Balances;
     type Messenger = GearMessenger;
+    type ProgramCreationQuota = ();
+    type ResumeSessionDeposit = ();
+    type ResumeSessionDuration = ();
+    type CodeDeposit = ();
+    type CodeDepositRefund = ();
+    type OnProgramPause = pallet_gear::OnProgramPause<Test>;
+    type OnProgramResume = pallet_gear::OnProgramResume<Test>;
 }
 
 parameter_types! {
     pub const MailboxThreshold: u64 = 3_000;
     pub const BlockGasLimit: u64 = 100_000_000_000;
     pub const OutgoingLimit: u32 = 1024;
+    pub const OutgoingBytesLimit: u32 = 64 * 1024 * 1024;
+    pub const ProgramCandidatesLimit: u32 = 128;
     pub GearSchedule: pallet_gear::Schedule<Test> = <pallet_gear::Schedule<Test>>::default();
 }
 
@@ -134,28 +143,47 @@ impl pallet_gear::Config for Test {
     type WeightInfo = ();
     type Schedule = GearSchedule;
     type OutgoingLimit = OutgoingLimit;
+    type OutgoingBytesLimit = OutgoingBytesLimit;
+    type ProgramCandidatesLimit = ProgramCandidatesLimit;
     type DebugInfo = ();
     type CodeStorage = GearProgram;
     type MailboxThreshold = MailboxThreshold;
+    type GasMinimums = ();
+    type GasEstimationDepth = ConstU32<16>;
+    type ProgramRentFreePeriod = ConstU64<1_000_000>;
+    type ExistentialDepositFreePrograms = ConstBool<false>;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
     type Scheduler = GearScheduler;
+    type QueueRunner = pallet_gear::ProcessUntilAllowance;
+    type ForbiddenDestinations = frame_support::traits::Nothing;
+    type ReplyChainDepthLimit = ConstU32<1024>;
+    type QueueProcessingOrigin = system::EnsureRoot<u64>;
+    type FallbackAuthor = ();
+    type DustBeneficiary = ();
+    type ProgramBanOrigin = system::EnsureRoot<u64>;
 }
 
 impl pallet_gear_scheduler::Config for Test {
     type BlockLimiter = GearGas;
     type ReserveThreshold = ConstU64<1>;
     type WaitlistCost = ConstU64<100>;
+    type MailboxCost = ConstU64<100>;
+    type DispatchStashCost = ConstU64<100>;
+    type ProgramCost = ConstU64<1_000>;
 }
 
 impl pallet_gear_gas::Config for Test {
+    type Event = Event;
     type BlockGasLimit = BlockGasLimit;
+    type EventThreshold = ConstU64<{ u64::MAX }>;
 }
 
 impl pallet_gear_messenger::Config for Test {
-    type Currency = Balances;
     type BlockLimiter = GearGas;
+    type WaitlistCapacity = ConstU32<1024>;
+    type WeightInfo = ();
 }
 
 pub struct FixedBlockAuthor;
@@ -193,6 +221,9 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
         .build_storage::<Test>()
         .unwrap();
 
+    let bank_address = pallet_gear::GearBank::<Test>::account();
+    let existential_deposit = ExistentialDeposit::get() as u128;
+
     pallet_balances::GenesisConfig::<Test> {
         balances: vec![
             (USER_1, 500_000_000_000_u128),
@@ -200,13 +231,21 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
             (USER_3, 500_000_000_000_u128),
             (LOW_BALANCE_USER, 1000_u128),
             (BLOCK_AUTHOR, 500_u128),
+            (bank_address, existential_deposit),
         ],
     }
     .assimilate_storage(&mut t)
     .unwrap();
 
     let mut ext = sp_io::TestExternalities::new(t);
-    ext.execute_with(|| System::set_block_number(1));
+    ext.execute_with(|| {
+        System::set_block_number(1);
+
+        // Bank is provided at genesis, so its existential deposit
+        // isn't paid by the users sending messages in tests.
+        Balances::reserve(&bank_address, existential_deposit)
+            .expect("Bank is endowed with existential deposit");
+    });
     ext
 }
 
@@ -230,3 +269,17 @@ pub fn run_to_block(n: u64, remaining_weight: Option<u64>) {
         Gear::on_idle(System::block_number(), remaining_weight);
     }
 }
+
+/// Fast-forwards to the nearest block with scheduled tasks and runs it.
+///
+/// Blocks before it are skipped without running, so it's expected
+/// that there are no messages in the queue, which need them to be processed.
+/// Returns the reached block number or `None` if no tasks are scheduled.
+pub fn run_to_next_task(remaining_weight: Option<u64>) -> Option<u64> {
+    let bn = GearScheduler::next_task_block(System::block_number())?;
+
+    System::set_block_number(bn - 1);
+    run_to_block(bn, remaining_weight);
+
+    Some(bn)
+}
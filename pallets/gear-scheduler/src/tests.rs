@@ -27,10 +27,11 @@ use core_processor::common::ExecutionErrorReason;
 use frame_support::traits::ReservableCurrency;
 use frame_system::Pallet as SystemPallet;
 use gear_core::{ids::*, message::*};
-use pallet_gear::{GasAllowanceOf, GasHandlerOf};
+use pallet_gear::{GasAllowanceOf, GasHandlerOf, GearBank};
 use sp_core::H256;
 
 type WaitlistOf<T> = <<T as pallet_gear::Config>::Messenger as Messenger>::Waitlist;
+type MailboxOf<T> = <<T as pallet_gear::Config>::Messenger as Messenger>::Mailbox;
 type TaskPoolOf<T> = <<T as pallet_gear::Config>::Scheduler as Scheduler>::TaskPool;
 
 pub(crate) fn init_logger() {
@@ -42,6 +43,10 @@ pub(crate) fn init_logger() {
 
 const DEFAULT_GAS: u64 = 1_000_000;
 
+fn bank_balance() -> u128 {
+    Balances::free_balance(GearBank::<Test>::account())
+}
+
 fn wl_cost_for(amount_of_blocks: u64) -> u128 {
     (<Pallet<Test> as Scheduler>::CostsPerBlock::waitlist() * amount_of_blocks) as u128
 }
@@ -72,12 +77,36 @@ fn populate_wl_from(
     TaskPoolOf::<Test>::add(bn, ScheduledTask::RemoveFromWaitlist(pid, mid))
         .expect("Failed to insert task");
     WaitlistOf::<Test>::insert(dispatch).expect("Failed to insert to waitlist");
-    Balances::reserve(&src, DEFAULT_GAS as u128).expect("Cannot reserve gas");
+    GearBank::<Test>::deposit(&src, DEFAULT_GAS, 0).expect("Cannot deposit gas");
     GasHandlerOf::<Test>::create(src, mid, DEFAULT_GAS).expect("Failed to create gas handler");
 
     (mid, pid)
 }
 
+fn populate_mailbox_of(
+    user_id: <Test as frame_system::Config>::AccountId,
+    bn: <Test as frame_system::Config>::BlockNumber,
+) -> (MessageId, ProgramId) {
+    let message = StoredMessage::new(
+        MessageId::from_origin(H256::random().into_origin()),
+        ProgramId::from_origin(H256::random().into_origin()),
+        ProgramId::from_origin(user_id.into_origin()),
+        Default::default(),
+        0,
+        None,
+    );
+    let mid = message.id();
+    let pid = message.source();
+
+    TaskPoolOf::<Test>::add(bn, ScheduledTask::RemoveFromMailbox(user_id, mid))
+        .expect("Failed to insert task");
+    MailboxOf::<Test>::insert(message).expect("Failed to insert to mailbox");
+    GearBank::<Test>::deposit(&user_id, DEFAULT_GAS, 0).expect("Cannot deposit gas");
+    GasHandlerOf::<Test>::create(user_id, mid, DEFAULT_GAS).expect("Failed to create gas handler");
+
+    (mid, pid)
+}
+
 fn task_and_wl_message_exist(
     mid: impl Into<MessageId>,
     pid: impl Into<ProgramId>,
@@ -113,7 +142,7 @@ fn out_of_rent_reply_exists(
         {
             msg.destination() == src
                 && msg.source() == pid
-                && msg.reply() == Some((mid, 1))
+                && msg.reply() == Some(ReplyDetails::new(mid, 1))
                 && msg.payload() == ExecutionErrorReason::OutOfRent.to_string().as_bytes()
         } else {
             false
@@ -159,7 +188,7 @@ fn gear_handles_tasks() {
             Balances::free_balance(USER_1),
             user1_balance - DEFAULT_GAS as u128
         );
-        assert_eq!(Balances::reserved_balance(USER_1), DEFAULT_GAS as u128);
+        assert_eq!(bank_balance(), DEFAULT_GAS as u128);
 
         // Check if task and message exist before start of block `bn`.
         run_to_block(bn - 1, Some(u64::MAX));
@@ -176,7 +205,7 @@ fn gear_handles_tasks() {
             Balances::free_balance(USER_1),
             user1_balance - DEFAULT_GAS as u128
         );
-        assert_eq!(Balances::reserved_balance(USER_1), DEFAULT_GAS as u128);
+        assert_eq!(bank_balance(), DEFAULT_GAS as u128);
 
         // Check if task and message got processed in block `bn`.
         run_to_block(bn, Some(u64::MAX));
@@ -194,7 +223,7 @@ fn gear_handles_tasks() {
             block_author_balance + cost
         );
         assert_eq!(Balances::free_balance(USER_1), user1_balance - cost);
-        assert_eq!(Balances::reserved_balance(USER_1), 0);
+        assert_eq!(bank_balance(), 0);
     });
 }
 
@@ -239,12 +268,11 @@ fn gear_handles_outdated_tasks() {
             Balances::free_balance(USER_1),
             user1_balance - DEFAULT_GAS as u128
         );
-        assert_eq!(Balances::reserved_balance(USER_1), DEFAULT_GAS as u128);
         assert_eq!(
             Balances::free_balance(USER_2),
             user2_balance - DEFAULT_GAS as u128
         );
-        assert_eq!(Balances::reserved_balance(USER_2), DEFAULT_GAS as u128);
+        assert_eq!(bank_balance(), 2 * DEFAULT_GAS as u128);
 
         // Check if tasks and messages exist before start of block `bn`.
         run_to_block(bn - 1, Some(u64::MAX));
@@ -262,12 +290,11 @@ fn gear_handles_outdated_tasks() {
             Balances::free_balance(USER_1),
             user1_balance - DEFAULT_GAS as u128
         );
-        assert_eq!(Balances::reserved_balance(USER_1), DEFAULT_GAS as u128);
         assert_eq!(
             Balances::free_balance(USER_2),
             user2_balance - DEFAULT_GAS as u128
         );
-        assert_eq!(Balances::reserved_balance(USER_2), DEFAULT_GAS as u128);
+        assert_eq!(bank_balance(), 2 * DEFAULT_GAS as u128);
 
         // Check if task and message got processed before start of block `bn`.
         // But due to the low gas allowance, we may process the only first task.
@@ -283,15 +310,15 @@ fn gear_handles_outdated_tasks() {
             assert!(!out_of_rent_reply_exists(USER_1, mid1, pid1));
             assert!(out_of_rent_reply_exists(USER_2, mid2, pid2));
             assert_eq!(Balances::free_balance(USER_2), user2_balance - cost1);
-            assert_eq!(Balances::reserved_balance(USER_2), 0);
         } else {
             assert!(task_and_wl_message_exist(mid2, pid2, bn));
             assert!(out_of_rent_reply_exists(USER_1, mid1, pid1));
             assert!(!out_of_rent_reply_exists(USER_2, mid2, pid2));
             assert_eq!(Balances::free_balance(USER_1), user1_balance - cost1);
-            assert_eq!(Balances::reserved_balance(USER_1), 0);
         }
 
+        assert_eq!(bank_balance(), DEFAULT_GAS as u128);
+
         assert_eq!(
             Balances::free_balance(BLOCK_AUTHOR),
             block_author_balance + cost1
@@ -318,7 +345,79 @@ fn gear_handles_outdated_tasks() {
             Balances::free_balance(USER_1) + Balances::free_balance(USER_2),
             user1_balance + user2_balance - cost1 - cost2
         );
-        assert_eq!(Balances::reserved_balance(USER_1), 0);
-        assert_eq!(Balances::reserved_balance(USER_2), 0);
+        assert_eq!(bank_balance(), 0);
+    });
+}
+
+#[test]
+fn gear_runs_to_next_task() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        run_to_block(2, Some(u64::MAX));
+
+        // No tasks scheduled, so there is nothing to reach.
+        assert_eq!(run_to_next_task(Some(u64::MAX)), None);
+        assert_eq!(SystemPallet::<Test>::block_number(), 2);
+
+        // Appending tasks and messages to mailbox.
+        let bn1 = 5;
+        let (mid1, _pid) = populate_mailbox_of(USER_1, bn1);
+        let bn2 = 10;
+        let (mid2, _pid) = populate_mailbox_of(USER_2, bn2);
+
+        // Only tasks of the reached block are processed.
+        assert_eq!(run_to_next_task(Some(u64::MAX)), Some(bn1));
+        assert_eq!(SystemPallet::<Test>::block_number(), bn1);
+        assert!(!MailboxOf::<Test>::contains(&USER_1, &mid1));
+        assert!(MailboxOf::<Test>::contains(&USER_2, &mid2));
+
+        assert_eq!(run_to_next_task(Some(u64::MAX)), Some(bn2));
+        assert!(!MailboxOf::<Test>::contains(&USER_2, &mid2));
+
+        assert_eq!(run_to_next_task(Some(u64::MAX)), None);
+    });
+}
+
+#[test]
+fn gear_replies_on_mailbox_expiry() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        run_to_block(2, Some(u64::MAX));
+
+        // Appending task and message to mailbox.
+        let bn = 5;
+        let (mid, _pid) = populate_mailbox_of(USER_1, bn);
+        assert!(MailboxOf::<Test>::contains(&USER_1, &mid));
+
+        // Check if message is still in mailbox before block `bn`.
+        run_to_block(bn - 1, Some(u64::MAX));
+        assert!(MailboxOf::<Test>::contains(&USER_1, &mid));
+
+        // Check if task got processed in block `bn`.
+        run_to_block(bn, Some(u64::MAX));
+        assert!(!MailboxOf::<Test>::contains(&USER_1, &mid));
+        assert!(!TaskPoolOf::<Test>::contains(
+            &bn,
+            &ScheduledTask::RemoveFromMailbox(USER_1, mid)
+        ));
+
+        let reply_id = MessageId::generate_reply(mid, core_processor::ERR_EXIT_CODE);
+
+        let events = SystemPallet::<Test>::events();
+
+        assert!(events.iter().any(|e| matches!(
+            &e.event,
+            mock::Event::Gear(pallet_gear::Event::UserMessageRead { id, .. }) if *id == mid
+        )));
+
+        // Not consumed reply was enqueued on behalf of user and processed.
+        assert!(events.iter().any(|e| matches!(
+            &e.event,
+            mock::Event::Gear(pallet_gear::Event::MessagesDispatched { statuses, .. })
+                if statuses.contains_key(&reply_id)
+        )));
+
+        // Gas passed to the reply is returned after its processing.
+        assert_eq!(bank_balance(), 0);
     });
 }
@@ -18,7 +18,11 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-pub use pallet_gear::{manager::HandleKind, GasInfo};
+use codec::Codec;
+pub use pallet_gear::{
+    manager::HandleKind, BlockRunStats, GasInfo, HoldKind, ScheduleInfo, StateInfo,
+    GAS_CALCULATION_SALT,
+};
 use sp_core::H256;
 use sp_std::vec::Vec;
 
@@ -26,5 +30,50 @@ sp_api::decl_runtime_apis! {
     pub trait GearApi {
         #[allow(clippy::too_many_arguments)]
         fn calculate_gas_info(source: H256, kind: HandleKind, payload: Vec<u8>, value: u128, allow_other_panics: bool, initial_gas: Option<u64>,) -> Result<GasInfo, Vec<u8>>;
+
+        /// Returns statistics of the queue processing within the block.
+        fn block_run_stats() -> BlockRunStats;
+
+        /// Returns scale-encoded state of the program along with id of its type.
+        fn read_state(program_id: H256, query: Vec<u8>) -> Result<StateInfo, Vec<u8>>;
+
+        /// Returns state of the program, encoded by `meta_state` function of its metadata binary.
+        fn read_meta_state(program_id: H256, meta_wasm: Vec<u8>, payload: Vec<u8>) -> Result<Vec<u8>, Vec<u8>>;
+
+        /// Returns id of the program, which will be created from the code with the salt.
+        fn calculate_program_id(code_id: H256, salt: Vec<u8>) -> H256;
+
+        /// Returns whether the code with the id is saved on chain.
+        fn code_hash_exists(code_id: H256) -> bool;
+
+        /// Returns the code with the id as it was uploaded, before instrumentation.
+        fn original_code(code_id: H256) -> Option<Vec<u8>>;
+
+        /// Returns id, which will be assigned to the next message sent by the user within the current block.
+        fn next_user_message_id(account_id: H256) -> H256;
+
+        /// Returns amount of dispatches in the message queue.
+        fn queue_len() -> u32;
+
+        /// Returns amount of messages in the waitlist of the program.
+        fn waitlist_len(program_id: H256) -> u32;
+
+        /// Returns amount of messages in the mailbox of the user.
+        fn mailbox_len(account_id: H256) -> u32;
+
+        /// Returns storage keys of the program record and of its pages with data within `[from_page, to_page)`.
+        fn program_state_keys(program_id: H256, from_page: u32, to_page: u32) -> Vec<Vec<u8>>;
+
+        /// Returns amount of blocks, which holding in the storage of the kind is paid for by the gas.
+        fn hold_duration(gas: u64, kind: HoldKind) -> u32;
+
+        /// Returns gas required for holding in the storage of the kind for the amount of blocks.
+        fn hold_cost(duration: u32, kind: HoldKind) -> u64;
+    }
+
+    /// Provides the schedule of the runtime, so that tooling could mirror it.
+    pub trait GearScheduleApi<Info> where Info: Codec {
+        /// Returns the schedule and other constants used for processing of messages.
+        fn schedule_info() -> Info;
     }
 }
@@ -28,7 +28,9 @@ use jsonrpsee::{
     types::error::{CallError, ErrorObject},
 };
 pub use pallet_gear_rpc_runtime_api::GearApi as GearRuntimeApi;
-use pallet_gear_rpc_runtime_api::{GasInfo, HandleKind};
+use pallet_gear_rpc_runtime_api::{GasInfo, HandleKind, StateInfo};
+use sc_client_api::ProofProvider;
+use sc_rpc_api::state::ReadProof;
 use sp_api::{ApiError, ApiRef, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_core::{Bytes, H256};
@@ -80,6 +82,59 @@ pub trait GearApi<BlockHash, ResponseType> {
         allow_other_panics: bool,
         at: Option<BlockHash>,
     ) -> RpcResult<GasInfo>;
+
+    #[method(name = "gear_readState")]
+    fn read_state(
+        &self,
+        program_id: H256,
+        query: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<StateInfo>;
+
+    #[method(name = "gear_readMetaState")]
+    fn read_meta_state(
+        &self,
+        program_id: H256,
+        meta_wasm: Bytes,
+        payload: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Bytes>;
+
+    #[method(name = "gear_calculateProgramId")]
+    fn calculate_program_id(
+        &self,
+        code_id: H256,
+        salt: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<H256>;
+
+    #[method(name = "gear_codeHashExists")]
+    fn code_hash_exists(&self, code_id: H256, at: Option<BlockHash>) -> RpcResult<bool>;
+
+    #[method(name = "gear_originalCode")]
+    fn original_code(&self, code_id: H256, at: Option<BlockHash>) -> RpcResult<Option<Bytes>>;
+
+    #[method(name = "gear_nextUserMessageId")]
+    fn next_user_message_id(&self, account_id: H256, at: Option<BlockHash>) -> RpcResult<H256>;
+
+    #[method(name = "gear_queueLen")]
+    fn queue_len(&self, at: Option<BlockHash>) -> RpcResult<u32>;
+
+    #[method(name = "gear_waitlistLen")]
+    fn waitlist_len(&self, program_id: H256, at: Option<BlockHash>) -> RpcResult<u32>;
+
+    #[method(name = "gear_mailboxLen")]
+    fn mailbox_len(&self, account_id: H256, at: Option<BlockHash>) -> RpcResult<u32>;
+
+    /// Returns proof of the program record and of its pages with data
+    /// within the half-open `page_range`, see `gear-program-proof` crate.
+    #[method(name = "gear_programStateProof")]
+    fn program_state_proof(
+        &self,
+        program_id: H256,
+        page_range: (u32, u32),
+        at: Option<BlockHash>,
+    ) -> RpcResult<ReadProof<BlockHash>>;
 }
 
 /// A struct that implements the [`GearApi`].
@@ -141,7 +196,7 @@ impl From<Error> for i64 {
 impl<C, Block> GearApiServer<<Block as BlockT>::Hash, Result<u64, Vec<u8>>> for Gear<C, Block>
 where
     Block: BlockT,
-    C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + ProofProvider<Block>,
     C::Api: GearRuntimeApi<Block>,
 {
     fn get_init_gas_spent(
@@ -255,4 +310,159 @@ where
             )
         })
     }
+
+    fn read_state(
+        &self,
+        program_id: H256,
+        query: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<StateInfo> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.run_with_api_copy(|api| api.read_state(&at, program_id, query.to_vec()))
+    }
+
+    fn read_meta_state(
+        &self,
+        program_id: H256,
+        meta_wasm: Bytes,
+        payload: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Bytes> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.run_with_api_copy(|api| {
+            api.read_meta_state(&at, program_id, meta_wasm.to_vec(), payload.to_vec())
+        })
+        .map(Bytes)
+    }
+
+    fn calculate_program_id(
+        &self,
+        code_id: H256,
+        salt: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<H256> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .calculate_program_id(&at, code_id, salt.to_vec())
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn code_hash_exists(
+        &self,
+        code_id: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<bool> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .code_hash_exists(&at, code_id)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn original_code(
+        &self,
+        code_id: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<Bytes>> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .original_code(&at, code_id)
+            .map(|code| code.map(Bytes))
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn next_user_message_id(
+        &self,
+        account_id: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<H256> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .next_user_message_id(&at, account_id)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn queue_len(&self, at: Option<<Block as BlockT>::Hash>) -> RpcResult<u32> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .queue_len(&at)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn waitlist_len(
+        &self,
+        program_id: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<u32> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .waitlist_len(&at, program_id)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn mailbox_len(&self, account_id: H256, at: Option<<Block as BlockT>::Hash>) -> RpcResult<u32> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .mailbox_len(&at, account_id)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn program_state_proof(
+        &self,
+        program_id: H256,
+        page_range: (u32, u32),
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<ReadProof<<Block as BlockT>::Hash>> {
+        // If the block hash is not supplied assume the best block.
+        let block = at.unwrap_or_else(|| self.client.info().best_hash);
+        let at = BlockId::hash(block);
+        let (from_page, to_page) = page_range;
+
+        let keys = self
+            .client
+            .runtime_api()
+            .program_state_keys(&at, program_id, from_page, to_page)
+            .map_err(runtime_error_into_rpc_error)?;
+
+        self.client
+            .read_proof(&at, &mut keys.iter().map(|key| key.as_slice()))
+            .map(|proof| ReadProof {
+                at: block,
+                proof: proof.into_iter_nodes().map(Bytes).collect(),
+            })
+            .map_err(runtime_error_into_rpc_error)
+    }
 }
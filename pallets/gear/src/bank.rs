@@ -0,0 +1,264 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bank, holding funds for gas and value of messages in flight.
+//!
+//! Funds are moved from the sender's account to the pallet-owned bank account
+//! on message submission instead of being reserved on the sender's account.
+//! Spent gas is paid to the block author out of the bank, while gas left and
+//! value are transferred from it to their receivers. So the funds are always
+//! moved by transfers of exact amounts, which don't depend on reserves of
+//! other pallets and don't leave any leftovers.
+//!
+//! Funds, which couldn't be paid to their receivers, are accounted as dust
+//! and routed to `Config::DustBeneficiary` in the beginning of the next block.
+
+use crate::{
+    BalanceOf, Config, DispatchStashOf, Dust, Event, GasHandlerOf, GearProgramPallet, MailboxOf,
+    Pallet, QueueOf, WaitlistOf,
+};
+use common::{storage::*, GasPrice, GasTree, Origin};
+use frame_support::{
+    traits::{Currency, ExistenceRequirement, Get, ReservableCurrency},
+    weights::Weight,
+    PalletId,
+};
+use gear_core::message::{DispatchKind, StoredDispatch};
+use sp_runtime::{
+    traits::{AccountIdConversion, Saturating, UniqueSaturatedInto, Zero},
+    DispatchError, DispatchResult,
+};
+use sp_std::marker::PhantomData;
+
+/// Id of the pallet, from which the bank account is derived.
+const BANK_ID: PalletId = PalletId(*b"py/gbank");
+
+/// Bank of the pallet, holding funds for gas and value.
+pub struct GearBank<T>(PhantomData<T>);
+
+impl<T: Config> GearBank<T>
+where
+    T::AccountId: Origin,
+{
+    /// Returns the bank account.
+    pub fn account() -> T::AccountId {
+        BANK_ID.into_account_truncating()
+    }
+
+    /// Provides the bank account, if it isn't provided yet.
+    ///
+    /// Existential deposit of the account is paid once by `funder` and
+    /// held in reserve, so the total balance of the bank never drops below
+    /// the minimum, whatever amount of funds it holds.
+    pub(crate) fn provide_account(funder: &T::AccountId) -> Result<T::AccountId, DispatchError> {
+        let account = Self::account();
+
+        if !frame_system::Pallet::<T>::account_exists(&account) {
+            let existential_deposit = <T as Config>::Currency::minimum_balance();
+
+            <T as Config>::Currency::transfer(
+                funder,
+                &account,
+                existential_deposit,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            <T as Config>::Currency::reserve(&account, existential_deposit)?;
+        }
+
+        Ok(account)
+    }
+
+    /// Moves funds for `gas` and `value` from `who` to the bank.
+    ///
+    /// Account of `who` is kept alive, so gas left could be refunded to it.
+    pub fn deposit(who: &T::AccountId, gas: u64, value: BalanceOf<T>) -> DispatchResult {
+        Self::deposit_impl(
+            who,
+            T::GasPrice::gas_price(gas).saturating_add(value),
+            ExistenceRequirement::KeepAlive,
+        )
+    }
+
+    /// Moves `value`, sent by program, from its account to the bank.
+    ///
+    /// Balance of the program is checked on sending, so the value
+    /// could take all of it.
+    pub(crate) fn deposit_value(who: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+        Self::deposit_impl(who, value, ExistenceRequirement::AllowDeath)
+    }
+
+    /// Transfers funds for `amount` of gas from the bank to `to`.
+    pub fn withdraw_gas(to: &T::AccountId, amount: u64) -> DispatchResult {
+        Self::withdraw_impl(to, T::GasPrice::gas_price(amount))
+    }
+
+    /// Transfers `value` from the bank to `to`.
+    pub fn withdraw_value(to: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+        Self::withdraw_impl(to, value)
+    }
+
+    /// Pays for `amount` of spent gas to the block author.
+    ///
+    /// Funds are accounted as dust, if there is no one to pay to.
+    pub(crate) fn spend_gas(amount: u64) {
+        let paid = match Pallet::<T>::block_author() {
+            Some(author) => Self::withdraw_gas(&author, amount).map_err(|e| {
+                log::debug!(
+                    target: "essential",
+                    "Failed to pay for {:?} of gas to {:?}: {:?}",
+                    amount,
+                    author,
+                    e,
+                )
+            }),
+            None => Err(()),
+        };
+
+        if paid.is_err() {
+            Self::add_dust(T::GasPrice::gas_price(amount));
+        }
+    }
+
+    /// Accounts `amount` held by the bank as dust.
+    pub(crate) fn add_dust(amount: BalanceOf<T>) {
+        if !amount.is_zero() {
+            Dust::<T>::mutate(|dust| *dust = dust.saturating_add(amount));
+        }
+    }
+
+    /// Transfers accumulated dust to `Config::DustBeneficiary`, if any.
+    ///
+    /// Dust is kept accumulating, while the transfer fails, e.g. if it's
+    /// not enough to create an account of the beneficiary.
+    pub(crate) fn route_dust() -> Weight {
+        let dust = Dust::<T>::get();
+
+        let beneficiary = match T::DustBeneficiary::get() {
+            Some(beneficiary) if !dust.is_zero() => beneficiary,
+            _ => return T::DbWeight::get().reads(1),
+        };
+
+        match Self::withdraw_impl(&beneficiary, dust) {
+            Ok(_) => {
+                Dust::<T>::kill();
+
+                Pallet::<T>::deposit_event(Event::DustRouted {
+                    beneficiary,
+                    amount: dust,
+                });
+            }
+            Err(e) => log::debug!(
+                "Failed to route dust {:?} to {:?}: {:?}",
+                dust,
+                beneficiary,
+                e
+            ),
+        }
+
+        T::DbWeight::get().reads_writes(3, 3)
+    }
+
+    /// Checks if value of the dispatch wasn't transferred to its destination yet.
+    ///
+    /// Value is transferred on the first execution of the dispatch, except for
+    /// init dispatches sent by programs, which value is held till the end of
+    /// the initialization.
+    pub(crate) fn holds_value(dispatch: &StoredDispatch) -> bool {
+        dispatch.value() != 0
+            && (dispatch.context().is_none()
+                || dispatch.kind() == DispatchKind::Init
+                    && GearProgramPallet::<T>::program_exists(dispatch.source()))
+    }
+
+    /// Checks that the bank holds funds for the whole gas supply, values of
+    /// messages in flight and dust.
+    ///
+    /// Anyone could transfer funds to the bank account, so only the lack
+    /// of funds is treated as an error.
+    pub fn check_holdings() -> Result<(), &'static str> {
+        let mut expected = T::GasPrice::gas_price(GasHandlerOf::<T>::total_supply())
+            .saturating_add(Dust::<T>::get());
+
+        let mut add_value = |value: u128| {
+            expected = expected.saturating_add(value.unique_saturated_into());
+        };
+
+        QueueOf::<T>::iter()
+            .filter_map(Result::ok)
+            .filter(Self::holds_value)
+            .for_each(|dispatch| add_value(dispatch.value()));
+
+        WaitlistOf::<T>::iter()
+            .filter(|(dispatch, _)| Self::holds_value(dispatch))
+            .for_each(|(dispatch, _)| add_value(dispatch.value()));
+
+        DispatchStashOf::<T>::iter()
+            .filter(|(dispatch, _)| Self::holds_value(dispatch))
+            .for_each(|(dispatch, _)| add_value(dispatch.value()));
+
+        MailboxOf::<T>::iter().for_each(|message| add_value(message.value()));
+
+        let holdings = <T as Config>::Currency::free_balance(&Self::account());
+
+        if holdings < expected {
+            log::error!(
+                "Bank holds {:?}, while {:?} is expected to be held",
+                holdings,
+                expected
+            );
+            return Err("Bank doesn't hold enough funds");
+        }
+
+        if holdings > expected {
+            log::warn!(
+                "Bank holds {:?} above {:?} expected",
+                holdings.saturating_sub(expected),
+                expected
+            );
+        }
+
+        Ok(())
+    }
+
+    fn deposit_impl(
+        who: &T::AccountId,
+        amount: BalanceOf<T>,
+        existence_requirement: ExistenceRequirement,
+    ) -> DispatchResult {
+        if amount.is_zero() {
+            return Ok(());
+        }
+
+        let bank = Self::provide_account(who)?;
+
+        <T as Config>::Currency::transfer(who, &bank, amount, existence_requirement)
+    }
+
+    fn withdraw_impl(to: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+        if amount.is_zero() {
+            return Ok(());
+        }
+
+        <T as Config>::Currency::transfer(
+            &Self::account(),
+            to,
+            amount,
+            ExistenceRequirement::KeepAlive,
+        )
+    }
+}
@@ -42,13 +42,16 @@ use core_processor::configs::{AllocationsConfig, BlockConfig, BlockInfo, Message
 use frame_benchmarking::{benchmarks, whitelisted_caller};
 use frame_support::traits::{Currency, Get};
 use frame_system::RawOrigin;
-use gear_core::ids::{MessageId, ProgramId};
+use gear_core::{
+    ids::{MessageId, ProgramId, ReservationId},
+    message::{ReplyDetails, MAX_EVENT_PAYLOAD_SIZE, MAX_EVENT_TOPICS},
+};
 use sp_core::H256;
 use sp_runtime::{
     traits::{Bounded, UniqueSaturatedInto},
     Perbill,
 };
-use sp_std::prelude::*;
+use sp_std::{convert::TryInto, prelude::*};
 use wasm_instrument::parity_wasm::elements::{BlockType, BrTableData, Instruction, ValueType};
 
 const MAX_PAYLOAD_LEN: u32 = 64 * 1024;
@@ -143,9 +146,10 @@ where
         "Suppose to run benches only with lazy pages"
     );
 
-    let ext_manager = ExtManager::<T>::default();
+    let mut ext_manager = ExtManager::<T>::default();
     let bn: u64 = <frame_system::Pallet<T>>::block_number().unique_saturated_into();
     let root_message_id = MessageId::from(bn);
+    let payload = payload.try_into()?;
 
     let dispatch = match kind {
         HandleKind::Init(ref code) => {
@@ -203,7 +207,7 @@ where
                     payload,
                     Some(u64::MAX),
                     value,
-                    Some((msg.id(), exit_code)),
+                    Some(ReplyDetails::new(msg.id(), exit_code)),
                 ),
             )
         }
@@ -225,7 +229,7 @@ where
         timestamp: <pallet_timestamp::Pallet<T>>::get().unique_saturated_into(),
     };
 
-    let existential_deposit = <T as Config>::Currency::minimum_balance().unique_saturated_into();
+    let existential_deposit = Gear::<T>::program_existential_deposit();
     let mailbox_threshold = <T as Config>::MailboxThreshold::get();
 
     let block_config = BlockConfig {
@@ -236,12 +240,18 @@ where
             alloc_cost: T::Schedule::get().memory_weights.allocation_cost,
             mem_grow_cost: T::Schedule::get().memory_weights.grow_cost,
             load_page_cost: T::Schedule::get().memory_weights.load_cost,
+            page_read_cost: T::Schedule::get().memory_weights.page_read_cost,
+            page_write_cost: T::Schedule::get().memory_weights.page_write_cost,
         },
         existential_deposit,
         outgoing_limit: 2048,
+        outgoing_bytes_limit: <T as Config>::OutgoingBytesLimit::get(),
+        program_candidates_limit: 2048,
+        outgoing_scaling: None,
         host_fn_weights: Default::default(),
         forbidden_funcs: Default::default(),
         mailbox_threshold,
+        gas_minimums: <T as Config>::GasMinimums::get(),
     };
 
     if let Some(queued_dispatch) = QueueOf::<T>::dequeue().map_err(|_| "MQ storage corrupted")? {
@@ -290,6 +300,19 @@ benchmarks! {
         assert!(<T as pallet::Config>::CodeStorage::exists(code_id));
     }
 
+    submit_code_metadata {
+        let r in 0 .. T::Schedule::get().limits.payload_len;
+        let caller: T::AccountId = whitelisted_caller();
+        <T as pallet::Config>::Currency::make_free_balance_be(&caller, caller_funding::<T>());
+        let WasmModule { code, hash: code_id, .. } = WasmModule::<T>::dummy();
+        Gear::<T>::submit_code(RawOrigin::Signed(caller.clone()).into(), code)?;
+        let registry = vec![0u8; r as usize];
+        let origin = RawOrigin::Signed(caller);
+    }: _(origin, code_id, registry, 0)
+    verify {
+        assert!(<T as pallet::Config>::CodeStorage::get_type_metadata(code_id).is_some());
+    }
+
     // This constructs a program that is maximal expensive to instrument.
     // It creates a maximum number of metering blocks per byte.
     // The size of the salt influences the runtime because is is hashed in order to
@@ -329,6 +352,17 @@ benchmarks! {
         assert!(matches!(QueueOf::<T>::dequeue(), Ok(Some(_))));
     }
 
+    send_message_without_value {
+        let caller = benchmarking::account("caller", 0, 0);
+        <T as pallet::Config>::Currency::deposit_creating(&caller, 100_000_000_000_000_u128.unique_saturated_into());
+        let program_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin());
+        let code = benchmarking::generate_wasm2(16.into()).unwrap();
+        benchmarking::set_program(program_id.into_origin(), code, 1.into());
+    }: send_message(RawOrigin::Signed(caller), program_id, vec![], 100_000_000_u64, 0_u32.into())
+    verify {
+        assert!(matches!(QueueOf::<T>::dequeue(), Ok(Some(_))));
+    }
+
     send_reply {
         let p in 0 .. MAX_PAYLOAD_LEN;
         let caller = benchmarking::account("caller", 0, 0);
@@ -351,6 +385,30 @@ benchmarks! {
         assert!(matches!(QueueOf::<T>::dequeue(), Ok(Some(_))));
     }
 
+    top_up_gas {
+        let caller: T::AccountId = benchmarking::account("caller", 0, 0);
+        <T as pallet::Config>::Currency::deposit_creating(&caller, 100_000_000_000_000_u128.unique_saturated_into());
+        let payer: T::AccountId = benchmarking::account("payer", 0, 0);
+        <T as pallet::Config>::Currency::deposit_creating(&payer, 100_000_000_000_000_u128.unique_saturated_into());
+        let message_id = MessageId::from_origin(benchmarking::account::<T::AccountId>("message", 0, 100).into_origin());
+        let _ = GasHandlerOf::<T>::create(caller, message_id, 100_000_000_u64);
+    }: _(RawOrigin::Signed(payer), message_id, 100_000_000_u64)
+    verify {
+        assert_eq!(GasHandlerOf::<T>::get_limit(message_id).ok().flatten(), Some((200_000_000_u64, message_id)));
+    }
+
+    pay_program_rent {
+        let caller = benchmarking::account("caller", 0, 0);
+        <T as pallet::Config>::Currency::deposit_creating(&caller, 100_000_000_000_000_u128.unique_saturated_into());
+        let program_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("program", 0, 100).into_origin());
+        let code = benchmarking::generate_wasm2(16.into()).unwrap();
+        benchmarking::set_program(program_id.into_origin(), code, 1.into());
+        Gear::<T>::set_program_expiration(program_id, 1_u32.into());
+    }: _(RawOrigin::Signed(caller), program_id, 100_u32.into())
+    verify {
+        assert_eq!(Gear::<T>::program_expiration(program_id), Some(101_u32.into()));
+    }
+
     initial_allocation {
         let q in 1 .. MAX_PAGES;
         let caller: T::AccountId = benchmarking::account("caller", 0, 0);
@@ -433,38 +491,43 @@ benchmarks! {
         >(&block_config, message_execution_context);
     }
 
-    // TODO: benchmark batches and size is bigger than memory limits
-    // free {
-    //     let r in 0 .. API_BENCHMARK_BATCHES;
-    //     let code = WasmModule::<T>::from(ModuleDefinition {
-    //         memory: Some(ImportedMemory::max::<T>()),
-    //         imported_functions: vec![ImportedFunction {
-    //             module: "env",
-    //             name: "alloc",
-    //             params: vec![ValueType::I32],
-    //             return_type: Some(ValueType::I32),
-    //         },
-    //         ImportedFunction {
-    //             module: "env",
-    //             name: "free",
-    //             params: vec![ValueType::I32],
-    //             return_type: None,
-    //         }],
-    //         init_body: Some(body::plain(vec![
-    //             Instruction::I32Const(1),
-    //             Instruction::Call(0),
-    //             Instruction::Drop,
-    //         ])),
-    //         handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-    //             Instruction::I32Const(1),
-    //             Instruction::Call(0),
-    //         ])),
-    //         .. Default::default()
-    //     });
-    //     let instance = Program::<T>::new(code, vec![])?;
-    // }: {
-    //     Gear::<T>::process_message(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
-    // }
+    // Each `free` is preceded by `alloc` of the same page, so the memory
+    // doesn't run out, while the cost of `alloc` is subtracted in the schedule.
+    free {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory { min_pages: 1 }),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "alloc",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            },
+            ImportedFunction {
+                module: "env",
+                name: "free",
+                params: vec![ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(1), // pages
+                Instruction::Call(0), // alloc
+                Instruction::Call(1), // free allocated page
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+    }
 
     gas {
         let r in 0 .. API_BENCHMARK_BATCHES;
@@ -914,6 +977,130 @@ benchmarks! {
         core_processor::handle_journal(journal, &mut ext_manager);
     }
 
+    gr_send_push_input {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send_init",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            },
+            ImportedFunction {
+                module: "env",
+                name: "gr_send_push_input",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // handle ptr
+                Instruction::Call(0), // get handle
+                Instruction::Drop,
+                Instruction::I32Const(0), // handle
+                Instruction::I32Const(0), // input offset
+                Instruction::I32Const(0), // input len
+                Instruction::Call(1), // send_push_input
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_send_push_input_per_kb {
+        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send_init",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            },
+            ImportedFunction {
+                module: "env",
+                name: "gr_send_push_input",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // handle ptr
+                Instruction::Call(0), // get handle
+                Instruction::Drop,
+                Instruction::I32Const(0), // handle
+                Instruction::I32Const(0), // input offset
+                Instruction::I32Const((n * 1024) as i32), // input len
+                Instruction::Call(1), // send_push_input
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![0u8; (n * 1024) as usize], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_send_cancel {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send_init",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            },
+            ImportedFunction {
+                module: "env",
+                name: "gr_send_cancel",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // handle ptr
+                Instruction::Call(0), // get handle
+                Instruction::Drop,
+                Instruction::I32Const(0), // handle, released one is taken again
+                Instruction::Call(1), // send_cancel
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
     // Benchmark the `gr_send_commit` call.
     // `gr_send` call is shortcut for `gr_send_init` + `gr_send_commit`
     gr_send_commit {
@@ -1184,6 +1371,69 @@ benchmarks! {
         >(&block_config, message_execution_context);
     }
 
+    // Benchmark the `gr_reply_push_input` call.
+    gr_reply_push_input {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reply_push_input",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // input offset
+                Instruction::I32Const(0), // input len
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+    }
+
+    gr_reply_push_input_per_kb {
+        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reply_push_input",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // input offset
+                Instruction::I32Const((n * 1024) as i32), // input len
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![0u8; (n * 1024) as usize], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+    }
+
     gr_reply_to {
         let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
@@ -1202,7 +1452,7 @@ benchmarks! {
         });
         let instance = Program::<T>::new(code, vec![])?;
         let msg_id = MessageId::from(10);
-        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), vec![], Some(1_000_000), 0, None).into_stored();
+        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), Default::default(), Some(1_000_000), 0, None).into_stored();
         MailboxOf::<T>::insert(msg).expect("Error during mailbox insertion");
         let Exec {
             mut ext_manager,
@@ -1266,7 +1516,7 @@ benchmarks! {
         });
         let instance = Program::<T>::new(code, vec![])?;
         let msg_id = MessageId::from(10);
-        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), vec![], Some(1_000_000), 0, None).into_stored();
+        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), Default::default(), Some(1_000_000), 0, None).into_stored();
         MailboxOf::<T>::insert(msg).expect("Error during mailbox insertion");
         let Exec {
             mut ext_manager,
@@ -1281,6 +1531,54 @@ benchmarks! {
         core_processor::handle_journal(journal, &mut ext_manager);
     }
 
+    gr_error {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        // Pushing to the handle, which wasn't initialized, fails,
+        // so there is an error to be read by `gr_error`.
+        let mut handle_body = vec![
+            Instruction::I32Const(0), // handle
+            Instruction::I32Const(0), // payload ptr
+            Instruction::I32Const(0), // payload len
+            Instruction::Call(0), // send_push
+            Instruction::Drop,
+        ];
+        for _ in 0 .. r * API_BENCHMARK_BATCH_SIZE {
+            handle_body.extend([
+                Instruction::I32Const(0), // error ptr
+                Instruction::Call(1),
+            ]);
+        }
+        handle_body.push(Instruction::End);
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send_push",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            },
+            ImportedFunction {
+                module: "env",
+                name: "gr_error",
+                params: vec![ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::plain(handle_body)),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+    }
+
     // We cannot call `gr_exit` multiple times. Therefore our weight determination is not
     // as precise as with other APIs.
     gr_exit {
@@ -1383,21 +1681,85 @@ benchmarks! {
         core_processor::handle_journal(journal, &mut ext_manager);
     }
 
-    gr_wake {
-        let r in 0 .. API_BENCHMARK_BATCHES;
-        let message_ids = (0..r * API_BENCHMARK_BATCH_SIZE)
-            .map(|i| gear_core::ids::MessageId::from(i as u64))
-            .collect::<Vec<_>>();
-        let message_id_len = message_ids.get(0).map(|i| i.encode().len()).unwrap_or(0);
-        let message_id_bytes = message_ids.iter().flat_map(|x| x.encode()).collect();
+    // We cannot call `gr_wait_for` multiple times. Therefore our weight determination is not
+    // as precise as with other APIs.
+    gr_wait_for {
+        let r in 0 .. 1;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_wake",
+                name: "gr_wait_for",
+                params: vec![ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r, &[
+                Instruction::I32Const(10), // duration
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    // We cannot call `gr_wait_up_to` multiple times. Therefore our weight determination is not
+    // as precise as with other APIs.
+    gr_wait_up_to {
+        let r in 0 .. 1;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_wait_up_to",
                 params: vec![ValueType::I32],
                 return_type: None,
             }],
+            handle_body: Some(body::repeated(r, &[
+                Instruction::I32Const(10), // duration
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_wake {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let message_ids = (0..r * API_BENCHMARK_BATCH_SIZE)
+            .map(|i| gear_core::ids::MessageId::from(i as u64))
+            .collect::<Vec<_>>();
+        let message_id_len = message_ids.get(0).map(|i| i.encode().len()).unwrap_or(0);
+        let message_id_bytes = message_ids.iter().flat_map(|x| x.encode()).collect();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_wake",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
             data_segments: vec![
                 DataSegment {
                     offset: 0_u32,
@@ -1407,12 +1769,13 @@ benchmarks! {
             handle_body: Some(body::repeated_dyn(r * API_BENCHMARK_BATCH_SIZE, vec![
                 Counter(0_u32, message_id_len as u32), // message_id_ptr
                 Regular(Instruction::Call(0)),
+                Regular(Instruction::Drop),
             ])),
             .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
         for message_id in message_ids {
-            let message = gear_core::message::Message::new(message_id, 1.into(), ProgramId::from(instance.addr.as_bytes()), vec![], Some(1_000_000), 0, None);
+            let message = gear_core::message::Message::new(message_id, 1.into(), ProgramId::from(instance.addr.as_bytes()), Default::default(), Some(1_000_000), 0, None);
             let dispatch = gear_core::message::Dispatch::new(gear_core::message::DispatchKind::Handle, message).into_stored();
             WaitlistOf::<T>::insert(dispatch.clone()).expect("Duplicate wl message");
         }
@@ -1438,6 +1801,8 @@ benchmarks! {
         let salt_bytes_len = salt_bytes.len();
         let value_bytes = 0_u128.encode();
         let value_bytes_len = value_bytes.len();
+        let mid_bytes = MessageId::from(101).encode();
+        let mid_bytes_len = mid_bytes.len();
         let pid_bytes = ProgramId::from(101).encode();
         let _ = Gear::<T>::submit_code_raw(RawOrigin::Signed(benchmarking::account("instantiator", 0, 0)).into(), module.code);
         let code = WasmModule::<T>::from(ModuleDefinition {
@@ -1445,7 +1810,7 @@ benchmarks! {
             imported_functions: vec![ImportedFunction {
                 module: "env",
                 name: "gr_create_program_wgas",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32],
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32, ValueType::I32],
                 return_type: None,
             }],
             data_segments: vec![
@@ -1463,6 +1828,10 @@ benchmarks! {
                 },
                 DataSegment {
                     offset: (value_bytes_len + salt_bytes_len + code_hash_len) as u32,
+                    value: mid_bytes,
+                },
+                DataSegment {
+                    offset: (mid_bytes_len + value_bytes_len + salt_bytes_len + code_hash_len) as u32,
                     value: pid_bytes,
                 },
             ],
@@ -1475,6 +1844,7 @@ benchmarks! {
                 Regular(Instruction::I64Const(100000000)),
                 Regular(Instruction::I32Const((salt_bytes_len + code_hash_len) as i32)),
                 Regular(Instruction::I32Const((value_bytes_len + salt_bytes_len + code_hash_len) as i32)),
+                Regular(Instruction::I32Const((mid_bytes_len + value_bytes_len + salt_bytes_len + code_hash_len) as i32)),
                 Regular(Instruction::Call(0)),
             ])),
             .. Default::default()
@@ -1503,6 +1873,8 @@ benchmarks! {
         let salt_bytes_len = salt_bytes.len();
         let value_bytes = 0_u128.encode();
         let value_bytes_len = value_bytes.len();
+        let mid_bytes = MessageId::from(101).encode();
+        let mid_bytes_len = mid_bytes.len();
         let pid_bytes = ProgramId::from(101).encode();
         let _ = Gear::<T>::submit_code_raw(RawOrigin::Signed(benchmarking::account("instantiator", 0, 0)).into(), module.code);
         let code = WasmModule::<T>::from(ModuleDefinition {
@@ -1510,7 +1882,7 @@ benchmarks! {
             imported_functions: vec![ImportedFunction {
                 module: "env",
                 name: "gr_create_program_wgas",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32],
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32, ValueType::I32],
                 return_type: None,
             }],
             data_segments: vec![
@@ -1528,6 +1900,10 @@ benchmarks! {
                 },
                 DataSegment {
                     offset: (value_bytes_len + salt_bytes_len + code_hash_len) as u32,
+                    value: mid_bytes,
+                },
+                DataSegment {
+                    offset: (mid_bytes_len + value_bytes_len + salt_bytes_len + code_hash_len) as u32,
                     value: pid_bytes,
                 },
             ],
@@ -1540,6 +1916,7 @@ benchmarks! {
                 Regular(Instruction::I64Const(100000000)),
                 Regular(Instruction::I32Const((salt_bytes_len + code_hash_len) as i32)),
                 Regular(Instruction::I32Const((value_bytes_len + salt_bytes_len + code_hash_len) as i32)),
+                Regular(Instruction::I32Const((mid_bytes_len + value_bytes_len + salt_bytes_len + code_hash_len) as i32)),
                 Regular(Instruction::Call(0)),
             ])),
             .. Default::default()
@@ -1559,6 +1936,278 @@ benchmarks! {
 
     }
 
+    gr_reserve_gas {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reserve_gas",
+                params: vec![ValueType::I64, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I64Const(1_000), // amount
+                Instruction::I32Const(10), // duration
+                Instruction::I32Const(0), // id_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_unreserve_gas {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let id_len = ReservationId::default().encode().len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![
+                ImportedFunction {
+                    module: "env",
+                    name: "gr_reserve_gas",
+                    params: vec![ValueType::I64, ValueType::I32, ValueType::I32],
+                    return_type: Some(ValueType::I32),
+                },
+                ImportedFunction {
+                    module: "env",
+                    name: "gr_unreserve_gas",
+                    params: vec![ValueType::I32, ValueType::I32],
+                    return_type: Some(ValueType::I32),
+                },
+            ],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I64Const(1_000), // amount
+                Instruction::I32Const(10), // duration
+                Instruction::I32Const(0), // id_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+                Instruction::I32Const(0), // id_ptr
+                Instruction::I32Const(id_len as i32), // amount_ptr
+                Instruction::Call(1),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_reservation_send {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let id_len = ReservationId::default().encode().len();
+        let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
+        let pid_bytes = instance.addr.encode();
+        let pid_len = pid_bytes.len();
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![
+                ImportedFunction {
+                    module: "env",
+                    name: "gr_reserve_gas",
+                    params: vec![ValueType::I64, ValueType::I32, ValueType::I32],
+                    return_type: Some(ValueType::I32),
+                },
+                ImportedFunction {
+                    module: "env",
+                    name: "gr_reservation_send",
+                    params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
+                    return_type: Some(ValueType::I32),
+                },
+            ],
+            data_segments: vec![
+                DataSegment {
+                    offset: id_len as u32,
+                    value: pid_bytes,
+                },
+                DataSegment {
+                    offset: (id_len + pid_len) as u32,
+                    value: value_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I64Const(1_000), // amount
+                Instruction::I32Const(10), // duration
+                Instruction::I32Const(0), // id_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+                Instruction::I32Const(0), // reservation_id_ptr
+                Instruction::I32Const(id_len as i32), // program_id_ptr
+                Instruction::I32Const(0), // payload_ptr
+                Instruction::I32Const(0), // payload_len
+                Instruction::I32Const((id_len + pid_len) as i32), // value_ptr
+                Instruction::I32Const((id_len + pid_len + value_len) as i32), // message_id_ptr
+                Instruction::Call(1),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_system_reserve_gas {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_system_reserve_gas",
+                params: vec![ValueType::I64],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I64Const(1_000), // amount
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_metric_increment {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_metric_increment",
+                params: vec![ValueType::I32, ValueType::I64],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // key_ptr
+                Instruction::I64Const(1), // value
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_emit_event {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_emit_event",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // topics ptr
+                Instruction::I32Const(MAX_EVENT_TOPICS as i32), // topics len
+                Instruction::I32Const(0), // payload ptr
+                Instruction::I32Const(0), // payload len
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
+    gr_emit_event_per_kb {
+        let n in 0 .. (MAX_EVENT_PAYLOAD_SIZE / 1024) as u32;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_emit_event",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // topics ptr
+                Instruction::I32Const(MAX_EVENT_TOPICS as i32), // topics len
+                Instruction::I32Const(0), // payload ptr
+                Instruction::I32Const((n * 1024) as i32), // payload len
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            mut ext_manager,
+            block_config,
+            message_execution_context,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        let journal = core_processor::process::<
+            ext::LazyPagesExt,
+            SandboxEnvironment<ext::LazyPagesExt>,
+        >(&block_config, message_execution_context);
+        core_processor::handle_journal(journal, &mut ext_manager);
+    }
+
     // We make the assumption that pushing a constant and dropping a value takes roughly
     // the same amount of time. We follow that `t.load` and `drop` both have the weight
     // of this benchmark / 2. We need to make this assumption because there is no way
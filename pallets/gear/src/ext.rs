@@ -27,9 +27,9 @@ use gear_backend_common::{
 use gear_core::{
     env::Ext as EnvExt,
     gas::GasAmount,
-    ids::{MessageId, ProgramId},
+    ids::{MessageId, ProgramId, ReservationId},
     memory::{Memory, PageBuf, PageNumber, WasmPageNumber},
-    message::{HandlePacket, ReplyPacket},
+    message::{ExitCode, HandlePacket, ProgramEvent, ReplyDetails, ReplyPacket},
 };
 use gear_core_errors::{CoreError, ExtError, MemoryError};
 use sp_std::collections::btree_map::BTreeMap;
@@ -93,9 +93,12 @@ impl IntoExtInfo for LazyPagesExt {
     ) -> Result<(ExtInfo, Option<TrapExplanation>), (MemoryError, GasAmount)> {
         let ProcessorContext {
             allocations_context,
-            message_context,
+            mut message_context,
             gas_counter,
+            gas_reserver,
             program_candidates_data,
+            metrics,
+            events,
             ..
         } = self.inner.context;
 
@@ -115,17 +118,22 @@ impl IntoExtInfo for LazyPagesExt {
             accessed_pages_data.insert(page, buf);
         }
 
+        message_context.set_reservation_nonce(gas_reserver.nonce());
+
         let (outcome, context_store) = message_context.drain();
         let (generated_dispatches, awakening) = outcome.drain();
 
         let info = ExtInfo {
             gas_amount: gas_counter.into(),
+            gas_reserver,
             allocations,
             pages_data: accessed_pages_data,
             generated_dispatches,
             awakening,
             context_store,
             program_candidates_data,
+            metrics,
+            events,
         };
         let trap_explanation = self
             .inner
@@ -278,24 +286,54 @@ impl EnvExt for LazyPagesExt {
             .map_err(Error::Processor)
     }
 
+    fn send_push_input(&mut self, handle: usize, offset: u32, len: u32) -> Result<(), Self::Error> {
+        self.inner
+            .send_push_input(handle, offset, len)
+            .map_err(Error::Processor)
+    }
+
+    fn send_cancel(&mut self, handle: usize) -> Result<(), Self::Error> {
+        self.inner.send_cancel(handle).map_err(Error::Processor)
+    }
+
     fn reply_push(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
         self.inner.reply_push(buffer).map_err(Error::Processor)
     }
 
+    fn reply_push_input(&mut self, offset: u32, len: u32) -> Result<(), Self::Error> {
+        self.inner
+            .reply_push_input(offset, len)
+            .map_err(Error::Processor)
+    }
+
     fn send_commit(&mut self, handle: usize, msg: HandlePacket) -> Result<MessageId, Self::Error> {
         self.inner
             .send_commit(handle, msg)
             .map_err(Error::Processor)
     }
 
+    fn reservation_send(
+        &mut self,
+        id: ReservationId,
+        msg: HandlePacket,
+    ) -> Result<MessageId, Self::Error> {
+        self.inner
+            .reservation_send(id, msg)
+            .map_err(Error::Processor)
+    }
+
     fn reply_commit(&mut self, msg: ReplyPacket) -> Result<MessageId, Self::Error> {
         self.inner.reply_commit(msg).map_err(Error::Processor)
     }
 
-    fn reply_to(&mut self) -> Result<Option<(MessageId, i32)>, Self::Error> {
+    fn reply_to(&mut self) -> Result<Option<ReplyDetails>, Self::Error> {
         self.inner.reply_to().map_err(Error::Processor)
     }
 
+    fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error> {
+        self.inner.exit_code().map_err(Error::Processor)
+    }
+
     fn source(&mut self) -> Result<ProgramId, Self::Error> {
         self.inner.source().map_err(Error::Processor)
     }
@@ -320,8 +358,22 @@ impl EnvExt for LazyPagesExt {
         self.inner.debug(data).map_err(Error::Processor)
     }
 
-    fn msg(&mut self) -> &[u8] {
-        self.inner.msg()
+    fn metric_increment(&mut self, key: [u8; 32], value: u64) -> Result<(), Self::Error> {
+        self.inner
+            .metric_increment(key, value)
+            .map_err(Error::Processor)
+    }
+
+    fn emit_event(&mut self, event: ProgramEvent) -> Result<(), Self::Error> {
+        self.inner.emit_event(event).map_err(Error::Processor)
+    }
+
+    fn read(&mut self, at: u32, len: u32) -> Result<&[u8], Self::Error> {
+        self.inner.read(at, len).map_err(Error::Processor)
+    }
+
+    fn size(&mut self) -> Result<usize, Self::Error> {
+        self.inner.size().map_err(Error::Processor)
     }
 
     fn charge_gas(&mut self, val: u32) -> Result<(), Self::Error> {
@@ -340,6 +392,22 @@ impl EnvExt for LazyPagesExt {
         self.inner.gas_available().map_err(Error::Processor)
     }
 
+    fn reserve_gas(&mut self, amount: u64, duration: u32) -> Result<ReservationId, Self::Error> {
+        self.inner
+            .reserve_gas(amount, duration)
+            .map_err(Error::Processor)
+    }
+
+    fn unreserve_gas(&mut self, id: ReservationId) -> Result<u64, Self::Error> {
+        self.inner.unreserve_gas(id).map_err(Error::Processor)
+    }
+
+    fn system_reserve_gas(&mut self, amount: u64) -> Result<(), Self::Error> {
+        self.inner
+            .system_reserve_gas(amount)
+            .map_err(Error::Processor)
+    }
+
     fn value(&mut self) -> Result<u128, Self::Error> {
         self.inner.value().map_err(Error::Processor)
     }
@@ -352,8 +420,16 @@ impl EnvExt for LazyPagesExt {
         self.inner.wait().map_err(Error::Processor)
     }
 
-    fn wake(&mut self, waker_id: MessageId) -> Result<(), Self::Error> {
-        self.inner.wake(waker_id).map_err(Error::Processor)
+    fn wait_for(&mut self, duration: u32) -> Result<(), Self::Error> {
+        self.inner.wait_for(duration).map_err(Error::Processor)
+    }
+
+    fn wait_up_to(&mut self, duration: u32) -> Result<(), Self::Error> {
+        self.inner.wait_up_to(duration).map_err(Error::Processor)
+    }
+
+    fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Self::Error> {
+        self.inner.wake(waker_id, delay).map_err(Error::Processor)
     }
 
     fn value_available(&mut self) -> Result<u128, Self::Error> {
@@ -363,7 +439,7 @@ impl EnvExt for LazyPagesExt {
     fn create_program(
         &mut self,
         packet: gear_core::message::InitPacket,
-    ) -> Result<ProgramId, Self::Error> {
+    ) -> Result<(MessageId, ProgramId), Self::Error> {
         self.inner.create_program(packet).map_err(Error::Processor)
     }
 
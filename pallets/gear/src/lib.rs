@@ -23,9 +23,11 @@ extern crate alloc;
 
 use codec::{Decode, Encode};
 
+mod bank;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 mod ext;
+mod queue;
 mod schedule;
 
 pub mod manager;
@@ -39,20 +41,26 @@ mod mock;
 mod tests;
 
 pub use crate::{
+    bank::GearBank,
     manager::{ExtManager, HandleKind},
     pallet::*,
-    schedule::{HostFnWeights, InstructionWeights, Limits, Schedule},
+    queue::{
+        ProcessFairShare, ProcessLimited, ProcessRoundRobin, ProcessUntilAllowance, QueueRunner,
+    },
+    schedule::{FloatsPolicy, HostFnWeights, InstructionWeights, Limits, Schedule},
 };
+pub use common::scheduler::HoldKind;
 pub use weights::WeightInfo;
 
 use common::{scheduler::*, storage::*, BlockLimiter, CodeStorage, GasProvider};
 use frame_support::{
     traits::{Currency, StorageVersion},
     weights::Weight,
+    RuntimeDebugNoBound,
 };
 use gear_backend_sandbox::SandboxEnvironment;
 use gear_core::{
-    code::{Code, CodeAndId, InstrumentedCode, InstrumentedCodeAndId},
+    code::{Code, CodeAndId, InstrumentedCode, InstrumentedCodeAndId, STATE_EXPORT_NAME},
     ids::{CodeId, MessageId, ProgramId},
     message::*,
     program::Program as NativeProgram,
@@ -63,6 +71,7 @@ use sp_runtime::traits::{SaturatedConversion, Saturating, UniqueSaturatedInto, Z
 use sp_std::{
     collections::{btree_map::BTreeMap, btree_set::BTreeSet},
     convert::TryInto,
+    ops::Range,
     prelude::*,
 };
 
@@ -78,19 +87,26 @@ pub(crate) type MessengerCapacityOf<T> = <<T as Config>::Messenger as Messenger>
 pub(crate) type TaskPoolOf<T> = <<T as Config>::Scheduler as Scheduler>::TaskPool;
 pub(crate) type MissedBlocksOf<T> = <<T as Config>::Scheduler as Scheduler>::MissedBlocks;
 pub(crate) type CostsPerBlockOf<T> = <<T as Config>::Scheduler as Scheduler>::CostsPerBlock;
+pub(crate) type DispatchStashOf<T> = <<T as Config>::Scheduler as Scheduler>::DispatchStash;
 pub type Authorship<T> = pallet_authorship::Pallet<T>;
 pub type GasAllowanceOf<T> = <<T as Config>::BlockLimiter as BlockLimiter>::GasAllowance;
 pub type GasHandlerOf<T> = <<T as Config>::GasProvider as GasProvider>::GasTree;
 pub type BlockGasLimitOf<T> = <<T as Config>::BlockLimiter as BlockLimiter>::BlockGasLimit;
 
 /// The current storage version.
-const GEAR_STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+const GEAR_STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
+/// Salt of programs, submitted during the gas calculation.
+pub const GAS_CALCULATION_SALT: &[u8] = b"calculate_gas_salt";
 
 pub trait DebugInfo {
     fn is_remap_id_enabled() -> bool;
     fn remap_id();
     fn do_snapshot();
     fn is_enabled() -> bool;
+    /// Adds `value` to the program's custom counter, incremented
+    /// by `gr_metric_increment` within the execution.
+    fn increment_metric(program_id: ProgramId, key: H256, value: u64);
 }
 
 impl DebugInfo for () {
@@ -102,6 +118,7 @@ impl DebugInfo for () {
     fn is_enabled() -> bool {
         false
     }
+    fn increment_metric(_program_id: ProgramId, _key: H256, _value: u64) {}
 }
 
 /// The struct contains results of gas calculation required to process
@@ -115,6 +132,73 @@ pub struct GasInfo {
     pub reserved: u64,
     /// Contains number of gas burned during message processing.
     pub burned: u64,
+    /// Flag of the message being waited during the processing.
+    pub waited: bool,
+}
+
+/// The struct contains result of program state reading.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct StateInfo {
+    /// Scale-encoded state, replied by program.
+    pub payload: Vec<u8>,
+    /// Id of the state type within registry uploaded for program code.
+    ///
+    /// Equals `None` if no type metadata uploaded for the code.
+    pub state_type: Option<u32>,
+}
+
+/// The struct contains the schedule and other constants the runtime uses
+/// for processing of messages, so that off-chain tooling could mirror them.
+#[derive(Clone, Decode, Encode, PartialEq, Eq, RuntimeDebugNoBound, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "std", serde(bound(serialize = "", deserialize = "")))]
+#[scale_info(skip_type_params(T))]
+pub struct ScheduleInfo<T: Config> {
+    /// Limits, instruction, host function and memory weights.
+    pub schedule: Schedule<T>,
+    /// The maximum amount of messages that can be produced in single run.
+    pub outgoing_limit: u32,
+    /// The maximum amount of payload bytes of messages that can be produced in single run.
+    pub outgoing_bytes_limit: u32,
+    /// The maximum amount of programs that can be created in single run.
+    pub program_candidates_limit: u32,
+    /// The minimal gas amount for message to be inserted in mailbox.
+    pub mailbox_threshold: u64,
+    /// Gas cost per block of holding a message in waitlist.
+    pub waitlist_cost: u64,
+    /// Gas cost per block of holding a message in mailbox.
+    pub mailbox_cost: u64,
+    /// Gas cost per block of holding a delayed dispatch in dispatch stash.
+    pub dispatch_stash_cost: u64,
+    /// Amount of blocks, which gas is reserved for in addition to holding.
+    pub reserve_for: u32,
+}
+
+/// Compact statistics of the queue processing within a block.
+#[derive(Clone, Debug, Default, Decode, Encode, PartialEq, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct BlockRunStats {
+    /// Amount of messages dequeued and processed.
+    pub messages_processed: u32,
+    /// Amount of gas burned by messages execution.
+    pub gas_burned: u64,
+    /// Amount of memory pages loaded from storage before execution.
+    pub pages_loaded: u32,
+    /// Amount of memory pages written to storage after execution.
+    pub pages_written: u32,
+    /// Usage of the instrumented code cache.
+    pub code_cache: CodeCacheStats,
+}
+
+/// Statistics of the instrumented code cache within a block.
+#[derive(Clone, Copy, Debug, Default, Decode, Encode, PartialEq, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct CodeCacheStats {
+    /// Amount of codes taken from the cache.
+    pub hits: u32,
+    /// Amount of codes read from storage.
+    pub misses: u32,
 }
 
 #[frame_support::pallet]
@@ -127,15 +211,21 @@ pub mod pallet {
     };
     use alloc::format;
     use common::{
-        self, event::*, lazy_pages, BlockLimiter, CodeMetadata, GasPrice, GasProvider, GasTree,
-        Origin, Program, ProgramState,
+        self,
+        event::*,
+        gas_provider::Error as GasError,
+        journal::{BlockJournal, JournalRecord},
+        lazy_pages, ActiveProgram, BlockLimiter, CodeMetadata, CodeTypeMetadata, GasPrice,
+        GasProvider, GasTree, Origin, Program, ProgramState,
     };
     use core_processor::{
         common::{
             Actor, DispatchOutcome as CoreDispatchOutcome, ExecutableActorData, JournalHandler,
             JournalNote,
         },
-        configs::{AllocationsConfig, BlockConfig, BlockInfo, MessageExecutionContext},
+        configs::{
+            AllocationsConfig, BlockConfig, BlockInfo, DispatchGasMinimums, MessageExecutionContext,
+        },
         Ext,
     };
     use frame_support::{
@@ -143,8 +233,8 @@ pub mod pallet {
         ensure,
         pallet_prelude::*,
         traits::{
-            BalanceStatus, Currency, ExistenceRequirement, Get, LockableCurrency,
-            ReservableCurrency,
+            BalanceStatus, Contains, Currency, EnsureOrigin, ExistenceRequirement, Get,
+            LockableCurrency, ReservableCurrency, WithdrawReasons,
         },
     };
     use frame_system::pallet_prelude::*;
@@ -176,6 +266,17 @@ pub mod pallet {
         #[pallet::constant]
         type OutgoingLimit: Get<u32>;
 
+        /// The maximum amount of payload bytes of messages that can be produced in single run.
+        #[pallet::constant]
+        type OutgoingBytesLimit: Get<u32>;
+
+        /// The maximum amount of programs that can be created in single run.
+        ///
+        /// Each created program is checked and stored by the pallet,
+        /// so it's limited separately from other outgoing messages.
+        #[pallet::constant]
+        type ProgramCandidatesLimit: Get<u32>;
+
         type DebugInfo: DebugInfo;
 
         type CodeStorage: CodeStorage;
@@ -186,10 +287,40 @@ pub mod pallet {
         /// for reply or claim, once gas ends, message removes.
         ///
         /// Messages with gas limit less than that minimum will not be added in mailbox,
-        /// but will be seen in events.
+        /// but will be seen in events, while their value is deposited to the user.
         #[pallet::constant]
         type MailboxThreshold: Get<u64>;
 
+        /// The minimal gas limits of init, handle and reply dispatches.
+        ///
+        /// Extrinsics and program sends declaring explicit gas limit less than
+        /// the minimum of the dispatch kind are rejected before enqueuing.
+        #[pallet::constant]
+        type GasMinimums: Get<DispatchGasMinimums>;
+
+        /// The maximal depth of dispatches chain, followed by gas estimation.
+        ///
+        /// Main dispatch has zero depth, dispatches generated by the dispatch
+        /// of depth `n` have depth `n + 1`. Deeper dispatches aren't executed
+        /// during estimation.
+        #[pallet::constant]
+        type GasEstimationDepth: Get<u32>;
+
+        /// The amount of blocks a newly created program stays active
+        /// for without paying rent.
+        #[pallet::constant]
+        type ProgramRentFreePeriod: Get<Self::BlockNumber>;
+
+        /// Whether program accounts are exempt from existential deposit.
+        ///
+        /// If so, each created program account is provided with
+        /// the existential deposit held in reserve, so value of any
+        /// amount could be sent to and held by programs.
+        ///
+        /// Shouldn't be changed while there are programs in storage.
+        #[pallet::constant]
+        type ExistentialDepositFreePrograms: Get<bool>;
+
         /// Messenger.
         type Messenger: Messenger<
             BlockNumber = Self::BlockNumber,
@@ -219,9 +350,41 @@ pub mod pallet {
         type Scheduler: Scheduler<
             BlockNumber = Self::BlockNumber,
             Cost = u64,
+            OutputError = DispatchError,
             Task = ScheduledTask<Self::AccountId>,
             MissedBlocksCollection = BTreeSet<Self::BlockNumber>,
         >;
+
+        /// Strategy of taking dispatches from the message queue within the block.
+        type QueueRunner: QueueRunner<Self>;
+
+        /// Destinations, messages to which are rejected if sent by programs,
+        /// e.g. burn addresses or system accounts.
+        type ForbiddenDestinations: Contains<ProgramId>;
+
+        /// Max depth of chains of messages sent by programs to each other,
+        /// starting from a message sent by user.
+        #[pallet::constant]
+        type ReplyChainDepthLimit: Get<u32>;
+
+        /// Origin allowed to pause and resume the message queue processing.
+        type QueueProcessingOrigin: EnsureOrigin<Self::Origin>;
+
+        /// Account receiving gas and rent charges within blocks without
+        /// author, e.g. on dev chains with manual seal.
+        ///
+        /// If `None`, charges of such blocks aren't paid to anyone.
+        type FallbackAuthor: Get<Option<Self::AccountId>>;
+
+        /// Account receiving funds held by the bank, which couldn't be paid
+        /// to their receivers, e.g. charges of blocks without author and
+        /// refunds to reaped accounts.
+        ///
+        /// If `None`, such funds are left in the bank.
+        type DustBeneficiary: Get<Option<Self::AccountId>>;
+
+        /// Origin allowed to ban programs from execution and to unban them.
+        type ProgramBanOrigin: EnsureOrigin<Self::Origin>;
     }
 
     #[pallet::pallet]
@@ -284,6 +447,8 @@ pub mod pallet {
             statuses: BTreeMap<MessageId, DispatchStatus>,
             /// Ids of programs, which state changed during queue processing.
             state_changes: BTreeSet<ProgramId>,
+            /// Usage of the instrumented code cache during queue processing.
+            code_cache: CodeCacheStats,
         },
 
         /// Temporary `Event` variant, showing that all storages was cleared.
@@ -343,12 +508,93 @@ pub mod pallet {
             /// NOTE: See more docs about change kinds at `gear_common::event`.
             change: ProgramChangeKind<T::BlockNumber>,
         },
+
+        /// Gas of the message was topped up by some account.
+        GasToppedUp {
+            /// Id of the message, which gas was topped up.
+            id: MessageId,
+            /// Account, which paid for the additional gas.
+            payer: T::AccountId,
+            /// Amount of the additional gas.
+            amount: u64,
+        },
+
+        /// Rent of the program was paid by some account.
+        ProgramRentPaid {
+            /// Id of the program, which rent was paid.
+            id: ProgramId,
+            /// Account, which paid the rent.
+            payer: T::AccountId,
+            /// Block number, until which the program stays active.
+            expiration: T::BlockNumber,
+        },
+
+        /// Gas left unused by the message chain was returned to its payers.
+        GasRefunded {
+            /// Id of the message, which consumption released the gas.
+            message_id: MessageId,
+            /// Amount of the gas refunded.
+            amount: u64,
+        },
+
+        /// Program emitted event with topics by `gr_emit_event`.
+        ///
+        /// Emitted only if the execution succeeded, so topics
+        /// could be used to filter events without parsing payloads.
+        ProgramEvent {
+            /// Id of the program, which emitted the event.
+            source: ProgramId,
+            /// Topics of the event.
+            topics: Vec<H256>,
+            /// Payload of the event.
+            payload: Vec<u8>,
+        },
+
+        /// Message queue processing was paused or resumed.
+        QueueProcessingSet {
+            /// Whether the queue is processed since the next block.
+            enabled: bool,
+        },
+
+        /// Message queue wasn't processed within the block,
+        /// since the processing is paused.
+        QueueProcessingSkipped,
+
+        /// Program was banned from execution.
+        ProgramBanned {
+            /// Id of the program banned.
+            id: ProgramId,
+        },
+
+        /// Program was unbanned, so its messages are executed again.
+        ProgramUnbanned {
+            /// Id of the program unbanned.
+            id: ProgramId,
+        },
+
+        /// Gas tree of the message or storage it's kept in is corrupted,
+        /// so the message was quarantined.
+        GasTreeCorrupted {
+            /// Id of the message, which gas node or storage operation failed.
+            id: MessageId,
+            /// Error returned by the gas tree or the storage.
+            error: DispatchError,
+        },
+
+        /// Funds, which couldn't be paid to their receivers,
+        /// were routed to the dust beneficiary.
+        DustRouted {
+            /// Account, which received the funds.
+            beneficiary: T::AccountId,
+            /// Amount of the funds.
+            amount: BalanceOf<T>,
+        },
     }
 
     // Gear pallet error.
     #[pallet::error]
     pub enum Error<T> {
-        /// Not enough balance to reserve.
+        /// Not enough balance to pay for gas and value.
         ///
         /// Usually occurs when gas_limit specified is such that origin account can't afford the message.
         NotEnoughBalanceForReserve,
@@ -387,8 +633,115 @@ pub mod pallet {
         MessagesStorageCorrupted,
         /// User contains mailboxed message from other user.
         UserRepliesToUser,
+        /// Gas limit too low.
+        ///
+        /// Occurs when an extrinsic's declared `gas_limit` is less than the minimal gas limit
+        /// for the kind of dispatch it enqueues.
+        GasLimitTooLow,
+        /// Origin isn't the author of the code.
+        ///
+        /// Occurs when someone except code author tries to upload type metadata for it.
+        NotCodeAuthor,
+        /// Program is not active.
+        ///
+        /// Occurs when paying rent for a program, which is paused, terminated or doesn't exist.
+        InactiveProgram,
+        /// Message payload exceeds the limit.
+        PayloadSizeLimitExceeded,
+        /// Program doesn't exist.
+        ///
+        /// Occurs when banning a program, which isn't in the program storage.
+        ProgramNotFound,
+        /// Program is already banned.
+        ProgramAlreadyBanned,
+        /// Program isn't banned.
+        ProgramNotBanned,
     }
 
+    /// Statistics of the queue processing within the last block.
+    ///
+    /// Overwritten each block, so could be read by light clients
+    /// without events indexing.
+    #[pallet::storage]
+    #[pallet::getter(fn block_run_stats)]
+    pub type LastBlockRunStats<T> = StorageValue<_, BlockRunStats, ValueQuery>;
+
+    /// Gas bought for messages in flight by accounts additionally to the
+    /// gas limit, keyed by the origin node of the gas tree.
+    ///
+    /// Additional payers are refunded first, once the gas tree is consumed.
+    #[pallet::storage]
+    pub(crate) type GasTopUps<T: Config> =
+        StorageDoubleMap<_, Identity, MessageId, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
+
+    /// Depth of the chain of messages sent by programs, which led to the
+    /// message to program. Messages sent by users have no entry here.
+    ///
+    /// Entry is removed once the message is consumed.
+    #[pallet::storage]
+    pub(crate) type ReplyChainDepth<T: Config> =
+        StorageMap<_, Identity, MessageId, u32, ValueQuery>;
+
+    /// Block numbers, at which rent of active programs expires, so that
+    /// they become paused unless the rent is paid further.
+    #[pallet::storage]
+    #[pallet::getter(fn program_expiration)]
+    pub type ProgramExpirations<T: Config> = StorageMap<_, Identity, ProgramId, T::BlockNumber>;
+
+    /// Block numbers, at which holding of waitlisted messages expires,
+    /// so that the task scheduled on it could be removed on earlier wake.
+    #[pallet::storage]
+    pub(crate) type WaitlistExpirations<T: Config> =
+        StorageMap<_, Identity, MessageId, T::BlockNumber>;
+
+    /// Block numbers, at which awakening of waitlisted messages is scheduled
+    /// with delay, so that the tasks could be removed on earlier wake.
+    #[pallet::storage]
+    pub(crate) type DelayedWakes<T: Config> =
+        StorageDoubleMap<_, Identity, MessageId, Identity, T::BlockNumber, ()>;
+
+    /// Block numbers, at which messages were put into the mailbox and at which
+    /// holding of them expires, so that the rent is charged on their removal.
+    #[pallet::storage]
+    pub(crate) type MailboxHolds<T: Config> =
+        StorageMap<_, Identity, MessageId, (T::BlockNumber, T::BlockNumber)>;
+
+    #[pallet::type_value]
+    pub fn DefaultForQueueProcessingEnabled() -> bool {
+        true
+    }
+
+    /// Whether the message queue is processed at the end of each block.
+    ///
+    /// Disabled as an emergency brake, e.g. once a bug in execution is
+    /// discovered. Messages are still enqueued while it's disabled.
+    #[pallet::storage]
+    #[pallet::getter(fn queue_processing_enabled)]
+    pub type QueueProcessingEnabled<T> =
+        StorageValue<_, bool, ValueQuery, DefaultForQueueProcessingEnabled>;
+
+    /// Programs banned from execution.
+    ///
+    /// Messages to banned programs are answered with error replies without
+    /// execution, while messages sent by them, which are still in the queue,
+    /// are dropped.
+    #[pallet::storage]
+    pub type BannedPrograms<T> = StorageMap<_, Identity, ProgramId, ()>;
+
+    /// Messages quarantined due to corruption of their gas tree.
+    ///
+    /// Quarantined messages are taken out of processing, while their gas
+    /// nodes are left in the tree as is for the investigation. Dispatch is
+    /// kept, if the message was quarantined on its way through the queue.
+    #[pallet::storage]
+    pub type QuarantinedMessages<T> = StorageMap<_, Identity, MessageId, Option<StoredDispatch>>;
+
+    /// Funds held by the bank, which couldn't be paid to their receivers.
+    ///
+    /// Accumulated till they can be routed to `Config::DustBeneficiary`.
+    #[pallet::storage]
+    pub type Dust<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T>
     where
@@ -397,14 +750,24 @@ pub mod pallet {
         fn on_runtime_upgrade() -> Weight {
             log::debug!(target: "runtime::gear", "⚙️ Runtime upgrade");
 
+            migration::migrate::<T>();
+
+            // Queue processing is skipped in the block of runtime upgrade.
             Weight::MAX
         }
 
+        /// Consistency check of the storage after runtime upgrade.
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            Self::check_gas_nodes()?;
+            GearBank::<T>::check_holdings()
+        }
+
         /// Initialization
         fn on_initialize(bn: BlockNumberFor<T>) -> Weight {
             log::debug!(target: "runtime::gear", "⚙️ Initialization of block #{:?}", bn);
 
-            0
+            GearBank::<T>::route_dust()
         }
 
         /// Finalization
@@ -437,8 +800,14 @@ pub mod pallet {
             // Processing regular and delayed tasks.
             Self::process_tasks(&mut ext_manager);
 
-            // Processing message queue.
-            Self::process_queue(ext_manager);
+            // Processing message queue, unless it's paused.
+            if QueueProcessingEnabled::<T>::get() {
+                Self::process_queue(ext_manager);
+            } else {
+                log::debug!(target: "runtime::gear", "⚙️ Queue processing is paused");
+
+                Self::deposit_event(Event::QueueProcessingSkipped);
+            }
 
             // Calculating weight burned within the block.
             let weight = remaining_weight.saturating_sub(GasAllowanceOf::<T>::get() as Weight);
@@ -490,6 +859,10 @@ pub mod pallet {
 
             let code_and_id = CodeAndId::new(code);
 
+            let init_payload = init_payload
+                .try_into()
+                .map_err(|_| Error::<T>::PayloadSizeLimitExceeded)?;
+
             let packet = InitPacket::new_with_gas(
                 code_and_id.code_id(),
                 salt,
@@ -505,11 +878,9 @@ pub mod pallet {
                 Error::<T>::ProgramAlreadyExists
             );
 
-            let reserve_fee = T::GasPrice::gas_price(gas_limit);
-
-            // First we reserve enough funds on the account to pay for `gas_limit`
-            // and to transfer declared value.
-            <T as Config>::Currency::reserve(&who, reserve_fee + value)
+            // First we move enough funds from the account to the bank
+            // to pay for `gas_limit` and to transfer declared value.
+            GearBank::<T>::deposit(&who, gas_limit, value)
                 .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
 
             let origin = who.clone().into_origin();
@@ -518,7 +889,9 @@ pub mod pallet {
 
             // By that call we follow the guarantee that we have in `Self::submit_code` -
             // if there's code in storage, there's also metadata for it.
-            if let Ok(code_id) = Self::set_code_with_metadata(code_and_id, origin) {
+            if !T::CodeStorage::exists(code_id) {
+                Self::set_code_with_metadata(code_and_id, origin)?;
+
                 // TODO: replace this temporary (`None`) value
                 // for expiration block number with properly
                 // calculated one (issues #646 and #969).
@@ -531,6 +904,7 @@ pub mod pallet {
             let message_id = Self::next_message_id(origin);
 
             ExtManager::<T>::default().set_program(program_id, code_id, message_id);
+            GearProgramPallet::<T>::set_program_creator(program_id, who.clone());
 
             let _ = GasHandlerOf::<T>::create(
                 who.clone(),
@@ -635,11 +1009,15 @@ pub mod pallet {
                 )
                 .map(
                     |GasInfo {
-                         reserved, burned, ..
+                         reserved,
+                         burned,
+                         waited,
+                         ..
                      }| GasInfo {
                         min_limit,
                         reserved,
                         burned,
+                        waited,
                     },
                 )
                 .map_err(|e| {
@@ -649,6 +1027,251 @@ pub mod pallet {
             })
         }
 
+        /// Returns the schedule and other constants used for processing of messages.
+        pub fn schedule_info() -> ScheduleInfo<T> {
+            ScheduleInfo {
+                schedule: T::Schedule::get(),
+                outgoing_limit: T::OutgoingLimit::get(),
+                outgoing_bytes_limit: T::OutgoingBytesLimit::get(),
+                program_candidates_limit: T::ProgramCandidatesLimit::get(),
+                mailbox_threshold: T::MailboxThreshold::get(),
+                waitlist_cost: CostsPerBlockOf::<T>::waitlist(),
+                mailbox_cost: CostsPerBlockOf::<T>::mailbox(),
+                dispatch_stash_cost: CostsPerBlockOf::<T>::dispatch_stash(),
+                reserve_for: CostsPerBlockOf::<T>::reserve_for().unique_saturated_into(),
+            }
+        }
+
+        /// Quarantines the message, which gas tree or storage is corrupted.
+        ///
+        /// Message is taken out of processing instead of panicking,
+        /// so the block stays producible.
+        pub(crate) fn quarantine(
+            id: MessageId,
+            dispatch: Option<StoredDispatch>,
+            error: DispatchError,
+        ) {
+            log::error!(
+                target: "essential",
+                "Gas tree or storage of message {:?} is corrupted, message is quarantined: {:?}",
+                id,
+                error,
+            );
+
+            QuarantinedMessages::<T>::insert(id, dispatch);
+
+            Self::deposit_event(Event::GasTreeCorrupted { id, error });
+        }
+
+        /// Queues the dispatch, quarantining it if the message queue is corrupted.
+        ///
+        /// Dispatch is consumed by the failed queue operation,
+        /// so only its id is kept in the quarantine.
+        pub(crate) fn queue_dispatch(dispatch: StoredDispatch) {
+            let id = dispatch.id();
+
+            if let Err(e) = QueueOf::<T>::queue(dispatch) {
+                Self::quarantine(id, None, e);
+            }
+        }
+
+        /// Returns origin key of the gas node, failing if there is no such node.
+        pub(crate) fn gas_origin_key(id: MessageId) -> Result<MessageId, DispatchError> {
+            GasHandlerOf::<T>::get_origin_key(id)?
+                .ok_or_else(|| <GasHandlerOf<T> as GasTree>::InternalError::node_not_found().into())
+        }
+
+        /// Checks that every queued and waitlisted message has a gas node.
+        pub fn check_gas_nodes() -> Result<(), &'static str> {
+            let has_gas_node =
+                |id: MessageId| matches!(GasHandlerOf::<T>::get_limit(id), Ok(Some(_)));
+
+            for dispatch in QueueOf::<T>::iter() {
+                let dispatch = dispatch.map_err(|_| "Message queue is inconsistent")?;
+
+                if !has_gas_node(dispatch.id()) {
+                    log::error!("Queued message {:?} has no gas node", dispatch.id());
+                    return Err("Queued message has no gas node");
+                }
+            }
+
+            for (dispatch, _) in WaitlistOf::<T>::iter() {
+                if !has_gas_node(dispatch.id()) {
+                    log::error!("Waitlisted message {:?} has no gas node", dispatch.id());
+                    return Err("Waitlisted message has no gas node");
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Returns account receiving charges within the current block:
+        /// the block author or `Config::FallbackAuthor` if there is none.
+        pub(crate) fn block_author() -> Option<T::AccountId> {
+            Authorship::<T>::author().or_else(T::FallbackAuthor::get)
+        }
+
+        /// Returns gas cost per block of holding in the storage of the kind.
+        pub fn hold_cost_per_block(kind: HoldKind) -> u64 {
+            match kind {
+                HoldKind::Waitlist => CostsPerBlockOf::<T>::waitlist(),
+                HoldKind::Mailbox => CostsPerBlockOf::<T>::mailbox(),
+                HoldKind::DispatchStash => CostsPerBlockOf::<T>::dispatch_stash(),
+                HoldKind::Reservation => 0,
+            }
+        }
+
+        /// Returns amount of blocks, which holding in the storage of the kind
+        /// is paid for by the gas, keeping the reserve for missed blocks.
+        ///
+        /// Holding, which isn't charged, is sustained for `u32::MAX` blocks.
+        pub fn hold_duration(gas: u64, kind: HoldKind) -> u32 {
+            let cost = Self::hold_cost_per_block(kind);
+
+            if cost == 0 {
+                return u32::MAX;
+            }
+
+            let reserve_for = CostsPerBlockOf::<T>::reserve_for().saturated_into::<u32>();
+
+            gas.saturating_div(cost)
+                .saturated_into::<u32>()
+                .saturating_sub(reserve_for)
+        }
+
+        /// Returns gas required for holding in the storage of the kind
+        /// for the amount of blocks along with the reserve for missed blocks.
+        pub fn hold_cost(duration: u32, kind: HoldKind) -> u64 {
+            let reserve_for = CostsPerBlockOf::<T>::reserve_for().saturated_into::<u64>();
+
+            Self::hold_cost_per_block(kind)
+                .saturating_mul(u64::from(duration).saturating_add(reserve_for))
+        }
+
+        /// Returns id of the program, which will be created from the code with the salt.
+        pub fn calculate_program_id(code_id: H256, salt: &[u8]) -> ProgramId {
+            ProgramId::generate(CodeId::from_origin(code_id), salt)
+        }
+
+        /// Returns whether the code with the id is saved on chain.
+        pub fn code_hash_exists(code_id: H256) -> bool {
+            T::CodeStorage::exists(CodeId::from_origin(code_id))
+        }
+
+        /// Returns the code with the id as it was uploaded, before instrumentation,
+        /// so that it could be compared with the locally built one.
+        pub fn original_code(code_id: H256) -> Option<Vec<u8>> {
+            T::CodeStorage::get_original_code(CodeId::from_origin(code_id))
+        }
+
+        /// Reads state of the program by executing its `state` export with `query`
+        /// as payload, so that program state isn't changed.
+        ///
+        /// Returns scale-encoded reply of the program along with id of the state type
+        /// within registry uploaded for program code.
+        pub fn read_state(program_id: H256, query: Vec<u8>) -> Result<StateInfo, Vec<u8>> {
+            let code_id = match common::get_program(program_id)
+                .ok_or_else(|| b"Program not found in the storage".to_vec())?
+            {
+                Program::Active(program) => CodeId::from_origin(program.code_hash),
+                _ => return Err(b"Program is not active".to_vec()),
+            };
+
+            let lazy_pages_enabled =
+                cfg!(feature = "lazy-pages") && lazy_pages::try_to_enable_lazy_pages();
+
+            let data = ExtManager::<T>::default()
+                .get_actor(ProgramId::from_origin(program_id), !lazy_pages_enabled)
+                .and_then(|actor| actor.executable_data)
+                .ok_or_else(|| b"Program not found in the storage".to_vec())?;
+
+            let block_info = BlockInfo {
+                height: <frame_system::Pallet<T>>::block_number().unique_saturated_into(),
+                timestamp: <pallet_timestamp::Pallet<T>>::get().unique_saturated_into(),
+            };
+
+            let gas_limit = BlockGasLimitOf::<T>::get();
+
+            let payload = if lazy_pages_enabled {
+                core_processor::execute_for_reply::<LazyPagesExt, SandboxEnvironment<_>>(
+                    STATE_EXPORT_NAME,
+                    data,
+                    query,
+                    gas_limit,
+                    block_info,
+                )
+            } else {
+                core_processor::execute_for_reply::<Ext, SandboxEnvironment<_>>(
+                    STATE_EXPORT_NAME,
+                    data,
+                    query,
+                    gas_limit,
+                    block_info,
+                )
+            }
+            .map_err(String::into_bytes)?;
+
+            let state_type =
+                T::CodeStorage::get_type_metadata(code_id).map(|metadata| metadata.state_type);
+
+            Ok(StateInfo {
+                payload,
+                state_type,
+            })
+        }
+
+        /// Reads state of the program by executing `meta_state` function of its
+        /// metadata binary `meta_wasm` over the program memory with `payload`
+        /// as incoming message, so that program state isn't changed.
+        ///
+        /// Returns state, encoded by `meta_state` function.
+        pub fn read_meta_state(
+            program_id: H256,
+            meta_wasm: Vec<u8>,
+            payload: Vec<u8>,
+        ) -> Result<Vec<u8>, Vec<u8>> {
+            let schedule = T::Schedule::get();
+
+            // Metadata binary isn't stored on chain, so it's instrumented here
+            // to prevent unlimited execution.
+            let module = wasm_instrument::parity_wasm::deserialize_buffer(&meta_wasm)
+                .map_err(|_| b"Failed to decode metadata binary".to_vec())?;
+            let module = wasm_instrument::gas_metering::inject(
+                module.clone(),
+                &schedule.rules(&module),
+                "env",
+            )
+            .map_err(|_| b"Failed to instrument metadata binary".to_vec())?;
+            let meta_wasm = wasm_instrument::parity_wasm::elements::serialize(module)
+                .map_err(|_| b"Failed to encode metadata binary".to_vec())?;
+
+            let lazy_pages_enabled =
+                cfg!(feature = "lazy-pages") && lazy_pages::try_to_enable_lazy_pages();
+
+            let data = ExtManager::<T>::default()
+                .get_actor(ProgramId::from_origin(program_id), !lazy_pages_enabled)
+                .and_then(|actor| actor.executable_data)
+                .ok_or_else(|| b"Program not found in the storage".to_vec())?;
+
+            let block_info = BlockInfo {
+                height: <frame_system::Pallet<T>>::block_number().unique_saturated_into(),
+                timestamp: <pallet_timestamp::Pallet<T>>::get().unique_saturated_into(),
+            };
+
+            let gas_limit = BlockGasLimitOf::<T>::get();
+
+            if lazy_pages_enabled {
+                core_processor::execute_for_meta_state::<LazyPagesExt, SandboxEnvironment<_>>(
+                    &meta_wasm, data, payload, gas_limit, block_info,
+                )
+            } else {
+                core_processor::execute_for_meta_state::<Ext, SandboxEnvironment<_>>(
+                    &meta_wasm, data, payload, gas_limit, block_info,
+                )
+            }
+            .map_err(String::into_bytes)
+        }
+
         pub fn run_with_ext_copy<R, F: FnOnce() -> R>(f: F) -> R {
             sp_externalities::with_externalities(|ext| {
                 ext.storage_start_transaction();
@@ -676,9 +1299,12 @@ pub mod pallet {
         ) -> Result<GasInfo, Vec<u8>> {
             let account = <T::AccountId as Origin>::from_origin(source);
 
+            // Account, paying for gas and value, is kept alive,
+            // so it has to hold the existential deposit on top.
             let balance = <T as Config>::Currency::free_balance(&account);
-            let max_balance: BalanceOf<T> =
-                T::GasPrice::gas_price(initial_gas) + value.unique_saturated_into();
+            let max_balance: BalanceOf<T> = T::GasPrice::gas_price(initial_gas)
+                + value.unique_saturated_into()
+                + <T as Config>::Currency::minimum_balance();
             <T as Config>::Currency::deposit_creating(
                 &account,
                 max_balance.saturating_sub(balance),
@@ -691,7 +1317,7 @@ pub mod pallet {
 
             match kind {
                 HandleKind::Init(code) => {
-                    let salt = b"calculate_gas_salt".to_vec();
+                    let salt = GAS_CALCULATION_SALT.to_vec();
                     Self::submit_program(who.into(), code, salt, payload, initial_gas, value)
                         .map_err(|e| {
                             format!("Internal error: submit_program failed with '{:?}'", e)
@@ -727,8 +1353,7 @@ pub mod pallet {
                 timestamp: <pallet_timestamp::Pallet<T>>::get().unique_saturated_into(),
             };
 
-            let existential_deposit =
-                <T as Config>::Currency::minimum_balance().unique_saturated_into();
+            let existential_deposit = Self::program_existential_deposit();
 
             let schedule = T::Schedule::get();
 
@@ -738,6 +1363,8 @@ pub mod pallet {
                 alloc_cost: schedule.memory_weights.allocation_cost,
                 mem_grow_cost: schedule.memory_weights.grow_cost,
                 load_page_cost: schedule.memory_weights.load_cost,
+                page_read_cost: schedule.memory_weights.page_read_cost,
+                page_write_cost: schedule.memory_weights.page_write_cost,
             };
 
             let block_config = BlockConfig {
@@ -745,25 +1372,48 @@ pub mod pallet {
                 allocations_config,
                 existential_deposit,
                 outgoing_limit: T::OutgoingLimit::get(),
+                outgoing_bytes_limit: T::OutgoingBytesLimit::get(),
+                program_candidates_limit: T::ProgramCandidatesLimit::get(),
+                outgoing_scaling: schedule.limits.outgoing_scaling(),
                 host_fn_weights: schedule.host_fn_weights.into_core(),
                 forbidden_funcs: ["gr_gas_available"].into(),
                 mailbox_threshold: T::MailboxThreshold::get(),
+                gas_minimums: T::GasMinimums::get(),
             };
 
             let mut min_limit = 0;
             let mut reserved = 0;
             let mut burned = 0;
+            let mut waited = false;
 
             let mut ext_manager = ExtManager::<T>::default();
 
+            // Depths of dispatches, generated during estimation.
+            let mut depths = BTreeMap::new();
+            let max_depth = T::GasEstimationDepth::get();
+
             while let Some(queued_dispatch) =
                 QueueOf::<T>::dequeue().map_err(|_| b"MQ storage corrupted".to_vec())?
             {
+                let depth = depths
+                    .get(&queued_dispatch.id())
+                    .copied()
+                    .unwrap_or_default();
+
+                if depth > max_depth {
+                    continue;
+                }
+
                 let actor_id = queued_dispatch.destination();
 
                 let lazy_pages_enabled =
                     cfg!(feature = "lazy-pages") && lazy_pages::try_to_enable_lazy_pages();
 
+                if lazy_pages_enabled {
+                    // Lazy pages read program pages directly from storage.
+                    ext_manager.flush_pages();
+                }
+
                 let actor = ext_manager
                     .get_actor(actor_id, !lazy_pages_enabled)
                     .ok_or_else(|| b"Program not found in the storage".to_vec())?;
@@ -820,6 +1470,8 @@ pub mod pallet {
 
                     match note {
                         JournalNote::SendDispatch { dispatch, .. } => {
+                            depths.insert(dispatch.id(), depth.saturating_add(1));
+
                             if from_main_chain(dispatch.id())? {
                                 let gas_limit = dispatch
                                     .gas_limit()
@@ -846,6 +1498,14 @@ pub mod pallet {
                             }
                         }
 
+                        // Waited messages are followed further, once the replies
+                        // generated during the estimation wake them.
+                        JournalNote::WaitDispatch { dispatch, .. } => {
+                            if from_main_chain(dispatch.id())? {
+                                waited = true;
+                            }
+                        }
+
                         JournalNote::MessageDispatched {
                             outcome: CoreDispatchOutcome::MessageTrap { trap, program_id },
                             ..
@@ -860,10 +1520,18 @@ pub mod pallet {
                 }
             }
 
+            // Replies, waking messages during the estimation, arrive in later
+            // blocks on chain, so the limit should cover holding in the waitlist
+            // at least for the next block along with the reserved blocks.
+            if waited {
+                min_limit = min_limit.saturating_add(Self::hold_cost(1, HoldKind::Waitlist));
+            }
+
             Ok(GasInfo {
                 min_limit,
                 reserved,
                 burned,
+                waited,
             })
         }
 
@@ -875,16 +1543,69 @@ pub mod pallet {
         }
 
         /// Returns true if a program has terminated status
+        ///
+        /// Programs exited by themselves are considered terminated as well.
         pub fn is_terminated(program_id: ProgramId) -> bool {
             common::get_program(program_id.into_origin())
-                .map(|p| p.is_terminated())
+                .map(|p| p.is_terminated() || p.is_exited())
                 .unwrap_or(false)
         }
 
+        /// Returns true if a program has exited status
+        pub fn is_exited(program_id: ProgramId) -> bool {
+            common::get_program(program_id.into_origin())
+                .map(|p| p.is_exited())
+                .unwrap_or(false)
+        }
+
+        /// Returns amount of dispatches in the message queue.
+        pub fn queue_len() -> MessengerCapacityOf<T> {
+            QueueOf::<T>::len()
+        }
+
+        /// Returns amount of messages in the waitlist of the program.
+        pub fn waitlist_len(program_id: ProgramId) -> usize {
+            WaitlistOf::<T>::len(&program_id)
+        }
+
+        /// Returns amount of messages in the mailbox of the user.
+        pub fn mailbox_len(account_id: &T::AccountId) -> usize {
+            MailboxOf::<T>::len(account_id)
+        }
+
+        /// Returns storage keys of the program record and of its pages
+        /// with data within the `pages` range.
+        ///
+        /// Proof of these keys is sufficient to verify the program state,
+        /// since pages, which aren't listed in the record, have no data.
+        pub fn program_state_keys(program_id: H256, pages: Range<u32>) -> Vec<Vec<u8>> {
+            let mut keys = vec![common::program_key(program_id)];
+
+            if let Some(Program::Active(program)) = common::get_program(program_id) {
+                keys.extend(
+                    program
+                        .pages_with_data
+                        .iter()
+                        .filter(|page| pages.contains(&page.0))
+                        .map(|page| common::page_key(program_id, *page)),
+                );
+            }
+
+            keys
+        }
+
         /// Returns MessageId for newly created user message.
         pub fn next_message_id(user_id: H256) -> MessageId {
-            let nonce = SentOf::<T>::get();
+            let message_id = Self::next_user_message_id(user_id);
             SentOf::<T>::increase();
+
+            message_id
+        }
+
+        /// Returns id, which will be assigned to the next message sent
+        /// by the user within the current block, without consuming it.
+        pub fn next_user_message_id(user_id: H256) -> MessageId {
+            let nonce = SentOf::<T>::get();
             let block_number = <frame_system::Pallet<T>>::block_number().unique_saturated_into();
             let user_id = ProgramId::from_origin(user_id);
 
@@ -1003,8 +1724,7 @@ pub mod pallet {
                 timestamp: <pallet_timestamp::Pallet<T>>::get().unique_saturated_into(),
             };
 
-            let existential_deposit =
-                <T as Config>::Currency::minimum_balance().unique_saturated_into();
+            let existential_deposit = Self::program_existential_deposit();
 
             let schedule = T::Schedule::get();
 
@@ -1014,6 +1734,8 @@ pub mod pallet {
                 alloc_cost: schedule.memory_weights.allocation_cost,
                 mem_grow_cost: schedule.memory_weights.grow_cost,
                 load_page_cost: schedule.memory_weights.load_cost,
+                page_read_cost: schedule.memory_weights.page_read_cost,
+                page_write_cost: schedule.memory_weights.page_write_cost,
             };
 
             let block_config = BlockConfig {
@@ -1021,19 +1743,29 @@ pub mod pallet {
                 allocations_config,
                 existential_deposit,
                 outgoing_limit: T::OutgoingLimit::get(),
+                outgoing_bytes_limit: T::OutgoingBytesLimit::get(),
+                program_candidates_limit: T::ProgramCandidatesLimit::get(),
+                outgoing_scaling: schedule.limits.outgoing_scaling(),
                 host_fn_weights: schedule.host_fn_weights.into_core(),
                 forbidden_funcs: Default::default(),
                 mailbox_threshold: T::MailboxThreshold::get(),
+                gas_minimums: T::GasMinimums::get(),
             };
 
             if T::DebugInfo::is_remap_id_enabled() {
                 T::DebugInfo::remap_id();
             }
 
+            let mut pages_loaded = 0u32;
+
+            // Journal is collected only if the node exports it to indexers.
+            let mut journal_records =
+                common::journal::is_export_enabled().then(Vec::<JournalRecord>::new);
+
+            let mut queue_runner = T::QueueRunner::default();
+
             while QueueProcessingOf::<T>::allowed() {
-                if let Some(dispatch) = QueueOf::<T>::dequeue()
-                    .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e))
-                {
+                if let Some(dispatch) = queue_runner.next_dispatch() {
                     let msg_id = dispatch.id();
                     let gas_limit: u64;
                     match GasHandlerOf::<T>::get_limit(msg_id) {
@@ -1048,9 +1780,7 @@ pub mod pallet {
                                     dispatch.destination(),
                                 );
 
-                                QueueOf::<T>::queue(dispatch).unwrap_or_else(|e| {
-                                    unreachable!("Message queue corrupted! {:?}", e)
-                                });
+                                Self::queue_dispatch(dispatch);
 
                                 // Since we requeue the message without GasHandler we have to take
                                 // into account that there can left only such messages in the queue.
@@ -1067,10 +1797,11 @@ pub mod pallet {
                                 continue;
                             };
                         }
-                        Err(_err) => {
-                            // We only can get an error here if the gas tree is invalidated
-                            // TODO: handle appropriately
-                            unreachable!("Can never happen unless gas tree corrupted");
+                        Err(e) => {
+                            // We only can get an error here if the gas tree is invalidated.
+                            Self::quarantine(dispatch.id(), Some(dispatch), e);
+
+                            continue;
                         }
                     };
 
@@ -1082,20 +1813,57 @@ pub mod pallet {
                         GasAllowanceOf::<T>::get(),
                     );
 
+                    // Messages sent by banned programs before the ban are dropped.
+                    if BannedPrograms::<T>::contains_key(dispatch.source()) {
+                        log::debug!(
+                            "Dropping message {:?} of banned program {:?}",
+                            dispatch.id(),
+                            dispatch.source(),
+                        );
+
+                        if dispatch.value() != 0 {
+                            ext_manager.send_value(dispatch.source(), None, dispatch.value());
+                        }
+
+                        ext_manager.message_consumed(dispatch.id());
+
+                        continue;
+                    }
+
                     let lazy_pages_enabled =
                         cfg!(feature = "lazy-pages") && lazy_pages::try_to_enable_lazy_pages();
                     let program_id = dispatch.destination();
                     let current_message_id = dispatch.id();
                     let maybe_message_reply = dispatch.reply();
 
-                    let active_actor_data = if let Some(maybe_active_program) =
+                    let active_actor_data = if BannedPrograms::<T>::contains_key(program_id) {
+                        // Banned program is unavailable, so the message is
+                        // answered with error reply without execution.
+                        log::debug!("Program '{:?}' is banned", program_id);
+                        None
+                    } else if maybe_message_reply.is_none()
+                        && WaitlistOf::<T>::is_full(&program_id)
+                        && matches!(
+                            common::get_program(program_id.into_origin()),
+                            Some(Program::Active(ActiveProgram {
+                                state: ProgramState::Uninitialized { message_id },
+                                ..
+                            })) if message_id != current_message_id
+                        )
+                    {
+                        // Message to uninitialized program can't be waitlisted
+                        // until the initialization, so it's answered with error
+                        // reply without execution.
+                        log::debug!("Waitlist of program '{:?}' is full", program_id);
+                        None
+                    } else if let Some(maybe_active_program) =
                         common::get_program(program_id.into_origin())
                     {
                         // Check whether message should be added to the wait list
                         if let Program::Active(prog) = maybe_active_program {
                             let schedule = T::Schedule::get();
                             let code_id = CodeId::from_origin(prog.code_hash);
-                            let code = if let Some(code) = T::CodeStorage::get_code(code_id) {
+                            let code = if let Some(code) = ext_manager.get_code(code_id) {
                                 if code.instruction_weights_version()
                                     == schedule.instruction_weights.version
                                 {
@@ -1103,6 +1871,7 @@ pub mod pallet {
                                 } else if let Ok(code) = Self::reinstrument_code(code_id, &schedule)
                                 {
                                     // todo: charge for code instrumenting
+                                    ext_manager.cache_code(code_id, code.clone());
                                     code
                                 } else {
                                     // todo: mark code as unable for instrument to skip next time
@@ -1126,28 +1895,31 @@ pub mod pallet {
                             if maybe_message_reply.is_none()
                                 && matches!(prog.state, ProgramState::Uninitialized {message_id} if message_id != current_message_id)
                             {
-                                let origin = if let Some(origin) =
-                                    GasHandlerOf::<T>::get_origin_key(dispatch.id()).unwrap_or_else(
-                                        |e| unreachable!("ValueTree corrupted: {:?}!", e),
-                                    ) {
-                                    if origin == dispatch.id() {
-                                        None
-                                    } else {
-                                        Some(origin)
+                                let origin = match Self::gas_origin_key(dispatch.id()) {
+                                    Ok(origin) if origin == dispatch.id() => None,
+                                    Ok(origin) => Some(origin),
+                                    Err(e) => {
+                                        Self::quarantine(dispatch.id(), Some(dispatch), e);
+
+                                        continue;
                                     }
-                                } else {
-                                    unreachable!("ValueTree corrupted!")
                                 };
 
-                                // TODO: replace this temporary (zero) value
-                                // for expiration block number with properly
-                                // calculated one (issues #646 and #969).
+                                let current_bn = <frame_system::Pallet<T>>::block_number()
+                                    .saturated_into::<u32>();
+
+                                let duration =
+                                    ExtManager::<T>::affordable_waitlist_duration(gas_limit);
+
+                                let deadline = current_bn.saturating_add(duration);
+                                let deadline: T::BlockNumber = deadline.unique_saturated_into();
+
                                 Pallet::<T>::deposit_event(Event::MessageWaited {
                                     id: dispatch.id(),
                                     origin,
                                     reason: MessageWaitedSystemReason::ProgramIsNotInitialized
                                         .into_reason(),
-                                    expiration: T::BlockNumber::zero(),
+                                    expiration: deadline,
                                 });
                                 common::waiting_init_append_message_id(
                                     program_id,
@@ -1156,30 +1928,17 @@ pub mod pallet {
 
                                 let message_id = dispatch.id();
                                 let program_id = dispatch.destination();
-                                WaitlistOf::<T>::insert(dispatch).unwrap_or_else(|e| {
-                                    unreachable!("Waitlist corrupted! {:?}", e)
-                                });
+                                if let Err(e) = WaitlistOf::<T>::insert(dispatch) {
+                                    Self::quarantine(message_id, None, e);
 
-                                let current_bn = <frame_system::Pallet<T>>::block_number()
-                                    .saturated_into::<u32>();
-
-                                let can_cover =
-                                    gas_limit.saturating_div(CostsPerBlockOf::<T>::waitlist());
-                                let reserve_for =
-                                    CostsPerBlockOf::<T>::reserve_for().saturated_into::<u32>();
-
-                                let duration = (can_cover as u32).saturating_sub(reserve_for);
-
-                                let deadline = current_bn.saturating_add(duration);
-                                let deadline: T::BlockNumber = deadline.unique_saturated_into();
+                                    continue;
+                                }
 
-                                TaskPoolOf::<T>::add(
+                                Pallet::<T>::set_waitlist_expiration(
+                                    message_id,
                                     deadline,
                                     ScheduledTask::RemoveFromWaitlist(program_id, message_id),
-                                )
-                                .unwrap_or_else(|e| {
-                                    unreachable!("Scheduling logic invalidated! {:?}", e)
-                                });
+                                );
                                 continue;
                             }
 
@@ -1191,12 +1950,13 @@ pub mod pallet {
                             );
 
                             let pages_data = if lazy_pages_enabled {
+                                // Lazy pages read program pages directly from storage.
+                                ext_manager.flush_pages();
                                 Default::default()
                             } else {
-                                match common::get_program_data_for_pages(
-                                    program_id.into_origin(),
-                                    prog.pages_with_data.iter(),
-                                ) {
+                                match ext_manager
+                                    .get_pages_data(program_id, prog.pages_with_data.iter())
+                                {
                                     Ok(data) => data,
                                     Err(err) => {
                                         log::error!(
@@ -1208,9 +1968,13 @@ pub mod pallet {
                                 }
                             };
 
+                            pages_loaded = pages_loaded.saturating_add(pages_data.len() as u32);
+
                             Some(ExecutableActorData {
                                 program,
                                 pages_data,
+                                gas_reservation_map: prog.gas_reservation_map,
+                                waitlist_full: WaitlistOf::<T>::is_full(&program_id),
                             })
                         } else {
                             // Reaching this branch is possible when init message was processed with failure, while other kind of messages
@@ -1241,10 +2005,11 @@ pub mod pallet {
                                 "Gas node is guaranteed to exist for the key due to earlier checks",
                             )
                         }
-                        Err(_err) => {
-                            // Error can only be due to invalid gas tree
-                            // TODO: handle appropriately
-                            unreachable!("Can never happen unless gas tree corrupted");
+                        Err(e) => {
+                            // Error can only be due to invalid gas tree.
+                            Self::quarantine(msg_id, Some(dispatch), e);
+
+                            continue;
                         }
                     };
 
@@ -1271,6 +2036,10 @@ pub mod pallet {
                         )
                     };
 
+                    if let Some(records) = journal_records.as_mut() {
+                        records.extend(journal.iter().filter_map(manager::journal_record));
+                    }
+
                     core_processor::handle_journal(journal, &mut ext_manager);
 
                     if T::DebugInfo::is_enabled() {
@@ -1285,19 +2054,41 @@ pub mod pallet {
                 }
             }
 
+            queue_runner.finish();
+
+            if let Some(records) = journal_records {
+                common::journal::export(&BlockJournal {
+                    number: <frame_system::Pallet<T>>::block_number(),
+                    parent_hash: <frame_system::Pallet<T>>::parent_hash(),
+                    records,
+                });
+            }
+
+            ext_manager.flush_pages();
+
             let post_data: QueuePostProcessingData = ext_manager.into();
             let total_handled = DequeuedOf::<T>::get();
 
+            let code_cache = post_data.stats.code_cache;
+
+            LastBlockRunStats::<T>::put(BlockRunStats {
+                messages_processed: total_handled,
+                pages_loaded,
+                ..post_data.stats
+            });
+
             if total_handled > 0 {
                 Self::deposit_event(Event::MessagesDispatched {
                     total: total_handled,
                     statuses: post_data.dispatch_statuses,
                     state_changes: post_data.state_changes,
+                    code_cache,
                 });
             }
         }
 
-        /// Sets `code` and metadata, if code doesn't exist in storage.
+        /// Sets `code` and metadata, if code doesn't exist in storage,
+        /// reserving code deposit from the uploader `who`.
         ///
         /// On success returns Blake256 hash of the `code`. If code already
         /// exists (*so, metadata exists as well*), returns unit `CodeAlreadyExists` error.
@@ -1310,6 +2101,15 @@ pub mod pallet {
         ) -> Result<CodeId, Error<T>> {
             let code_id = code_and_id.code_id();
 
+            ensure!(
+                !T::CodeStorage::exists(code_id),
+                Error::<T>::CodeAlreadyExists
+            );
+
+            let uploader = <T::AccountId as Origin>::from_origin(who);
+            GearProgramPallet::<T>::reserve_code_deposit(code_id, uploader)
+                .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
+
             let metadata = {
                 let block_number =
                     <frame_system::Pallet<T>>::block_number().unique_saturated_into();
@@ -1328,10 +2128,11 @@ pub mod pallet {
         ) -> Result<InstrumentedCode, DispatchError> {
             let original_code =
                 T::CodeStorage::get_original_code(code_id).ok_or(Error::<T>::CodeNotFound)?;
-            let code = Code::try_new(
+            let code = Code::try_new_with_floats(
                 original_code,
                 schedule.instruction_weights.version,
                 |module| schedule.rules(module),
+                schedule.limits.floats.canonicalize(),
             )
             .map_err(|e| {
                 log::debug!("Code failed to load: {:?}", e);
@@ -1346,6 +2147,194 @@ pub mod pallet {
         }
     }
 
+    impl<T: Config> Pallet<T>
+    where
+        T::AccountId: Origin,
+    {
+        /// Refunds `gas_left` of the consumed gas tree, which origin node is `origin_key`.
+        ///
+        /// Accounts, which topped up gas of the tree, are refunded first
+        /// (up to their contributions), the rest goes to `external`.
+        pub(crate) fn refund_gas(origin_key: MessageId, external: T::AccountId, gas_left: u64) {
+            let mut gas_left = gas_left;
+
+            for (payer, topped_up) in GasTopUps::<T>::drain_prefix(origin_key) {
+                let refund = gas_left.min(topped_up);
+                gas_left -= refund;
+
+                Self::withdraw_gas(&payer, refund);
+            }
+
+            Self::withdraw_gas(&external, gas_left);
+        }
+
+        /// Refunds `amount` of gas out of the bank to `to`, logging failures.
+        ///
+        /// Refund could fail only if the account was reaped after paying for the gas.
+        /// Funds, which weren't refunded, are accounted as dust.
+        fn withdraw_gas(to: &T::AccountId, amount: u64) {
+            if let Err(e) = GearBank::<T>::withdraw_gas(to, amount) {
+                log::debug!(
+                    target: "essential",
+                    "Failed to refund {:?} of gas to {:?}: {:?}",
+                    amount,
+                    to,
+                    e,
+                );
+
+                GearBank::<T>::add_dust(T::GasPrice::gas_price(amount));
+            }
+        }
+
+        /// Returns existential deposit values sent by or to programs are checked against.
+        ///
+        /// It's zero, if programs are exempt from existential deposit.
+        pub(crate) fn program_existential_deposit() -> u128 {
+            if T::ExistentialDepositFreePrograms::get() {
+                0
+            } else {
+                <T as Config>::Currency::minimum_balance().unique_saturated_into()
+            }
+        }
+
+        /// Sets block number, at which rent of the program expires,
+        /// rescheduling pausing of the program to it.
+        pub(crate) fn set_program_expiration(program_id: ProgramId, expiration: T::BlockNumber) {
+            Self::remove_program_expiration(program_id);
+
+            match TaskPoolOf::<T>::add(expiration, ScheduledTask::PauseProgram(program_id)) {
+                Ok(()) => ProgramExpirations::<T>::insert(program_id, expiration),
+                Err(e) => log::error!(
+                    target: "essential",
+                    "Pausing of program {:?} isn't scheduled: {:?}",
+                    program_id,
+                    e,
+                ),
+            }
+        }
+
+        /// Removes block number, at which rent of the program expires,
+        /// along with the task pausing the program, if any.
+        pub(crate) fn remove_program_expiration(program_id: ProgramId) {
+            if let Some(expiration) = ProgramExpirations::<T>::take(program_id) {
+                let _ =
+                    TaskPoolOf::<T>::delete(expiration, ScheduledTask::PauseProgram(program_id));
+            }
+        }
+
+        /// Schedules the task on the block number, at which holding
+        /// of the message in the waitlist expires.
+        pub(crate) fn set_waitlist_expiration(
+            message_id: MessageId,
+            expiration: T::BlockNumber,
+            task: ScheduledTask<T::AccountId>,
+        ) {
+            match TaskPoolOf::<T>::add(expiration, task) {
+                Ok(()) => WaitlistExpirations::<T>::insert(message_id, expiration),
+                Err(e) => Self::quarantine(message_id, None, e),
+            }
+        }
+
+        /// Schedules awakening of the waitlisted message on the block number,
+        /// unless the message is already scheduled to wake on it by another
+        /// wake or by its own deadline.
+        pub(crate) fn set_delayed_wake(
+            program_id: ProgramId,
+            message_id: MessageId,
+            waking_bn: T::BlockNumber,
+        ) {
+            let task = ScheduledTask::WakeMessage(program_id, message_id);
+
+            if TaskPoolOf::<T>::contains(&waking_bn, &task) {
+                return;
+            }
+
+            match TaskPoolOf::<T>::add(waking_bn, task) {
+                Ok(()) => DelayedWakes::<T>::insert(message_id, waking_bn, ()),
+                Err(e) => Self::quarantine(message_id, None, e),
+            }
+        }
+
+        /// Removes the task scheduled on the expiration of holding the message
+        /// in the waitlist along with delayed wakes of the message, so they
+        /// don't affect the message if waited again.
+        pub(crate) fn remove_waitlist_expiration(program_id: ProgramId, message_id: MessageId) {
+            if let Some(expiration) = WaitlistExpirations::<T>::take(message_id) {
+                let _ = TaskPoolOf::<T>::delete(
+                    expiration,
+                    ScheduledTask::WakeMessage(program_id, message_id),
+                );
+                let _ = TaskPoolOf::<T>::delete(
+                    expiration,
+                    ScheduledTask::RemoveFromWaitlist(program_id, message_id),
+                );
+            }
+
+            for (waking_bn, ()) in DelayedWakes::<T>::drain_prefix(message_id) {
+                let _ = TaskPoolOf::<T>::delete(
+                    waking_bn,
+                    ScheduledTask::WakeMessage(program_id, message_id),
+                );
+            }
+        }
+
+        /// Removes tasks scheduled on expirations of holding the messages
+        /// in the waitlist of the program, which is about to be paused.
+        pub(crate) fn remove_waitlist_expirations(program_id: ProgramId) {
+            WaitlistOf::<T>::iter_key(program_id).for_each(|(dispatch, _)| {
+                Self::remove_waitlist_expiration(program_id, dispatch.id())
+            });
+        }
+
+        /// Schedules removal of the messages from the waitlist of the resumed
+        /// program. Deadlines requested by the program aren't kept while it's
+        /// paused, so messages are held as long as they are able to pay for.
+        pub(crate) fn set_waitlist_expirations(program_id: ProgramId) {
+            let current_bn = <frame_system::Pallet<T>>::block_number().saturated_into::<u32>();
+            let limits = T::Schedule::get().limits;
+
+            for (dispatch, _) in WaitlistOf::<T>::iter_key(program_id) {
+                let message_id = dispatch.id();
+
+                let limit = match GasHandlerOf::<T>::get_limit(message_id) {
+                    Ok(Some((limit, _))) => limit,
+                    _ => {
+                        log::error!("Waitlisted message {:?} has no gas limit", message_id);
+                        continue;
+                    }
+                };
+
+                let duration = limits.wait_duration(Self::hold_duration(limit, HoldKind::Waitlist));
+                let deadline: T::BlockNumber =
+                    current_bn.saturating_add(duration).unique_saturated_into();
+
+                Self::set_waitlist_expiration(
+                    message_id,
+                    deadline,
+                    ScheduledTask::RemoveFromWaitlist(program_id, message_id),
+                );
+            }
+        }
+
+        /// Schedules removal of the message from the user's mailbox
+        /// on the block number, at which holding of it expires.
+        pub(crate) fn set_mailbox_expiration(
+            user_id: T::AccountId,
+            message_id: MessageId,
+            expiration: T::BlockNumber,
+        ) {
+            let task = ScheduledTask::RemoveFromMailbox(user_id, message_id);
+
+            match TaskPoolOf::<T>::add(expiration, task) {
+                Ok(()) => MailboxHolds::<T>::insert(
+                    message_id,
+                    (<frame_system::Pallet<T>>::block_number(), expiration),
+                ),
+                Err(e) => Self::quarantine(message_id, None, e),
+            }
+        }
+    }
+
     #[pallet::call]
     impl<T: Config> Pallet<T>
     where
@@ -1380,9 +2369,12 @@ pub mod pallet {
                 Error::<T>::CodeTooLarge
             );
 
-            let code = Code::try_new(code, schedule.instruction_weights.version, |module| {
-                schedule.rules(module)
-            })
+            let code = Code::try_new_with_floats(
+                code,
+                schedule.instruction_weights.version,
+                |module| schedule.rules(module),
+                schedule.limits.floats.canonicalize(),
+            )
             .map_err(|e| {
                 log::debug!("Code failed to load: {:?}", e);
                 Error::<T>::FailedToConstructProgram
@@ -1406,6 +2398,49 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// Saves type metadata for the code in storage.
+        ///
+        /// The metadata contains scale-encoded type registry and id of the program state
+        /// type within it, so that state, read from any program of the code, could be decoded
+        /// without program-specific bindings.
+        ///
+        /// Only the author of the code is able to upload its type metadata. Previously uploaded
+        /// metadata is overwritten.
+        ///
+        /// Parameters:
+        /// - `code_id`: id of the code, which metadata is uploaded.
+        /// - `registry`: scale-encoded portable type registry.
+        /// - `state_type`: id of the state type within `registry`.
+        ///
+        /// Emits the following events:
+        /// - `CodeChanged { change: TypeMetadataChanged, .. }` when the metadata is saved in storage.
+        #[pallet::weight(
+            <T as Config>::WeightInfo::submit_code_metadata(registry.len() as u32)
+        )]
+        pub fn submit_code_metadata(
+            origin: OriginFor<T>,
+            code_id: CodeId,
+            registry: Vec<u8>,
+            state_type: u32,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let metadata = T::CodeStorage::get_metadata(code_id).ok_or(Error::<T>::CodeNotFound)?;
+            ensure!(
+                metadata.author == who.into_origin(),
+                Error::<T>::NotCodeAuthor
+            );
+
+            T::CodeStorage::set_type_metadata(code_id, CodeTypeMetadata::new(registry, state_type));
+
+            Self::deposit_event(Event::CodeChanged {
+                id: code_id,
+                change: CodeChangeKind::TypeMetadataChanged,
+            });
+
+            Ok(().into())
+        }
+
         /// Creates program initialization request (message), that is scheduled to be run in the same block.
         ///
         /// There are no guarantees that initialization message will be run in the same block due to block
@@ -1463,8 +2498,14 @@ pub mod pallet {
                 Error::<T>::GasLimitTooHigh
             );
 
+            // Check that provided `gas_limit` value is enough for init dispatch
+            ensure!(
+                gas_limit >= T::GasMinimums::get().init,
+                Error::<T>::GasLimitTooLow
+            );
+
             let numeric_value: u128 = value.unique_saturated_into();
-            let minimum: u128 = <T as Config>::Currency::minimum_balance().unique_saturated_into();
+            let minimum = Self::program_existential_deposit();
 
             // Check that provided `value` equals 0 or greater than existential deposit
             ensure!(
@@ -1479,9 +2520,12 @@ pub mod pallet {
                 Error::<T>::CodeTooLarge
             );
 
-            let code = Code::try_new(code, schedule.instruction_weights.version, |module| {
-                schedule.rules(module)
-            })
+            let code = Code::try_new_with_floats(
+                code,
+                schedule.instruction_weights.version,
+                |module| schedule.rules(module),
+                schedule.limits.floats.canonicalize(),
+            )
             .map_err(|e| {
                 log::debug!("Code failed to load: {:?}", e);
                 Error::<T>::FailedToConstructProgram
@@ -1494,6 +2538,10 @@ pub mod pallet {
 
             let code_and_id = CodeAndId::new(code);
 
+            let init_payload = init_payload
+                .try_into()
+                .map_err(|_| Error::<T>::PayloadSizeLimitExceeded)?;
+
             let packet = InitPacket::new_with_gas(
                 code_and_id.code_id(),
                 salt,
@@ -1509,11 +2557,9 @@ pub mod pallet {
                 Error::<T>::ProgramAlreadyExists
             );
 
-            let reserve_fee = T::GasPrice::gas_price(gas_limit);
-
-            // First we reserve enough funds on the account to pay for `gas_limit`
-            // and to transfer declared value.
-            <T as Config>::Currency::reserve(&who, reserve_fee + value)
+            // First we move enough funds from the account to the bank
+            // to pay for `gas_limit` and to transfer declared value.
+            GearBank::<T>::deposit(&who, gas_limit, value)
                 .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
 
             let origin = who.clone().into_origin();
@@ -1522,12 +2568,14 @@ pub mod pallet {
 
             // By that call we follow the guarantee that we have in `Self::submit_code` -
             // if there's code in storage, there's also metadata for it.
-            if let Ok(code_hash) = Self::set_code_with_metadata(code_and_id, origin) {
+            if !T::CodeStorage::exists(code_id) {
+                Self::set_code_with_metadata(code_and_id, origin)?;
+
                 // TODO: replace this temporary (`None`) value
                 // for expiration block number with properly
                 // calculated one (issues #646 and #969).
                 Self::deposit_event(Event::CodeChanged {
-                    id: code_hash,
+                    id: code_id,
                     change: CodeChangeKind::Active { expiration: None },
                 });
             }
@@ -1535,6 +2583,7 @@ pub mod pallet {
             let message_id = Self::next_message_id(origin);
 
             ExtManager::<T>::default().set_program(program_id, code_id, message_id);
+            GearProgramPallet::<T>::set_program_creator(program_id, who.clone());
 
             let _ = GasHandlerOf::<T>::create(
                 who.clone(),
@@ -1578,7 +2627,13 @@ pub mod pallet {
         ///
         /// Emits the following events:
         /// - `DispatchMessageEnqueued(MessageInfo)` when dispatch message is placed in the queue.
-        #[pallet::weight(<T as Config>::WeightInfo::send_message(payload.len() as u32))]
+        #[pallet::weight(
+            if value.is_zero() && payload.is_empty() {
+                <T as Config>::WeightInfo::send_message_without_value()
+            } else {
+                <T as Config>::WeightInfo::send_message(payload.len() as u32)
+            }
+        )]
         pub fn send_message(
             origin: OriginFor<T>,
             destination: ProgramId,
@@ -1590,7 +2645,11 @@ pub mod pallet {
             let origin = who.clone().into_origin();
 
             let numeric_value: u128 = value.unique_saturated_into();
-            let minimum: u128 = <T as Config>::Currency::minimum_balance().unique_saturated_into();
+            let minimum: u128 = if GearProgramPallet::<T>::program_exists(destination) {
+                Self::program_existential_deposit()
+            } else {
+                <T as Config>::Currency::minimum_balance().unique_saturated_into()
+            };
 
             // Check that provided `gas_limit` value does not exceed the block gas limit
             ensure!(
@@ -1604,6 +2663,10 @@ pub mod pallet {
                 Error::<T>::ValueLessThanMinimal
             );
 
+            let payload = payload
+                .try_into()
+                .map_err(|_| Error::<T>::PayloadSizeLimitExceeded)?;
+
             let message = HandleMessage::from_packet(
                 Self::next_message_id(origin),
                 HandlePacket::new_with_gas(
@@ -1620,16 +2683,16 @@ pub mod pallet {
                     Error::<T>::ProgramIsTerminated
                 );
 
+                // Check that provided `gas_limit` value is enough for handle dispatch
+                ensure!(
+                    gas_limit >= T::GasMinimums::get().handle,
+                    Error::<T>::GasLimitTooLow
+                );
+
                 // Message is not guaranteed to be executed, that's why value is not immediately transferred.
                 // That's because destination can fail to be initialized, while this dispatch message is next
-                // in the queue.
-                <T as Config>::Currency::reserve(&who, value.unique_saturated_into())
-                    .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
-
-                let gas_limit_reserve = T::GasPrice::gas_price(gas_limit);
-
-                // First we reserve enough funds on the account to pay for `gas_limit`
-                <T as Config>::Currency::reserve(&who, gas_limit_reserve)
+                // in the queue. So value is held by the bank along with funds to pay for `gas_limit`.
+                GearBank::<T>::deposit(&who, gas_limit, value)
                     .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
 
                 let _ = GasHandlerOf::<T>::create(who.clone(), message.id(), gas_limit);
@@ -1689,7 +2752,8 @@ pub mod pallet {
             let who = ensure_signed(origin)?;
 
             let numeric_value: u128 = value.unique_saturated_into();
-            let minimum: u128 = <T as Config>::Currency::minimum_balance().unique_saturated_into();
+            // Replies are addressed to programs only.
+            let minimum = Self::program_existential_deposit();
 
             // Ensure the `gas_limit` allows the extrinsic to fit into a block
             ensure!(
@@ -1697,16 +2761,25 @@ pub mod pallet {
                 Error::<T>::GasLimitTooHigh
             );
 
+            // Check that provided `gas_limit` value is enough for reply dispatch
+            ensure!(
+                gas_limit >= T::GasMinimums::get().reply,
+                Error::<T>::GasLimitTooLow
+            );
+
             // Check that provided `value` equals 0 or greater than existential deposit
             ensure!(
                 0 == numeric_value || numeric_value >= minimum,
                 Error::<T>::ValueLessThanMinimal
             );
 
+            let payload = payload
+                .try_into()
+                .map_err(|_| Error::<T>::PayloadSizeLimitExceeded)?;
+
             // Claim outstanding value from the original message first
-            let original_message = MailboxOf::<T>::remove(who.clone(), reply_to_id)?;
-            // TODO: burn here for holding #646.
             let mut ext_manager: ExtManager<T> = Default::default();
+            let original_message = ext_manager.read_message_impl(who.clone(), reply_to_id, true)?;
             ext_manager.message_consumed(reply_to_id);
             let destination = original_message.source();
 
@@ -1723,8 +2796,8 @@ pub mod pallet {
 
             // Message is not guaranteed to be executed, that's why value is not immediately transferred.
             // That's because destination can fail to be initialized, while this dispatch message is next
-            // in the queue.
-            <T as Config>::Currency::reserve(&who, value.unique_saturated_into())
+            // in the queue. So value is held by the bank along with funds to pay for `gas_limit`.
+            GearBank::<T>::deposit(&who, gas_limit, value)
                 .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
 
             let origin = who.clone();
@@ -1734,12 +2807,6 @@ pub mod pallet {
                 ReplyPacket::new_with_gas(payload, gas_limit, value.unique_saturated_into());
             let message = ReplyMessage::from_packet(message_id, packet);
 
-            let gas_limit_reserve = T::GasPrice::gas_price(gas_limit);
-
-            // First we reserve enough funds on the account to pay for `gas_limit`
-            <T as Config>::Currency::reserve(&who, gas_limit_reserve)
-                .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
-
             let _ = GasHandlerOf::<T>::create(origin.clone(), message_id, gas_limit);
 
             Self::deposit_event(Event::UserMessageRead {
@@ -1766,14 +2833,23 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// Claim value from message that is in user's mailbox.
+        ///
+        /// The origin must be Signed and be the recipient of the message.
+        /// Value, held by the message, is transferred to the recipient and
+        /// the message is removed from the mailbox.
+        ///
+        /// Parameters:
+        /// - `message_id`: id of the message in the mailbox.
+        ///
+        /// - `UserMessageRead` when message is claimed.
         #[pallet::weight(T::DbWeight::get().writes(1))]
-        pub fn claim_value_from_mailbox(
+        pub fn claim_value(
             origin: OriginFor<T>,
             message_id: MessageId,
         ) -> DispatchResultWithPostInfo {
-            let _ = MailboxOf::<T>::remove(ensure_signed(origin)?, message_id)?;
-            // TODO: burn here for holding #646.
             let mut ext_manager: ExtManager<T> = Default::default();
+            let _ = ext_manager.read_message_impl(ensure_signed(origin)?, message_id, true)?;
             ext_manager.message_consumed(message_id);
 
             Self::deposit_event(Event::UserMessageRead {
@@ -1784,6 +2860,177 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// Tops up gas of the message, which is in flight.
+        ///
+        /// The extrinsic was created to rescue messages stuck in the waitlist or
+        /// the dispatch stash, which gas limit proves to be too small. Anyone can
+        /// call it, paying for the additional gas. Once the message gas tree is
+        /// consumed, gas left is refunded to additional payers in first place
+        /// (up to their contributions) and only then to the original payer.
+        ///
+        /// Parameters:
+        /// - `message_id`: id of the message, which gas is topped up.
+        /// - `amount`: amount of the additional gas.
+        ///
+        /// - `GasToppedUp` when gas of the message is topped up.
+        #[pallet::weight(<T as Config>::WeightInfo::top_up_gas())]
+        pub fn top_up_gas(
+            origin: OriginFor<T>,
+            message_id: MessageId,
+            amount: u64,
+        ) -> DispatchResultWithPostInfo {
+            let payer = ensure_signed(origin)?;
+
+            let origin_key = GasHandlerOf::<T>::get_origin_key(message_id)
+                .ok()
+                .flatten()
+                .ok_or(Error::<T>::NoMessageTree)?;
+
+            GearBank::<T>::deposit(&payer, amount, Zero::zero())
+                .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
+
+            let _ = GasHandlerOf::<T>::refill(message_id, amount)
+                .map_err(|_| Error::<T>::NoMessageTree)?;
+
+            GasTopUps::<T>::mutate(origin_key, &payer, |topped_up| {
+                *topped_up = topped_up.saturating_add(amount)
+            });
+
+            Self::deposit_event(Event::GasToppedUp {
+                id: message_id,
+                payer,
+                amount,
+            });
+
+            Ok(().into())
+        }
+
+        /// Pays rent of the active program for `block_count` blocks.
+        ///
+        /// Anyone can pay rent of any program. The payment is transferred to the
+        /// block author and prolongs the period the program stays active for,
+        /// once it's over, the program is paused.
+        ///
+        /// Parameters:
+        /// - `program_id`: id of the program, which rent is paid.
+        /// - `block_count`: amount of blocks the rent is paid for.
+        ///
+        /// - `ProgramRentPaid` when the rent is paid.
+        #[pallet::weight(<T as Config>::WeightInfo::pay_program_rent())]
+        pub fn pay_program_rent(
+            origin: OriginFor<T>,
+            program_id: ProgramId,
+            block_count: T::BlockNumber,
+        ) -> DispatchResultWithPostInfo {
+            let payer = ensure_signed(origin)?;
+
+            ensure!(
+                matches!(
+                    common::get_program(program_id.into_origin()),
+                    Some(Program::Active(_))
+                ),
+                Error::<T>::InactiveProgram
+            );
+
+            let rent = T::GasPrice::gas_price(
+                CostsPerBlockOf::<T>::program().saturating_mul(block_count.unique_saturated_into()),
+            );
+
+            if let Some(author) = Self::block_author() {
+                <T as Config>::Currency::transfer(
+                    &payer,
+                    &author,
+                    rent,
+                    ExistenceRequirement::KeepAlive,
+                )?;
+            } else {
+                let _ = <T as Config>::Currency::withdraw(
+                    &payer,
+                    rent,
+                    WithdrawReasons::FEE,
+                    ExistenceRequirement::KeepAlive,
+                )?;
+            }
+
+            // Expiration is set for each active program on its creation
+            // or by the migration, so the current block is only a fallback.
+            let expiration = Self::program_expiration(program_id)
+                .unwrap_or_else(<frame_system::Pallet<T>>::block_number)
+                .saturating_add(block_count);
+
+            Self::set_program_expiration(program_id, expiration);
+
+            Self::deposit_event(Event::ProgramChanged {
+                id: program_id,
+                change: ProgramChangeKind::Active { expiration },
+            });
+
+            Self::deposit_event(Event::ProgramRentPaid {
+                id: program_id,
+                payer,
+                expiration,
+            });
+
+            Ok(().into())
+        }
+
+        /// Pauses or resumes the message queue processing.
+        ///
+        /// The origin must be `Config::QueueProcessingOrigin`. While the
+        /// processing is paused, messages are still accepted into the queue.
+        #[pallet::weight(T::DbWeight::get().writes(1))]
+        pub fn set_queue_processing(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+            T::QueueProcessingOrigin::ensure_origin(origin)?;
+
+            QueueProcessingEnabled::<T>::put(enabled);
+
+            Self::deposit_event(Event::QueueProcessingSet { enabled });
+
+            Ok(())
+        }
+
+        /// Bans the program from execution.
+        ///
+        /// The origin must be `Config::ProgramBanOrigin`. Messages to the
+        /// program are answered with error replies without execution, while
+        /// queued messages sent by the program are dropped.
+        #[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+        pub fn ban_program(origin: OriginFor<T>, program_id: ProgramId) -> DispatchResult {
+            T::ProgramBanOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                common::program_exists(program_id.into_origin()),
+                Error::<T>::ProgramNotFound
+            );
+            ensure!(
+                !BannedPrograms::<T>::contains_key(program_id),
+                Error::<T>::ProgramAlreadyBanned
+            );
+
+            BannedPrograms::<T>::insert(program_id, ());
+
+            Self::deposit_event(Event::ProgramBanned { id: program_id });
+
+            Ok(())
+        }
+
+        /// Unbans the program, so its messages are executed again.
+        ///
+        /// The origin must be `Config::ProgramBanOrigin`.
+        #[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+        pub fn unban_program(origin: OriginFor<T>, program_id: ProgramId) -> DispatchResult {
+            T::ProgramBanOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                BannedPrograms::<T>::take(program_id).is_some(),
+                Error::<T>::ProgramNotBanned
+            );
+
+            Self::deposit_event(Event::ProgramUnbanned { id: program_id });
+
+            Ok(())
+        }
+
         /// Reset all pallet associated storage.
         #[pallet::weight(0)]
         pub fn reset(origin: OriginFor<T>) -> DispatchResult {
@@ -1830,4 +3077,37 @@ pub mod pallet {
             Ok(())
         }
     }
+
+    /// Callback on pausing of the program.
+    ///
+    /// Program isn't paused on rent expiration anymore, so the expiration
+    /// is removed along with its task. Waitlist of the program is taken
+    /// along with it, so tasks scheduled for the waitlisted messages
+    /// are removed as well.
+    pub struct OnProgramPause<T: Config>(PhantomData<T>);
+
+    impl<T: Config> Callback<ProgramId> for OnProgramPause<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call(program_id: &ProgramId) {
+            Pallet::<T>::remove_program_expiration(*program_id);
+            Pallet::<T>::remove_waitlist_expirations(*program_id);
+        }
+    }
+
+    /// Callback on resuming of the program.
+    ///
+    /// Restored waitlist of the program is scheduled for removal
+    /// the same way as messages waited without duration.
+    pub struct OnProgramResume<T: Config>(PhantomData<T>);
+
+    impl<T: Config> Callback<ProgramId> for OnProgramResume<T>
+    where
+        T::AccountId: Origin,
+    {
+        fn call(program_id: &ProgramId) {
+            Pallet::<T>::set_waitlist_expirations(*program_id);
+        }
+    }
 }
@@ -17,23 +17,30 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    manager::{ExtManager, TOL},
-    Authorship, Config, CostsPerBlockOf, Event, GasAllowanceOf, GasHandlerOf, GearProgramPallet,
-    MailboxOf, Pallet, QueueOf, SentOf, TaskPoolOf, WaitlistOf,
+    manager::ExtManager, Config, DebugInfo, DispatchStashOf, Event, GasAllowanceOf, GasHandlerOf,
+    GearBank, GearProgramPallet, MailboxOf, Pallet, QueueOf, ReplyChainDepth, SentOf, TaskPoolOf,
+    WaitlistOf,
 };
-use common::{event::*, scheduler::*, storage::*, CodeStorage, GasPrice, GasTree, Origin, Program};
-use core_processor::common::{
-    DispatchOutcome as CoreDispatchOutcome, ExecutionErrorReason, JournalHandler,
-};
-use frame_support::traits::{
-    BalanceStatus, Currency, ExistenceRequirement, Get, Imbalance, ReservableCurrency,
+use codec::Encode;
+use common::{
+    event::*, journal::JournalRecord, scheduler::*, storage::*, CodeStorage, GasTree, Origin,
+    Program,
 };
+use core::convert::TryInto;
+use core_processor::common::{DispatchOutcome as CoreDispatchOutcome, JournalHandler, JournalNote};
+use frame_support::traits::{Contains, Currency, ExistenceRequirement, Get, Imbalance};
 use gear_core::{
-    ids::{CodeId, MessageId, ProgramId},
+    gas::GasReservationSlot,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber},
-    message::{Dispatch, StoredDispatch},
+    message::{
+        Dispatch, ExitCode, MessageWaitedType, ProgramEvent, ReplyMessage, SignalMessage,
+        StoredDispatch,
+    },
 };
-use sp_runtime::traits::{SaturatedConversion, UniqueSaturatedInto, Zero};
+use gear_core_errors::ErrorReplyReason;
+use primitive_types::H256;
+use sp_runtime::traits::{One, SaturatedConversion, Saturating, UniqueSaturatedInto, Zero};
 
 use sp_std::{
     collections::{btree_map::BTreeMap, btree_set::BTreeSet},
@@ -62,8 +69,7 @@ where
                             reason: MessageWokenSystemReason::ProgramGotInitialized.into_reason(),
                         });
 
-                        QueueOf::<T>::queue(m)
-                            .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+                        Pallet::<T>::queue_dispatch(m);
                     } else {
                         log::error!("Cannot find message in wl")
                     }
@@ -106,14 +112,12 @@ where
                 wake_waiting_init_msgs(program_id);
                 common::set_program_initialized(program_id.into_origin());
 
-                // TODO: replace this temporary (zero) value for expiration
-                // block number with properly calculated one
-                // (issues #646 and #969).
+                let expiration = Pallet::<T>::program_expiration(program_id)
+                    .unwrap_or_else(<frame_system::Pallet<T>>::block_number);
+
                 Pallet::<T>::deposit_event(Event::ProgramChanged {
                     id: program_id,
-                    change: ProgramChangeKind::Active {
-                        expiration: T::BlockNumber::zero(),
-                    },
+                    change: ProgramChangeKind::Active { expiration },
                 });
 
                 DispatchStatus::Success
@@ -132,8 +136,34 @@ where
                 // dequeued. The other case is async init.
                 wake_waiting_init_msgs(program_id);
 
+                let code_id = match common::get_program(program_id.into_origin()) {
+                    Some(Program::Active(program)) => CodeId::from_origin(program.code_hash),
+                    _ => unreachable!("Only active program can cause init failure"),
+                };
+
                 common::set_program_terminated_status(program_id.into_origin())
                     .expect("Only active program can cause init failure");
+                self.forget_pages(program_id);
+                GearProgramPallet::<T>::remove_program_creator(program_id);
+                Pallet::<T>::remove_program_expiration(program_id);
+
+                // Program failed to init holds no value, so its account isn't needed anymore.
+                Self::release_program_account(program_id);
+
+                // Code could be left without programs, so it's pruned
+                // within the next block, if no program is created from it.
+                if GearProgramPallet::<T>::remove_code_user(code_id) {
+                    let bn = <frame_system::Pallet<T>>::block_number().saturating_add(One::one());
+
+                    if let Err(e) = TaskPoolOf::<T>::add(bn, ScheduledTask::RemoveCode(code_id)) {
+                        log::error!(
+                            target: "essential",
+                            "Removal of code {:?} isn't scheduled: {:?}",
+                            code_id,
+                            e,
+                        );
+                    }
+                }
 
                 DispatchStatus::Failed
             }
@@ -153,59 +183,10 @@ where
         log::debug!("Burned: {:?} from: {:?}", amount, message_id);
 
         GasAllowanceOf::<T>::decrease(amount);
+        self.stats.gas_burned = self.stats.gas_burned.saturating_add(amount);
 
         match GasHandlerOf::<T>::spend(message_id, amount) {
-            Ok(_) => {
-                match GasHandlerOf::<T>::get_external(message_id) {
-                    Ok(maybe_origin) => {
-                        if let Some(origin) = maybe_origin {
-                            let charge = T::GasPrice::gas_price(amount);
-                            if let Some(author) = Authorship::<T>::author() {
-                                match <T as Config>::Currency::repatriate_reserved(
-                                    &origin,
-                                    &author,
-                                    charge,
-                                    BalanceStatus::Free,
-                                ) {
-                                    Ok(leftover) => {
-                                        if leftover > TOL.unique_saturated_into() {
-                                            log::debug!(
-                                                target: "essential",
-                                                "Reserved funds not fully repatriated from {:?} to 0x{:?}: amount = {:?}, leftover = {:?}",
-                                                origin,
-                                                author,
-                                                charge,
-                                                leftover,
-                                            );
-                                        }
-                                    }
-                                    Err(e) => {
-                                        log::debug!(
-                                            target: "essential",
-                                            "Failure to repatriate reserves of {:?} from {:?} to 0x{:?}: {:?}",
-                                            charge,
-                                            origin,
-                                            author,
-                                            e,
-                                        )
-                                    }
-                                }
-                            }
-                        } else {
-                            log::debug!(
-                                target: "essential",
-                                "Failed to get limit of {:?}",
-                                message_id,
-                            );
-                        }
-                    }
-                    Err(_err) => {
-                        // We only can get an error here if the gas tree is invalidated
-                        // TODO: handle appropriately
-                        unreachable!("Can never happen unless gas tree corrupted");
-                    }
-                }
-            }
+            Ok(_) => GearBank::<T>::spend_gas(amount),
             Err(err) => {
                 log::debug!(
                     "Error spending {:?} gas for message_id {:?}: {:?}",
@@ -218,37 +199,60 @@ where
     }
 
     fn exit_dispatch(&mut self, id_exited: ProgramId, value_destination: ProgramId) {
+        // Messages from the waitlist are queued back, so they will receive
+        // error replies as addressed to the inactive program.
+        //
         // TODO: update gas limit in `ValueTree` here (issue #1022).
         for (message, bn) in WaitlistOf::<T>::drain_key(id_exited) {
+            Pallet::<T>::remove_waitlist_expiration(id_exited, message.id());
             self.charge_for_wake(message.id(), bn);
 
-            QueueOf::<T>::queue(message)
-                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+            Pallet::<T>::queue_dispatch(message);
         }
 
         let _ = common::waiting_init_take_messages(id_exited);
-        let res = common::set_program_terminated_status(id_exited.into_origin());
+        let res = common::set_program_exited_status(id_exited.into_origin(), value_destination);
         assert!(res.is_ok(), "`exit` can be called only from active program");
+        self.forget_pages(id_exited);
+        GearProgramPallet::<T>::remove_program_creator(id_exited);
+        Pallet::<T>::remove_program_expiration(id_exited);
 
         let program_account = &<T::AccountId as Origin>::from_origin(id_exited.into_origin());
-        let balance = <T as Config>::Currency::total_balance(program_account);
+        let balance = <T as Config>::Currency::free_balance(program_account);
         if !balance.is_zero() {
-            <T as Config>::Currency::transfer(
+            let res = <T as Config>::Currency::transfer(
                 program_account,
                 &<T::AccountId as Origin>::from_origin(value_destination.into_origin()),
                 balance,
                 ExistenceRequirement::AllowDeath,
-            )
-            .expect("balance is not zero; should not fail");
+            );
+
+            // Balance of program exempt from existential deposit
+            // could be not enough to create an account of the user.
+            if !T::ExistentialDepositFreePrograms::get() {
+                res.expect("balance is not zero; should not fail");
+            }
+        }
+
+        // Balance, which couldn't be sent to the inheritor, keeps
+        // the account of the exited program alive.
+        if <T as Config>::Currency::free_balance(program_account).is_zero() {
+            Self::release_program_account(id_exited);
         }
     }
 
     fn message_consumed(&mut self, message_id: MessageId) {
+        ReplyChainDepth::<T>::remove(message_id);
+
+        let origin_key = GasHandlerOf::<T>::get_origin_key(message_id)
+            .ok()
+            .flatten()
+            .unwrap_or(message_id);
+
         match GasHandlerOf::<T>::consume(message_id) {
-            Err(_e) => {
-                // We only can get an error here if the gas tree is invalidated
-                // TODO: handle appropriately
-                unreachable!("Can never happen unless gas tree corrupted");
+            Err(e) => {
+                // We only can get an error here if the gas tree is invalidated.
+                Pallet::<T>::quarantine(message_id, None, e);
             }
             Ok(maybe_outcome) => {
                 if let Some((neg_imbalance, external)) = maybe_outcome {
@@ -261,24 +265,56 @@ where
                             external
                         );
 
-                        let refund = T::GasPrice::gas_price(gas_left);
+                        Pallet::<T>::refund_gas(origin_key, external, gas_left);
 
-                        let _ = <T as Config>::Currency::unreserve(&external, refund);
+                        Pallet::<T>::deposit_event(Event::GasRefunded {
+                            message_id,
+                            amount: gas_left,
+                        });
                     }
                 }
             }
         }
     }
 
-    fn send_dispatch(&mut self, message_id: MessageId, dispatch: Dispatch) {
+    fn send_dispatch(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: u32,
+        reservation: Option<ReservationId>,
+    ) {
         let gas_limit = dispatch.gas_limit();
+        let source = dispatch.source();
+
+        // Dispatches sent from reservation take gas from its node.
+        let gas_node = reservation
+            .map(|id| MessageId::from_origin(id.into_origin()))
+            .unwrap_or(message_id);
+
         let dispatch = dispatch.into_stored();
 
-        if dispatch.value() != 0 {
-            <T as Config>::Currency::reserve(
+        // Depth of the chain of messages sent by programs to each other,
+        // which this dispatch continues. Messages sent by users, including
+        // replies on mailbox messages, start new chains.
+        let depth = ReplyChainDepth::<T>::get(message_id).saturating_add(1);
+        let limit = T::ReplyChainDepthLimit::get();
+
+        // Rejected dispatches aren't sent: their value
+        // isn't moved to the bank and gas isn't split.
+        let rejection = if T::ForbiddenDestinations::contains(&dispatch.destination()) {
+            Some(ErrorReplyReason::ForbiddenDestination)
+        } else if depth > limit && self.check_program_id(&dispatch.destination()) {
+            Some(ErrorReplyReason::ReplyChainTooDeep)
+        } else {
+            None
+        };
+
+        if dispatch.value() != 0 && rejection.is_none() {
+            GearBank::<T>::deposit_value(
                 &<T::AccountId as Origin>::from_origin(dispatch.source().into_origin()),
                 dispatch.value().unique_saturated_into(),
-            ).unwrap_or_else(|_| unreachable!("Value reservation can't fail due to value sending rules. For more info, see module docs."));
+            ).unwrap_or_else(|_| unreachable!("Value deposit can't fail due to value sending rules. For more info, see module docs."));
         }
 
         log::debug!(
@@ -288,23 +324,101 @@ where
             gas_limit,
         );
 
-        if self.check_program_id(&dispatch.destination()) {
-            if let Some(gas_limit) = gas_limit {
-                let _ = GasHandlerOf::<T>::split_with_value(message_id, dispatch.id(), gas_limit);
-            } else {
-                let _ = GasHandlerOf::<T>::split(message_id, dispatch.id());
+        if let Some(reason) = rejection {
+            log::debug!(
+                "Message {:?} to {:?} is rejected: {}",
+                dispatch.id(),
+                dispatch.destination(),
+                reason,
+            );
+
+            // Notifying program, that its message was rejected, by error
+            // reply, which takes gas of the sending one. Same as for
+            // non-executable destinations, error replies aren't sent on
+            // error replies, and the chain, exceeding the limit, isn't
+            // continued by them any further.
+            let notify =
+                matches!(dispatch.exit_code(), None | Some(0)) && depth <= limit.saturating_add(1);
+
+            if notify {
+                match reason.encode().try_into() {
+                    Ok(reason) => {
+                        let reply = ReplyMessage::system(
+                            dispatch.id(),
+                            reason,
+                            core_processor::ERR_EXIT_CODE,
+                        )
+                        .into_stored_dispatch(
+                            dispatch.destination(),
+                            source,
+                            dispatch.id(),
+                        );
+
+                        match GasHandlerOf::<T>::split(gas_node, reply.id()) {
+                            Ok(()) => {
+                                ReplyChainDepth::<T>::insert(reply.id(), depth);
+
+                                Pallet::<T>::queue_dispatch(reply);
+                            }
+                            Err(e) => Pallet::<T>::quarantine(reply.id(), Some(reply), e),
+                        }
+                    }
+                    Err(e) => log::error!(
+                        "Rejection reason of {:?} doesn't fit the payload: {:?}",
+                        dispatch.id(),
+                        e
+                    ),
+                }
             }
+        } else if self.check_program_id(&dispatch.destination()) {
+            let split = if let Some(gas_limit) = gas_limit {
+                GasHandlerOf::<T>::split_with_value(gas_node, dispatch.id(), gas_limit)
+            } else {
+                GasHandlerOf::<T>::split(gas_node, dispatch.id())
+            };
+
+            // Delayed dispatches are stashed till the sending block. Gas for
+            // them is already split, so it's kept reserved while waiting and
+            // charged for holding on the sending.
+            if let Err(e) = split {
+                Pallet::<T>::quarantine(dispatch.id(), Some(dispatch), e);
+            } else if delay == 0 {
+                ReplyChainDepth::<T>::insert(dispatch.id(), depth);
 
-            QueueOf::<T>::queue(dispatch)
-                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+                Pallet::<T>::queue_dispatch(dispatch);
+            } else {
+                ReplyChainDepth::<T>::insert(dispatch.id(), depth);
+
+                let current_bn = <frame_system::Pallet<T>>::block_number();
+                let sending_bn: T::BlockNumber = current_bn
+                    .saturated_into::<u32>()
+                    .saturating_add(delay)
+                    .unique_saturated_into();
+
+                let task = ScheduledTask::SendDispatch(dispatch.id());
+
+                match TaskPoolOf::<T>::add(sending_bn, task) {
+                    Ok(()) => {
+                        let interval = Interval {
+                            start: current_bn,
+                            finish: sending_bn,
+                        };
+
+                        DispatchStashOf::<T>::insert(dispatch.id(), (dispatch, interval));
+                    }
+                    Err(e) => Pallet::<T>::quarantine(dispatch.id(), Some(dispatch), e),
+                }
+            }
         } else {
+            // Delays are applied only to dispatches to programs:
+            // messages to users are sent right away.
             let message = match dispatch.exit_code() {
                 Some(0) | None => dispatch.into_parts().1,
                 _ => {
                     let message = dispatch.into_parts().1;
                     message
                         .clone()
-                        .with_string_payload::<ExecutionErrorReason>()
+                        .with_string_payload::<ErrorReplyReason>()
                         .unwrap_or(message)
                 }
             };
@@ -313,7 +427,7 @@ where
 
             // TODO: replace this unwrap_or_default in #1130.
             let gas_limit = gas_limit.unwrap_or_else(|| {
-                GasHandlerOf::<T>::get_limit(message_id)
+                GasHandlerOf::<T>::get_limit(gas_node)
                     .ok()
                     .flatten()
                     .map(|(v, _)| v)
@@ -321,70 +435,129 @@ where
                     .min(mailbox_threshold)
             });
 
-            if gas_limit >= mailbox_threshold {
+            // Message, which can't be held in the mailbox along with its gas,
+            // is quarantined. It's consumed by the mailbox, so only its id is kept.
+            let stored = (gas_limit >= mailbox_threshold).then(|| {
                 MailboxOf::<T>::insert(message.clone())
-                    .unwrap_or_else(|e| unreachable!("Mailbox corrupted! {:?}", e));
-                let _ = GasHandlerOf::<T>::cut(message_id, message.id(), gas_limit);
-                // TODO: replace this temporary (zero) value for expiration
-                // block number with properly calculated one
-                // (issues #646 and #969).
+                    .and_then(|()| GasHandlerOf::<T>::cut(gas_node, message.id(), gas_limit))
+            });
+
+            if let Some(Err(e)) = stored {
+                Pallet::<T>::quarantine(message.id(), None, e);
+            } else if let Some(Ok(())) = stored {
+                let current_bn = <frame_system::Pallet<T>>::block_number().saturated_into::<u32>();
+
+                let duration = Pallet::<T>::hold_duration(gas_limit, HoldKind::Mailbox);
+
+                let deadline = current_bn.saturating_add(duration);
+                let deadline: T::BlockNumber = deadline.unique_saturated_into();
+
+                let user_id =
+                    <T::AccountId as Origin>::from_origin(message.destination().into_origin());
+
+                Pallet::<T>::set_mailbox_expiration(user_id, message.id(), deadline);
+
                 Pallet::<T>::deposit_event(Event::UserMessageSent {
                     message,
-                    expiration: Some(T::BlockNumber::zero()),
+                    expiration: Some(deadline),
                 })
             } else {
+                // Message isn't stored, so its value is deposited to the user right away.
+                self.send_value(
+                    message.source(),
+                    Some(message.destination()),
+                    message.value(),
+                );
+
                 Pallet::<T>::deposit_event(Event::UserMessageSent {
                     message,
                     expiration: None,
                 });
             }
         }
+
+        // Reservation is removed after sending: the rest of its gas is returned.
+        if let Some(reservation_id) = reservation {
+            if let Some(GasReservationSlot { expiration, .. }) =
+                self.remove_gas_reservation_impl(source, reservation_id)
+            {
+                let _ = TaskPoolOf::<T>::delete(
+                    expiration.unique_saturated_into(),
+                    ScheduledTask::RemoveGasReservation(source, reservation_id),
+                );
+            }
+        }
     }
 
-    fn wait_dispatch(&mut self, dispatch: StoredDispatch) {
+    fn wait_dispatch(
+        &mut self,
+        dispatch: StoredDispatch,
+        duration: Option<u32>,
+        waited_type: MessageWaitedType,
+    ) {
         if let Ok(Some((limit, _))) = GasHandlerOf::<T>::get_limit(dispatch.id()) {
             let message_id = dispatch.id();
             let program_id = dispatch.destination();
 
-            WaitlistOf::<T>::insert(dispatch)
-                .unwrap_or_else(|e| unreachable!("Waitlist corrupted! {:?}", e));
-
-            let current_bn = <frame_system::Pallet<T>>::block_number().saturated_into::<u32>();
+            let origin_key = match Pallet::<T>::gas_origin_key(message_id) {
+                Ok(key) if key == message_id => None,
+                Ok(key) => Some(key),
+                Err(e) => {
+                    Pallet::<T>::quarantine(message_id, Some(dispatch), e);
 
-            let can_cover = limit.saturating_div(CostsPerBlockOf::<T>::waitlist());
-            let reserve_for = CostsPerBlockOf::<T>::reserve_for().saturated_into::<u32>();
+                    return;
+                }
+            };
 
-            let duration = (can_cover as u32).saturating_sub(reserve_for);
+            if let Err(e) = WaitlistOf::<T>::insert(dispatch) {
+                Pallet::<T>::quarantine(message_id, None, e);
 
-            let deadline = current_bn.saturating_add(duration);
-            let deadline: T::BlockNumber = deadline.unique_saturated_into();
+                return;
+            }
 
-            TaskPoolOf::<T>::add(
-                deadline,
-                ScheduledTask::RemoveFromWaitlist(program_id, message_id),
-            )
-            .unwrap_or_else(|e| unreachable!("Scheduling logic invalidated! {:?}", e));
+            let current_bn = <frame_system::Pallet<T>>::block_number().saturated_into::<u32>();
 
-            let origin_key = if let Some(key) = GasHandlerOf::<T>::get_origin_key(message_id)
-                .unwrap_or_else(|e| unreachable!("ValueTree corrupted: {:?}!", e))
-            {
-                if key == message_id {
-                    None
-                } else {
-                    Some(key)
+            let affordable = Self::affordable_waitlist_duration(limit);
+            let limits = T::Schedule::get().limits;
+
+            // Messages waited for the bounded duration are woken on the deadline.
+            // Others, including ones unable to wait for the whole requested duration,
+            // are removed from the waitlist with error reply.
+            let (duration, task) = match (waited_type, duration) {
+                (MessageWaitedType::WaitFor, Some(duration))
+                    if limits.can_wait_for(duration, affordable) =>
+                {
+                    (duration, ScheduledTask::WakeMessage(program_id, message_id))
                 }
-            } else {
-                unreachable!("ValueTree corrupted!")
+                (MessageWaitedType::WaitUpTo, Some(duration)) => (
+                    limits.wait_up_to_duration(duration, affordable),
+                    ScheduledTask::WakeMessage(program_id, message_id),
+                ),
+                (MessageWaitedType::WaitFor, _) => (
+                    affordable.min(limits.max_wait_duration),
+                    ScheduledTask::RemoveFromWaitlist(program_id, message_id),
+                ),
+                _ => (
+                    limits.wait_duration(affordable),
+                    ScheduledTask::RemoveFromWaitlist(program_id, message_id),
+                ),
             };
 
-            // TODO: replace this temporary (zero) value
-            // for expiration block number with properly
-            // calculated one (issues #646 and #969).
+            let deadline = current_bn.saturating_add(duration);
+            let deadline: T::BlockNumber = deadline.unique_saturated_into();
+
+            Pallet::<T>::set_waitlist_expiration(message_id, deadline, task);
+
             Pallet::<T>::deposit_event(Event::MessageWaited {
                 id: message_id,
                 origin: origin_key,
-                reason: MessageWaitedRuntimeReason::WaitCalled.into_reason(),
-                expiration: T::BlockNumber::zero(),
+                reason: match waited_type {
+                    MessageWaitedType::Wait => MessageWaitedRuntimeReason::WaitCalled,
+                    MessageWaitedType::WaitFor => MessageWaitedRuntimeReason::WaitForCalled,
+                    MessageWaitedType::WaitUpTo => MessageWaitedRuntimeReason::WaitUpToCalled,
+                }
+                .into_reason(),
+                expiration: deadline,
             });
         }
     }
@@ -394,15 +567,35 @@ where
         message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
+        delay: u32,
     ) {
+        // Delayed wakes are scheduled till the waking block. Message stays
+        // in the waitlist meanwhile, so it's charged for holding on wake.
+        if delay > 0 {
+            if WaitlistOf::<T>::contains(&program_id, &awakening_id) {
+                let current_bn = <frame_system::Pallet<T>>::block_number().saturated_into::<u32>();
+                let waking_bn: T::BlockNumber =
+                    current_bn.saturating_add(delay).unique_saturated_into();
+
+                Pallet::<T>::set_delayed_wake(program_id, awakening_id, waking_bn);
+            } else {
+                log::debug!(
+                    "Attempt to schedule awakening of unknown message {:?} from {:?}",
+                    awakening_id,
+                    message_id
+                );
+            }
+
+            return;
+        }
+
         if let Some(dispatch) = self.wake_message_impl(program_id, awakening_id) {
             Pallet::<T>::deposit_event(Event::MessageWoken {
                 id: dispatch.id(),
                 reason: MessageWokenRuntimeReason::WakeCalled.into_reason(),
             });
 
-            QueueOf::<T>::queue(dispatch)
-                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+            Pallet::<T>::queue_dispatch(dispatch);
         } else {
             log::debug!(
                 "Attempt to awaken unknown message {:?} from {:?}",
@@ -418,15 +611,30 @@ where
         pages_data: BTreeMap<PageNumber, PageBuf>,
     ) {
         self.state_changes.insert(program_id);
-        let program_id = program_id.into_origin();
-        let program = common::get_program(program_id)
+        self.stats.pages_written = self
+            .stats
+            .pages_written
+            .saturating_add(pages_data.len() as u32);
+        let program = common::get_program(program_id.into_origin())
             .expect("page update guaranteed to be called only for existing and active program");
         if let Program::Active(mut program) = program {
+            let cached = self.pages.entry(program_id).or_default();
             for (page, data) in pages_data {
-                common::set_program_page_data(program_id, page, data);
+                // Zero pages aren't stored: pages without data are zeroed on load.
+                if data.is_zero() {
+                    cached.remove(&page);
+                    if program.pages_with_data.remove(&page) {
+                        self.dirty_pages.insert((program_id, page));
+                    }
+
+                    continue;
+                }
+
+                cached.insert(page, data);
+                self.dirty_pages.insert((program_id, page));
                 program.pages_with_data.insert(page);
             }
-            common::set_program(program_id, program);
+            common::set_program(program_id.into_origin(), program);
         }
     }
 
@@ -435,26 +643,36 @@ where
         program_id: ProgramId,
         allocations: BTreeSet<gear_core::memory::WasmPageNumber>,
     ) {
-        let program_id = program_id.into_origin();
-        let program = common::get_program(program_id)
+        let program = common::get_program(program_id.into_origin())
             .expect("page update guaranteed to be called only for existing and active program");
         if let Program::Active(mut program) = program {
             let removed_pages = program.allocations.difference(&allocations);
             for page in removed_pages.flat_map(|p| p.to_gear_pages_iter()) {
                 if program.pages_with_data.remove(&page) {
-                    common::remove_program_page_data(program_id, page);
+                    if let Some(cached) = self.pages.get_mut(&program_id) {
+                        cached.remove(&page);
+                    }
+                    self.dirty_pages.remove(&(program_id, page));
+                    common::remove_program_page_data(program_id.into_origin(), page);
                 }
             }
             program.allocations = allocations;
-            common::set_program(program_id, program);
+            common::set_program(program_id.into_origin(), program);
         }
     }
 
     fn send_value(&mut self, from: ProgramId, to: Option<ProgramId>, value: u128) {
+        // Value-less transfers don't touch balances.
+        if value == 0 {
+            return;
+        }
+
         let from = from.into_origin();
+        let from_account = <T::AccountId as Origin>::from_origin(from);
         let value = value.unique_saturated_into();
+
+        // Value is held by the bank since the time of sending.
         if let Some(to) = to.map(|id| id.into_origin()) {
-            let from_account = <T::AccountId as Origin>::from_origin(from);
             let to_account = <T::AccountId as Origin>::from_origin(to);
             log::debug!(
                 "Sending value of amount {:?} from {:?} to {:?}",
@@ -462,78 +680,63 @@ where
                 from,
                 to
             );
-            let res = if <T as Config>::Currency::can_reserve(
-                &to_account,
-                <T as Config>::Currency::minimum_balance(),
-            ) {
-                // `to` account exists, so we can repatriate reserved value for it.
-                match <T as Config>::Currency::repatriate_reserved(
-                    &from_account,
-                    &to_account,
-                    value,
-                    BalanceStatus::Free,
-                ) {
-                    Ok(leftover) => {
-                        if leftover > TOL.unique_saturated_into() {
-                            log::debug!(
-                                target: "essential",
-                                "Reserved funds not fully repatriated from 0x{:?} to 0x{:?}: amount = {:?}, leftover = {:?}",
-                                from_account,
-                                to_account,
-                                value,
-                                leftover,
-                            );
-                        }
-                        Ok(())
-                    }
-                    Err(e) => {
-                        // This is a error, as reserved should always be repatriatable
-                        log::error!(
-                            target: "essential",
-                            "Failure to repatriate reserves of {:?} from 0x{:?} to 0x{:?}: {:?}",
-                            value,
-                            from_account,
-                            to_account,
-                            e,
-                        );
-                        Ok(())
-                    }
-                }
-            } else {
-                let not_freed = <T as Config>::Currency::unreserve(&from_account, value);
-                if not_freed != 0u128.unique_saturated_into() {
-                    unreachable!("All requested value for unreserve must be freed. For more info, see module docs.");
+
+            if let Err(e) = GearBank::<T>::withdraw_value(&to_account, value) {
+                // Programs exempt from existential deposit could send value,
+                // which isn't enough to create an account of the user.
+                if !T::ExistentialDepositFreePrograms::get() {
+                    unreachable!("Value transfers can't fail. For more info, see module docs.");
                 }
-                <T as Config>::Currency::transfer(
-                    &from_account,
-                    &to_account,
-                    value,
-                    ExistenceRequirement::AllowDeath,
-                )
-            };
 
-            res.unwrap_or_else(|_| {
-                unreachable!("Value transfers can't fail. For more info, see module docs.")
-            });
-        } else {
-            let from_account = <T::AccountId as Origin>::from_origin(from);
-            let not_freed = <T as Config>::Currency::unreserve(&from_account, value);
-            if not_freed == 0u128.unique_saturated_into() {
                 log::debug!(
-                    "Value amount amount {:?} successfully unreserved from {:?}",
+                    "Value {:?} returned to {:?}, as it can't be sent to {:?}: {:?}",
                     value,
                     from,
+                    to,
+                    e
                 );
-            } else {
-                unreachable!("All requested value for unreserve must be freed. For more info, see module docs.");
+
+                GearBank::<T>::withdraw_value(&from_account, value).unwrap_or_else(|e| {
+                    unreachable!("Account of the program exempt from existential deposit always exists: {:?}", e)
+                });
+            }
+        } else {
+            match GearBank::<T>::withdraw_value(&from_account, value) {
+                Ok(_) => log::debug!(
+                    "Value amount {:?} successfully returned to {:?}",
+                    value,
+                    from,
+                ),
+                Err(e) => unreachable!(
+                    "Value returned to the sender can't fail. For more info, see module docs: {:?}",
+                    e
+                ),
             }
         }
     }
 
-    fn store_new_programs(&mut self, code_id: CodeId, candidates: Vec<(ProgramId, MessageId)>) {
+    fn store_new_programs(
+        &mut self,
+        program_id: ProgramId,
+        code_id: CodeId,
+        candidates: Vec<(ProgramId, MessageId)>,
+    ) {
         if T::CodeStorage::get_code(code_id).is_some() {
             for (candidate_id, init_message) in candidates {
                 if !GearProgramPallet::<T>::program_exists(candidate_id) {
+                    if let Err(e) = GearProgramPallet::<T>::link_child(program_id, candidate_id) {
+                        log::debug!(
+                            "Program {:?} can't create program {:?}: {:?}",
+                            program_id,
+                            candidate_id,
+                            e
+                        );
+
+                        // Init message will be processed as sent to non-executable program.
+                        self.programs.insert(candidate_id);
+                        continue;
+                    }
+
                     self.set_program(candidate_id, code_id, init_message);
                 } else {
                     log::debug!("Program with id {:?} already exists", candidate_id);
@@ -560,7 +763,261 @@ where
 
         SentOf::<T>::increase();
         GasAllowanceOf::<T>::decrease(gas_burned);
-        QueueOf::<T>::requeue(dispatch)
-            .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+        let id = dispatch.id();
+
+        if let Err(e) = QueueOf::<T>::requeue(dispatch) {
+            Pallet::<T>::quarantine(id, None, e);
+        }
     }
+
+    fn reserve_gas(
+        &mut self,
+        message_id: MessageId,
+        reservation_id: ReservationId,
+        program_id: ProgramId,
+        amount: u64,
+        expiration: u32,
+    ) {
+        log::debug!(
+            "Reserve {} of gas from {:?} till block #{} for {:?}",
+            amount,
+            message_id,
+            expiration,
+            reservation_id,
+        );
+
+        if let Err(e) = GasHandlerOf::<T>::reserve(
+            message_id,
+            MessageId::from_origin(reservation_id.into_origin()),
+            amount,
+        ) {
+            Pallet::<T>::quarantine(message_id, None, e);
+
+            return;
+        }
+
+        if let Some(Program::Active(mut prog)) = common::get_program(program_id.into_origin()) {
+            prog.gas_reservation_map
+                .insert(reservation_id, GasReservationSlot { amount, expiration });
+            common::set_program_gas_reservation_map(
+                program_id.into_origin(),
+                prog.gas_reservation_map,
+            );
+        }
+
+        if let Err(e) = TaskPoolOf::<T>::add(
+            expiration.unique_saturated_into(),
+            ScheduledTask::RemoveGasReservation(program_id, reservation_id),
+        ) {
+            Pallet::<T>::quarantine(message_id, None, e);
+        }
+    }
+
+    fn unreserve_gas(
+        &mut self,
+        reservation_id: ReservationId,
+        program_id: ProgramId,
+        expiration: u32,
+    ) {
+        log::debug!("Unreserve gas of {:?}", reservation_id);
+
+        self.remove_gas_reservation_impl(program_id, reservation_id);
+
+        let _ = TaskPoolOf::<T>::delete(
+            expiration.unique_saturated_into(),
+            ScheduledTask::RemoveGasReservation(program_id, reservation_id),
+        );
+    }
+
+    fn system_reserve_gas(&mut self, message_id: MessageId, amount: u64) {
+        log::debug!("Reserve {} of gas for signal from {:?}", amount, message_id);
+
+        if let Err(e) = GasHandlerOf::<T>::split_with_value(
+            message_id,
+            MessageId::generate_signal(message_id),
+            amount,
+        ) {
+            Pallet::<T>::quarantine(message_id, None, e);
+        }
+    }
+
+    fn system_unreserve_gas(&mut self, message_id: MessageId) {
+        log::debug!("Unreserve gas for signal from {:?}", message_id);
+
+        JournalHandler::message_consumed(self, MessageId::generate_signal(message_id));
+    }
+
+    fn send_signal(&mut self, message_id: MessageId, destination: ProgramId, exit_code: ExitCode) {
+        log::debug!(
+            "Sending signal with code {} to {:?} for {:?}",
+            exit_code,
+            destination,
+            message_id,
+        );
+
+        // Signal is paid by the system reservation, which gas node
+        // shares id with the signal message itself.
+        let dispatch = SignalMessage::new(message_id, exit_code)
+            .into_dispatch(message_id, destination)
+            .into_stored();
+
+        Pallet::<T>::queue_dispatch(dispatch);
+    }
+
+    fn increment_metric(&mut self, program_id: ProgramId, key: [u8; 32], value: u64) {
+        T::DebugInfo::increment_metric(program_id, H256(key), value);
+    }
+
+    fn emit_event(&mut self, program_id: ProgramId, event: ProgramEvent) {
+        let (topics, payload) = event.into_parts();
+
+        Pallet::<T>::deposit_event(Event::ProgramEvent {
+            source: program_id,
+            topics: topics.into_iter().map(H256).collect(),
+            payload,
+        });
+    }
+}
+
+/// Summarizes journal note for export to indexers.
+///
+/// Returns `None` for notes, which don't change the state.
+pub(crate) fn journal_record(note: &JournalNote) -> Option<JournalRecord> {
+    use JournalNote::*;
+
+    let record = match note {
+        MessageDispatched {
+            message_id,
+            source,
+            outcome,
+        } => {
+            let status = match outcome {
+                CoreDispatchOutcome::Exit { .. }
+                | CoreDispatchOutcome::InitSuccess { .. }
+                | CoreDispatchOutcome::Success => DispatchStatus::Success,
+                CoreDispatchOutcome::InitFailure { .. }
+                | CoreDispatchOutcome::MessageTrap { .. } => DispatchStatus::Failed,
+                CoreDispatchOutcome::NoExecution => DispatchStatus::NotExecuted,
+            };
+
+            JournalRecord::MessageDispatched {
+                message_id: *message_id,
+                source: *source,
+                status,
+            }
+        }
+        GasBurned { message_id, amount } => JournalRecord::GasBurned {
+            message_id: *message_id,
+            amount: *amount,
+        },
+        ExitDispatch {
+            id_exited,
+            value_destination,
+        } => JournalRecord::ProgramExited {
+            program_id: *id_exited,
+            value_destination: *value_destination,
+        },
+        MessageConsumed(message_id) => JournalRecord::MessageConsumed(*message_id),
+        SendDispatch {
+            message_id,
+            dispatch,
+            delay,
+            ..
+        } => JournalRecord::MessageSent {
+            message_id: *message_id,
+            dispatch_id: dispatch.id(),
+            kind: dispatch.kind(),
+            destination: dispatch.destination(),
+            gas_limit: dispatch.gas_limit(),
+            value: dispatch.value(),
+            delay: *delay,
+        },
+        WaitDispatch { dispatch, .. } => JournalRecord::MessageWaited {
+            message_id: dispatch.id(),
+            program_id: dispatch.destination(),
+        },
+        WakeMessage {
+            message_id,
+            program_id,
+            awakening_id,
+            ..
+        } => JournalRecord::MessageWoken {
+            message_id: *message_id,
+            program_id: *program_id,
+            awakening_id: *awakening_id,
+        },
+        UpdatePage {
+            program_id,
+            page_number,
+            ..
+        } => JournalRecord::PageUpdated {
+            program_id: *program_id,
+            page_number: *page_number,
+        },
+        UpdateAllocations {
+            program_id,
+            allocations,
+        } => JournalRecord::AllocationsUpdated {
+            program_id: *program_id,
+            allocations: allocations.clone(),
+        },
+        SendValue { from, to, value } => JournalRecord::ValueSent {
+            from: *from,
+            to: *to,
+            value: *value,
+        },
+        StoreNewPrograms {
+            program_id,
+            code_hash,
+            candidates,
+        } => JournalRecord::ProgramsCreated {
+            program_id: *program_id,
+            code_id: *code_hash,
+            candidates: candidates.clone(),
+        },
+        StopProcessing { .. } => return None,
+        ReserveGas {
+            message_id,
+            reservation_id,
+            program_id,
+            amount,
+            expiration,
+        } => JournalRecord::GasReserved {
+            message_id: *message_id,
+            reservation_id: *reservation_id,
+            program_id: *program_id,
+            amount: *amount,
+            expiration: *expiration,
+        },
+        UnreserveGas {
+            reservation_id,
+            program_id,
+            ..
+        } => JournalRecord::GasUnreserved {
+            reservation_id: *reservation_id,
+            program_id: *program_id,
+        },
+        SystemReserveGas { message_id, amount } => JournalRecord::SystemGasReserved {
+            message_id: *message_id,
+            amount: *amount,
+        },
+        SystemUnreserveGas { message_id } => JournalRecord::SystemGasUnreserved {
+            message_id: *message_id,
+        },
+        SendSignal {
+            message_id,
+            destination,
+            exit_code,
+        } => JournalRecord::SignalSent {
+            message_id: *message_id,
+            destination: *destination,
+            exit_code: *exit_code,
+        },
+        // Custom counters are accumulated only on debug chains.
+        IncrementMetric { .. } => return None,
+        // Already recorded on chain by `Event::ProgramEvent`.
+        EmitEvent { .. } => return None,
+    };
+
+    Some(record)
 }
@@ -20,26 +20,30 @@
 //!
 //! Should be mentioned, that if message contains value we have a guarantee that it will be sent further in case of successful execution,
 //! or sent back in case execution ends up with an error. This guarantee is reached by the following conditions:
-//! 1. **Reserve/unreserve model for transferring values**.
-//! Ownership over message value is moved not by simple transfer operation, which decreases **free** balance of sender. That is done by
-//! reserving value before message is executed and repatriating reserved in favor of beneficiary in case of successful execution, or unreserving
-//! in case of execution resulting in a trap. So, it gives us a guarantee that regardless of the result of message execution, there is **always some
-//! value** to perform asset management, i.e move tokens further to the recipient or give back to sender. The guarantee is implemented by using
-//! corresponding `pallet_balances` functions (`reserve`, `repatriate_reserved`, `unreserve` along with `transfer`) in `pallet_gear` extrinsics,
-//! [`JournalHandler::send_dispatch`] and [`JournalHandler::send_value`] procedures.
+//! 1. **Bank model for transferring values**.
+//! Ownership over message value is moved not by simple transfer operation from sender to beneficiary. Value is moved
+//! to the bank account of the pallet before message is executed and transferred from the bank in favor of beneficiary
+//! in case of successful execution, or back to the sender in case of execution resulting in a trap. So, it gives us
+//! a guarantee that regardless of the result of message execution, there is **always some value** to perform asset
+//! management, i.e move tokens further to the recipient or give back to sender. The guarantee is implemented by using
+//! [`GearBank`] in `pallet_gear` extrinsics, [`JournalHandler::send_dispatch`] and [`JournalHandler::send_value`] procedures.
 //!
 //! 2. **Balance sufficiency before adding message with value to the queue**.
 //! Before message is added to the queue, sender's balance is checked for having adequate amount of assets to send desired value. For actors, who
 //! can sign transactions, these checks are done in extrinsic calls. For programs these checks are done on core backend level during execution. In details,
 //! when a message is executed, it has some context, which is set from the pallet level, and a part of the context data is program's actual balance (current balance +
 //! value sent within the executing message). So if during execution of the original message some other messages were sent, message send call is followed
-//! by program's balance checks. The check gives guarantee that value reservation call in [`JournalHandler::send_dispatch`] for program's messages won't fail,
+//! by program's balance checks. The check gives guarantee that moving value to the bank in [`JournalHandler::send_dispatch`] for program's messages won't fail,
 //! because there is always a sufficient balance for the call.
 //!
 //! 3. **Messages's value management considers existential deposit rule**.
 //! It means that before message with value is added to the queue, value is checked to be in the valid range - `{0} ∪ [existential_deposit; +inf)`. This is
 //! crucial for programs. The check gives guarantee that if funds were moved to the program, the program will definitely have an account in `pallet_balances`
 //! registry and will be able then to manage these funds. Without this check, program could receive funds, but won't be able to use them.
+//! If `Config::ExistentialDepositFreePrograms` is set, programs are exempt from the rule: each program account is provided with the existential
+//! deposit held in reserve, so it always exists in `pallet_balances` registry and values of any amount could be sent to and by programs. Values below
+//! existential deposit, which can't be moved to a user account, are returned to the sending program. The account is released, once the program
+//! fails to initialize or exits.
 //!
 //! Due to these 3 conditions implemented in `pallet_gear`, we have a guarantee that value management calls, performed by user or program, won't fail.
 
@@ -49,29 +53,39 @@ mod task;
 pub use journal::*;
 pub use task::*;
 
-use crate::{Authorship, Config, CostsPerBlockOf, GasHandlerOf, GearProgramPallet, WaitlistOf};
+use crate::{
+    BlockRunStats, Config, CostsPerBlockOf, GasHandlerOf, GearBank, GearProgramPallet,
+    MailboxHolds, MailboxOf, Pallet, TaskPoolOf, WaitlistOf,
+};
 use codec::{Decode, Encode};
 use common::{
     event::*, scheduler::*, storage::*, ActiveProgram, CodeStorage, GasPrice, GasTree, Origin,
     ProgramState,
 };
-use core_processor::common::{Actor, ExecutableActorData};
-use frame_support::traits::{BalanceStatus, Currency, ReservableCurrency};
+use core_processor::common::{Actor, ExecutableActorData, JournalHandler};
+use frame_support::traits::{Currency, ReservableCurrency};
 use gear_core::{
-    ids::{CodeId, MessageId, ProgramId},
-    message::{ExitCode, StoredDispatch},
+    code::InstrumentedCode,
+    gas::GasReservationSlot,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
+    memory::{Error as MemoryError, PageBuf, PageNumber},
+    message::{ExitCode, StoredDispatch, StoredMessage},
     program::Program as NativeProgram,
 };
-use sp_runtime::traits::{SaturatedConversion, UniqueSaturatedInto};
+use sp_runtime::{
+    traits::{SaturatedConversion, Saturating, UniqueSaturatedInto},
+    DispatchError,
+};
 use sp_std::{
     collections::{btree_map::BTreeMap, btree_set::BTreeSet},
     convert::TryInto,
     marker::PhantomData,
+    mem,
     prelude::*,
 };
 
-// Tolerance towards rounding error when converting gas to balance etc.
-pub(crate) const TOL: u128 = 10;
+/// Maximal amount of instrumented codes cached during the queue processing.
+pub const CODE_CACHE_CAPACITY: usize = 32;
 
 #[derive(Clone, Decode, Encode)]
 pub enum HandleKind {
@@ -90,6 +104,17 @@ pub struct ExtManager<T: Config> {
     dispatch_statuses: BTreeMap<MessageId, DispatchStatus>,
     /// Programs, which state changed.
     state_changes: BTreeSet<ProgramId>,
+    /// Statistics of the queue processing.
+    stats: BlockRunStats,
+    /// Program pages read or written during the queue processing.
+    pages: BTreeMap<ProgramId, BTreeMap<PageNumber, PageBuf>>,
+    /// Cached pages, which aren't written into storage yet.
+    dirty_pages: BTreeSet<(ProgramId, PageNumber)>,
+    /// Instrumented codes read during the queue processing,
+    /// along with the tick of their last use.
+    codes: BTreeMap<CodeId, (InstrumentedCode, u32)>,
+    /// Tick of the code cache, increased on each use.
+    codes_tick: u32,
     /// Phantom data for generic usage.
     _phantom: PhantomData<T>,
 }
@@ -100,6 +125,8 @@ pub struct QueuePostProcessingData {
     pub dispatch_statuses: BTreeMap<MessageId, DispatchStatus>,
     /// Programs, which state changed.
     pub state_changes: BTreeSet<ProgramId>,
+    /// Statistics of the queue processing.
+    pub stats: BlockRunStats,
 }
 
 impl<T: Config> From<ExtManager<T>> for QueuePostProcessingData {
@@ -107,6 +134,7 @@ impl<T: Config> From<ExtManager<T>> for QueuePostProcessingData {
         Self {
             dispatch_statuses: ext_manager.dispatch_statuses,
             state_changes: ext_manager.state_changes,
+            stats: ext_manager.stats,
         }
     }
 }
@@ -122,6 +150,11 @@ where
             programs: Default::default(),
             dispatch_statuses: Default::default(),
             state_changes: Default::default(),
+            stats: Default::default(),
+            pages: Default::default(),
+            dirty_pages: Default::default(),
+            codes: Default::default(),
+            codes_tick: 0,
         }
     }
 }
@@ -146,6 +179,42 @@ where
         }
     }
 
+    /// Returns instrumented code, reading it from storage only if it isn't cached.
+    pub fn get_code(&mut self, code_id: CodeId) -> Option<InstrumentedCode> {
+        self.codes_tick = self.codes_tick.saturating_add(1);
+
+        if let Some((code, used)) = self.codes.get_mut(&code_id) {
+            *used = self.codes_tick;
+            self.stats.code_cache.hits = self.stats.code_cache.hits.saturating_add(1);
+
+            return Some(code.clone());
+        }
+
+        self.stats.code_cache.misses = self.stats.code_cache.misses.saturating_add(1);
+
+        let code = T::CodeStorage::get_code(code_id)?;
+        self.cache_code(code_id, code.clone());
+
+        Some(code)
+    }
+
+    /// Caches the code, evicting the least recently used one if the cache is full.
+    pub fn cache_code(&mut self, code_id: CodeId, code: InstrumentedCode) {
+        if !self.codes.contains_key(&code_id) && self.codes.len() >= CODE_CACHE_CAPACITY {
+            let lru = self
+                .codes
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(id, _)| *id);
+
+            if let Some(lru) = lru {
+                self.codes.remove(&lru);
+            }
+        }
+
+        self.codes.insert(code_id, (code, self.codes_tick));
+    }
+
     /// Check if id is user and save result.
     pub fn check_user_id(&mut self, id: &ProgramId) -> bool {
         !self.check_program_id(id)
@@ -153,11 +222,11 @@ where
 
     /// NOTE: By calling this function we can't differ whether `None` returned, because
     /// program with `id` doesn't exist or it's terminated
-    pub fn get_actor(&self, id: ProgramId, with_pages: bool) -> Option<Actor> {
+    pub fn get_actor(&mut self, id: ProgramId, with_pages: bool) -> Option<Actor> {
         let active: ActiveProgram = common::get_program(id.into_origin())?.try_into().ok()?;
         let program = {
             let code_id = CodeId::from_origin(active.code_hash);
-            let code = T::CodeStorage::get_code(code_id)?;
+            let code = self.get_code(code_id)?;
             NativeProgram::from_parts(
                 id,
                 code,
@@ -171,7 +240,7 @@ where
         )
         .unique_saturated_into();
         let pages_data = if with_pages {
-            common::get_program_data_for_pages(id.into_origin(), active.pages_with_data.iter())
+            self.get_pages_data(id, active.pages_with_data.iter())
                 .ok()?
         } else {
             Default::default()
@@ -183,10 +252,69 @@ where
             executable_data: Some(ExecutableActorData {
                 program,
                 pages_data,
+                gas_reservation_map: active.gas_reservation_map,
+                waitlist_full: WaitlistOf::<T>::is_full(&id),
             }),
         })
     }
 
+    /// Returns data for all pages from `pages` arg, which has data.
+    ///
+    /// Storage is read only for pages, which weren't read or written
+    /// earlier during the queue processing.
+    pub fn get_pages_data<'a>(
+        &mut self,
+        program_id: ProgramId,
+        pages: impl Iterator<Item = &'a PageNumber>,
+    ) -> Result<BTreeMap<PageNumber, PageBuf>, MemoryError> {
+        let cached = self.pages.entry(program_id).or_default();
+
+        let mut pages_data = BTreeMap::new();
+        for page in pages {
+            let data = if let Some(data) = cached.get(page) {
+                data.clone()
+            } else if let Some(data) =
+                common::get_program_page_data(program_id.into_origin(), *page)
+            {
+                let data = data?;
+                cached.insert(*page, data.clone());
+                data
+            } else {
+                continue;
+            };
+
+            pages_data.insert(*page, data);
+        }
+
+        Ok(pages_data)
+    }
+
+    /// Writes pages changed during the queue processing into storage.
+    ///
+    /// Pages, which became zero, are removed from storage instead.
+    ///
+    /// Must be called before anything reads program pages directly
+    /// from storage, e.g. lazy pages.
+    pub fn flush_pages(&mut self) {
+        for (program_id, page) in mem::take(&mut self.dirty_pages) {
+            if let Some(data) = self
+                .pages
+                .get(&program_id)
+                .and_then(|pages| pages.get(&page))
+            {
+                common::set_program_page_data(program_id.into_origin(), page, data.clone());
+            } else {
+                common::remove_program_page_data(program_id.into_origin(), page);
+            }
+        }
+    }
+
+    /// Drops cached pages of the program, which pages were removed from storage.
+    fn forget_pages(&mut self, program_id: ProgramId) {
+        self.pages.remove(&program_id);
+        self.dirty_pages.retain(|(id, _)| *id != program_id);
+    }
+
     pub fn set_program(&self, program_id: ProgramId, code_id: CodeId, message_id: MessageId) {
         // Program can be added to the storage only with code, which is done in `submit_program` extrinsic.
         // Code can exist without program, but the latter can't exist without code.
@@ -199,11 +327,61 @@ where
         let program = common::ActiveProgram {
             allocations: Default::default(),
             pages_with_data: Default::default(),
+            gas_reservation_map: Default::default(),
             code_hash: code_id.into_origin(),
             state: common::ProgramState::Uninitialized { message_id },
         };
 
         common::set_program(program_id.into_origin(), program);
+        GearProgramPallet::<T>::add_code_user(code_id);
+        Self::provide_program_account(program_id);
+
+        // Program stays active for free for some period after its creation.
+        let expiration = <frame_system::Pallet<T>>::block_number()
+            .saturating_add(T::ProgramRentFreePeriod::get());
+
+        Pallet::<T>::set_program_expiration(program_id, expiration);
+    }
+
+    /// Exempts program account from existential deposit, if configured.
+    ///
+    /// Account gets a dedicated provider reference and the existential
+    /// deposit held in reserve, so its total balance never drops below
+    /// the minimum, whatever amount of value the program holds.
+    fn provide_program_account(program_id: ProgramId) {
+        if !T::ExistentialDepositFreePrograms::get() {
+            return;
+        }
+
+        let account = <T::AccountId as Origin>::from_origin(program_id.into_origin());
+        let existential_deposit = <T as Config>::Currency::minimum_balance();
+
+        frame_system::Pallet::<T>::inc_providers(&account);
+        let _ = <T as Config>::Currency::deposit_creating(&account, existential_deposit);
+        <T as Config>::Currency::reserve(&account, existential_deposit)
+            .unwrap_or_else(|e| unreachable!("Existential deposit was just deposited: {:?}", e));
+    }
+
+    /// Releases existential deposit and provider reference of the program
+    /// account, provided by [`Self::provide_program_account`].
+    ///
+    /// Must be called only for programs, which hold no value.
+    fn release_program_account(program_id: ProgramId) {
+        if !T::ExistentialDepositFreePrograms::get() {
+            return;
+        }
+
+        let account = <T::AccountId as Origin>::from_origin(program_id.into_origin());
+        let existential_deposit = <T as Config>::Currency::minimum_balance();
+
+        let _ = <T as Config>::Currency::slash_reserved(&account, existential_deposit);
+        let _ = frame_system::Pallet::<T>::dec_providers(&account);
+    }
+
+    /// Amount of blocks the message with `gas_limit` is able to pay
+    /// the waitlist rent for, keeping the reserve for missed blocks.
+    pub fn affordable_waitlist_duration(gas_limit: u64) -> u32 {
+        Pallet::<T>::hold_duration(gas_limit, HoldKind::Waitlist)
     }
 
     pub fn charge_for_wake(
@@ -215,64 +393,27 @@ where
             .saturated_into::<u32>()
             .saturating_sub(bn.saturated_into::<u32>());
 
-        let holding_cost = (duration as u64).saturating_mul(CostsPerBlockOf::<T>::waitlist());
+        self.charge_for_hold(message_id, duration, CostsPerBlockOf::<T>::waitlist());
+    }
+
+    /// Charges message's gas node for holding it in storage
+    /// for `duration` blocks with given cost per block.
+    pub fn charge_for_hold(&self, message_id: MessageId, duration: u32, cost_per_block: u64) {
+        let holding_cost = (duration as u64).saturating_mul(cost_per_block);
+
+        // Delayed wakes and dispatches may keep message in storage for
+        // longer than its gas covers, so the charge is limited by the gas left.
+        let holding_cost = match GasHandlerOf::<T>::get_limit(message_id) {
+            Ok(Some((limit, _))) => holding_cost.min(limit),
+            _ => holding_cost,
+        };
 
         match GasHandlerOf::<T>::spend(message_id, holding_cost) {
-            Ok(_) => {
-                match GasHandlerOf::<T>::get_external(message_id) {
-                    Ok(maybe_origin) => {
-                        if let Some(origin) = maybe_origin {
-                            let charge = T::GasPrice::gas_price(holding_cost);
-                            if let Some(author) = Authorship::<T>::author() {
-                                match <T as Config>::Currency::repatriate_reserved(
-                                    &origin,
-                                    &author,
-                                    charge,
-                                    BalanceStatus::Free,
-                                ) {
-                                    Ok(leftover) => {
-                                        if leftover > TOL.unique_saturated_into() {
-                                            log::debug!(
-                                                target: "essential",
-                                                "Reserved funds not fully repatriated from {:?} to 0x{:?}: amount = {:?}, leftover = {:?}",
-                                                origin,
-                                                author,
-                                                charge,
-                                                leftover,
-                                            );
-                                        }
-                                    }
-                                    Err(e) => {
-                                        log::debug!(
-                                            target: "essential",
-                                            "Failure to repatriate reserves of {:?} from {:?} to 0x{:?}: {:?}",
-                                            charge,
-                                            origin,
-                                            author,
-                                            e,
-                                        );
-                                    }
-                                }
-                            }
-                        } else {
-                            log::debug!(
-                                target: "essential",
-                                "Failed to get origin of {:?}",
-                                message_id,
-                            );
-                        }
-                    }
-                    Err(_err) => {
-                        // We only can get an error here if the gas tree is invalidated
-                        // TODO: handle appropriately
-                        unreachable!("Can never happen unless gas tree corrupted");
-                    }
-                }
-            }
+            Ok(_) => GearBank::<T>::spend_gas(holding_cost),
             Err(err) => {
                 log::debug!(
                     target: "essential",
-                    "Error charging {:?} of gas rent for awakening message {:?}: {:?}",
+                    "Error charging {:?} of gas rent for holding message {:?}: {:?}",
                     holding_cost,
                     message_id,
                     err,
@@ -288,8 +429,68 @@ where
     ) -> Option<StoredDispatch> {
         let (waitlisted, bn) = WaitlistOf::<T>::remove(program_id, message_id).ok()?;
 
+        Pallet::<T>::remove_waitlist_expiration(program_id, message_id);
         self.charge_for_wake(waitlisted.id(), bn);
 
         Some(waitlisted)
     }
+
+    /// Removes the message from the user's mailbox, charging its gas node
+    /// for the blocks it was held there. Value of the message is transferred
+    /// to the user if `claim` is set, otherwise it's returned to the sender.
+    pub fn read_message_impl(
+        &self,
+        user_id: T::AccountId,
+        message_id: MessageId,
+        claim: bool,
+    ) -> Result<StoredMessage, DispatchError> {
+        let mailboxed = MailboxOf::<T>::remove(user_id.clone(), message_id)?;
+
+        if let Some((bn, expiration)) = MailboxHolds::<T>::take(message_id) {
+            // Task is already drained from the pool, if the holding expired.
+            let _ = TaskPoolOf::<T>::delete(
+                expiration,
+                ScheduledTask::RemoveFromMailbox(user_id.clone(), message_id),
+            );
+
+            let duration = <frame_system::Pallet<T>>::block_number()
+                .saturated_into::<u32>()
+                .saturating_sub(bn.saturated_into::<u32>());
+
+            self.charge_for_hold(message_id, duration, CostsPerBlockOf::<T>::mailbox());
+        }
+
+        if mailboxed.value() > 0 {
+            let source = <T::AccountId as Origin>::from_origin(mailboxed.source().into_origin());
+            let value = mailboxed.value().unique_saturated_into();
+
+            // Value is held by the bank since the time of sending.
+            if claim {
+                GearBank::<T>::withdraw_value(&user_id, value)?;
+            } else if GearBank::<T>::withdraw_value(&source, value).is_err() {
+                GearBank::<T>::add_dust(value);
+            }
+        }
+
+        Ok(mailboxed)
+    }
+
+    /// Removes gas reservation from the program and consumes its gas node.
+    ///
+    /// Returns removed reservation, if it existed.
+    pub fn remove_gas_reservation_impl(
+        &mut self,
+        program_id: ProgramId,
+        reservation_id: ReservationId,
+    ) -> Option<GasReservationSlot> {
+        let mut prog: ActiveProgram = common::get_program(program_id.into_origin())?
+            .try_into()
+            .ok()?;
+        let slot = prog.gas_reservation_map.remove(&reservation_id)?;
+        common::set_program_gas_reservation_map(program_id.into_origin(), prog.gas_reservation_map);
+
+        self.message_consumed(MessageId::from_origin(reservation_id.into_origin()));
+
+        Some(slot)
+    }
 }
@@ -16,50 +16,140 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{manager::ExtManager, Config, Event, GasHandlerOf, Pallet, QueueOf};
+use crate::{
+    manager::ExtManager, Config, CostsPerBlockOf, DispatchStashOf, Event, GasHandlerOf, GasTopUps,
+    GearProgramPallet, Pallet, ProgramExpirations,
+};
 use alloc::string::ToString;
 use codec::Encode;
 use common::{
-    event::{MessageWokenSystemReason, SystemReason},
+    event::{CodeChangeKind, MessageWokenSystemReason, SystemReason, UserMessageReadSystemReason},
     scheduler::*,
     storage::*,
     GasTree, Origin,
 };
-use core_processor::common::{ExecutionErrorReason, JournalHandler};
+use core::convert::TryInto;
+use core_processor::common::JournalHandler;
+use frame_support::traits::Imbalance;
 use gear_core::{
-    ids::{CodeId, MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     message::ReplyMessage,
 };
+use gear_core_errors::ErrorReplyReason;
+use sp_runtime::traits::SaturatedConversion;
 
 impl<T: Config> TaskHandler<T::AccountId> for ExtManager<T>
 where
     T::AccountId: Origin,
 {
-    fn pause_program(&mut self, _program_id: ProgramId) {
-        todo!("#646");
+    fn pause_program(&mut self, program_id: ProgramId) {
+        ProgramExpirations::<T>::remove(program_id);
+
+        // Pausing reads program pages from storage.
+        self.flush_pages();
+
+        // Program could be already paused by its creator or terminated,
+        // so there is nothing to do in this case.
+        if let Err(e) = GearProgramPallet::<T>::pause_program(program_id) {
+            log::debug!("Program {:?} isn't paused: {:?}", program_id, e);
+        }
     }
 
-    fn remove_code(&mut self, _code_id: CodeId) {
-        todo!("#646");
+    fn remove_code(&mut self, code_id: CodeId) {
+        // Code could be used by a program created after pruning was scheduled,
+        // so there is nothing to do in this case.
+        if !GearProgramPallet::<T>::prune_code(code_id) {
+            return;
+        }
+
+        // Depositing appropriate event.
+        Pallet::<T>::deposit_event(Event::CodeChanged {
+            id: code_id,
+            change: CodeChangeKind::Inactive,
+        });
     }
 
-    fn remove_from_mailbox(&mut self, _user_id: T::AccountId, _message_id: MessageId) {
-        todo!("#646");
+    fn remove_from_mailbox(&mut self, user_id: T::AccountId, message_id: MessageId) {
+        // Taking message from mailbox, charging for holding there
+        // and returning its value to the sender.
+        //
+        // Message could be already claimed or replied by user,
+        // so there is nothing to do in this case.
+        let mailboxed = match self.read_message_impl(user_id.clone(), message_id, false) {
+            Ok(mailboxed) => mailboxed,
+            Err(_) => return,
+        };
+
+        // Depositing appropriate event.
+        Pallet::<T>::deposit_event(Event::UserMessageRead {
+            id: message_id,
+            reason: UserMessageReadSystemReason::OutOfRent.into_reason(),
+        });
+
+        // Creating system reply, notifying program, that its message
+        // wasn't consumed. Mailbox messages are always sent by programs.
+        let reason = ErrorReplyReason::NotConsumed
+            .encode()
+            .try_into()
+            .unwrap_or_default();
+
+        let reply = ReplyMessage::system(message_id, reason, core_processor::ERR_EXIT_CODE)
+            .into_stored_dispatch(
+                ProgramId::from_origin(user_id.into_origin()),
+                mailboxed.source(),
+                message_id,
+            );
+
+        // Consuming gas handler for mailboxed message and passing
+        // gas left to the reply: it's still reserved on external account.
+        let origin_key = GasHandlerOf::<T>::get_origin_key(message_id)
+            .ok()
+            .flatten()
+            .unwrap_or(message_id);
+
+        match GasHandlerOf::<T>::consume(message_id) {
+            Ok(Some((neg_imbalance, external))) => {
+                if let Err(e) =
+                    GasHandlerOf::<T>::create(external, reply.id(), neg_imbalance.peek())
+                {
+                    Pallet::<T>::quarantine(reply.id(), Some(reply), e);
+
+                    return;
+                }
+
+                // Additional payers keep their refund rights for the reply gas.
+                for (payer, topped_up) in GasTopUps::<T>::drain_prefix(origin_key) {
+                    GasTopUps::<T>::insert(reply.id(), payer, topped_up);
+                }
+
+                // Enqueueing dispatch into message queue.
+                Pallet::<T>::queue_dispatch(reply);
+            }
+            Ok(None) => log::debug!(
+                "Gas of mailboxed message {:?} wasn't released, reply isn't sent",
+                message_id
+            ),
+            Err(e) => Pallet::<T>::quarantine(message_id, None, e),
+        }
     }
 
     // TODO: generate system signal for program (#647).
     fn remove_from_waitlist(&mut self, program_id: ProgramId, message_id: MessageId) {
         // Taking message from waitlist and charging for holding there.
         //
-        // It's guaranteed to be addressed to program
-        // or waitlist/scheduler storage invalidated!
-        //
-        // Note:
-        // `assert_eq!(waitlisted.id(), message_id)`
-        // `assert_eq!(waitlisted.destination(), program_id)`
-        let waitlisted = self
-            .wake_message_impl(program_id, message_id)
-            .unwrap_or_else(|| unreachable!("Scheduling logic invalidated!"));
+        // Message could be already taken from the waitlist along with
+        // the paused program, so there is nothing to do in this case.
+        let waitlisted = match self.wake_message_impl(program_id, message_id) {
+            Some(waitlisted) => waitlisted,
+            None => {
+                log::debug!(
+                    "Message {:?} isn't in the waitlist of {:?} anymore",
+                    message_id,
+                    program_id
+                );
+                return;
+            }
+        };
 
         // Depositing appropriate event.
         Pallet::<T>::deposit_event(Event::MessageWoken {
@@ -68,30 +158,29 @@ where
         });
 
         // Trap explanation.
-        let trap = ExecutionErrorReason::OutOfRent;
+        let trap = ErrorReplyReason::OutOfRent;
 
         // Generate trap reply.
         if self.check_program_id(&waitlisted.source()) {
             // Sending trap reply to program, by enqueuing it to message queue.
-            let trap = trap.encode();
+            let trap = trap.encode().try_into().unwrap_or_default();
 
             // Creating reply message.
             let trap_reply = ReplyMessage::system(message_id, trap, core_processor::ERR_EXIT_CODE)
                 .into_stored_dispatch(program_id, waitlisted.source(), message_id);
 
-            // Splitting gas for newly created reply message.
-            // TODO: handle error case for `split` (#1130).
-            let _ = GasHandlerOf::<T>::split(trap_reply.id(), message_id);
-
-            // Enqueueing dispatch into message queue.
-            QueueOf::<T>::queue(trap_reply)
-                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+            // Splitting gas for newly created reply message and
+            // enqueueing dispatch into message queue.
+            match GasHandlerOf::<T>::split(message_id, trap_reply.id()) {
+                Ok(()) => Pallet::<T>::queue_dispatch(trap_reply),
+                Err(e) => Pallet::<T>::quarantine(trap_reply.id(), Some(trap_reply), e),
+            }
         } else {
             // Sending trap reply to user, by depositing event.
 
             // Note: for users, trap replies always contain
             // string explanation of the error.
-            let trap = trap.to_string().into_bytes();
+            let trap = trap.to_string().into_bytes().try_into().unwrap_or_default();
 
             // Creating reply message.
             let trap_reply = ReplyMessage::system(message_id, trap, core_processor::ERR_EXIT_CODE)
@@ -112,7 +201,61 @@ where
         todo!("#646");
     }
 
-    fn wake_message(&mut self, _program_id: ProgramId, _message_id: MessageId) {
-        todo!("issue #349");
+    fn wake_message(&mut self, program_id: ProgramId, message_id: MessageId) {
+        // Message could be already removed from waitlist by the time
+        // of scheduled wake, so there is nothing to do in this case.
+        if let Some(dispatch) = self.wake_message_impl(program_id, message_id) {
+            // Depositing appropriate event.
+            Pallet::<T>::deposit_event(Event::MessageWoken {
+                id: dispatch.id(),
+                reason: MessageWokenSystemReason::TimeoutHasCome.into_reason(),
+            });
+
+            // Enqueueing dispatch into message queue.
+            Pallet::<T>::queue_dispatch(dispatch);
+        }
+    }
+
+    fn send_dispatch(&mut self, stashed_message_id: MessageId) {
+        // Taking dispatch from stash.
+        //
+        // Gas for it was already split on the stashing,
+        // so it's ready to be enqueued.
+        let (dispatch, interval) = match DispatchStashOf::<T>::take(stashed_message_id) {
+            Some(stashed) => stashed,
+            None => {
+                log::error!(
+                    target: "essential",
+                    "Stashed dispatch {:?} isn't found",
+                    stashed_message_id
+                );
+                return;
+            }
+        };
+
+        // Charging for holding dispatch in stash.
+        //
+        // Task could be processed later than the sending block in case
+        // of missed blocks, but the rent is charged only for the interval
+        // requested by the program.
+        let duration = interval
+            .finish
+            .saturated_into::<u32>()
+            .saturating_sub(interval.start.saturated_into::<u32>());
+
+        self.charge_for_hold(
+            dispatch.id(),
+            duration,
+            CostsPerBlockOf::<T>::dispatch_stash(),
+        );
+
+        // Enqueueing dispatch into message queue.
+        Pallet::<T>::queue_dispatch(dispatch);
+    }
+
+    fn remove_gas_reservation(&mut self, program_id: ProgramId, reservation_id: ReservationId) {
+        // Reservation could be already used or removed by the program,
+        // so there is nothing to do in this case.
+        let _ = self.remove_gas_reservation_impl(program_id, reservation_id);
     }
 }
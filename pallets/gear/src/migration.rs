@@ -17,13 +17,173 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{Config, Pallet, Weight};
+use common::Origin;
+use frame_support::traits::{Get, StorageVersion};
 
 /// Wrapper for all migrations of this pallet, based on `StorageVersion`.
-pub fn migrate<T: Config>() -> Weight {
-    use frame_support::traits::StorageVersion;
+pub fn migrate<T: Config>() -> Weight
+where
+    T::AccountId: Origin,
+{
+    let version = StorageVersion::get::<Pallet<T>>();
+    let mut weight: Weight = 0;
 
-    let _version = StorageVersion::get::<Pallet<T>>();
-    let weight: Weight = 0;
+    if version < 2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
+
+    if version < 3 {
+        weight = weight.saturating_add(v3::migrate::<T>());
+    }
 
     weight
 }
+
+/// Funds for gas and value of messages in flight are held by the bank
+/// instead of being reserved on the accounts of their senders.
+pub(crate) mod v2 {
+    use super::*;
+    use crate::{
+        BalanceOf, DispatchStashOf, GasHandlerOf, GearBank, MailboxOf, QueueOf, WaitlistOf,
+    };
+    use common::{storage::*, GasPrice, GasTree};
+    use frame_support::traits::{BalanceStatus, ReservableCurrency};
+    use gear_core::message::StoredDispatch;
+    use sp_runtime::traits::{Saturating, UniqueSaturatedInto, Zero};
+    use sp_std::collections::btree_map::BTreeMap;
+
+    pub(crate) fn migrate<T: Config>() -> Weight
+    where
+        T::AccountId: Origin,
+    {
+        let mut reserves: BTreeMap<T::AccountId, BalanceOf<T>> = BTreeMap::new();
+        let mut count: u64 = 0;
+
+        let mut add_reserve = |who: T::AccountId, amount: BalanceOf<T>| {
+            count += 1;
+
+            let reserve = reserves.entry(who).or_insert_with(Zero::zero);
+            *reserve = reserve.saturating_add(amount);
+        };
+
+        let gas_values = GasHandlerOf::<T>::values_by_external().unwrap_or_else(|e| {
+            log::error!(
+                "Gas tree is corrupted, gas reserves aren't migrated: {:?}",
+                e
+            );
+            Default::default()
+        });
+
+        for (external, gas) in gas_values {
+            add_reserve(external, T::GasPrice::gas_price(gas));
+        }
+
+        let mut add_value_reserve = |dispatch: &StoredDispatch| {
+            if GearBank::<T>::holds_value(dispatch) {
+                add_reserve(
+                    <T::AccountId as Origin>::from_origin(dispatch.source().into_origin()),
+                    dispatch.value().unique_saturated_into(),
+                );
+            }
+        };
+
+        QueueOf::<T>::iter()
+            .filter_map(Result::ok)
+            .for_each(|dispatch| add_value_reserve(&dispatch));
+
+        WaitlistOf::<T>::iter().for_each(|(dispatch, _)| add_value_reserve(&dispatch));
+
+        DispatchStashOf::<T>::iter().for_each(|(dispatch, _)| add_value_reserve(&dispatch));
+
+        for message in MailboxOf::<T>::iter().filter(|message| message.value() != 0) {
+            add_reserve(
+                <T::AccountId as Origin>::from_origin(message.source().into_origin()),
+                message.value().unique_saturated_into(),
+            );
+        }
+
+        let accounts = reserves.len() as u64;
+
+        // Existential deposit of the bank is paid by the first of its
+        // users, which is able to pay it.
+        let bank = reserves
+            .keys()
+            .find_map(|who| GearBank::<T>::provide_account(who).ok());
+
+        if let Some(bank) = bank {
+            for (who, reserve) in reserves {
+                match <T as Config>::Currency::repatriate_reserved(
+                    &who,
+                    &bank,
+                    reserve,
+                    BalanceStatus::Free,
+                ) {
+                    Ok(leftover) if !leftover.is_zero() => log::debug!(
+                        "Reserve of {:?} isn't fully moved to the bank: amount = {:?}, leftover = {:?}",
+                        who,
+                        reserve,
+                        leftover,
+                    ),
+                    Err(e) => log::error!("Failed to move reserve of {:?} to the bank: {:?}", who, e),
+                    _ => {}
+                }
+            }
+        } else if accounts != 0 {
+            log::error!("Bank account isn't provided, reserves aren't migrated");
+        }
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!(
+            "Migrated reserves of {} gas nodes and messages of {} accounts to the bank",
+            count,
+            accounts
+        );
+
+        T::DbWeight::get().reads_writes(count + 3 * accounts + 1, 2 * accounts + 6)
+    }
+}
+
+/// Active programs, created before rent was introduced, stay active
+/// for the rent free period since the migration, then they are paused.
+pub(crate) mod v3 {
+    use super::*;
+    use crate::ProgramExpirations;
+    use common::Program;
+    use gear_core::ids::ProgramId;
+    use sp_runtime::traits::Saturating;
+
+    pub(crate) fn migrate<T: Config>() -> Weight
+    where
+        T::AccountId: Origin,
+    {
+        let expiration = <frame_system::Pallet<T>>::block_number()
+            .saturating_add(T::ProgramRentFreePeriod::get());
+
+        let mut programs: u64 = 0;
+        let mut count: u64 = 0;
+
+        for id in common::program_ids() {
+            programs += 1;
+
+            let program_id = ProgramId::from_origin(id);
+
+            if matches!(common::get_program(id), Some(Program::Active(_)))
+                && !ProgramExpirations::<T>::contains_key(program_id)
+            {
+                Pallet::<T>::set_program_expiration(program_id, expiration);
+                count += 1;
+            }
+        }
+
+        StorageVersion::new(3).put::<Pallet<T>>();
+
+        log::info!(
+            "Scheduled pausing of {} active programs at block {:?}",
+            count,
+            expiration
+        );
+
+        T::DbWeight::get().reads_writes(2 * programs + 1, 2 * count + 1)
+    }
+}
@@ -22,13 +22,14 @@ use frame_support::{
     construct_runtime,
     pallet_prelude::*,
     parameter_types,
-    traits::{ConstU64, FindAuthor},
+    traits::{ConstU64, Contains, FindAuthor, ReservableCurrency},
 };
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
 };
 use sp_std::convert::{TryFrom, TryInto};
 
@@ -41,6 +42,7 @@ pub(crate) const USER_2: AccountId = 2;
 pub(crate) const USER_3: AccountId = 3;
 pub(crate) const LOW_BALANCE_USER: AccountId = 4;
 pub(crate) const BLOCK_AUTHOR: AccountId = 255;
+pub(crate) const FORBIDDEN_DESTINATION: AccountId = 256;
 
 // Configure a mock runtime to test the pallet.
 construct_runtime!(
@@ -54,7 +56,7 @@ construct_runtime!(
         GearMessenger: pallet_gear_messenger::{Pallet},
         GearScheduler: pallet_gear_scheduler::{Pallet},
         Gear: pallet_gear::{Pallet, Call, Storage, Event<T>},
-        GearGas: pallet_gear_gas::{Pallet},
+        GearGas: pallet_gear_gas::{Pallet, Event<T>},
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
         Authorship: pallet_authorship::{Pallet, Storage},
         Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
@@ -116,13 +118,31 @@ impl pallet_gear_program::Config for Test {
     type WeightInfo = ();
     type Currency = Balances;
     type Messenger = GearMessenger;
+    type ProgramCreationQuota = ();
+    type ResumeSessionDeposit = ();
+    type ResumeSessionDuration = ();
+    type CodeDeposit = CodeDeposit;
+    type CodeDepositRefund = CodeDepositRefund;
+    type OnProgramPause = pallet_gear::OnProgramPause<Test>;
+    type OnProgramResume = pallet_gear::OnProgramResume<Test>;
 }
 
 parameter_types! {
     pub const MailboxThreshold: u64 = 3_000;
     pub const BlockGasLimit: u64 = 100_000_000_000;
     pub const OutgoingLimit: u32 = 1024;
+    pub const OutgoingBytesLimit: u32 = 64 * 1024 * 1024;
+    pub const ProgramCandidatesLimit: u32 = 128;
+    pub const ReplyChainDepthLimit: u32 = 16;
     pub GearSchedule: pallet_gear::Schedule<Test> = <pallet_gear::Schedule<Test>>::default();
+    pub storage GasMinimums: core_processor::configs::DispatchGasMinimums = Default::default();
+    pub storage GasEstimationDepth: u32 = 16;
+    pub storage WaitlistCapacity: u32 = 2048;
+    pub storage ProgramRentFreePeriod: u64 = 1_000_000;
+    pub static CodeDeposit: u128 = 0;
+    pub static CodeDepositRefund: Option<Perbill> = None;
+    pub static ExistentialDepositFreePrograms: bool = false;
+    pub static DustBeneficiary: Option<AccountId> = None;
 }
 
 impl pallet_gear::Config for Test {
@@ -132,28 +152,57 @@ impl pallet_gear::Config for Test {
     type WeightInfo = ();
     type Schedule = GearSchedule;
     type OutgoingLimit = OutgoingLimit;
+    type OutgoingBytesLimit = OutgoingBytesLimit;
+    type ProgramCandidatesLimit = ProgramCandidatesLimit;
     type DebugInfo = ();
     type CodeStorage = GearProgram;
     type MailboxThreshold = MailboxThreshold;
+    type GasMinimums = GasMinimums;
+    type GasEstimationDepth = GasEstimationDepth;
+    type ProgramRentFreePeriod = ProgramRentFreePeriod;
+    type ExistentialDepositFreePrograms = ExistentialDepositFreePrograms;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
     type Scheduler = GearScheduler;
+    type QueueRunner = pallet_gear::ProcessUntilAllowance;
+    type ForbiddenDestinations = ForbiddenDestinations;
+    type ReplyChainDepthLimit = ReplyChainDepthLimit;
+    type QueueProcessingOrigin = system::EnsureRoot<AccountId>;
+    type FallbackAuthor = ();
+    type DustBeneficiary = DustBeneficiary;
+    type ProgramBanOrigin = system::EnsureRoot<AccountId>;
+}
+
+pub struct ForbiddenDestinations;
+
+impl Contains<ProgramId> for ForbiddenDestinations {
+    fn contains(id: &ProgramId) -> bool {
+        *id == <ProgramId as common::Origin>::from_origin(common::Origin::into_origin(
+            FORBIDDEN_DESTINATION,
+        ))
+    }
 }
 
 impl pallet_gear_scheduler::Config for Test {
     type BlockLimiter = GearGas;
     type ReserveThreshold = ConstU64<1>;
     type WaitlistCost = ConstU64<100>;
+    type MailboxCost = ConstU64<100>;
+    type DispatchStashCost = ConstU64<100>;
+    type ProgramCost = ConstU64<1_000>;
 }
 
 impl pallet_gear_gas::Config for Test {
+    type Event = Event;
     type BlockGasLimit = BlockGasLimit;
+    type EventThreshold = ConstU64<{ u64::MAX }>;
 }
 
 impl pallet_gear_messenger::Config for Test {
-    type Currency = Balances;
     type BlockLimiter = GearGas;
+    type WaitlistCapacity = WaitlistCapacity;
+    type WeightInfo = ();
 }
 
 pub struct FixedBlockAuthor;
@@ -191,6 +240,9 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
         .build_storage::<Test>()
         .unwrap();
 
+    let bank_address = GearBank::<Test>::account();
+    let existential_deposit = ExistentialDeposit::get() as u128;
+
     pallet_balances::GenesisConfig::<Test> {
         balances: vec![
             (USER_1, 500_000_000_000_u128),
@@ -198,13 +250,21 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
             (USER_3, 500_000_000_000_u128),
             (LOW_BALANCE_USER, 1000_u128),
             (BLOCK_AUTHOR, 500_u128),
+            (bank_address, existential_deposit),
         ],
     }
     .assimilate_storage(&mut t)
     .unwrap();
 
     let mut ext = sp_io::TestExternalities::new(t);
-    ext.execute_with(|| System::set_block_number(1));
+    ext.execute_with(|| {
+        System::set_block_number(1);
+
+        // Bank is provided at genesis, so its existential deposit
+        // isn't paid by the users sending messages in tests.
+        Balances::reserve(&bank_address, existential_deposit)
+            .expect("Bank is endowed with existential deposit");
+    });
     ext
 }
 
@@ -232,3 +292,17 @@ pub fn run_to_block(n: u64, remaining_weight: Option<u64>) {
 pub fn run_to_next_block(remaining_weight: Option<u64>) {
     run_to_block(System::block_number() + 1, remaining_weight);
 }
+
+/// Fast-forwards to the nearest block with scheduled tasks and runs it.
+///
+/// Blocks before it are skipped without running, so it's expected
+/// that there are no messages in the queue, which need them to be processed.
+/// Returns the reached block number or `None` if no tasks are scheduled.
+pub fn run_to_next_task(remaining_weight: Option<u64>) -> Option<u64> {
+    let bn = GearScheduler::next_task_block(System::block_number())?;
+
+    System::set_block_number(bn - 1);
+    run_to_block(bn, remaining_weight);
+
+    Some(bn)
+}
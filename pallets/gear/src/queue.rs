@@ -0,0 +1,250 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Strategies of the message queue processing.
+//!
+//! Strategy is selected by `Config::QueueRunner`, so the experimental ones
+//! could be tried on dev chains. Regardless of the strategy, processing is
+//! stopped once the block gas allowance is exceeded.
+//!
+//! Strategies only change the order of taking dispatches, so dispatches
+//! to the same program are always processed in the order of queueing.
+
+use crate::{Config, GasAllowanceOf, Pallet, QueueOf};
+use common::storage::*;
+use frame_support::traits::Get;
+use gear_core::{ids::ProgramId, message::StoredDispatch};
+use sp_std::{
+    collections::{btree_map::BTreeMap, vec_deque::VecDeque},
+    marker::PhantomData,
+    prelude::*,
+};
+
+/// Strategy of taking dispatches from the message queue.
+///
+/// Runner is created for the queue processing of each block,
+/// so it could keep the state of the current block's processing.
+pub trait QueueRunner<T: Config>: Default {
+    /// Takes the next dispatch to process within the block.
+    ///
+    /// Returns `None` if the queue processing should be stopped.
+    fn next_dispatch(&mut self) -> Option<StoredDispatch>;
+
+    /// Finishes the queue processing within the block.
+    ///
+    /// Dispatches taken from the queue, but not returned for processing,
+    /// are put back to its head in their original order.
+    fn finish(&mut self) {}
+}
+
+/// Takes the dispatch from the head of the queue.
+///
+/// Corrupted queue stops the processing within the block.
+fn dequeue<T: Config>() -> Option<StoredDispatch> {
+    QueueOf::<T>::dequeue().unwrap_or_else(|e| {
+        log::error!(target: "essential", "Message queue corrupted! {:?}", e);
+        None
+    })
+}
+
+fn requeue<T: Config>(deferred: impl DoubleEndedIterator<Item = StoredDispatch>) {
+    for dispatch in deferred.rev() {
+        let id = dispatch.id();
+
+        if let Err(e) = QueueOf::<T>::requeue(dispatch) {
+            Pallet::<T>::quarantine(id, None, e);
+        }
+    }
+}
+
+/// Storage cost of deferring the dispatch: taking it from the head
+/// of the queue and putting it back there on finish. Both update
+/// the head, the node and the counter of dequeued dispatches.
+fn deferral_cost<T: Config>() -> u64 {
+    T::DbWeight::get().reads_writes(6, 6)
+}
+
+/// Processes dispatches in the queue order, while the block
+/// gas allowance suffices.
+#[derive(Default)]
+pub struct ProcessUntilAllowance;
+
+impl<T: Config> QueueRunner<T> for ProcessUntilAllowance {
+    fn next_dispatch(&mut self) -> Option<StoredDispatch> {
+        dequeue::<T>()
+    }
+}
+
+/// Processes at most `Limit` dispatches per block in the queue order.
+pub struct ProcessLimited<Limit> {
+    processed: u32,
+    _phantom: PhantomData<Limit>,
+}
+
+impl<Limit> Default for ProcessLimited<Limit> {
+    fn default() -> Self {
+        Self {
+            processed: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Config, Limit: Get<u32>> QueueRunner<T> for ProcessLimited<Limit> {
+    fn next_dispatch(&mut self) -> Option<StoredDispatch> {
+        if self.processed >= Limit::get() {
+            return None;
+        }
+
+        let dispatch = dequeue::<T>()?;
+        self.processed = self.processed.saturating_add(1);
+
+        Some(dispatch)
+    }
+}
+
+/// Processes at most `Share` dispatches per program within the block.
+///
+/// Dispatches of programs, which have already used their share, are
+/// deferred till the end of the block and put back to the head of
+/// the queue to be processed in the next blocks.
+pub struct ProcessFairShare<Share> {
+    processed: BTreeMap<ProgramId, u32>,
+    deferred: Vec<StoredDispatch>,
+    _phantom: PhantomData<Share>,
+}
+
+impl<Share> Default for ProcessFairShare<Share> {
+    fn default() -> Self {
+        Self {
+            processed: Default::default(),
+            deferred: Default::default(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Config, Share: Get<u32>> QueueRunner<T> for ProcessFairShare<Share> {
+    fn next_dispatch(&mut self) -> Option<StoredDispatch> {
+        let cost = deferral_cost::<T>();
+
+        loop {
+            let dispatch = dequeue::<T>()?;
+            let processed = self.processed.entry(dispatch.destination()).or_default();
+
+            if *processed < Share::get() {
+                *processed = processed.saturating_add(1);
+
+                return Some(dispatch);
+            }
+
+            self.deferred.push(dispatch);
+            GasAllowanceOf::<T>::decrease(cost);
+
+            if GasAllowanceOf::<T>::get() < cost {
+                return None;
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        requeue::<T>(self.deferred.drain(..));
+    }
+}
+
+/// Processes dispatches round-robin over destination programs, taking at
+/// most `Share` dispatches per program within the block.
+///
+/// Each round takes a single dispatch of every program having queued ones,
+/// so programs with many queued dispatches can't starve the others.
+/// Dispatches of programs, which were already served within the round,
+/// are deferred to the next round, which goes through them before
+/// the rest of the queue, keeping the order of dispatches to the same
+/// program. Deferred dispatches are kept till the end of the block,
+/// so each of them is taken from the storage only once.
+pub struct ProcessRoundRobin<Share> {
+    processed: BTreeMap<ProgramId, u32>,
+    round: u32,
+    current: VecDeque<StoredDispatch>,
+    deferred: Vec<StoredDispatch>,
+    _phantom: PhantomData<Share>,
+}
+
+impl<Share> Default for ProcessRoundRobin<Share> {
+    fn default() -> Self {
+        Self {
+            processed: Default::default(),
+            round: 0,
+            current: Default::default(),
+            deferred: Default::default(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Config, Share: Get<u32>> QueueRunner<T> for ProcessRoundRobin<Share> {
+    fn next_dispatch(&mut self) -> Option<StoredDispatch> {
+        let cost = deferral_cost::<T>();
+
+        loop {
+            if self.round >= Share::get() {
+                return None;
+            }
+
+            // Dispatches deferred within the previous round
+            // precede the ones left in the queue.
+            let (dispatch, stored) = match self.current.pop_front() {
+                Some(dispatch) => (dispatch, false),
+                None => match dequeue::<T>() {
+                    Some(dispatch) => (dispatch, true),
+                    None if self.deferred.is_empty() => return None,
+                    None => {
+                        self.round = self.round.saturating_add(1);
+                        self.current = self.deferred.drain(..).collect();
+
+                        continue;
+                    }
+                },
+            };
+
+            let processed = self.processed.entry(dispatch.destination()).or_default();
+
+            if *processed <= self.round {
+                *processed = processed.saturating_add(1);
+
+                return Some(dispatch);
+            }
+
+            self.deferred.push(dispatch);
+
+            if stored {
+                GasAllowanceOf::<T>::decrease(cost);
+
+                if GasAllowanceOf::<T>::get() < cost {
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        // Dispatches deferred within the current round were taken
+        // before the ones, which the round hasn't reached yet.
+        requeue::<T>(self.deferred.drain(..).chain(self.current.drain(..)));
+    }
+}
@@ -24,6 +24,7 @@
 use crate::{weights::WeightInfo, Config};
 
 use codec::{Decode, Encode};
+use core_processor::configs::OutgoingLimitScaling;
 use frame_support::{weights::Weight, DefaultNoBound};
 use gear_core::costs::HostFnWeights as CoreHostFnWeights;
 use pallet_gear_proc_macro::{ScheduleDebug, WeightDebug};
@@ -152,6 +153,51 @@ pub struct Limits {
     /// version of the code. Therefore `instantiate_with_code` can fail even when supplying
     /// a wasm binary below this maximum size.
     pub code_len: u32,
+
+    /// The maximum amount of blocks a message is allowed to stay in the waitlist.
+    pub max_wait_duration: u32,
+
+    /// The amount of blocks plain `wait` keeps a message in the waitlist,
+    /// as if `wait_up_to` was called with this value.
+    ///
+    /// `None` means that chain allows infinite waits: plain `wait` keeps
+    /// a message in the waitlist while it is able to pay the rent.
+    pub default_wait_up_to: Option<u32>,
+
+    /// The way float instructions of a program code are treated.
+    ///
+    /// Changing this value requires bumping [`InstructionWeights::version`],
+    /// so the codes uploaded before are re-instrumented.
+    pub floats: FloatsPolicy,
+
+    /// The amount of gas each outgoing message of a dispatch is backed with.
+    ///
+    /// If set, the outgoing messages limit of a dispatch is proportional to its
+    /// gas limit, but not less than `outgoing_floor` and not greater than
+    /// `Config::OutgoingLimit`. `None` means the limit is the same for all dispatches.
+    pub outgoing_gas_per_message: Option<u64>,
+
+    /// The minimal outgoing messages limit of a dispatch,
+    /// if the limit is scaled with its gas limit.
+    pub outgoing_floor: u32,
+}
+
+/// Describes how float instructions of a program code are treated.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum FloatsPolicy {
+    /// Codes containing float instructions are rejected.
+    Reject,
+    /// Float instructions are allowed, but each NaN they produce is replaced
+    /// with the canonical one, which makes their execution deterministic.
+    Canonicalize,
+}
+
+impl FloatsPolicy {
+    /// Whether NaNs produced by float instructions should be canonicalized.
+    pub fn canonicalize(&self) -> bool {
+        matches!(self, Self::Canonicalize)
+    }
 }
 
 impl Limits {
@@ -159,6 +205,37 @@ impl Limits {
     pub fn max_memory_size(&self) -> u32 {
         self.memory_pages * 64 * 1024
     }
+
+    /// The amount of blocks a message waited with plain `wait` stays in the waitlist,
+    /// given the amount of blocks the message is able to pay the rent for.
+    pub fn wait_duration(&self, affordable: u32) -> u32 {
+        match self.default_wait_up_to {
+            Some(up_to) => affordable.min(up_to.min(self.max_wait_duration)),
+            None => affordable,
+        }
+    }
+
+    /// The amount of blocks a message waited with `wait_up_to` stays in the waitlist,
+    /// given the requested duration and the amount of blocks the message is able
+    /// to pay the rent for.
+    pub fn wait_up_to_duration(&self, duration: u32, affordable: u32) -> u32 {
+        duration.min(affordable).min(self.max_wait_duration)
+    }
+
+    /// Whether a message waited with `wait_for` is able to stay in the waitlist
+    /// for the whole requested duration.
+    pub fn can_wait_for(&self, duration: u32, affordable: u32) -> bool {
+        duration <= affordable.min(self.max_wait_duration)
+    }
+
+    /// Scaling of the outgoing messages limit with the gas limit of a dispatch.
+    pub fn outgoing_scaling(&self) -> Option<OutgoingLimitScaling> {
+        self.outgoing_gas_per_message
+            .map(|gas_per_message| OutgoingLimitScaling {
+                gas_per_message,
+                floor: self.outgoing_floor,
+            })
+    }
 }
 
 /// Describes the weight for all categories of supported wasm instructions.
@@ -261,6 +338,9 @@ pub struct HostFnWeights<T: Config> {
     /// Weight of calling `alloc`.
     pub alloc: Weight,
 
+    /// Weight of calling `free`.
+    pub free: Weight,
+
     /// Weight of calling `gr_gas_available`.
     pub gr_gas_available: Weight,
 
@@ -306,6 +386,15 @@ pub struct HostFnWeights<T: Config> {
     /// Weight per payload byte by `gr_send_push`.
     pub gr_send_push_per_byte: Weight,
 
+    /// Weight of calling `gr_send_push_input`.
+    pub gr_send_push_input: Weight,
+
+    /// Weight per forwarded byte by `gr_send_push_input`.
+    pub gr_send_push_input_per_byte: Weight,
+
+    /// Weight of calling `gr_send_cancel`.
+    pub gr_send_cancel: Weight,
+
     /// Weight of calling `gr_send_commit`.
     pub gr_send_commit: Weight,
 
@@ -324,6 +413,12 @@ pub struct HostFnWeights<T: Config> {
     /// Weight per payload byte by `gr_reply_push`.
     pub gr_reply_push_per_byte: Weight,
 
+    /// Weight of calling `gr_reply_push_input`.
+    pub gr_reply_push_input: Weight,
+
+    /// Weight per forwarded byte by `gr_reply_push_input`.
+    pub gr_reply_push_input_per_byte: Weight,
+
     /// Weight of calling `gr_reply_to`.
     pub gr_reply_to: Weight,
 
@@ -333,6 +428,9 @@ pub struct HostFnWeights<T: Config> {
     /// Weight of calling `gr_exit_code`.
     pub gr_exit_code: Weight,
 
+    /// Weight of calling `gr_error`.
+    pub gr_error: Weight,
+
     /// Weight of calling `gr_exit`.
     pub gr_exit: Weight,
 
@@ -342,6 +440,12 @@ pub struct HostFnWeights<T: Config> {
     /// Weight of calling `gr_wait`.
     pub gr_wait: Weight,
 
+    /// Weight of calling `gr_wait_for`.
+    pub gr_wait_for: Weight,
+
+    /// Weight of calling `gr_wait_up_to`.
+    pub gr_wait_up_to: Weight,
+
     /// Weight of calling `gr_wake`.
     pub gr_wake: Weight,
 
@@ -351,6 +455,27 @@ pub struct HostFnWeights<T: Config> {
     /// Weight per payload byte by `create_program_wgas`.
     pub gr_create_program_wgas_per_byte: Weight,
 
+    /// Weight of calling `gr_reserve_gas`.
+    pub gr_reserve_gas: Weight,
+
+    /// Weight of calling `gr_unreserve_gas`.
+    pub gr_unreserve_gas: Weight,
+
+    /// Weight of calling `gr_reservation_send`.
+    pub gr_reservation_send: Weight,
+
+    /// Weight of calling `gr_system_reserve_gas`.
+    pub gr_system_reserve_gas: Weight,
+
+    /// Weight of calling `gr_metric_increment`.
+    pub gr_metric_increment: Weight,
+
+    /// Weight of calling `gr_emit_event`.
+    pub gr_emit_event: Weight,
+
+    /// Weight per payload byte by `gr_emit_event`.
+    pub gr_emit_event_per_byte: Weight,
+
     /// Weight of calling `gas`.
     pub gas: Weight,
 
@@ -376,6 +501,12 @@ pub struct MemoryWeights<T: Config> {
     /// Weight of loading page.
     pub load_cost: Weight,
 
+    /// Weight of the first read of a gear page within the execution.
+    pub page_read_cost: Weight,
+
+    /// Weight of the first write of a gear page within the execution.
+    pub page_write_cost: Weight,
+
     /// The type parameter is used in the default implementation.
     #[codec(skip)]
     pub _phantom: PhantomData<T>,
@@ -478,6 +609,13 @@ impl Default for Limits {
             call_depth: 32,
             payload_len: 64 * 1024,
             code_len: 512 * 1024,
+            // ~ 1 month of 6 seconds blocks.
+            max_wait_duration: 432_000,
+            // ~ 1 week of 6 seconds blocks.
+            default_wait_up_to: Some(100_800),
+            floats: FloatsPolicy::Reject,
+            outgoing_gas_per_message: None,
+            outgoing_floor: 16,
         }
     }
 }
@@ -545,6 +683,7 @@ impl<T: Config> HostFnWeights<T> {
     pub fn into_core(self) -> CoreHostFnWeights {
         CoreHostFnWeights {
             alloc: self.alloc,
+            free: self.free,
             gr_gas_available: self.gr_gas_available,
             gr_msg_id: self.gr_msg_id,
             gr_origin: self.gr_origin,
@@ -560,21 +699,36 @@ impl<T: Config> HostFnWeights<T> {
             gr_send_init: self.gr_send_init,
             gr_send_push: self.gr_send_push,
             gr_send_push_per_byte: self.gr_send_push_per_byte,
+            gr_send_push_input: self.gr_send_push_input,
+            gr_send_push_input_per_byte: self.gr_send_push_input_per_byte,
+            gr_send_cancel: self.gr_send_cancel,
             gr_send_commit: self.gr_send_commit,
             gr_send_commit_per_byte: self.gr_send_commit_per_byte,
             gr_reply_commit: self.gr_reply_commit,
             gr_reply_commit_per_byte: self.gr_reply_commit_per_byte,
             gr_reply_push: self.gr_reply_push,
             gr_reply_push_per_byte: self.gr_reply_push_per_byte,
+            gr_reply_push_input: self.gr_reply_push_input,
+            gr_reply_push_input_per_byte: self.gr_reply_push_input_per_byte,
             gr_debug: self.gr_debug,
             gr_reply_to: self.gr_reply_to,
             gr_exit_code: self.gr_exit_code,
+            gr_error: self.gr_error,
             gr_exit: self.gr_exit,
             gr_leave: self.gr_leave,
             gr_wait: self.gr_wait,
+            gr_wait_for: self.gr_wait_for,
+            gr_wait_up_to: self.gr_wait_up_to,
             gr_wake: self.gr_wake,
             gr_create_program_wgas: self.gr_create_program_wgas,
             gr_create_program_wgas_per_byte: self.gr_create_program_wgas_per_byte,
+            gr_reserve_gas: self.gr_reserve_gas,
+            gr_unreserve_gas: self.gr_unreserve_gas,
+            gr_reservation_send: self.gr_reservation_send,
+            gr_system_reserve_gas: self.gr_system_reserve_gas,
+            gr_metric_increment: self.gr_metric_increment,
+            gr_emit_event: self.gr_emit_event,
+            gr_emit_event_per_byte: self.gr_emit_event_per_byte,
             gas: self.gas,
         }
     }
@@ -584,6 +738,7 @@ impl<T: Config> Default for HostFnWeights<T> {
     fn default() -> Self {
         Self {
             alloc: cost_batched!(alloc),
+            free: cost_batched!(free).saturating_sub(cost_batched!(alloc)),
             gr_gas_available: cost_batched!(gr_gas_available),
             gr_msg_id: cost_batched!(gr_msg_id),
             gr_origin: cost_batched!(gr_origin),
@@ -599,21 +754,36 @@ impl<T: Config> Default for HostFnWeights<T> {
             gr_send_init: cost_batched!(gr_send_init),
             gr_send_push: cost_batched!(gr_send_push),
             gr_send_push_per_byte: cost_byte_batched!(gr_send_push_per_kb),
+            gr_send_push_input: cost_batched!(gr_send_push_input),
+            gr_send_push_input_per_byte: cost_byte_batched!(gr_send_push_input_per_kb),
+            gr_send_cancel: cost_batched!(gr_send_cancel) - cost_batched!(gr_send_init),
             gr_send_commit: cost_batched!(gr_send_commit) - cost_batched!(gr_send_init),
             gr_send_commit_per_byte: cost_byte!(gr_send_commit_per_kb),
             gr_reply_commit: cost_batched!(gr_reply_commit),
             gr_reply_commit_per_byte: cost_byte_batched!(gr_reply_commit_per_kb),
             gr_reply_push: cost_batched!(gr_reply_push),
             gr_reply_push_per_byte: cost_byte_batched!(gr_reply_push_per_kb),
+            gr_reply_push_input: cost_batched!(gr_reply_push_input),
+            gr_reply_push_input_per_byte: cost_byte_batched!(gr_reply_push_input_per_kb),
             gr_debug: cost_batched!(gr_debug),
             gr_reply_to: cost_batched!(gr_reply_to),
             gr_exit_code: cost_batched!(gr_exit_code),
+            gr_error: cost_batched!(gr_error),
             gr_exit: cost!(gr_exit),
             gr_leave: cost!(gr_leave),
             gr_wait: cost!(gr_wait),
+            gr_wait_for: cost!(gr_wait_for),
+            gr_wait_up_to: cost!(gr_wait_up_to),
             gr_wake: cost_batched!(gr_wake),
             gr_create_program_wgas: cost!(gr_create_program_wgas),
             gr_create_program_wgas_per_byte: cost_byte_batched!(gr_create_program_wgas_per_kb),
+            gr_reserve_gas: cost_batched!(gr_reserve_gas),
+            gr_unreserve_gas: cost_batched!(gr_unreserve_gas) - cost_batched!(gr_reserve_gas),
+            gr_reservation_send: cost_batched!(gr_reservation_send) - cost_batched!(gr_reserve_gas),
+            gr_system_reserve_gas: cost_batched!(gr_system_reserve_gas),
+            gr_metric_increment: cost_batched!(gr_metric_increment),
+            gr_emit_event: cost_batched!(gr_emit_event),
+            gr_emit_event_per_byte: cost_byte_batched!(gr_emit_event_per_kb),
             gas: cost_batched!(gas),
             _phantom: PhantomData,
         }
@@ -627,6 +797,8 @@ impl<T: Config> Default for MemoryWeights<T> {
             allocation_cost: <T as super::pallet::Config>::WeightInfo::allocation_cost(),
             grow_cost: <T as super::pallet::Config>::WeightInfo::grow_cost(),
             load_cost: <T as super::pallet::Config>::WeightInfo::load_cost(),
+            page_read_cost: <T as super::pallet::Config>::WeightInfo::page_read_cost(),
+            page_write_cost: <T as super::pallet::Config>::WeightInfo::page_write_cost(),
             _phantom: PhantomData,
         }
     }
@@ -730,6 +902,11 @@ impl<'a, T: Config> gas_metering::Rules for ScheduleRules<'a, T> {
             I32Rotl | I64Rotl => w.i64rotl,
             I32Rotr | I64Rotr => w.i64rotr,
 
+            // Float instructions are priced as their integer counterparts.
+            _ if self.schedule.limits.floats.canonicalize() => {
+                return float_instruction_cost(w, instruction)
+            }
+
             // Returning None makes the gas instrumentation fail which we intend for
             // unsupported or unknown instructions.
             _ => return None,
@@ -742,14 +919,120 @@ impl<'a, T: Config> gas_metering::Rules for ScheduleRules<'a, T> {
     }
 }
 
+/// Weight of a float instruction, which is allowed by [`FloatsPolicy::Canonicalize`].
+///
+/// NaN canonicalization instructions added after the arithmetic
+/// ones are charged separately, as any other instruction.
+fn float_instruction_cost<T: Config>(
+    w: &InstructionWeights<T>,
+    instruction: &elements::Instruction,
+) -> Option<u32> {
+    use self::elements::Instruction::*;
+
+    let weight = match *instruction {
+        F32Const(_) | F64Const(_) => w.i64const,
+        F32Load(_, _) | F64Load(_, _) => w.i64load,
+        F32Store(_, _) | F64Store(_, _) => w.i64store,
+        F32Eq | F64Eq => w.i64eq,
+        F32Ne | F64Ne => w.i64ne,
+        F32Lt | F64Lt => w.i64lts,
+        F32Gt | F64Gt => w.i64gts,
+        F32Le | F64Le => w.i64les,
+        F32Ge | F64Ge => w.i64ges,
+        F32Abs | F64Abs | F32Neg | F64Neg | F32Copysign | F64Copysign => w.i64and,
+        F32Add | F64Add => w.i64add,
+        F32Sub | F64Sub => w.i64sub,
+        F32Mul | F64Mul => w.i64mul,
+        F32Min | F64Min | F32Max | F64Max => w.select,
+        F32Div | F64Div | F32Sqrt | F64Sqrt | F32Ceil | F64Ceil | F32Floor | F64Floor
+        | F32Trunc | F64Trunc | F32Nearest | F64Nearest => w.i64divs,
+        I32TruncSF32 | I32TruncUF32 | I32TruncSF64 | I32TruncUF64 | I64TruncSF32 | I64TruncUF32
+        | I64TruncSF64 | I64TruncUF64 => w.i64divs,
+        F32ConvertSI32 | F32ConvertUI32 | F32ConvertSI64 | F32ConvertUI64 | F64ConvertSI32
+        | F64ConvertUI32 | F64ConvertSI64 | F64ConvertUI64 => w.i64extendsi32,
+        F32DemoteF64 | F64PromoteF32 | I32ReinterpretF32 | I64ReinterpretF64
+        | F32ReinterpretI32 | F64ReinterpretI64 => w.i32wrapi64,
+        _ => return None,
+    };
+
+    Some(weight)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::mock::Test;
+    use gas_metering::Rules;
 
     #[test]
     fn print_test_schedule() {
         let schedule = Schedule::<Test>::default();
         println!("{:#?}", schedule);
     }
+
+    #[test]
+    fn plain_wait_duration_is_limited() {
+        let mut limits = Limits {
+            max_wait_duration: 100,
+            default_wait_up_to: Some(10),
+            ..Default::default()
+        };
+
+        assert_eq!(limits.wait_duration(5), 5);
+        assert_eq!(limits.wait_duration(1_000), 10);
+
+        limits.default_wait_up_to = Some(1_000);
+        assert_eq!(limits.wait_duration(10_000), 100);
+
+        limits.default_wait_up_to = None;
+        assert_eq!(limits.wait_duration(10_000), 10_000);
+    }
+
+    #[test]
+    fn bounded_wait_durations_are_limited() {
+        let limits = Limits {
+            max_wait_duration: 100,
+            ..Default::default()
+        };
+
+        assert_eq!(limits.wait_up_to_duration(10, 1_000), 10);
+        assert_eq!(limits.wait_up_to_duration(50, 20), 20);
+        assert_eq!(limits.wait_up_to_duration(1_000, 10_000), 100);
+
+        assert!(limits.can_wait_for(10, 1_000));
+        assert!(!limits.can_wait_for(50, 20));
+        assert!(!limits.can_wait_for(1_000, 10_000));
+    }
+
+    #[test]
+    fn outgoing_limit_scales_with_gas_limit() {
+        assert_eq!(Limits::default().outgoing_scaling(), None);
+
+        let limits = Limits {
+            outgoing_gas_per_message: Some(1_000),
+            outgoing_floor: 4,
+            ..Default::default()
+        };
+        let scaling = limits.outgoing_scaling().expect("Scaling is set");
+
+        assert_eq!(scaling.limit(0, 1024), 4);
+        assert_eq!(scaling.limit(100_000, 1024), 100);
+        assert_eq!(scaling.limit(u64::MAX, 1024), 1024);
+        assert_eq!(scaling.limit(100_000, 2), 2);
+    }
+
+    #[test]
+    fn floats_are_priced_only_if_canonicalized() {
+        let mut schedule = Schedule::<Test>::default();
+        let module = elements::Module::default();
+        let f32_add = elements::Instruction::F32Add;
+
+        assert_eq!(schedule.rules(&module).instruction_cost(&f32_add), None);
+
+        schedule.limits.floats = FloatsPolicy::Canonicalize;
+        assert_eq!(
+            schedule.rules(&module).instruction_cost(&f32_add),
+            Some(schedule.instruction_weights.i64add)
+        );
+    }
 }
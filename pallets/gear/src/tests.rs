@@ -19,36 +19,50 @@
 use crate::{
     manager::HandleKind,
     mock::{
-        new_test_ext, run_to_block, run_to_next_block, Balances, Event as MockEvent, Gear,
-        GearProgram, MailboxThreshold, Origin, System, Test, BLOCK_AUTHOR, LOW_BALANCE_USER,
-        USER_1, USER_2, USER_3,
+        new_test_ext, run_to_block, run_to_next_block, run_to_next_task, Balances, CodeDeposit,
+        CodeDepositRefund, DustBeneficiary, Event as MockEvent, ExistentialDepositFreePrograms,
+        Gear, GearProgram, MailboxThreshold, Origin, ProgramRentFreePeriod, ReplyChainDepthLimit,
+        System, Test, BLOCK_AUTHOR, FORBIDDEN_DESTINATION, LOW_BALANCE_USER, USER_1, USER_2,
+        USER_3,
     },
-    pallet, BlockGasLimitOf, Config, Error, Event, GasAllowanceOf, GasHandlerOf, GasInfo,
-    GearProgramPallet, MailboxOf, Pallet as GearPallet, WaitlistOf,
+    pallet, BlockGasLimitOf, CodeCacheStats, Config, CostsPerBlockOf, DispatchStashOf, Error,
+    Event, ExtManager, GasAllowanceOf, GasHandlerOf, GasInfo, GearBank, GearProgramPallet,
+    MailboxOf, Pallet as GearPallet, QueueOf, ReplyChainDepth, TaskPoolOf, WaitlistOf,
 };
 use codec::{Decode, Encode};
 use common::{
-    event::*, program_exists, storage::*, CodeStorage, GasPrice as _, GasTree, Origin as _,
+    event::*,
+    program_exists,
+    scheduler::{HoldKind, ScheduledTask, SchedulingCostsPerBlock, TaskPool},
+    storage::*,
+    CodeStorage, GasPrice as _, GasTree, Origin as _,
 };
-use core_processor::common::ExecutionErrorReason;
+use core_processor::common::{ExecutionErrorReason, JournalHandler};
 use demo_compose::WASM_BINARY as COMPOSE_WASM_BINARY;
 use demo_distributor::{Request, WASM_BINARY};
 use demo_mul_by_const::WASM_BINARY as MUL_CONST_WASM_BINARY;
 use demo_program_factory::{CreateProgram, WASM_BINARY as PROGRAM_FACTORY_WASM_BINARY};
 use demo_waiting_proxy::WASM_BINARY as WAITING_PROXY_WASM_BINARY;
 use frame_support::{
-    assert_noop, assert_ok, dispatch::Dispatchable, sp_runtime::traits::Zero, traits::Currency,
+    assert_noop, assert_ok,
+    dispatch::Dispatchable,
+    sp_runtime::{traits::Zero, DispatchError, Perbill},
+    traits::{Currency, ReservableCurrency},
 };
 use frame_system::Pallet as SystemPallet;
 use gear_backend_common::TrapExplanation;
 use gear_core::{
     code::Code,
     ids::{CodeId, MessageId, ProgramId},
+    message::{Dispatch, DispatchKind, Message, MAX_PAYLOAD_SIZE},
 };
 use gear_core_errors::*;
 use pallet_balances::{self, Pallet as BalancesPallet};
+use primitive_types::H256;
 use utils::*;
 
+mod snapshot;
+
 #[test]
 fn unstoppable_block_execution_works() {
     init_logger();
@@ -146,6 +160,75 @@ fn submit_program_expected_failure() {
     })
 }
 
+#[test]
+fn gas_limits_below_minimums_rejected() {
+    use core_processor::configs::DispatchGasMinimums;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(submit_program_default(USER_1, ProgramCodeKind::Default));
+        let program_id = utils::get_last_program_id();
+        run_to_block(2, None);
+
+        crate::mock::GasMinimums::set(&DispatchGasMinimums {
+            init: DEFAULT_GAS_LIMIT + 1,
+            handle: DEFAULT_GAS_LIMIT + 2,
+            reply: DEFAULT_GAS_LIMIT + 3,
+        });
+
+        assert_noop!(
+            GearPallet::<Test>::submit_program(
+                Origin::signed(USER_1),
+                ProgramCodeKind::Default.to_bytes(),
+                b"another salt".to_vec(),
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT,
+                0
+            ),
+            Error::<Test>::GasLimitTooLow
+        );
+
+        assert_noop!(
+            GearPallet::<Test>::send_message(
+                Origin::signed(USER_1),
+                program_id,
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT + 1,
+                0
+            ),
+            Error::<Test>::GasLimitTooLow
+        );
+
+        assert_noop!(
+            GearPallet::<Test>::send_reply(
+                Origin::signed(USER_1),
+                MessageId::from_origin(5.into_origin()),
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT + 2,
+                0
+            ),
+            Error::<Test>::GasLimitTooLow
+        );
+
+        // Minimums apply to programs only, so sending to user is still allowed.
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            USER_2.into(),
+            EMPTY_PAYLOAD.to_vec(),
+            0,
+            0
+        ));
+
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT + 2,
+            0
+        ));
+    })
+}
+
 #[test]
 fn submit_program_fails_on_duplicate_id() {
     init_logger();
@@ -327,6 +410,76 @@ fn mailbox_threshold_works() {
     })
 }
 
+#[test]
+fn value_below_mailbox_threshold_is_deposited() {
+    // Sends message with value of 1000 and no gas to USER_1.
+    let wat = r#"
+    (module
+        (import "env" "gr_send_wgas" (func $send (param i32 i32 i32 i64 i32 i32) (result i32)))
+        (import "env" "memory" (memory 1))
+        (export "handle" (func $handle))
+        (export "init" (func $init))
+        (func $handle
+            (local $msg_source i32)
+            (local $msg_val i32)
+            (i32.store offset=2
+                (get_local $msg_source)
+                (i32.const 1)
+            )
+            (i32.store offset=10
+                (get_local $msg_val)
+                (i32.const 1000)
+            )
+            (call $send (i32.const 2) (i32.const 0) (i32.const 32) (i64.const 0) (i32.const 10) (i32.const 40000))
+            (if
+                (then unreachable)
+                (else)
+            )
+        )
+        (func $init)
+    )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let prog_id = submit_program_default(USER_3, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+
+        run_to_block(2, None);
+
+        let prog_account = AccountId::from_origin(prog_id.into_origin());
+        let value = 1000;
+
+        assert_ok!(
+            <BalancesPallet::<Test> as frame_support::traits::Currency<_>>::transfer(
+                &USER_3,
+                &prog_account,
+                value,
+                frame_support::traits::ExistenceRequirement::AllowDeath
+            )
+        );
+
+        let user_balance = BalancesPallet::<Test>::free_balance(USER_1);
+        let prog_reserved = BalancesPallet::<Test>::reserved_balance(prog_account);
+
+        assert_ok!(send_default_message(USER_2, prog_id));
+        run_to_block(3, None);
+
+        // Message is only seen in events, while its value is already the user's.
+        let message = maybe_last_message(USER_1).expect("Message is sent to the user");
+        assert_eq!(message.value(), value);
+        assert!(MailboxOf::<Test>::is_empty(&USER_1));
+
+        assert_eq!(
+            BalancesPallet::<Test>::free_balance(USER_1),
+            user_balance + value
+        );
+        assert_eq!(
+            BalancesPallet::<Test>::reserved_balance(prog_account),
+            prog_reserved
+        );
+    })
+}
+
 #[test]
 fn send_message_expected_failure() {
     init_logger();
@@ -393,6 +546,18 @@ fn send_message_expected_failure() {
             ),
             Error::<Test>::GasLimitTooHigh
         );
+
+        // Payload too big
+        assert_noop!(
+            GearPallet::<Test>::send_message(
+                Origin::signed(USER_1),
+                program_id,
+                vec![0; MAX_PAYLOAD_SIZE + 1],
+                DEFAULT_GAS_LIMIT,
+                0
+            ),
+            Error::<Test>::PayloadSizeLimitExceeded
+        );
     })
 }
 
@@ -477,10 +642,7 @@ fn unused_gas_released_back_works() {
             BalancesPallet::<Test>::free_balance(USER_1),
             user1_initial_balance - user1_potential_msgs_spends
         );
-        assert_eq!(
-            BalancesPallet::<Test>::reserved_balance(USER_1),
-            user1_potential_msgs_spends
-        );
+        assert_eq!(bank_balance(), user1_potential_msgs_spends);
 
         run_to_block(2, None);
 
@@ -772,6 +934,66 @@ fn memory_access_cases() {
     });
 }
 
+#[test]
+fn pages_written_in_block_are_visible_to_next_messages() {
+    use gear_core::memory::PageNumber;
+
+    // Each handle increments counter stored in static memory, so every
+    // message in the block must observe data written by the previous one.
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        i32.const 0x100
+        i32.const 0x100
+        i32.load
+        i32.const 0x1
+        i32.add
+        i32.store
+    )
+)
+"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = ProgramCodeKind::Custom(wat).to_bytes();
+        let salt = DEFAULT_SALT.to_vec();
+        let pid = generate_program_id(&code, &salt);
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            code,
+            salt,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+
+        for _ in 0..3 {
+            assert_ok!(GearPallet::<Test>::send_message(
+                Origin::signed(USER_1),
+                pid,
+                EMPTY_PAYLOAD.to_vec(),
+                10_000_000_000,
+                0,
+            ));
+        }
+
+        run_to_block(3, None);
+        assert_last_dequeued(3);
+
+        let page = common::get_program_page_data(pid.into_origin(), PageNumber(0))
+            .expect("page is written")
+            .expect("page data is valid");
+        assert_eq!(page[0x100..0x104], 3u32.to_le_bytes());
+    });
+}
+
 #[cfg(feature = "lazy-pages")]
 #[test]
 fn lazy_pages() {
@@ -1128,10 +1350,8 @@ fn mailbox_works() {
         // caution: runs to block 2
         let reply_to_id = setup_mailbox_test_state(USER_1);
 
-        assert_eq!(
-            BalancesPallet::<Test>::reserved_balance(USER_1),
-            OUTGOING_WITH_VALUE_IN_HANDLE_VALUE
-        );
+        // Bank holds gas and value of the mailboxed message.
+        assert_eq!(bank_balance(), OUTGOING_WITH_VALUE_IN_HANDLE_VALUE + 1000);
 
         let mailbox_message = {
             let res = MailboxOf::<Test>::remove(USER_1, reply_to_id);
@@ -1450,8 +1670,8 @@ fn send_reply_failure_to_claim_from_mailbox() {
             MailboxOf::<Test>::iter_key(USER_1)
                 .next()
                 .expect("Element should be")
-                .reply(),
-            Some((_, 1))
+                .exit_code(),
+            Some(1)
         ));
     })
 }
@@ -1506,9 +1726,10 @@ fn send_reply_value_claiming_works() {
 
             assert!(MailboxOf::<Test>::contains(&USER_1, &reply_to_id));
 
-            assert_eq!(
-                BalancesPallet::<Test>::reserved_balance(USER_2),
-                OUTGOING_WITH_VALUE_IN_HANDLE_VALUE
+            // Bank holds gas and value of the mailboxed message.
+            let bank_balance_before_reply = bank_balance();
+            assert!(
+                bank_balance_before_reply >= OUTGOING_WITH_VALUE_IN_HANDLE_VALUE + locked_value
             );
 
             // nothing changed
@@ -1530,11 +1751,16 @@ fn send_reply_value_claiming_works() {
                 BalancesPallet::<Test>::free_balance(USER_1),
                 user_balance + locked_value - currently_sent
             );
+            assert_eq!(BalancesPallet::<Test>::reserved_balance(USER_1), 0);
+
+            // Gas and value of the mailboxed message left the bank,
+            // while funds for the reply were moved into it.
             assert_eq!(
-                BalancesPallet::<Test>::reserved_balance(USER_1),
-                currently_sent
+                bank_balance(),
+                bank_balance_before_reply + currently_sent
+                    - OUTGOING_WITH_VALUE_IN_HANDLE_VALUE
+                    - locked_value
             );
-            assert_eq!(BalancesPallet::<Test>::reserved_balance(USER_2), 0,);
         }
     })
 }
@@ -1543,7 +1769,7 @@ fn send_reply_value_claiming_works() {
 // prog send to user 1 msg to mailbox
 // user 1 claims it from mailbox
 #[test]
-fn claim_value_from_mailbox_works() {
+fn claim_value_works() {
     init_logger();
     new_test_ext().execute_with(|| {
         let sender_balance = BalancesPallet::<Test>::free_balance(USER_2);
@@ -1580,9 +1806,9 @@ fn claim_value_from_mailbox_works() {
 
         run_to_block(3, None);
 
-        assert_ok!(GearPallet::<Test>::claim_value_from_mailbox(
+        assert_ok!(GearPallet::<Test>::claim_value(
             Origin::signed(USER_1),
-            reply_to_id,
+            reply_to_id
         ));
 
         assert_eq!(BalancesPallet::<Test>::reserved_balance(USER_1), 0);
@@ -1594,8 +1820,10 @@ fn claim_value_from_mailbox_works() {
             expected_claimer_balance
         );
 
-        // Gas left returns to sender from consuming of value tree while claiming.
-        let expected_sender_balance = sender_balance - value_sent - gas_burned;
+        // Gas left returns to sender from consuming of value tree while claiming,
+        // except the rent for holding the message in the mailbox for a block.
+        let mailbox_rent = GasPrice::gas_price(CostsPerBlockOf::<Test>::mailbox());
+        let expected_sender_balance = sender_balance - value_sent - gas_burned - mailbox_rent;
         assert_eq!(
             BalancesPallet::<Test>::free_balance(USER_2),
             expected_sender_balance
@@ -1611,6 +1839,61 @@ fn claim_value_from_mailbox_works() {
     })
 }
 
+#[test]
+fn mailbox_expiration_charges_rent_and_returns_value() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let prog_id = {
+            let res = submit_program_default(USER_3, ProgramCodeKind::OutgoingWithValueInHandle);
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        increase_prog_balance_for_mailbox_test(USER_3, prog_id);
+
+        let reply_to_id = populate_mailbox_from_program(prog_id, USER_2, 2, 10_000_000_000, 0);
+        let value = get_last_mail(USER_1).value();
+
+        let (_, expiration) =
+            crate::MailboxHolds::<Test>::get(reply_to_id).expect("Mailbox holding is tracked");
+
+        let prog_account = AccountId::from_origin(prog_id.into_origin());
+        let prog_reserved = BalancesPallet::<Test>::reserved_balance(prog_account);
+        let user_balance = BalancesPallet::<Test>::free_balance(USER_1);
+        let author_balance = BalancesPallet::<Test>::free_balance(BLOCK_AUTHOR);
+
+        // Removing the message as the task, scheduled on expiration, does.
+        System::set_block_number(expiration);
+        let mut ext_manager = ExtManager::<Test>::default();
+        common::scheduler::TaskHandler::remove_from_mailbox(&mut ext_manager, USER_1, reply_to_id);
+
+        assert!(!MailboxOf::<Test>::contains(&USER_1, &reply_to_id));
+        assert!(!crate::MailboxHolds::<Test>::contains_key(reply_to_id));
+
+        // Value isn't claimed by the user, but returned to the program.
+        assert_eq!(BalancesPallet::<Test>::free_balance(USER_1), user_balance);
+        assert_eq!(
+            BalancesPallet::<Test>::reserved_balance(prog_account),
+            prog_reserved - value
+        );
+
+        // Rent for the whole holding is paid to the block author.
+        let rent = (expiration - 2) * CostsPerBlockOf::<Test>::mailbox();
+        assert_eq!(
+            BalancesPallet::<Test>::free_balance(BLOCK_AUTHOR),
+            author_balance + GasPrice::gas_price(rent)
+        );
+
+        SystemPallet::<Test>::assert_last_event(
+            Event::UserMessageRead {
+                id: reply_to_id,
+                reason: UserMessageReadSystemReason::OutOfRent.into_reason(),
+            }
+            .into(),
+        );
+    })
+}
+
 #[test]
 fn distributor_initialize() {
     init_logger();
@@ -1754,6 +2037,62 @@ fn test_same_code_submission_fails() {
     })
 }
 
+#[test]
+fn test_code_metadata_submission() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = ProgramCodeKind::Default.to_bytes();
+        let code_id = CodeId::generate(&code);
+        let registry = vec![1, 2, 3];
+
+        assert_noop!(
+            GearPallet::<Test>::submit_code_metadata(
+                Origin::signed(USER_1),
+                code_id,
+                registry.clone(),
+                0
+            ),
+            Error::<Test>::CodeNotFound,
+        );
+
+        assert_ok!(GearPallet::<Test>::submit_code(
+            Origin::signed(USER_1),
+            code
+        ));
+
+        // Only code author is able to upload its metadata.
+        assert_noop!(
+            GearPallet::<Test>::submit_code_metadata(
+                Origin::signed(USER_2),
+                code_id,
+                registry.clone(),
+                0
+            ),
+            Error::<Test>::NotCodeAuthor,
+        );
+
+        assert_ok!(GearPallet::<Test>::submit_code_metadata(
+            Origin::signed(USER_1),
+            code_id,
+            registry.clone(),
+            5
+        ));
+
+        assert_eq!(
+            <Test as Config>::CodeStorage::get_type_metadata(code_id),
+            Some(common::CodeTypeMetadata::new(registry, 5))
+        );
+
+        SystemPallet::<Test>::assert_last_event(
+            Event::CodeChanged {
+                id: code_id,
+                change: CodeChangeKind::TypeMetadataChanged,
+            }
+            .into(),
+        );
+    })
+}
+
 #[test]
 fn test_code_is_not_submitted_twice_after_program_submission() {
     init_logger();
@@ -1878,12 +2217,15 @@ fn messages_to_uninitialized_program_wait() {
 }
 
 #[test]
-fn uninitialized_program_should_accept_replies() {
+fn top_up_gas_of_waitlisted_message() {
     use demo_init_wait::WASM_BINARY;
 
     init_logger();
     new_test_ext().execute_with(|| {
-        System::reset_events();
+        assert_noop!(
+            GearPallet::<Test>::top_up_gas(Origin::signed(USER_2), MessageId::default(), 1_000),
+            Error::<Test>::NoMessageTree,
+        );
 
         assert_ok!(GearPallet::<Test>::submit_program(
             Origin::signed(USER_1),
@@ -1894,23 +2236,82 @@ fn uninitialized_program_should_accept_replies() {
             0u128
         ));
 
-        let program_id = utils::get_last_program_id();
-
-        assert!(!Gear::is_initialized(program_id));
-        assert!(!Gear::is_terminated(program_id));
+        let message_id = utils::get_last_message_id();
 
         run_to_block(2, None);
 
-        // there should be one message for the program author
-        let message_id = MailboxOf::<Test>::iter_key(USER_1)
-            .next()
-            .expect("Element should be")
-            .id();
-        assert_eq!(MailboxOf::<Test>::len(&USER_1), 1);
+        // Init message waits for the reply.
+        let (gas_limit, _) = GasHandlerOf::<Test>::get_limit(message_id)
+            .unwrap()
+            .unwrap();
 
-        assert_ok!(GearPallet::<Test>::send_reply(
-            Origin::signed(USER_1),
-            message_id,
+        let bank_funds = bank_balance();
+        let user2_free = BalancesPallet::<Test>::free_balance(USER_2);
+        let top_up = 1_000_000u64;
+
+        assert_ok!(GearPallet::<Test>::top_up_gas(
+            Origin::signed(USER_2),
+            message_id,
+            top_up
+        ));
+
+        // Funds for additional gas are moved to the bank.
+        assert_eq!(bank_balance(), bank_funds + GasPrice::gas_price(top_up));
+        assert_eq!(
+            BalancesPallet::<Test>::free_balance(USER_2),
+            user2_free - GasPrice::gas_price(top_up)
+        );
+        assert_eq!(
+            GasHandlerOf::<Test>::get_limit(message_id).unwrap(),
+            Some((gas_limit + top_up, message_id))
+        );
+        assert_eq!(crate::GasTopUps::<Test>::get(message_id, USER_2), top_up);
+
+        SystemPallet::<Test>::assert_last_event(
+            Event::GasToppedUp {
+                id: message_id,
+                payer: USER_2,
+                amount: top_up,
+            }
+            .into(),
+        );
+    })
+}
+
+#[test]
+fn uninitialized_program_should_accept_replies() {
+    use demo_init_wait::WASM_BINARY;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        System::reset_events();
+
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            vec![],
+            Vec::new(),
+            10_000_000_000u64,
+            0u128
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        assert!(!Gear::is_initialized(program_id));
+        assert!(!Gear::is_terminated(program_id));
+
+        run_to_block(2, None);
+
+        // there should be one message for the program author
+        let message_id = MailboxOf::<Test>::iter_key(USER_1)
+            .next()
+            .expect("Element should be")
+            .id();
+        assert_eq!(MailboxOf::<Test>::len(&USER_1), 1);
+
+        assert_ok!(GearPallet::<Test>::send_reply(
+            Origin::signed(USER_1),
+            message_id,
             b"PONG".to_vec(),
             10_000_000_000u64,
             0,
@@ -2602,6 +3003,10 @@ fn exit_handle() {
         run_to_block(2, None);
 
         assert!(Gear::is_initialized(program_id));
+        assert_eq!(
+            GearProgramPallet::<Test>::program_creator(program_id),
+            Some(USER_1)
+        );
 
         // An expensive operation since "gr_exit" removes all program pages from storage.
         assert_ok!(GearPallet::<Test>::send_message(
@@ -2616,8 +3021,17 @@ fn exit_handle() {
 
         assert!(Gear::is_terminated(program_id));
         assert!(MailboxOf::<Test>::is_empty(&USER_3));
+        assert!(GearProgramPallet::<Test>::program_creator(program_id).is_none());
+        assert!(GearPallet::<Test>::program_expiration(program_id).is_none());
         assert!(!Gear::is_initialized(program_id));
         assert!(Gear::is_terminated(program_id));
+        assert!(Gear::is_exited(program_id));
+        assert_eq!(
+            common::get_program(program_id.into_origin()),
+            Some(common::Program::Exited {
+                inheritor: ProgramId::from_origin(USER_1.into_origin())
+            })
+        );
 
         assert!(<Test as Config>::CodeStorage::exists(CodeId::from_origin(
             code_hash
@@ -2667,6 +3081,8 @@ fn no_redundant_gas_value_after_exiting() {
             true,
         )
         .expect("calculate_gas_info failed");
+
+        let bank_before_send = bank_balance();
         assert_ok!(GearPallet::<Test>::send_message(
             Origin::signed(USER_1),
             prog_id,
@@ -2681,8 +3097,7 @@ fn no_redundant_gas_value_after_exiting() {
 
         // before execution
         let free_after_send = BalancesPallet::<Test>::free_balance(USER_1);
-        let reserved_after_send = BalancesPallet::<Test>::reserved_balance(USER_1);
-        assert_eq!(reserved_after_send, gas_spent as u128);
+        assert_eq!(bank_balance(), bank_before_send + gas_spent as u128);
 
         run_to_block(3, None);
 
@@ -2690,16 +3105,12 @@ fn no_redundant_gas_value_after_exiting() {
         let maybe_limit = GasHandlerOf::<Test>::get_limit(msg_id).expect("invalid algo");
         assert_eq!(maybe_limit, None);
 
-        // the (reserved_after_send - gas_spent) has been unreserved
+        // gas_limit has been fully spent, so nothing is refunded
         let free_after_execution = BalancesPallet::<Test>::free_balance(USER_1);
-        assert_eq!(
-            free_after_execution,
-            free_after_send + (reserved_after_send - gas_spent as u128)
-        );
+        assert_eq!(free_after_execution, free_after_send);
 
-        // reserved balance after execution is zero
-        let reserved_after_execution = BalancesPallet::<Test>::reserved_balance(USER_1);
-        assert!(reserved_after_execution.is_zero());
+        // and nothing is left in the bank
+        assert_eq!(bank_balance(), bank_before_send);
     })
 }
 
@@ -2967,6 +3378,55 @@ fn program_messages_to_paused_program_skipped() {
     })
 }
 
+#[test]
+fn program_messages_to_forbidden_destination_rejected() {
+    use demo_proxy::{InputArgs, WASM_BINARY as PROXY_WASM_BINARY};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            PROXY_WASM_BINARY.to_vec(),
+            vec![],
+            InputArgs {
+                destination: FORBIDDEN_DESTINATION.into_origin().into()
+            }
+            .encode(),
+            50_000_000_000u64,
+            0u128
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            program_id,
+            vec![],
+            20_000_000_000u64,
+            1_000u128
+        ));
+
+        run_to_block(3, None);
+
+        // Neither the message nor its value reached the destination.
+        assert!(MailboxOf::<Test>::iter_key(FORBIDDEN_DESTINATION)
+            .next()
+            .is_none());
+        assert_eq!(
+            BalancesPallet::<Test>::free_balance(FORBIDDEN_DESTINATION),
+            0
+        );
+        assert_eq!(
+            1_000u128,
+            BalancesPallet::<Test>::free_balance(
+                &<utils::AccountId as common::Origin>::from_origin(program_id.into_origin())
+            )
+        );
+    })
+}
+
 #[test]
 fn resume_program_works() {
     use demo_init_wait::WASM_BINARY;
@@ -3022,6 +3482,7 @@ fn resume_program_works() {
         assert_ok!(GearProgramPallet::<Test>::resume_program(
             Origin::signed(USER_3),
             program_id,
+            program,
             memory_pages,
             Default::default(),
             50_000u128
@@ -3046,6 +3507,240 @@ fn resume_program_works() {
     })
 }
 
+#[test]
+fn program_paused_once_rent_expired() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        ProgramRentFreePeriod::set(&10);
+
+        assert_noop!(
+            GearPallet::<Test>::pay_program_rent(Origin::signed(USER_2), ProgramId::default(), 5),
+            Error::<Test>::InactiveProgram,
+        );
+
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result was asserted");
+
+        let expiration = System::block_number() + 10;
+        assert_eq!(
+            GearPallet::<Test>::program_expiration(program_id),
+            Some(expiration)
+        );
+
+        let user2_free = BalancesPallet::<Test>::free_balance(USER_2);
+        let author_free = BalancesPallet::<Test>::free_balance(BLOCK_AUTHOR);
+
+        assert_ok!(GearPallet::<Test>::pay_program_rent(
+            Origin::signed(USER_2),
+            program_id,
+            5
+        ));
+
+        // Rent is transferred to the block author.
+        let rent = GasPrice::gas_price(CostsPerBlockOf::<Test>::program() * 5);
+        assert_eq!(
+            BalancesPallet::<Test>::free_balance(USER_2),
+            user2_free - rent
+        );
+        assert_eq!(
+            BalancesPallet::<Test>::free_balance(BLOCK_AUTHOR),
+            author_free + rent
+        );
+
+        let expiration = expiration + 5;
+        assert_eq!(
+            GearPallet::<Test>::program_expiration(program_id),
+            Some(expiration)
+        );
+
+        SystemPallet::<Test>::assert_last_event(
+            Event::ProgramRentPaid {
+                id: program_id,
+                payer: USER_2,
+                expiration,
+            }
+            .into(),
+        );
+
+        run_to_block(expiration - 1, None);
+        assert!(!GearProgram::program_paused(program_id));
+
+        run_to_block(expiration, None);
+        assert!(GearProgram::program_paused(program_id));
+        assert!(GearPallet::<Test>::program_expiration(program_id).is_none());
+    })
+}
+
+#[test]
+fn program_expiration_removed_once_paused_by_creator() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result was asserted");
+
+        let expiration = System::block_number() + ProgramRentFreePeriod::get();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(GearProgramPallet::<Test>::pause_own_program(
+            Origin::signed(USER_1),
+            program_id,
+            0
+        ));
+
+        assert!(GearProgram::program_paused(program_id));
+        assert!(GearPallet::<Test>::program_expiration(program_id).is_none());
+        assert!(!TaskPoolOf::<Test>::contains(
+            &expiration,
+            &ScheduledTask::PauseProgram(program_id)
+        ));
+    })
+}
+
+#[test]
+fn waitlist_is_unscheduled_once_rent_expired() {
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_wait" (func $gr_wait))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (call $gr_wait)
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        ProgramRentFreePeriod::set(&5);
+
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        let rent_expiration = System::block_number() + 5;
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let waited_id = get_last_message_id();
+        run_to_block(3, None);
+
+        let expiration =
+            crate::WaitlistExpirations::<Test>::get(waited_id).expect("Message wasn't waited");
+        assert!(expiration > rent_expiration);
+
+        let task = ScheduledTask::RemoveFromWaitlist(pid, waited_id);
+        assert!(TaskPoolOf::<Test>::contains(&expiration, &task));
+
+        // Waitlist is taken along with the paused program.
+        run_to_block(rent_expiration, None);
+        assert!(GearProgram::program_paused(pid));
+        assert!(!WaitlistOf::<Test>::contains(&pid, &waited_id));
+        assert!(crate::WaitlistExpirations::<Test>::get(waited_id).is_none());
+        assert!(!TaskPoolOf::<Test>::contains(&expiration, &task));
+
+        // Stale task of the message is skipped on the deadline.
+        assert_ok!(TaskPoolOf::<Test>::add(expiration, task));
+        assert_eq!(run_to_next_task(None), Some(expiration));
+        assert!(GearProgram::program_paused(pid));
+        assert_eq!(run_to_next_task(None), None);
+    })
+}
+
+#[test]
+fn program_paused_once_default_rent_free_period_expired() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result was asserted");
+
+        let expiration = System::block_number() + ProgramRentFreePeriod::get();
+        assert_eq!(
+            GearPallet::<Test>::program_expiration(program_id),
+            Some(expiration)
+        );
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(program_id));
+
+        // Skipping blocks until the expiration without running them.
+        assert_eq!(run_to_next_task(None), Some(expiration));
+        assert!(GearProgram::program_paused(program_id));
+        assert!(GearPallet::<Test>::program_expiration(program_id).is_none());
+
+        assert_eq!(run_to_next_task(None), None);
+    })
+}
+
+#[test]
+fn unused_code_pruned_after_init_failure() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        CodeDeposit::set(&1_000);
+        CodeDepositRefund::set(&Some(Perbill::from_percent(40)));
+
+        let code_id: CodeId =
+            generate_code_hash(ProgramCodeKind::GreedyInit.to_bytes().as_slice()).into();
+        let issuance = BalancesPallet::<Test>::total_issuance();
+
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::GreedyInit)
+            .expect("submit result was asserted");
+
+        assert!(<Test as Config>::CodeStorage::exists(code_id));
+        assert!(BalancesPallet::<Test>::reserved_balance(USER_1) >= CodeDeposit::get());
+
+        // Init fails, so pruning of the code is scheduled for the next block.
+        run_to_block(2, None);
+        assert!(Gear::is_terminated(program_id));
+        assert!(GearProgramPallet::<Test>::program_creator(program_id).is_none());
+        assert!(GearPallet::<Test>::program_expiration(program_id).is_none());
+        assert!(<Test as Config>::CodeStorage::exists(code_id));
+
+        run_to_block(3, None);
+        assert!(!<Test as Config>::CodeStorage::exists(code_id));
+        assert!(<Test as Config>::CodeStorage::get_metadata(code_id).is_none());
+
+        SystemPallet::<Test>::assert_has_event(
+            Event::CodeChanged {
+                id: code_id,
+                change: CodeChangeKind::Inactive,
+            }
+            .into(),
+        );
+
+        // Refunded part of the deposit is returned to the uploader,
+        // while the rest of it is slashed.
+        assert!(BalancesPallet::<Test>::reserved_balance(USER_1).is_zero());
+        assert_eq!(
+            BalancesPallet::<Test>::total_issuance(),
+            issuance - Perbill::from_percent(60) * CodeDeposit::get()
+        );
+    })
+}
+
+#[test]
+fn code_kept_after_init_failure_without_refund() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        CodeDeposit::set(&1_000);
+
+        let code_id: CodeId =
+            generate_code_hash(ProgramCodeKind::GreedyInit.to_bytes().as_slice()).into();
+
+        submit_program_default(USER_1, ProgramCodeKind::GreedyInit)
+            .expect("submit result was asserted");
+
+        run_to_block(3, None);
+
+        // Pruning is disabled, so the code and its deposit are kept.
+        assert!(<Test as Config>::CodeStorage::exists(code_id));
+        assert_eq!(
+            BalancesPallet::<Test>::reserved_balance(USER_1),
+            CodeDeposit::get()
+        );
+    })
+}
+
 #[test]
 fn gas_spent_vs_balance() {
     use demo_btree::{Request, WASM_BINARY};
@@ -3132,35 +3827,117 @@ fn gas_spent_vs_balance() {
 }
 
 #[test]
-fn gas_spent_precalculated() {
+fn read_meta_state_works() {
+    // Stores "gear" at 0x108 on init.
     let wat = r#"
     (module
         (import "env" "memory" (memory 1))
-        (export "handle" (func $handle))
-        (func $add (; 0 ;) (param $0 i32) (param $1 i32)
-            (local $2 i32)
-            get_local $0
-            get_local $1
-            i32.add
-            set_local $2
+        (export "init" (func $init))
+        (func $init
+            (i32.store (i32.const 0x108) (i32.const 0x72616567))
         )
-        (func $handle
-            (call $add
-                (i32.const 2)
-                (i32.const 2)
-            )
+    )"#;
+
+    // Returns pointer to the fat pointer of 4 bytes at 0x108.
+    let meta_wat = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (export "meta_state" (func $meta_state))
+        (func $meta_state (result i32)
+            (i32.store (i32.const 0x100) (i32.const 0x108))
+            (i32.store (i32.const 0x104) (i32.const 4))
+            (i32.const 0x100)
         )
     )"#;
 
     init_logger();
     new_test_ext().execute_with(|| {
-        let prog_id = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
             .expect("submit result was asserted");
 
         run_to_block(2, None);
 
-        let GasInfo {
-            min_limit: gas_spent_1,
+        let meta_wasm = ProgramCodeKind::Custom(meta_wat).to_bytes();
+
+        assert_eq!(
+            GearPallet::<Test>::read_meta_state(program_id.into_origin(), meta_wasm, vec![]),
+            Ok(b"gear".to_vec())
+        );
+    });
+}
+
+#[test]
+fn ids_are_predicted_before_submission() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = ProgramCodeKind::Default.to_bytes();
+        let code_id = CodeId::generate(&code).into_origin();
+
+        let expected_program_id = GearPallet::<Test>::calculate_program_id(code_id, DEFAULT_SALT);
+        let expected_message_id = GearPallet::<Test>::next_user_message_id(USER_1.into_origin());
+
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result was asserted");
+
+        assert_eq!(program_id, expected_program_id);
+        assert_eq!(get_last_message_id(), expected_message_id);
+
+        let expected_message_id = GearPallet::<Test>::next_user_message_id(USER_1.into_origin());
+        assert_ok!(send_default_message(USER_1, program_id));
+        assert_eq!(get_last_message_id(), expected_message_id);
+    });
+}
+
+#[test]
+fn original_code_matches_local_build() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = ProgramCodeKind::Default.to_bytes();
+        let code_id = CodeId::generate(&code).into_origin();
+
+        assert!(!GearPallet::<Test>::code_hash_exists(code_id));
+        assert_eq!(GearPallet::<Test>::original_code(code_id), None);
+
+        assert_ok!(GearPallet::<Test>::submit_code(
+            Origin::signed(USER_1),
+            code.clone()
+        ));
+
+        assert!(GearPallet::<Test>::code_hash_exists(code_id));
+        assert_eq!(GearPallet::<Test>::original_code(code_id), Some(code));
+    });
+}
+
+#[test]
+fn gas_spent_precalculated() {
+    let wat = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (export "handle" (func $handle))
+        (func $add (; 0 ;) (param $0 i32) (param $1 i32)
+            (local $2 i32)
+            get_local $0
+            get_local $1
+            i32.add
+            set_local $2
+        )
+        (func $handle
+            (call $add
+                (i32.const 2)
+                (i32.const 2)
+            )
+        )
+    )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let prog_id = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result was asserted");
+
+        run_to_block(2, None);
+
+        let GasInfo {
+            min_limit: gas_spent_1,
             ..
         } = Gear::calculate_gas_info(
             USER_1.into_origin(),
@@ -3207,6 +3984,61 @@ fn gas_spent_precalculated() {
     });
 }
 
+#[test]
+fn gas_spent_for_written_pages() {
+    let wat = r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (export "handle" (func $handle))
+        (func $handle
+            (i32.store
+                (i32.const 0)
+                (i32.const 42)
+            )
+        )
+    )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let prog_id = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result was asserted");
+
+        run_to_block(2, None);
+
+        let calculate_gas = || {
+            Gear::calculate_gas_info(
+                USER_1.into_origin(),
+                HandleKind::Handle(prog_id),
+                EMPTY_PAYLOAD.to_vec(),
+                0,
+                true,
+            )
+            .expect("calculate_gas_info failed")
+            .min_limit
+        };
+
+        let gas_spent_1 = calculate_gas();
+
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            prog_id,
+            EMPTY_PAYLOAD.to_vec(),
+            gas_spent_1,
+            0,
+        ));
+
+        run_to_block(3, None);
+
+        // The page already contains the stored value, so it isn't written anymore.
+        let gas_spent_2 = calculate_gas();
+        let page_write_cost = <Test as Config>::Schedule::get()
+            .memory_weights
+            .page_write_cost;
+
+        assert_eq!(gas_spent_1, gas_spent_2 + page_write_cost);
+    });
+}
+
 #[test]
 fn test_two_contracts_composition_works() {
     init_logger();
@@ -3269,6 +4101,157 @@ fn test_two_contracts_composition_works() {
     });
 }
 
+#[test]
+fn gas_estimation_follows_generated_dispatches() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let contract_a_id = generate_program_id(MUL_CONST_WASM_BINARY, b"contract_a");
+        let contract_b_id = generate_program_id(MUL_CONST_WASM_BINARY, b"contract_b");
+        let compose_id = generate_program_id(COMPOSE_WASM_BINARY, b"salt");
+
+        for (salt, factor) in [(b"contract_a", 50_u64), (b"contract_b", 75_u64)] {
+            assert_ok!(Gear::submit_program(
+                Origin::signed(USER_1),
+                MUL_CONST_WASM_BINARY.to_vec(),
+                salt.to_vec(),
+                factor.encode(),
+                10_000_000_000,
+                0,
+            ));
+        }
+
+        assert_ok!(Gear::submit_program(
+            Origin::signed(USER_1),
+            COMPOSE_WASM_BINARY.to_vec(),
+            b"salt".to_vec(),
+            (
+                <[u8; 32]>::from(contract_a_id),
+                <[u8; 32]>::from(contract_b_id)
+            )
+                .encode(),
+            10_000_000_000,
+            0,
+        ));
+
+        run_to_block(2, None);
+
+        let estimate = || {
+            Gear::calculate_gas_info(
+                USER_1.into_origin(),
+                HandleKind::Handle(compose_id),
+                100_u64.to_le_bytes().to_vec(),
+                0,
+                true,
+            )
+            .expect("calculate_gas_info failed")
+        };
+
+        let deep = estimate();
+
+        // Only the compose program itself is executed.
+        crate::mock::GasEstimationDepth::set(&0);
+        let shallow = estimate();
+
+        assert!(shallow.burned < deep.burned);
+    });
+}
+
+#[test]
+fn program_accounts_exempt_from_existential_deposit() {
+    use demo_init_with_value::{SendMessage, WASM_BINARY};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        ExistentialDepositFreePrograms::set(&true);
+
+        let ed = get_ed();
+
+        assert_ok!(GearPallet::<Test>::submit_code(
+            Origin::signed(USER_1),
+            ProgramCodeKind::Default.to_bytes(),
+        ));
+
+        // Program creates another one with value less than ED.
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            b"test1".to_vec(),
+            vec![SendMessage::Init { value: ed - 1 }].encode(),
+            10_000_000_000,
+            1000,
+        ));
+
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        // init messages sent by user and by program
+        assert_total_dequeued(2);
+        // programs deployed by user and by program
+        assert_init_success(2);
+
+        let child_id =
+            generate_program_id(&ProgramCodeKind::Default.to_bytes(), &0i32.to_le_bytes());
+
+        let program_account = AccountId::from_origin(program_id.into_origin());
+        let child_account = AccountId::from_origin(child_id.into_origin());
+
+        // Accounts hold existential deposit in reserve, so values of any amount are kept.
+        assert_eq!(Balances::free_balance(&program_account), 1000 - (ed - 1));
+        assert_eq!(Balances::reserved_balance(&program_account), ed);
+        assert_eq!(Balances::free_balance(&child_account), ed - 1);
+        assert_eq!(Balances::reserved_balance(&child_account), ed);
+    })
+}
+
+#[test]
+fn program_accounts_exempt_from_existential_deposit_released_on_exit() {
+    use demo_exit_handle::WASM_BINARY;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        ExistentialDepositFreePrograms::set(&true);
+
+        let ed = get_ed();
+
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+
+        let program_id = get_last_program_id();
+        let program_account = AccountId::from_origin(program_id.into_origin());
+
+        run_to_block(2, None);
+
+        assert_init_success(1);
+        assert_eq!(Balances::reserved_balance(&program_account), ed);
+
+        // Value less than existential deposit could be sent to the program.
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            ed - 1,
+        ));
+
+        let user_balance = Balances::free_balance(USER_1);
+
+        run_to_block(3, None);
+
+        // Program exits, sending the value to the user and releasing its account.
+        assert!(Gear::is_exited(program_id));
+        assert!(Balances::free_balance(USER_1) >= user_balance + ed - 1);
+        assert_eq!(Balances::total_balance(&program_account), 0);
+        assert!(!SystemPallet::<Test>::account_exists(&program_account));
+    })
+}
+
 // Before introducing this test, submit_program extrinsic didn't check the value.
 // Also value wasn't check in `create_program` sys-call. There could be the next test case, which could affect badly.
 //
@@ -3556,9 +4539,9 @@ fn test_reply_to_terminated_program() {
         log::debug!("mailbox: {:?}", MailboxOf::<Test>::iter_key(USER_1).next());
 
         // the only way to claim value from terminated destination is a corresponding extrinsic call
-        assert_ok!(GearPallet::<Test>::claim_value_from_mailbox(
+        assert_ok!(GearPallet::<Test>::claim_value(
             Origin::signed(USER_1),
-            mail_id,
+            mail_id
         ));
 
         assert!(MailboxOf::<Test>::is_empty(&USER_1));
@@ -3641,10 +4624,8 @@ fn cascading_messages_with_value_do_not_overcharge() {
             <Test as Config>::GasPrice::gas_price(<Test as Config>::MailboxThreshold::get());
 
         assert_eq!(user_balance_before_calculating, user_initial_balance);
-        assert_eq!(
-            BalancesPallet::<Test>::reserved_balance(USER_1),
-            mailbox_threshold_reserved * 2
-        );
+
+        let bank_initial_balance = bank_balance();
 
         assert_ok!(Gear::send_message(
             Origin::signed(USER_1),
@@ -3663,16 +4644,13 @@ fn cascading_messages_with_value_do_not_overcharge() {
             user_initial_balance - reserved_balance
         );
 
-        assert_eq!(
-            BalancesPallet::<Test>::reserved_balance(USER_1),
-            reserved_balance + mailbox_threshold_reserved * 2
-        );
+        assert_eq!(bank_balance(), bank_initial_balance + reserved_balance);
 
         run_to_block(5, None);
 
         assert_eq!(
-            BalancesPallet::<Test>::reserved_balance(USER_1),
-            mailbox_threshold_reserved * 3
+            bank_balance(),
+            bank_initial_balance + mailbox_threshold_reserved
         );
 
         assert_eq!(
@@ -3683,29 +4661,112 @@ fn cascading_messages_with_value_do_not_overcharge() {
 }
 
 #[test]
-fn execution_over_blocks() {
+fn gas_estimation_follows_woken_messages() {
     init_logger();
+    new_test_ext().execute_with(|| {
+        let contract_id = generate_program_id(MUL_CONST_WASM_BINARY, b"contract");
+        let wrapper_id = generate_program_id(WAITING_PROXY_WASM_BINARY, b"salt");
 
-    let assert_last_message = |src: [u8; 32], count: u128| {
-        use demo_calc_hash::verify_result;
-
-        let last_message = maybe_last_message(USER_1).expect("Get last message failed.");
-        let result = <[u8; 32]>::decode(&mut last_message.payload()).expect("Decode result failed");
-
-        assert!(verify_result(src, count, result));
+        assert_ok!(Gear::submit_program(
+            Origin::signed(USER_1),
+            MUL_CONST_WASM_BINARY.to_vec(),
+            b"contract".to_vec(),
+            50_u64.encode(),
+            5_000_000_000,
+            0,
+        ));
 
-        SystemPallet::<Test>::reset_events();
-    };
+        assert_ok!(Gear::submit_program(
+            Origin::signed(USER_1),
+            WAITING_PROXY_WASM_BINARY.to_vec(),
+            b"salt".to_vec(),
+            <[u8; 32]>::from(contract_id).encode(),
+            5_000_000_000,
+            0,
+        ));
 
-    let estimate_gas_per_calc = || -> (u64, u64) {
-        use demo_calc_hash_in_one_block::{Package, WASM_BINARY};
+        run_to_block(2, None);
 
-        let (src, times) = ([0; 32], 1);
+        let payload = 100_u64.to_le_bytes().to_vec();
 
-        let init_gas = Gear::calculate_gas_info(
+        // Proxy waits for the reply of the contract, which wakes it
+        // within the estimation, so both executions are accounted.
+        let GasInfo {
+            min_limit,
+            burned,
+            waited,
+            ..
+        } = Gear::calculate_gas_info(
             USER_1.into_origin(),
-            HandleKind::Init(WASM_BINARY.to_vec()),
-            EMPTY_PAYLOAD.to_vec(),
+            HandleKind::Handle(wrapper_id),
+            payload.clone(),
+            0,
+            true,
+        )
+        .expect("Failed to get gas spent");
+
+        assert!(waited);
+        assert!(min_limit >= burned);
+
+        let GasInfo { waited, .. } = Gear::calculate_gas_info(
+            USER_1.into_origin(),
+            HandleKind::Handle(contract_id),
+            payload.clone(),
+            0,
+            true,
+        )
+        .expect("Failed to get gas spent");
+
+        assert!(!waited);
+
+        // Estimated limit is enough for the proxy to be woken and reply.
+        assert_ok!(Gear::send_message(
+            Origin::signed(USER_1),
+            wrapper_id,
+            payload,
+            min_limit,
+            0,
+        ));
+
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+
+        assert_succeed(message_id);
+        assert_eq!(
+            maybe_last_message(USER_1)
+                .expect("Proxy replied")
+                .payload()
+                .to_vec(),
+            (50_u64 * 100).encode().encode()
+        );
+    });
+}
+
+#[test]
+fn execution_over_blocks() {
+    init_logger();
+
+    let assert_last_message = |src: [u8; 32], count: u128| {
+        use demo_calc_hash::verify_result;
+
+        let last_message = maybe_last_message(USER_1).expect("Get last message failed.");
+        let result = <[u8; 32]>::decode(&mut last_message.payload()).expect("Decode result failed");
+
+        assert!(verify_result(src, count, result));
+
+        SystemPallet::<Test>::reset_events();
+    };
+
+    let estimate_gas_per_calc = || -> (u64, u64) {
+        use demo_calc_hash_in_one_block::{Package, WASM_BINARY};
+
+        let (src, times) = ([0; 32], 1);
+
+        let init_gas = Gear::calculate_gas_info(
+            USER_1.into_origin(),
+            HandleKind::Init(WASM_BINARY.to_vec()),
+            EMPTY_PAYLOAD.to_vec(),
             0,
             true,
         )
@@ -4012,53 +5073,1627 @@ fn test_async_messages() {
     })
 }
 
-mod utils {
-    #![allow(unused)]
+#[test]
+fn queue_runners_take_dispatches_by_strategy() {
+    use crate::{ProcessFairShare, ProcessLimited, ProcessRoundRobin, QueueRunner};
+    use frame_support::traits::ConstU32;
 
-    use super::{
-        assert_ok, pallet, run_to_block, BalancesPallet, Event, GearPallet, MailboxOf, MockEvent,
-        Origin, SystemPallet, Test,
-    };
-    use codec::Decode;
-    use common::{
-        event::*,
-        storage::{CountedByKey, IterableByKeyMap},
-        Origin as _,
-    };
-    use core_processor::common::ExecutionErrorReason;
-    use frame_support::{
-        dispatch::{DispatchErrorWithPostInfo, DispatchResultWithPostInfo},
-        traits::tokens::currency::Currency,
-    };
-    use gear_backend_common::TrapExplanation;
-    use gear_core::{
-        ids::{CodeId, MessageId, ProgramId},
-        message::StoredMessage,
-    };
-    use gear_core_errors::ExtError;
-    use sp_core::H256;
-    use sp_runtime::traits::UniqueSaturatedInto;
-    use sp_std::{convert::TryFrom, fmt::Debug};
+    fn take<R: QueueRunner<Test>>(mut runner: R) -> Vec<ProgramId> {
+        let taken = std::iter::from_fn(|| runner.next_dispatch())
+            .map(|dispatch| dispatch.destination())
+            .collect();
+        runner.finish();
+        taken
+    }
 
-    pub(super) const DEFAULT_GAS_LIMIT: u64 = 100_000_000;
-    pub(super) const DEFAULT_SALT: &[u8; 4] = b"salt";
-    pub(super) const EMPTY_PAYLOAD: &[u8; 0] = b"";
-    pub(super) const OUTGOING_WITH_VALUE_IN_HANDLE_VALUE: u128 = 10000000;
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_a = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result is not ok");
 
-    pub(super) type DispatchCustomResult<T> = Result<T, DispatchErrorWithPostInfo>;
-    pub(super) type AccountId = <Test as frame_system::Config>::AccountId;
-    pub(super) type GasPrice = <Test as pallet::Config>::GasPrice;
-    type BlockNumber = <Test as frame_system::Config>::BlockNumber;
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            ProgramCodeKind::Default.to_bytes(),
+            b"salt_b".to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT,
+            0,
+        ));
+        let program_b = get_last_program_id();
 
-    pub(super) fn init_logger() {
-        let _ = env_logger::Builder::from_default_env()
-            .format_module_path(false)
-            .format_level(true)
-            .try_init();
+        run_to_block(2, None);
+
+        for program in [program_a, program_a, program_b] {
+            assert_ok!(send_default_message(USER_1, program));
+        }
+
+        assert_eq!(
+            take(ProcessLimited::<ConstU32<2>>::default()),
+            [program_a; 2]
+        );
+        assert_eq!(QueueOf::<Test>::len(), 1);
+
+        for _ in 0..2 {
+            assert_ok!(send_default_message(USER_1, program_a));
+        }
+
+        // Second dispatch of `program_a` is deferred till the next block.
+        assert_eq!(
+            take(ProcessFairShare::<ConstU32<1>>::default()),
+            [program_b, program_a]
+        );
+        assert_eq!(QueueOf::<Test>::len(), 1);
+
+        for program in [program_a, program_a, program_b, program_b] {
+            assert_ok!(send_default_message(USER_1, program));
+        }
+
+        // Each round serves both programs, while the remaining dispatch
+        // of `program_a` exceeds its share and waits for the next block.
+        assert_eq!(
+            take(ProcessRoundRobin::<ConstU32<2>>::default()),
+            [program_a, program_b, program_a, program_b]
+        );
+        assert_eq!(QueueOf::<Test>::len(), 1);
+    })
+}
+
+#[test]
+fn queue_runners_keep_order_of_deferred_dispatches() {
+    use crate::{ProcessFairShare, ProcessRoundRobin, QueueRunner};
+    use frame_support::traits::ConstU32;
+
+    fn take<R: QueueRunner<Test>>(mut runner: R) -> Vec<MessageId> {
+        let taken = std::iter::from_fn(|| runner.next_dispatch())
+            .map(|dispatch| dispatch.id())
+            .collect();
+        runner.finish();
+        taken
     }
 
-    pub(super) fn get_ed() -> u128 {
-        <Test as pallet::Config>::Currency::minimum_balance().unique_saturated_into()
+    fn queued() -> Vec<MessageId> {
+        QueueOf::<Test>::iter()
+            .map(|dispatch| dispatch.expect("Message queue corrupted").id())
+            .collect()
+    }
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_a = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result is not ok");
+
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            ProgramCodeKind::Default.to_bytes(),
+            b"salt_b".to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT,
+            0,
+        ));
+        let program_b = get_last_program_id();
+
+        run_to_block(2, None);
+
+        let send = |program| {
+            assert_ok!(send_default_message(USER_1, program));
+            get_last_message_id()
+        };
+
+        let [a1, a2, b1, a3] = [program_a, program_a, program_b, program_a].map(send);
+
+        assert_eq!(take(ProcessFairShare::<ConstU32<1>>::default()), [a1, b1]);
+        assert_eq!(queued(), [a2, a3]);
+
+        assert_eq!(take(ProcessFairShare::<ConstU32<1>>::default()), [a2]);
+        assert_eq!(queued(), [a3]);
+
+        let [a4, b2, a5] = [program_a, program_b, program_a].map(send);
+
+        assert_eq!(take(ProcessRoundRobin::<ConstU32<1>>::default()), [a3, b2]);
+        assert_eq!(queued(), [a4, a5]);
+    })
+}
+
+#[test]
+fn hold_duration_and_cost_agree() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let reserve_for = CostsPerBlockOf::<Test>::reserve_for();
+
+        for (kind, cost) in vec![
+            (HoldKind::Waitlist, CostsPerBlockOf::<Test>::waitlist()),
+            (HoldKind::Mailbox, CostsPerBlockOf::<Test>::mailbox()),
+            (
+                HoldKind::DispatchStash,
+                CostsPerBlockOf::<Test>::dispatch_stash(),
+            ),
+        ] {
+            let gas = GearPallet::<Test>::hold_cost(10, kind);
+
+            assert_eq!(gas, cost * (10 + reserve_for));
+            assert_eq!(GearPallet::<Test>::hold_duration(gas, kind), 10);
+            assert_eq!(GearPallet::<Test>::hold_duration(gas - 1, kind), 9);
+        }
+
+        // Gas isn't enough even for the reserve.
+        assert_eq!(GearPallet::<Test>::hold_duration(0, HoldKind::Mailbox), 0);
+
+        // Reservations aren't charged for holding.
+        assert_eq!(GearPallet::<Test>::hold_cost(10, HoldKind::Reservation), 0);
+        assert_eq!(
+            GearPallet::<Test>::hold_duration(0, HoldKind::Reservation),
+            u32::MAX
+        );
+    })
+}
+
+#[test]
+fn program_event_emitted_on_success_only() {
+    let wat = |after_emit: &str| {
+        format!(
+            r#"
+    (module
+        (import "env" "memory" (memory 1))
+        (import "env" "gr_emit_event" (func $gr_emit_event (param i32 i32 i32 i32)))
+        (export "handle" (func $handle))
+        (export "init" (func $init))
+        (func $handle
+            i32.const 0  ;; topics ptr
+            i32.const 1  ;; topics len
+            i32.const 32 ;; payload ptr
+            i32.const 5  ;; payload len
+            call $gr_emit_event
+            {}
+        )
+        (func $init)
+        (data (i32.const 0) "topic")
+        (data (i32.const 32) "hello")
+    )"#,
+            after_emit
+        )
+    };
+    let succeeding = wat("");
+    let trapping = wat("unreachable");
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let succeeding = submit_program_default(USER_1, ProgramCodeKind::Custom(&succeeding))
+            .expect("submit result was asserted");
+        let trapping = submit_program_default(USER_1, ProgramCodeKind::Custom(&trapping))
+            .expect("submit result was asserted");
+
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, succeeding));
+        assert_ok!(send_default_message(USER_1, trapping));
+
+        run_to_block(3, None);
+
+        let mut topic = [0u8; 32];
+        topic[..5].copy_from_slice(b"topic");
+
+        let events: Vec<_> = System::events()
+            .into_iter()
+            .filter_map(|e| match e.event {
+                MockEvent::Gear(Event::ProgramEvent {
+                    source,
+                    topics,
+                    payload,
+                }) => Some((source, topics, payload)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            events,
+            vec![(succeeding, vec![H256(topic)], b"hello".to_vec())]
+        );
+    })
+}
+
+#[test]
+fn queue_processing_paused_and_resumed() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Gear::set_queue_processing(Origin::signed(USER_1), false),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Gear::set_queue_processing(Origin::root(), false));
+        assert!(!Gear::queue_processing_enabled());
+
+        // Messages are still enqueued while processing is paused.
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result was asserted");
+
+        run_to_block(2, None);
+
+        assert_eq!(QueueOf::<Test>::len(), 1);
+        assert!(System::events()
+            .into_iter()
+            .any(|e| matches!(e.event, MockEvent::Gear(Event::QueueProcessingSkipped))));
+
+        assert_ok!(Gear::set_queue_processing(Origin::root(), true));
+        SystemPallet::<Test>::assert_last_event(Event::QueueProcessingSet { enabled: true }.into());
+
+        run_to_block(3, None);
+
+        assert!(QueueOf::<Test>::is_empty());
+        assert!(common::get_program(program_id.into_origin())
+            .expect("Program was submitted")
+            .is_initialized());
+    })
+}
+
+#[test]
+fn banned_program_isnt_executed() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result was asserted");
+
+        run_to_block(2, None);
+
+        assert_noop!(
+            Gear::ban_program(Origin::signed(USER_1), program_id),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Gear::ban_program(Origin::root(), USER_2.into()),
+            Error::<Test>::ProgramNotFound
+        );
+
+        assert_ok!(Gear::ban_program(Origin::root(), program_id));
+        assert_noop!(
+            Gear::ban_program(Origin::root(), program_id),
+            Error::<Test>::ProgramAlreadyBanned
+        );
+
+        // Message to the banned program is answered with error reply.
+        assert_ok!(send_default_message(USER_1, program_id));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+
+        assert_not_executed(message_id);
+
+        let reply = maybe_last_message(USER_1).expect("Error reply should be sent");
+        assert_eq!(reply.source(), program_id);
+        assert!(matches!(reply.exit_code(), Some(code) if code != 0));
+
+        assert_ok!(Gear::unban_program(Origin::root(), program_id));
+        assert_noop!(
+            Gear::unban_program(Origin::root(), program_id),
+            Error::<Test>::ProgramNotBanned
+        );
+
+        assert_ok!(send_default_message(USER_1, program_id));
+        let message_id = get_last_message_id();
+
+        run_to_block(4, None);
+
+        assert_succeed(message_id);
+    })
+}
+
+#[test]
+fn block_run_stats_overwritten_each_block() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_eq!(Gear::block_run_stats(), Default::default());
+
+        assert_ok!(submit_program_default(USER_1, ProgramCodeKind::Default));
+        run_to_block(2, None);
+
+        let stats = Gear::block_run_stats();
+        assert_eq!(stats.messages_processed, 1);
+        assert!(stats.gas_burned > 0);
+
+        // Nothing was processed within the next block.
+        run_to_block(3, None);
+        assert_eq!(Gear::block_run_stats(), Default::default());
+    })
+}
+
+#[test]
+fn code_read_from_storage_once_per_block() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = {
+            let res = submit_program_default(USER_1, ProgramCodeKind::Default);
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        run_to_block(2, None);
+
+        for _ in 0..3 {
+            assert_ok!(send_default_message(USER_1, program_id));
+        }
+
+        run_to_block(3, None);
+
+        let code_cache = CodeCacheStats { hits: 2, misses: 1 };
+
+        assert_eq!(Gear::block_run_stats().code_cache, code_cache);
+        assert!(System::events().into_iter().any(|e| matches!(
+            e.event,
+            MockEvent::Gear(Event::MessagesDispatched { code_cache: stats, .. }) if stats == code_cache
+        )));
+    })
+}
+
+#[test]
+fn corrupted_gas_tree_quarantines_message() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        // Consuming message, which has no gas node.
+        let message_id = MessageId::from(1);
+        ExtManager::<Test>::default().message_consumed(message_id);
+
+        // Block stays producible, while the message is quarantined.
+        assert_eq!(
+            crate::QuarantinedMessages::<Test>::get(message_id),
+            Some(None)
+        );
+        assert!(SystemPallet::<Test>::events().iter().any(|e| matches!(
+            &e.event,
+            MockEvent::Gear(Event::GasTreeCorrupted { id, .. }) if *id == message_id
+        )));
+
+        run_to_block(2, None);
+    })
+}
+
+#[test]
+fn failed_gas_split_quarantines_sent_dispatch() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = {
+            let res = submit_program_default(USER_1, ProgramCodeKind::Default);
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        run_to_block(2, None);
+
+        // Sending from the message, which has no gas node to split.
+        let message = Message::new(
+            MessageId::from(2),
+            program_id,
+            program_id,
+            Default::default(),
+            Some(DEFAULT_GAS_LIMIT / 2),
+            0,
+            None,
+        );
+        let dispatch = Dispatch::new(DispatchKind::Handle, message);
+        let dispatch_id = dispatch.id();
+
+        let queued = QueueOf::<Test>::len();
+        ExtManager::<Test>::default().send_dispatch(MessageId::from(1), dispatch.clone(), 0, None);
+
+        // Dispatch isn't queued without gas, but kept in the quarantine.
+        assert_eq!(QueueOf::<Test>::len(), queued);
+        assert_eq!(
+            crate::QuarantinedMessages::<Test>::get(dispatch_id),
+            Some(Some(dispatch.into_stored()))
+        );
+    })
+}
+
+#[test]
+fn storage_consistency_checks_work() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = {
+            let res = submit_program_default(USER_1, ProgramCodeKind::Default);
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        assert_ok!(GearPallet::<Test>::check_gas_nodes());
+        assert_ok!(GearBank::<Test>::check_holdings());
+
+        run_to_block(2, None);
+
+        assert_ok!(GearPallet::<Test>::check_gas_nodes());
+        assert_ok!(GearBank::<Test>::check_holdings());
+
+        // Dust isn't backed by funds of the bank.
+        crate::Dust::<Test>::put(1);
+        assert_eq!(
+            GearBank::<Test>::check_holdings(),
+            Err("Bank doesn't hold enough funds")
+        );
+        crate::Dust::<Test>::kill();
+
+        // Gas node of the queued message is lost.
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT,
+            0,
+        ));
+        pallet_gear_gas::GasNodes::<Test>::remove(get_last_message_id());
+
+        assert_eq!(
+            GearPallet::<Test>::check_gas_nodes(),
+            Err("Queued message has no gas node")
+        );
+    })
+}
+
+#[test]
+fn delayed_dispatch_is_charged_for_stash_interval() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = {
+            let res = submit_program_default(USER_1, ProgramCodeKind::Default);
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        run_to_block(2, None);
+
+        // Imitating program, which sends delayed message to itself.
+        let origin_msg_id = MessageId::from(1);
+        assert_ok!(GasHandlerOf::<Test>::create(
+            USER_1,
+            origin_msg_id,
+            DEFAULT_GAS_LIMIT
+        ));
+
+        let gas_limit = DEFAULT_GAS_LIMIT / 2;
+        let message = Message::new(
+            MessageId::from(2),
+            program_id,
+            program_id,
+            Default::default(),
+            Some(gas_limit),
+            0,
+            None,
+        );
+        let delayed_id = message.id();
+
+        let mut ext_manager = ExtManager::<Test>::default();
+        ext_manager.send_dispatch(
+            origin_msg_id,
+            Dispatch::new(DispatchKind::Handle, message),
+            3,
+            None,
+        );
+
+        // Releasing the dispatch off schedule, as it happens for missed
+        // blocks, still charges for the stashed interval only.
+        System::set_block_number(10);
+        common::scheduler::TaskHandler::send_dispatch(&mut ext_manager, delayed_id);
+
+        assert!(!DispatchStashOf::<Test>::contains_key(&delayed_id));
+        assert_eq!(QueueOf::<Test>::len(), 1);
+
+        let stash_cost = CostsPerBlockOf::<Test>::dispatch_stash();
+        assert_eq!(
+            GasHandlerOf::<Test>::get_limit(delayed_id)
+                .expect("Gas node exists")
+                .map(|(limit, _)| limit),
+            Some(gas_limit - 3 * stash_cost)
+        );
+    })
+}
+
+#[test]
+fn delayed_dispatch_is_stashed_till_sending_block() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = {
+            let res = submit_program_default(USER_1, ProgramCodeKind::Default);
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        run_to_block(2, None);
+
+        // Imitating program, which sends delayed message to itself.
+        let origin_msg_id = MessageId::from(1);
+        assert_ok!(GasHandlerOf::<Test>::create(
+            USER_1,
+            origin_msg_id,
+            DEFAULT_GAS_LIMIT
+        ));
+
+        let message = Message::new(
+            MessageId::from(2),
+            program_id,
+            program_id,
+            Default::default(),
+            Some(DEFAULT_GAS_LIMIT / 2),
+            0,
+            None,
+        );
+        let delayed_id = message.id();
+
+        let mut ext_manager = ExtManager::<Test>::default();
+        ext_manager.send_dispatch(
+            origin_msg_id,
+            Dispatch::new(DispatchKind::Handle, message),
+            3,
+            None,
+        );
+
+        assert!(DispatchStashOf::<Test>::contains_key(&delayed_id));
+        assert!(QueueOf::<Test>::is_empty());
+
+        run_to_block(4, None);
+        assert!(DispatchStashOf::<Test>::contains_key(&delayed_id));
+
+        // Dispatch is enqueued and processed within the sending block.
+        run_to_block(5, None);
+        assert!(!DispatchStashOf::<Test>::contains_key(&delayed_id));
+        assert_eq!(Gear::block_run_stats().messages_processed, 1);
+    })
+}
+
+#[test]
+fn reply_chain_depth_limit_enforced() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = {
+            let res = submit_program_default(USER_1, ProgramCodeKind::Default);
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        run_to_block(2, None);
+
+        // Imitating program, executing message at the max depth
+        // of the chain, which sends message to itself.
+        let origin_msg_id = MessageId::from(1);
+        assert_ok!(GasHandlerOf::<Test>::create(
+            USER_1,
+            origin_msg_id,
+            DEFAULT_GAS_LIMIT
+        ));
+        ReplyChainDepth::<Test>::insert(origin_msg_id, ReplyChainDepthLimit::get());
+
+        let message = Message::new(
+            MessageId::from(2),
+            program_id,
+            program_id,
+            Default::default(),
+            Some(DEFAULT_GAS_LIMIT / 2),
+            0,
+            None,
+        );
+        let rejected_id = message.id();
+
+        let mut ext_manager = ExtManager::<Test>::default();
+        ext_manager.send_dispatch(
+            origin_msg_id,
+            Dispatch::new(DispatchKind::Handle, message),
+            0,
+            None,
+        );
+
+        // Only the error reply is queued, continuing the chain.
+        assert_eq!(QueueOf::<Test>::len(), 1);
+        assert!(matches!(
+            GasHandlerOf::<Test>::get_limit(rejected_id),
+            Ok(None)
+        ));
+
+        let reply = QueueOf::<Test>::dequeue()
+            .expect("Queue corrupted")
+            .expect("Error reply queued");
+
+        assert_eq!(reply.reply_to(), Some(rejected_id));
+        assert_eq!(reply.exit_code(), Some(core_processor::ERR_EXIT_CODE));
+        assert_eq!(
+            ErrorReplyReason::from_payload(reply.payload()),
+            Some(ErrorReplyReason::ReplyChainTooDeep)
+        );
+        assert_eq!(
+            ReplyChainDepth::<Test>::get(reply.id()),
+            ReplyChainDepthLimit::get() + 1
+        );
+
+        // Consuming the message clears its depth.
+        ext_manager.message_consumed(origin_msg_id);
+        assert!(!ReplyChainDepth::<Test>::contains_key(origin_msg_id));
+    })
+}
+
+#[test]
+fn wait_for_wakes_message_on_deadline() {
+    // Handle waits for 5 blocks once, then finishes.
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_wait_for" (func $gr_wait_for (param i32)))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (if (i32.eqz (i32.load (i32.const 0x0)))
+            (then
+                (i32.store (i32.const 0x0) (i32.const 0x1))
+                (call $gr_wait_for (i32.const 5))
+            )
+        )
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &message_id));
+
+        run_to_block(7, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &message_id));
+
+        // Message is woken and executed within the deadline block.
+        run_to_block(8, None);
+        assert!(!WaitlistOf::<Test>::contains(&pid, &message_id));
+        assert!(SystemPallet::<Test>::events().iter().any(|e| {
+            e.event
+                == MockEvent::Gear(Event::MessageWoken {
+                    id: message_id,
+                    reason: MessageWokenSystemReason::TimeoutHasCome.into_reason(),
+                })
+        }));
+        assert_succeed(message_id);
+    });
+}
+
+#[test]
+fn wake_delayed_wakes_message_after_delay() {
+    // First handle waits, second one wakes the first with delay of 3 blocks.
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_msg_id" (func $gr_msg_id (param i32)))
+    (import "env" "gr_wait" (func $gr_wait))
+    (import "env" "gr_wake_delayed" (func $gr_wake_delayed (param i32 i32) (result i32)))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (if (i32.eqz (i32.load (i32.const 0x0)))
+            (then
+                (i32.store (i32.const 0x0) (i32.const 0x1))
+                (call $gr_msg_id (i32.const 0x10))
+                (call $gr_wait)
+            )
+        )
+        (if (i32.eq (i32.load (i32.const 0x0)) (i32.const 0x1))
+            (then
+                (i32.store (i32.const 0x0) (i32.const 0x2))
+                (drop (call $gr_wake_delayed (i32.const 0x10) (i32.const 3)))
+            )
+        )
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let waited_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let waker_id = get_last_message_id();
+
+        run_to_block(4, None);
+        assert_succeed(waker_id);
+
+        run_to_block(6, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+
+        run_to_block(7, None);
+        assert!(!WaitlistOf::<Test>::contains(&pid, &waited_id));
+        assert_succeed(waited_id);
+    });
+}
+
+#[test]
+fn delayed_wakes_are_removed_on_earlier_wake() {
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_wait" (func $gr_wait))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (call $gr_wait)
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let waited_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+
+        let waking_bn = System::block_number() + 3;
+        let task = ScheduledTask::WakeMessage(pid, waited_id);
+
+        // Imitating programs, which wake the message with the same delay.
+        let mut ext_manager = ExtManager::<Test>::default();
+        ext_manager.wake_message(MessageId::from(1), pid, waited_id, 3);
+        ext_manager.wake_message(MessageId::from(2), pid, waited_id, 3);
+        assert!(TaskPoolOf::<Test>::contains(&waking_bn, &task));
+
+        ext_manager.wake_message(MessageId::from(3), pid, waited_id, 0);
+        assert!(!WaitlistOf::<Test>::contains(&pid, &waited_id));
+        assert!(!TaskPoolOf::<Test>::contains(&waking_bn, &task));
+    });
+}
+
+#[test]
+fn waitlist_expiration_is_removed_on_wake() {
+    // First handle waits, second one wakes the first.
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_msg_id" (func $gr_msg_id (param i32)))
+    (import "env" "gr_wait" (func $gr_wait))
+    (import "env" "gr_wake" (func $gr_wake (param i32) (result i32)))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (if (i32.eqz (i32.load (i32.const 0x0)))
+            (then
+                (i32.store (i32.const 0x0) (i32.const 0x1))
+                (call $gr_msg_id (i32.const 0x10))
+                (call $gr_wait)
+            )
+        )
+        (if (i32.eq (i32.load (i32.const 0x0)) (i32.const 0x1))
+            (then
+                (i32.store (i32.const 0x0) (i32.const 0x2))
+                (drop (call $gr_wake (i32.const 0x10)))
+            )
+        )
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let waited_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+
+        let expiration = SystemPallet::<Test>::events()
+            .into_iter()
+            .find_map(|e| match e.event {
+                MockEvent::Gear(Event::MessageWaited { id, expiration, .. }) if id == waited_id => {
+                    Some(expiration)
+                }
+                _ => None,
+            })
+            .expect("Message wasn't waited");
+
+        // Holding is capped by the rent the message is able to pay.
+        let gas_limit = GasHandlerOf::<Test>::get_limit(waited_id)
+            .unwrap()
+            .map(|(g, _)| g)
+            .unwrap();
+        assert_eq!(
+            expiration,
+            3 + ExtManager::<Test>::affordable_waitlist_duration(gas_limit) as u64
+        );
+
+        let task = ScheduledTask::RemoveFromWaitlist(pid, waited_id);
+        assert!(TaskPoolOf::<Test>::contains(&expiration, &task));
+
+        assert_ok!(send_default_message(USER_1, pid));
+        run_to_block(4, None);
+
+        assert!(!WaitlistOf::<Test>::contains(&pid, &waited_id));
+        assert!(!TaskPoolOf::<Test>::contains(&expiration, &task));
+        assert_succeed(waited_id);
+    });
+}
+
+#[test]
+fn send_cancel_discards_message_and_refunds_pushed_bytes() {
+    // Handle pushes bytes into the message and cancels it, checking
+    // that it can't be cancelled twice.
+    let wat = |len: u32| {
+        format!(
+            r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_send_init" (func $gr_send_init (param i32) (result i32)))
+    (import "env" "gr_send_push" (func $gr_send_push (param i32 i32 i32) (result i32)))
+    (import "env" "gr_send_cancel" (func $gr_send_cancel (param i32) (result i32)))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (if (call $gr_send_init (i32.const 0x0))
+            (then unreachable)
+        )
+        (if (call $gr_send_push (i32.load (i32.const 0x0)) (i32.const 0x1000) (i32.const {}))
+            (then unreachable)
+        )
+        (if (call $gr_send_cancel (i32.load (i32.const 0x0)))
+            (then unreachable)
+        )
+        (if (i32.eqz (call $gr_send_cancel (i32.load (i32.const 0x0))))
+            (then unreachable)
+        )
+    )
+)"#,
+            len
+        )
+    };
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        // Lengths are chosen to have the same size of encoding,
+        // so programs differ only by the amount of pushed bytes.
+        let (short, long) = (wat(0x40), wat(0x1000));
+
+        let pid1 = submit_program_default(USER_1, ProgramCodeKind::Custom(&short))
+            .expect("submit result is not ok");
+        let pid2 = submit_program_default(USER_1, ProgramCodeKind::Custom(&long))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        let gas_info = |pid| {
+            Gear::calculate_gas_info(
+                USER_1.into_origin(),
+                HandleKind::Handle(pid),
+                EMPTY_PAYLOAD.to_vec(),
+                0,
+                true,
+            )
+            .expect("calculate_gas_info failed")
+        };
+
+        // Gas charged for pushed bytes is refunded on cancel.
+        let gas1 = gas_info(pid1);
+        let gas2 = gas_info(pid2);
+        assert_eq!(gas1.burned, gas2.burned);
+
+        // Nothing is sent, so no gas is needed for outgoing messages.
+        assert_eq!(gas2.burned, gas2.min_limit);
+
+        assert_ok!(send_default_message(USER_1, pid2));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert_succeed(message_id);
+        assert!(MailboxOf::<Test>::is_empty(&USER_1));
+    });
+}
+
+#[test]
+fn reply_push_input_forwards_incoming_payload() {
+    // Handle replies with the incoming payload except the first byte,
+    // checking that pushing out of the payload bounds fails.
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_size" (func $gr_size (result i32)))
+    (import "env" "gr_reply_push_input" (func $gr_reply_push_input (param i32 i32) (result i32)))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (if (call $gr_reply_push_input (i32.const 0x1) (i32.sub (call $gr_size) (i32.const 0x1)))
+            (then unreachable)
+        )
+        (if (i32.eqz (call $gr_reply_push_input (i32.const 0x0) (i32.add (call $gr_size) (i32.const 0x1))))
+            (then unreachable)
+        )
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            b"!Hello, world!".to_vec(),
+            DEFAULT_GAS_LIMIT,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert_succeed(message_id);
+
+        let reply = maybe_last_message(USER_1).expect("Reply should be sent");
+        assert_eq!(reply.payload(), b"Hello, world!");
+    });
+}
+
+#[test]
+fn read_out_of_payload_bounds_fails() {
+    // Handle reads the incoming payload along with one byte beyond it.
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_size" (func $gr_size (result i32)))
+    (import "env" "gr_read" (func $gr_read (param i32 i32 i32)))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (call $gr_read (i32.const 0x0) (i32.add (call $gr_size) (i32.const 0x1)) (i32.const 0x0))
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            b"Hello, world!".to_vec(),
+            DEFAULT_GAS_LIMIT,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Core(ExtError::Message(
+                MessageError::OutOfBoundsInputRange,
+            ))),
+        );
+    });
+}
+
+#[test]
+fn wait_up_to_zero_blocks_fails() {
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_wait_up_to" (func $gr_wait_up_to (param i32)))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (call $gr_wait_up_to (i32.const 0))
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert!(!WaitlistOf::<Test>::contains(&pid, &message_id));
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Core(ExtError::Execution(
+                ExecutionError::ZeroWaitDuration,
+            ))),
+        );
+    });
+}
+
+#[test]
+fn wait_fails_on_full_waitlist() {
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_wait" (func $gr_wait))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (call $gr_wait)
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        crate::mock::WaitlistCapacity::set(&1);
+
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let waited_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+        assert!(WaitlistOf::<Test>::is_full(&pid));
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let message_id = get_last_message_id();
+
+        run_to_block(4, None);
+        assert!(!WaitlistOf::<Test>::contains(&pid, &message_id));
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Core(ExtError::Execution(
+                ExecutionError::WaitlistFull,
+            ))),
+        );
+        assert_eq!(GearPallet::<Test>::waitlist_len(pid), 1);
+    });
+}
+
+#[test]
+fn messenger_lens_are_maintained() {
+    let wat = r#"
+(module
+    (import "env" "memory" (memory 1))
+    (import "env" "gr_wait" (func $gr_wait))
+    (export "handle" (func $handle))
+    (export "init" (func $init))
+    (func $init)
+    (func $handle
+        (call $gr_wait)
+    )
+)"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        // Caution: runs to block 2.
+        let reply_to_id = setup_mailbox_test_state(USER_2);
+        assert_eq!(GearPallet::<Test>::mailbox_len(&USER_2), 1);
+        assert_eq!(GearPallet::<Test>::mailbox_len(&USER_1), 0);
+
+        assert_ok!(MailboxOf::<Test>::remove(USER_2, reply_to_id));
+        assert_eq!(GearPallet::<Test>::mailbox_len(&USER_2), 0);
+
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Custom(wat))
+            .expect("submit result is not ok");
+        assert_eq!(GearPallet::<Test>::queue_len(), 1);
+
+        run_to_block(3, None);
+        assert_eq!(GearPallet::<Test>::queue_len(), 0);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let message_id = get_last_message_id();
+        assert_ok!(send_default_message(USER_1, pid));
+        assert_eq!(GearPallet::<Test>::queue_len(), 2);
+
+        run_to_block(4, None);
+        assert_eq!(GearPallet::<Test>::queue_len(), 0);
+        assert_eq!(GearPallet::<Test>::waitlist_len(pid), 2);
+
+        assert_ok!(WaitlistOf::<Test>::remove(pid, message_id));
+        assert_eq!(GearPallet::<Test>::waitlist_len(pid), 1);
+    });
+}
+
+#[test]
+fn unused_gas_refund_is_reported() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let pid = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result is not ok");
+        run_to_block(2, None);
+
+        assert_ok!(send_default_message(USER_1, pid));
+        let message_id = get_last_message_id();
+        let balance = BalancesPallet::<Test>::free_balance(USER_1);
+
+        run_to_block(3, None);
+        assert_succeed(message_id);
+
+        let refunded = SystemPallet::<Test>::events()
+            .into_iter()
+            .find_map(|e| match e.event {
+                MockEvent::Gear(Event::GasRefunded {
+                    message_id: id,
+                    amount,
+                }) if id == message_id => Some(amount),
+                _ => None,
+            })
+            .expect("Refund of the message wasn't reported");
+
+        assert!(refunded > 0);
+        assert_eq!(
+            BalancesPallet::<Test>::free_balance(USER_1),
+            balance + <Test as Config>::GasPrice::gas_price(refunded)
+        );
+    });
+}
+
+#[test]
+fn demo_wait_wake_waits_for_blocks_and_wakes_delayed() {
+    use demo_wait_wake::{Request, WASM_BINARY};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        let pid = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(pid));
+
+        // Waiting for 5 blocks, the message is woken by timeout.
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            Request::EchoWaitFor {
+                echo: 42,
+                duration: 5,
+            }
+            .encode(),
+            10_000_000_000,
+            0,
+        ));
+        let waited_id = get_last_message_id();
+
+        run_to_block(7, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+
+        run_to_block(8, None);
+        assert!(!WaitlistOf::<Test>::contains(&pid, &waited_id));
+        assert_succeed(waited_id);
+        assert_eq!(
+            maybe_last_message(USER_1).map(|msg| msg.payload().to_vec()),
+            Some(42u32.encode())
+        );
+
+        // Waiting with no timeout, the message is woken by another one with delay.
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            Request::EchoWait(7).encode(),
+            10_000_000_000,
+            0,
+        ));
+        let waited_id = get_last_message_id();
+
+        run_to_block(9, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+
+        // Message ids are encoded the same way in the pallet and in programs.
+        let wake_id = Decode::decode(&mut waited_id.encode().as_ref()).unwrap();
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            Request::WakeDelayed(wake_id, 3).encode(),
+            10_000_000_000,
+            0,
+        ));
+        let waker_id = get_last_message_id();
+
+        run_to_block(10, None);
+        assert_succeed(waker_id);
+
+        run_to_block(12, None);
+        assert!(WaitlistOf::<Test>::contains(&pid, &waited_id));
+
+        run_to_block(13, None);
+        assert!(!WaitlistOf::<Test>::contains(&pid, &waited_id));
+        assert_succeed(waited_id);
+        assert_eq!(
+            maybe_last_message(USER_1).map(|msg| msg.payload().to_vec()),
+            Some(7u32.encode())
+        );
+    });
+}
+
+#[test]
+fn demo_reserve_gas_sends_from_reservation() {
+    use demo_reserve_gas::{Request, WASM_BINARY};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        let pid = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(pid));
+
+        let send_request = |request: Request| {
+            assert_ok!(GearPallet::<Test>::send_message(
+                Origin::signed(USER_1),
+                pid,
+                request.encode(),
+                10_000_000_000,
+                0,
+            ));
+            get_last_message_id()
+        };
+
+        // Reserved gas outlives the message and pays for the later send.
+        let message_id = send_request(Request::Reserve {
+            amount: 1_000_000,
+            duration: 10,
+        });
+        run_to_next_block(None);
+        assert_succeed(message_id);
+
+        let message_id = send_request(Request::SendFromReservation(b"Reserved".to_vec()));
+        run_to_next_block(None);
+        assert_succeed(message_id);
+
+        let mail = get_last_mail(USER_1);
+        assert_eq!(mail.payload(), b"Reserved");
+
+        // Unreserved gas is returned to the program's message.
+        let message_id = send_request(Request::Reserve {
+            amount: 2_000_000,
+            duration: 10,
+        });
+        run_to_next_block(None);
+        assert_succeed(message_id);
+
+        let message_id = send_request(Request::Unreserve);
+        run_to_next_block(None);
+        assert_succeed(message_id);
+        assert_eq!(
+            maybe_last_message(USER_1).map(|msg| msg.payload().to_vec()),
+            Some(2_000_000u64.encode())
+        );
+    });
+}
+
+#[test]
+fn demo_signal_entry_receives_signal_on_trap() {
+    use demo_signal_entry::{HandleAction, WASM_BINARY};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        let pid = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(pid));
+
+        // Successful execution returns gas reserved for signal.
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            HandleAction::Reply.encode(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert_succeed(message_id);
+        assert_eq!(
+            maybe_last_message(USER_1).map(|msg| msg.payload().to_vec()),
+            Some(b"Handled".to_vec())
+        );
+        assert!(QueueOf::<Test>::is_empty());
+
+        // Failed execution is reported by the signal to the owner.
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            HandleAction::Panic.encode(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(4, None);
+        assert_last_dequeued(2);
+
+        let report = maybe_last_message(USER_1).expect("Signal report should be sent");
+        assert_eq!(
+            report.payload(),
+            (message_id, core_processor::TRAP_SIGNAL_CODE).encode()
+        );
+    });
+}
+
+#[test]
+fn demo_signal_entry_cannot_wait_in_signal() {
+    use demo_signal_entry::{HandleAction, SignalAction, WASM_BINARY};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(GearPallet::<Test>::submit_program(
+            Origin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            DEFAULT_SALT.to_vec(),
+            SignalAction::Wait.encode(),
+            10_000_000_000,
+            0,
+        ));
+        let pid = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(pid));
+
+        assert_ok!(GearPallet::<Test>::send_message(
+            Origin::signed(USER_1),
+            pid,
+            HandleAction::Panic.encode(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert_last_dequeued(2);
+
+        // Waiting is forbidden in the signal handler, so it traps
+        // before reporting and the signal isn't added to the waitlist.
+        assert_eq!(WaitlistOf::<Test>::iter_key(pid).count(), 0);
+        assert_ne!(
+            maybe_last_message(USER_1).map(|msg| msg.payload().to_vec()),
+            Some((message_id, core_processor::TRAP_SIGNAL_CODE).encode())
+        );
+    });
+}
+
+#[test]
+fn snapshot_program_lifecycle() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        snapshot::assert_snapshot("program_lifecycle", || {
+            let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+                .expect("Failed to submit program");
+            run_to_block(2, None);
+
+            assert_ok!(send_default_message(USER_1, program_id));
+            run_to_block(3, None);
+
+            assert!(Gear::is_initialized(program_id));
+        });
+    })
+}
+
+#[test]
+fn snapshot_mailbox_claim() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        snapshot::assert_snapshot("mailbox_claim", || {
+            let reply_to_id = setup_mailbox_test_state(USER_1);
+
+            assert_ok!(GearPallet::<Test>::claim_value(
+                Origin::signed(USER_1),
+                reply_to_id
+            ));
+            run_to_block(3, None);
+
+            assert!(MailboxOf::<Test>::is_empty(&USER_1));
+        });
+    })
+}
+
+#[test]
+fn migration_moves_reserves_to_bank() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<GearPallet<Test>>();
+
+        // Imitating message, sent before the bank was introduced:
+        // funds for its gas and value are reserved on the sender's account.
+        let message_id = MessageId::from(1);
+        let value = 10_000;
+        assert_ok!(GasHandlerOf::<Test>::create(
+            USER_1,
+            message_id,
+            DEFAULT_GAS_LIMIT
+        ));
+
+        let message = Message::new(
+            message_id,
+            ProgramId::from_origin(USER_1.into_origin()),
+            ProgramId::from(1),
+            Default::default(),
+            Some(DEFAULT_GAS_LIMIT),
+            value,
+            None,
+        );
+        assert_ok!(QueueOf::<Test>::queue(
+            Dispatch::new(DispatchKind::Handle, message).into_stored()
+        ));
+
+        let reserved = GasPrice::gas_price(DEFAULT_GAS_LIMIT) + value;
+        assert_ok!(Balances::reserve(&USER_1, reserved));
+
+        let user_balance = Balances::free_balance(USER_1);
+
+        crate::migration::v2::migrate::<Test>();
+
+        assert_eq!(GearPallet::<Test>::on_chain_storage_version(), 2);
+        assert!(Balances::reserved_balance(USER_1).is_zero());
+        assert_eq!(Balances::free_balance(USER_1), user_balance);
+        assert_eq!(bank_balance(), reserved);
+    })
+}
+
+#[test]
+fn migration_schedules_pausing_of_programs_without_expiration() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = submit_program_default(USER_1, ProgramCodeKind::Default)
+            .expect("submit result was asserted");
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(program_id));
+
+        // Imitating program, created before rent was introduced.
+        GearPallet::<Test>::remove_program_expiration(program_id);
+        StorageVersion::new(2).put::<GearPallet<Test>>();
+
+        crate::migration::migrate::<Test>();
+
+        assert_eq!(GearPallet::<Test>::on_chain_storage_version(), 3);
+
+        let expiration = System::block_number() + ProgramRentFreePeriod::get();
+        assert_eq!(
+            GearPallet::<Test>::program_expiration(program_id),
+            Some(expiration)
+        );
+        assert!(TaskPoolOf::<Test>::contains(
+            &expiration,
+            &ScheduledTask::PauseProgram(program_id)
+        ));
+    })
+}
+
+#[test]
+fn dust_is_routed_to_beneficiary() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let beneficiary = 1000;
+        DustBeneficiary::set(&Some(beneficiary));
+
+        let ed = get_ed();
+
+        // Bank holds funds, which couldn't be paid to their receivers.
+        assert_ok!(GearBank::<Test>::deposit(&USER_1, 0, 2 * ed));
+        GearBank::<Test>::add_dust(ed - 1);
+
+        run_to_block(2, None);
+
+        // Dust isn't enough to create an account of the beneficiary, so it's kept.
+        assert!(Balances::free_balance(beneficiary).is_zero());
+        assert_eq!(crate::Dust::<Test>::get(), ed - 1);
+
+        GearBank::<Test>::add_dust(ed);
+
+        run_to_block(3, None);
+
+        assert_eq!(Balances::free_balance(beneficiary), 2 * ed - 1);
+        assert!(crate::Dust::<Test>::get().is_zero());
+        assert_eq!(bank_balance(), 1);
+
+        SystemPallet::<Test>::assert_has_event(
+            Event::DustRouted {
+                beneficiary,
+                amount: 2 * ed - 1,
+            }
+            .into(),
+        );
+    })
+}
+
+mod utils {
+    #![allow(unused)]
+
+    use super::{
+        assert_ok, pallet, run_to_block, BalancesPallet, Event, GearBank, GearPallet, MailboxOf,
+        MockEvent, Origin, SystemPallet, Test,
+    };
+    use codec::Decode;
+    use common::{
+        event::*,
+        storage::{CountedByKey, IterableByKeyMap},
+        Origin as _,
+    };
+    use core_processor::common::ExecutionErrorReason;
+    use frame_support::{
+        dispatch::{DispatchErrorWithPostInfo, DispatchResultWithPostInfo},
+        traits::tokens::currency::Currency,
+    };
+    use gear_backend_common::TrapExplanation;
+    use gear_core::{
+        ids::{CodeId, MessageId, ProgramId},
+        message::StoredMessage,
+    };
+    use gear_core_errors::ExtError;
+    use sp_core::H256;
+    use sp_runtime::traits::UniqueSaturatedInto;
+    use sp_std::{convert::TryFrom, fmt::Debug};
+
+    pub(super) const DEFAULT_GAS_LIMIT: u64 = 100_000_000;
+    pub(super) const DEFAULT_SALT: &[u8; 4] = b"salt";
+    pub(super) const EMPTY_PAYLOAD: &[u8; 0] = b"";
+    pub(super) const OUTGOING_WITH_VALUE_IN_HANDLE_VALUE: u128 = 10000000;
+
+    pub(super) type DispatchCustomResult<T> = Result<T, DispatchErrorWithPostInfo>;
+    pub(super) type AccountId = <Test as frame_system::Config>::AccountId;
+    pub(super) type GasPrice = <Test as pallet::Config>::GasPrice;
+    type BlockNumber = <Test as frame_system::Config>::BlockNumber;
+
+    pub(super) fn init_logger() {
+        let _ = env_logger::Builder::from_default_env()
+            .format_module_path(false)
+            .format_level(true)
+            .try_init();
+    }
+
+    pub(super) fn get_ed() -> u128 {
+        <Test as pallet::Config>::Currency::minimum_balance().unique_saturated_into()
+    }
+
+    /// Funds held by the bank for gas and value of messages in flight.
+    pub(super) fn bank_balance() -> u128 {
+        BalancesPallet::<Test>::free_balance(GearBank::<Test>::account())
     }
 
     pub(super) fn assert_init_success(expected: u32) {
@@ -4272,9 +6907,9 @@ mod utils {
 
         SystemPallet::<Test>::events().into_iter().for_each(|e| {
             if let MockEvent::Gear(Event::UserMessageSent { message, .. }) = e.event {
-                if let Some((id, exit_code)) = message.reply() {
-                    if id == message_id {
-                        assert_ne!(exit_code, 0);
+                if let Some(details) = message.reply() {
+                    if details.reply_to() == message_id {
+                        assert_ne!(details.exit_code(), 0);
                         actual_error = Some(
                             String::from_utf8(message.payload().to_vec())
                                 .expect("Unable to decode string from error reply"),
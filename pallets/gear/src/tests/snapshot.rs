@@ -0,0 +1,213 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Snapshot harness for scripted scenarios.
+//!
+//! Captures journals of the blocks processed within the scenario and
+//! storage changes made by it, and compares them with the snapshot
+//! stored in `src/tests/snapshots`. Missing snapshot fails the test.
+//! Set `GEAR_UPDATE_SNAPSHOTS` environment variable to record new
+//! snapshots or re-record them after intended changes of the behavior.
+
+use codec::Decode;
+use common::journal::BlockJournal;
+use frame_support::storage::storage_prefix;
+use gear_runtime_interface::journal_export;
+use sp_core::H256;
+use sp_io::hashing::twox_128;
+use std::{cell::RefCell, collections::BTreeMap, fmt::Write as _, fs, io, path::PathBuf};
+
+/// Environment variable forcing snapshots to be recorded.
+const UPDATE_ENV: &str = "GEAR_UPDATE_SNAPSHOTS";
+
+/// Pallets of the mock runtime, used to make storage keys readable.
+const PALLETS: &[&str] = &[
+    "System",
+    "GearProgram",
+    "GearMessenger",
+    "GearScheduler",
+    "Gear",
+    "GearGas",
+    "Balances",
+    "Authorship",
+    "Timestamp",
+];
+
+type BlockNumber = <crate::mock::Test as frame_system::Config>::BlockNumber;
+type Storage = BTreeMap<Vec<u8>, Vec<u8>>;
+
+thread_local! {
+    static JOURNALS: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+}
+
+/// Journals sink writing into the buffer of the current thread,
+/// so tests running in parallel don't mix their journals.
+struct ThreadSink;
+
+impl io::Write for ThreadSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        JOURNALS.with(|journals| journals.borrow_mut().extend_from_slice(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs the scenario within current externalities and asserts
+/// its journals and storage changes match the snapshot `name`.
+pub(super) fn assert_snapshot(name: &str, scenario: impl FnOnce()) {
+    // Sink is shared by all tests of the process,
+    // so it could be already set by another one.
+    let _ = journal_export::set_sink(ThreadSink);
+    JOURNALS.with(|journals| journals.borrow_mut().clear());
+
+    let before = storage();
+    scenario();
+    let actual = render(&journals(), &before, &storage());
+
+    let path = snapshot_path(name);
+
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).expect("Failed to create snapshots directory");
+        }
+
+        fs::write(&path, actual).expect("Failed to write snapshot");
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Snapshot `{}` can't be read: {}, set `{}` to record it",
+            name, e, UPDATE_ENV
+        )
+    });
+
+    assert_eq!(
+        expected, actual,
+        "Snapshot `{}` doesn't match, set `{}` to re-record it if the change is intended",
+        name, UPDATE_ENV
+    );
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    // Gas charging differs with lazy pages, so they have own snapshots.
+    let suffix = if cfg!(feature = "lazy-pages") {
+        "-lazy-pages"
+    } else {
+        ""
+    };
+
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/tests/snapshots")
+        .join(format!("{}{}.snap", name, suffix))
+}
+
+fn journals() -> Vec<BlockJournal<BlockNumber, H256>> {
+    let bytes = JOURNALS.with(|journals| journals.take());
+    let mut input = &bytes[..];
+    let mut journals = Vec::new();
+
+    // Each journal is prefixed with its little-endian `u32` length.
+    while !input.is_empty() {
+        let len = u32::decode(&mut input).expect("Journal length is corrupted") as usize;
+        let (mut journal, rest) = input.split_at(len);
+
+        journals.push(BlockJournal::decode(&mut journal).expect("Journal is corrupted"));
+        input = rest;
+    }
+
+    journals
+}
+
+fn storage() -> Storage {
+    let mut storage = Storage::new();
+    let mut key = Vec::new();
+
+    while let Some(next) = sp_io::storage::next_key(&key) {
+        if !is_ignored(&next) {
+            if let Some(value) = sp_io::storage::get(&next) {
+                storage.insert(next.clone(), value.to_vec());
+            }
+        }
+
+        key = next;
+    }
+
+    storage
+}
+
+/// System storage, except accounts, holds only blocks bookkeeping
+/// and events, which are already reflected by journals.
+fn is_ignored(key: &[u8]) -> bool {
+    key.starts_with(&twox_128(b"System"))
+        && !key.starts_with(&storage_prefix(b"System", b"Account"))
+}
+
+fn render(
+    journals: &[BlockJournal<BlockNumber, H256>],
+    before: &Storage,
+    after: &Storage,
+) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "# Journals").unwrap();
+    for journal in journals {
+        writeln!(out, "{:#?}", journal).unwrap();
+    }
+
+    writeln!(out, "\n# Storage changes").unwrap();
+    for (key, value) in after {
+        match before.get(key) {
+            Some(prev) if prev == value => {}
+            Some(_) => writeln!(out, "~ {} = {}", render_key(key), hex(value)).unwrap(),
+            None => writeln!(out, "+ {} = {}", render_key(key), hex(value)).unwrap(),
+        }
+    }
+    for key in before.keys().filter(|key| !after.contains_key(*key)) {
+        writeln!(out, "- {}", render_key(key)).unwrap();
+    }
+
+    out
+}
+
+fn render_key(key: &[u8]) -> String {
+    for pallet in PALLETS {
+        if let Some(rest) = key.strip_prefix(&twox_128(pallet.as_bytes())[..]) {
+            return format!("{}::{}", pallet, hex(rest));
+        }
+    }
+
+    // Storages of `gear-common` are prefixed with readable strings.
+    let readable = key.iter().take_while(|b| b.is_ascii_graphic()).count();
+    let (prefix, rest) = key.split_at(readable);
+
+    format!("{}{}", String::from_utf8_lossy(prefix), hex(rest))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::from("0x");
+
+    for byte in bytes {
+        write!(out, "{:02x}", byte).unwrap();
+    }
+
+    out
+}
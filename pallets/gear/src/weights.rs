@@ -21,6 +21,10 @@
 //! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
 //! DATE: 2022-06-22, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
 //! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//!
+//! Weights marked as placeholders were added by hand after the generation and
+//! weren't produced by the benchmark CLI. Regenerate the file with the command
+//! below before relying on them.
 
 // Executed Command:
 // ./target/release/gear-node benchmark pallet --chain=dev --steps=50 --repeat=20 --pallet=pallet_gear --extrinsic=* --execution=wasm --wasm-execution=compiled --heap-pages=4096 --output=./pallets/gear/src/weights.rs --template=./.maintain/gear-weight-template.hbs
@@ -36,13 +40,18 @@ use sp_std::marker::PhantomData;
 /// Weight functions needed for pallet_gear.
 pub trait WeightInfo {
 	fn submit_code(c: u32, ) -> Weight;
+	fn submit_code_metadata(r: u32, ) -> Weight;
 	fn submit_program(c: u32, s: u32, ) -> Weight;
 	fn send_message(p: u32, ) -> Weight;
+	fn send_message_without_value() -> Weight;
 	fn send_reply(p: u32, ) -> Weight;
+	fn top_up_gas() -> Weight;
+	fn pay_program_rent() -> Weight;
 	fn initial_allocation(q: u32, ) -> Weight;
 	fn alloc_in_handle(q: u32, ) -> Weight;
 	fn reinstrument(c: u32, ) -> Weight;
 	fn alloc(r: u32, ) -> Weight;
+	fn free(r: u32, ) -> Weight;
 	fn gas(r: u32, ) -> Weight;
 	fn gr_gas_available(r: u32, ) -> Weight;
 	fn gr_msg_id(r: u32, ) -> Weight;
@@ -59,25 +68,42 @@ pub trait WeightInfo {
 	fn gr_send_init(r: u32, ) -> Weight;
 	fn gr_send_push(r: u32, ) -> Weight;
 	fn gr_send_push_per_kb(n: u32, ) -> Weight;
+	fn gr_send_push_input(r: u32, ) -> Weight;
+	fn gr_send_push_input_per_kb(n: u32, ) -> Weight;
+	fn gr_send_cancel(r: u32, ) -> Weight;
 	fn gr_send_commit(r: u32, ) -> Weight;
 	fn gr_send_commit_per_kb(n: u32, ) -> Weight;
 	fn gr_reply_commit(r: u32, ) -> Weight;
 	fn gr_reply_commit_per_kb(n: u32, ) -> Weight;
 	fn gr_reply_push(r: u32, ) -> Weight;
 	fn gr_reply_push_per_kb(n: u32, ) -> Weight;
+	fn gr_reply_push_input(r: u32, ) -> Weight;
+	fn gr_reply_push_input_per_kb(n: u32, ) -> Weight;
 	fn gr_reply_to(r: u32, ) -> Weight;
 	fn gr_debug(r: u32, ) -> Weight;
 	fn gr_exit_code(r: u32, ) -> Weight;
+	fn gr_error(r: u32, ) -> Weight;
 	fn gr_exit(r: u32, ) -> Weight;
 	fn gr_leave(r: u32, ) -> Weight;
 	fn gr_wait(r: u32, ) -> Weight;
+	fn gr_wait_for(r: u32, ) -> Weight;
+	fn gr_wait_up_to(r: u32, ) -> Weight;
 	fn gr_wake(r: u32, ) -> Weight;
 	fn gr_create_program_wgas(r: u32, ) -> Weight;
 	fn gr_create_program_wgas_per_kb(n: u32, ) -> Weight;
+	fn gr_reserve_gas(r: u32, ) -> Weight;
+	fn gr_unreserve_gas(r: u32, ) -> Weight;
+	fn gr_reservation_send(r: u32, ) -> Weight;
+	fn gr_system_reserve_gas(r: u32, ) -> Weight;
+	fn gr_metric_increment(r: u32, ) -> Weight;
+	fn gr_emit_event(r: u32, ) -> Weight;
+	fn gr_emit_event_per_kb(n: u32, ) -> Weight;
 	fn initial_cost() -> Weight;
 	fn allocation_cost() -> Weight;
 	fn grow_cost() -> Weight;
 	fn load_cost() -> Weight;
+	fn page_read_cost() -> Weight;
+	fn page_write_cost() -> Weight;
 	fn instr_i64const(r: u32, ) -> Weight;
 	fn instr_i64load(r: u32, ) -> Weight;
 	fn instr_i64store(r: u32, ) -> Weight;
@@ -140,6 +166,13 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
 			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn submit_code_metadata(r: u32, ) -> Weight {
+		(24_310_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 	fn submit_program(c: u32, s: u32, ) -> Weight {
 		(381_898_000 as Weight)
 			// Standard Error: 0
@@ -156,6 +189,12 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(10 as Weight))
 			.saturating_add(T::DbWeight::get().writes(8 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn send_message_without_value() -> Weight {
+		(101_412_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
 	fn send_reply(p: u32, ) -> Weight {
 		(132_192_000 as Weight)
 			// Standard Error: 0
@@ -163,6 +202,18 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(10 as Weight))
 			.saturating_add(T::DbWeight::get().writes(8 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn top_up_gas() -> Weight {
+		(48_519_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn pay_program_rent() -> Weight {
+		(52_174_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
 	fn initial_allocation(_q: u32, ) -> Weight {
 		(297_230_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(24 as Weight))
@@ -185,6 +236,11 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			// Standard Error: 57_913_000
 			.saturating_add((15_020_426_000 as Weight).saturating_mul(r as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn free(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((15_106_351_000 as Weight).saturating_mul(r as Weight))
+	}
 	fn gas(r: u32, ) -> Weight {
 		(2_558_419_000 as Weight)
 			// Standard Error: 201_000
@@ -277,6 +333,27 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(9 as Weight))
 			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_send_push_input(r: u32, ) -> Weight {
+		(3_247_162_000 as Weight)
+			.saturating_add((298_553_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_send_push_input_per_kb(n: u32, ) -> Weight {
+		(3_598_214_000 as Weight)
+			.saturating_add((31_407_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_send_cancel(r: u32, ) -> Weight {
+		(3_228_451_000 as Weight)
+			.saturating_add((305_204_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
 	fn gr_send_commit(r: u32, ) -> Weight {
 		(3_432_548_000 as Weight)
 			// Standard Error: 527_000
@@ -317,6 +394,16 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			// Standard Error: 312_000
 			.saturating_add((58_504_000 as Weight).saturating_mul(n as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reply_push_input(r: u32, ) -> Weight {
+		(3_341_806_000 as Weight)
+			.saturating_add((126_315_000 as Weight).saturating_mul(r as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reply_push_input_per_kb(n: u32, ) -> Weight {
+		(3_571_032_000 as Weight)
+			.saturating_add((29_116_000 as Weight).saturating_mul(n as Weight))
+	}
 	fn gr_reply_to(r: u32, ) -> Weight {
 		(3_499_809_000 as Weight)
 			// Standard Error: 504_000
@@ -338,6 +425,11 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(8 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_error(r: u32, ) -> Weight {
+		(3_044_367_000 as Weight)
+			.saturating_add((101_712_000 as Weight).saturating_mul(r as Weight))
+	}
 	fn gr_exit(r: u32, ) -> Weight {
 		(3_495_394_000 as Weight)
 			// Standard Error: 10_352_000
@@ -359,6 +451,18 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(8 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_wait_for(_r: u32, ) -> Weight {
+		(3_491_372_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_wait_up_to(_r: u32, ) -> Weight {
+		(3_493_804_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
 	fn gr_wake(r: u32, ) -> Weight {
 		(3_189_935_000 as Weight)
 			// Standard Error: 5_823_000
@@ -384,6 +488,55 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(9 as Weight))
 			.saturating_add(T::DbWeight::get().writes(5 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reserve_gas(r: u32, ) -> Weight {
+		(3_134_852_000 as Weight)
+			.saturating_add((161_234_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_unreserve_gas(r: u32, ) -> Weight {
+		(3_141_725_000 as Weight)
+			.saturating_add((312_508_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reservation_send(r: u32, ) -> Weight {
+		(3_152_371_000 as Weight)
+			.saturating_add((471_639_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_system_reserve_gas(r: u32, ) -> Weight {
+		(3_128_417_000 as Weight)
+			.saturating_add((124_906_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_metric_increment(r: u32, ) -> Weight {
+		(3_469_512_000 as Weight)
+			.saturating_add((112_384_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_emit_event(r: u32, ) -> Weight {
+		(3_412_846_000 as Weight)
+			.saturating_add((168_935_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_emit_event_per_kb(n: u32, ) -> Weight {
+		(3_527_114_000 as Weight)
+			.saturating_add((58_617_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
 	fn initial_cost() -> Weight {
 		100_000_000 as Weight
 	}
@@ -396,6 +549,12 @@ impl<T: frame_system::Config> WeightInfo for GearWeight<T> {
 	fn load_cost() -> Weight {
 		20_000_000 as Weight
 	}
+	fn page_read_cost() -> Weight {
+		10_000_000 as Weight
+	}
+	fn page_write_cost() -> Weight {
+		40_000_000 as Weight
+	}
 	fn instr_i64const(r: u32, ) -> Weight {
 		(8_821_000 as Weight)
 			// Standard Error: 0
@@ -657,6 +816,13 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn submit_code_metadata(r: u32, ) -> Weight {
+		(24_310_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
 	fn submit_program(c: u32, s: u32, ) -> Weight {
 		(381_898_000 as Weight)
 			// Standard Error: 0
@@ -673,6 +839,12 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn send_message_without_value() -> Weight {
+		(101_412_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
 	fn send_reply(p: u32, ) -> Weight {
 		(132_192_000 as Weight)
 			// Standard Error: 0
@@ -680,6 +852,18 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn top_up_gas() -> Weight {
+		(48_519_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn pay_program_rent() -> Weight {
+		(52_174_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
 	fn initial_allocation(_q: u32, ) -> Weight {
 		(297_230_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(24 as Weight))
@@ -702,6 +886,11 @@ impl WeightInfo for () {
 			// Standard Error: 57_913_000
 			.saturating_add((15_020_426_000 as Weight).saturating_mul(r as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn free(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((15_106_351_000 as Weight).saturating_mul(r as Weight))
+	}
 	fn gas(r: u32, ) -> Weight {
 		(2_558_419_000 as Weight)
 			// Standard Error: 201_000
@@ -794,6 +983,27 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_send_push_input(r: u32, ) -> Weight {
+		(3_247_162_000 as Weight)
+			.saturating_add((298_553_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_send_push_input_per_kb(n: u32, ) -> Weight {
+		(3_598_214_000 as Weight)
+			.saturating_add((31_407_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_send_cancel(r: u32, ) -> Weight {
+		(3_228_451_000 as Weight)
+			.saturating_add((305_204_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
 	fn gr_send_commit(r: u32, ) -> Weight {
 		(3_432_548_000 as Weight)
 			// Standard Error: 527_000
@@ -834,6 +1044,16 @@ impl WeightInfo for () {
 			// Standard Error: 312_000
 			.saturating_add((58_504_000 as Weight).saturating_mul(n as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reply_push_input(r: u32, ) -> Weight {
+		(3_341_806_000 as Weight)
+			.saturating_add((126_315_000 as Weight).saturating_mul(r as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reply_push_input_per_kb(n: u32, ) -> Weight {
+		(3_571_032_000 as Weight)
+			.saturating_add((29_116_000 as Weight).saturating_mul(n as Weight))
+	}
 	fn gr_reply_to(r: u32, ) -> Weight {
 		(3_499_809_000 as Weight)
 			// Standard Error: 504_000
@@ -855,6 +1075,11 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_error(r: u32, ) -> Weight {
+		(3_044_367_000 as Weight)
+			.saturating_add((101_712_000 as Weight).saturating_mul(r as Weight))
+	}
 	fn gr_exit(r: u32, ) -> Weight {
 		(3_495_394_000 as Weight)
 			// Standard Error: 10_352_000
@@ -876,6 +1101,18 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_wait_for(_r: u32, ) -> Weight {
+		(3_491_372_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_wait_up_to(_r: u32, ) -> Weight {
+		(3_493_804_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
 	fn gr_wake(r: u32, ) -> Weight {
 		(3_189_935_000 as Weight)
 			// Standard Error: 5_823_000
@@ -901,6 +1138,55 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
 	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reserve_gas(r: u32, ) -> Weight {
+		(3_134_852_000 as Weight)
+			.saturating_add((161_234_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_unreserve_gas(r: u32, ) -> Weight {
+		(3_141_725_000 as Weight)
+			.saturating_add((312_508_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_reservation_send(r: u32, ) -> Weight {
+		(3_152_371_000 as Weight)
+			.saturating_add((471_639_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_system_reserve_gas(r: u32, ) -> Weight {
+		(3_128_417_000 as Weight)
+			.saturating_add((124_906_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_metric_increment(r: u32, ) -> Weight {
+		(3_469_512_000 as Weight)
+			.saturating_add((112_384_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_emit_event(r: u32, ) -> Weight {
+		(3_412_846_000 as Weight)
+			.saturating_add((168_935_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Placeholder weight, not produced by the benchmark CLI.
+	fn gr_emit_event_per_kb(n: u32, ) -> Weight {
+		(3_527_114_000 as Weight)
+			.saturating_add((58_617_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
 	fn initial_cost() -> Weight {
 		100_000_000 as Weight
 	}
@@ -913,6 +1199,12 @@ impl WeightInfo for () {
 	fn load_cost() -> Weight {
 		20_000_000 as Weight
 	}
+	fn page_read_cost() -> Weight {
+		10_000_000 as Weight
+	}
+	fn page_write_cost() -> Weight {
+		40_000_000 as Weight
+	}
 	fn instr_i64const(r: u32, ) -> Weight {
 		(8_821_000 as Weight)
 			// Standard Error: 0
@@ -19,7 +19,10 @@
 use crate as pallet_gear_payment;
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::{ConstU8, Contains, Currency, FindAuthor, OnFinalize, OnInitialize, OnUnbalanced},
+    traits::{
+        ConstU8, Contains, Currency, FindAuthor, OnFinalize, OnInitialize, OnUnbalanced,
+        ReservableCurrency,
+    },
     weights::IdentityFee,
 };
 use frame_system as system;
@@ -27,7 +30,7 @@ use pallet_transaction_payment::CurrencyAdapter;
 use primitive_types::H256;
 use sp_runtime::{
     testing::{Header, TestXt},
-    traits::{BlakeTwo256, ConstU64, IdentityLookup},
+    traits::{BlakeTwo256, ConstBool, ConstU32, ConstU64, IdentityLookup},
 };
 use sp_std::{
     convert::{TryFrom, TryInto},
@@ -49,7 +52,7 @@ construct_runtime!(
     {
         System: system::{Pallet, Call, Config, Storage, Event<T>},
         Gear: pallet_gear::{Pallet, Call, Storage, Event<T>},
-        GearGas: pallet_gear_gas::{Pallet},
+        GearGas: pallet_gear_gas::{Pallet, Event<T>},
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
         Authorship: pallet_authorship::{Pallet, Storage},
         TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
@@ -158,6 +161,8 @@ impl common::GasPrice for GasConverter {
 parameter_types! {
     pub const BlockGasLimit: u64 = 500_000;
     pub const OutgoingLimit: u32 = 1024;
+    pub const OutgoingBytesLimit: u32 = 64 * 1024 * 1024;
+    pub const ProgramCandidatesLimit: u32 = 128;
     pub GearSchedule: pallet_gear::Schedule<Test> = <pallet_gear::Schedule<Test>>::default();
 }
 
@@ -168,13 +173,26 @@ impl pallet_gear::Config for Test {
     type WeightInfo = ();
     type Schedule = GearSchedule;
     type OutgoingLimit = OutgoingLimit;
+    type OutgoingBytesLimit = OutgoingBytesLimit;
+    type ProgramCandidatesLimit = ProgramCandidatesLimit;
     type DebugInfo = ();
     type CodeStorage = GearProgram;
     type MailboxThreshold = ConstU64<0>;
+    type GasMinimums = ();
+    type GasEstimationDepth = ConstU32<16>;
+    type ProgramRentFreePeriod = ConstU64<1_000_000>;
+    type ExistentialDepositFreePrograms = ConstBool<false>;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
     type Scheduler = GearScheduler;
+    type QueueRunner = pallet_gear::ProcessUntilAllowance;
+    type ForbiddenDestinations = frame_support::traits::Nothing;
+    type ReplyChainDepthLimit = ConstU32<1024>;
+    type QueueProcessingOrigin = system::EnsureRoot<u64>;
+    type FallbackAuthor = ();
+    type DustBeneficiary = ();
+    type ProgramBanOrigin = system::EnsureRoot<u64>;
 }
 
 impl pallet_gear_program::Config for Test {
@@ -182,21 +200,34 @@ impl pallet_gear_program::Config for Test {
     type WeightInfo = ();
     type Currency = Balances;
     type Messenger = GearMessenger;
+    type ProgramCreationQuota = ();
+    type ResumeSessionDeposit = ();
+    type ResumeSessionDuration = ();
+    type CodeDeposit = ();
+    type CodeDepositRefund = ();
+    type OnProgramPause = pallet_gear::OnProgramPause<Test>;
+    type OnProgramResume = pallet_gear::OnProgramResume<Test>;
 }
 
 impl pallet_gear_gas::Config for Test {
+    type Event = Event;
     type BlockGasLimit = BlockGasLimit;
+    type EventThreshold = ConstU64<{ u64::MAX }>;
 }
 
 impl pallet_gear_scheduler::Config for Test {
     type BlockLimiter = GearGas;
     type ReserveThreshold = ConstU64<1>;
     type WaitlistCost = ConstU64<100>;
+    type MailboxCost = ConstU64<100>;
+    type DispatchStashCost = ConstU64<100>;
+    type ProgramCost = ConstU64<1_000>;
 }
 
 impl pallet_gear_messenger::Config for Test {
-    type Currency = Balances;
     type BlockLimiter = GearGas;
+    type WaitlistCapacity = ConstU32<1024>;
+    type WeightInfo = ();
 }
 
 type NegativeImbalance = <Balances as Currency<u64>>::NegativeImbalance;
@@ -241,14 +272,28 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
         .build_storage::<Test>()
         .unwrap();
 
+    let bank_address = pallet_gear::GearBank::<Test>::account();
+    let existential_deposit = ExistentialDeposit::get() as u128;
+
     pallet_balances::GenesisConfig::<Test> {
-        balances: vec![(ALICE, 1_000_000_000_u128), (BLOCK_AUTHOR, 1_000_u128)],
+        balances: vec![
+            (ALICE, 1_000_000_000_u128),
+            (BLOCK_AUTHOR, 1_000_u128),
+            (bank_address, existential_deposit),
+        ],
     }
     .assimilate_storage(&mut t)
     .unwrap();
 
     let mut ext = sp_io::TestExternalities::new(t);
-    ext.execute_with(|| System::set_block_number(1));
+    ext.execute_with(|| {
+        System::set_block_number(1);
+
+        // Bank is provided at genesis, so its existential deposit
+        // isn't paid by the users sending messages in tests.
+        Balances::reserve(&bank_address, existential_deposit)
+            .expect("Bank is endowed with existential deposit");
+    });
     ext
 }
 
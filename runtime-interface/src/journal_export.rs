@@ -0,0 +1,55 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Node-side sink for journals of processed blocks.
+//!
+//! Each journal is written as little-endian `u32` length followed by
+//! the scale-encoded journal itself.
+
+use once_cell::sync::OnceCell;
+use std::{io::Write, sync::Mutex};
+
+static SINK: OnceCell<Mutex<Box<dyn Write + Send>>> = OnceCell::new();
+
+/// Sets the sink for journals export.
+///
+/// Returns `false` if the sink is already set.
+pub fn set_sink(sink: impl Write + Send + 'static) -> bool {
+    SINK.set(Mutex::new(Box::new(sink))).is_ok()
+}
+
+pub(crate) fn is_enabled() -> bool {
+    SINK.get().is_some()
+}
+
+pub(crate) fn export(journal: &[u8]) {
+    let sink = match SINK.get() {
+        Some(sink) => sink,
+        None => return,
+    };
+
+    let mut sink = sink.lock().unwrap_or_else(|e| e.into_inner());
+
+    let res = sink
+        .write_all(&(journal.len() as u32).to_le_bytes())
+        .and_then(|_| sink.write_all(journal))
+        .and_then(|_| sink.flush());
+
+    if let Err(e) = res {
+        log::error!("Failed to export journal: {}", e);
+    }
+}
@@ -40,6 +40,9 @@ use gear_core::memory::PageNumber;
 #[cfg(feature = "std")]
 use gear_lazy_pages as lazy_pages;
 
+#[cfg(feature = "std")]
+pub mod journal_export;
+
 pub use sp_std::{result::Result, vec::Vec};
 
 #[cfg(test)]
@@ -233,6 +236,16 @@ pub trait GearRI {
         lazy_pages::reset_context()
     }
 
+    /// Returns whether the node exports journals of processed blocks.
+    fn is_journal_export_enabled() -> bool {
+        journal_export::is_enabled()
+    }
+
+    /// Writes scale-encoded journal of the block to the node's sink.
+    fn export_journal(journal: &[u8]) {
+        journal_export::export(journal)
+    }
+
     #[deprecated]
     fn set_wasm_mem_begin_addr(addr: u64) {
         lazy_pages::set_wasm_mem_begin_addr(addr);
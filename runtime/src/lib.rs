@@ -50,7 +50,7 @@ pub use pallet_gear::manager::{ExtManager, HandleKind};
 pub use frame_support::{
     construct_runtime, parameter_types,
     traits::{
-        ConstU128, ConstU32, ConstU64, ConstU8, Contains, Currency, FindAuthor,
+        ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, Contains, Currency, FindAuthor,
         KeyOwnerProofSystem, OnUnbalanced, Randomness, StorageInfo,
     },
     weights::{
@@ -330,9 +330,17 @@ impl pallet_gear_program::Config for Runtime {
     type WeightInfo = pallet_gear_program::weights::GearProgramWeight<Runtime>;
     type Currency = Balances;
     type Messenger = GearMessenger;
+    type ProgramCreationQuota = ProgramCreationQuota;
+    type ResumeSessionDeposit = ConstU128<1_000_000>;
+    type ResumeSessionDuration = ConstU32<DAYS>;
+    type CodeDeposit = ();
+    type CodeDepositRefund = ();
+    type OnProgramPause = pallet_gear::OnProgramPause<Runtime>;
+    type OnProgramResume = pallet_gear::OnProgramResume<Runtime>;
 }
 
 parameter_types! {
+    pub const ProgramCreationQuota: Option<u32> = Some(1024);
     pub const GasLimitMaxPercentage: Percent = Percent::from_percent(75);
     pub BlockGasLimit: u64 = GasLimitMaxPercentage::get() * BlockWeights::get().max_block;
     pub const WaitListTraversalInterval: u32 = 10;
@@ -348,13 +356,26 @@ impl pallet_gear::Config for Runtime {
     type WeightInfo = pallet_gear::weights::GearWeight<Runtime>;
     type Schedule = Schedule;
     type OutgoingLimit = ConstU32<1024>;
+    type OutgoingBytesLimit = ConstU32<{ 64 * 1024 * 1024 }>;
+    type ProgramCandidatesLimit = ConstU32<128>;
     type DebugInfo = DebugInfo;
     type CodeStorage = GearProgram;
     type MailboxThreshold = ConstU64<0>;
+    type GasMinimums = ();
+    type GasEstimationDepth = ConstU32<16>;
+    type ProgramRentFreePeriod = ConstU32<{ 30 * DAYS }>;
+    type ExistentialDepositFreePrograms = ConstBool<false>;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
     type Scheduler = GearScheduler;
+    type QueueRunner = pallet_gear::ProcessRoundRobin<ConstU32<64>>;
+    type ForbiddenDestinations = ForbiddenDestinations;
+    type ReplyChainDepthLimit = ConstU32<1024>;
+    type QueueProcessingOrigin = frame_system::EnsureRoot<AccountId>;
+    type FallbackAuthor = ();
+    type DustBeneficiary = ();
+    type ProgramBanOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 #[cfg(feature = "debug-mode")]
@@ -369,15 +390,21 @@ impl pallet_gear_scheduler::Config for Runtime {
     type BlockLimiter = GearGas;
     type ReserveThreshold = ConstU32<1>;
     type WaitlistCost = ConstU64<100>;
+    type MailboxCost = ConstU64<100>;
+    type DispatchStashCost = ConstU64<100>;
+    type ProgramCost = ConstU64<1_000>;
 }
 
 impl pallet_gear_gas::Config for Runtime {
+    type Event = Event;
     type BlockGasLimit = BlockGasLimit;
+    type EventThreshold = ConstU64<100_000_000_000>;
 }
 
 impl pallet_gear_messenger::Config for Runtime {
-    type Currency = Balances;
     type BlockLimiter = GearGas;
+    type WaitlistCapacity = ConstU32<2048>;
+    type WeightInfo = pallet_gear_messenger::weights::GearMessengerWeight<Runtime>;
 }
 
 pub struct ExtraFeeFilter;
@@ -393,6 +420,15 @@ impl Contains<Call> for ExtraFeeFilter {
     }
 }
 
+pub struct ForbiddenDestinations;
+impl Contains<gear_core::ids::ProgramId> for ForbiddenDestinations {
+    fn contains(id: &gear_core::ids::ProgramId) -> bool {
+        // Zero address is the conventional burn address:
+        // value sent there is lost for everyone.
+        *id == Default::default()
+    }
+}
+
 type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
 
 pub struct DealWithFees;
@@ -525,6 +561,7 @@ mod benches {
         [pallet_balances, Balances]
         [pallet_timestamp, Timestamp]
         [pallet_gear, Gear]
+        [pallet_gear_messenger, GearMessenger]
     );
 }
 
@@ -671,6 +708,64 @@ impl_runtime_apis! {
         ) -> Result<pallet_gear::GasInfo, Vec<u8>> {
             Gear::calculate_gas_info(account_id, kind, payload, value, allow_other_panics, initial_gas)
         }
+
+        fn block_run_stats() -> pallet_gear::BlockRunStats {
+            Gear::block_run_stats()
+        }
+
+        fn read_state(program_id: H256, query: Vec<u8>) -> Result<pallet_gear::StateInfo, Vec<u8>> {
+            Gear::read_state(program_id, query)
+        }
+
+        fn read_meta_state(program_id: H256, meta_wasm: Vec<u8>, payload: Vec<u8>) -> Result<Vec<u8>, Vec<u8>> {
+            Gear::read_meta_state(program_id, meta_wasm, payload)
+        }
+
+        fn calculate_program_id(code_id: H256, salt: Vec<u8>) -> H256 {
+            gear_common::Origin::into_origin(Gear::calculate_program_id(code_id, &salt))
+        }
+
+        fn code_hash_exists(code_id: H256) -> bool {
+            Gear::code_hash_exists(code_id)
+        }
+
+        fn original_code(code_id: H256) -> Option<Vec<u8>> {
+            Gear::original_code(code_id)
+        }
+
+        fn next_user_message_id(account_id: H256) -> H256 {
+            gear_common::Origin::into_origin(Gear::next_user_message_id(account_id))
+        }
+
+        fn queue_len() -> u32 {
+            Gear::queue_len()
+        }
+
+        fn waitlist_len(program_id: H256) -> u32 {
+            Gear::waitlist_len(gear_common::Origin::from_origin(program_id)) as u32
+        }
+
+        fn mailbox_len(account_id: H256) -> u32 {
+            Gear::mailbox_len(&gear_common::Origin::from_origin(account_id)) as u32
+        }
+
+        fn program_state_keys(program_id: H256, from_page: u32, to_page: u32) -> Vec<Vec<u8>> {
+            Gear::program_state_keys(program_id, from_page..to_page)
+        }
+
+        fn hold_duration(gas: u64, kind: pallet_gear::HoldKind) -> u32 {
+            Gear::hold_duration(gas, kind)
+        }
+
+        fn hold_cost(duration: u32, kind: pallet_gear::HoldKind) -> u64 {
+            Gear::hold_cost(duration, kind)
+        }
+    }
+
+    impl pallet_gear_rpc_runtime_api::GearScheduleApi<Block, pallet_gear::ScheduleInfo<Runtime>> for Runtime {
+        fn schedule_info() -> pallet_gear::ScheduleInfo<Runtime> {
+            Gear::schedule_info()
+        }
     }
 
     #[cfg(feature = "runtime-benchmarks")]
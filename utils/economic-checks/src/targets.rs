@@ -554,6 +554,129 @@ mod tests {
         });
     }
 
+    #[test]
+    fn wait_and_wake_keep_gas_balance() {
+        use codec::Decode;
+        use common::storage::IterableMap;
+        use demo_wait_wake::{Request, WASM_BINARY as WAIT_WAKE_WASM_BINARY};
+
+        init_logger();
+        let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+        new_test_ext(
+            vec![(alice.clone(), 1_000_000_000_000_000_u128)],
+            vec![authority_keys_from_seed("Val")],
+            alice.clone(),
+        )
+        .execute_with(|| {
+            let wait_wake_id = generate_program_id(WAIT_WAKE_WASM_BINARY, b"salt");
+
+            assert_ok!(Gear::submit_program(
+                Origin::signed(alice.clone()),
+                WAIT_WAKE_WASM_BINARY.to_vec(),
+                b"salt".to_vec(),
+                vec![],
+                2_500_000_000,
+                0,
+            ));
+
+            run_to_block(2, None);
+
+            assert_ok!(Gear::send_message(
+                Origin::signed(alice.clone()),
+                wait_wake_id,
+                Request::EchoWait(42).encode(),
+                10_000_000_000,
+                0,
+            ));
+
+            run_to_block(3, None);
+
+            // Gas balance adds up: all gas is held by waiting messages only
+            assert!(total_gas_in_wait_list() > 0);
+            assert_eq!(
+                GasHandlerOf::<Runtime>::total_supply(),
+                total_gas_in_wait_list()
+            );
+
+            let (waited, _) = WaitlistOf::<Runtime>::iter()
+                .next()
+                .expect("Message is waiting");
+            let waited_id =
+                Decode::decode(&mut waited.id().encode().as_ref()).expect("Ids are compatible");
+
+            assert_ok!(Gear::send_message(
+                Origin::signed(alice.clone()),
+                wait_wake_id,
+                Request::Wake(waited_id).encode(),
+                10_000_000_000,
+                0,
+            ));
+
+            run_to_block(5, None);
+
+            // Woken message is executed, so no gas is held anymore
+            assert_eq!(total_gas_in_wait_list(), 0);
+            assert_eq!(GasHandlerOf::<Runtime>::total_supply(), 0);
+        });
+    }
+
+    #[test]
+    fn gas_reservation_keeps_gas_balance() {
+        use demo_reserve_gas::{Request, WASM_BINARY as RESERVE_GAS_WASM_BINARY};
+
+        init_logger();
+        let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+        new_test_ext(
+            vec![(alice.clone(), 1_000_000_000_000_000_u128)],
+            vec![authority_keys_from_seed("Val")],
+            alice.clone(),
+        )
+        .execute_with(|| {
+            let reserve_gas_id = generate_program_id(RESERVE_GAS_WASM_BINARY, b"salt");
+
+            assert_ok!(Gear::submit_program(
+                Origin::signed(alice.clone()),
+                RESERVE_GAS_WASM_BINARY.to_vec(),
+                b"salt".to_vec(),
+                vec![],
+                2_500_000_000,
+                0,
+            ));
+
+            run_to_block(2, None);
+
+            assert_ok!(Gear::send_message(
+                Origin::signed(alice.clone()),
+                reserve_gas_id,
+                Request::Reserve {
+                    amount: 1_000_000_000,
+                    duration: 100,
+                }
+                .encode(),
+                10_000_000_000,
+                0,
+            ));
+
+            run_to_block(3, None);
+
+            // Gas balance adds up: all gas is held by the reservation only
+            assert_eq!(GasHandlerOf::<Runtime>::total_supply(), 1_000_000_000);
+
+            assert_ok!(Gear::send_message(
+                Origin::signed(alice.clone()),
+                reserve_gas_id,
+                Request::Unreserve.encode(),
+                10_000_000_000,
+                0,
+            ));
+
+            run_to_block(4, None);
+
+            // Unreserved gas is returned along with the message's one
+            assert_eq!(GasHandlerOf::<Runtime>::total_supply(), 0);
+        });
+    }
+
     #[test]
     fn queue_filled_up_ok() {
         let seed = [0_u8; 32];
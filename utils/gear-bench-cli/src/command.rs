@@ -0,0 +1,185 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    util::{new_bench_ext, process_block, sender, storage_size, BlockStats},
+    GearBenchCmd,
+};
+use codec::Encode;
+use demo_btree::{Request, WASM_BINARY as BTREE_WASM_BINARY};
+use demo_mul_by_const::WASM_BINARY as MUL_BY_CONST_WASM_BINARY;
+use gear_core::ids::{CodeId, ProgramId};
+use gear_runtime::{Origin, Runtime};
+use pallet_gear::Pallet as GearPallet;
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_service::Configuration;
+use std::time::{Duration, Instant};
+
+/// Gas limit of each synthetic extrinsic.
+const GAS_LIMIT: u64 = 10_000_000_000;
+
+/// Demo program uploaded and messaged by the benchmark.
+#[derive(Debug, Clone, Copy)]
+enum Demo {
+    MulByConst,
+    BTree,
+}
+
+impl Demo {
+    fn code(self) -> &'static [u8] {
+        match self {
+            Demo::MulByConst => MUL_BY_CONST_WASM_BINARY,
+            Demo::BTree => BTREE_WASM_BINARY,
+        }
+    }
+
+    fn init_payload(self) -> Vec<u8> {
+        match self {
+            Demo::MulByConst => 3u64.encode(),
+            Demo::BTree => Vec::new(),
+        }
+    }
+
+    fn handle_payload(self, nonce: u32) -> Vec<u8> {
+        match self {
+            Demo::MulByConst => (nonce as u64).encode(),
+            // Distinct keys make program memory grow along the benchmark.
+            Demo::BTree => Request::Insert(nonce, nonce).encode(),
+        }
+    }
+}
+
+/// Synthetic extrinsics source.
+#[derive(Default)]
+struct Workload {
+    programs: Vec<(ProgramId, Demo)>,
+    nonce: u32,
+}
+
+impl Workload {
+    fn upload(&mut self) -> sc_cli::Result<()> {
+        let demo = if self.programs.len() % 2 == 0 {
+            Demo::MulByConst
+        } else {
+            Demo::BTree
+        };
+        let salt = (self.programs.len() as u32).to_le_bytes().to_vec();
+
+        GearPallet::<Runtime>::submit_program(
+            Origin::from(Some(sender())),
+            demo.code().to_vec(),
+            salt.clone(),
+            demo.init_payload(),
+            GAS_LIMIT,
+            0,
+        )
+        .map_err(|e| format!("Submit program error: {:?}", e))?;
+
+        let id = ProgramId::generate(CodeId::generate(demo.code()), &salt);
+        self.programs.push((id, demo));
+
+        Ok(())
+    }
+
+    fn send(&mut self) -> sc_cli::Result<()> {
+        let (id, demo) = self.programs[self.nonce as usize % self.programs.len()];
+
+        GearPallet::<Runtime>::send_message(
+            Origin::from(Some(sender())),
+            id,
+            demo.handle_payload(self.nonce),
+            GAS_LIMIT,
+            0,
+        )
+        .map_err(|e| format!("Send message error: {:?}", e))?;
+
+        self.nonce = self.nonce.wrapping_add(1);
+
+        Ok(())
+    }
+}
+
+impl GearBenchCmd {
+    /// Fills blocks of the temporary chain with gear extrinsics
+    /// and reports throughput of their processing.
+    pub fn run(&self, _config: Configuration) -> sc_cli::Result<()> {
+        if self.programs == 0 {
+            return Err("At least one program is required".into());
+        }
+
+        new_bench_ext().execute_with(|| {
+            let mut workload = Workload::default();
+
+            // Programs are initialized before measurements.
+            for _ in 0..self.programs {
+                workload.upload()?;
+            }
+            process_block();
+
+            let initial_storage = storage_size();
+            let mut total = BlockStats::default();
+            let mut elapsed = Duration::default();
+
+            for _ in 0..self.blocks {
+                let now = Instant::now();
+
+                for _ in 0..self.uploads {
+                    workload.upload()?;
+                }
+                for _ in 0..self.messages {
+                    workload.send()?;
+                }
+                let stats = process_block();
+
+                let block_elapsed = now.elapsed();
+                println!(
+                    "Block: {} messages, {} gas, {:?}",
+                    stats.messages, stats.gas, block_elapsed
+                );
+
+                total.messages += stats.messages;
+                total.gas += stats.gas;
+                elapsed += block_elapsed;
+            }
+
+            let secs = elapsed.as_secs_f64();
+            let storage_growth = storage_size().saturating_sub(initial_storage);
+
+            println!("Total blocks: {}, time: {:?}", self.blocks, elapsed);
+            println!(
+                "Messages: {} ({:.2}/sec)",
+                total.messages,
+                total.messages as f64 / secs
+            );
+            println!("Gas: {} ({:.2}/sec)", total.gas, total.gas as f64 / secs);
+            println!(
+                "Storage growth: {} bytes ({} bytes/block)",
+                storage_growth,
+                storage_growth / u64::from(self.blocks.max(1))
+            );
+
+            Ok(())
+        })
+    }
+}
+
+impl CliConfiguration for GearBenchCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+}
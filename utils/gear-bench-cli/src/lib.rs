@@ -0,0 +1,44 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+mod command;
+mod util;
+
+/// The `gear-bench` command used to measure gear throughput.
+#[derive(Debug, clap::Parser)]
+pub struct GearBenchCmd {
+    /// Number of blocks to fill with extrinsics.
+    #[clap(long, value_parser, default_value = "10")]
+    pub blocks: u32,
+
+    /// Number of programs uploaded before the benchmark starts.
+    #[clap(long, value_parser, default_value = "10")]
+    pub programs: u32,
+
+    /// Number of programs uploaded within each block.
+    #[clap(long, value_parser, default_value = "1")]
+    pub uploads: u32,
+
+    /// Number of messages sent to the programs within each block.
+    #[clap(long, value_parser, default_value = "100")]
+    pub messages: u32,
+
+    #[allow(missing_docs)]
+    #[clap(flatten)]
+    pub shared_params: sc_cli::SharedParams,
+}
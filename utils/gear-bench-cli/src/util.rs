@@ -0,0 +1,99 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use frame_support::traits::{OnFinalize, OnIdle, OnInitialize};
+use frame_system as system;
+use gear_common::Origin;
+use gear_runtime::{Gear, GearGas, GearMessenger, Runtime, System};
+use pallet_gear::BlockGasLimitOf;
+use sp_runtime::{app_crypto::UncheckedFrom, AccountId32};
+
+/// Statistics of the single processed block.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BlockStats {
+    /// Amount of messages dispatched from the queue.
+    pub messages: u64,
+    /// Gas burned while processing the queue.
+    pub gas: u64,
+}
+
+/// Account signing all the synthetic extrinsics.
+pub fn sender() -> AccountId32 {
+    AccountId32::unchecked_from(1000001.into_origin())
+}
+
+// Build genesis storage of the temporary chain.
+pub fn new_bench_ext() -> sp_io::TestExternalities {
+    let mut t = system::GenesisConfig::default()
+        .build_storage::<Runtime>()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<Runtime> {
+        balances: vec![(sender(), u128::MAX / 2)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+/// Processes message queue of the current block, finalizes it
+/// and initializes the next one.
+pub fn process_block() -> BlockStats {
+    let bn = System::block_number();
+
+    let gas = Gear::on_idle(bn, BlockGasLimitOf::<Runtime>::get());
+    let messages = System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            gear_runtime::Event::Gear(pallet_gear::Event::MessagesDispatched { total, .. }) => {
+                Some(total as u64)
+            }
+            _ => None,
+        })
+        .sum();
+
+    Gear::on_finalize(bn);
+    System::on_finalize(bn);
+    System::reset_events();
+
+    System::set_block_number(bn + 1);
+    System::on_initialize(bn + 1);
+    GearGas::on_initialize(bn + 1);
+    GearMessenger::on_initialize(bn + 1);
+    Gear::on_initialize(bn + 1);
+
+    BlockStats { messages, gas }
+}
+
+/// Returns total size of keys and values in the storage.
+pub fn storage_size() -> u64 {
+    let mut size = 0;
+    let mut key = Vec::new();
+
+    while let Some(next) = sp_io::storage::next_key(&key) {
+        size += next.len() as u64;
+        size += sp_io::storage::get(&next).map_or(0, |value| value.len() as u64);
+
+        key = next;
+    }
+
+    size
+}
@@ -47,6 +47,7 @@ use sp_core::H256;
 use sp_runtime::{app_crypto::UncheckedFrom, AccountId32};
 use std::{
     collections::BTreeMap,
+    convert::TryInto,
     sync::atomic::{AtomicUsize, Ordering},
     time::Instant,
 };
@@ -241,6 +242,7 @@ fn run_fixture(test: &'_ sample::Test, fixture: &sample::Fixture) -> ColoredStri
         let program = gear_common::ActiveProgram {
             allocations: Default::default(),
             pages_with_data: Default::default(),
+            gas_reservation_map: Default::default(),
             code_hash: H256::default(),
             state: gear_common::ProgramState::Initialized,
         };
@@ -312,7 +314,7 @@ fn run_fixture(test: &'_ sample::Test, fixture: &sample::Fixture) -> ColoredStri
                 id,
                 ProgramId::from_origin(source),
                 dest,
-                payload,
+                payload.try_into().expect("Payload size limit exceeded"),
                 value,
                 None,
             );
@@ -388,7 +390,10 @@ fn run_fixture(test: &'_ sample::Test, fixture: &sample::Fixture) -> ColoredStri
                         msg.id(),
                         msg.source(),
                         ProgramId::from(id.as_bytes()),
-                        msg.payload().to_vec(),
+                        msg.payload()
+                            .to_vec()
+                            .try_into()
+                            .expect("Payload of stored message is already bounded"),
                         msg.value(),
                         msg.reply(),
                     );
@@ -440,6 +445,8 @@ fn run_fixture(test: &'_ sample::Test, fixture: &sample::Fixture) -> ColoredStri
                             info.persistent_pages.clone(),
                         )
                         .unwrap(),
+                        gas_reservation_map: info.gas_reservation_map.clone(),
+                        waitlist_full: false,
                     })
                 } else {
                     None
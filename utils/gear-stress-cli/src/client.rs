@@ -0,0 +1,123 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Minimal client signing and submitting extrinsics to the node.
+
+use codec::Encode;
+use gear_runtime::{
+    AccountId, Address, Call, Index, Signature, SignedExtra, SignedPayload, UncheckedExtrinsic,
+    VERSION,
+};
+use jsonrpsee::{
+    core::client::ClientT,
+    rpc_params,
+    ws_client::{WsClient, WsClientBuilder},
+};
+use sp_core::{crypto::Ss58Codec, sr25519, Bytes, Pair, H256};
+use sp_runtime::generic::Era;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Client of the node, signing extrinsics by the single account.
+pub struct Client {
+    rpc: WsClient,
+    signer: sr25519::Pair,
+    account: AccountId,
+    genesis: H256,
+    nonce: Index,
+}
+
+impl Client {
+    /// Connects to the node at `url` and signs extrinsics by the `suri` account.
+    pub async fn new(url: &str, suri: &str) -> Result<Self> {
+        let signer = sr25519::Pair::from_string(suri, None)
+            .map_err(|e| format!("Invalid secret uri: {:?}", e))?;
+        let account = AccountId::from(signer.public());
+
+        let rpc = WsClientBuilder::default().build(url).await?;
+        let genesis: H256 = rpc.request("chain_getBlockHash", rpc_params![0u32]).await?;
+        let nonce: Index = rpc
+            .request(
+                "system_accountNextIndex",
+                rpc_params![account.to_ss58check()],
+            )
+            .await?;
+
+        Ok(Self {
+            rpc,
+            signer,
+            account,
+            genesis,
+            nonce,
+        })
+    }
+
+    /// Returns account, which signs the extrinsics.
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
+    /// Signs the call with the next nonce and submits it to the transaction pool.
+    ///
+    /// Extrinsics are expected to be valid, so nonces are tracked locally
+    /// without waiting for the inclusion.
+    pub async fn submit(&mut self, call: Call) -> Result<H256> {
+        let extra: SignedExtra = (
+            frame_system::CheckNonZeroSender::new(),
+            frame_system::CheckSpecVersion::new(),
+            frame_system::CheckTxVersion::new(),
+            frame_system::CheckGenesis::new(),
+            frame_system::CheckEra::from(Era::Immortal),
+            frame_system::CheckNonce::from(self.nonce),
+            frame_system::CheckWeight::new(),
+            pallet_transaction_payment::ChargeTransactionPayment::from(0),
+        );
+        let additional = (
+            (),
+            VERSION.spec_version,
+            VERSION.transaction_version,
+            self.genesis,
+            self.genesis,
+            (),
+            (),
+            (),
+        );
+
+        let payload = SignedPayload::from_raw(call, extra, additional);
+        let signature = payload.using_encoded(|payload| self.signer.sign(payload));
+        let (call, extra, _) = payload.deconstruct();
+
+        let extrinsic = UncheckedExtrinsic::new_signed(
+            call,
+            Address::Id(self.account.clone()),
+            Signature::from(signature),
+            extra,
+        );
+
+        let hash = self
+            .rpc
+            .request(
+                "author_submitExtrinsic",
+                rpc_params![Bytes(extrinsic.encode())],
+            )
+            .await?;
+        self.nonce += 1;
+
+        Ok(hash)
+    }
+}
@@ -0,0 +1,203 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Constructs worst-case chain states on a dev node via extrinsics.
+//!
+//! States are used to validate weight functions and bounds of the storages
+//! under real pressure, so the node is expected to be a disposable dev one.
+//! The tool is built along with the runtime and signs extrinsics with its
+//! version, so both must be built from the same sources.
+
+use clap::{Parser, Subcommand};
+use client::{Client, Result};
+use codec::Encode;
+use demo_wait_wake::{Request, WASM_BINARY as WAIT_WAKE_WASM_BINARY};
+use demo_waiting_proxy::WASM_BINARY as WAITING_PROXY_WASM_BINARY;
+use gear_core::ids::{CodeId, ProgramId};
+use gear_runtime::Call;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod client;
+
+#[derive(Parser)]
+struct Cli {
+    /// Websocket endpoint of the node.
+    #[clap(long, value_parser, default_value = "ws://127.0.0.1:9944")]
+    url: String,
+    /// Secret uri of the account signing the extrinsics.
+    #[clap(long, value_parser, default_value = "//Alice")]
+    suri: String,
+    /// Gas limit of each program upload and message.
+    #[clap(long, value_parser, default_value = "10000000000")]
+    gas_limit: u64,
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Fills the waitlist of a single program with waiting messages.
+    Waitlist {
+        #[clap(long, value_parser, default_value = "1000")]
+        messages: u32,
+    },
+    /// Leaves messages waiting for replies, which never come, so their
+    /// contexts stay stored along with the sent messages in the mailbox.
+    ContextStores {
+        #[clap(long, value_parser, default_value = "1000")]
+        messages: u32,
+    },
+    /// Builds gas trees as deep as the chain of waiting proxies,
+    /// each of them holding the gas of its waiting message.
+    GasTree {
+        #[clap(long, value_parser, default_value = "100")]
+        depth: u32,
+        #[clap(long, value_parser, default_value = "10")]
+        messages: u32,
+    },
+}
+
+/// Uploads programs and sends messages to them.
+struct Stress {
+    client: Client,
+    gas_limit: u64,
+    // Programs of different runs on the same node mustn't collide.
+    salt_prefix: Vec<u8>,
+    uploaded: u32,
+}
+
+impl Stress {
+    fn salt(&mut self) -> Vec<u8> {
+        let mut salt = self.salt_prefix.clone();
+        salt.extend(self.uploaded.to_le_bytes());
+        self.uploaded += 1;
+
+        salt
+    }
+
+    async fn upload(&mut self, code: &[u8], init_payload: Vec<u8>) -> Result<ProgramId> {
+        let salt = self.salt();
+        let id = ProgramId::generate(CodeId::generate(code), &salt);
+
+        self.client
+            .submit(Call::Gear(pallet_gear::Call::submit_program {
+                code: code.to_vec(),
+                salt,
+                init_payload,
+                gas_limit: self.gas_limit,
+                value: 0,
+            }))
+            .await?;
+
+        Ok(id)
+    }
+
+    async fn send(&mut self, destination: ProgramId, payload: Vec<u8>) -> Result<()> {
+        self.client
+            .submit(Call::Gear(pallet_gear::Call::send_message {
+                destination,
+                payload,
+                gas_limit: self.gas_limit,
+                value: 0,
+            }))
+            .await
+            .map(|_| ())
+    }
+
+    /// Uploads chain of waiting proxies of the `depth` length, the last of
+    /// which sends to the signer. Returns the head of the chain.
+    async fn upload_proxy_chain(&mut self, depth: u32) -> Result<ProgramId> {
+        let mut destination: [u8; 32] = self.client.account().clone().into();
+
+        // Proxies are initialized with their destinations, so are uploaded from the tail.
+        for _ in 0..depth {
+            let id = self
+                .upload(WAITING_PROXY_WASM_BINARY, destination.encode())
+                .await?;
+            destination = id.into();
+        }
+
+        Ok(destination.into())
+    }
+
+    async fn run(&mut self, command: Commands) -> Result<()> {
+        match command {
+            Commands::Waitlist { messages } => {
+                let program = self.upload(WAIT_WAKE_WASM_BINARY, vec![]).await?;
+
+                for n in 0..messages {
+                    self.send(program, Request::EchoWait(n).encode()).await?;
+                }
+
+                println!("Waitlist of {:?}: {} messages", program, messages);
+            }
+            Commands::ContextStores { messages } => {
+                let program = self.upload_proxy_chain(1).await?;
+
+                for n in 0..messages {
+                    self.send(program, n.encode()).await?;
+                }
+
+                println!("Context stores of {:?}: {} messages", program, messages);
+            }
+            Commands::GasTree { depth, messages } => {
+                if depth == 0 {
+                    return Err("Depth must be positive".into());
+                }
+
+                let head = self.upload_proxy_chain(depth).await?;
+
+                for n in 0..messages {
+                    self.send(head, n.encode()).await?;
+                }
+
+                println!(
+                    "Gas trees via {:?}: {} trees of depth {}",
+                    head, messages, depth
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let Cli {
+        url,
+        suri,
+        gas_limit,
+        command,
+    } = Cli::parse();
+
+    let salt_prefix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)?
+        .as_nanos()
+        .to_le_bytes()
+        .to_vec();
+
+    let mut stress = Stress {
+        client: Client::new(&url, &suri).await?,
+        gas_limit,
+        salt_prefix,
+        uploaded: 0,
+    };
+
+    stress.run(command).await
+}
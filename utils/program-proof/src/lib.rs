@@ -0,0 +1,121 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Verification of proofs returned by the `gear_programStateProof` rpc.
+//!
+//! Proof is checked against the state root of the block header, so light
+//! clients and bridges could trust the program state without a full node.
+
+use codec::Decode;
+use gear_common::Program;
+use gear_core::memory::PageNumber;
+use sp_core::{Bytes, H256};
+use sp_runtime::traits::BlakeTwo256;
+use sp_state_machine::{read_proof_check, StorageProof};
+use std::{collections::BTreeMap, ops::Range};
+
+#[cfg(test)]
+mod tests;
+
+/// Error of the program state proof verification.
+#[derive(Debug, derive_more::Display)]
+pub enum Error {
+    /// Proof doesn't match the state root or lacks requested keys.
+    #[display(fmt = "Invalid proof: {}", _0)]
+    InvalidProof(String),
+    /// Program doesn't exist within the proven state.
+    #[display(fmt = "Program not found")]
+    ProgramNotFound,
+    /// Program record can't be decoded.
+    #[display(fmt = "Program decoding failed: {}", _0)]
+    Decode(codec::Error),
+    /// Page is listed in the program record, but its data isn't proven.
+    #[display(fmt = "Data of page {} not found", _0)]
+    PageNotFound(u32),
+}
+
+impl std::error::Error for Error {}
+
+/// Program state proven against the state root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramState {
+    pub program: Program,
+    /// Data of the pages within the requested range.
+    ///
+    /// Pages, which are absent here, have no data, i.e. are zero-filled.
+    pub pages: BTreeMap<PageNumber, Vec<u8>>,
+}
+
+fn read(
+    state_root: H256,
+    proof: &StorageProof,
+    keys: &[Vec<u8>],
+) -> Result<Vec<Option<Vec<u8>>>, Error> {
+    let mut values = read_proof_check::<BlakeTwo256, _>(state_root, proof.clone(), keys)
+        .map_err(|e| Error::InvalidProof(e.to_string()))?;
+
+    Ok(keys
+        .iter()
+        .map(|key| values.remove(key).flatten())
+        .collect())
+}
+
+/// Verifies proof of the program state within the `pages` range.
+///
+/// `proof` is the one returned by `gear_programStateProof` rpc for the same
+/// program and range, `state_root` is taken from the header of the block.
+pub fn verify(
+    state_root: H256,
+    proof: Vec<Bytes>,
+    program_id: H256,
+    pages: Range<u32>,
+) -> Result<ProgramState, Error> {
+    let proof = StorageProof::new(proof.into_iter().map(|node| node.0));
+
+    let program = read(state_root, &proof, &[gear_common::program_key(program_id)])?
+        .pop()
+        .flatten()
+        .ok_or(Error::ProgramNotFound)?;
+    let program = Program::decode(&mut &program[..]).map_err(Error::Decode)?;
+
+    let pages_with_data: Vec<PageNumber> = match &program {
+        Program::Active(active) => active
+            .pages_with_data
+            .iter()
+            .filter(|page| pages.contains(&page.0))
+            .copied()
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let keys: Vec<_> = pages_with_data
+        .iter()
+        .map(|page| gear_common::page_key(program_id, *page))
+        .collect();
+
+    let pages = pages_with_data
+        .into_iter()
+        .zip(read(state_root, &proof, &keys)?)
+        .map(|(page, data)| {
+            data.map(|data| (page, data))
+                .ok_or(Error::PageNotFound(page.0))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(ProgramState { program, pages })
+}
@@ -0,0 +1,93 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use codec::Encode;
+use gear_common::{ActiveProgram, ProgramState as ActiveState};
+use sp_core::storage::StateVersion;
+use sp_state_machine::{prove_read, InMemoryBackend};
+
+fn program() -> Program {
+    Program::Active(ActiveProgram {
+        allocations: Default::default(),
+        pages_with_data: vec![PageNumber(1), PageNumber(5)].into_iter().collect(),
+        gas_reservation_map: Default::default(),
+        code_hash: H256::zero(),
+        state: ActiveState::Initialized,
+    })
+}
+
+/// Returns state root and proof of the program record and its first page.
+fn prove(program_id: H256) -> (H256, Vec<Bytes>) {
+    let storage: BTreeMap<_, _> = vec![
+        (gear_common::program_key(program_id), program().encode()),
+        (
+            gear_common::page_key(program_id, PageNumber(1)),
+            vec![1; 16],
+        ),
+        (
+            gear_common::page_key(program_id, PageNumber(5)),
+            vec![5; 16],
+        ),
+    ]
+    .into_iter()
+    .collect();
+
+    let backend = InMemoryBackend::<BlakeTwo256>::from((storage, StateVersion::V1));
+    let state_root = *backend.root();
+
+    let keys = [
+        gear_common::program_key(program_id),
+        gear_common::page_key(program_id, PageNumber(1)),
+    ];
+    let proof = prove_read(backend, &keys).expect("Failed to prove");
+
+    (state_root, proof.into_iter_nodes().map(Bytes).collect())
+}
+
+#[test]
+fn proof_verified() {
+    let program_id = H256::repeat_byte(1);
+    let (state_root, proof) = prove(program_id);
+
+    let state = verify(state_root, proof, program_id, 0..4).expect("Proof is valid");
+
+    assert_eq!(state.program, program());
+    assert_eq!(
+        state.pages,
+        vec![(PageNumber(1), vec![1; 16])].into_iter().collect()
+    );
+}
+
+#[test]
+fn proof_rejected() {
+    let program_id = H256::repeat_byte(1);
+    let (state_root, proof) = prove(program_id);
+
+    // Proof doesn't match the state root.
+    assert!(matches!(
+        verify(H256::zero(), proof.clone(), program_id, 0..4),
+        Err(Error::InvalidProof(_))
+    ));
+
+    // Data of the page 5 isn't proven.
+    assert!(verify(state_root, proof.clone(), program_id, 0..8).is_err());
+
+    // Another program isn't proven.
+    assert!(verify(state_root, proof, H256::repeat_byte(2), 0..4).is_err());
+}
@@ -0,0 +1,63 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Builds the same tree as [`crate::junit_tree::build_tree`] from criterion output
+/// (usually `target/criterion`): benchmark groups with mean times in seconds.
+pub fn build_tree<P: AsRef<Path>>(path: P) -> BTreeMap<String, BTreeMap<String, f64>> {
+    let mut result: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+    collect(path.as_ref(), &mut result);
+    result
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn collect(dir: &Path, result: &mut BTreeMap<String, BTreeMap<String, f64>>) {
+    // criterion stores the latest run of every benchmark in its `new` folder.
+    if dir.ends_with("new") && dir.join("benchmark.json").exists() {
+        let benchmark = read_json(&dir.join("benchmark.json"));
+        let estimates = read_json(&dir.join("estimates.json"));
+
+        let group = benchmark["group_id"].as_str().unwrap().to_string();
+        let name = match (
+            benchmark["function_id"].as_str(),
+            benchmark["value_str"].as_str(),
+        ) {
+            (Some(function), Some(value)) => format!("{}/{}", function, value),
+            (Some(name), None) | (None, Some(name)) => name.to_string(),
+            (None, None) => group.clone(),
+        };
+        // estimates are in nanoseconds.
+        let time = estimates["mean"]["point_estimate"].as_f64().unwrap() / 1_000_000_000.0;
+
+        result.entry(group).or_default().insert(name, time);
+
+        return;
+    }
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect(&path, result);
+        }
+    }
+}
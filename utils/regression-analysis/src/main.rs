@@ -27,6 +27,7 @@ use std::{
 };
 use tabled::{Style, Table};
 
+mod criterion_tree;
 mod junit_tree;
 mod output;
 
@@ -59,6 +60,9 @@ enum Commands {
         output_path: PathBuf,
         #[clap(long, value_parser)]
         disable_filter: bool,
+        /// Entries of the data folder are criterion output folders instead of junit files.
+        #[clap(long, value_parser)]
+        criterion: bool,
     },
     Compare {
         #[clap(long, value_parser)]
@@ -67,13 +71,21 @@ enum Commands {
         current_junit_path: PathBuf,
         #[clap(long, value_parser)]
         disable_filter: bool,
+        /// The current junit path is a criterion output folder.
+        #[clap(long, value_parser)]
+        criterion: bool,
     },
 }
 
 fn build_tree<P: AsRef<Path>>(
     disable_filter: bool,
+    criterion: bool,
     path: P,
 ) -> BTreeMap<String, BTreeMap<String, f64>> {
+    if criterion {
+        return criterion_tree::build_tree(path);
+    }
+
     let filter = |pallet_name: &str| {
         if disable_filter {
             return true;
@@ -110,11 +122,12 @@ fn collect_data<P: AsRef<Path>>(
     data_folder_path: P,
     output_path: P,
     disable_filter: bool,
+    criterion: bool,
     preallocate: usize,
 ) {
     let mut statistics: BTreeMap<_, BTreeMap<_, Vec<_>>> = BTreeMap::default();
     for entry in fs::read_dir(data_folder_path).unwrap() {
-        let executions = build_tree(disable_filter, &entry.unwrap().path());
+        let executions = build_tree(disable_filter, criterion, &entry.unwrap().path());
         for (ref key, ref times) in executions {
             if !statistics.contains_key(key) {
                 statistics.insert(key.clone(), Default::default());
@@ -145,10 +158,15 @@ fn collect_data<P: AsRef<Path>>(
     serde_json::to_writer_pretty(writer, &statistics).unwrap();
 }
 
-fn compare<P: AsRef<Path>>(data_path: P, current_junit_path: P, disable_filter: bool) {
+fn compare<P: AsRef<Path>>(
+    data_path: P,
+    current_junit_path: P,
+    disable_filter: bool,
+    criterion: bool,
+) {
     let mut statistics: BTreeMap<String, BTreeMap<String, Vec<u64>>> =
         serde_json::from_str(&fs::read_to_string(data_path).unwrap()).unwrap();
-    let executions = build_tree(disable_filter, current_junit_path);
+    let executions = build_tree(disable_filter, criterion, current_junit_path);
     let mut compared = executions
         .iter()
         .filter_map(|(key, tests)| {
@@ -208,15 +226,23 @@ fn main() {
             data_folder_path,
             disable_filter,
             output_path,
+            criterion,
         } => {
-            collect_data(data_folder_path, output_path, *disable_filter, PREALLOCATE);
+            collect_data(
+                data_folder_path,
+                output_path,
+                *disable_filter,
+                *criterion,
+                PREALLOCATE,
+            );
         }
         Commands::Compare {
             data_path,
             current_junit_path,
             disable_filter,
+            criterion,
         } => {
-            compare(data_path, current_junit_path, *disable_filter);
+            compare(data_path, current_junit_path, *disable_filter, *criterion);
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::builder_error::BuilderError;
 use anyhow::{Context, Result};
 use colored::Colorize;
+use gear_core::code::ROUTE_EXPORT_PREFIX;
 use pwasm_utils::{
     parity_wasm,
     parity_wasm::elements::{Internal, Module, Serialize},
@@ -46,17 +47,26 @@ impl Optimizer {
         let mut binary_module = self.module.clone();
         let binary_file_name = self.optimized_file_name();
 
-        pwasm_utils::optimize(
-            &mut binary_module,
-            vec!["handle", "handle_reply", "init", "__gear_stack_end"],
-        )
-        .map_err(OptimizerError)
-        .with_context(|| {
-            format!(
-                "unable to optimize the WASM file `{0}`",
-                self.file.display()
-            )
-        })?;
+        // Routes (`handle::<name>` exports) must survive optimization.
+        let routes = route_exports(&self.module);
+        let mut exports = vec![
+            "handle",
+            "handle_reply",
+            "handle_signal",
+            "init",
+            "state",
+            "__gear_stack_end",
+        ];
+        exports.extend(routes.iter().map(String::as_str));
+
+        pwasm_utils::optimize(&mut binary_module, exports)
+            .map_err(OptimizerError)
+            .with_context(|| {
+                format!(
+                    "unable to optimize the WASM file `{0}`",
+                    self.file.display()
+                )
+            })?;
 
         check_exports(&binary_module, &binary_file_name)?;
 
@@ -233,6 +243,23 @@ pub fn do_optimization(
     Ok(())
 }
 
+fn route_exports(module: &Module) -> Vec<String> {
+    module
+        .export_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .filter(|entry| {
+                    matches!(entry.internal(), Internal::Function(_))
+                        && entry.field().starts_with(ROUTE_EXPORT_PREFIX)
+                })
+                .map(|entry| entry.field().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn check_exports(module: &Module, path: &Path) -> Result<()> {
     if module
         .export_section()
@@ -32,9 +32,12 @@ exports.getWasmMetadata = async (wasmBytes) => {
             gr_send_commit_wgas: () => { },
             gr_send_init: () => { },
             gr_send_push: () => { },
+            gr_send_push_input: () => { },
+            gr_send_cancel: () => { },
             gr_reply: () => { },
             gr_reply_wgas: () => { },
             gr_reply_push: () => { },
+            gr_reply_push_input: () => { },
             gr_reply_commit: () => { },
             gr_reply_commit_wgas: () => { },
             gr_reply_to: () => { },